@@ -15,15 +15,33 @@ pub async fn main() -> Result<()> {
                 Sub::TxCustom(TxCustom(args)) => {
                     tx::submit_custom(ctx, args).await;
                 }
+                Sub::TxBatch(TxBatch(args)) => {
+                    tx::submit_batch(ctx, args).await;
+                }
                 Sub::TxTransfer(TxTransfer(args)) => {
                     tx::submit_transfer(ctx, args).await;
                 }
                 Sub::TxIbcTransfer(TxIbcTransfer(args)) => {
                     tx::submit_ibc_transfer(ctx, args).await;
                 }
+                Sub::TxIcaRegister(TxIcaRegister(args)) => {
+                    tx::submit_ica_register(ctx, args).await;
+                }
+                Sub::TxIcaSend(TxIcaSend(args)) => {
+                    tx::submit_ica_send(ctx, args).await;
+                }
                 Sub::TxUpdateVp(TxUpdateVp(args)) => {
                     tx::submit_update_vp(ctx, args).await;
                 }
+                Sub::TxInitHtlc(TxInitHtlc(args)) => {
+                    tx::submit_init_htlc(ctx, args).await;
+                }
+                Sub::TxClaimHtlc(TxClaimHtlc(args)) => {
+                    tx::submit_claim_htlc(ctx, args).await;
+                }
+                Sub::TxRefundHtlc(TxRefundHtlc(args)) => {
+                    tx::submit_refund_htlc(ctx, args).await;
+                }
                 Sub::TxInitAccount(TxInitAccount(args)) => {
                     tx::submit_init_account(ctx, args).await;
                 }
@@ -36,6 +54,12 @@ pub async fn main() -> Result<()> {
                 Sub::TxVoteProposal(TxVoteProposal(args)) => {
                     tx::submit_vote_proposal(ctx, args).await;
                 }
+                Sub::TxTallyOfflineProposal(TxTallyOfflineProposal(args)) => {
+                    tx::submit_tally_offline_proposal(ctx, args).await;
+                }
+                Sub::TxTopUpProposalDeposit(TxTopUpProposalDeposit(args)) => {
+                    tx::submit_top_up_proposal_deposit(ctx, args).await;
+                }
                 Sub::TxRevealPk(TxRevealPk(args)) => {
                     tx::submit_reveal_pk(ctx, args).await;
                 }
@@ -92,9 +116,40 @@ pub async fn main() -> Result<()> {
                 Sub::QueryProposalResult(QueryProposalResult(args)) => {
                     rpc::query_proposal_result(ctx, args).await;
                 }
+                Sub::QuerySlashFundDisbursements(
+                    QuerySlashFundDisbursements(args),
+                ) => {
+                    rpc::query_slash_fund_disbursements(ctx, args).await;
+                }
                 Sub::QueryProtocolParameters(QueryProtocolParameters(args)) => {
                     rpc::query_protocol_parameters(ctx, args).await;
                 }
+                Sub::QueryIbc(sub) => match sub {
+                    QueryIbc::ClientState(QueryIbcClientState(args)) => {
+                        rpc::query_ibc_client_state(args).await;
+                    }
+                    QueryIbc::ConsensusState(QueryIbcConsensusState(args)) => {
+                        rpc::query_ibc_consensus_state(args).await;
+                    }
+                    QueryIbc::Connection(QueryIbcConnection(args)) => {
+                        rpc::query_ibc_connection(args).await;
+                    }
+                    QueryIbc::Channel(QueryIbcChannel(args)) => {
+                        rpc::query_ibc_channel(args).await;
+                    }
+                    QueryIbc::Commitments(QueryIbcCommitments(args)) => {
+                        rpc::query_ibc_packet_commitments(args).await;
+                    }
+                    QueryIbc::Acks(QueryIbcAcks(args)) => {
+                        rpc::query_ibc_packet_acks(args).await;
+                    }
+                    QueryIbc::Denoms(QueryIbcDenoms(args)) => {
+                        rpc::query_ibc_denoms(args).await;
+                    }
+                    QueryIbc::Flow(QueryIbcFlow(args)) => {
+                        rpc::query_ibc_flow(args).await;
+                    }
+                },
             }
         }
         cli::NamadaClient::WithoutContext(cmd, global_args) => match cmd {
@@ -0,0 +1,47 @@
+//! Namada remote signer CLI.
+
+use eyre::{eyre, Context, Result};
+use namada_apps::cli::{self, cmds};
+use namada_apps::remote_signer::{self, SignerKeys};
+
+pub fn main() -> Result<()> {
+    let (cmd, mut ctx) = cli::namada_signer_cli()?;
+    match cmd {
+        cmds::NamadaSigner::Run(cmds::SignerRun(args)) => {
+            let consensus_key = ctx.get_cached(&args.consensus_key);
+            let remote_signer = ctx
+                .config
+                .ledger
+                .tendermint
+                .remote_signer
+                .clone()
+                .ok_or_else(|| {
+                    eyre!(
+                        "The remote signer must be configured in the ledger's \
+                         Tendermint config"
+                    )
+                })?;
+            let ledger_config = ctx.config.ledger;
+            let protocol_key = ctx
+                .wallet
+                .take_validator_data()
+                .ok_or_else(|| {
+                    eyre!(
+                        "Validator data should have been stored in the wallet"
+                    )
+                })?
+                .keys
+                .protocol_keypair;
+            remote_signer::run(
+                &ledger_config,
+                remote_signer,
+                SignerKeys {
+                    consensus_key,
+                    protocol_key,
+                },
+            )
+            .wrap_err("The remote signer failed")?;
+        }
+    }
+    Ok(())
+}
@@ -1,7 +1,9 @@
 //! Namada Wallet CLI.
 
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{self, Write};
+use std::path::Path;
 
 use borsh::BorshSerialize;
 use color_eyre::eyre::Result;
@@ -11,8 +13,16 @@ use namada::types::key::*;
 use namada::types::masp::{MaspValue, PaymentAddress};
 use namada_apps::cli;
 use namada_apps::cli::{args, cmds, Context};
+use namada_apps::client::hardware_wallet;
 use namada_apps::client::tx::find_valid_diversifier;
-use namada_apps::wallet::{DecryptionError, FindKeyError};
+use namada_apps::wallet::address_book::{
+    self, AddressBookEntry, AddressBookFormat, ContactInfo,
+};
+use namada_apps::wallet::{
+    read_bip39_passphrase, read_mnemonic_code, read_new_password,
+    read_password, DecryptionError, DerivationPath, FindKeyError, HardwareKey,
+    Wallet,
+};
 use rand_core::OsRng;
 
 pub fn main() -> Result<()> {
@@ -22,6 +32,15 @@ pub fn main() -> Result<()> {
             cmds::WalletKey::Gen(cmds::KeyGen(args)) => {
                 key_and_address_gen(ctx, args)
             }
+            cmds::WalletKey::Derive(cmds::KeyDerive(args)) => {
+                key_derive(ctx, args)
+            }
+            cmds::WalletKey::Restore(cmds::KeyRestore(args)) => {
+                key_restore(ctx, args)
+            }
+            cmds::WalletKey::AddLedger(cmds::KeyAddLedger(args)) => {
+                key_add_ledger(ctx, args)
+            }
             cmds::WalletKey::Find(cmds::KeyFind(args)) => key_find(ctx, args),
             cmds::WalletKey::List(cmds::KeyList(args)) => key_list(ctx, args),
             cmds::WalletKey::Export(cmds::Export(args)) => {
@@ -60,6 +79,23 @@ pub fn main() -> Result<()> {
                 address_key_find(ctx, args)
             }
         },
+        cmds::NamadaWallet::AddressBook(sub) => match sub {
+            cmds::WalletAddressBook::Add(cmds::AddressBookAdd(args)) => {
+                address_book_add(ctx, args)
+            }
+            cmds::WalletAddressBook::List(cmds::AddressBookList(args)) => {
+                address_book_list(ctx, args)
+            }
+            cmds::WalletAddressBook::Import(cmds::AddressBookImport(args)) => {
+                address_book_import(ctx, args)
+            }
+            cmds::WalletAddressBook::Export(cmds::AddressBookExport(args)) => {
+                address_book_export(ctx, args)
+            }
+        },
+        cmds::NamadaWallet::ChangePassword(cmds::WalletChangePassword) => {
+            change_password(ctx)
+        }
     }
     Ok(())
 }
@@ -302,11 +338,32 @@ fn key_and_address_gen(
     args::KeyAndAddressGen {
         scheme,
         alias,
+        mnemonic,
+        derivation_path,
+        bip39_passphrase,
         unsafe_dont_encrypt,
     }: args::KeyAndAddressGen,
 ) {
     let mut wallet = ctx.wallet;
-    let (alias, _key) = wallet.gen_key(scheme, alias, unsafe_dont_encrypt);
+    let (alias, _key) = if mnemonic {
+        let mnemonic = Wallet::gen_mnemonic_code();
+        println!(
+            "Safely store your 24 words mnemonic code. It's the only way to \
+             restore the key if the wallet is lost:"
+        );
+        println!("{}", mnemonic.phrase());
+        let bip39_passphrase = read_bip39_passphrase(bip39_passphrase);
+        wallet.derive_key_from_mnemonic_code(
+            scheme,
+            alias,
+            derivation_path,
+            &mnemonic,
+            &bip39_passphrase,
+            unsafe_dont_encrypt,
+        )
+    } else {
+        wallet.gen_key(scheme, alias, unsafe_dont_encrypt)
+    };
     wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
     println!(
         "Successfully added a key and an address with alias: \"{}\"",
@@ -314,6 +371,141 @@ fn key_and_address_gen(
     );
 }
 
+/// Derive a key at the given HD path from a mnemonic code and store it in the
+/// wallet.
+fn key_derive(
+    ctx: Context,
+    args::KeyDerive {
+        scheme,
+        alias,
+        derivation_path,
+        shielded,
+        bip39_passphrase,
+        unsafe_dont_encrypt,
+    }: args::KeyDerive,
+) {
+    key_derive_from_mnemonic_code(
+        ctx,
+        scheme,
+        alias,
+        Some(derivation_path),
+        shielded,
+        bip39_passphrase,
+        unsafe_dont_encrypt,
+    )
+}
+
+/// Restore a key generated with a mnemonic code, which is derived at the
+/// default HD path, and store it in the wallet.
+fn key_restore(
+    ctx: Context,
+    args::KeyRestore {
+        scheme,
+        alias,
+        shielded,
+        bip39_passphrase,
+        unsafe_dont_encrypt,
+    }: args::KeyRestore,
+) {
+    key_derive_from_mnemonic_code(
+        ctx,
+        scheme,
+        alias,
+        None,
+        shielded,
+        bip39_passphrase,
+        unsafe_dont_encrypt,
+    )
+}
+
+/// Derive a keypair and its implicit address, or a shielded spending key,
+/// from a mnemonic code read from stdin and store them in the wallet.
+fn key_derive_from_mnemonic_code(
+    ctx: Context,
+    scheme: SchemeType,
+    alias: Option<String>,
+    derivation_path: Option<DerivationPath>,
+    shielded: bool,
+    bip39_passphrase: bool,
+    unsafe_dont_encrypt: bool,
+) {
+    let mut wallet = ctx.wallet;
+    let mnemonic = read_mnemonic_code();
+    let bip39_passphrase = read_bip39_passphrase(bip39_passphrase);
+    if shielded {
+        let alias = alias
+            .expect("An alias is required for a shielded spending key")
+            .to_lowercase();
+        let (alias, _key) = wallet.derive_spending_key_from_mnemonic_code(
+            alias,
+            derivation_path,
+            &mnemonic,
+            &bip39_passphrase,
+            unsafe_dont_encrypt,
+        );
+        wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
+        println!(
+            "Successfully added a spending key with alias: \"{}\"",
+            alias
+        );
+    } else {
+        let (alias, _key) = wallet.derive_key_from_mnemonic_code(
+            scheme,
+            alias,
+            derivation_path,
+            &mnemonic,
+            &bip39_passphrase,
+            unsafe_dont_encrypt,
+        );
+        wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
+        println!(
+            "Successfully added a key and an address with alias: \"{}\"",
+            alias
+        );
+    }
+}
+
+/// Add the public key at the given HD path of a Ledger device and its
+/// implicit address to the wallet.
+fn key_add_ledger(
+    ctx: Context,
+    args::KeyAddLedger {
+        scheme,
+        alias,
+        derivation_path,
+    }: args::KeyAddLedger,
+) {
+    let mut wallet = ctx.wallet;
+    let derivation_path = derivation_path
+        .unwrap_or_else(|| DerivationPath::default_for_scheme(scheme));
+    let device = hardware_wallet::connect().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        cli::safe_exit(1)
+    });
+    println!("Please confirm the address on your Ledger...");
+    let public_key = device
+        .get_public_key(&derivation_path, scheme, true)
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            cli::safe_exit(1)
+        });
+    let pkh = PublicKeyHash::from(&public_key);
+    let key = HardwareKey {
+        public_key,
+        derivation_path,
+    };
+    let alias = wallet.insert_hardware_key(alias, key).unwrap_or_else(|| {
+        eprintln!("Hardware key not added");
+        cli::safe_exit(1)
+    });
+    wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
+    println!(
+        "Successfully added a Ledger key and an address with alias: \"{}\" \
+         and public key hash: {}",
+        alias, pkh
+    );
+}
+
 /// Find a keypair in the wallet store.
 fn key_find(
     ctx: Context,
@@ -403,6 +595,24 @@ fn key_list(
             }
         }
     }
+    let hardware_keys: BTreeMap<_, _> =
+        wallet.get_hardware_keys().into_iter().collect();
+    if !hardware_keys.is_empty() {
+        let stdout = io::stdout();
+        let mut w = stdout.lock();
+        writeln!(w, "Known Ledger keys:").unwrap();
+        for (alias, key) in hardware_keys {
+            writeln!(w, "  Alias \"{}\":", alias).unwrap();
+            writeln!(
+                w,
+                "    Public key hash: {}",
+                PublicKeyHash::from(&key.public_key)
+            )
+            .unwrap();
+            writeln!(w, "    Public key: {}", key.public_key).unwrap();
+            writeln!(w, "    HD path: {}", key.derivation_path).unwrap();
+        }
+    }
 }
 
 /// Export a keypair to a file.
@@ -494,3 +704,158 @@ fn address_add(ctx: Context, args: args::AddressAdd) {
         args.alias.to_lowercase()
     );
 }
+
+/// Add an entry to the address book or edit its contact information.
+fn address_book_add(ctx: Context, args: args::AddressBookAdd) {
+    let mut wallet = ctx.wallet;
+    let entry = AddressBookEntry {
+        alias: args.alias.to_lowercase(),
+        address: args.address,
+        info: ContactInfo {
+            label: args.label,
+            notes: args.notes,
+            tags: args.tags.into_iter().collect(),
+        },
+    };
+    let alias = wallet.add_address_book_entry(entry).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        cli::safe_exit(1)
+    });
+    wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
+    println!(
+        "Successfully added an address book entry with alias: \"{}\"",
+        alias
+    );
+}
+
+/// List the entries of the address book, optionally only those with a tag.
+fn address_book_list(ctx: Context, args: args::AddressBookList) {
+    let entries = address_book_entries(&ctx.wallet, args.tag.as_deref());
+    if entries.is_empty() {
+        println!(
+            "No address book entries found. Try `address-book add` to add an \
+             entry."
+        );
+        return;
+    }
+    let stdout = io::stdout();
+    let mut w = stdout.lock();
+    writeln!(w, "Address book:").unwrap();
+    for entry in entries {
+        writeln!(w, "  \"{}\": {}", entry.alias, entry.address).unwrap();
+        if let Some(label) = &entry.info.label {
+            writeln!(w, "    Label: {}", label).unwrap();
+        }
+        if !entry.info.tags.is_empty() {
+            writeln!(
+                w,
+                "    Tags: {}",
+                itertools::join(&entry.info.tags, ", ")
+            )
+            .unwrap();
+        }
+        if let Some(notes) = &entry.info.notes {
+            writeln!(w, "    Notes: {}", notes).unwrap();
+        }
+    }
+}
+
+/// Import the entries of a file into the address book.
+fn address_book_import(ctx: Context, args: args::AddressBookImport) {
+    let mut wallet = ctx.wallet;
+    let format = address_book_format(args.format, &args.file);
+    let entries = File::open(&args.file)
+        .map_err(Into::into)
+        .and_then(|file| address_book::read_entries(format, file))
+        .unwrap_or_else(|err| {
+            eprintln!(
+                "Failed to read the address book file {}: {}",
+                args.file.to_string_lossy(),
+                err
+            );
+            cli::safe_exit(1)
+        });
+    let total = entries.len();
+    let mut imported = 0;
+    for entry in entries {
+        match wallet.add_address_book_entry(entry) {
+            Ok(_) => imported += 1,
+            Err(err) => eprintln!("Skipped an entry: {}", err),
+        }
+    }
+    wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
+    println!(
+        "Imported {} of the {} entries into the address book.",
+        imported, total
+    );
+}
+
+/// Export the entries of the address book, optionally only those with a tag,
+/// to a file.
+fn address_book_export(ctx: Context, args: args::AddressBookExport) {
+    let format = address_book_format(args.format, &args.file);
+    let entries = address_book_entries(&ctx.wallet, args.tag.as_deref());
+    File::create(&args.file)
+        .map_err(Into::into)
+        .and_then(|file| address_book::write_entries(&entries, format, file))
+        .unwrap_or_else(|err| {
+            eprintln!(
+                "Failed to write the address book file {}: {}",
+                args.file.to_string_lossy(),
+                err
+            );
+            cli::safe_exit(1)
+        });
+    println!(
+        "Exported {} entries of the address book to {}.",
+        entries.len(),
+        args.file.to_string_lossy()
+    );
+}
+
+/// Get the entries of the address book, optionally only those with a tag.
+fn address_book_entries(
+    wallet: &Wallet,
+    tag: Option<&str>,
+) -> Vec<AddressBookEntry> {
+    let mut entries = wallet.get_address_book();
+    if let Some(tag) = tag {
+        entries.retain(|entry| entry.info.has_tag(tag));
+    }
+    entries
+}
+
+/// Get the format of an address book file, from its extension if not given.
+fn address_book_format(
+    format: Option<AddressBookFormat>,
+    file: &Path,
+) -> AddressBookFormat {
+    format
+        .or_else(|| AddressBookFormat::from_path(file))
+        .unwrap_or_else(|| {
+            eprintln!(
+                "Unknown format of the address book file {}, use the \
+                 `--format` argument to specify it.",
+                file.to_string_lossy()
+            );
+            cli::safe_exit(1)
+        })
+}
+
+/// Change the password of the encrypted keys of the wallet.
+fn change_password(ctx: Context) {
+    let mut wallet = ctx.wallet;
+    let password = read_password("Enter the current password: ");
+    let new_password = read_new_password();
+    if let Err((alias, err)) = wallet.change_password(&password, &new_password)
+    {
+        eprintln!(
+            "Failed to decrypt the key with alias \"{}\", no changes were \
+             made: {}",
+            alias, err
+        );
+        cli::safe_exit(1);
+    }
+    wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
+    println!("Successfully changed the password of the wallet's keys.");
+}
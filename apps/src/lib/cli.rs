@@ -152,14 +152,23 @@ pub mod cmds {
             app
                 // Simple transactions
                 .subcommand(TxCustom::def().display_order(1))
+                .subcommand(TxBatch::def().display_order(1))
                 .subcommand(TxTransfer::def().display_order(1))
                 .subcommand(TxIbcTransfer::def().display_order(1))
+                .subcommand(TxIcaRegister::def().display_order(1))
+                .subcommand(TxIcaSend::def().display_order(1))
                 .subcommand(TxUpdateVp::def().display_order(1))
                 .subcommand(TxInitAccount::def().display_order(1))
                 .subcommand(TxRevealPk::def().display_order(1))
+                // HTLC transactions
+                .subcommand(TxInitHtlc::def().display_order(1))
+                .subcommand(TxClaimHtlc::def().display_order(1))
+                .subcommand(TxRefundHtlc::def().display_order(1))
                 // Proposal transactions
                 .subcommand(TxInitProposal::def().display_order(1))
                 .subcommand(TxVoteProposal::def().display_order(1))
+                .subcommand(TxTallyOfflineProposal::def().display_order(1))
+                .subcommand(TxTopUpProposalDeposit::def().display_order(1))
                 // PoS transactions
                 .subcommand(TxInitValidator::def().display_order(2))
                 .subcommand(Bond::def().display_order(2))
@@ -179,7 +188,9 @@ pub mod cmds {
                 .subcommand(QueryRawBytes::def().display_order(3))
                 .subcommand(QueryProposal::def().display_order(3))
                 .subcommand(QueryProposalResult::def().display_order(3))
+                .subcommand(QuerySlashFundDisbursements::def().display_order(3))
                 .subcommand(QueryProtocolParameters::def().display_order(3))
+                .subcommand(QueryIbc::def().display_order(3))
                 // Utils
                 .subcommand(Utils::def().display_order(5))
         }
@@ -187,17 +198,27 @@ pub mod cmds {
         fn parse(matches: &ArgMatches) -> Option<Self> {
             use NamadaClientWithContext::*;
             let tx_custom = Self::parse_with_ctx(matches, TxCustom);
+            let tx_batch = Self::parse_with_ctx(matches, TxBatch);
             let tx_transfer = Self::parse_with_ctx(matches, TxTransfer);
             let tx_ibc_transfer = Self::parse_with_ctx(matches, TxIbcTransfer);
+            let tx_ica_register = Self::parse_with_ctx(matches, TxIcaRegister);
+            let tx_ica_send = Self::parse_with_ctx(matches, TxIcaSend);
             let tx_update_vp = Self::parse_with_ctx(matches, TxUpdateVp);
             let tx_init_account = Self::parse_with_ctx(matches, TxInitAccount);
             let tx_init_validator =
                 Self::parse_with_ctx(matches, TxInitValidator);
             let tx_reveal_pk = Self::parse_with_ctx(matches, TxRevealPk);
+            let tx_init_htlc = Self::parse_with_ctx(matches, TxInitHtlc);
+            let tx_claim_htlc = Self::parse_with_ctx(matches, TxClaimHtlc);
+            let tx_refund_htlc = Self::parse_with_ctx(matches, TxRefundHtlc);
             let tx_init_proposal =
                 Self::parse_with_ctx(matches, TxInitProposal);
             let tx_vote_proposal =
                 Self::parse_with_ctx(matches, TxVoteProposal);
+            let tx_tally_offline_proposal =
+                Self::parse_with_ctx(matches, TxTallyOfflineProposal);
+            let tx_top_up_proposal_deposit =
+                Self::parse_with_ctx(matches, TxTopUpProposalDeposit);
             let bond = Self::parse_with_ctx(matches, Bond);
             let unbond = Self::parse_with_ctx(matches, Unbond);
             let withdraw = Self::parse_with_ctx(matches, Withdraw);
@@ -218,17 +239,28 @@ pub mod cmds {
             let query_proposal = Self::parse_with_ctx(matches, QueryProposal);
             let query_proposal_result =
                 Self::parse_with_ctx(matches, QueryProposalResult);
+            let query_slash_fund_disbursements =
+                Self::parse_with_ctx(matches, QuerySlashFundDisbursements);
             let query_protocol_parameters =
                 Self::parse_with_ctx(matches, QueryProtocolParameters);
+            let query_ibc = Self::parse_with_ctx(matches, QueryIbc);
             let utils = SubCmd::parse(matches).map(Self::WithoutContext);
             tx_custom
+                .or(tx_batch)
                 .or(tx_transfer)
                 .or(tx_ibc_transfer)
+                .or(tx_ica_register)
+                .or(tx_ica_send)
                 .or(tx_update_vp)
                 .or(tx_init_account)
                 .or(tx_reveal_pk)
+                .or(tx_init_htlc)
+                .or(tx_claim_htlc)
+                .or(tx_refund_htlc)
                 .or(tx_init_proposal)
                 .or(tx_vote_proposal)
+                .or(tx_tally_offline_proposal)
+                .or(tx_top_up_proposal_deposit)
                 .or(tx_init_validator)
                 .or(bond)
                 .or(unbond)
@@ -246,7 +278,9 @@ pub mod cmds {
                 .or(query_raw_bytes)
                 .or(query_proposal)
                 .or(query_proposal_result)
+                .or(query_slash_fund_disbursements)
                 .or(query_protocol_parameters)
+                .or(query_ibc)
                 .or(utils)
         }
     }
@@ -284,15 +318,23 @@ pub mod cmds {
     pub enum NamadaClientWithContext {
         // Ledger cmds
         TxCustom(TxCustom),
+        TxBatch(TxBatch),
         TxTransfer(TxTransfer),
         TxIbcTransfer(TxIbcTransfer),
+        TxIcaRegister(TxIcaRegister),
+        TxIcaSend(TxIcaSend),
         QueryResult(QueryResult),
         TxUpdateVp(TxUpdateVp),
         TxInitAccount(TxInitAccount),
         TxInitValidator(TxInitValidator),
         TxInitProposal(TxInitProposal),
         TxVoteProposal(TxVoteProposal),
+        TxTallyOfflineProposal(TxTallyOfflineProposal),
+        TxTopUpProposalDeposit(TxTopUpProposalDeposit),
         TxRevealPk(TxRevealPk),
+        TxInitHtlc(TxInitHtlc),
+        TxClaimHtlc(TxClaimHtlc),
+        TxRefundHtlc(TxRefundHtlc),
         Bond(Bond),
         Unbond(Unbond),
         Withdraw(Withdraw),
@@ -309,7 +351,9 @@ pub mod cmds {
         QueryRawBytes(QueryRawBytes),
         QueryProposal(QueryProposal),
         QueryProposalResult(QueryProposalResult),
+        QuerySlashFundDisbursements(QuerySlashFundDisbursements),
         QueryProtocolParameters(QueryProtocolParameters),
+        QueryIbc(QueryIbc),
     }
 
     #[allow(clippy::large_enum_variant)]
@@ -321,6 +365,10 @@ pub mod cmds {
         Address(WalletAddress),
         /// MASP key, address management commands
         Masp(WalletMasp),
+        /// Address book commands
+        AddressBook(WalletAddressBook),
+        /// Change the password of the encrypted keys
+        ChangePassword(WalletChangePassword),
     }
 
     impl Cmd for NamadaWallet {
@@ -328,13 +376,21 @@ pub mod cmds {
             app.subcommand(WalletKey::def())
                 .subcommand(WalletAddress::def())
                 .subcommand(WalletMasp::def())
+                .subcommand(WalletAddressBook::def())
+                .subcommand(WalletChangePassword::def())
         }
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
             let key = SubCmd::parse(matches).map(Self::Key);
             let address = SubCmd::parse(matches).map(Self::Address);
             let masp = SubCmd::parse(matches).map(Self::Masp);
-            key.or(address).or(masp)
+            let address_book = SubCmd::parse(matches).map(Self::AddressBook);
+            let change_password =
+                SubCmd::parse(matches).map(Self::ChangePassword);
+            key.or(address)
+                .or(masp)
+                .or(address_book)
+                .or(change_password)
         }
     }
 
@@ -356,10 +412,72 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct WalletChangePassword;
+
+    impl SubCmd for WalletChangePassword {
+        const CMD: &'static str = "change-password";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|_matches| Self)
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD).about(
+                "Change the password of all the encrypted keys and spending \
+                 keys of the wallet, which must share the current password. \
+                 The keys are re-encrypted with the current key derivation \
+                 function.",
+            )
+        }
+    }
+
+    /// Used as top-level commands (`Cmd` instance) in `namada-signer` binary.
+    #[derive(Clone, Debug)]
+    pub enum NamadaSigner {
+        Run(SignerRun),
+    }
+
+    impl Cmd for NamadaSigner {
+        fn add_sub(app: App) -> App {
+            app.subcommand(SignerRun::def())
+        }
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            SubCmd::parse(matches).map(Self::Run)
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct SignerRun(pub args::SignerRun);
+
+    impl SubCmd for SignerRun {
+        const CMD: &'static str = "run";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::SignerRun::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Run the remote signer of the validator's consensus and \
+                     protocol keys, configured in the ledger's Tendermint \
+                     config.",
+                )
+                .add_args::<args::SignerRun>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     #[allow(clippy::large_enum_variant)]
     pub enum WalletKey {
         Gen(KeyGen),
+        Derive(KeyDerive),
+        Restore(KeyRestore),
+        AddLedger(KeyAddLedger),
         Find(KeyFind),
         List(KeyList),
         Export(Export),
@@ -371,10 +489,19 @@ pub mod cmds {
         fn parse(matches: &ArgMatches) -> Option<Self> {
             matches.subcommand_matches(Self::CMD).and_then(|matches| {
                 let generate = SubCmd::parse(matches).map(Self::Gen);
+                let derive = SubCmd::parse(matches).map(Self::Derive);
+                let restore = SubCmd::parse(matches).map(Self::Restore);
+                let add_ledger = SubCmd::parse(matches).map(Self::AddLedger);
                 let lookup = SubCmd::parse(matches).map(Self::Find);
                 let list = SubCmd::parse(matches).map(Self::List);
                 let export = SubCmd::parse(matches).map(Self::Export);
-                generate.or(lookup).or(list).or(export)
+                generate
+                    .or(derive)
+                    .or(restore)
+                    .or(add_ledger)
+                    .or(lookup)
+                    .or(list)
+                    .or(export)
             })
         }
 
@@ -386,6 +513,9 @@ pub mod cmds {
                 )
                 .setting(AppSettings::SubcommandRequiredElseHelp)
                 .subcommand(KeyGen::def())
+                .subcommand(KeyDerive::def())
+                .subcommand(KeyRestore::def())
+                .subcommand(KeyAddLedger::def())
                 .subcommand(KeyFind::def())
                 .subcommand(KeyList::def())
                 .subcommand(Export::def())
@@ -416,6 +546,76 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct KeyDerive(pub args::KeyDerive);
+
+    impl SubCmd for KeyDerive {
+        const CMD: &'static str = "derive";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::KeyDerive::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Derives a keypair at the given HD path from a mnemonic \
+                     code and stores it with its implicit address, or a \
+                     shielded spending key at the given ZIP-32 path.",
+                )
+                .add_args::<args::KeyDerive>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct KeyRestore(pub args::KeyRestore);
+
+    impl SubCmd for KeyRestore {
+        const CMD: &'static str = "restore";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::KeyRestore::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Restores the keypair generated with a mnemonic code, \
+                     which is derived at the default HD path of its scheme, \
+                     or the shielded spending key at the default ZIP-32 path.",
+                )
+                .add_args::<args::KeyRestore>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct KeyAddLedger(pub args::KeyAddLedger);
+
+    impl SubCmd for KeyAddLedger {
+        const CMD: &'static str = "add-ledger";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::KeyAddLedger::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Adds the public key at the given HD path of a connected \
+                     Ledger device and its implicit address. The secret key \
+                     stays on the device, which signs the transactions of the \
+                     address.",
+                )
+                .add_args::<args::KeyAddLedger>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct KeyFind(pub args::KeyFind);
 
@@ -756,6 +956,135 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub enum WalletAddressBook {
+        Add(AddressBookAdd),
+        List(AddressBookList),
+        Import(AddressBookImport),
+        Export(AddressBookExport),
+    }
+
+    impl SubCmd for WalletAddressBook {
+        const CMD: &'static str = "address-book";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).and_then(|matches| {
+                let add = SubCmd::parse(matches).map(Self::Add);
+                let list = SubCmd::parse(matches).map(Self::List);
+                let import = SubCmd::parse(matches).map(Self::Import);
+                let export = SubCmd::parse(matches).map(Self::Export);
+                add.or(list).or(import).or(export)
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Address book of the transparent and payment addresses, \
+                     with their labels, notes and tags. A label can be used \
+                     in place of an address in the arguments of commands.",
+                )
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(AddressBookAdd::def())
+                .subcommand(AddressBookList::def())
+                .subcommand(AddressBookImport::def())
+                .subcommand(AddressBookExport::def())
+        }
+    }
+
+    /// Add or edit an entry of the address book
+    #[derive(Clone, Debug)]
+    pub struct AddressBookAdd(pub args::AddressBookAdd);
+
+    impl SubCmd for AddressBookAdd {
+        const CMD: &'static str = "add";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::AddressBookAdd::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Store an address or a payment address with its contact \
+                     information in the address book. If the alias is already \
+                     used for the same address, its contact information is \
+                     replaced.",
+                )
+                .add_args::<args::AddressBookAdd>()
+        }
+    }
+
+    /// List the entries of the address book
+    #[derive(Clone, Debug)]
+    pub struct AddressBookList(pub args::AddressBookList);
+
+    impl SubCmd for AddressBookList {
+        const CMD: &'static str = "list";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::AddressBookList::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("List the entries of the address book.")
+                .add_args::<args::AddressBookList>()
+        }
+    }
+
+    /// Import entries into the address book from a file
+    #[derive(Clone, Debug)]
+    pub struct AddressBookImport(pub args::AddressBookImport);
+
+    impl SubCmd for AddressBookImport {
+        const CMD: &'static str = "import";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::AddressBookImport::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Import the entries of a CSV or JSON file into the \
+                     address book. A CSV file has a header with the columns \
+                     alias, address, label, notes and tags, with the tags \
+                     separated by a semicolon.",
+                )
+                .add_args::<args::AddressBookImport>()
+        }
+    }
+
+    /// Export the entries of the address book to a file
+    #[derive(Clone, Debug)]
+    pub struct AddressBookExport(pub args::AddressBookExport);
+
+    impl SubCmd for AddressBookExport {
+        const CMD: &'static str = "export";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::AddressBookExport::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Export the entries of the address book to a CSV or JSON \
+                     file.",
+                )
+                .add_args::<args::AddressBookExport>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub enum Ledger {
         Run(LedgerRun),
@@ -947,6 +1276,35 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct QuerySlashFundDisbursements(
+        pub args::QuerySlashFundDisbursements,
+    );
+
+    impl SubCmd for QuerySlashFundDisbursements {
+        const CMD: &'static str = "query-slash-fund-disbursements";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QuerySlashFundDisbursements(
+                    args::QuerySlashFundDisbursements::parse(matches),
+                )
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query the pending and executed slash fund disbursements \
+                     of passed proposals.",
+                )
+                .add_args::<args::QuerySlashFundDisbursements>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryProtocolParameters(pub args::QueryProtocolParameters);
 
@@ -990,6 +1348,28 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxBatch(pub args::TxBatch);
+
+    impl SubCmd for TxBatch {
+        const CMD: &'static str = "batch";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| TxBatch(args::TxBatch::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a batch of transactions that are applied \
+                     atomically, in order.",
+                )
+                .add_args::<args::TxBatch>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxTransfer(pub args::TxTransfer);
 
@@ -1028,6 +1408,50 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxIcaRegister(pub args::TxIcaRegister);
+
+    impl SubCmd for TxIcaRegister {
+        const CMD: &'static str = "ica-register";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxIcaRegister(args::TxIcaRegister::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to open a channel for the \
+                     interchain account of the owner on the host chain.",
+                )
+                .add_args::<args::TxIcaRegister>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxIcaSend(pub args::TxIcaSend);
+
+    impl SubCmd for TxIcaSend {
+        const CMD: &'static str = "ica-send";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| TxIcaSend(args::TxIcaSend::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to be executed on the \
+                     interchain account of the owner on the host chain.",
+                )
+                .add_args::<args::TxIcaSend>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxUpdateVp(pub args::TxUpdateVp);
 
@@ -1073,63 +1497,131 @@ pub mod cmds {
     }
 
     #[derive(Clone, Debug)]
-    pub struct TxInitValidator(pub args::TxInitValidator);
+    pub struct TxInitHtlc(pub args::TxInitHtlc);
 
-    impl SubCmd for TxInitValidator {
-        const CMD: &'static str = "init-validator";
+    impl SubCmd for TxInitHtlc {
+        const CMD: &'static str = "init-htlc";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
-            matches.subcommand_matches(Self::CMD).map(|matches| {
-                TxInitValidator(args::TxInitValidator::parse(matches))
-            })
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| TxInitHtlc(args::TxInitHtlc::parse(matches)))
         }
 
         fn def() -> App {
             App::new(Self::CMD)
                 .about(
-                    "Send a signed transaction to create a new validator \
-                     account.",
+                    "Send a signed transaction to create a new \
+                     hash-time-locked contract (HTLC) account that locks the \
+                     sender's tokens for the recipient.",
                 )
-                .add_args::<args::TxInitValidator>()
+                .add_args::<args::TxInitHtlc>()
         }
     }
 
     #[derive(Clone, Debug)]
-    pub struct Bond(pub args::Bond);
+    pub struct TxClaimHtlc(pub args::TxClaimHtlc);
 
-    impl SubCmd for Bond {
-        const CMD: &'static str = "bond";
+    impl SubCmd for TxClaimHtlc {
+        const CMD: &'static str = "claim-htlc";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
             matches
                 .subcommand_matches(Self::CMD)
-                .map(|matches| Bond(args::Bond::parse(matches)))
+                .map(|matches| TxClaimHtlc(args::TxClaimHtlc::parse(matches)))
         }
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about("Bond tokens in PoS system.")
-                .add_args::<args::Bond>()
+                .about(
+                    "Send a signed transaction to claim the tokens locked in \
+                     an HTLC for its recipient by revealing the preimage of \
+                     its hash lock.",
+                )
+                .add_args::<args::TxClaimHtlc>()
         }
     }
 
     #[derive(Clone, Debug)]
-    pub struct Unbond(pub args::Unbond);
+    pub struct TxRefundHtlc(pub args::TxRefundHtlc);
 
-    impl SubCmd for Unbond {
-        const CMD: &'static str = "unbond";
+    impl SubCmd for TxRefundHtlc {
+        const CMD: &'static str = "refund-htlc";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
             matches
                 .subcommand_matches(Self::CMD)
-                .map(|matches| Unbond(args::Unbond::parse(matches)))
+                .map(|matches| TxRefundHtlc(args::TxRefundHtlc::parse(matches)))
         }
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about("Unbond tokens from a PoS bond.")
-                .add_args::<args::Unbond>()
-        }
+                .about(
+                    "Send a signed transaction to refund the tokens locked in \
+                     an expired HTLC to its sender.",
+                )
+                .add_args::<args::TxRefundHtlc>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxInitValidator(pub args::TxInitValidator);
+
+    impl SubCmd for TxInitValidator {
+        const CMD: &'static str = "init-validator";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxInitValidator(args::TxInitValidator::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to create a new validator \
+                     account.",
+                )
+                .add_args::<args::TxInitValidator>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct Bond(pub args::Bond);
+
+    impl SubCmd for Bond {
+        const CMD: &'static str = "bond";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Bond(args::Bond::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Bond tokens in PoS system.")
+                .add_args::<args::Bond>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct Unbond(pub args::Unbond);
+
+    impl SubCmd for Unbond {
+        const CMD: &'static str = "unbond";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Unbond(args::Unbond::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Unbond tokens from a PoS bond.")
+                .add_args::<args::Unbond>()
+        }
     }
 
     #[derive(Clone, Debug)]
@@ -1366,6 +1858,232 @@ pub mod cmds {
         }
     }
 
+    /// IBC state queries
+    #[derive(Clone, Debug)]
+    pub enum QueryIbc {
+        ClientState(QueryIbcClientState),
+        ConsensusState(QueryIbcConsensusState),
+        Connection(QueryIbcConnection),
+        Channel(QueryIbcChannel),
+        Commitments(QueryIbcCommitments),
+        Acks(QueryIbcAcks),
+        Denoms(QueryIbcDenoms),
+        Flow(QueryIbcFlow),
+    }
+
+    impl SubCmd for QueryIbc {
+        const CMD: &'static str = "query-ibc";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).and_then(|matches| {
+                let client_state =
+                    SubCmd::parse(matches).map(Self::ClientState);
+                let consensus_state =
+                    SubCmd::parse(matches).map(Self::ConsensusState);
+                let connection = SubCmd::parse(matches).map(Self::Connection);
+                let channel = SubCmd::parse(matches).map(Self::Channel);
+                let commitments = SubCmd::parse(matches).map(Self::Commitments);
+                let acks = SubCmd::parse(matches).map(Self::Acks);
+                let denoms = SubCmd::parse(matches).map(Self::Denoms);
+                let flow = SubCmd::parse(matches).map(Self::Flow);
+                client_state
+                    .or(consensus_state)
+                    .or(connection)
+                    .or(channel)
+                    .or(commitments)
+                    .or(acks)
+                    .or(denoms)
+                    .or(flow)
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query the IBC state: clients, connections, channels, \
+                     packets and denom traces.",
+                )
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(QueryIbcClientState::def())
+                .subcommand(QueryIbcConsensusState::def())
+                .subcommand(QueryIbcConnection::def())
+                .subcommand(QueryIbcChannel::def())
+                .subcommand(QueryIbcCommitments::def())
+                .subcommand(QueryIbcAcks::def())
+                .subcommand(QueryIbcDenoms::def())
+                .subcommand(QueryIbcFlow::def())
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryIbcClientState(pub args::QueryIbcClientState);
+
+    impl SubCmd for QueryIbcClientState {
+        const CMD: &'static str = "client-state";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryIbcClientState(args::QueryIbcClientState::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query the state of an IBC client.")
+                .add_args::<args::QueryIbcClientState>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryIbcConsensusState(pub args::QueryIbcConsensusState);
+
+    impl SubCmd for QueryIbcConsensusState {
+        const CMD: &'static str = "consensus-state";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryIbcConsensusState(args::QueryIbcConsensusState::parse(
+                    matches,
+                ))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query the consensus state of an IBC client at the given \
+                     client height.",
+                )
+                .add_args::<args::QueryIbcConsensusState>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryIbcConnection(pub args::QueryIbcConnection);
+
+    impl SubCmd for QueryIbcConnection {
+        const CMD: &'static str = "connection";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryIbcConnection(args::QueryIbcConnection::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query an IBC connection end.")
+                .add_args::<args::QueryIbcConnection>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryIbcChannel(pub args::QueryIbcChannel);
+
+    impl SubCmd for QueryIbcChannel {
+        const CMD: &'static str = "channel";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryIbcChannel(args::QueryIbcChannel::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query an IBC channel end.")
+                .add_args::<args::QueryIbcChannel>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryIbcCommitments(pub args::QueryIbcChannel);
+
+    impl SubCmd for QueryIbcCommitments {
+        const CMD: &'static str = "commitments";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryIbcCommitments(args::QueryIbcChannel::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query the commitments of the packets sent on an IBC \
+                     channel which haven't been acknowledged yet.",
+                )
+                .add_args::<args::QueryIbcChannel>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryIbcAcks(pub args::QueryIbcChannel);
+
+    impl SubCmd for QueryIbcAcks {
+        const CMD: &'static str = "acks";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryIbcAcks(args::QueryIbcChannel::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query the acknowledgement commitments of the packets \
+                     received on an IBC channel.",
+                )
+                .add_args::<args::QueryIbcChannel>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryIbcDenoms(pub args::QueryIbcDenoms);
+
+    impl SubCmd for QueryIbcDenoms {
+        const CMD: &'static str = "denoms";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryIbcDenoms(args::QueryIbcDenoms::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query the denom trace of an IBC token, or all of them if \
+                     no token hash is given.",
+                )
+                .add_args::<args::QueryIbcDenoms>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryIbcFlow(pub args::QueryIbcFlow);
+
+    impl SubCmd for QueryIbcFlow {
+        const CMD: &'static str = "flow";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| QueryIbcFlow(args::QueryIbcFlow::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query the flow limit and the flows in the current epoch \
+                     of a token on an IBC channel.",
+                )
+                .add_args::<args::QueryIbcFlow>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxInitProposal(pub args::InitProposal);
 
@@ -1410,6 +2128,62 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxTallyOfflineProposal(pub args::TallyOfflineProposal);
+
+    impl SubCmd for TxTallyOfflineProposal {
+        const CMD: &'static str = "tally-offline-proposal";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxTallyOfflineProposal(args::TallyOfflineProposal::parse(
+                    matches,
+                ))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Submit an offline proposal with its votes to be tallied \
+                     on-chain once its votes submission window closes. The \
+                     proposal can be submitted again with more votes while \
+                     the window is open.",
+                )
+                .add_args::<args::TallyOfflineProposal>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxTopUpProposalDeposit(pub args::TopUpProposalDeposit);
+
+    impl SubCmd for TxTopUpProposalDeposit {
+        const CMD: &'static str = "top-up-proposal-deposit";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxTopUpProposalDeposit(args::TopUpProposalDeposit::parse(
+                    matches,
+                ))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Top up the deposit of a proposal before its voting \
+                     period starts.",
+                )
+                .add_args::<args::TopUpProposalDeposit>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxRevealPk(pub args::RevealPk);
 
@@ -1566,10 +2340,12 @@ pub mod args {
     use std::path::PathBuf;
     use std::str::FromStr;
 
-    use namada::ibc::core::ics24_host::identifier::{ChannelId, PortId};
+    use namada::ibc::core::ics24_host::identifier::{
+        ChannelId, ClientId, ConnectionId, PortId,
+    };
     use namada::types::address::Address;
     use namada::types::chain::{ChainId, ChainIdPrefix};
-    use namada::types::governance::ProposalVote;
+    use namada::types::governance::{ProposalStatus, ProposalVote};
     use namada::types::key::*;
     use namada::types::masp::MaspValue;
     use namada::types::storage::{self, Epoch};
@@ -1586,14 +2362,19 @@ pub mod args {
     use crate::config::TendermintMode;
     use crate::facade::tendermint::Timeout;
     use crate::facade::tendermint_config::net::Address as TendermintAddress;
+    use crate::wallet::address_book::{AddressBookFormat, BookAddress};
+    use crate::wallet::DerivationPath;
 
     const ADDRESS: Arg<WalletAddress> = arg("address");
     const ALIAS_OPT: ArgOpt<String> = ALIAS.opt();
     const ALIAS: Arg<String> = arg("alias");
+    const ADDRESS_BOOK_FORMAT_OPT: ArgOpt<AddressBookFormat> =
+        arg_opt("format");
     const ALLOW_DUPLICATE_IP: ArgFlag = flag("allow-duplicate-ip");
     const AMOUNT: Arg<token::Amount> = arg("amount");
     const ARCHIVE_DIR: ArgOpt<PathBuf> = arg_opt("archive-dir");
     const BALANCE_OWNER: ArgOpt<WalletBalanceOwner> = arg_opt("owner");
+    const BIP39_PASSPHRASE: ArgFlag = flag("bip39-passphrase");
     const BASE_DIR: ArgDefault<PathBuf> = arg_default(
         "base-dir",
         DefaultFn(|| match env::var("NAMADA_BASE_DIR") {
@@ -1602,35 +2383,51 @@ pub mod args {
         }),
     );
     // const BLOCK_HEIGHT_OPT: ArgOpt<BlockHeight> = arg_opt("height");
+    const BOOK_ADDRESS: Arg<BookAddress> = arg("address");
     const BROADCAST_ONLY: ArgFlag = flag("broadcast-only");
     const CHAIN_ID: Arg<ChainId> = arg("chain-id");
     const CHAIN_ID_OPT: ArgOpt<ChainId> = CHAIN_ID.opt();
     const CHAIN_ID_PREFIX: Arg<ChainIdPrefix> = arg("chain-prefix");
     const CHANNEL_ID: Arg<ChannelId> = arg("channel-id");
+    const CLIENT_ID: Arg<ClientId> = arg("client-id");
     const CODE_PATH: Arg<PathBuf> = arg("code-path");
     const CODE_PATH_OPT: ArgOpt<PathBuf> = CODE_PATH.opt();
     const COMMISSION_RATE: Arg<Decimal> = arg("commission-rate");
+    const CONNECTION_ID: Arg<ConnectionId> = arg("connection-id");
+    const CONSENSUS_KEY: Arg<WalletKeypair> = arg("consensus-key");
     const CONSENSUS_TIMEOUT_COMMIT: ArgDefault<Timeout> = arg_default(
         "consensus-timeout-commit",
         DefaultFn(|| Timeout::from_str("1s").unwrap()),
     );
     const DATA_PATH_OPT: ArgOpt<PathBuf> = arg_opt("data-path");
     const DATA_PATH: Arg<PathBuf> = arg("data-path");
+    const DEADLINE: Arg<DateTimeUtc> = arg("deadline");
+    const DERIVATION_PATH: Arg<DerivationPath> = arg("path");
+    const DERIVATION_PATH_OPT: ArgOpt<DerivationPath> = DERIVATION_PATH.opt();
     const DECRYPT: ArgFlag = flag("decrypt");
+    const DENOM: Arg<String> = arg("denom");
+    const DEPOSITOR: Arg<WalletAddress> = arg("depositor");
     const DONT_ARCHIVE: ArgFlag = flag("dont-archive");
     const DRY_RUN_TX: ArgFlag = flag("dry-run");
     const DUMP_TX: ArgFlag = flag("dump-tx");
+    const EMBED_TX_CODE: ArgFlag = flag("embed-tx-code");
     const EPOCH: ArgOpt<Epoch> = arg_opt("epoch");
+    const FEE_PAYER: ArgOpt<WalletAddress> = arg_opt("fee-payer");
+    const FILE: Arg<PathBuf> = arg("file");
     const FORCE: ArgFlag = flag("force");
+    const FROM_FILE: Arg<PathBuf> = arg("from-file");
     const DONT_PREFETCH_WASM: ArgFlag = flag("dont-prefetch-wasm");
     const GAS_AMOUNT: ArgDefault<token::Amount> =
         arg_default("gas-amount", DefaultFn(|| token::Amount::from(0)));
     const GAS_LIMIT: ArgDefault<token::Amount> =
-        arg_default("gas-limit", DefaultFn(|| token::Amount::from(0)));
+        arg_default("gas-limit", DefaultFn(|| token::Amount::from(20_000_000)));
     const GAS_TOKEN: ArgDefaultFromCtx<WalletAddress> =
         arg_default_from_ctx("gas-token", DefaultFn(|| "NAM".into()));
     const GENESIS_PATH: Arg<PathBuf> = arg("genesis-path");
+    const HASH_LOCK: Arg<String> = arg("hash-lock");
+    const HTLC: Arg<WalletAddress> = arg("htlc");
     const GENESIS_VALIDATOR: ArgOpt<String> = arg("genesis-validator").opt();
+    const LABEL_OPT: ArgOpt<String> = arg_opt("label");
     const LEDGER_ADDRESS_ABOUT: &str =
         "Address of a ledger node as \"{scheme}://{host}:{port}\". If the \
          scheme is not supplied, it is assumed to be TCP.";
@@ -1643,12 +2440,15 @@ pub mod args {
     const LEDGER_ADDRESS: Arg<TendermintAddress> = arg("ledger-address");
     const LOCALHOST: ArgFlag = flag("localhost");
     const MASP_VALUE: Arg<MaspValue> = arg("value");
+    const MNEMONIC: ArgFlag = flag("mnemonic");
     const MAX_COMMISSION_RATE_CHANGE: Arg<Decimal> =
         arg("max-commission-rate-change");
+    const MEMO: ArgOpt<String> = arg_opt("memo");
     const MODE: ArgOpt<String> = arg_opt("mode");
     const NET_ADDRESS: Arg<SocketAddr> = arg("net-address");
     const NAMADA_START_TIME: ArgOpt<DateTimeUtc> = arg_opt("time");
     const NO_CONVERSIONS: ArgFlag = flag("no-conversions");
+    const NOTES_OPT: ArgOpt<String> = arg_opt("notes");
     const OUT_FILE_PATH_OPT: ArgOpt<PathBuf> = arg_opt("out-file-path");
     const OWNER: Arg<WalletAddress> = arg("owner");
     const OWNER_OPT: ArgOpt<WalletAddress> = OWNER.opt();
@@ -1658,18 +2458,26 @@ pub mod args {
         DefaultFn(|| PortId::from_str("transfer").unwrap()),
     );
     const PROPOSAL_OFFLINE: ArgFlag = flag("offline");
+    const PROVE: ArgFlag = flag("prove");
     const PROTOCOL_KEY: ArgOpt<WalletPublicKey> = arg_opt("protocol-key");
     const PRE_GENESIS_PATH: ArgOpt<PathBuf> = arg_opt("pre-genesis-path");
+    const PREIMAGE: Arg<String> = arg("preimage");
     const PUBLIC_KEY: Arg<WalletPublicKey> = arg("public-key");
     const PROPOSAL_ID: Arg<u64> = arg("proposal-id");
     const PROPOSAL_ID_OPT: ArgOpt<u64> = arg_opt("proposal-id");
+    const PROPOSAL_STATUS_OPT: ArgOpt<ProposalStatus> = arg_opt("status");
     const PROPOSAL_VOTE: Arg<ProposalVote> = arg("vote");
     const RAW_ADDRESS: Arg<Address> = arg("address");
     const RAW_ADDRESS_OPT: ArgOpt<Address> = RAW_ADDRESS.opt();
     const RAW_PUBLIC_KEY_OPT: ArgOpt<common::PublicKey> = arg_opt("public-key");
     const RECEIVER: Arg<String> = arg("receiver");
+    const RECIPIENT: Arg<WalletAddress> = arg("recipient");
+    const REVISION_HEIGHT: Arg<u64> = arg("revision-height");
+    const REVISION_NUMBER: ArgDefault<u64> =
+        arg_default("revision-number", DefaultFn(|| 0));
     const SCHEME: ArgDefault<SchemeType> =
         arg_default("scheme", DefaultFn(|| SchemeType::Ed25519));
+    const SHIELDED: ArgFlag = flag("shielded");
     const SIGNER: ArgOpt<WalletAddress> = arg_opt("signer");
     const SIGNING_KEY_OPT: ArgOpt<WalletKeypair> = SIGNING_KEY.opt();
     const SIGNING_KEY: Arg<WalletKeypair> = arg("signing-key");
@@ -1677,8 +2485,12 @@ pub mod args {
     const SOURCE_OPT: ArgOpt<WalletAddress> = SOURCE.opt();
     const STORAGE_KEY: Arg<storage::Key> = arg("storage-key");
     const SUB_PREFIX: ArgOpt<String> = arg_opt("sub-prefix");
+    const TAG: Arg<String> = arg("tag");
+    const TAG_OPT: ArgOpt<String> = TAG.opt();
+    const TAGS: ArgMulti<String> = TAG.multi();
     const TIMEOUT_HEIGHT: ArgOpt<u64> = arg_opt("timeout-height");
     const TIMEOUT_SEC_OFFSET: ArgOpt<u64> = arg_opt("timeout-sec-offset");
+    const TOKEN_HASH_OPT: ArgOpt<String> = arg_opt("token-hash");
     const TOKEN_OPT: ArgOpt<WalletAddress> = TOKEN.opt();
     const TOKEN: Arg<WalletAddress> = arg("token");
     const TRANSFER_SOURCE: Arg<WalletTransferSource> = arg("source");
@@ -1767,6 +2579,27 @@ pub mod args {
         }
     }
 
+    /// Remote signer arguments
+    #[derive(Clone, Debug)]
+    pub struct SignerRun {
+        /// The validator's consensus key
+        pub consensus_key: WalletKeypair,
+    }
+
+    impl Args for SignerRun {
+        fn parse(matches: &ArgMatches) -> Self {
+            let consensus_key = CONSENSUS_KEY.parse(matches);
+            Self { consensus_key }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(CONSENSUS_KEY.def().about(
+                "The validator's consensus key from the wallet. The protocol \
+                 key is taken from the wallet's validator data.",
+            ))
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct LedgerDumpDb {
         // TODO: allow to specify height
@@ -1862,18 +2695,45 @@ pub mod args {
         }
     }
 
-    /// Transfer transaction arguments
+    /// Batch transaction arguments
     #[derive(Clone, Debug)]
-    pub struct TxTransfer {
+    pub struct TxBatch {
         /// Common tx arguments
         pub tx: Tx,
-        /// Transfer source address
-        pub source: WalletTransferSource,
-        /// Transfer target address
-        pub target: WalletTransferTarget,
-        /// Transferred token address
-        pub token: WalletAddress,
-        /// Transferred token address
+        /// Path to the JSON file with the calls of the batch
+        pub from_file: PathBuf,
+    }
+
+    impl Args for TxBatch {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let from_file = FROM_FILE.parse(matches);
+            Self { tx, from_file }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>().arg(FROM_FILE.def().about(
+                "The path to a JSON file with an array of the calls of the \
+                 batch, e.g. `[{\"transfer\": {..}}, {\"bond\": {..}}]`. A \
+                 call can also be a `custom` tx with a `code_path`, an \
+                 optional `data_path` and a `signer` address. Each call is \
+                 signed by its source or signer.",
+            ))
+        }
+    }
+
+    /// Transfer transaction arguments
+    #[derive(Clone, Debug)]
+    pub struct TxTransfer {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Transfer source address
+        pub source: WalletTransferSource,
+        /// Transfer target address
+        pub target: WalletTransferTarget,
+        /// Transferred token address
+        pub token: WalletAddress,
+        /// Transferred token address
         pub sub_prefix: Option<String>,
         /// Transferred token amount
         pub amount: token::Amount,
@@ -2002,6 +2862,108 @@ pub mod args {
         }
     }
 
+    /// Interchain account registration arguments
+    #[derive(Clone, Debug)]
+    pub struct TxIcaRegister {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// The owner of the interchain account
+        pub owner: WalletAddress,
+        /// Connection ID to the host chain
+        pub connection_id: ConnectionId,
+    }
+
+    impl Args for TxIcaRegister {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let owner = OWNER.parse(matches);
+            let connection_id = CONNECTION_ID.parse(matches);
+            Self {
+                tx,
+                owner,
+                connection_id,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(OWNER.def().about(
+                    "The owner of the interchain account. The owner's key is \
+                     used to produce the signature.",
+                ))
+                .arg(
+                    CONNECTION_ID
+                        .def()
+                        .about("The connection ID to the host chain."),
+                )
+        }
+    }
+
+    /// Interchain account transaction arguments
+    #[derive(Clone, Debug)]
+    pub struct TxIcaSend {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// The owner of the interchain account
+        pub owner: WalletAddress,
+        /// Connection ID to the host chain
+        pub connection_id: ConnectionId,
+        /// Path to the transaction data to be executed on the host chain
+        pub data_path: PathBuf,
+        /// Memo of the packet
+        pub memo: Option<String>,
+        /// Timeout height of the host chain
+        pub timeout_height: Option<u64>,
+        /// Timeout timestamp offset
+        pub timeout_sec_offset: Option<u64>,
+    }
+
+    impl Args for TxIcaSend {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let owner = OWNER.parse(matches);
+            let connection_id = CONNECTION_ID.parse(matches);
+            let data_path = DATA_PATH.parse(matches);
+            let memo = MEMO.parse(matches);
+            let timeout_height = TIMEOUT_HEIGHT.parse(matches);
+            let timeout_sec_offset = TIMEOUT_SEC_OFFSET.parse(matches);
+            Self {
+                tx,
+                owner,
+                connection_id,
+                data_path,
+                memo,
+                timeout_height,
+                timeout_sec_offset,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(OWNER.def().about(
+                    "The owner of the interchain account. The owner's key is \
+                     used to produce the signature.",
+                ))
+                .arg(
+                    CONNECTION_ID
+                        .def()
+                        .about("The connection ID to the host chain."),
+                )
+                .arg(DATA_PATH.def().about(
+                    "The data file of the transaction to be executed on the \
+                     interchain account, e.g. an encoded CosmosTx for a \
+                     Cosmos SDK chain.",
+                ))
+                .arg(MEMO.def().about("The memo of the packet."))
+                .arg(
+                    TIMEOUT_HEIGHT
+                        .def()
+                        .about("The timeout height of the host chain."),
+                )
+                .arg(TIMEOUT_SEC_OFFSET.def().about("The timeout as seconds."))
+        }
+    }
+
     /// Transaction to initialize a new account
     #[derive(Clone, Debug)]
     pub struct TxInitAccount {
@@ -2046,6 +3008,119 @@ pub mod args {
         }
     }
 
+    /// Transaction to initialize a new HTLC account
+    #[derive(Clone, Debug)]
+    pub struct TxInitHtlc {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// The account whose tokens are locked
+        pub source: WalletAddress,
+        /// The account that may claim the tokens
+        pub recipient: WalletAddress,
+        /// The locked token
+        pub token: WalletAddress,
+        /// The amount of locked tokens
+        pub amount: token::Amount,
+        /// The hex-encoded SHA-256 hash of the preimage
+        pub hash_lock: String,
+        /// The time from which the tokens can be refunded
+        pub deadline: DateTimeUtc,
+    }
+
+    impl Args for TxInitHtlc {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let source = SOURCE.parse(matches);
+            let recipient = RECIPIENT.parse(matches);
+            let token = TOKEN.parse(matches);
+            let amount = AMOUNT.parse(matches);
+            let hash_lock = HASH_LOCK.parse(matches);
+            let deadline = DEADLINE.parse(matches);
+            Self {
+                tx,
+                source,
+                recipient,
+                token,
+                amount,
+                hash_lock,
+                deadline,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(SOURCE.def().about(
+                    "The sender's address whose tokens are locked. The \
+                     sender's key is used to produce the signature.",
+                ))
+                .arg(RECIPIENT.def().about(
+                    "The recipient's address that may claim the tokens.",
+                ))
+                .arg(TOKEN.def().about("The locked token."))
+                .arg(AMOUNT.def().about("The amount to lock in decimal."))
+                .arg(HASH_LOCK.def().about(
+                    "The hex-encoded SHA-256 hash of the preimage required to \
+                     claim the tokens.",
+                ))
+                .arg(DEADLINE.def().about(
+                    "The time in RFC3339 format from which the tokens can no \
+                     longer be claimed and can be refunded to the sender.",
+                ))
+        }
+    }
+
+    /// Transaction to claim the tokens of an HTLC
+    #[derive(Clone, Debug)]
+    pub struct TxClaimHtlc {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// The HTLC account
+        pub htlc: WalletAddress,
+        /// The hex-encoded preimage of the hash lock
+        pub preimage: String,
+    }
+
+    impl Args for TxClaimHtlc {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let htlc = HTLC.parse(matches);
+            let preimage = PREIMAGE.parse(matches);
+            Self { tx, htlc, preimage }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(HTLC.def().about("The address of the HTLC account."))
+                .arg(
+                    PREIMAGE.def().about(
+                        "The hex-encoded preimage of the HTLC's hash lock.",
+                    ),
+                )
+        }
+    }
+
+    /// Transaction to refund the tokens of an expired HTLC
+    #[derive(Clone, Debug)]
+    pub struct TxRefundHtlc {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// The HTLC account
+        pub htlc: WalletAddress,
+    }
+
+    impl Args for TxRefundHtlc {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let htlc = HTLC.parse(matches);
+            Self { tx, htlc }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(HTLC.def().about("The address of the HTLC account."))
+        }
+    }
+
     /// Transaction to initialize a new account
     #[derive(Clone, Debug)]
     pub struct TxInitValidator {
@@ -2330,11 +3405,9 @@ pub mod args {
                             DATA_PATH_OPT.name,
                         ]),
                 )
-                .arg(
-                    PROPOSAL_VOTE
-                        .def()
-                        .about("The vote for the proposal. Either yay or nay."),
-                )
+                .arg(PROPOSAL_VOTE.def().about(
+                    "The vote for the proposal. Either yay, nay or veto.",
+                ))
                 .arg(
                     PROPOSAL_OFFLINE
                         .def()
@@ -2353,6 +3426,75 @@ pub mod args {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TallyOfflineProposal {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// The folder containing the proposal and votes
+        pub proposal_folder: PathBuf,
+    }
+
+    impl Args for TallyOfflineProposal {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let proposal_folder = DATA_PATH.parse(matches);
+
+            Self {
+                tx,
+                proposal_folder,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>().arg(DATA_PATH.def().about(
+                "The folder containing the offline proposal in a file named \
+                 \"proposal\" and its vote files.",
+            ))
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TopUpProposalDeposit {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// The proposal id
+        pub proposal_id: u64,
+        /// The address depositing the tokens
+        pub depositor: WalletAddress,
+        /// The amount of native tokens to deposit
+        pub amount: token::Amount,
+    }
+
+    impl Args for TopUpProposalDeposit {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let proposal_id = PROPOSAL_ID.parse(matches);
+            let depositor = DEPOSITOR.parse(matches);
+            let amount = AMOUNT.parse(matches);
+
+            Self {
+                tx,
+                proposal_id,
+                depositor,
+                amount,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(PROPOSAL_ID.def().about("The proposal identifier."))
+                .arg(DEPOSITOR.def().about(
+                    "The address depositing the tokens, to which they're \
+                     refunded with the proposal's deposit.",
+                ))
+                .arg(
+                    AMOUNT
+                        .def()
+                        .about("The amount of native tokens to deposit."),
+                )
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct RevealPk {
         /// Common tx arguments
@@ -2381,19 +3523,35 @@ pub mod args {
         pub query: Query,
         /// Proposal id
         pub proposal_id: Option<u64>,
+        /// Only list the proposals with this status
+        pub status: Option<ProposalStatus>,
     }
 
     impl Args for QueryProposal {
         fn parse(matches: &ArgMatches) -> Self {
             let query = Query::parse(matches);
             let proposal_id = PROPOSAL_ID_OPT.parse(matches);
+            let status = PROPOSAL_STATUS_OPT.parse(matches);
 
-            Self { query, proposal_id }
+            Self {
+                query,
+                proposal_id,
+                status,
+            }
         }
 
         fn def(app: App) -> App {
             app.add_args::<Tx>()
                 .arg(PROPOSAL_ID_OPT.def().about("The proposal identifier."))
+                .arg(
+                    PROPOSAL_STATUS_OPT
+                        .def()
+                        .about(
+                            "Only list the proposals with the given status: \
+                             pending, voting, passed, rejected or executed.",
+                        )
+                        .conflicts_with(PROPOSAL_ID_OPT.name),
+                )
         }
     }
 
@@ -2448,6 +3606,31 @@ pub mod args {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct QuerySlashFundDisbursements {
+        /// Common query args
+        pub query: Query,
+        /// Only list the disbursements of this proposal
+        pub proposal_id: Option<u64>,
+    }
+
+    impl Args for QuerySlashFundDisbursements {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let proposal_id = PROPOSAL_ID_OPT.parse(matches);
+
+            Self { query, proposal_id }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>().arg(
+                PROPOSAL_ID_OPT
+                    .def()
+                    .about("Only list the disbursements of this proposal."),
+            )
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryProtocolParameters {
         /// Common query args
@@ -2727,121 +3910,339 @@ pub mod args {
 
         fn def(app: App) -> App {
             app.add_args::<Query>()
-                .arg(VALIDATOR.def().about(
-                    "The validator's address whose commission rate to change.",
-                ))
+                .arg(VALIDATOR.def().about(
+                    "The validator's address whose commission rate to change.",
+                ))
+                .arg(
+                    COMMISSION_RATE
+                        .def()
+                        .about("The desired new commission rate."),
+                )
+        }
+    }
+
+    /// Query PoS commission rate
+    #[derive(Clone, Debug)]
+    pub struct QueryCommissionRate {
+        /// Common query args
+        pub query: Query,
+        /// Address of a validator
+        pub validator: WalletAddress,
+        /// Epoch in which to find commission rate
+        pub epoch: Option<Epoch>,
+    }
+
+    impl Args for QueryCommissionRate {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let epoch = EPOCH.parse(matches);
+            Self {
+                query,
+                validator,
+                epoch,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>()
+                .arg(VALIDATOR.def().about(
+                    "The validator's address whose commission rate to query.",
+                ))
+                .arg(EPOCH.def().about(
+                    "The epoch at which to query (last committed, if not \
+                     specified).",
+                ))
+        }
+    }
+
+    /// Query PoS slashes
+    #[derive(Clone, Debug)]
+    pub struct QuerySlashes {
+        /// Common query args
+        pub query: Query,
+        /// Address of a validator
+        pub validator: Option<WalletAddress>,
+    }
+
+    impl Args for QuerySlashes {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let validator = VALIDATOR_OPT.parse(matches);
+            Self { query, validator }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>().arg(
+                VALIDATOR_OPT
+                    .def()
+                    .about("The validator's address whose slashes to query."),
+            )
+        }
+    }
+
+    /// Query PoS delegations
+    #[derive(Clone, Debug)]
+    pub struct QueryDelegations {
+        /// Common query args
+        pub query: Query,
+        /// Address of an owner
+        pub owner: WalletAddress,
+    }
+
+    impl Args for QueryDelegations {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let owner = OWNER.parse(matches);
+            Self { query, owner }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>().arg(
+                OWNER.def().about(
+                    "The address of the owner of the delegations to find.",
+                ),
+            )
+        }
+    }
+
+    /// Query the raw bytes of given storage key
+    #[derive(Clone, Debug)]
+    pub struct QueryRawBytes {
+        /// The storage key to query
+        pub storage_key: storage::Key,
+        /// Common query args
+        pub query: Query,
+    }
+
+    impl Args for QueryRawBytes {
+        fn parse(matches: &ArgMatches) -> Self {
+            let storage_key = STORAGE_KEY.parse(matches);
+            let query = Query::parse(matches);
+            Self { storage_key, query }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>()
+                .arg(STORAGE_KEY.def().about("Storage key"))
+        }
+    }
+
+    const PROVE_ABOUT: &str = "Query the Merkle proof of the state, too.";
+
+    /// Query an IBC client state
+    #[derive(Clone, Debug)]
+    pub struct QueryIbcClientState {
+        /// Common query args
+        pub query: Query,
+        /// Query the proof, too
+        pub prove: bool,
+        /// The client ID
+        pub client_id: ClientId,
+    }
+
+    impl Args for QueryIbcClientState {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let prove = PROVE.parse(matches);
+            let client_id = CLIENT_ID.parse(matches);
+            Self {
+                query,
+                prove,
+                client_id,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>()
+                .arg(PROVE.def().about(PROVE_ABOUT))
+                .arg(CLIENT_ID.def().about("The IBC client ID."))
+        }
+    }
+
+    /// Query an IBC consensus state
+    #[derive(Clone, Debug)]
+    pub struct QueryIbcConsensusState {
+        /// Common query args
+        pub query: Query,
+        /// Query the proof, too
+        pub prove: bool,
+        /// The client ID
+        pub client_id: ClientId,
+        /// The revision number of the client height
+        pub revision_number: u64,
+        /// The revision height of the client height
+        pub revision_height: u64,
+    }
+
+    impl Args for QueryIbcConsensusState {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let prove = PROVE.parse(matches);
+            let client_id = CLIENT_ID.parse(matches);
+            let revision_number = REVISION_NUMBER.parse(matches);
+            let revision_height = REVISION_HEIGHT.parse(matches);
+            Self {
+                query,
+                prove,
+                client_id,
+                revision_number,
+                revision_height,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>()
+                .arg(PROVE.def().about(PROVE_ABOUT))
+                .arg(CLIENT_ID.def().about("The IBC client ID."))
                 .arg(
-                    COMMISSION_RATE
+                    REVISION_NUMBER
                         .def()
-                        .about("The desired new commission rate."),
+                        .about("The revision number of the client height."),
+                )
+                .arg(
+                    REVISION_HEIGHT
+                        .def()
+                        .about("The revision height of the client height."),
                 )
         }
     }
 
-    /// Query PoS commission rate
+    /// Query an IBC connection end
     #[derive(Clone, Debug)]
-    pub struct QueryCommissionRate {
+    pub struct QueryIbcConnection {
         /// Common query args
         pub query: Query,
-        /// Address of a validator
-        pub validator: WalletAddress,
-        /// Epoch in which to find commission rate
-        pub epoch: Option<Epoch>,
+        /// Query the proof, too
+        pub prove: bool,
+        /// The connection ID
+        pub connection_id: ConnectionId,
     }
 
-    impl Args for QueryCommissionRate {
+    impl Args for QueryIbcConnection {
         fn parse(matches: &ArgMatches) -> Self {
             let query = Query::parse(matches);
-            let validator = VALIDATOR.parse(matches);
-            let epoch = EPOCH.parse(matches);
+            let prove = PROVE.parse(matches);
+            let connection_id = CONNECTION_ID.parse(matches);
             Self {
                 query,
-                validator,
-                epoch,
+                prove,
+                connection_id,
             }
         }
 
         fn def(app: App) -> App {
             app.add_args::<Query>()
-                .arg(VALIDATOR.def().about(
-                    "The validator's address whose commission rate to query.",
-                ))
-                .arg(EPOCH.def().about(
-                    "The epoch at which to query (last committed, if not \
-                     specified).",
-                ))
+                .arg(PROVE.def().about(PROVE_ABOUT))
+                .arg(CONNECTION_ID.def().about("The IBC connection ID."))
         }
     }
 
-    /// Query PoS slashes
+    /// Query an IBC channel end, or the packet states of the channel
     #[derive(Clone, Debug)]
-    pub struct QuerySlashes {
+    pub struct QueryIbcChannel {
         /// Common query args
         pub query: Query,
-        /// Address of a validator
-        pub validator: Option<WalletAddress>,
+        /// Query the proof, too
+        pub prove: bool,
+        /// The port ID
+        pub port_id: PortId,
+        /// The channel ID
+        pub channel_id: ChannelId,
     }
 
-    impl Args for QuerySlashes {
+    impl Args for QueryIbcChannel {
         fn parse(matches: &ArgMatches) -> Self {
             let query = Query::parse(matches);
-            let validator = VALIDATOR_OPT.parse(matches);
-            Self { query, validator }
+            let prove = PROVE.parse(matches);
+            let port_id = PORT_ID.parse(matches);
+            let channel_id = CHANNEL_ID.parse(matches);
+            Self {
+                query,
+                prove,
+                port_id,
+                channel_id,
+            }
         }
 
         fn def(app: App) -> App {
-            app.add_args::<Query>().arg(
-                VALIDATOR_OPT
-                    .def()
-                    .about("The validator's address whose slashes to query."),
-            )
+            app.add_args::<Query>()
+                .arg(PROVE.def().about(PROVE_ABOUT))
+                .arg(PORT_ID.def().about("The IBC port ID."))
+                .arg(CHANNEL_ID.def().about("The IBC channel ID."))
         }
     }
 
-    /// Query PoS delegations
+    /// Query IBC denom traces
     #[derive(Clone, Debug)]
-    pub struct QueryDelegations {
+    pub struct QueryIbcDenoms {
         /// Common query args
         pub query: Query,
-        /// Address of an owner
-        pub owner: WalletAddress,
+        /// Query the proof, too
+        pub prove: bool,
+        /// The hash of the IBC token. All the denom traces are queried if
+        /// it's not given.
+        pub token_hash: Option<String>,
     }
 
-    impl Args for QueryDelegations {
+    impl Args for QueryIbcDenoms {
         fn parse(matches: &ArgMatches) -> Self {
             let query = Query::parse(matches);
-            let owner = OWNER.parse(matches);
-            Self { query, owner }
+            let prove = PROVE.parse(matches);
+            let token_hash = TOKEN_HASH_OPT.parse(matches);
+            Self {
+                query,
+                prove,
+                token_hash,
+            }
         }
 
         fn def(app: App) -> App {
-            app.add_args::<Query>().arg(
-                OWNER.def().about(
-                    "The address of the owner of the delegations to find.",
-                ),
-            )
+            app.add_args::<Query>()
+                .arg(PROVE.def().about(PROVE_ABOUT))
+                .arg(
+                    TOKEN_HASH_OPT
+                        .def()
+                        .about("The hash of the IBC token denom."),
+                )
         }
     }
 
-    /// Query the raw bytes of given storage key
+    /// Query an IBC token flow
     #[derive(Clone, Debug)]
-    pub struct QueryRawBytes {
-        /// The storage key to query
-        pub storage_key: storage::Key,
+    pub struct QueryIbcFlow {
         /// Common query args
         pub query: Query,
+        /// The port ID
+        pub port_id: PortId,
+        /// The channel ID
+        pub channel_id: ChannelId,
+        /// The denom on this chain
+        pub denom: String,
     }
 
-    impl Args for QueryRawBytes {
+    impl Args for QueryIbcFlow {
         fn parse(matches: &ArgMatches) -> Self {
-            let storage_key = STORAGE_KEY.parse(matches);
             let query = Query::parse(matches);
-            Self { storage_key, query }
+            let port_id = PORT_ID.parse(matches);
+            let channel_id = CHANNEL_ID.parse(matches);
+            let denom = DENOM.parse(matches);
+            Self {
+                query,
+                port_id,
+                channel_id,
+                denom,
+            }
         }
 
         fn def(app: App) -> App {
             app.add_args::<Query>()
-                .arg(STORAGE_KEY.def().about("Storage key"))
+                .arg(PORT_ID.def().about("The IBC port ID."))
+                .arg(CHANNEL_ID.def().about("The IBC channel ID."))
+                .arg(DENOM.def().about(
+                    "The token denom on this chain, e.g. \
+                     transfer/channel-0/uatom for a received token.",
+                ))
         }
     }
     /// Common transaction arguments
@@ -2851,6 +4252,8 @@ pub mod args {
         pub dry_run: bool,
         /// Dump the transaction bytes
         pub dump_tx: bool,
+        /// Embed the full transaction code instead of referring to it by hash
+        pub embed_tx_code: bool,
         /// Submit the transaction even if it doesn't pass client checks
         pub force: bool,
         /// Do not wait for the transaction to be added to the blockchain
@@ -2870,6 +4273,8 @@ pub mod args {
         pub signing_key: Option<WalletKeypair>,
         /// Sign the tx with the keypair of the public key of the given address
         pub signer: Option<WalletAddress>,
+        /// Pay the fee from the given address, which co-signs the wrapper tx
+        pub fee_payer: Option<WalletAddress>,
     }
 
     impl Tx {
@@ -2877,6 +4282,7 @@ pub mod args {
             ParsedTxArgs {
                 dry_run: self.dry_run,
                 dump_tx: self.dump_tx,
+                embed_tx_code: self.embed_tx_code,
                 force: self.force,
                 broadcast_only: self.broadcast_only,
                 ledger_address: self.ledger_address.clone(),
@@ -2891,6 +4297,10 @@ pub mod args {
                     .as_ref()
                     .map(|sk| ctx.get_cached(sk)),
                 signer: self.signer.as_ref().map(|signer| ctx.get(signer)),
+                fee_payer: self
+                    .fee_payer
+                    .as_ref()
+                    .map(|fee_payer| ctx.get(fee_payer)),
             }
         }
     }
@@ -2903,6 +4313,10 @@ pub mod args {
                     .about("Simulate the transaction application."),
             )
             .arg(DUMP_TX.def().about("Dump transaction bytes to a file."))
+            .arg(EMBED_TX_CODE.def().about(
+                "Embed the full transaction code in the transaction instead \
+                 of referring to the code stored on-chain by its hash.",
+            ))
             .arg(FORCE.def().about(
                 "Submit the transaction even if it doesn't pass client checks.",
             ))
@@ -2918,7 +4332,10 @@ pub mod args {
                  address joined with a number.",
             ))
             .arg(GAS_AMOUNT.def().about(
-                "The amount being paid for the inclusion of this transaction",
+                "The amount being paid for the inclusion of this transaction. \
+                 If it's lower than the minimum fee for the gas limit at the \
+                 minimum gas price of the fee token, the minimum fee is paid \
+                 instead. The fee for the unused gas is partially refunded.",
             ))
             .arg(GAS_TOKEN.def().about("The token for paying the gas"))
             .arg(
@@ -2945,11 +4362,17 @@ pub mod args {
                     )
                     .conflicts_with(SIGNING_KEY_OPT.name),
             )
+            .arg(FEE_PAYER.def().about(
+                "Pay the fee from the given address instead of the signer's \
+                 implicit account. The fee payer co-signs the wrapper \
+                 transaction with the keypair of its public key.",
+            ))
         }
 
         fn parse(matches: &ArgMatches) -> Self {
             let dry_run = DRY_RUN_TX.parse(matches);
             let dump_tx = DUMP_TX.parse(matches);
+            let embed_tx_code = EMBED_TX_CODE.parse(matches);
             let force = FORCE.parse(matches);
             let broadcast_only = BROADCAST_ONLY.parse(matches);
             let ledger_address = LEDGER_ADDRESS_DEFAULT.parse(matches);
@@ -2960,9 +4383,11 @@ pub mod args {
 
             let signing_key = SIGNING_KEY_OPT.parse(matches);
             let signer = SIGNER.parse(matches);
+            let fee_payer = FEE_PAYER.parse(matches);
             Self {
                 dry_run,
                 dump_tx,
+                embed_tx_code,
                 force,
                 broadcast_only,
                 ledger_address,
@@ -2972,6 +4397,7 @@ pub mod args {
                 gas_limit,
                 signing_key,
                 signer,
+                fee_payer,
             }
         }
     }
@@ -3111,6 +4537,12 @@ pub mod args {
         pub scheme: SchemeType,
         /// Key alias
         pub alias: Option<String>,
+        /// Generate a mnemonic code and derive the keypair from it
+        pub mnemonic: bool,
+        /// The HD path of a keypair derived from a mnemonic code
+        pub derivation_path: Option<DerivationPath>,
+        /// Prompt for a BIP39 passphrase that extends the mnemonic code
+        pub bip39_passphrase: bool,
         /// Don't encrypt the keypair
         pub unsafe_dont_encrypt: bool,
     }
@@ -3119,10 +4551,16 @@ pub mod args {
         fn parse(matches: &ArgMatches) -> Self {
             let scheme = SCHEME.parse(matches);
             let alias = ALIAS_OPT.parse(matches);
+            let mnemonic = MNEMONIC.parse(matches);
+            let derivation_path = DERIVATION_PATH_OPT.parse(matches);
+            let bip39_passphrase = BIP39_PASSPHRASE.parse(matches);
             let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
             Self {
                 scheme,
                 alias,
+                mnemonic,
+                derivation_path,
+                bip39_passphrase,
                 unsafe_dont_encrypt,
             }
         }
@@ -3137,6 +4575,32 @@ pub mod args {
                 "The key and address alias. If none provided, the alias will \
                  be the public key hash.",
             ))
+            .arg(MNEMONIC.def().about(
+                "Generate a BIP39 mnemonic code of 24 words and derive the \
+                 keypair from it. The mnemonic code can restore the keypair \
+                 and derive more keys, so write it down and keep it safe.",
+            ))
+            .arg(
+                DERIVATION_PATH_OPT
+                    .def()
+                    .about(
+                        "The HD path of the keypair derived from the mnemonic \
+                         code. If none provided, the default is \
+                         m/44'/877'/0'/0'/0' for ed25519 and \
+                         m/44'/877'/0'/0/0 for secp256k1.",
+                    )
+                    .requires(MNEMONIC.name),
+            )
+            .arg(
+                BIP39_PASSPHRASE
+                    .def()
+                    .about(
+                        "Prompt for a BIP39 passphrase that extends the \
+                         mnemonic code. The passphrase is also needed to \
+                         restore the keypair.",
+                    )
+                    .requires(MNEMONIC.name),
+            )
             .arg(UNSAFE_DONT_ENCRYPT.def().about(
                 "UNSAFE: Do not encrypt the keypair. Do not use this for keys \
                  used in a live network.",
@@ -3144,6 +4608,150 @@ pub mod args {
         }
     }
 
+    /// Wallet key derivation from a mnemonic code arguments
+    #[derive(Clone, Debug)]
+    pub struct KeyDerive {
+        /// Scheme type
+        pub scheme: SchemeType,
+        /// Key alias
+        pub alias: Option<String>,
+        /// The HD path of the key
+        pub derivation_path: DerivationPath,
+        /// Derive a shielded spending key instead of a keypair
+        pub shielded: bool,
+        /// Prompt for a BIP39 passphrase that extends the mnemonic code
+        pub bip39_passphrase: bool,
+        /// Don't encrypt the key
+        pub unsafe_dont_encrypt: bool,
+    }
+
+    impl Args for KeyDerive {
+        fn parse(matches: &ArgMatches) -> Self {
+            let scheme = SCHEME.parse(matches);
+            let alias = ALIAS_OPT.parse(matches);
+            let derivation_path = DERIVATION_PATH.parse(matches);
+            let shielded = SHIELDED.parse(matches);
+            let bip39_passphrase = BIP39_PASSPHRASE.parse(matches);
+            let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
+            Self {
+                scheme,
+                alias,
+                derivation_path,
+                shielded,
+                bip39_passphrase,
+                unsafe_dont_encrypt,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(DERIVATION_PATH.def().about(
+                "The HD path of the key, e.g. m/44'/877'/0'/0'/1'. Ed25519 \
+                 keys only support hardened indices. For a shielded spending \
+                 key, this is a ZIP-32 path, e.g. m/32'/877'/1'.",
+            ))
+            .add_args::<KeyRestore>()
+        }
+    }
+
+    /// Wallet Ledger key arguments
+    #[derive(Clone, Debug)]
+    pub struct KeyAddLedger {
+        /// Scheme type
+        pub scheme: SchemeType,
+        /// Key alias
+        pub alias: String,
+        /// The HD path of the key on the device
+        pub derivation_path: Option<DerivationPath>,
+    }
+
+    impl Args for KeyAddLedger {
+        fn parse(matches: &ArgMatches) -> Self {
+            let scheme = SCHEME.parse(matches);
+            let alias = ALIAS.parse(matches);
+            let derivation_path = DERIVATION_PATH_OPT.parse(matches);
+            Self {
+                scheme,
+                alias,
+                derivation_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(SCHEME.def().about(
+                "The type of the key on the device. Argument must be either \
+                 ed25519 or secp256k1. If none provided, the default key \
+                 scheme is ed25519.",
+            ))
+            .arg(ALIAS.def().about("The key and address alias."))
+            .arg(DERIVATION_PATH_OPT.def().about(
+                "The HD path of the key on the device. If none provided, the \
+                 default BIP44 path of the key scheme is used.",
+            ))
+        }
+    }
+
+    /// Wallet key restoration from a mnemonic code arguments
+    #[derive(Clone, Debug)]
+    pub struct KeyRestore {
+        /// Scheme type
+        pub scheme: SchemeType,
+        /// Key alias
+        pub alias: Option<String>,
+        /// Restore a shielded spending key instead of a keypair
+        pub shielded: bool,
+        /// Prompt for a BIP39 passphrase that extends the mnemonic code
+        pub bip39_passphrase: bool,
+        /// Don't encrypt the key
+        pub unsafe_dont_encrypt: bool,
+    }
+
+    impl Args for KeyRestore {
+        fn parse(matches: &ArgMatches) -> Self {
+            let scheme = SCHEME.parse(matches);
+            let alias = ALIAS_OPT.parse(matches);
+            let shielded = SHIELDED.parse(matches);
+            let bip39_passphrase = BIP39_PASSPHRASE.parse(matches);
+            let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
+            Self {
+                scheme,
+                alias,
+                shielded,
+                bip39_passphrase,
+                unsafe_dont_encrypt,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(SCHEME.def().about(
+                "The type of key that should be derived. Argument must be \
+                 either ed25519 or secp256k1. If none provided, the default \
+                 key scheme is ed25519.",
+            ))
+            .arg(ALIAS_OPT.def().about(
+                "The key and address alias. If none provided, the alias will \
+                 be the public key hash. Required for a shielded spending key.",
+            ))
+            .arg(
+                SHIELDED
+                    .def()
+                    .about(
+                        "Derive a shielded spending key with ZIP-32 instead \
+                         of a keypair.",
+                    )
+                    .conflicts_with(SCHEME.name)
+                    .requires(ALIAS_OPT.name),
+            )
+            .arg(BIP39_PASSPHRASE.def().about(
+                "Prompt for the BIP39 passphrase that extends the mnemonic \
+                 code, if any.",
+            ))
+            .arg(UNSAFE_DONT_ENCRYPT.def().about(
+                "UNSAFE: Do not encrypt the key. Do not use this for keys \
+                 used in a live network.",
+            ))
+        }
+    }
+
     /// Wallet key lookup arguments
     #[derive(Clone, Debug)]
     pub struct KeyFind {
@@ -3358,6 +4966,127 @@ pub mod args {
         }
     }
 
+    /// Wallet address book add arguments
+    #[derive(Clone, Debug)]
+    pub struct AddressBookAdd {
+        pub alias: String,
+        pub address: BookAddress,
+        pub label: Option<String>,
+        pub notes: Option<String>,
+        pub tags: Vec<String>,
+    }
+
+    impl Args for AddressBookAdd {
+        fn parse(matches: &ArgMatches) -> Self {
+            let alias = ALIAS.parse(matches);
+            let address = BOOK_ADDRESS.parse(matches);
+            let label = LABEL_OPT.parse(matches);
+            let notes = NOTES_OPT.parse(matches);
+            let tags = TAGS.parse(matches);
+            Self {
+                alias,
+                address,
+                label,
+                notes,
+                tags,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(
+                ALIAS
+                    .def()
+                    .about("An alias to be associated with the address."),
+            )
+            .arg(BOOK_ADDRESS.def().about(
+                "The bech32m encoded address or payment address string.",
+            ))
+            .arg(LABEL_OPT.def().about(
+                "A human readable name of the address, unique in the wallet, \
+                 which can be used in place of the address in commands.",
+            ))
+            .arg(NOTES_OPT.def().about("Free-form notes about the address."))
+            .arg(TAGS.def().about(
+                "Tags of the address, e.g. exchange, validator or team. Can \
+                 be given multiple times.",
+            ))
+        }
+    }
+
+    /// Wallet address book list arguments
+    #[derive(Clone, Debug)]
+    pub struct AddressBookList {
+        pub tag: Option<String>,
+    }
+
+    impl Args for AddressBookList {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tag = TAG_OPT.parse(matches);
+            Self { tag }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(
+                TAG_OPT
+                    .def()
+                    .about("Only list the entries with the given tag."),
+            )
+        }
+    }
+
+    /// Wallet address book import arguments
+    #[derive(Clone, Debug)]
+    pub struct AddressBookImport {
+        pub file: PathBuf,
+        pub format: Option<AddressBookFormat>,
+    }
+
+    impl Args for AddressBookImport {
+        fn parse(matches: &ArgMatches) -> Self {
+            let file = FILE.parse(matches);
+            let format = ADDRESS_BOOK_FORMAT_OPT.parse(matches);
+            Self { file, format }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(FILE.def().about("The path of the file to import."))
+                .arg(ADDRESS_BOOK_FORMAT_OPT.def().about(
+                    "The format of the file, csv or json. Defaults to the \
+                     file's extension.",
+                ))
+        }
+    }
+
+    /// Wallet address book export arguments
+    #[derive(Clone, Debug)]
+    pub struct AddressBookExport {
+        pub file: PathBuf,
+        pub format: Option<AddressBookFormat>,
+        pub tag: Option<String>,
+    }
+
+    impl Args for AddressBookExport {
+        fn parse(matches: &ArgMatches) -> Self {
+            let file = FILE.parse(matches);
+            let format = ADDRESS_BOOK_FORMAT_OPT.parse(matches);
+            let tag = TAG_OPT.parse(matches);
+            Self { file, format, tag }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(FILE.def().about("The path of the file to export to."))
+                .arg(ADDRESS_BOOK_FORMAT_OPT.def().about(
+                    "The format of the file, csv or json. Defaults to the \
+                     file's extension.",
+                ))
+                .arg(
+                    TAG_OPT
+                        .def()
+                        .about("Only export the entries with the given tag."),
+                )
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct JoinNetwork {
         pub chain_id: ChainId,
@@ -3600,6 +5329,11 @@ pub fn namada_wallet_cli() -> Result<(cmds::NamadaWallet, Context)> {
     cmds::NamadaWallet::parse_or_print_help(app)
 }
 
+pub fn namada_signer_cli() -> Result<(cmds::NamadaSigner, Context)> {
+    let app = namada_signer_app();
+    cmds::NamadaSigner::parse_or_print_help(app)
+}
+
 fn namada_app() -> App {
     let app = App::new(APP_NAME)
         .version(namada_version())
@@ -3631,3 +5365,11 @@ fn namada_wallet_app() -> App {
         .setting(AppSettings::SubcommandRequiredElseHelp);
     cmds::NamadaWallet::add_sub(args::Global::def(app))
 }
+
+fn namada_signer_app() -> App {
+    let app = App::new(APP_NAME)
+        .version(namada_version())
+        .about("Namada remote signer command line interface.")
+        .setting(AppSettings::SubcommandRequiredElseHelp);
+    cmds::NamadaSigner::add_sub(args::Global::def(app))
+}
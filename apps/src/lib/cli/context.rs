@@ -16,6 +16,7 @@ use crate::client::tx::ShieldedContext;
 use crate::config::genesis::genesis_config;
 use crate::config::global::GlobalConfig;
 use crate::config::{self, Config};
+use crate::wallet::address_book::BookAddress;
 use crate::wallet::Wallet;
 use crate::wasm_loader;
 
@@ -24,16 +25,17 @@ const ENV_VAR_CHAIN_ID: &str = "NAMADA_CHAIN_ID";
 /// Env. var to set wasm directory
 pub const ENV_VAR_WASM_DIR: &str = "NAMADA_WASM_DIR";
 
-/// A raw address (bech32m encoding) or an alias of an address that may be found
-/// in the wallet
+/// A raw address (bech32m encoding), an alias of an address that may be found
+/// in the wallet or a label of an address in the address book, e.g. of a
+/// validator
 pub type WalletAddress = FromContext<Address>;
 
 /// A raw extended spending key (bech32m encoding) or an alias of an extended
 /// spending key in the wallet
 pub type WalletSpendingKey = FromContext<ExtendedSpendingKey>;
 
-/// A raw payment address (bech32m encoding) or an alias of a payment address
-/// in the wallet
+/// A raw payment address (bech32m encoding), an alias of a payment address
+/// in the wallet or a label of a payment address in the address book
 pub type WalletPaymentAddr = FromContext<PaymentAddress>;
 
 /// A raw full viewing key (bech32m encoding) or an alias of a full viewing key
@@ -44,8 +46,8 @@ pub type WalletViewingKey = FromContext<ExtendedViewingKey>;
 /// of either in the wallet
 pub type WalletTransferSource = FromContext<TransferSource>;
 
-/// A raw address or a raw payment address (bech32m encoding) or an alias of
-/// either in the wallet
+/// A raw address or a raw payment address (bech32m encoding), an alias of
+/// either in the wallet or a label of either in the address book
 pub type WalletTransferTarget = FromContext<TransferTarget>;
 
 /// A raw keypair (hex encoding), an alias, a public key or a public key hash of
@@ -56,8 +58,9 @@ pub type WalletKeypair = FromContext<common::SecretKey>;
 /// alias of an public key that may be found in the wallet
 pub type WalletPublicKey = FromContext<common::PublicKey>;
 
-/// A raw address or a raw full viewing key (bech32m encoding) or an alias of
-/// either in the wallet
+/// A raw address, a raw full viewing key or a raw payment address (bech32m
+/// encoding), an alias of either in the wallet or a label of an address in the
+/// address book
 pub type WalletBalanceOwner = FromContext<BalanceOwner>;
 
 /// Command execution context
@@ -322,17 +325,38 @@ impl ArgFromContext for Address {
     ) -> Result<Self, String> {
         let raw = raw.as_ref();
         // An address can be either raw (bech32m encoding)
-        FromStr::from_str(raw)
-            // Or it can be an alias that may be found in the wallet
-            .or_else(|_| {
-                ctx.wallet
-                    .find_address(raw)
-                    .cloned()
-                    .ok_or_else(|| format!("Unknown address {}", raw))
-            })
+        FromStr::from_str(raw).or_else(|_| {
+            // Or it can be an alias or a label of the address book
+            match find_book_address(ctx, raw) {
+                Some(BookAddress::Transparent(address)) => Ok(address),
+                Some(BookAddress::Shielded(_)) => Err(format!(
+                    "{} is a payment address, expected a transparent address",
+                    raw
+                )),
+                None => Err(format!("Unknown address {}", raw)),
+            }
+        })
     }
 }
 
+/// Find an address by its alias in the wallet or its label in the address
+/// book. Aliases and labels of transparent addresses, including validators,
+/// and of payment addresses are resolved the same way for any address
+/// argument, which then checks the kind of the address.
+fn find_book_address(ctx: &Context, raw: &str) -> Option<BookAddress> {
+    ctx.wallet
+        .find_address(raw)
+        .cloned()
+        .map(BookAddress::Transparent)
+        .or_else(|| {
+            ctx.wallet
+                .find_payment_addr(raw)
+                .cloned()
+                .map(BookAddress::Shielded)
+        })
+        .or_else(|| ctx.wallet.find_book_address_by_label(raw))
+}
+
 impl ArgFromMutContext for common::SecretKey {
     fn arg_from_mut_ctx(
         ctx: &mut Context,
@@ -415,11 +439,15 @@ impl ArgFromContext for PaymentAddress {
         let raw = raw.as_ref();
         // Either the string is a payment address
         FromStr::from_str(raw).or_else(|_parse_err| {
-            // Or it is a stored alias of one
-            ctx.wallet
-                .find_payment_addr(raw)
-                .cloned()
-                .ok_or_else(|| format!("Unknown payment address {}", raw))
+            // Or it is an alias or a label of the address book
+            match find_book_address(ctx, raw) {
+                Some(BookAddress::Shielded(payment_addr)) => Ok(payment_addr),
+                Some(BookAddress::Transparent(_)) => Err(format!(
+                    "{} is a transparent address, expected a payment address",
+                    raw
+                )),
+                None => Err(format!("Unknown payment address {}", raw)),
+            }
         })
     }
 }
@@ -0,0 +1,777 @@
+//! Signing with a Ledger hardware wallet running the Namada app. The client
+//! sends APDU commands to the app over a [`Transport`], which is USB HID for a
+//! device (with the `ledger-hid` feature) or an [`Emulator`] of the app for
+//! tests. The secret keys never leave the device, the wallet only stores
+//! their public keys and HD paths.
+//!
+//! The app displays a transaction from its stable encoding (see
+//! [`Tx::signing_bytes`]), which it hashes and signs once the user approves
+//! it, like a secret key does in memory.
+//!
+//! The fields of a wrapper transaction are decoded for review, but the data of
+//! an inner transaction is only displayed by its hash. The type of the data
+//! depends on the transaction's code, which the app only knows by its hash, so
+//! it can't decode the data. The user has to check the code hash against the
+//! published checksums of the transaction wasms and trust the client that
+//! built the transaction for its data.
+
+use std::sync::{Arc, Mutex};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use namada::proto::{Tx, TxCode};
+use namada::types::hash::Hash;
+use namada::types::key::*;
+use namada::types::transaction::{hash_tx, TxType};
+use thiserror::Error;
+
+use super::signer::{self, TxSigner};
+use crate::wallet::{DerivationPath, HardwareKey};
+
+/// The class byte of the Namada app's APDU commands
+const CLA: u8 = 0x57;
+/// Get the public key at an HD path
+const INS_GET_PUBLIC_KEY: u8 = 0x01;
+/// Sign a transaction with the key at an HD path
+const INS_SIGN: u8 = 0x02;
+/// P1 of a public key request that is shown on the device for confirmation
+const P1_CONFIRM: u8 = 0x01;
+/// P1 of a public key request that isn't shown on the device
+const P1_NO_CONFIRM: u8 = 0x00;
+/// P1 of the first chunk of a sign command, which contains the HD path
+const P1_INIT: u8 = 0x00;
+/// P1 of a following chunk of a sign command
+const P1_ADD: u8 = 0x01;
+/// P1 of the last chunk of a sign command
+const P1_LAST: u8 = 0x02;
+/// P2 of an ed25519 key
+const P2_ED25519: u8 = 0x00;
+/// P2 of a secp256k1 key
+const P2_SECP256K1: u8 = 0x01;
+/// Status word of a successful command
+const SW_OK: u16 = 0x9000;
+/// Status word of a command rejected by the user
+const SW_REJECTED: u16 = 0x6986;
+/// Status word of a command with invalid data
+const SW_INVALID_DATA: u16 = 0x6984;
+/// Status word of an unsupported command
+const SW_INS_NOT_SUPPORTED: u16 = 0x6d00;
+/// The maximum size of the data of an APDU command
+const MAX_CHUNK_SIZE: usize = 250;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Unable to connect to a Ledger device: {0}")]
+    Connection(String),
+    #[error("Ledger device transport error: {0}")]
+    Transport(String),
+    #[error("The Ledger device failed with status {0:#06x}")]
+    Status(u16),
+    #[error("The transaction was rejected on the Ledger device")]
+    Rejected,
+    #[error("Invalid response from the Ledger device: {0}")]
+    InvalidResponse(String),
+    #[error("Unable to display the transaction: {0}")]
+    InvalidTx(String),
+}
+
+/// Result of the Ledger device functions
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An APDU command of the Namada app
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApduCommand {
+    /// Instruction
+    pub ins: u8,
+    /// First parameter
+    pub p1: u8,
+    /// Second parameter
+    pub p2: u8,
+    /// Data of at most [`MAX_CHUNK_SIZE`] bytes
+    pub data: Vec<u8>,
+}
+
+impl ApduCommand {
+    /// Serialize the command with the class byte of the Namada app
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = vec![CLA, self.ins, self.p1, self.p2];
+        bytes.push(self.data.len() as u8);
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+
+    /// Deserialize a command of the Namada app
+    pub fn deserialize(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [CLA, ins, p1, p2, len, data @ ..]
+                if data.len() == *len as usize =>
+            {
+                Some(Self {
+                    ins: *ins,
+                    p1: *p1,
+                    p2: *p2,
+                    data: data.to_vec(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A transport of APDU commands to a Ledger device
+pub trait Transport {
+    /// Send a serialized APDU command and receive the response data followed
+    /// by the 2 bytes status word
+    fn exchange(&self, command: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// A Ledger device running the Namada app
+pub struct LedgerDevice {
+    transport: Box<dyn Transport>,
+}
+
+impl LedgerDevice {
+    /// Use the device connected with the given transport
+    pub fn new(transport: Box<dyn Transport>) -> Self {
+        Self { transport }
+    }
+
+    /// Get the public key of the given scheme at the HD path. If `confirm`,
+    /// the device shows the implicit address of the key for confirmation.
+    pub fn get_public_key(
+        &self,
+        path: &DerivationPath,
+        scheme: SchemeType,
+        confirm: bool,
+    ) -> Result<common::PublicKey> {
+        let response = self.send(ApduCommand {
+            ins: INS_GET_PUBLIC_KEY,
+            p1: if confirm { P1_CONFIRM } else { P1_NO_CONFIRM },
+            p2: scheme_p2(scheme),
+            data: serialize_path(path),
+        })?;
+        let invalid = |err: std::io::Error| {
+            Error::InvalidResponse(format!("Invalid public key: {}", err))
+        };
+        match scheme {
+            SchemeType::Secp256k1 => {
+                secp256k1::PublicKey::try_from_slice(&response)
+                    .map(common::PublicKey::Secp256k1)
+                    .map_err(invalid)
+            }
+            SchemeType::Ed25519 | SchemeType::Common => {
+                ed25519::PublicKey::try_from_slice(&response)
+                    .map(common::PublicKey::Ed25519)
+                    .map_err(invalid)
+            }
+        }
+    }
+
+    /// Sign the hash of a transaction from its stable encoding with the key
+    /// of the given scheme at the HD path, once the user approves it on the
+    /// device
+    pub fn sign(
+        &self,
+        path: &DerivationPath,
+        scheme: SchemeType,
+        signing_bytes: &[u8],
+    ) -> Result<common::Signature> {
+        let p2 = scheme_p2(scheme);
+        self.send(ApduCommand {
+            ins: INS_SIGN,
+            p1: P1_INIT,
+            p2,
+            data: serialize_path(path),
+        })?;
+        let chunks: Vec<&[u8]> = signing_bytes.chunks(MAX_CHUNK_SIZE).collect();
+        let mut response = vec![];
+        for (ix, chunk) in chunks.iter().enumerate() {
+            let p1 = if ix + 1 == chunks.len() {
+                P1_LAST
+            } else {
+                P1_ADD
+            };
+            response = self.send(ApduCommand {
+                ins: INS_SIGN,
+                p1,
+                p2,
+                data: chunk.to_vec(),
+            })?;
+        }
+        let invalid = |err: std::io::Error| {
+            Error::InvalidResponse(format!("Invalid signature: {}", err))
+        };
+        match scheme {
+            SchemeType::Secp256k1 => {
+                secp256k1::Signature::try_from_slice(&response)
+                    .map(common::Signature::Secp256k1)
+                    .map_err(invalid)
+            }
+            SchemeType::Ed25519 | SchemeType::Common => {
+                ed25519::Signature::try_from_slice(&response)
+                    .map(common::Signature::Ed25519)
+                    .map_err(invalid)
+            }
+        }
+    }
+
+    /// Send a command and check the status word of its response
+    fn send(&self, command: ApduCommand) -> Result<Vec<u8>> {
+        let mut response = self.transport.exchange(&command.serialize())?;
+        if response.len() < 2 {
+            return Err(Error::InvalidResponse(
+                "Missing status word".to_owned(),
+            ));
+        }
+        let status = response.split_off(response.len() - 2);
+        match u16::from_be_bytes([status[0], status[1]]) {
+            SW_OK => Ok(response),
+            SW_REJECTED => Err(Error::Rejected),
+            status => Err(Error::Status(status)),
+        }
+    }
+}
+
+/// A signer with a key kept on a Ledger device
+pub struct LedgerSigner {
+    device: LedgerDevice,
+    key: HardwareKey,
+}
+
+impl LedgerSigner {
+    /// Sign with the given key of the device
+    pub fn new(device: LedgerDevice, key: HardwareKey) -> Self {
+        Self { device, key }
+    }
+}
+
+impl TxSigner for LedgerSigner {
+    fn public_key(&self) -> common::PublicKey {
+        self.key.public_key.clone()
+    }
+
+    fn sign_tx_bytes(
+        &self,
+        signing_bytes: &[u8],
+    ) -> signer::Result<common::Signature> {
+        println!("Please review and approve the transaction on your Ledger:");
+        let fields = tx_display_fields(signing_bytes)?;
+        for (field, value) in &fields {
+            println!("  {}: {}", field, value);
+        }
+        if fields.iter().any(|(field, _)| field == "Data hash") {
+            println!(
+                "The Ledger can't decode the data of this transaction and \
+                 only displays its hash."
+            );
+        }
+        let pk = &self.key.public_key;
+        let sig = self.device.sign(
+            &self.key.derivation_path,
+            pk_scheme(pk),
+            signing_bytes,
+        )?;
+        // The device may be holding the keys of another seed
+        common::SigScheme::verify_signature_raw(
+            pk,
+            &hash_tx(signing_bytes).0,
+            &sig,
+        )
+        .map_err(|err| {
+            Error::InvalidResponse(format!(
+                "The signature doesn't match the public key {}: {}",
+                pk, err
+            ))
+        })?;
+        Ok(sig)
+    }
+}
+
+/// Connect to the first Ledger device found over USB HID
+#[cfg(feature = "ledger-hid")]
+pub fn connect() -> Result<LedgerDevice> {
+    Ok(LedgerDevice::new(Box::new(hid::HidTransport::connect()?)))
+}
+
+/// Connect to the first Ledger device found over USB HID
+#[cfg(not(feature = "ledger-hid"))]
+pub fn connect() -> Result<LedgerDevice> {
+    Err(Error::Connection(
+        "the client was built without the `ledger-hid` feature".to_owned(),
+    ))
+}
+
+/// Get the fields of a transaction that the device displays for review from
+/// its stable encoding. The fields of a wrapper transaction show its fee. The
+/// data of any other transaction is not decoded and only its hash is shown.
+pub fn tx_display_fields(
+    signing_bytes: &[u8],
+) -> Result<Vec<(String, String)>> {
+    let tx = Tx::try_from(signing_bytes)
+        .map_err(|err| Error::InvalidTx(err.to_string()))?;
+    // The stable encoding has the code hash in place of the code
+    let code_hash = match &tx.code {
+        TxCode::Wasm(code_hash) => Hash::try_from(&code_hash[..])
+            .map_err(|err| Error::InvalidTx(err.to_string()))?,
+        TxCode::Hash(code_hash) => code_hash.clone(),
+        TxCode::Batch(_) => {
+            return Err(Error::InvalidTx(
+                "the stable encoding can't contain batch calls".to_owned(),
+            ));
+        }
+    };
+    let mut fields = vec![
+        ("Code hash".to_owned(), code_hash.to_string()),
+        ("Timestamp".to_owned(), tx.timestamp.to_rfc3339()),
+    ];
+    let data = tx.data.unwrap_or_default();
+    // A wrapper tx has no code
+    let wrapper = if code_hash == hash_tx(&[]) {
+        match TxType::try_from_slice(&data) {
+            Ok(TxType::Wrapper(wrapper)) => Some(wrapper),
+            _ => None,
+        }
+    } else {
+        None
+    };
+    match wrapper {
+        Some(wrapper) => fields.extend([
+            ("Type".to_owned(), "Wrapper".to_owned()),
+            ("Fee".to_owned(), wrapper.fee.amount.to_string()),
+            ("Fee token".to_owned(), wrapper.fee.token.to_string()),
+            (
+                "Gas limit".to_owned(),
+                u64::from(&wrapper.gas_limit).to_string(),
+            ),
+            ("Epoch".to_owned(), wrapper.epoch.to_string()),
+            ("Inner tx hash".to_owned(), wrapper.tx_hash.to_string()),
+        ]),
+        None => {
+            fields.push(("Data hash".to_owned(), hash_tx(&data).to_string()))
+        }
+    }
+    Ok(fields)
+}
+
+/// Get the scheme of a public key
+fn pk_scheme(pk: &common::PublicKey) -> SchemeType {
+    match pk {
+        common::PublicKey::Ed25519(_) => SchemeType::Ed25519,
+        common::PublicKey::Secp256k1(_) => SchemeType::Secp256k1,
+    }
+}
+
+/// Get the P2 parameter of a key scheme. The `Common` scheme is ed25519.
+fn scheme_p2(scheme: SchemeType) -> u8 {
+    match scheme {
+        SchemeType::Secp256k1 => P2_SECP256K1,
+        SchemeType::Ed25519 | SchemeType::Common => P2_ED25519,
+    }
+}
+
+/// Get the key scheme of a P2 parameter
+fn p2_scheme(p2: u8) -> Option<SchemeType> {
+    match p2 {
+        P2_ED25519 => Some(SchemeType::Ed25519),
+        P2_SECP256K1 => Some(SchemeType::Secp256k1),
+        _ => None,
+    }
+}
+
+/// Serialize an HD path as its number of indices followed by the indices as
+/// big-endian u32
+fn serialize_path(path: &DerivationPath) -> Vec<u8> {
+    let indices = path.indices();
+    let mut bytes = vec![indices.len() as u8];
+    for index in indices {
+        bytes.extend_from_slice(&index.to_be_bytes());
+    }
+    bytes
+}
+
+/// Deserialize an HD path serialized with [`serialize_path`]
+fn deserialize_path(bytes: &[u8]) -> Option<DerivationPath> {
+    let (len, indices) = bytes.split_first()?;
+    if indices.len() != *len as usize * 4 {
+        return None;
+    }
+    let indices = indices
+        .chunks(4)
+        .map(|index| u32::from_be_bytes(index.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    Some(indices.into())
+}
+
+/// The framing of APDU commands and responses in the packets of the USB HID
+/// transport. Each packet starts with the channel, the APDU tag and its
+/// sequence number, and the first packet also contains the length of the
+/// APDU.
+mod framing {
+    use super::{Error, Result};
+
+    /// The size of a HID packet
+    pub const PACKET_SIZE: usize = 64;
+    /// The channel of the packets
+    const CHANNEL: u16 = 0x0101;
+    /// The tag of APDU packets
+    const TAG_APDU: u8 = 0x05;
+    /// The size of a packet's header
+    const HEADER_SIZE: usize = 5;
+
+    /// Split an APDU into zero-padded packets
+    pub fn to_packets(apdu: &[u8]) -> Vec<[u8; PACKET_SIZE]> {
+        let mut payload = (apdu.len() as u16).to_be_bytes().to_vec();
+        payload.extend_from_slice(apdu);
+        payload
+            .chunks(PACKET_SIZE - HEADER_SIZE)
+            .enumerate()
+            .map(|(seq, chunk)| {
+                let mut packet = [0; PACKET_SIZE];
+                packet[..2].copy_from_slice(&CHANNEL.to_be_bytes());
+                packet[2] = TAG_APDU;
+                packet[3..5].copy_from_slice(&(seq as u16).to_be_bytes());
+                packet[HEADER_SIZE..HEADER_SIZE + chunk.len()]
+                    .copy_from_slice(chunk);
+                packet
+            })
+            .collect()
+    }
+
+    /// Reassemble an APDU from its packets, which are read from the given
+    /// function
+    pub fn from_packets(
+        mut read_packet: impl FnMut() -> Result<[u8; PACKET_SIZE]>,
+    ) -> Result<Vec<u8>> {
+        let mut payload = vec![];
+        let mut len = None;
+        for seq in 0.. {
+            let packet = read_packet()?;
+            if packet[..2] != CHANNEL.to_be_bytes()
+                || packet[2] != TAG_APDU
+                || packet[3..5] != (seq as u16).to_be_bytes()
+            {
+                return Err(Error::Transport(format!(
+                    "Unexpected packet header {:02x?}",
+                    &packet[..HEADER_SIZE]
+                )));
+            }
+            payload.extend_from_slice(&packet[HEADER_SIZE..]);
+            let apdu_len = *len.get_or_insert_with(|| {
+                u16::from_be_bytes([payload[0], payload[1]]) as usize
+            });
+            if payload.len() >= apdu_len + 2 {
+                payload.truncate(apdu_len + 2);
+                return Ok(payload.split_off(2));
+            }
+        }
+        unreachable!()
+    }
+}
+
+/// The USB HID transport of a Ledger device
+#[cfg(feature = "ledger-hid")]
+mod hid {
+    use super::framing::{self, PACKET_SIZE};
+    use super::{Error, Result, Transport};
+
+    /// The vendor ID of Ledger devices
+    const LEDGER_VENDOR_ID: u16 = 0x2c97;
+    /// The usage page of the HID interface of Ledger devices
+    const LEDGER_USAGE_PAGE: u16 = 0xffa0;
+
+    /// The USB HID transport of a Ledger device
+    pub struct HidTransport {
+        device: hidapi::HidDevice,
+    }
+
+    impl HidTransport {
+        /// Connect to the first Ledger device found
+        pub fn connect() -> Result<Self> {
+            let api = hidapi::HidApi::new()
+                .map_err(|err| Error::Connection(err.to_string()))?;
+            let info = api
+                .device_list()
+                .find(|info| {
+                    info.vendor_id() == LEDGER_VENDOR_ID
+                        && info.usage_page() == LEDGER_USAGE_PAGE
+                })
+                .ok_or_else(|| {
+                    Error::Connection(
+                        "No Ledger device found, please connect and unlock it \
+                         and open the Namada app"
+                            .to_owned(),
+                    )
+                })?;
+            let device = info
+                .open_device(&api)
+                .map_err(|err| Error::Connection(err.to_string()))?;
+            Ok(Self { device })
+        }
+    }
+
+    impl Transport for HidTransport {
+        fn exchange(&self, command: &[u8]) -> Result<Vec<u8>> {
+            for packet in framing::to_packets(command) {
+                // The first byte is the report ID
+                let mut report = vec![0];
+                report.extend_from_slice(&packet);
+                self.device
+                    .write(&report)
+                    .map_err(|err| Error::Transport(err.to_string()))?;
+            }
+            framing::from_packets(|| {
+                let mut packet = [0; PACKET_SIZE];
+                // Block until the user has reviewed the command
+                let read = self
+                    .device
+                    .read_timeout(&mut packet, -1)
+                    .map_err(|err| Error::Transport(err.to_string()))?;
+                if read == PACKET_SIZE {
+                    Ok(packet)
+                } else {
+                    Err(Error::Transport(format!(
+                        "Read a packet of {} bytes",
+                        read
+                    )))
+                }
+            })
+        }
+    }
+}
+
+/// A software emulator of the Namada app for tests, which derives its keys
+/// from a seed. It records the fields of the transactions that it displays
+/// and approves them, unless it's set to reject them.
+#[derive(Clone)]
+pub struct Emulator {
+    seed: Vec<u8>,
+    state: Arc<Mutex<EmulatorState>>,
+}
+
+#[derive(Default)]
+struct EmulatorState {
+    /// Reject the transactions instead of approving them
+    reject: bool,
+    /// The HD path and the key scheme of the transaction being signed
+    signing_path: Option<(DerivationPath, SchemeType)>,
+    /// The stable encoding of the transaction being signed
+    signing_bytes: Vec<u8>,
+    /// The fields of the displayed transactions
+    displayed: Vec<Vec<(String, String)>>,
+}
+
+impl Emulator {
+    /// Emulate a device whose keys are derived from the given seed
+    pub fn new(seed: Vec<u8>) -> Self {
+        Self {
+            seed,
+            state: Default::default(),
+        }
+    }
+
+    /// Set the emulated user to reject the transactions
+    pub fn reject_txs(&self, reject: bool) {
+        self.state.lock().unwrap().reject = reject;
+    }
+
+    /// Get the fields of the displayed transactions
+    pub fn displayed(&self) -> Vec<Vec<(String, String)>> {
+        self.state.lock().unwrap().displayed.clone()
+    }
+
+    /// Handle a command and return the response data or the status word of
+    /// the error
+    fn handle(
+        &self,
+        command: ApduCommand,
+    ) -> std::result::Result<Vec<u8>, u16> {
+        let scheme = p2_scheme(command.p2).ok_or(SW_INVALID_DATA)?;
+        let derive_sk = |path: &DerivationPath| {
+            path.derive_sk(scheme, &self.seed)
+                .map_err(|_| SW_INVALID_DATA)
+        };
+        let mut state = self.state.lock().unwrap();
+        match (command.ins, command.p1) {
+            (INS_GET_PUBLIC_KEY, P1_CONFIRM | P1_NO_CONFIRM) => {
+                let path =
+                    deserialize_path(&command.data).ok_or(SW_INVALID_DATA)?;
+                Ok(match derive_sk(&path)?.ref_to() {
+                    common::PublicKey::Ed25519(pk) => pk.try_to_vec(),
+                    common::PublicKey::Secp256k1(pk) => pk.try_to_vec(),
+                }
+                .unwrap())
+            }
+            (INS_SIGN, P1_INIT) => {
+                let path =
+                    deserialize_path(&command.data).ok_or(SW_INVALID_DATA)?;
+                state.signing_path = Some((path, scheme));
+                state.signing_bytes.clear();
+                Ok(vec![])
+            }
+            (INS_SIGN, P1_ADD | P1_LAST) => {
+                state.signing_bytes.extend(command.data);
+                if command.p1 == P1_ADD {
+                    return Ok(vec![]);
+                }
+                let (path, init_scheme) =
+                    state.signing_path.take().ok_or(SW_INVALID_DATA)?;
+                let signing_bytes = std::mem::take(&mut state.signing_bytes);
+                if init_scheme != scheme {
+                    return Err(SW_INVALID_DATA);
+                }
+                let fields = tx_display_fields(&signing_bytes)
+                    .map_err(|_| SW_INVALID_DATA)?;
+                state.displayed.push(fields);
+                if state.reject {
+                    return Err(SW_REJECTED);
+                }
+                let sk = derive_sk(&path)?;
+                let sig =
+                    common::SigScheme::sign(&sk, hash_tx(&signing_bytes).0);
+                Ok(match sig {
+                    common::Signature::Ed25519(sig) => sig.try_to_vec(),
+                    common::Signature::Secp256k1(sig) => sig.try_to_vec(),
+                }
+                .unwrap())
+            }
+            _ => Err(SW_INS_NOT_SUPPORTED),
+        }
+    }
+}
+
+impl Transport for Emulator {
+    fn exchange(&self, command: &[u8]) -> Result<Vec<u8>> {
+        // The commands go through the framing of the USB HID transport
+        let mut packets = framing::to_packets(command).into_iter();
+        let command = framing::from_packets(|| {
+            packets
+                .next()
+                .ok_or_else(|| Error::Transport("Missing packet".to_owned()))
+        })?;
+        let command = ApduCommand::deserialize(&command)
+            .ok_or_else(|| Error::Transport("Invalid command".to_owned()))?;
+        let (mut response, status) = match self.handle(command) {
+            Ok(response) => (response, SW_OK),
+            Err(status) => (vec![], status),
+        };
+        response.extend_from_slice(&status.to_be_bytes());
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::types::address::nam;
+    use namada::types::storage::Epoch;
+    use namada::types::token::Amount;
+    use namada::types::transaction::{Fee, WrapperTx};
+
+    use super::*;
+
+    /// Get an emulated device and a signer of its key of the given scheme at
+    /// the default HD path
+    fn emulated_signer(scheme: SchemeType) -> (Emulator, LedgerSigner) {
+        let emulator = Emulator::new(vec![7; 64]);
+        let device = LedgerDevice::new(Box::new(emulator.clone()));
+        let derivation_path = DerivationPath::default_for_scheme(scheme);
+        let public_key = device
+            .get_public_key(&derivation_path, scheme, true)
+            .unwrap();
+        let key = HardwareKey {
+            public_key,
+            derivation_path,
+        };
+        (emulator, LedgerSigner::new(device, key))
+    }
+
+    #[test]
+    fn test_get_public_key() {
+        for scheme in [SchemeType::Ed25519, SchemeType::Secp256k1] {
+            let (_emulator, signer) = emulated_signer(scheme);
+            let sk = DerivationPath::default_for_scheme(scheme)
+                .derive_sk(scheme, &[7; 64])
+                .unwrap();
+            assert_eq!(signer.public_key(), sk.ref_to());
+        }
+    }
+
+    #[test]
+    fn test_sign_tx() {
+        for scheme in [SchemeType::Ed25519, SchemeType::Secp256k1] {
+            let (emulator, signer) = emulated_signer(scheme);
+            // The tx is larger than a chunk
+            let tx = Tx::new(vec![1; 1000], Some(vec![2; 1000]));
+            let signed_tx = signer.sign_tx(tx.clone()).unwrap();
+
+            let signed = namada::proto::SignedTxData::try_from_slice(
+                &signed_tx.data.clone().unwrap(),
+            )
+            .unwrap();
+            signed_tx
+                .verify_sig(&signer.public_key(), &signed.sig)
+                .unwrap();
+
+            let displayed = emulator.displayed();
+            assert_eq!(displayed.len(), 1);
+            assert_eq!(
+                displayed[0][0],
+                ("Code hash".to_owned(), Hash(tx.code_hash()).to_string())
+            );
+            assert_eq!(
+                displayed[0][2],
+                ("Data hash".to_owned(), hash_tx(&[2; 1000]).to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_sign_wrapper_tx() {
+        let (emulator, signer) = emulated_signer(SchemeType::Ed25519);
+        let tx = Tx::new(vec![1; 10], None);
+        let wrapper = WrapperTx::new_with_pk(
+            Fee {
+                amount: Amount::from(10),
+                token: nam(),
+            },
+            signer.public_key(),
+            Epoch(1),
+            0.into(),
+            tx,
+            Default::default(),
+            #[cfg(not(feature = "mainnet"))]
+            None,
+        );
+        signer.sign_tx(wrapper.unsigned_tx()).unwrap();
+
+        let displayed = emulator.displayed();
+        assert!(
+            displayed[0].contains(&("Type".to_owned(), "Wrapper".to_owned()))
+        );
+        assert!(displayed[0]
+            .contains(&("Fee".to_owned(), Amount::from(10).to_string())));
+    }
+
+    #[test]
+    fn test_rejected_tx() {
+        let (emulator, signer) = emulated_signer(SchemeType::Ed25519);
+        emulator.reject_txs(true);
+        let tx = Tx::new(vec![1; 10], None);
+        assert!(matches!(
+            signer.sign_tx(tx),
+            Err(signer::Error::HardwareWallet(Error::Rejected))
+        ));
+        assert_eq!(emulator.displayed().len(), 1);
+    }
+
+    #[test]
+    fn test_hid_framing() {
+        for len in [0, 10, 57, 58, 59, 200, 300] {
+            let apdu: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let mut packets = framing::to_packets(&apdu).into_iter();
+            let reassembled =
+                framing::from_packets(|| Ok(packets.next().unwrap())).unwrap();
+            assert_eq!(reassembled, apdu);
+            assert!(packets.next().is_none());
+        }
+    }
+}
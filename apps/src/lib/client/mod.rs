@@ -1,4 +1,6 @@
+pub mod hardware_wallet;
 pub mod rpc;
+pub mod signer;
 pub mod signing;
 pub mod tendermint_rpc_types;
 pub mod tx;
@@ -23,6 +23,7 @@ use masp_primitives::transaction::components::Amount;
 use masp_primitives::zip32::ExtendedFullViewingKey;
 #[cfg(not(feature = "mainnet"))]
 use namada::core::ledger::testnet_pow;
+use namada::ibc::core::ics02_client::height::Height;
 use namada::ledger::events::Event;
 use namada::ledger::governance::parameters::GovParams;
 use namada::ledger::governance::storage as gov_storage;
@@ -36,8 +37,8 @@ use namada::ledger::storage::ConversionState;
 use namada::proto::{SignedTxData, Tx};
 use namada::types::address::{masp, tokens, Address};
 use namada::types::governance::{
-    OfflineProposal, OfflineVote, ProposalResult, ProposalVote, TallyResult,
-    VotePower,
+    DisbursementSchedule, OfflineProposal, OfflineVote, ProposalResult,
+    ProposalStatus, ProposalSummary, TallyResult, VotePower,
 };
 use namada::types::hash::Hash;
 use namada::types::key::*;
@@ -51,6 +52,7 @@ use namada::types::transaction::{
     WrapperTx,
 };
 use namada::types::{address, storage, token};
+use rust_decimal::Decimal;
 use tokio::time::{Duration, Instant};
 
 use crate::cli::{self, args, Context};
@@ -436,6 +438,208 @@ pub async fn query_raw_bytes(_ctx: Context, args: args::QueryRawBytes) {
     }
 }
 
+/// Query an IBC client state
+pub async fn query_ibc_client_state(args: args::QueryIbcClientState) {
+    let client = HttpClient::new(args.query.ledger_address).unwrap();
+    let response = unwrap_client_response(
+        RPC.ibc()
+            .decoded_client_state(&client, None, args.prove, &args.client_id)
+            .await,
+    );
+    match response.data {
+        Some(state) => println!("Client state: {:#?}", state),
+        None => println!("No client state found for {}", args.client_id),
+    }
+    print_proof(response.proof);
+}
+
+/// Query an IBC consensus state
+pub async fn query_ibc_consensus_state(args: args::QueryIbcConsensusState) {
+    let client = HttpClient::new(args.query.ledger_address).unwrap();
+    let client_height = Height::new(args.revision_number, args.revision_height);
+    let response = unwrap_client_response(
+        RPC.ibc()
+            .decoded_consensus_state(
+                &client,
+                None,
+                args.prove,
+                &args.client_id,
+                &client_height,
+            )
+            .await,
+    );
+    match response.data {
+        Some(state) => println!("Consensus state: {:#?}", state),
+        None => println!(
+            "No consensus state found for {} at {}",
+            args.client_id, client_height
+        ),
+    }
+    print_proof(response.proof);
+}
+
+/// Query an IBC connection end
+pub async fn query_ibc_connection(args: args::QueryIbcConnection) {
+    let client = HttpClient::new(args.query.ledger_address).unwrap();
+    let response = unwrap_client_response(
+        RPC.ibc()
+            .decoded_connection(&client, None, args.prove, &args.connection_id)
+            .await,
+    );
+    match response.data {
+        Some(connection) => println!("Connection: {:#?}", connection),
+        None => println!("No connection found for {}", args.connection_id),
+    }
+    print_proof(response.proof);
+}
+
+/// Query an IBC channel end
+pub async fn query_ibc_channel(args: args::QueryIbcChannel) {
+    let client = HttpClient::new(args.query.ledger_address).unwrap();
+    let response = unwrap_client_response(
+        RPC.ibc()
+            .decoded_channel(
+                &client,
+                None,
+                args.prove,
+                &args.port_id,
+                &args.channel_id,
+            )
+            .await,
+    );
+    match response.data {
+        Some(channel) => println!("Channel: {:#?}", channel),
+        None => println!(
+            "No channel found for {}/{}",
+            args.port_id, args.channel_id
+        ),
+    }
+    print_proof(response.proof);
+}
+
+/// Query the commitments of the pending packets sent on an IBC channel
+pub async fn query_ibc_packet_commitments(args: args::QueryIbcChannel) {
+    let client = HttpClient::new(args.query.ledger_address).unwrap();
+    let response = unwrap_client_response(
+        RPC.ibc()
+            .decoded_packet_commitments(
+                &client,
+                args.prove,
+                &args.port_id,
+                &args.channel_id,
+            )
+            .await,
+    );
+    if response.data.is_empty() {
+        println!(
+            "No pending packet found on {}/{}",
+            args.port_id, args.channel_id
+        );
+    }
+    for (sequence, commitment) in response.data {
+        println!(
+            "Sequence {}: commitment 0x{}",
+            sequence,
+            HEXLOWER.encode(&commitment)
+        );
+    }
+    print_proof(response.proof);
+}
+
+/// Query the acknowledgement commitments of the packets received on an IBC
+/// channel
+pub async fn query_ibc_packet_acks(args: args::QueryIbcChannel) {
+    let client = HttpClient::new(args.query.ledger_address).unwrap();
+    let response = unwrap_client_response(
+        RPC.ibc()
+            .decoded_packet_acks(
+                &client,
+                args.prove,
+                &args.port_id,
+                &args.channel_id,
+            )
+            .await,
+    );
+    if response.data.is_empty() {
+        println!(
+            "No acknowledgement found on {}/{}",
+            args.port_id, args.channel_id
+        );
+    }
+    for (sequence, ack) in response.data {
+        println!(
+            "Sequence {}: acknowledgement 0x{}",
+            sequence,
+            HEXLOWER.encode(&ack)
+        );
+    }
+    print_proof(response.proof);
+}
+
+/// Query the denom trace of an IBC token, or all of them
+pub async fn query_ibc_denoms(args: args::QueryIbcDenoms) {
+    let client = HttpClient::new(args.query.ledger_address).unwrap();
+    match args.token_hash {
+        Some(token_hash) => {
+            let response = unwrap_client_response(
+                RPC.ibc()
+                    .decoded_denom(&client, None, args.prove, &token_hash)
+                    .await,
+            );
+            match response.data {
+                Some(denom) => println!("{}: {}", token_hash, denom),
+                None => println!("No denom found for {}", token_hash),
+            }
+            print_proof(response.proof);
+        }
+        None => {
+            let response = unwrap_client_response(
+                RPC.ibc().denoms(&client, None, None, args.prove).await,
+            );
+            if response.data.is_empty() {
+                println!("No IBC denom found");
+            }
+            for PrefixValue { key, value } in response.data {
+                let token_hash =
+                    key.last().map(|seg| seg.raw()).unwrap_or_default();
+                match String::from_utf8(value) {
+                    Ok(denom) => println!("{}: {}", token_hash, denom),
+                    Err(_) => {
+                        eprintln!("Invalid denom for {}", token_hash)
+                    }
+                }
+            }
+            print_proof(response.proof);
+        }
+    }
+}
+
+/// Query the flow limit and the current flows of a token on an IBC channel
+pub async fn query_ibc_flow(args: args::QueryIbcFlow) {
+    let client = HttpClient::new(args.query.ledger_address).unwrap();
+    let (limit, flow) = unwrap_client_response(
+        RPC.ibc()
+            .flow(&client, &args.port_id, &args.channel_id, &args.denom)
+            .await,
+    );
+    let show = |amount: Option<token::Amount>| match amount {
+        Some(amount) => amount.to_string(),
+        None => "unlimited".to_string(),
+    };
+    println!(
+        "Flows of {} on {}/{} in epoch {}",
+        args.denom, args.port_id, args.channel_id, flow.epoch
+    );
+    println!("Inflow: {} (limit: {})", flow.inflow, show(limit.inflow));
+    println!("Outflow: {} (limit: {})", flow.outflow, show(limit.outflow));
+}
+
+fn print_proof(proof: Option<Proof>) {
+    if let Some(proof) = proof {
+        println!("Proof: {:#?}", proof);
+    }
+}
+
 /// Query token balance(s)
 pub async fn query_balance(mut ctx: Context, args: args::QueryBalance) {
     // Query the balances of shielded or transparent account types depending on
@@ -746,109 +950,72 @@ fn print_balances(
 
 /// Query Proposals
 pub async fn query_proposal(_ctx: Context, args: args::QueryProposal) {
-    async fn print_proposal(
-        client: &HttpClient,
-        id: u64,
-        current_epoch: Epoch,
-        details: bool,
-    ) -> Option<()> {
-        let author_key = gov_storage::get_author_key(id);
-        let start_epoch_key = gov_storage::get_voting_start_epoch_key(id);
-        let end_epoch_key = gov_storage::get_voting_end_epoch_key(id);
-
-        let author =
-            query_storage_value::<Address>(client, &author_key).await?;
-        let start_epoch =
-            query_storage_value::<Epoch>(client, &start_epoch_key).await?;
-        let end_epoch =
-            query_storage_value::<Epoch>(client, &end_epoch_key).await?;
-
-        if details {
-            let content_key = gov_storage::get_content_key(id);
-            let grace_epoch_key = gov_storage::get_grace_epoch_key(id);
-            let content = query_storage_value::<HashMap<String, String>>(
-                client,
-                &content_key,
-            )
-            .await?;
-            let grace_epoch =
-                query_storage_value::<Epoch>(client, &grace_epoch_key).await?;
-
-            println!("Proposal: {}", id);
-            println!("{:4}Author: {}", "", author);
-            println!("{:4}Content:", "");
-            for (key, value) in &content {
-                println!("{:8}{}: {}", "", key, value);
-            }
-            println!("{:4}Start Epoch: {}", "", start_epoch);
-            println!("{:4}End Epoch: {}", "", end_epoch);
-            println!("{:4}Grace Epoch: {}", "", grace_epoch);
-            if start_epoch > current_epoch {
-                println!("{:4}Status: pending", "");
-            } else if start_epoch <= current_epoch && current_epoch <= end_epoch
-            {
-                let votes = get_proposal_votes(client, start_epoch, id).await;
-                let partial_proposal_result =
-                    compute_tally(client, start_epoch, votes).await;
-                println!(
-                    "{:4}Yay votes: {}",
-                    "", partial_proposal_result.total_yay_power
-                );
-                println!(
-                    "{:4}Nay votes: {}",
-                    "", partial_proposal_result.total_nay_power
-                );
-                println!("{:4}Status: on-going", "");
-            } else {
-                let votes = get_proposal_votes(client, start_epoch, id).await;
-                let proposal_result =
-                    compute_tally(client, start_epoch, votes).await;
-                println!("{:4}Status: done", "");
-                println!("{:4}Result: {}", "", proposal_result);
-            }
-        } else {
-            println!("Proposal: {}", id);
-            println!("{:4}Author: {}", "", author);
-            println!("{:4}Start Epoch: {}", "", start_epoch);
-            println!("{:4}End Epoch: {}", "", end_epoch);
-            if start_epoch > current_epoch {
-                println!("{:4}Status: pending", "");
-            } else if start_epoch <= current_epoch && current_epoch <= end_epoch
-            {
-                println!("{:4}Status: on-going", "");
-            } else {
-                println!("{:4}Status: done", "");
-            }
-        }
-
-        Some(())
+    fn print_summary(summary: &ProposalSummary) {
+        println!("Proposal: {}", summary.id);
+        println!("{:4}Author: {}", "", summary.author);
+        println!("{:4}Start Epoch: {}", "", summary.voting_start_epoch);
+        println!("{:4}End Epoch: {}", "", summary.voting_end_epoch);
     }
 
     let client = HttpClient::new(args.query.ledger_address.clone()).unwrap();
-    let current_epoch = query_and_print_epoch(args.query.clone()).await;
+    query_and_print_epoch(args.query.clone()).await;
     match args.proposal_id {
         Some(id) => {
-            if print_proposal(&client, id, current_epoch, true)
-                .await
-                .is_none()
-            {
-                eprintln!("No valid proposal was found with id {}", id)
+            let proposal =
+                unwrap_client_response(RPC.gov().proposal(&client, &id).await);
+            let proposal = match proposal {
+                Some(proposal) => proposal,
+                None => {
+                    eprintln!("No valid proposal was found with id {}", id);
+                    return;
+                }
+            };
+            print_summary(&proposal.summary);
+            println!("{:4}Grace Epoch: {}", "", proposal.summary.grace_epoch);
+            println!("{:4}Content:", "");
+            for (key, value) in &proposal.content {
+                println!("{:8}{}: {}", "", key, value);
+            }
+            if let Some(code_hash) = proposal.code_hash {
+                println!("{:4}Code hash: {}", "", code_hash);
+            }
+            println!("{:4}Status: {}", "", proposal.summary.status);
+            if proposal.summary.status != ProposalStatus::Pending {
+                let tally =
+                    unwrap_client_response(RPC.gov().tally(&client, &id).await);
+                if let Some(tally) = tally {
+                    if proposal.summary.status == ProposalStatus::Voting {
+                        println!(
+                            "{:4}Yay votes: {}",
+                            "", tally.total_yay_power
+                        );
+                        println!(
+                            "{:4}Nay votes: {}",
+                            "", tally.total_nay_power
+                        );
+                    } else {
+                        println!("{:4}Result: {}", "", tally);
+                    }
+                }
             }
         }
         None => {
-            let last_proposal_id_key = gov_storage::get_counter_key();
-            let last_proposal_id =
-                query_storage_value::<u64>(&client, &last_proposal_id_key)
-                    .await
-                    .unwrap();
-
-            for id in 0..last_proposal_id {
-                if print_proposal(&client, id, current_epoch, false)
-                    .await
-                    .is_none()
-                {
-                    eprintln!("No valid proposal was found with id {}", id)
-                };
+            let mut from_id = 0;
+            loop {
+                // The page size is capped by the ledger
+                let page = unwrap_client_response(
+                    RPC.gov()
+                        .proposals(&client, &args.status, &from_id, &u64::MAX)
+                        .await,
+                );
+                for summary in &page.proposals {
+                    print_summary(summary);
+                    println!("{:4}Status: {}", "", summary.status);
+                }
+                match page.next_id {
+                    Some(next_id) => from_id = next_id,
+                    None => break,
+                }
             }
         }
     }
@@ -1164,32 +1331,93 @@ pub async fn get_token_balance(
     query_storage_value(client, &balance_key).await
 }
 
+/// Query the pending and executed slash fund disbursements
+pub async fn query_slash_fund_disbursements(
+    ctx: Context,
+    args: args::QuerySlashFundDisbursements,
+) {
+    let client = HttpClient::new(args.query.ledger_address.clone()).unwrap();
+    query_and_print_epoch(args.query.clone()).await;
+
+    let pending = unwrap_client_response(
+        RPC.gov()
+            .pending_disbursements(&client, &args.proposal_id)
+            .await,
+    );
+    if pending.is_empty() {
+        println!("No pending slash fund disbursements");
+    } else {
+        println!("Pending slash fund disbursements:");
+    }
+    for pending in pending {
+        let disbursement = pending.disbursement;
+        println!(
+            "{:2}Proposal: {}, disbursement: {}",
+            "", pending.proposal_id, pending.index
+        );
+        println!(
+            "{:4}Target: {}",
+            "",
+            lookup_alias(&ctx, &disbursement.target)
+        );
+        println!("{:4}Amount: {}", "", disbursement.amount);
+        match disbursement.schedule {
+            DisbursementSchedule::OneOff => {
+                println!("{:4}Schedule: one-off", "")
+            }
+            DisbursementSchedule::Streamed {
+                start_epoch,
+                end_epoch,
+            } => println!(
+                "{:4}Schedule: every epoch from {} to {}",
+                "", start_epoch, end_epoch
+            ),
+        }
+    }
+
+    let executed = unwrap_client_response(
+        RPC.gov()
+            .executed_disbursements(&client, &args.proposal_id)
+            .await,
+    );
+    if executed.is_empty() {
+        println!("No executed slash fund disbursements");
+    } else {
+        println!("Executed slash fund disbursements:");
+    }
+    for payment in executed {
+        println!(
+            "{:2}Proposal: {}, disbursement: {}, epoch: {}",
+            "", payment.proposal_id, payment.index, payment.epoch
+        );
+        println!("{:4}Target: {}", "", lookup_alias(&ctx, &payment.target));
+        println!("{:4}Amount: {}", "", payment.amount);
+    }
+}
+
 pub async fn query_proposal_result(
     _ctx: Context,
     args: args::QueryProposalResult,
 ) {
     let client = HttpClient::new(args.query.ledger_address.clone()).unwrap();
-    let current_epoch = query_and_print_epoch(args.query.clone()).await;
+    query_and_print_epoch(args.query.clone()).await;
 
     match args.proposal_id {
         Some(id) => {
-            let end_epoch_key = gov_storage::get_voting_end_epoch_key(id);
-            let end_epoch =
-                query_storage_value::<Epoch>(&client, &end_epoch_key).await;
-
-            match end_epoch {
-                Some(end_epoch) => {
-                    if current_epoch > end_epoch {
-                        let votes =
-                            get_proposal_votes(&client, end_epoch, id).await;
-                        let proposal_result =
-                            compute_tally(&client, end_epoch, votes).await;
-                        println!("Proposal: {}", id);
-                        println!("{:4}Result: {}", "", proposal_result);
-                    } else {
-                        eprintln!("Proposal is still in progress.");
-                        cli::safe_exit(1)
-                    }
+            let proposal =
+                unwrap_client_response(RPC.gov().proposal(&client, &id).await);
+            match proposal.map(|proposal| proposal.summary.status) {
+                Some(ProposalStatus::Pending | ProposalStatus::Voting) => {
+                    eprintln!("Proposal is still in progress.");
+                    cli::safe_exit(1)
+                }
+                Some(_) => {
+                    let proposal_result = unwrap_client_response(
+                        RPC.gov().tally(&client, &id).await,
+                    )
+                    .expect("The proposal should have a tally");
+                    println!("Proposal: {}", id);
+                    println!("{:4}Result: {}", "", proposal_result);
                 }
                 None => {
                     eprintln!("Error while retriving proposal.");
@@ -1280,6 +1508,21 @@ pub async fn query_proposal_result(
                                 .await;
 
                         println!("{:4}Result: {}", "", proposal_result);
+
+                        let onchain_result = unwrap_client_response(
+                            RPC.gov()
+                                .offline_proposal_tally(
+                                    &client,
+                                    &proposal.compute_hash(),
+                                )
+                                .await,
+                        );
+                        if let Some(onchain_result) = onchain_result {
+                            println!(
+                                "{:4}On-chain result: {}",
+                                "", onchain_result
+                            );
+                        }
                     }
                     None => {
                         eprintln!(
@@ -2190,74 +2433,6 @@ pub async fn query_result(_ctx: Context, args: args::QueryResult) {
     }
 }
 
-pub async fn get_proposal_votes(
-    client: &HttpClient,
-    epoch: Epoch,
-    proposal_id: u64,
-) -> Votes {
-    let validators = get_all_validators(client, epoch).await;
-
-    let vote_prefix_key =
-        gov_storage::get_proposal_vote_prefix_key(proposal_id);
-    let vote_iter =
-        query_storage_prefix::<ProposalVote>(client, &vote_prefix_key).await;
-
-    let mut yay_validators: HashMap<Address, VotePower> = HashMap::new();
-    let mut yay_delegators: HashMap<Address, HashMap<Address, VotePower>> =
-        HashMap::new();
-    let mut nay_delegators: HashMap<Address, HashMap<Address, VotePower>> =
-        HashMap::new();
-
-    if let Some(vote_iter) = vote_iter {
-        for (key, vote) in vote_iter {
-            let voter_address = gov_storage::get_voter_address(&key)
-                .expect("Vote key should contain the voting address.")
-                .clone();
-            if vote.is_yay() && validators.contains(&voter_address) {
-                let amount: VotePower =
-                    get_validator_stake(client, epoch, &voter_address)
-                        .await
-                        .unwrap_or_default()
-                        .into();
-                yay_validators.insert(voter_address, amount);
-            } else if !validators.contains(&voter_address) {
-                let validator_address =
-                    gov_storage::get_vote_delegation_address(&key)
-                        .expect(
-                            "Vote key should contain the delegation address.",
-                        )
-                        .clone();
-                let delegator_token_amount = get_bond_amount_at(
-                    client,
-                    &voter_address,
-                    &validator_address,
-                    epoch,
-                )
-                .await;
-                if let Some(amount) = delegator_token_amount {
-                    if vote.is_yay() {
-                        let entry =
-                            yay_delegators.entry(voter_address).or_default();
-                        entry
-                            .insert(validator_address, VotePower::from(amount));
-                    } else {
-                        let entry =
-                            nay_delegators.entry(voter_address).or_default();
-                        entry
-                            .insert(validator_address, VotePower::from(amount));
-                    }
-                }
-            }
-        }
-    }
-
-    Votes {
-        yay_validators,
-        yay_delegators,
-        nay_delegators,
-    }
-}
-
 pub async fn get_proposal_offline_votes(
     client: &HttpClient,
     proposal: OfflineProposal,
@@ -2434,6 +2609,7 @@ pub async fn get_proposal_offline_votes(
         yay_validators,
         yay_delegators,
         nay_delegators,
+        ..Votes::default()
     }
 }
 
@@ -2450,6 +2626,7 @@ pub async fn compute_tally(
         yay_validators,
         yay_delegators,
         nay_delegators,
+        ..
     } = votes;
 
     let mut total_yay_staked_tokens = VotePower::from(0_u64);
@@ -2481,6 +2658,7 @@ pub async fn compute_tally(
             total_voting_power: total_staked_tokens,
             total_yay_power: total_yay_staked_tokens,
             total_nay_power: 0,
+            total_veto_power: 0,
         }
     } else {
         ProposalResult {
@@ -2488,6 +2666,7 @@ pub async fn compute_tally(
             total_voting_power: total_staked_tokens,
             total_yay_power: total_yay_staked_tokens,
             total_nay_power: 0,
+            total_veto_power: 0,
         }
     }
 }
@@ -2586,6 +2765,39 @@ pub async fn get_governance_parameters(client: &HttpClient) -> GovParams {
         .await
         .expect("Parameter should be definied.");
 
+    let key = gov_storage::get_min_expedited_proposal_period_key();
+    let min_expedited_proposal_period =
+        query_storage_value::<u64>(client, &key)
+            .await
+            .expect("Parameter should be definied.");
+
+    let key = gov_storage::get_min_expedited_proposal_grace_epoch_key();
+    let min_expedited_proposal_grace_epochs =
+        query_storage_value::<u64>(client, &key)
+            .await
+            .expect("Parameter should be definied.");
+
+    let key = gov_storage::get_expedited_proposal_threshold_key();
+    let expedited_proposal_threshold =
+        query_storage_value::<Decimal>(client, &key)
+            .await
+            .expect("Parameter should be definied.");
+
+    let key = gov_storage::get_min_proposal_quorum_key();
+    let min_proposal_quorum = query_storage_value::<Decimal>(client, &key)
+        .await
+        .expect("Parameter should be definied.");
+
+    let key = gov_storage::get_proposal_veto_threshold_key();
+    let proposal_veto_threshold = query_storage_value::<Decimal>(client, &key)
+        .await
+        .expect("Parameter should be definied.");
+
+    let key = gov_storage::get_no_quorum_burn_rate_key();
+    let no_quorum_burn_rate = query_storage_value::<Decimal>(client, &key)
+        .await
+        .expect("Parameter should be definied.");
+
     GovParams {
         min_proposal_fund: u64::from(min_proposal_fund),
         max_proposal_code_size,
@@ -2593,6 +2805,12 @@ pub async fn get_governance_parameters(client: &HttpClient) -> GovParams {
         max_proposal_period,
         max_proposal_content_size,
         min_proposal_grace_epochs,
+        min_expedited_proposal_period,
+        min_expedited_proposal_grace_epochs,
+        expedited_proposal_threshold,
+        min_proposal_quorum,
+        proposal_veto_threshold,
+        no_quorum_burn_rate,
     }
 }
 
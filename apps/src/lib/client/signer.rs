@@ -0,0 +1,65 @@
+//! Signers of transactions, which abstract over where the secret keys are
+//! kept: in the wallet, from which they're loaded into memory, on a hardware
+//! wallet or, for a validator's protocol key, on a remote signer.
+
+use namada::proto::Tx;
+use namada::types::key::*;
+use namada::types::transaction::hash_tx;
+use thiserror::Error;
+
+use super::hardware_wallet;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Hardware wallet error: {0}")]
+    HardwareWallet(#[from] hardware_wallet::Error),
+    #[error("Remote signer error: {0}")]
+    RemoteSigner(#[from] crate::remote_signer::Error),
+}
+
+/// Result of signing
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A signer of transactions
+pub trait TxSigner {
+    /// The public key of the signer
+    fn public_key(&self) -> common::PublicKey;
+
+    /// Sign the hash of a transaction from its stable encoding (see
+    /// [`Tx::signing_bytes`]).
+    fn sign_tx_bytes(&self, signing_bytes: &[u8]) -> Result<common::Signature>;
+
+    /// Sign a transaction
+    fn sign_tx(&self, tx: Tx) -> Result<Tx> {
+        let sig = self.sign_tx_bytes(&tx.signing_bytes())?;
+        Ok(tx.with_signature(sig))
+    }
+}
+
+/// A secret key loaded from the wallet signs in memory
+impl TxSigner for common::SecretKey {
+    fn public_key(&self) -> common::PublicKey {
+        self.ref_to()
+    }
+
+    fn sign_tx_bytes(&self, signing_bytes: &[u8]) -> Result<common::Signature> {
+        let hash = hash_tx(signing_bytes);
+        Ok(common::SigScheme::sign(self, hash.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_signer() {
+        let keypair = namada::types::key::testing::keypair_1();
+        let tx = Tx::new(b"wasm code".to_vec(), Some(b"data".to_vec()));
+        let signer: &dyn TxSigner = &keypair;
+        assert_eq!(signer.public_key(), keypair.ref_to());
+        let signed_tx = signer.sign_tx(tx.clone()).unwrap();
+        assert_eq!(signed_tx, tx.sign(&keypair));
+    }
+}
@@ -1,5 +1,7 @@
 //! Helpers for making digital signatures using cryptographic keys from the
-//! wallet.
+//! wallet or from a hardware wallet.
+
+use std::collections::BTreeMap;
 
 use borsh::BorshSerialize;
 use namada::ledger::parameters::storage as parameter_storage;
@@ -10,9 +12,11 @@ use namada::types::key::*;
 use namada::types::storage::Epoch;
 use namada::types::token;
 use namada::types::token::Amount;
-use namada::types::transaction::{hash_tx, Fee, WrapperTx, MIN_FEE};
+use namada::types::transaction::{hash_tx, Fee, WrapperTx};
 
+use super::hardware_wallet::{self, LedgerSigner};
 use super::rpc;
+use super::signer::TxSigner;
 use crate::cli::context::{WalletAddress, WalletKeypair};
 use crate::cli::{self, args, Context};
 use crate::client::tendermint_rpc_types::TxBroadcastData;
@@ -72,6 +76,47 @@ pub async fn find_keypair(
     }
 }
 
+/// Find the signer of the given address. A key of a hardware wallet is used if
+/// the wallet knows one for the address, otherwise the keypair is loaded from
+/// the wallet. Panics if the key cannot be found or loaded.
+pub async fn find_signer(
+    wallet: &mut Wallet,
+    addr: &Address,
+    ledger_address: TendermintAddress,
+) -> Box<dyn TxSigner> {
+    let hardware_key = match addr {
+        Address::Implicit(ImplicitAddress(pkh)) => {
+            wallet.find_hardware_key_by_pkh(pkh).cloned()
+        }
+        // Only look-up the public key if there may be a hardware key for it
+        Address::Established(_) if !wallet.get_hardware_keys().is_empty() => {
+            rpc::get_public_key(addr, ledger_address.clone())
+                .await
+                .and_then(|pk| wallet.find_hardware_key_by_pk(&pk).cloned())
+        }
+        _ => None,
+    };
+    match hardware_key {
+        Some(key) => {
+            let device = hardware_wallet::connect().unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                cli::safe_exit(1)
+            });
+            Box::new(LedgerSigner::new(device, key))
+        }
+        None => Box::new(find_keypair(wallet, addr, ledger_address).await),
+    }
+}
+
+/// Sign a transaction with the given signer. Exits if the signing fails, e.g.
+/// if the transaction is rejected on a hardware wallet.
+pub fn sign_with(signer: &dyn TxSigner, tx: Tx) -> Tx {
+    signer.sign_tx(tx).unwrap_or_else(|err| {
+        eprintln!("Unable to sign the transaction: {}", err);
+        cli::safe_exit(1)
+    })
+}
+
 /// Carries types that can be directly/indirectly used to sign a transaction.
 #[allow(clippy::large_enum_variant)]
 #[derive(Clone)]
@@ -87,14 +132,14 @@ pub enum TxSigningKey {
 }
 
 /// Given CLI arguments and some defaults, determine the rightful transaction
-/// signer. Return the given signing key or public key of the given signer if
+/// signer. Return the given signing key or the signer of the given address if
 /// possible. If no explicit signer given, use the `default`. If no `default`
 /// is given, panics.
 pub async fn tx_signer(
     ctx: &mut Context,
     args: &args::Tx,
     mut default: TxSigningKey,
-) -> common::SecretKey {
+) -> Box<dyn TxSigner> {
     // Override the default signing key source if possible
     if let Some(signing_key) = &args.signing_key {
         default = TxSigningKey::WalletKeypair(signing_key.clone());
@@ -104,11 +149,11 @@ pub async fn tx_signer(
     // Now actually fetch the signing key and apply it
     match default {
         TxSigningKey::WalletKeypair(signing_key) => {
-            ctx.get_cached(&signing_key)
+            Box::new(ctx.get_cached(&signing_key))
         }
         TxSigningKey::WalletAddress(signer) => {
             let signer = ctx.get(&signer);
-            let signing_key = find_keypair(
+            let tx_signer = find_signer(
                 &mut ctx.wallet,
                 &signer,
                 args.ledger_address.clone(),
@@ -117,16 +162,16 @@ pub async fn tx_signer(
             // Check if the signer is implicit account that needs to reveal its
             // PK first
             if matches!(signer, Address::Implicit(_)) {
-                let pk: common::PublicKey = signing_key.ref_to();
+                let pk = tx_signer.public_key();
                 super::tx::reveal_pk_if_needed(ctx, &pk, args).await;
             }
-            signing_key
+            tx_signer
         }
         TxSigningKey::SecretKey(signing_key) => {
             // Check if the signing key needs to reveal its PK first
             let pk: common::PublicKey = signing_key.ref_to();
             super::tx::reveal_pk_if_needed(ctx, &pk, args).await;
-            signing_key
+            Box::new(signing_key)
         }
         TxSigningKey::None => {
             panic!(
@@ -137,6 +182,35 @@ pub async fn tx_signer(
     }
 }
 
+/// Find the address and keypair of the third-party fee payer given in the CLI
+/// arguments, if any.
+pub async fn fee_payer_keypair(
+    ctx: &mut Context,
+    args: &args::Tx,
+) -> Option<(Address, common::SecretKey)> {
+    let fee_payer = ctx.get(args.fee_payer.as_ref()?);
+    let keypair =
+        find_keypair(&mut ctx.wallet, &fee_payer, args.ledger_address.clone())
+            .await;
+    Some((fee_payer, keypair))
+}
+
+/// Refer to the code of a signed tx by its hash if it's already stored
+/// on-chain, unless the code should be embedded. The signature only covers the
+/// code hash, so it stays valid.
+pub async fn refer_to_stored_code(args: &args::Tx, tx: Tx) -> Tx {
+    if args.embed_tx_code {
+        return tx;
+    }
+    let client = HttpClient::new(args.ledger_address.clone()).unwrap();
+    let code_key = parameter_storage::get_wasm_code_key(&Hash(tx.code_hash()));
+    if rpc::query_has_storage_key(&client, &code_key).await {
+        tx.with_code_hash_ref()
+    } else {
+        tx
+    }
+}
+
 /// Sign a transaction with a given signing key or public key of a given signer.
 /// If no explicit signer given, use the `default`. If no `default` is given,
 /// panics.
@@ -156,8 +230,10 @@ pub async fn sign_tx(
         dump_tx_helper(&ctx, &tx, "unsigned", None);
     }
 
-    let keypair = tx_signer(&mut ctx, args, default).await;
-    let tx = tx.sign(&keypair);
+    let signer = tx_signer(&mut ctx, args, default).await;
+    let fee_payer = fee_payer_keypair(&mut ctx, args).await;
+    let tx = sign_with(signer.as_ref(), tx);
+    let tx = refer_to_stored_code(args, tx).await;
     if args.dump_tx {
         dump_tx_helper(&ctx, &tx, "signed", None);
     }
@@ -174,7 +250,8 @@ pub async fn sign_tx(
             args,
             epoch,
             tx,
-            &keypair,
+            signer.as_ref(),
+            fee_payer,
             #[cfg(not(feature = "mainnet"))]
             requires_pow,
         )
@@ -227,35 +304,63 @@ pub fn dump_tx_helper(
 /// Create a wrapper tx from a normal tx. Get the hash of the
 /// wrapper and its payload which is needed for monitoring its
 /// progress on chain.
+///
+/// If a third-party fee payer is given, it co-signs the wrapper to pay its
+/// fee.
 pub async fn sign_wrapper(
     ctx: &Context,
     args: &args::Tx,
     epoch: Epoch,
     tx: Tx,
-    keypair: &common::SecretKey,
+    signer: &dyn TxSigner,
+    fee_payer: Option<(Address, common::SecretKey)>,
     #[cfg(not(feature = "mainnet"))] requires_pow: bool,
 ) -> TxBroadcastData {
     let client = HttpClient::new(args.ledger_address.clone()).unwrap();
 
-    let fee_amount = if cfg!(feature = "mainnet") {
-        Amount::whole(MIN_FEE)
-    } else {
-        let wrapper_tx_fees_key = parameter_storage::get_wrapper_tx_fees_key();
-        rpc::query_storage_value::<token::Amount>(&client, &wrapper_tx_fees_key)
-            .await
-            .unwrap_or_default()
-    };
     let fee_token = ctx.get(&args.fee_token);
-    let source = Address::from(&keypair.ref_to());
-    let balance_key = token::balance_key(&fee_token, &source);
+    // The fee must cover the minimum gas price of the fee token for the gas
+    // limit
+    let gas_prices_key = parameter_storage::get_gas_prices_key();
+    let gas_prices =
+        rpc::query_storage_value::<BTreeMap<Address, token::Amount>>(
+            &client,
+            &gas_prices_key,
+        )
+        .await
+        .unwrap_or_default();
+    let min_fee = match gas_prices.get(&fee_token) {
+        Some(gas_price) => {
+            args.gas_limit.fee(*gas_price).unwrap_or_else(|| {
+                eprintln!("The fee for the given gas limit overflows.");
+                cli::safe_exit(1)
+            })
+        }
+        None => {
+            eprintln!(
+                "The token {fee_token} is not allowed for the payment of fees."
+            );
+            if !args.force {
+                cli::safe_exit(1);
+            }
+            Amount::default()
+        }
+    };
+    let fee_amount = std::cmp::max(args.fee_amount, min_fee);
+    let source = Address::from(&signer.public_key());
+    let payer = match &fee_payer {
+        Some((fee_payer, _)) => fee_payer.clone(),
+        None => source.clone(),
+    };
+    let balance_key = token::balance_key(&fee_token, &payer);
     let balance =
         rpc::query_storage_value::<token::Amount>(&client, &balance_key)
             .await
             .unwrap_or_default();
     if balance < fee_amount {
         eprintln!(
-            "The wrapper transaction source doesn't have enough balance to \
-             pay fee {fee_amount}, got {balance}."
+            "The wrapper transaction fee payer {payer} doesn't have enough \
+             balance to pay fee {fee_amount}, got {balance}."
         );
         if !args.force && cfg!(feature = "mainnet") {
             cli::safe_exit(1);
@@ -287,12 +392,12 @@ pub async fn sign_wrapper(
     };
 
     let tx = {
-        WrapperTx::new(
+        WrapperTx::new_with_pk(
             Fee {
                 amount: fee_amount,
                 token: fee_token,
             },
-            keypair,
+            signer.public_key(),
             epoch,
             args.gas_limit.clone(),
             tx,
@@ -302,6 +407,13 @@ pub async fn sign_wrapper(
             pow_solution,
         )
     };
+    // The fee payer signs the wrapper before its signer
+    let tx = match fee_payer {
+        Some((fee_payer, fee_payer_keypair)) => {
+            tx.sign_fee_payer(fee_payer, &fee_payer_keypair)
+        }
+        None => tx,
+    };
 
     // We use this to determine when the wrapper tx makes it on-chain
     let wrapper_hash = hash_tx(&tx.try_to_vec().unwrap()).to_string();
@@ -309,9 +421,7 @@ pub async fn sign_wrapper(
     // on-chain
     let decrypted_hash = tx.tx_hash.to_string();
     TxBroadcastData::Wrapper {
-        tx: tx
-            .sign(keypair)
-            .expect("Wrapper tx signing keypair should be correct"),
+        tx: sign_with(signer, tx.unsigned_tx()),
         wrapper_hash,
         decrypted_hash,
     }
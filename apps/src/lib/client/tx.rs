@@ -7,10 +7,12 @@ use std::fs::{File, OpenOptions};
 use std::io::{Read, Write};
 use std::ops::Deref;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use async_std::io::prelude::WriteExt;
 use async_std::io::{self};
 use borsh::{BorshDeserialize, BorshSerialize};
+use data_encoding::HEXLOWER_PERMISSIVE;
 use itertools::Either::*;
 use masp_primitives::asset_type::AssetType;
 use masp_primitives::consensus::{BranchId, TestNetwork};
@@ -31,12 +33,21 @@ use masp_primitives::transaction::Transaction;
 use masp_primitives::zip32::{ExtendedFullViewingKey, ExtendedSpendingKey};
 use masp_proofs::prover::LocalTxProver;
 use namada::ibc::applications::ics20_fungible_token_transfer::msgs::transfer::MsgTransfer;
+use namada::ibc::core::ics04_channel::channel::{
+    ChannelEnd, Counterparty, Order, State as ChanState,
+};
+use namada::ibc::core::ics04_channel::msgs::chan_open_init::MsgChannelOpenInit;
+use namada::ibc::core::ics04_channel::Version as ChanVersion;
+use namada::ibc::core::ics24_host::identifier::PortId;
 use namada::ibc::signer::Signer;
 use namada::ibc::timestamp::Timestamp as IbcTimestamp;
 use namada::ibc::tx_msg::Msg;
 use namada::ibc::Height as IbcHeight;
 use namada::ibc_proto::cosmos::base::v1beta1::Coin;
 use namada::ledger::governance::storage as gov_storage;
+use namada::ledger::ibc::handler::{
+    ica_controller_port_id, ICA_HOST_PORT_ID, ICA_VERSION,
+};
 use namada::ledger::masp;
 use namada::ledger::pos::{CommissionPair, PosParams};
 use namada::proto::Tx;
@@ -44,6 +55,9 @@ use namada::types::address::{masp, masp_tx_key, Address};
 use namada::types::governance::{
     OfflineProposal, OfflineVote, Proposal, ProposalVote,
 };
+use namada::types::hash::Hash;
+use namada::types::htlc::{self, ClaimHtlc, Htlc, InitHtlc, RefundHtlc};
+use namada::types::ibc::data::MsgSendIcaTx;
 use namada::types::key::*;
 use namada::types::masp::{PaymentAddress, TransferTarget};
 use namada::types::storage::{
@@ -54,22 +68,28 @@ use namada::types::token::{
     Transfer, HEAD_TX_KEY, PIN_KEY_PREFIX, TX_KEY_PREFIX,
 };
 use namada::types::transaction::governance::{
-    InitProposalData, VoteProposalData,
+    InitProposalData, TallyOfflineProposalData, TopUpProposalDepositData,
+    VoteProposalData,
 };
 use namada::types::transaction::{pos, InitAccount, InitValidator, UpdateVp};
 use namada::types::{storage, token};
 use namada::vm;
 use rand_core::{CryptoRng, OsRng, RngCore};
 use rust_decimal::Decimal;
+use serde::Deserialize;
 use sha2::Digest;
 use tokio::time::{Duration, Instant};
 
 use super::rpc;
+use super::signer::TxSigner;
 use super::types::ShieldedTransferContext;
 use crate::cli::context::WalletAddress;
 use crate::cli::{args, safe_exit, Context};
 use crate::client::rpc::{query_conversion, query_storage_value};
-use crate::client::signing::{find_keypair, sign_tx, tx_signer, TxSigningKey};
+use crate::client::signing::{
+    fee_payer_keypair, find_keypair, find_signer, refer_to_stored_code,
+    sign_tx, sign_with, sign_wrapper, tx_signer, TxSigningKey,
+};
 use crate::client::tendermint_rpc_types::{TxBroadcastData, TxResponse};
 use crate::client::types::ParsedTxTransferArgs;
 use crate::facade::tendermint_config::net::Address as TendermintAddress;
@@ -79,14 +99,20 @@ use crate::facade::tendermint_rpc::{Client, HttpClient};
 use crate::node::ledger::tendermint_node;
 
 const TX_INIT_ACCOUNT_WASM: &str = "tx_init_account.wasm";
+const TX_INIT_HTLC_WASM: &str = "tx_init_htlc.wasm";
+const TX_CLAIM_HTLC_WASM: &str = "tx_claim_htlc.wasm";
+const TX_REFUND_HTLC_WASM: &str = "tx_refund_htlc.wasm";
 const TX_INIT_VALIDATOR_WASM: &str = "tx_init_validator.wasm";
 const TX_INIT_PROPOSAL: &str = "tx_init_proposal.wasm";
 const TX_VOTE_PROPOSAL: &str = "tx_vote_proposal.wasm";
+const TX_TALLY_OFFLINE_PROPOSAL: &str = "tx_tally_offline_proposal.wasm";
+const TX_TOP_UP_PROPOSAL_DEPOSIT: &str = "tx_top_up_proposal_deposit.wasm";
 const TX_REVEAL_PK: &str = "tx_reveal_pk.wasm";
 const TX_UPDATE_VP_WASM: &str = "tx_update_vp.wasm";
 const TX_TRANSFER_WASM: &str = "tx_transfer.wasm";
 const TX_IBC_WASM: &str = "tx_ibc.wasm";
 const VP_USER_WASM: &str = "vp_user.wasm";
+const VP_HTLC_WASM: &str = "vp_htlc.wasm";
 const TX_BOND_WASM: &str = "tx_bond.wasm";
 const TX_UNBOND_WASM: &str = "tx_unbond.wasm";
 const TX_WITHDRAW_WASM: &str = "tx_withdraw.wasm";
@@ -119,6 +145,128 @@ pub async fn submit_custom(ctx: Context, args: args::TxCustom) {
     save_initialized_accounts(ctx, &args.tx, initialized_accounts).await;
 }
 
+/// A call of a batch transaction, as given in the JSON file of
+/// `namada client batch --from-file`, which contains an array of calls
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchCall {
+    /// A transparent transfer signed by its source
+    Transfer(token::Transfer),
+    /// A bond signed by its source or the validator for a self-bond
+    Bond(pos::Bond),
+    /// An unbond signed by its source or the validator for a self-bond
+    Unbond(pos::Unbond),
+    /// A withdrawal signed by its source or the validator for a self-bond
+    Withdraw(pos::Withdraw),
+    /// A custom wasm transaction signed by the given signer
+    Custom {
+        /// The path to the wasm code
+        code_path: PathBuf,
+        /// The path to the data, if any
+        data_path: Option<PathBuf>,
+        /// The address whose key signs the transaction
+        signer: Address,
+    },
+}
+
+pub async fn submit_batch(mut ctx: Context, args: args::TxBatch) {
+    let file = File::open(&args.from_file).unwrap_or_else(|err| {
+        eprintln!("Unable to open the batch file: {}", err);
+        safe_exit(1)
+    });
+    let calls: Vec<BatchCall> =
+        serde_json::from_reader(file).unwrap_or_else(|err| {
+            eprintln!("Unable to parse the batch file: {}", err);
+            safe_exit(1)
+        });
+    if calls.is_empty() {
+        eprintln!("The batch file doesn't contain any calls.");
+        safe_exit(1)
+    }
+
+    // Each call is signed by its own signer
+    let mut signed_calls = Vec::with_capacity(calls.len());
+    let mut first_signer = None;
+    for call in calls {
+        let (tx_code, data, signer) = match call {
+            BatchCall::Transfer(transfer) => {
+                let data = transfer.try_to_vec();
+                (ctx.read_wasm(TX_TRANSFER_WASM), data, transfer.source)
+            }
+            BatchCall::Bond(bond) => {
+                let data = bond.try_to_vec();
+                let signer = bond.source.unwrap_or(bond.validator);
+                (ctx.read_wasm(TX_BOND_WASM), data, signer)
+            }
+            BatchCall::Unbond(unbond) => {
+                let data = unbond.try_to_vec();
+                let signer = unbond.source.unwrap_or(unbond.validator);
+                (ctx.read_wasm(TX_UNBOND_WASM), data, signer)
+            }
+            BatchCall::Withdraw(withdraw) => {
+                let data = withdraw.try_to_vec();
+                let signer = withdraw.source.unwrap_or(withdraw.validator);
+                (ctx.read_wasm(TX_WITHDRAW_WASM), data, signer)
+            }
+            BatchCall::Custom {
+                code_path,
+                data_path,
+                signer,
+            } => {
+                let data = data_path.map(|data_path| {
+                    std::fs::read(data_path)
+                        .expect("Expected a file at given data path")
+                });
+                (
+                    ctx.read_wasm(code_path),
+                    Ok(data.unwrap_or_default()),
+                    signer,
+                )
+            }
+        };
+        let data = data.expect("Encoding tx data shouldn't fail");
+        let call_signer = find_signer(
+            &mut ctx.wallet,
+            &signer,
+            args.tx.ledger_address.clone(),
+        )
+        .await;
+        let tx = sign_with(call_signer.as_ref(), Tx::new(tx_code, Some(data)));
+        signed_calls.push(refer_to_stored_code(&args.tx, tx).await);
+        first_signer.get_or_insert(signer);
+    }
+    let tx = Tx::new_batch(signed_calls);
+
+    // The wrapper is signed by the signer of the first call by default
+    let first_signer = first_signer.expect("The batch has at least one call");
+    let default_signer =
+        TxSigningKey::WalletAddress(WalletAddress::new(first_signer.encode()));
+    let signer = tx_signer(&mut ctx, &args.tx, default_signer).await;
+    let fee_payer = fee_payer_keypair(&mut ctx, &args.tx).await;
+    let to_broadcast = if args.tx.dry_run {
+        TxBroadcastData::DryRun(tx)
+    } else {
+        let epoch = rpc::query_and_print_epoch(args::Query {
+            ledger_address: args.tx.ledger_address.clone(),
+        })
+        .await;
+        sign_wrapper(
+            &ctx,
+            &args.tx,
+            epoch,
+            tx,
+            signer.as_ref(),
+            fee_payer,
+            #[cfg(not(feature = "mainnet"))]
+            false,
+        )
+        .await
+    };
+    let (ctx, initialized_accounts) =
+        submit_broadcast_data(ctx, &args.tx, to_broadcast).await;
+    save_initialized_accounts(ctx, &args.tx, initialized_accounts).await;
+}
+
 pub async fn submit_update_vp(ctx: Context, args: args::TxUpdateVp) {
     let addr = ctx.get(&args.addr);
 
@@ -215,6 +363,167 @@ pub async fn submit_init_account(mut ctx: Context, args: args::TxInitAccount) {
     save_initialized_accounts(ctx, &args.tx, initialized_accounts).await;
 }
 
+pub async fn submit_init_htlc(mut ctx: Context, args: args::TxInitHtlc) {
+    let hash_lock = HEXLOWER_PERMISSIVE
+        .decode(args.hash_lock.as_bytes())
+        .ok()
+        .and_then(|bytes| Hash::try_from(&bytes[..]).ok())
+        .unwrap_or_else(|| {
+            eprintln!("The hash lock must be a hex-encoded SHA-256 hash.");
+            safe_exit(1)
+        });
+    let sender = ctx.get(&args.source);
+    let recipient = ctx.get(&args.recipient);
+    let token = ctx.get(&args.token);
+    // Check that the recipient address exists on chain
+    let recipient_exists =
+        rpc::known_address(&recipient, args.tx.ledger_address.clone()).await;
+    if !recipient_exists {
+        eprintln!(
+            "The recipient address {} doesn't exist on chain.",
+            recipient
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+    // Check sender balance
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let balance_key = token::balance_key(&token, &sender);
+    let balance =
+        rpc::query_storage_value::<token::Amount>(&client, &balance_key)
+            .await
+            .unwrap_or_default();
+    if balance < args.amount {
+        eprintln!(
+            "The balance of the sender {} of token {} is lower than the \
+             amount to be locked. Amount to lock is {} and the balance is {}.",
+            sender, token, args.amount, balance
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let htlc = Htlc {
+        sender,
+        recipient,
+        token,
+        amount: args.amount,
+        hash_lock,
+        deadline: args.deadline,
+    };
+    if let Err(err) = htlc.validate(DateTimeUtc::now()) {
+        eprintln!("Invalid HTLC terms: {}", err);
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let vp_code = ctx.read_wasm(VP_HTLC_WASM);
+    let tx_code = ctx.read_wasm(TX_INIT_HTLC_WASM);
+    let data = InitHtlc { vp_code, htlc };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, initialized_accounts) = process_tx(
+        ctx,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(args.source),
+        #[cfg(not(feature = "mainnet"))]
+        false,
+    )
+    .await;
+    save_initialized_accounts(ctx, &args.tx, initialized_accounts).await;
+}
+
+pub async fn submit_claim_htlc(ctx: Context, args: args::TxClaimHtlc) {
+    let address = ctx.get(&args.htlc);
+    let preimage = HEXLOWER_PERMISSIVE
+        .decode(args.preimage.as_bytes())
+        .unwrap_or_else(|err| {
+            eprintln!("The preimage must be hex-encoded: {}", err);
+            safe_exit(1)
+        });
+    let htlc = query_htlc(&address, &args.tx).await;
+    if Hash::sha256(&preimage) != htlc.hash_lock {
+        eprintln!("The preimage doesn't match the hash lock of the HTLC.");
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+    if htlc.is_expired(DateTimeUtc::now()) {
+        eprintln!(
+            "The HTLC has expired at {} and can no longer be claimed.",
+            htlc.deadline.to_rfc3339()
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_CLAIM_HTLC_WASM);
+    let data = ClaimHtlc {
+        htlc: address,
+        preimage,
+    };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    process_tx(
+        ctx,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(WalletAddress::new(
+            htlc.recipient.encode(),
+        )),
+        #[cfg(not(feature = "mainnet"))]
+        false,
+    )
+    .await;
+}
+
+pub async fn submit_refund_htlc(ctx: Context, args: args::TxRefundHtlc) {
+    let address = ctx.get(&args.htlc);
+    let htlc = query_htlc(&address, &args.tx).await;
+    if !htlc.is_expired(DateTimeUtc::now()) {
+        eprintln!(
+            "The HTLC cannot be refunded before its deadline {}.",
+            htlc.deadline.to_rfc3339()
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_REFUND_HTLC_WASM);
+    let data = RefundHtlc { htlc: address };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    process_tx(
+        ctx,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(WalletAddress::new(htlc.sender.encode())),
+        #[cfg(not(feature = "mainnet"))]
+        false,
+    )
+    .await;
+}
+
+/// Query the terms of the given HTLC account or exit if it doesn't exist
+async fn query_htlc(address: &Address, args: &args::Tx) -> Htlc {
+    let client = HttpClient::new(args.ledger_address.clone()).unwrap();
+    rpc::query_storage_value::<Htlc>(&client, &htlc::htlc_key(address))
+        .await
+        .unwrap_or_else(|| {
+            eprintln!("No HTLC found at the address {}.", address);
+            safe_exit(1)
+        })
+}
+
 pub async fn submit_init_validator(
     mut ctx: Context,
     args::TxInitValidator {
@@ -1609,7 +1918,7 @@ pub async fn submit_transfer(mut ctx: Context, args: args::TxTransfer) {
     // will need to cover the gas fees.
     let chosen_signer = tx_signer(&mut ctx, &args.tx, default_signer.clone())
         .await
-        .ref_to();
+        .public_key();
     let shielded_gas = masp_tx_key().ref_to() == chosen_signer;
     // Determine whether to pin this transaction to a storage key
     let key = match ctx.get(&args.target) {
@@ -1809,6 +2118,121 @@ pub async fn submit_ibc_transfer(ctx: Context, args: args::TxIbcTransfer) {
     .await;
 }
 
+pub async fn submit_ica_register(ctx: Context, args: args::TxIcaRegister) {
+    let owner = ctx.get(&args.owner);
+    // Check that the owner address exists on chain
+    let owner_exists =
+        rpc::known_address(&owner, args.tx.ledger_address.clone()).await;
+    if !owner_exists {
+        eprintln!("The owner address {} doesn't exist on chain.", owner);
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_IBC_WASM);
+
+    // the controller port of the owner and the host port on the host chain
+    let port_id = ica_controller_port_id(&owner).unwrap_or_else(|err| {
+        eprintln!("Invalid controller port for the owner {}: {}", owner, err);
+        safe_exit(1)
+    });
+    let counterparty = Counterparty::new(
+        PortId::from_str(ICA_HOST_PORT_ID).expect("valid port ID"),
+        None,
+    );
+    let channel = ChannelEnd::new(
+        ChanState::Init,
+        Order::Ordered,
+        counterparty,
+        vec![args.connection_id],
+        ChanVersion::new(ICA_VERSION.to_string()),
+    );
+    let msg = MsgChannelOpenInit {
+        port_id,
+        channel,
+        signer: Signer::new(owner.to_string()),
+    };
+    tracing::debug!("Interchain account registration message {:?}", msg);
+    let any_msg = msg.to_any();
+    let mut data = vec![];
+    prost::Message::encode(&any_msg, &mut data)
+        .expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    process_tx(
+        ctx,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(args.owner),
+        #[cfg(not(feature = "mainnet"))]
+        false,
+    )
+    .await;
+}
+
+pub async fn submit_ica_send(ctx: Context, args: args::TxIcaSend) {
+    let owner = ctx.get(&args.owner);
+    // Check that the owner address exists on chain
+    let owner_exists =
+        rpc::known_address(&owner, args.tx.ledger_address.clone()).await;
+    if !owner_exists {
+        eprintln!("The owner address {} doesn't exist on chain.", owner);
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let data = std::fs::read(&args.data_path).unwrap_or_else(|err| {
+        eprintln!(
+            "Failed to read the transaction data file {}: {}",
+            args.data_path.to_string_lossy(),
+            err
+        );
+        safe_exit(1)
+    });
+    let tx_code = ctx.read_wasm(TX_IBC_WASM);
+
+    // this height should be that of the host chain, not this chain
+    let timeout_height = args.timeout_height.unwrap_or_default();
+    let now: namada::tendermint::Time = DateTimeUtc::now().try_into().unwrap();
+    let now: IbcTimestamp = now.into();
+    let timeout_timestamp = if let Some(offset) = args.timeout_sec_offset {
+        (now + Duration::new(offset, 0)).unwrap()
+    } else if timeout_height == 0 {
+        // we cannot set 0 to both the height and the timestamp
+        (now + Duration::new(3600, 0)).unwrap()
+    } else {
+        IbcTimestamp::none()
+    };
+
+    let msg = MsgSendIcaTx {
+        owner,
+        connection_id: args.connection_id.to_string(),
+        data,
+        memo: args.memo.unwrap_or_default(),
+        timeout_revision_number: 0,
+        timeout_revision_height: timeout_height,
+        timeout_timestamp: timeout_timestamp.nanoseconds(),
+    };
+    tracing::debug!("Interchain account message {:?}", msg);
+    let any_msg = msg.to_any();
+    let mut data = vec![];
+    prost::Message::encode(&any_msg, &mut data)
+        .expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    process_tx(
+        ctx,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(args.owner),
+        #[cfg(not(feature = "mainnet"))]
+        false,
+    )
+    .await;
+}
+
 pub async fn submit_init_proposal(mut ctx: Context, args: args::InitProposal) {
     let file = File::open(&args.proposal_data).expect("File must exist.");
     let proposal: Proposal =
@@ -1822,55 +2246,77 @@ pub async fn submit_init_proposal(mut ctx: Context, args: args::InitProposal) {
         ledger_address: args.tx.ledger_address.clone(),
     })
     .await;
+    // Expedited proposals have their own minimum voting and grace periods
+    let (min_proposal_period, min_proposal_grace_epochs) = if proposal.expedited
+    {
+        (
+            governance_parameters.min_expedited_proposal_period,
+            governance_parameters.min_expedited_proposal_grace_epochs,
+        )
+    } else {
+        (
+            governance_parameters.min_proposal_period,
+            governance_parameters.min_proposal_grace_epochs,
+        )
+    };
 
     if proposal.voting_start_epoch <= current_epoch
-        || proposal.voting_start_epoch.0
-            % governance_parameters.min_proposal_period
-            != 0
+        || proposal.voting_start_epoch.0 % min_proposal_period != 0
     {
         println!("{}", proposal.voting_start_epoch <= current_epoch);
         println!(
             "{}",
-            proposal.voting_start_epoch.0
-                % governance_parameters.min_proposal_period
-                == 0
+            proposal.voting_start_epoch.0 % min_proposal_period == 0
         );
         eprintln!(
             "Invalid proposal start epoch: {} must be greater than current \
              epoch {} and a multiple of {}",
-            proposal.voting_start_epoch,
-            current_epoch,
-            governance_parameters.min_proposal_period
+            proposal.voting_start_epoch, current_epoch, min_proposal_period
         );
         if !args.tx.force {
             safe_exit(1)
         }
     } else if proposal.voting_end_epoch <= proposal.voting_start_epoch
         || proposal.voting_end_epoch.0 - proposal.voting_start_epoch.0
-            < governance_parameters.min_proposal_period
+            < min_proposal_period
         || proposal.voting_end_epoch.0 - proposal.voting_start_epoch.0
             > governance_parameters.max_proposal_period
-        || proposal.voting_end_epoch.0 % 3 != 0
+        || proposal.voting_end_epoch.0 % min_proposal_period != 0
     {
         eprintln!(
             "Invalid proposal end epoch: difference between proposal start \
              and end epoch must be at least {} and at max {} and end epoch \
              must be a multiple of {}",
-            governance_parameters.min_proposal_period,
+            min_proposal_period,
             governance_parameters.max_proposal_period,
-            governance_parameters.min_proposal_period
+            min_proposal_period
         );
         if !args.tx.force {
             safe_exit(1)
         }
     } else if proposal.grace_epoch <= proposal.voting_end_epoch
         || proposal.grace_epoch.0 - proposal.voting_end_epoch.0
-            < governance_parameters.min_proposal_grace_epochs
+            < min_proposal_grace_epochs
     {
         eprintln!(
             "Invalid proposal grace epoch: difference between proposal grace \
              and end epoch must be at least {}",
-            governance_parameters.min_proposal_grace_epochs
+            min_proposal_grace_epochs
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    if proposal
+        .slash_fund_disbursements
+        .iter()
+        .any(|disbursement| !disbursement.is_valid(proposal.grace_epoch))
+    {
+        eprintln!(
+            "Invalid slash fund disbursements: amounts must be positive and \
+             streamed payments must start no earlier than the grace epoch and \
+             end no earlier than they start"
         );
         if !args.tx.force {
             safe_exit(1)
@@ -2107,6 +2553,177 @@ pub async fn submit_vote_proposal(mut ctx: Context, args: args::VoteProposal) {
     }
 }
 
+pub async fn submit_tally_offline_proposal(
+    ctx: Context,
+    args: args::TallyOfflineProposal,
+) {
+    let proposal_path = args.proposal_folder.join("proposal");
+    let file = File::open(&proposal_path).unwrap_or_else(|err| {
+        eprintln!(
+            "The folder must contain the offline proposal in a file named \
+             \"proposal\": {}",
+            err
+        );
+        safe_exit(1)
+    });
+    let proposal: OfflineProposal = serde_json::from_reader(file)
+        .expect("JSON was not well-formatted for proposal.");
+    let public_key =
+        rpc::get_public_key(&proposal.address, args.tx.ledger_address.clone())
+            .await
+            .expect("Public key should exist.");
+    if !proposal.check_signature(&public_key) {
+        eprintln!("Bad proposal signature.");
+        safe_exit(1)
+    }
+
+    let current_epoch = rpc::query_and_print_epoch(args::Query {
+        ledger_address: args.tx.ledger_address.clone(),
+    })
+    .await;
+    if proposal.tally_epoch > current_epoch {
+        eprintln!(
+            "The offline proposal cannot be tallied before its tally epoch {}.",
+            proposal.tally_epoch
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    // Only submit the valid votes, as any invalid one would get the whole tx
+    // rejected
+    let proposal_hash = proposal.compute_hash();
+    let entries = std::fs::read_dir(&args.proposal_folder)
+        .expect("Should be able to read the directory.");
+    let mut votes = vec![];
+    for entry in entries {
+        let path = entry.expect("Should be able to read the entry.").path();
+        if !path.is_file() || path == proposal_path {
+            continue;
+        }
+        let file = File::open(&path).expect("Vote file must exist.");
+        let vote: OfflineVote = match serde_json::from_reader(file) {
+            Ok(vote) => vote,
+            Err(err) => {
+                eprintln!(
+                    "Skipping {}, which isn't an offline vote: {}",
+                    path.display(),
+                    err
+                );
+                continue;
+            }
+        };
+        let public_key =
+            rpc::get_public_key(&vote.address, args.tx.ledger_address.clone())
+                .await;
+        let is_valid = vote.proposal_hash == proposal_hash
+            && public_key
+                .map_or(false, |public_key| vote.check_signature(&public_key));
+        if is_valid {
+            votes.push(vote);
+        } else {
+            eprintln!("Skipping the invalid vote in {}", path.display());
+        }
+    }
+    println!(
+        "Submitting the offline proposal {} with {} votes.",
+        proposal_hash,
+        votes.len()
+    );
+
+    let data = TallyOfflineProposalData { proposal, votes };
+    let data = data
+        .try_to_vec()
+        .expect("Encoding proposal data shouldn't fail");
+    let tx_code = ctx.read_wasm(TX_TALLY_OFFLINE_PROPOSAL);
+    let tx = Tx::new(tx_code, Some(data));
+
+    process_tx(
+        ctx,
+        &args.tx,
+        tx,
+        TxSigningKey::None,
+        #[cfg(not(feature = "mainnet"))]
+        false,
+    )
+    .await;
+}
+
+pub async fn submit_top_up_proposal_deposit(
+    mut ctx: Context,
+    args: args::TopUpProposalDeposit,
+) {
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let current_epoch = rpc::query_and_print_epoch(args::Query {
+        ledger_address: args.tx.ledger_address.clone(),
+    })
+    .await;
+
+    let proposal_start_epoch_key =
+        gov_storage::get_voting_start_epoch_key(args.proposal_id);
+    let proposal_start_epoch =
+        rpc::query_storage_value::<Epoch>(&client, &proposal_start_epoch_key)
+            .await;
+    match proposal_start_epoch {
+        Some(epoch) if current_epoch >= epoch => {
+            eprintln!(
+                "The deposit of a proposal can only be topped up before its \
+                 voting start epoch {}, current epoch is {}",
+                epoch, current_epoch
+            );
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+        Some(_) => {}
+        None => {
+            eprintln!("Proposal id {} doesn't exist.", args.proposal_id);
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+
+    let depositor = ctx.get(&args.depositor);
+    let balance_key = token::balance_key(&ctx.native_token, &depositor);
+    let balance =
+        rpc::query_storage_value::<token::Amount>(&client, &balance_key)
+            .await
+            .unwrap_or_default();
+    if balance < args.amount {
+        eprintln!(
+            "The balance of the depositor is lower than the amount to be \
+             deposited. Amount to deposit is {} and the balance is {}.",
+            args.amount, balance
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let data = TopUpProposalDepositData {
+        id: args.proposal_id,
+        depositor,
+        amount: args.amount,
+    };
+    let data = data
+        .try_to_vec()
+        .expect("Encoding proposal data shouldn't fail");
+    let tx_code = ctx.read_wasm(TX_TOP_UP_PROPOSAL_DEPOSIT);
+    let tx = Tx::new(tx_code, Some(data));
+
+    process_tx(
+        ctx,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(args.depositor),
+        #[cfg(not(feature = "mainnet"))]
+        false,
+    )
+    .await;
+}
+
 pub async fn submit_reveal_pk(mut ctx: Context, args: args::RevealPk) {
     let args::RevealPk {
         tx: args,
@@ -2157,15 +2774,16 @@ pub async fn submit_reveal_pk_aux(
     let tx = Tx::new(tx_code, Some(tx_data));
 
     // submit_tx without signing the inner tx
-    let keypair = if let Some(signing_key) = &args.signing_key {
-        ctx.get_cached(signing_key)
+    let signer: Box<dyn TxSigner> = if let Some(signing_key) = &args.signing_key
+    {
+        Box::new(ctx.get_cached(signing_key))
     } else if let Some(signer) = args.signer.as_ref() {
         let signer = ctx.get(signer);
-        find_keypair(&mut ctx.wallet, &signer, args.ledger_address.clone())
-            .await
+        find_signer(&mut ctx.wallet, &signer, args.ledger_address.clone()).await
     } else {
-        find_keypair(&mut ctx.wallet, &addr, args.ledger_address.clone()).await
+        find_signer(&mut ctx.wallet, &addr, args.ledger_address.clone()).await
     };
+    let fee_payer = super::signing::fee_payer_keypair(ctx, args).await;
     let epoch = rpc::query_and_print_epoch(args::Query {
         ledger_address: args.ledger_address.clone(),
     })
@@ -2178,7 +2796,8 @@ pub async fn submit_reveal_pk_aux(
             args,
             epoch,
             tx,
-            &keypair,
+            signer.as_ref(),
+            fee_payer,
             #[cfg(not(feature = "mainnet"))]
             false,
         )
@@ -2594,6 +3213,16 @@ async fn process_tx(
         requires_pow,
     )
     .await;
+    submit_broadcast_data(ctx, args, to_broadcast).await
+}
+
+/// Dry run the given tx or submit its wrapper to the ledger. Returns the
+/// addresses of the accounts initialized by the tx, if any.
+async fn submit_broadcast_data(
+    ctx: Context,
+    args: &args::Tx,
+    to_broadcast: TxBroadcastData,
+) -> (Context, Vec<Address>) {
     // NOTE: use this to print the request JSON body:
 
     // let request =
@@ -20,6 +20,8 @@ pub struct ParsedTxArgs {
     pub dry_run: bool,
     /// Dump the transaction bytes
     pub dump_tx: bool,
+    /// Embed the full transaction code instead of referring to it by hash
+    pub embed_tx_code: bool,
     /// Submit the transaction even if it doesn't pass client checks
     pub force: bool,
     /// Do not wait for the transaction to be added to the blockchain
@@ -39,6 +41,8 @@ pub struct ParsedTxArgs {
     pub signing_key: Option<key::common::SecretKey>,
     /// Sign the tx with the keypair of the public key of the given address
     pub signer: Option<Address>,
+    /// Pay the fee from the given address, which co-signs the wrapper tx
+    pub fee_payer: Option<Address>,
 }
 
 #[derive(Clone, Debug)]
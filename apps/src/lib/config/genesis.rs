@@ -1,6 +1,6 @@
 //! The parameters used for the chain's genesis
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 #[cfg(not(feature = "dev"))]
 use std::path::Path;
 
@@ -8,6 +8,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use derivative::Derivative;
 #[cfg(not(feature = "mainnet"))]
 use namada::core::ledger::testnet_pow;
+use namada::ledger::gas::GasSchedule;
 use namada::ledger::governance::parameters::GovParams;
 use namada::ledger::parameters::EpochDuration;
 use namada::ledger::pos::{GenesisValidator, PosParams};
@@ -18,13 +19,14 @@ use namada::types::chain::ProposalBytes;
 use namada::types::key::dkg_session_keys::DkgPublicKey;
 use namada::types::key::*;
 use namada::types::time::{DateTimeUtc, DurationSecs};
+use namada::types::vesting::VestingSchedule;
 use namada::types::{storage, token};
 use rust_decimal::Decimal;
 
 /// Genesis configuration file format
 pub mod genesis_config {
     use std::array::TryFromSliceError;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
     use std::convert::TryInto;
     use std::path::Path;
     use std::str::FromStr;
@@ -33,6 +35,7 @@ pub mod genesis_config {
     use eyre::Context;
     #[cfg(not(feature = "mainnet"))]
     use namada::core::ledger::testnet_pow;
+    use namada::ledger::gas::GasSchedule;
     use namada::ledger::governance::parameters::GovParams;
     use namada::ledger::parameters::EpochDuration;
     use namada::ledger::pos::{GenesisValidator, PosParams};
@@ -40,8 +43,9 @@ pub mod genesis_config {
     use namada::types::chain::ProposalBytes;
     use namada::types::key::dkg_session_keys::DkgPublicKey;
     use namada::types::key::*;
-    use namada::types::time::Rfc3339String;
-    use namada::types::{storage, token};
+    use namada::types::time::{DateTimeUtc, Rfc3339String};
+    use namada::types::vesting::VestingSchedule;
+    use namada::types::{storage, token, transaction};
     use rust_decimal::Decimal;
     use serde::{Deserialize, Serialize};
     use thiserror::Error;
@@ -159,6 +163,25 @@ pub mod genesis_config {
         // Minimum number of epoch between end and grace epoch
         // XXX: u64 doesn't work with toml-rs!
         pub min_proposal_grace_epochs: u64,
+        // Minimum expedited proposal period length in epochs
+        // XXX: u64 doesn't work with toml-rs!
+        pub min_expedited_proposal_period: u64,
+        // Minimum number of epoch between end and grace epoch of an
+        // expedited proposal
+        // XXX: u64 doesn't work with toml-rs!
+        pub min_expedited_proposal_grace_epochs: u64,
+        // Fraction of the total voting power required to pass an expedited
+        // proposal
+        pub expedited_proposal_threshold: Decimal,
+        // Fraction of the total voting power that must vote for the deposits
+        // of a proposal to be refunded
+        pub min_proposal_quorum: Decimal,
+        // Fraction of the cast voting power that must veto a proposal to
+        // reject it and burn its deposits
+        pub proposal_veto_threshold: Decimal,
+        // Fraction of the deposits burnt when a proposal doesn't reach the
+        // quorum
+        pub no_quorum_burn_rate: Decimal,
     }
 
     /// Validator pre-genesis configuration can be created with client utils
@@ -224,6 +247,24 @@ pub mod genesis_config {
         pub public_key: Option<HexString>,
         // Initial storage key values.
         pub storage: Option<HashMap<String, HexString>>,
+        // Vesting schedule of the account's tokens, if any. This should be
+        // used with the vesting VP.
+        pub vesting: Option<VestingConfig>,
+    }
+
+    #[derive(Clone, Debug, Deserialize, Serialize)]
+    pub struct VestingConfig {
+        // Address of the vested token
+        pub token: String,
+        // Total amount of the vested tokens
+        // XXX: u64 doesn't work with toml-rs!
+        pub amount: u64,
+        // Time from which the tokens start to vest
+        pub start: Rfc3339String,
+        // Time before which no tokens are unlocked (default: start)
+        pub cliff: Option<Rfc3339String>,
+        // Time at which all the tokens are unlocked
+        pub end: Rfc3339String,
     }
 
     #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -267,9 +308,18 @@ pub mod genesis_config {
         pub pos_gain_p: Decimal,
         /// PoS gain d
         pub pos_gain_d: Decimal,
-        #[cfg(not(feature = "mainnet"))]
-        /// Fix wrapper tx fees
-        pub wrapper_tx_fees: Option<token::Amount>,
+        /// The gas costs of transactions' and VPs' operations. Any missing
+        /// cost is set to its default value.
+        #[serde(default)]
+        pub gas_schedule: GasSchedule,
+        /// The tokens allowed for the payment of fees with their minimum gas
+        /// price. `None` value allows only the native token with the default
+        /// minimum gas price.
+        pub gas_prices: Option<BTreeMap<Address, token::Amount>>,
+        /// How many block heights in the past can the block headers be read
+        /// by transactions and validity predicates. `None` value sets it to
+        /// the default of 3600 blocks.
+        pub storage_read_past_height_limit: Option<u64>,
     }
 
     #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -505,9 +555,33 @@ pub mod genesis_config {
                     )
                 })
                 .collect(),
+            vesting: config.vesting.as_ref().map(load_vesting),
         }
     }
 
+    fn load_vesting(config: &VestingConfig) -> VestingSchedule {
+        let start: DateTimeUtc = config.start.clone().try_into().unwrap();
+        let schedule = VestingSchedule {
+            token: Address::decode(&config.token).unwrap(),
+            amount: token::Amount::whole(config.amount),
+            start,
+            cliff: config
+                .cliff
+                .clone()
+                .map(|cliff| cliff.try_into().unwrap())
+                .unwrap_or(start),
+            end: config.end.clone().try_into().unwrap(),
+        };
+        if !schedule.is_valid() {
+            eprintln!(
+                "The cliff of a vesting schedule must be between its start \
+                 and end"
+            );
+            cli::safe_exit(1);
+        }
+        schedule
+    }
+
     fn load_implicit(config: &ImplicitAccountConfig) -> ImplicitAccount {
         ImplicitAccount {
             public_key: config
@@ -591,6 +665,18 @@ pub mod genesis_config {
             .to_sha256_bytes()
             .unwrap();
 
+        // All the known wasm blobs are stored on-chain under their hash to
+        // allow transactions to refer to their code by the hash
+        let wasm_codes = wasm
+            .values()
+            .filter_map(|config| {
+                config.sha256.as_ref().map(|sha256| WasmCode {
+                    code_path: config.filename.to_owned(),
+                    sha256: sha256.to_sha256_bytes().unwrap(),
+                })
+            })
+            .collect();
+
         let min_duration: i64 =
             60 * 60 * 24 * 365 / (parameters.epochs_per_year as i64);
         let parameters = Parameters {
@@ -616,7 +702,16 @@ pub mod genesis_config {
             pos_gain_d: parameters.pos_gain_d,
             staked_ratio: Decimal::ZERO,
             pos_inflation_amount: 0,
-            wrapper_tx_fees: parameters.wrapper_tx_fees,
+            gas_schedule: parameters.gas_schedule,
+            gas_prices: parameters.gas_prices.unwrap_or_else(|| {
+                BTreeMap::from([(
+                    native_token.clone(),
+                    token::Amount::from(transaction::MIN_FEE),
+                )])
+            }),
+            storage_read_past_height_limit: parameters
+                .storage_read_past_height_limit
+                .unwrap_or(3600),
         };
 
         let GovernanceParamsConfig {
@@ -626,6 +721,12 @@ pub mod genesis_config {
             max_proposal_content_size,
             min_proposal_grace_epochs,
             max_proposal_period,
+            min_expedited_proposal_period,
+            min_expedited_proposal_grace_epochs,
+            expedited_proposal_threshold,
+            min_proposal_quorum,
+            proposal_veto_threshold,
+            no_quorum_burn_rate,
         } = gov_params;
         let gov_params = GovParams {
             min_proposal_fund,
@@ -634,6 +735,12 @@ pub mod genesis_config {
             max_proposal_content_size,
             min_proposal_grace_epochs,
             max_proposal_period,
+            min_expedited_proposal_period,
+            min_expedited_proposal_grace_epochs,
+            expedited_proposal_threshold,
+            min_proposal_quorum,
+            proposal_veto_threshold,
+            no_quorum_burn_rate,
         };
 
         let PosParamsConfig {
@@ -675,6 +782,7 @@ pub mod genesis_config {
             parameters,
             pos_params,
             gov_params,
+            wasm_codes,
         };
         genesis.init();
         genesis
@@ -727,6 +835,8 @@ pub struct Genesis {
     pub parameters: Parameters,
     pub pos_params: PosParams,
     pub gov_params: GovParams,
+    /// Wasm codes to be stored on-chain under their hash
+    pub wasm_codes: Vec<WasmCode>,
 }
 
 impl Genesis {
@@ -736,6 +846,7 @@ impl Genesis {
         self.token_accounts.sort();
         self.established_accounts.sort();
         self.implicit_accounts.sort();
+        self.wasm_codes.sort();
     }
 }
 
@@ -788,6 +899,9 @@ pub struct EstablishedAccount {
     /// Account's sub-space storage. The values must be borsh encoded bytes.
     #[derivative(PartialOrd = "ignore", Ord = "ignore")]
     pub storage: HashMap<storage::Key, Vec<u8>>,
+    /// The vesting schedule of the account's tokens, if any
+    #[derivative(PartialOrd = "ignore", Ord = "ignore")]
+    pub vesting: Option<VestingSchedule>,
 }
 
 #[derive(
@@ -822,6 +936,24 @@ pub struct ImplicitAccount {
     pub public_key: common::PublicKey,
 }
 
+#[derive(
+    Clone,
+    Debug,
+    BorshSerialize,
+    BorshDeserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+/// A wasm code which is stored on-chain under its hash
+pub struct WasmCode {
+    /// WASM code path
+    pub code_path: String,
+    /// Expected SHA-256 hash of the WASM code
+    pub sha256: [u8; 32],
+}
+
 /// Protocol parameters. This is almost the same as
 /// `ledger::parameters::Parameters`, but instead of having the `implicit_vp`
 /// WASM code bytes, it only has the name and sha as the actual code is loaded
@@ -862,9 +994,13 @@ pub struct Parameters {
     pub staked_ratio: Decimal,
     /// PoS inflation amount from the last epoch (read + write for every epoch)
     pub pos_inflation_amount: u64,
-    /// Fixed Wrapper tx fees
-    #[cfg(not(feature = "mainnet"))]
-    pub wrapper_tx_fees: Option<token::Amount>,
+    /// The gas costs of transactions' and VPs' operations
+    pub gas_schedule: GasSchedule,
+    /// The tokens allowed for the payment of fees with their minimum gas price
+    pub gas_prices: BTreeMap<Address, token::Amount>,
+    /// How many block heights in the past can the block headers be read by
+    /// transactions and validity predicates
+    pub storage_read_past_height_limit: u64,
 }
 
 #[cfg(not(feature = "dev"))]
@@ -925,7 +1061,9 @@ pub fn genesis() -> Genesis {
         pos_gain_d: dec!(0.1),
         staked_ratio: dec!(0.0),
         pos_inflation_amount: 0,
-        wrapper_tx_fees: Some(token::Amount::whole(0)),
+        gas_schedule: GasSchedule::default(),
+        gas_prices: BTreeMap::from([(address::nam(), token::Amount::from(0))]),
+        storage_read_past_height_limit: 3600,
     };
     let albert = EstablishedAccount {
         address: wallet::defaults::albert_address(),
@@ -933,6 +1071,7 @@ pub fn genesis() -> Genesis {
         vp_sha256: Default::default(),
         public_key: Some(wallet::defaults::albert_keypair().ref_to()),
         storage: HashMap::default(),
+        vesting: None,
     };
     let bertha = EstablishedAccount {
         address: wallet::defaults::bertha_address(),
@@ -940,6 +1079,7 @@ pub fn genesis() -> Genesis {
         vp_sha256: Default::default(),
         public_key: Some(wallet::defaults::bertha_keypair().ref_to()),
         storage: HashMap::default(),
+        vesting: None,
     };
     let christel = EstablishedAccount {
         address: wallet::defaults::christel_address(),
@@ -947,6 +1087,7 @@ pub fn genesis() -> Genesis {
         vp_sha256: Default::default(),
         public_key: Some(wallet::defaults::christel_keypair().ref_to()),
         storage: HashMap::default(),
+        vesting: None,
     };
     let masp = EstablishedAccount {
         address: namada::types::address::masp(),
@@ -954,6 +1095,7 @@ pub fn genesis() -> Genesis {
         vp_sha256: Default::default(),
         public_key: None,
         storage: HashMap::default(),
+        vesting: None,
     };
     let implicit_accounts = vec![ImplicitAccount {
         public_key: wallet::defaults::daewon_keypair().ref_to(),
@@ -991,6 +1133,26 @@ pub fn genesis() -> Genesis {
             balances: balances.clone(),
         })
         .collect();
+    let wasm_codes = [
+        "tx_bond.wasm",
+        "tx_change_validator_commission.wasm",
+        "tx_ibc.wasm",
+        "tx_init_account.wasm",
+        "tx_init_proposal.wasm",
+        "tx_init_validator.wasm",
+        "tx_reveal_pk.wasm",
+        "tx_transfer.wasm",
+        "tx_unbond.wasm",
+        "tx_update_vp.wasm",
+        "tx_vote_proposal.wasm",
+        "tx_withdraw.wasm",
+    ]
+    .into_iter()
+    .map(|code_path| WasmCode {
+        code_path: code_path.into(),
+        sha256: Default::default(),
+    })
+    .collect();
     Genesis {
         genesis_time: DateTimeUtc::now(),
         validators: vec![validator],
@@ -1000,6 +1162,7 @@ pub fn genesis() -> Genesis {
         parameters,
         pos_params: PosParams::default(),
         gov_params: GovParams::default(),
+        wasm_codes,
         native_token: address::nam(),
         #[cfg(not(feature = "mainnet"))]
         faucet_pow_difficulty: None,
@@ -10,6 +10,7 @@ use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use namada::types::address::Address;
 use namada::types::chain::ChainId;
 use namada::types::time::Rfc3339String;
 use serde::{Deserialize, Serialize};
@@ -118,6 +119,27 @@ pub struct Tendermint {
     pub instrumentation_prometheus: bool,
     pub instrumentation_prometheus_listen_addr: SocketAddr,
     pub instrumentation_namespace: String,
+    /// When set, the validator's consensus and protocol keys are held by a
+    /// remote signer instead of the node
+    #[serde(default)]
+    pub remote_signer: Option<RemoteSigner>,
+}
+
+/// The sockets of a remote signer (`namada-signer`), which holds a
+/// validator's consensus and protocol keys. The sockets are either Unix
+/// sockets (`unix://<path>`) or TCP sockets (`tcp://<host>:<port>`), whose
+/// connections are encrypted with Tendermint's secret connection protocol.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RemoteSigner {
+    /// The address of the validator, whose keys are held by the signer
+    pub validator_address: Address,
+    /// The socket on which Tendermint listens for the connection of the
+    /// signer, which signs consensus messages with Tendermint's privval
+    /// protocol
+    pub privval_addr: TendermintAddress,
+    /// The socket on which the signer serves the signing of protocol txs and
+    /// vote extensions
+    pub protocol_addr: TendermintAddress,
 }
 
 impl Ledger {
@@ -164,6 +186,7 @@ impl Ledger {
                     26661,
                 ),
                 instrumentation_namespace: "namadan_tm".to_string(),
+                remote_signer: None,
             },
         }
     }
@@ -10,6 +10,7 @@ pub mod client;
 pub mod config;
 pub mod logging;
 pub mod node;
+pub mod remote_signer;
 pub mod wallet;
 pub mod wasm_loader;
 
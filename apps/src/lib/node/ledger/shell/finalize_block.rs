@@ -3,11 +3,14 @@
 use namada::ledger::pos::namada_proof_of_stake;
 use namada::ledger::pos::types::into_tm_voting_power;
 use namada::ledger::protocol;
-use namada::ledger::storage_api::StorageRead;
+use namada::types::event::TX_HASH_ATTRIBUTE;
 use namada::types::storage::{BlockHash, BlockResults, Header};
 use namada::types::token::Amount;
 
-use super::governance::execute_governance_proposals;
+use super::governance::{
+    execute_governance_proposals, execute_offline_tallies,
+    execute_slash_fund_disbursements,
+};
 use super::*;
 use crate::facade::tendermint_proto::abci::Misbehavior as Evidence;
 use crate::facade::tendermint_proto::crypto::PublicKey as TendermintPublicKey;
@@ -47,8 +50,12 @@ where
 
         let mut response = shim::response::FinalizeBlock::default();
         // begin the next block and check if a new epoch began
-        let (height, new_epoch) =
-            self.update_state(req.header, req.hash, req.byzantine_validators);
+        let (height, new_epoch) = self.update_state(
+            req.header,
+            req.hash,
+            req.proposer_address,
+            req.byzantine_validators,
+        );
 
         let current_epoch = self.wl_storage.storage.block.epoch;
 
@@ -59,6 +66,7 @@ where
 
             let _proposals_result =
                 execute_governance_proposals(self, &mut response)?;
+            execute_slash_fund_disbursements(self)?;
 
             // Copy the new_epoch + pipeline_len - 1 validator set into
             // new_epoch + pipeline_len
@@ -73,7 +81,9 @@ where
             )?;
         }
 
-        let wrapper_fees = self.get_wrapper_tx_fees();
+        // the gas schedule may have been updated by a governance proposal
+        self.gas_meter.set_schedule(self.get_gas_schedule());
+
         let mut stats = InternalStats::default();
 
         // Tracks the accepted transactions
@@ -145,69 +155,77 @@ where
                 tx_event["gas_used"] = "0".into();
                 response.events.push(tx_event);
                 // if the rejected tx was decrypted, remove it
-                // from the queue of txs to be processed
+                // from the queue of txs to be processed and its wrapper pays
+                // the full fee
                 if let TxType::Decrypted(_) = &tx_type {
-                    self.wl_storage.storage.tx_queue.pop();
+                    if let Some(wrapper) =
+                        self.wl_storage.storage.tx_queue.pop()
+                    {
+                        self.settle_wrapper_fee(&wrapper, Amount::default());
+                    }
                 }
                 continue;
             }
 
+            // The wrapper of a decrypted tx, whose fee is settled once the
+            // decrypted tx is applied
+            let mut queued_wrapper = None;
+            // Whether the unused gas of a decrypted tx is refunded
+            let mut refund_unused_gas = false;
             let mut tx_event = match &tx_type {
                 TxType::Wrapper(wrapper) => {
                     let mut tx_event = Event::new_tx_event(&tx_type, height.0);
 
+                    // The fee payer's sponsorship policy may have changed
+                    // since the proposal was processed, so it's checked again
+                    // before the fee is charged
+                    if let Err(msg) = self.validate_fee_payer(wrapper) {
+                        tx_event["info"] = msg;
+                        tx_event["code"] = ErrorCodes::InvalidTx.into();
+                        tx_event["gas_used"] = "0".to_string();
+
+                        response.events.push(tx_event);
+                        continue;
+                    }
+
                     #[cfg(not(feature = "mainnet"))]
                     let has_valid_pow =
                         self.invalidate_pow_solution_if_valid(wrapper);
+                    #[cfg(feature = "mainnet")]
+                    let has_valid_pow = false;
 
-                    // Charge fee
-                    let fee_payer =
-                        if wrapper.pk != address::masp_tx_key().ref_to() {
-                            wrapper.fee_payer()
+                    // Charge the fee for the whole gas limit. It's held in the
+                    // fee pool until the gas used by the inner tx is known. In
+                    // testnets, tx with a valid PoW is not charged.
+                    if !has_valid_pow {
+                        let fee_payer = wrapper_fee_payer(wrapper);
+                        let balance =
+                            self.get_balance(&wrapper.fee.token, &fee_payer);
+                        let fee = if balance < wrapper.fee.amount {
+                            balance
                         } else {
-                            address::masp()
+                            wrapper.fee.amount
                         };
+                        storage_api::token::transfer(
+                            &mut self.wl_storage,
+                            &wrapper.fee.token,
+                            &fee_payer,
+                            &address::FEE_POOL,
+                            fee,
+                        )
+                        .expect("Must be able to transfer the fee");
 
-                    let balance_key =
-                        token::balance_key(&wrapper.fee.token, &fee_payer);
-                    let balance: token::Amount = self
-                        .wl_storage
-                        .read(&balance_key)
-                        .expect("must be able to read")
-                        .unwrap_or_default();
+                        if fee < wrapper.fee.amount {
+                            // The remaining funds are paid to the block
+                            // proposer
+                            self.pay_fee_to_proposer(&wrapper.fee.token, fee);
+                            tx_event["info"] =
+                                "Insufficient balance for fee".into();
+                            tx_event["code"] = ErrorCodes::InvalidTx.into();
+                            tx_event["gas_used"] = "0".to_string();
 
-                    match balance.checked_sub(wrapper_fees) {
-                        Some(amount) => {
-                            self.wl_storage
-                                .storage
-                                .write(
-                                    &balance_key,
-                                    amount.try_to_vec().unwrap(),
-                                )
-                                .unwrap();
-                        }
-                        None => {
-                            #[cfg(not(feature = "mainnet"))]
-                            let reject = !has_valid_pow;
-                            #[cfg(feature = "mainnet")]
-                            let reject = true;
-                            if reject {
-                                // Burn remaining funds
-                                self.wl_storage
-                                    .storage
-                                    .write(
-                                        &balance_key,
-                                        Amount::from(0).try_to_vec().unwrap(),
-                                    )
-                                    .unwrap();
-                                tx_event["info"] =
-                                    "Insufficient balance for fee".into();
-                                tx_event["code"] = ErrorCodes::InvalidTx.into();
-                                tx_event["gas_used"] = "0".to_string();
-
-                                response.events.push(tx_event);
-                                continue;
-                            }
+                            response.events.push(tx_event);
+                            continue;
                         }
                     }
 
@@ -220,7 +238,7 @@ where
                 }
                 TxType::Decrypted(inner) => {
                     // We remove the corresponding wrapper tx from the queue
-                    self.wl_storage.storage.tx_queue.pop();
+                    queued_wrapper = self.wl_storage.storage.tx_queue.pop();
                     let mut event = Event::new_tx_event(&tx_type, height.0);
 
                     match inner {
@@ -232,6 +250,14 @@ where
                                 namada::core::types::hash::Hash(tx.code_hash())
                                     .to_string(),
                             );
+                            // The inner tx is aborted once it exceeds the gas
+                            // limit of its wrapper
+                            if let Some(wrapper) = &queued_wrapper {
+                                self.gas_meter.set_transaction_gas_limit(
+                                    u64::from(&wrapper.tx.gas_limit),
+                                );
+                            }
+                            refund_unused_gas = true;
                         }
                         DecryptedTx::Undecryptable(_) => {
                             event["log"] =
@@ -258,6 +284,15 @@ where
                 }
             };
 
+            // A third-party fee payer of the wrapper must also accept the
+            // decrypted tx
+            let fee_payer = queued_wrapper.as_ref().and_then(|wrapper| {
+                wrapper
+                    .tx
+                    .fee_payer
+                    .as_ref()
+                    .map(|fee_payer| fee_payer.address.clone())
+            });
             match protocol::apply_tx(
                 tx_type,
                 tx_length,
@@ -266,6 +301,7 @@ where
                         .try_into()
                         .expect("transaction index out of bounds"),
                 ),
+                fee_payer.as_ref(),
                 &mut self.gas_meter,
                 &mut self.wl_storage.write_log,
                 &self.wl_storage.storage,
@@ -275,6 +311,16 @@ where
             .map_err(Error::TxApply)
             {
                 Ok(result) => {
+                    if let Some(wrapper) = &queued_wrapper {
+                        // A third-party fee payer whose VP rejected the tx
+                        // still pays for the block gas that the tx used
+                        let refund = if refund_unused_gas {
+                            wrapper.tx.fee_refund(result.gas_used)
+                        } else {
+                            Amount::default()
+                        };
+                        self.settle_wrapper_fee(wrapper, refund);
+                    }
                     if result.is_accepted() {
                         tracing::trace!(
                             "all VPs accepted transaction {} storage \
@@ -292,11 +338,27 @@ where
                                 .results
                                 .accept(tx_index);
                         }
-                        if let Some(ibc_event) = &result.ibc_event {
-                            // Add the IBC event besides the tx_event
-                            let event = Event::from(ibc_event.clone());
-                            response.events.push(event);
-                        }
+                        // Add the IBC events besides the tx_event, including
+                        // those of the calls of a batch tx
+                        let ibc_events = result.ibc_event.iter().chain(
+                            result
+                                .batch_calls
+                                .iter()
+                                .filter_map(|call| call.ibc_event.as_ref()),
+                        );
+                        response
+                            .events
+                            .extend(ibc_events.cloned().map(Event::from));
+                        // Add the custom events emitted by the tx with its
+                        // hash
+                        response.events.extend(result.events.iter().map(
+                            |event| {
+                                let mut event = Event::from(event.clone());
+                                event[TX_HASH_ATTRIBUTE] =
+                                    tx_event["hash"].clone();
+                                event
+                            },
+                        ));
                         match serde_json::to_string(
                             &result.initialized_accounts,
                         ) {
@@ -323,6 +385,20 @@ where
                         self.wl_storage.drop_tx();
                         tx_event["code"] = ErrorCodes::InvalidTx.into();
                     }
+                    // Add an event with the result of each applied call of a
+                    // batch tx
+                    for (index, call) in result.batch_calls.iter().enumerate() {
+                        let mut call_event =
+                            Event::new_batch_call_event(&tx_event, index);
+                        call_event["code"] = if call.is_accepted() {
+                            ErrorCodes::Ok.into()
+                        } else {
+                            ErrorCodes::InvalidTx.into()
+                        };
+                        call_event["gas_used"] = call.gas_used.to_string();
+                        call_event["info"] = call.to_string();
+                        response.events.push(call_event);
+                    }
                     tx_event["gas_used"] = result.gas_used.to_string();
                     tx_event["info"] = result.to_string();
                 }
@@ -334,10 +410,19 @@ where
                     );
                     stats.increment_errored_txs();
                     self.wl_storage.drop_tx();
-                    tx_event["gas_used"] = self
-                        .gas_meter
-                        .get_current_transaction_gas()
-                        .to_string();
+                    let gas_used = self.gas_meter.get_current_transaction_gas();
+                    // Add the gas to the block and reset the transaction meter
+                    // and its gas limit
+                    let _ = self.gas_meter.finalize_transaction();
+                    if let Some(wrapper) = &queued_wrapper {
+                        let refund = if refund_unused_gas {
+                            wrapper.tx.fee_refund(gas_used)
+                        } else {
+                            Amount::default()
+                        };
+                        self.settle_wrapper_fee(wrapper, refund);
+                    }
+                    tx_event["gas_used"] = gas_used.to_string();
                     tx_event["info"] = msg.to_string();
                     tx_event["code"] = ErrorCodes::WasmRuntimeError.into();
                 }
@@ -345,6 +430,8 @@ where
             response.events.push(tx_event);
         }
 
+        execute_offline_tallies(self, &mut response)?;
+
         stats.set_tx_cache_size(
             self.tx_wasm_cache.get_size(),
             self.tx_wasm_cache.get_cache_size(),
@@ -372,8 +459,51 @@ where
         Ok(response)
     }
 
+    /// Settle the fee of a wrapper tx held in the fee pool once its decrypted
+    /// tx has been applied. The refund is returned to the fee payer and the
+    /// rest of the fee is paid to the block proposer. In testnets, tx with a
+    /// valid PoW has not been charged.
+    fn settle_wrapper_fee(
+        &mut self,
+        wrapper: &WrapperTxInQueue,
+        refund: token::Amount,
+    ) {
+        #[cfg(not(feature = "mainnet"))]
+        if wrapper.has_valid_pow {
+            return;
+        }
+        let WrapperTxInQueue { tx: wrapper, .. } = wrapper;
+        storage_api::token::transfer(
+            &mut self.wl_storage,
+            &wrapper.fee.token,
+            &address::FEE_POOL,
+            &wrapper_fee_payer(wrapper),
+            refund,
+        )
+        .expect("Must be able to refund the fee");
+        self.pay_fee_to_proposer(
+            &wrapper.fee.token,
+            wrapper.fee.amount - refund,
+        );
+    }
+
+    /// Pay the given amount of fee from the fee pool to the proposer of the
+    /// current block. If the proposer is not known, the fee stays in the pool.
+    fn pay_fee_to_proposer(&mut self, token: &Address, amount: token::Amount) {
+        if let Some(proposer) = self.wl_storage.storage.block_proposer.clone() {
+            storage_api::token::transfer(
+                &mut self.wl_storage,
+                token,
+                &address::FEE_POOL,
+                &proposer,
+                amount,
+            )
+            .expect("Must be able to pay the fee to the block proposer");
+        }
+    }
+
     /// Sets the metadata necessary for a new block, including
-    /// the hash, height, validator changes, and evidence of
+    /// the hash, height, proposer, validator changes, and evidence of
     /// byzantine behavior. Applies slashes if necessary.
     /// Returns a bool indicating if a new epoch began and
     /// the height of the new block.
@@ -381,6 +511,7 @@ where
         &mut self,
         header: Header,
         hash: BlockHash,
+        proposer_address: Vec<u8>,
         byzantine_validators: Vec<Evidence>,
     ) -> (BlockHeight, bool) {
         let height = self.wl_storage.storage.last_height + 1;
@@ -398,6 +529,17 @@ where
             .set_header(header)
             .expect("Setting a header shouldn't fail");
 
+        let proposer = if proposer_address.is_empty() {
+            None
+        } else {
+            proof_of_stake::find_validator_by_raw_hash(
+                &self.wl_storage,
+                tm_raw_hash_to_string(proposer_address),
+            )
+            .expect("Must be able to read storage")
+        };
+        self.wl_storage.storage.block_proposer = proposer;
+
         self.byzantine_validators = byzantine_validators;
 
         let new_epoch = self
@@ -463,15 +605,33 @@ mod test_finalize_block {
     use std::collections::BTreeMap;
     use std::str::FromStr;
 
+    use namada::core::ledger::slash_fund::{
+        storage as slash_fund_storage, ADDRESS as slash_fund_address,
+    };
+    use namada::ledger::events::EventType;
+    use namada::ledger::governance::{
+        storage as gov_storage, ADDRESS as gov_address,
+    };
+    use namada::ledger::native_vp::governance::utils::{
+        get_proposal_status, is_expedited,
+    };
+    use namada::ledger::parameters::storage::get_wasm_code_key;
     use namada::ledger::parameters::EpochDuration;
-    use namada::ledger::storage_api;
-    use namada::types::governance::ProposalVote;
+    use namada::ledger::storage_api::{self, StorageWrite};
+    use namada::types::governance::{
+        DisbursementSchedule, OfflineProposal, OfflineVote, ProposalResult,
+        ProposalStatus, ProposalVote, SlashFundDisbursement, TallyResult,
+    };
+    use namada::types::hash::Hash;
     use namada::types::storage::Epoch;
     use namada::types::time::DurationSecs;
     use namada::types::transaction::governance::{
-        InitProposalData, VoteProposalData,
+        InitProposalData, TallyOfflineProposalData, VoteProposalData,
+    };
+    use namada::types::transaction::{
+        EncryptionKey, Fee, WrapperTx, GAS_LIMIT_RESOLUTION, MIN_FEE,
     };
-    use namada::types::transaction::{EncryptionKey, Fee, WrapperTx, MIN_FEE};
+    use rust_decimal::Decimal;
 
     use super::*;
     use crate::node::ledger::shell::test_utils::*;
@@ -643,6 +803,7 @@ mod test_finalize_block {
                 token: shell.wl_storage.storage.native_token.clone(),
             },
             pk: keypair.ref_to(),
+            fee_payer: None,
             epoch: Epoch(0),
             gas_limit: 0.into(),
             inner_tx,
@@ -723,7 +884,7 @@ mod test_finalize_block {
                 },
                 &keypair,
                 Epoch(0),
-                0.into(),
+                GAS_LIMIT_RESOLUTION.into(),
                 raw_tx.clone(),
                 Default::default(),
                 #[cfg(not(feature = "mainnet"))]
@@ -824,6 +985,383 @@ mod test_finalize_block {
         assert_eq!(counter, 2);
     }
 
+    /// Test that the fee for the unused gas of a decrypted tx is refunded to
+    /// the fee payer and that the rest of the fee is kept in the fee pool
+    /// when the block proposer is not known.
+    #[test]
+    fn test_decrypted_tx_fee_refund() {
+        let (mut shell, _) = setup();
+        let keypair = gen_keypair();
+        let fee_payer = Address::from(&keypair.ref_to());
+        let native_token = shell.wl_storage.storage.native_token.clone();
+
+        let mut wasm_path = top_level_directory();
+        wasm_path.push("wasm_for_tests/tx_no_op.wasm");
+        let tx_code = std::fs::read(wasm_path)
+            .expect("Expected a file at given code path");
+        let raw_tx =
+            Tx::new(tx_code, Some("transaction data".as_bytes().to_owned()));
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: Amount::whole(1),
+                token: native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            GAS_LIMIT_RESOLUTION.into(),
+            raw_tx.clone(),
+            Default::default(),
+            #[cfg(not(feature = "mainnet"))]
+            None,
+        );
+        shell.enqueue_tx(wrapper.clone());
+        let processed_tx = ProcessedTx {
+            tx: Tx::from(TxType::Decrypted(DecryptedTx::Decrypted {
+                tx: raw_tx,
+                #[cfg(not(feature = "mainnet"))]
+                has_valid_pow: false,
+            }))
+            .to_bytes(),
+            result: TxResult {
+                code: ErrorCodes::Ok.into(),
+                info: "".into(),
+            },
+        };
+
+        let event = shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed")
+            .remove(0);
+        let code = event.attributes.get("code").expect("Test failed");
+        assert_eq!(code, &String::from(ErrorCodes::Ok));
+        let gas_used: u64 = event
+            .attributes
+            .get("gas_used")
+            .expect("Test failed")
+            .parse()
+            .expect("Test failed");
+
+        let refund = wrapper.fee_refund(gas_used);
+        assert!(refund > Amount::default());
+        assert!(refund < wrapper.fee.amount);
+        assert_eq!(shell.get_balance(&native_token, &fee_payer), refund);
+        assert_eq!(
+            shell.get_balance(&native_token, &address::FEE_POOL),
+            wrapper.fee.amount - refund
+        );
+    }
+
+    /// Test that a third-party fee payer whose VP rejects the decrypted tx
+    /// pays for the gas used by the tx and only gets the fee for the unused
+    /// gas back.
+    #[test]
+    fn test_rejected_sponsorship_fee_refund() {
+        let (mut shell, _) = setup();
+        let keypair = gen_keypair();
+        let fee_payer_keypair = gen_keypair();
+        let fee_payer = address::testing::established_address_1();
+        let native_token = shell.wl_storage.storage.native_token.clone();
+
+        let mut wasm_path = top_level_directory();
+        wasm_path.push("wasm_for_tests/vp_always_false.wasm");
+        let vp_code = std::fs::read(wasm_path)
+            .expect("Expected a file at given code path");
+        shell
+            .wl_storage
+            .storage
+            .write(&Key::validity_predicate(&fee_payer), vp_code)
+            .expect("Test failed");
+
+        let mut wasm_path = top_level_directory();
+        wasm_path.push("wasm_for_tests/tx_no_op.wasm");
+        let tx_code = std::fs::read(wasm_path)
+            .expect("Expected a file at given code path");
+        let raw_tx =
+            Tx::new(tx_code, Some("transaction data".as_bytes().to_owned()));
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: Amount::whole(1),
+                token: native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            GAS_LIMIT_RESOLUTION.into(),
+            raw_tx.clone(),
+            Default::default(),
+            #[cfg(not(feature = "mainnet"))]
+            None,
+        )
+        .sign_fee_payer(fee_payer.clone(), &fee_payer_keypair);
+        shell.enqueue_tx(wrapper.clone());
+        let processed_tx = ProcessedTx {
+            tx: Tx::from(TxType::Decrypted(DecryptedTx::Decrypted {
+                tx: raw_tx,
+                #[cfg(not(feature = "mainnet"))]
+                has_valid_pow: false,
+            }))
+            .to_bytes(),
+            result: TxResult {
+                code: ErrorCodes::Ok.into(),
+                info: "".into(),
+            },
+        };
+
+        let event = shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed")
+            .remove(0);
+        let code = event.attributes.get("code").expect("Test failed");
+        assert_eq!(code, &String::from(ErrorCodes::InvalidTx));
+        let gas_used: u64 = event
+            .attributes
+            .get("gas_used")
+            .expect("Test failed")
+            .parse()
+            .expect("Test failed");
+        assert!(gas_used > 0);
+
+        let refund = wrapper.fee_refund(gas_used);
+        assert!(refund < wrapper.fee.amount);
+        assert_eq!(shell.get_balance(&native_token, &fee_payer), refund);
+        assert_eq!(
+            shell.get_balance(&native_token, &address::FEE_POOL),
+            wrapper.fee.amount - refund
+        );
+    }
+
+    /// Test that the fee of a wrapper tx isn't charged from a third-party fee
+    /// payer that doesn't sponsor the wrapper's signer and that the wrapper
+    /// isn't queued for decryption.
+    #[test]
+    fn test_unsponsored_wrapper_not_charged() {
+        let (mut shell, _) = setup();
+        let keypair = gen_keypair();
+        let fee_payer_keypair = gen_keypair();
+        let fee_payer = Address::from(&fee_payer_keypair.ref_to());
+        let native_token = shell.wl_storage.storage.native_token.clone();
+        let balance = Amount::whole(1_000);
+        storage_api::token::credit_tokens(
+            &mut shell.wl_storage,
+            &native_token,
+            &fee_payer,
+            balance,
+        )
+        .expect("Test failed");
+        // The fee payer only sponsors another user
+        let sponsored_users = BTreeSet::from([gen_keypair().ref_to()]);
+        shell
+            .wl_storage
+            .storage
+            .write(
+                &sponsor::sponsored_users_key(&fee_payer),
+                sponsored_users.try_to_vec().unwrap(),
+            )
+            .expect("Test failed");
+
+        let raw_tx = Tx::new(
+            "wasm_code".as_bytes().to_owned(),
+            Some("transaction data".as_bytes().to_owned()),
+        );
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: Amount::whole(1),
+                token: native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            GAS_LIMIT_RESOLUTION.into(),
+            raw_tx,
+            Default::default(),
+            #[cfg(not(feature = "mainnet"))]
+            None,
+        )
+        .sign_fee_payer(fee_payer.clone(), &fee_payer_keypair);
+        let tx = wrapper.sign(&keypair).expect("Test failed");
+        let processed_tx = ProcessedTx {
+            tx: tx.to_bytes(),
+            result: TxResult {
+                code: ErrorCodes::Ok.into(),
+                info: "".into(),
+            },
+        };
+
+        let event = shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed")
+            .remove(0);
+        let code = event.attributes.get("code").expect("Test failed");
+        assert_eq!(code, &String::from(ErrorCodes::InvalidTx));
+
+        assert_eq!(shell.get_balance(&native_token, &fee_payer), balance);
+        assert!(shell.wl_storage.storage.tx_queue.is_empty());
+    }
+
+    /// Test that the calls of a batch tx are applied in order with an event
+    /// for each call and that the batch fails as a whole when any of its
+    /// calls fails.
+    #[test]
+    fn test_batch_tx() {
+        let (mut shell, _) = setup();
+        let keypair = gen_keypair();
+        let native_token = shell.wl_storage.storage.native_token.clone();
+
+        let read_wasm = |name: &str| {
+            let mut wasm_path = top_level_directory();
+            wasm_path.push("wasm_for_tests");
+            wasm_path.push(name);
+            std::fs::read(wasm_path)
+                .expect("Expected a file at given code path")
+        };
+        let no_op = Tx::new(read_wasm("tx_no_op.wasm"), None);
+        // reading a missing key fails
+        let missing_key = Key::parse("missing").expect("Test failed");
+        let read_missing_key = Tx::new(
+            read_wasm("tx_read_storage_key.wasm"),
+            Some(missing_key.try_to_vec().expect("Test failed")),
+        );
+
+        let mut finalize_batch = |calls: Vec<Tx>| {
+            let batch = Tx::new_batch(calls);
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 0.into(),
+                    token: native_token.clone(),
+                },
+                &keypair,
+                Epoch(0),
+                GAS_LIMIT_RESOLUTION.into(),
+                batch.clone(),
+                Default::default(),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            shell.enqueue_tx(wrapper);
+            let processed_tx = ProcessedTx {
+                tx: Tx::from(TxType::Decrypted(DecryptedTx::Decrypted {
+                    tx: batch,
+                    #[cfg(not(feature = "mainnet"))]
+                    has_valid_pow: false,
+                }))
+                .to_bytes(),
+                result: TxResult {
+                    code: ErrorCodes::Ok.into(),
+                    info: "".into(),
+                },
+            };
+            shell
+                .finalize_block(FinalizeBlock {
+                    txs: vec![processed_tx],
+                    ..Default::default()
+                })
+                .expect("Test failed")
+        };
+
+        // all the calls are applied
+        let events = finalize_batch(vec![no_op.clone(), no_op.clone()]);
+        assert_eq!(events.len(), 3);
+        for (index, event) in events[..2].iter().enumerate() {
+            assert_eq!(event.event_type, EventType::BatchCall);
+            assert_eq!(event["index"], index.to_string());
+            assert_eq!(event["code"], String::from(ErrorCodes::Ok));
+        }
+        let calls_gas: u64 = events[..2]
+            .iter()
+            .map(|event| event["gas_used"].parse::<u64>().expect("Test failed"))
+            .sum();
+        let event = &events[2];
+        assert_eq!(event.event_type, EventType::Applied);
+        assert_eq!(event["code"], String::from(ErrorCodes::Ok));
+        let gas_used: u64 = event["gas_used"].parse().expect("Test failed");
+        assert!(gas_used > calls_gas);
+
+        // a failing call fails the whole batch
+        let events = finalize_batch(vec![no_op, read_missing_key]);
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.event_type, EventType::Applied);
+        assert_eq!(event["code"], String::from(ErrorCodes::WasmRuntimeError));
+    }
+
+    /// Test that a tx referring to its code by hash runs the code stored
+    /// on-chain under that hash, and that it fails without affecting the
+    /// block when no code is stored under it.
+    #[test]
+    fn test_tx_code_hash() {
+        let (mut shell, _) = setup();
+        let keypair = gen_keypair();
+        let native_token = shell.wl_storage.storage.native_token.clone();
+
+        let mut wasm_path = top_level_directory();
+        wasm_path.push("wasm_for_tests/tx_no_op.wasm");
+        let tx_code = std::fs::read(wasm_path)
+            .expect("Expected a file at given code path");
+        let code_hash = Hash::sha256(&tx_code);
+        shell
+            .wl_storage
+            .write_bytes(&get_wasm_code_key(&code_hash), tx_code)
+            .expect("Test failed");
+        shell.wl_storage.commit_block().expect("Test failed");
+
+        let mut finalize_tx = |raw_tx: Tx| {
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 0.into(),
+                    token: native_token.clone(),
+                },
+                &keypair,
+                Epoch(0),
+                GAS_LIMIT_RESOLUTION.into(),
+                raw_tx.clone(),
+                Default::default(),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            shell.enqueue_tx(wrapper);
+            let processed_tx = ProcessedTx {
+                tx: Tx::from(TxType::Decrypted(DecryptedTx::Decrypted {
+                    tx: raw_tx,
+                    #[cfg(not(feature = "mainnet"))]
+                    has_valid_pow: false,
+                }))
+                .to_bytes(),
+                result: TxResult {
+                    code: ErrorCodes::Ok.into(),
+                    info: "".into(),
+                },
+            };
+            shell
+                .finalize_block(FinalizeBlock {
+                    txs: vec![processed_tx],
+                    ..Default::default()
+                })
+                .expect("Test failed")
+                .remove(0)
+        };
+
+        // the stored code is applied
+        let event = finalize_tx(Tx::new_with_code_hash(code_hash, None));
+        assert_eq!(event.event_type, EventType::Applied);
+        assert_eq!(event["code"], String::from(ErrorCodes::Ok));
+
+        // a tx referring to a missing code fails
+        let missing_hash = Hash::sha256(b"missing code");
+        let event =
+            finalize_tx(Tx::new_with_code_hash(missing_hash.clone(), None));
+        assert_eq!(event.event_type, EventType::Applied);
+        assert_eq!(event["code"], String::from(ErrorCodes::WasmRuntimeError));
+        assert!(event["info"].contains(&missing_hash.to_string()));
+        assert!(shell.wl_storage.storage.tx_queue.is_empty());
+    }
+
     /// Test that the finalize block handler never commits changes directly to
     /// the DB.
     #[test]
@@ -855,6 +1393,8 @@ mod test_finalize_block {
                 voting_end_epoch: Epoch::default().next(),
                 grace_epoch: Epoch::default().next(),
                 proposal_code: None,
+                expedited: false,
+                slash_fund_disbursements: vec![],
             };
             storage_api::governance::init_proposal(
                 &mut shell.wl_storage,
@@ -915,4 +1455,522 @@ mod test_finalize_block {
             last_storage_state = store_block_state(&shell);
         }
     }
+
+    /// Finalize an empty block that begins a new epoch and commit it
+    fn finalize_new_epoch(shell: &mut TestShell) -> Vec<Event> {
+        shell.wl_storage.storage.next_epoch_min_start_height =
+            shell.wl_storage.storage.last_height + 1;
+        shell.wl_storage.storage.next_epoch_min_start_time = DateTimeUtc::now();
+        let response = shell.finalize_block(FinalizeBlock::default()).unwrap();
+        shell.wl_storage.commit_block().unwrap();
+        response.events
+    }
+
+    /// Init a proposal authored by the only validator and voting from the
+    /// genesis epoch until the next one, with the validator's vote, if any
+    fn init_voted_proposal(
+        shell: &mut TestShell,
+        proposal_id: u64,
+        vote: Option<ProposalVote>,
+        expedited: bool,
+        grace_epoch: Epoch,
+    ) {
+        let validator = shell.mode.get_validator_address().unwrap().clone();
+        let proposal = InitProposalData {
+            id: Some(proposal_id),
+            content: vec![],
+            author: validator.clone(),
+            voting_start_epoch: Epoch::default(),
+            voting_end_epoch: Epoch::default().next(),
+            grace_epoch,
+            proposal_code: None,
+            expedited,
+            slash_fund_disbursements: vec![],
+        };
+        storage_api::governance::init_proposal(&mut shell.wl_storage, proposal)
+            .unwrap();
+        if let Some(vote) = vote {
+            let vote = VoteProposalData {
+                id: proposal_id,
+                vote,
+                voter: validator,
+                delegations: vec![],
+            };
+            storage_api::governance::vote_proposal(&mut shell.wl_storage, vote)
+                .unwrap();
+        }
+    }
+
+    /// Test that an expedited proposal meeting its threshold is accepted at
+    /// its grace epoch
+    #[test]
+    fn test_expedited_proposal_passes() {
+        let (mut shell, _) = setup();
+        init_voted_proposal(
+            &mut shell,
+            0,
+            Some(ProposalVote::Yay),
+            true,
+            Epoch(1),
+        );
+        shell.wl_storage.commit_block().unwrap();
+
+        shell.proposal_data.insert(0);
+        let events = finalize_new_epoch(&mut shell);
+
+        let status: Option<ProposalStatus> = shell
+            .wl_storage
+            .read(&gov_storage::get_proposal_status_key(0))
+            .unwrap();
+        assert_eq!(status, Some(ProposalStatus::Passed));
+        assert!(is_expedited(&shell.wl_storage, 0).unwrap());
+        assert!(events.iter().any(|event| {
+            event.event_type == EventType::Proposal
+                && event.get("proposal_id") == Some(&"0".to_string())
+                && event.get("tally_result")
+                    == Some(&TallyResult::Passed.to_string())
+        }));
+    }
+
+    /// Test that an expedited proposal missing its threshold is downgraded
+    /// to a normal proposal with an extended voting period
+    #[test]
+    fn test_expedited_proposal_downgraded() {
+        let (mut shell, _) = setup();
+        init_voted_proposal(
+            &mut shell,
+            0,
+            Some(ProposalVote::Nay),
+            true,
+            Epoch(1),
+        );
+        shell.wl_storage.commit_block().unwrap();
+        let min_period: u64 = shell
+            .wl_storage
+            .read(&gov_storage::get_min_proposal_period_key())
+            .unwrap()
+            .unwrap();
+        let min_grace_epochs: u64 = shell
+            .wl_storage
+            .read(&gov_storage::get_min_proposal_grace_epoch_key())
+            .unwrap()
+            .unwrap();
+
+        shell.proposal_data.insert(0);
+        let events = finalize_new_epoch(&mut shell);
+        assert!(
+            !events
+                .iter()
+                .any(|event| event.event_type == EventType::Proposal)
+        );
+
+        // The proposal goes on as a normal one
+        let end_epoch: Epoch = shell
+            .wl_storage
+            .read(&gov_storage::get_voting_end_epoch_key(0))
+            .unwrap()
+            .unwrap();
+        let grace_epoch: Epoch = shell
+            .wl_storage
+            .read(&gov_storage::get_grace_epoch_key(0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(end_epoch, Epoch(min_period));
+        assert_eq!(grace_epoch, Epoch(min_period + min_grace_epochs));
+        assert!(
+            shell
+                .wl_storage
+                .has_key(&gov_storage::get_committing_proposals_key(
+                    0,
+                    grace_epoch.0
+                ))
+                .unwrap()
+        );
+        assert!(!is_expedited(&shell.wl_storage, 0).unwrap());
+        assert!(
+            !shell
+                .wl_storage
+                .has_key(&gov_storage::get_proposal_status_key(0))
+                .unwrap()
+        );
+        let current_epoch = shell.wl_storage.storage.block.epoch;
+        assert_eq!(
+            get_proposal_status(&shell.wl_storage, current_epoch, 0).unwrap(),
+            Some(ProposalStatus::Voting)
+        );
+    }
+
+    /// Test that an expedited proposal missing its threshold is rejected if
+    /// its voting period can't be extended within the maximum proposal
+    /// period
+    #[test]
+    fn test_expedited_proposal_downgrade_beyond_max_period() {
+        let (mut shell, _) = setup();
+        let min_period: u64 = shell
+            .wl_storage
+            .read(&gov_storage::get_min_proposal_period_key())
+            .unwrap()
+            .unwrap();
+        shell
+            .wl_storage
+            .write(&gov_storage::get_max_proposal_period_key(), min_period)
+            .unwrap();
+        init_voted_proposal(
+            &mut shell,
+            0,
+            Some(ProposalVote::Nay),
+            true,
+            Epoch(min_period),
+        );
+        shell.wl_storage.commit_block().unwrap();
+
+        // Move on to the epoch before the grace epoch
+        for _ in 1..min_period {
+            finalize_new_epoch(&mut shell);
+        }
+        let current_epoch = shell.wl_storage.storage.block.epoch;
+        assert_eq!(
+            get_proposal_status(&shell.wl_storage, current_epoch, 0).unwrap(),
+            Some(ProposalStatus::Rejected)
+        );
+
+        shell.proposal_data.insert(0);
+        finalize_new_epoch(&mut shell);
+
+        let status: Option<ProposalStatus> = shell
+            .wl_storage
+            .read(&gov_storage::get_proposal_status_key(0))
+            .unwrap();
+        assert_eq!(status, Some(ProposalStatus::Rejected));
+        let end_epoch: Epoch = shell
+            .wl_storage
+            .read(&gov_storage::get_voting_end_epoch_key(0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(end_epoch, Epoch(1));
+    }
+
+    /// Read the native token balance of the given owner
+    fn read_native_balance(shell: &TestShell, owner: &Address) -> Amount {
+        let balance_key =
+            token::balance_key(&shell.wl_storage.storage.native_token, owner);
+        shell
+            .wl_storage
+            .read(&balance_key)
+            .unwrap()
+            .unwrap_or_default()
+    }
+
+    /// Finalize a proposal with the given vote of the only validator and
+    /// check its tally result and the amount refunded to its author
+    fn finalize_tallied_proposal(
+        vote: Option<ProposalVote>,
+        expected_result: TallyResult,
+        expected_burn_rate: impl Fn(&TestShell) -> Decimal,
+    ) {
+        let (mut shell, _) = setup();
+        init_voted_proposal(&mut shell, 0, vote, false, Epoch(1));
+        shell.wl_storage.commit_block().unwrap();
+        let author = shell.mode.get_validator_address().unwrap().clone();
+        let funds: Amount = shell
+            .wl_storage
+            .read(&gov_storage::get_funds_key(0))
+            .unwrap()
+            .unwrap();
+        let burn_rate = expected_burn_rate(&shell);
+        let author_balance = read_native_balance(&shell, &author);
+        let gov_balance = read_native_balance(&shell, &gov_address);
+
+        shell.proposal_data.insert(0);
+        let events = finalize_new_epoch(&mut shell);
+
+        assert!(events.iter().any(|event| {
+            event.event_type == EventType::Proposal
+                && event.get("proposal_id") == Some(&"0".to_string())
+                && event.get("tally_result")
+                    == Some(&expected_result.to_string())
+        }));
+        let status: Option<ProposalStatus> = shell
+            .wl_storage
+            .read(&gov_storage::get_proposal_status_key(0))
+            .unwrap();
+        assert_eq!(status, Some(ProposalStatus::Rejected));
+
+        let burnt = Amount::from(Decimal::from(funds) * burn_rate);
+        assert_eq!(
+            read_native_balance(&shell, &author),
+            author_balance + (funds - burnt)
+        );
+        assert_eq!(
+            read_native_balance(&shell, &gov_address),
+            gov_balance - funds
+        );
+    }
+
+    /// Test that a vetoed proposal is rejected and its deposit burnt
+    #[test]
+    fn test_vetoed_proposal_burns_deposit() {
+        finalize_tallied_proposal(
+            Some(ProposalVote::Veto),
+            TallyResult::Rejected,
+            |_| Decimal::ONE,
+        );
+    }
+
+    /// Test that a proposal below quorum is rejected and its deposit partially
+    /// burnt
+    #[test]
+    fn test_proposal_below_quorum_burns_part_of_deposit() {
+        finalize_tallied_proposal(None, TallyResult::Rejected, |shell| {
+            shell
+                .wl_storage
+                .read(&gov_storage::get_no_quorum_burn_rate_key())
+                .unwrap()
+                .unwrap()
+        });
+    }
+
+    /// Test that a rejected proposal that reached the quorum gets its deposit
+    /// refunded
+    #[test]
+    fn test_rejected_proposal_with_quorum_refunds_deposit() {
+        finalize_tallied_proposal(
+            Some(ProposalVote::Nay),
+            TallyResult::Rejected,
+            |_| Decimal::ZERO,
+        );
+    }
+
+    /// Test that the slash fund disbursements of an accepted proposal are
+    /// paid from its grace epoch, and that a one-off payment the fund can't
+    /// cover is retried until it can
+    #[test]
+    fn test_slash_fund_disbursements() {
+        let (mut shell, _) = setup();
+        init_voted_proposal(
+            &mut shell,
+            0,
+            Some(ProposalVote::Yay),
+            false,
+            Epoch(1),
+        );
+        let one_off_target = address::testing::established_address_1();
+        let streamed_target = address::testing::established_address_2();
+        let late_target = address::testing::established_address_3();
+        let uncovered_target = address::testing::established_address_4();
+        let disbursements = vec![
+            SlashFundDisbursement {
+                target: one_off_target.clone(),
+                amount: Amount::whole(10),
+                schedule: DisbursementSchedule::OneOff,
+            },
+            SlashFundDisbursement {
+                target: streamed_target.clone(),
+                amount: Amount::whole(5),
+                schedule: DisbursementSchedule::Streamed {
+                    start_epoch: Epoch(1),
+                    end_epoch: Epoch(2),
+                },
+            },
+            // A streamed payment due to start before the proposal is
+            // executed, as for a downgraded expedited proposal
+            SlashFundDisbursement {
+                target: late_target.clone(),
+                amount: Amount::whole(1),
+                schedule: DisbursementSchedule::Streamed {
+                    start_epoch: Epoch(0),
+                    end_epoch: Epoch(1),
+                },
+            },
+            SlashFundDisbursement {
+                target: uncovered_target.clone(),
+                amount: Amount::whole(1000),
+                schedule: DisbursementSchedule::OneOff,
+            },
+        ];
+        shell
+            .wl_storage
+            .write(
+                &gov_storage::get_proposal_disbursements_key(0),
+                disbursements,
+            )
+            .unwrap();
+        let slash_fund_balance_key = token::balance_key(
+            &shell.wl_storage.storage.native_token,
+            &slash_fund_address,
+        );
+        shell
+            .wl_storage
+            .write(&slash_fund_balance_key, Amount::whole(100))
+            .unwrap();
+        shell.wl_storage.commit_block().unwrap();
+
+        // The proposal is accepted and the first payments are made in its
+        // grace epoch
+        shell.proposal_data.insert(0);
+        finalize_new_epoch(&mut shell);
+        assert_eq!(
+            read_native_balance(&shell, &one_off_target),
+            Amount::whole(10)
+        );
+        assert_eq!(
+            read_native_balance(&shell, &streamed_target),
+            Amount::whole(5)
+        );
+        assert_eq!(read_native_balance(&shell, &late_target), Amount::whole(1));
+        assert_eq!(
+            read_native_balance(&shell, &uncovered_target),
+            Amount::default()
+        );
+        assert!(
+            shell
+                .wl_storage
+                .has_key(&slash_fund_storage::get_executed_disbursement_key(
+                    0, 1, 1
+                ))
+                .unwrap()
+        );
+        assert!(
+            !shell
+                .wl_storage
+                .has_key(&slash_fund_storage::get_pending_disbursement_key(
+                    0, 0
+                ))
+                .unwrap()
+        );
+
+        // The streamed payments go on, the uncovered payment is still pending
+        finalize_new_epoch(&mut shell);
+        assert_eq!(
+            read_native_balance(&shell, &one_off_target),
+            Amount::whole(10)
+        );
+        assert_eq!(
+            read_native_balance(&shell, &streamed_target),
+            Amount::whole(10)
+        );
+        assert_eq!(read_native_balance(&shell, &late_target), Amount::whole(2));
+        assert_eq!(
+            read_native_balance(&shell, &uncovered_target),
+            Amount::default()
+        );
+        for index in [1, 2] {
+            assert!(
+                !shell
+                    .wl_storage
+                    .has_key(&slash_fund_storage::get_pending_disbursement_key(
+                        0, index
+                    ))
+                    .unwrap()
+            );
+        }
+        let uncovered_key =
+            slash_fund_storage::get_pending_disbursement_key(0, 3);
+        assert!(shell.wl_storage.has_key(&uncovered_key).unwrap());
+
+        // Once the fund covers it, the pending payment is made
+        shell
+            .wl_storage
+            .write(&slash_fund_balance_key, Amount::whole(1000))
+            .unwrap();
+        shell.wl_storage.commit_block().unwrap();
+        finalize_new_epoch(&mut shell);
+        assert_eq!(
+            read_native_balance(&shell, &uncovered_target),
+            Amount::whole(1000)
+        );
+        assert!(!shell.wl_storage.has_key(&uncovered_key).unwrap());
+        assert_eq!(
+            read_native_balance(&shell, &slash_fund_address),
+            Amount::default()
+        );
+    }
+
+    /// Test that an offline proposal is tallied once its votes submission
+    /// window closes and that it's not tallied again afterwards
+    #[test]
+    fn test_offline_proposal_tallied_once() {
+        let (mut shell, _) = setup();
+        let validator = shell.mode.get_validator_address().unwrap().clone();
+        let signing_key = key::testing::keypair_1();
+        let mut proposal = OfflineProposal {
+            content: BTreeMap::new(),
+            author: validator.clone(),
+            tally_epoch: Epoch::default(),
+            signature: common::SigScheme::sign(&signing_key, []),
+            address: validator.clone(),
+        };
+        proposal.signature =
+            common::SigScheme::sign(&signing_key, proposal.compute_hash());
+        let proposal_hash = proposal.compute_hash();
+        let vote = OfflineVote::new(
+            &proposal,
+            ProposalVote::Yay,
+            validator,
+            &signing_key,
+        );
+        storage_api::governance::tally_offline_proposal(
+            &mut shell.wl_storage,
+            TallyOfflineProposalData {
+                proposal,
+                votes: vec![vote],
+            },
+        )
+        .unwrap();
+        shell.wl_storage.commit_block().unwrap();
+        let pending_key =
+            gov_storage::get_pending_offline_tally_key(&proposal_hash);
+        let close_epoch: Epoch =
+            shell.wl_storage.read(&pending_key).unwrap().unwrap();
+        let result_key =
+            gov_storage::get_offline_proposal_result_key(&proposal_hash);
+
+        let count_tallies = |events: &[Event]| {
+            events
+                .iter()
+                .filter(|event| {
+                    event.event_type == EventType::Proposal
+                        && event.get("offline_proposal_hash")
+                            == Some(&proposal_hash.to_string())
+                })
+                .count()
+        };
+
+        // Nothing is tallied while the window is open
+        while shell.wl_storage.storage.block.epoch.next() < close_epoch {
+            let events = finalize_new_epoch(&mut shell);
+            assert_eq!(count_tallies(&events), 0);
+            assert!(!shell.wl_storage.has_key(&result_key).unwrap());
+        }
+
+        // The proposal is tallied in the block that closes the window
+        let events = finalize_new_epoch(&mut shell);
+        assert_eq!(shell.wl_storage.storage.block.epoch, close_epoch);
+        assert_eq!(count_tallies(&events), 1);
+        assert!(events.iter().any(|event| {
+            event.get("offline_proposal_hash")
+                == Some(&proposal_hash.to_string())
+                && event.get("tally_result")
+                    == Some(&TallyResult::Passed.to_string())
+        }));
+        let result: Option<ProposalResult> =
+            shell.wl_storage.read(&result_key).unwrap();
+        assert_eq!(
+            result.map(|result| result.result),
+            Some(TallyResult::Passed)
+        );
+        assert!(!shell.wl_storage.has_key(&pending_key).unwrap());
+
+        // It's not tallied again in the following blocks and epochs
+        for _ in 0..2 {
+            let events = shell
+                .finalize_block(FinalizeBlock::default())
+                .unwrap()
+                .events;
+            shell.wl_storage.commit_block().unwrap();
+            assert_eq!(count_tallies(&events), 0);
+            let events = finalize_new_epoch(&mut shell);
+            assert_eq!(count_tallies(&events), 0);
+        }
+    }
 }
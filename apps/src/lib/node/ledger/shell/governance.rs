@@ -1,18 +1,26 @@
-use namada::core::ledger::slash_fund::ADDRESS as slash_fund_address;
+use namada::core::ledger::slash_fund::{
+    storage as slash_fund_storage, ADDRESS as slash_fund_address,
+};
 use namada::ledger::events::EventType;
 use namada::ledger::governance::{
     storage as gov_storage, ADDRESS as gov_address,
 };
 use namada::ledger::native_vp::governance::utils::{
-    compute_tally, get_proposal_votes, ProposalEvent,
+    compute_tally, get_deposit_burn_rate, get_downgraded_voting_period,
+    get_offline_proposal_votes, get_proposal_votes, is_expedited,
+    is_expedited_threshold_met, ProposalEvent,
 };
 use namada::ledger::protocol;
 use namada::ledger::storage::types::encode;
 use namada::ledger::storage::{DBIter, StorageHasher, DB};
-use namada::ledger::storage_api::{token, StorageWrite};
+use namada::ledger::storage_api::{self, token, StorageRead, StorageWrite};
 use namada::types::address::Address;
-use namada::types::governance::TallyResult;
+use namada::types::governance::{
+    DisbursementPayment, DisbursementSchedule, OfflineProposal, ProposalStatus,
+    SlashFundDisbursement, TallyResult,
+};
 use namada::types::storage::Epoch;
+use rust_decimal::Decimal;
 
 use super::*;
 
@@ -50,24 +58,43 @@ where
                 )
             })?;
 
-        let votes =
-            get_proposal_votes(&shell.wl_storage, proposal_end_epoch, id);
-        let is_accepted = votes.and_then(|votes| {
-            compute_tally(&shell.wl_storage, proposal_end_epoch, votes)
+        let is_expedited = is_expedited(&shell.wl_storage, id)?;
+        let tally =
+            get_proposal_votes(&shell.wl_storage, proposal_end_epoch, id)
+                .and_then(|votes| {
+                    compute_tally(&shell.wl_storage, proposal_end_epoch, votes)
+                });
+        // The deposits are refunded if the proposal couldn't be tallied
+        let deposit_burn_rate = match &tally {
+            Ok(result) => get_deposit_burn_rate(&shell.wl_storage, result)
+                .unwrap_or_else(|err| {
+                    tracing::error!(
+                        "Unexpectedly failed to read the deposit burn rate of \
+                         proposal ID {id} with error {err}"
+                    );
+                    Decimal::ZERO
+                }),
+            Err(_) => Decimal::ZERO,
+        };
+        let is_accepted = tally.and_then(|result| {
+            if is_expedited {
+                is_expedited_threshold_met(&shell.wl_storage, &result)
+            } else {
+                Ok(result.result == TallyResult::Passed)
+            }
         });
 
-        let transfer_address = match is_accepted {
-            Ok(true) => {
-                let proposal_author_key = gov_storage::get_author_key(id);
-                let proposal_author = shell
-                    .read_storage_key::<Address>(&proposal_author_key)
-                    .ok_or_else(|| {
-                        Error::BadProposal(
-                            id,
-                            "Invalid proposal author.".to_string(),
-                        )
-                    })?;
+        // An expedited proposal that doesn't meet its threshold goes on as a
+        // normal proposal, if its voting period can be extended
+        if is_expedited
+            && matches!(is_accepted, Ok(false))
+            && downgrade_expedited_proposal(shell, id)?
+        {
+            continue;
+        }
 
+        let status = match is_accepted {
+            Ok(true) => {
                 let proposal_code_key = gov_storage::get_proposal_code_key(id);
                 let proposal_code =
                     shell.read_storage_key_bytes(&proposal_code_key);
@@ -86,13 +113,16 @@ where
                             .wl_storage
                             .write(&pending_execution_key, ())
                             .expect("Should be able to write to storage.");
+                        let mut gas_meter =
+                            BlockGasMeter::new(shell.get_gas_schedule());
                         let tx_result = protocol::apply_tx(
                             tx_type,
                             0, /*  this is used to compute the fee
                                 * based on the code size. We dont
                                 * need it here. */
                             TxIndex::default(),
-                            &mut BlockGasMeter::default(),
+                            None,
+                            &mut gas_meter,
                             &mut shell.wl_storage.write_log,
                             &shell.wl_storage.storage,
                             &mut shell.vp_wasm_cache,
@@ -118,7 +148,7 @@ where
                                     response.events.push(proposal_event);
                                     proposals_result.passed.push(id);
 
-                                    proposal_author
+                                    ProposalStatus::Executed
                                 } else {
                                     shell.wl_storage.write_log.drop_tx();
                                     let proposal_event: Event =
@@ -133,7 +163,7 @@ where
                                     response.events.push(proposal_event);
                                     proposals_result.rejected.push(id);
 
-                                    slash_fund_address
+                                    ProposalStatus::Rejected
                                 }
                             }
                             Err(_e) => {
@@ -149,7 +179,7 @@ where
                                 response.events.push(proposal_event);
                                 proposals_result.rejected.push(id);
 
-                                slash_fund_address
+                                ProposalStatus::Rejected
                             }
                         }
                     }
@@ -165,7 +195,7 @@ where
                         response.events.push(proposal_event);
                         proposals_result.passed.push(id);
 
-                        proposal_author
+                        ProposalStatus::Passed
                     }
                 }
             }
@@ -181,7 +211,7 @@ where
                 response.events.push(proposal_event);
                 proposals_result.rejected.push(id);
 
-                slash_fund_address
+                ProposalStatus::Rejected
             }
             Err(err) => {
                 tracing::error!(
@@ -198,24 +228,342 @@ where
                 .into();
                 response.events.push(proposal_event);
 
-                slash_fund_address
+                ProposalStatus::Rejected
             }
         };
 
-        let native_token = shell.wl_storage.storage.native_token.clone();
-        // transfer proposal locked funds
+        // Record the final status of the proposal for queries
+        shell
+            .wl_storage
+            .write(&gov_storage::get_proposal_status_key(id), status)
+            .expect("Should be able to write to storage.");
+
+        // The slash fund pays the disbursements of an accepted proposal from
+        // now on
+        if matches!(status, ProposalStatus::Passed | ProposalStatus::Executed) {
+            register_slash_fund_disbursements(shell, id)?;
+        }
+
+        settle_proposal_deposits(shell, id, funds, deposit_burn_rate)?;
+    }
+
+    Ok(proposals_result)
+}
+
+/// Record the slash fund disbursements of an accepted proposal as pending
+fn register_slash_fund_disbursements<D, H>(
+    shell: &mut Shell<D, H>,
+    id: u64,
+) -> Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    let current_epoch = shell.wl_storage.storage.block.epoch;
+    let disbursements: Vec<SlashFundDisbursement> = shell
+        .wl_storage
+        .read(&gov_storage::get_proposal_disbursements_key(id))?
+        .unwrap_or_default();
+    for (index, mut disbursement) in disbursements.into_iter().enumerate() {
+        // A downgraded expedited proposal is executed after the grace epoch
+        // its streamed payments were checked against, so a late schedule is
+        // shifted to start now with the same number of payments
+        if let DisbursementSchedule::Streamed {
+            start_epoch,
+            end_epoch,
+        } = &mut disbursement.schedule
+        {
+            if *start_epoch < current_epoch {
+                let delay = current_epoch.0 - start_epoch.0;
+                *start_epoch = current_epoch;
+                *end_epoch = *end_epoch + delay;
+            }
+        }
+        let pending_key =
+            slash_fund_storage::get_pending_disbursement_key(id, index as u64);
+        shell.wl_storage.write(&pending_key, disbursement)?;
+    }
+    Ok(())
+}
+
+/// Pay the slash fund disbursements due in the current epoch. A one-off
+/// payment that the fund can't cover yet is retried in the next epoch, while
+/// a streamed payment is skipped for the epoch.
+pub fn execute_slash_fund_disbursements<D, H>(
+    shell: &mut Shell<D, H>,
+) -> Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    let current_epoch = shell.wl_storage.storage.block.epoch;
+    let native_token = shell.wl_storage.storage.native_token.clone();
+    let pending_prefix = slash_fund_storage::get_pending_disbursements_prefix();
+    let pending = storage_api::iter_prefix::<SlashFundDisbursement>(
+        &shell.wl_storage,
+        &pending_prefix,
+    )?
+    .collect::<storage_api::Result<Vec<_>>>()?;
+
+    for (pending_key, disbursement) in pending {
+        let (proposal_id, index) =
+            match slash_fund_storage::get_pending_disbursement_ids(&pending_key)
+            {
+                Some(ids) => ids,
+                None => continue,
+            };
+        let is_due = match disbursement.schedule {
+            DisbursementSchedule::OneOff => true,
+            DisbursementSchedule::Streamed {
+                start_epoch,
+                end_epoch,
+            } => start_epoch <= current_epoch && current_epoch <= end_epoch,
+        };
+        let balance = token::read_balance(
+            &shell.wl_storage,
+            &native_token,
+            &slash_fund_address,
+        )?;
+
+        let is_paid = if !is_due {
+            false
+        } else if balance < disbursement.amount {
+            tracing::warn!(
+                "The slash fund balance {balance} doesn't cover the \
+                 disbursement {index} of proposal ID {proposal_id} of \
+                 {amount} in epoch {current_epoch}",
+                amount = disbursement.amount,
+            );
+            false
+        } else {
+            token::transfer(
+                &mut shell.wl_storage,
+                &native_token,
+                &slash_fund_address,
+                &disbursement.target,
+                disbursement.amount,
+            )?;
+            let payment = DisbursementPayment {
+                proposal_id,
+                index,
+                target: disbursement.target.clone(),
+                amount: disbursement.amount,
+                epoch: current_epoch,
+            };
+            shell.wl_storage.write(
+                &slash_fund_storage::get_executed_disbursement_key(
+                    proposal_id,
+                    index,
+                    current_epoch.0,
+                ),
+                payment,
+            )?;
+            true
+        };
+
+        let is_done = match disbursement.schedule {
+            DisbursementSchedule::OneOff => is_paid,
+            DisbursementSchedule::Streamed { end_epoch, .. } => {
+                end_epoch <= current_epoch
+            }
+        };
+        if is_done {
+            shell.wl_storage.delete(&pending_key)?;
+        }
+    }
+    Ok(())
+}
+
+/// Refund the deposits of a tallied proposal to its author and to the
+/// addresses that topped it up, after burning the given fraction of each
+fn settle_proposal_deposits<D, H>(
+    shell: &mut Shell<D, H>,
+    id: u64,
+    funds: token::Amount,
+    burn_rate: Decimal,
+) -> Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    let author: Address = shell
+        .wl_storage
+        .read(&gov_storage::get_author_key(id))?
+        .ok_or_else(|| {
+            Error::BadProposal(id, "Invalid proposal author.".to_string())
+        })?;
+    let mut deposits = vec![(author, funds)];
+    let deposit_prefix = gov_storage::get_proposal_deposit_prefix(id);
+    for next_deposit in storage_api::iter_prefix::<token::Amount>(
+        &shell.wl_storage,
+        &deposit_prefix,
+    )? {
+        let (key, amount) = next_deposit?;
+        if let Some(depositor) = gov_storage::get_depositor_address(&key) {
+            deposits.push((depositor.clone(), amount));
+        }
+    }
+
+    let native_token = shell.wl_storage.storage.native_token.clone();
+    for (depositor, amount) in deposits {
+        let burnt =
+            token::Amount::from(Decimal::from(amount) * burn_rate).min(amount);
+        token::burn_tokens(
+            &mut shell.wl_storage,
+            &native_token,
+            &gov_address,
+            burnt,
+        )
+        .expect(
+            "Must be able to burn governance locked funds after proposal has \
+             been tallied",
+        );
         token::transfer(
             &mut shell.wl_storage,
             &native_token,
             &gov_address,
-            &transfer_address,
-            funds,
+            &depositor,
+            amount - burnt,
         )
         .expect(
             "Must be able to transfer governance locked funds after proposal \
              has been tallied",
         );
     }
+    Ok(())
+}
 
-    Ok(proposals_result)
+/// Downgrade an expedited proposal to a normal one by extending its voting
+/// period to the next multiple of the minimum proposal period and postponing
+/// its grace epoch accordingly. Returns `false` if the voting period cannot be
+/// extended within the maximum proposal period.
+fn downgrade_expedited_proposal<D, H>(
+    shell: &mut Shell<D, H>,
+    id: u64,
+) -> Result<bool>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    let current_epoch = shell.wl_storage.storage.block.epoch;
+    let start_epoch: Epoch = shell
+        .wl_storage
+        .read(&gov_storage::get_voting_start_epoch_key(id))?
+        .ok_or_else(|| {
+            Error::BadProposal(id, "Invalid proposal start_epoch.".to_string())
+        })?;
+    let min_grace_epochs: u64 = shell
+        .wl_storage
+        .read(&gov_storage::get_min_proposal_grace_epoch_key())?
+        .unwrap_or_default();
+
+    let period = match get_downgraded_voting_period(
+        &shell.wl_storage,
+        start_epoch,
+        current_epoch,
+    )? {
+        Some(period) => period,
+        None => return Ok(false),
+    };
+    let end_epoch = start_epoch + period;
+    let grace_epoch = end_epoch + min_grace_epochs;
+
+    shell
+        .wl_storage
+        .write(&gov_storage::get_voting_end_epoch_key(id), end_epoch)?;
+    shell
+        .wl_storage
+        .write(&gov_storage::get_grace_epoch_key(id), grace_epoch)?;
+    shell.wl_storage.write(
+        &gov_storage::get_committing_proposals_key(id, grace_epoch.0),
+        (),
+    )?;
+    shell
+        .wl_storage
+        .delete(&gov_storage::get_proposal_expedited_key(id))?;
+
+    tracing::info!(
+        "Expedited proposal ID {id} didn't meet its threshold, it's been \
+         downgraded to a normal proposal with voting end epoch {end_epoch} \
+         and grace epoch {grace_epoch}"
+    );
+    Ok(true)
+}
+
+/// Tally the offline proposals whose votes submission window has closed at
+/// the stake of their tally epoch and store their result
+pub fn execute_offline_tallies<D, H>(
+    shell: &mut Shell<D, H>,
+    response: &mut shim::response::FinalizeBlock,
+) -> Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    let current_epoch = shell.wl_storage.storage.block.epoch;
+    let pending_prefix = gov_storage::get_pending_offline_tally_prefix();
+    let pending_keys =
+        storage_api::iter_prefix::<Epoch>(&shell.wl_storage, &pending_prefix)?
+            .filter_map(|next| match next {
+                Ok((key, close_epoch)) if close_epoch <= current_epoch => {
+                    Some(Ok(key))
+                }
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<storage_api::Result<Vec<_>>>()?;
+
+    for pending_key in pending_keys {
+        shell.wl_storage.delete(&pending_key)?;
+        let proposal_hash =
+            match gov_storage::get_offline_proposal_hash(&pending_key) {
+                Some(proposal_hash) => proposal_hash,
+                None => continue,
+            };
+        let proposal: OfflineProposal = shell
+            .wl_storage
+            .read(&gov_storage::get_offline_proposal_key(&proposal_hash))?
+            .ok_or_else(|| {
+                Error::BadOfflineProposal(
+                    proposal_hash,
+                    "Missing offline proposal.".to_string(),
+                )
+            })?;
+        let tally_epoch = proposal.tally_epoch;
+
+        let result = get_offline_proposal_votes(
+            &shell.wl_storage,
+            tally_epoch,
+            &proposal_hash,
+        )
+        .and_then(|votes| compute_tally(&shell.wl_storage, tally_epoch, votes));
+        let tally_result = match result {
+            Ok(result) => {
+                let tally_result = result.result;
+                shell.wl_storage.write(
+                    &gov_storage::get_offline_proposal_result_key(
+                        &proposal_hash,
+                    ),
+                    result,
+                )?;
+                tally_result
+            }
+            Err(err) => {
+                tracing::error!(
+                    "Unexpectedly failed to tally offline proposal \
+                     {proposal_hash} with error {err}"
+                );
+                TallyResult::Failed
+            }
+        };
+        let proposal_event: Event = ProposalEvent::new_offline(
+            EventType::Proposal.to_string(),
+            tally_result,
+            &proposal_hash,
+            tally_epoch,
+        )
+        .into();
+        response.events.push(proposal_event);
+    }
+    Ok(())
 }
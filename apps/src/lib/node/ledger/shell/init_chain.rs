@@ -4,10 +4,12 @@ use std::hash::Hash;
 
 #[cfg(not(feature = "mainnet"))]
 use namada::core::ledger::testnet_pow;
+use namada::ledger::parameters::storage::get_wasm_code_key;
 use namada::ledger::parameters::Parameters;
 use namada::ledger::pos::into_tm_voting_power;
 use namada::ledger::storage_api::StorageWrite;
 use namada::types::key::*;
+use namada::types::vesting;
 #[cfg(not(feature = "dev"))]
 use sha2::{Digest, Sha256};
 
@@ -83,7 +85,9 @@ where
             pos_gain_d,
             staked_ratio,
             pos_inflation_amount,
-            wrapper_tx_fees,
+            gas_schedule,
+            gas_prices,
+            storage_read_past_height_limit,
         } = genesis.parameters;
         // borrow necessary for release build, annoys clippy on dev build
         #[allow(clippy::needless_borrow)]
@@ -135,10 +139,11 @@ where
             pos_gain_d,
             staked_ratio,
             pos_inflation_amount,
+            gas_schedule,
+            gas_prices,
+            storage_read_past_height_limit,
             #[cfg(not(feature = "mainnet"))]
             faucet_account,
-            #[cfg(not(feature = "mainnet"))]
-            wrapper_tx_fees,
         };
         parameters
             .init_storage(&mut self.wl_storage)
@@ -166,6 +171,7 @@ where
             vp_sha256,
             public_key,
             storage,
+            vesting,
         } in genesis.established_accounts
         {
             let vp_code = match vp_code_cache.get(&vp_code_path).cloned() {
@@ -210,6 +216,15 @@ where
                 self.wl_storage.write_bytes(&key, value).unwrap();
             }
 
+            if let Some(schedule) = vesting {
+                self.wl_storage
+                    .write_bytes(
+                        &vesting::vesting_schedule_key(&address),
+                        schedule.try_to_vec().unwrap(),
+                    )
+                    .unwrap();
+            }
+
             // When using a faucet WASM, initialize its PoW challenge storage
             #[cfg(not(feature = "mainnet"))]
             if vp_code_path == "vp_testnet_faucet.wasm" {
@@ -335,6 +350,29 @@ where
                 .expect("Unable to set genesis user public DKG session key");
         }
 
+        // Initialize the wasm codes referable by their hash
+        for genesis::WasmCode { code_path, sha256 } in genesis.wasm_codes {
+            let code = vp_code_cache
+                .get_or_insert_with(code_path.clone(), || {
+                    wasm_loader::read_wasm(&self.wasm_dir, &code_path).unwrap()
+                });
+            let code_hash = namada::types::hash::Hash::sha256(&code);
+
+            // In dev, we don't check the hash
+            #[cfg(feature = "dev")]
+            let _ = sha256;
+            #[cfg(not(feature = "dev"))]
+            assert_eq!(
+                code_hash.0, sha256,
+                "Invalid wasm code sha256 hash for {}",
+                code_path
+            );
+
+            self.wl_storage
+                .write_bytes(&get_wasm_code_key(&code_hash), code)
+                .expect("Unable to write wasm code");
+        }
+
         // PoS system depends on epoch being initialized
         let (current_epoch, _gas) = self.wl_storage.storage.get_current_epoch();
         pos::init_genesis_storage(
@@ -398,13 +436,17 @@ mod test {
     use std::collections::BTreeMap;
     use std::str::FromStr;
 
+    use namada::ledger::parameters::storage::get_wasm_code_key;
     use namada::ledger::storage::DBIter;
+    use namada::ledger::storage_api::StorageRead;
     use namada::types::chain::ChainId;
+    use namada::types::hash::Hash;
     use namada::types::storage;
 
     use crate::facade::tendermint_proto::abci::RequestInitChain;
     use crate::facade::tendermint_proto::google::protobuf::Timestamp;
-    use crate::node::ledger::shell::test_utils::TestShell;
+    use crate::node::ledger::shell::test_utils::{self, TestShell};
+    use crate::wasm_loader;
 
     /// Test that the init-chain handler never commits changes directly to the
     /// DB.
@@ -447,4 +489,20 @@ mod test {
             storage_state.iter(),
         );
     }
+
+    /// Test that the init-chain handler stores the genesis wasm codes under
+    /// their hash, so that transactions can refer to them
+    #[test]
+    fn test_init_chain_stores_wasm_codes() {
+        let (shell, _receiver) = test_utils::setup();
+
+        for code_path in ["tx_transfer.wasm", "tx_bond.wasm"] {
+            let code = wasm_loader::read_wasm(&shell.wasm_dir, code_path)
+                .expect("Test failed");
+            let key = get_wasm_code_key(&Hash::sha256(&code));
+            let stored_code =
+                shell.wl_storage.read_bytes(&key).expect("Test failed");
+            assert_eq!(stored_code, Some(code));
+        }
+    }
 }
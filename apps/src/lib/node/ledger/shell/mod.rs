@@ -13,7 +13,7 @@ mod process_proposal;
 mod queries;
 mod stats;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::mem;
 use std::path::{Path, PathBuf};
@@ -23,7 +23,7 @@ use std::rc::Rc;
 use borsh::{BorshDeserialize, BorshSerialize};
 use namada::ledger::events::log::EventLog;
 use namada::ledger::events::Event;
-use namada::ledger::gas::BlockGasMeter;
+use namada::ledger::gas::{BlockGasMeter, GasSchedule};
 use namada::ledger::pos::namada_proof_of_stake::types::{
     ConsensusValidator, ValidatorSetUpdate,
 };
@@ -35,9 +35,9 @@ use namada::ledger::storage_api::{self, StorageRead};
 use namada::ledger::{ibc, pos, protocol};
 use namada::proof_of_stake::{self, read_pos_params, slash};
 use namada::proto::{self, Tx};
-use namada::types::address;
 use namada::types::address::{masp, masp_tx_key, Address};
 use namada::types::chain::ChainId;
+use namada::types::hash::Hash;
 use namada::types::internal::WrapperTxInQueue;
 use namada::types::key::*;
 use namada::types::storage::{BlockHeight, Key, TxIndex};
@@ -45,8 +45,9 @@ use namada::types::time::{DateTimeUtc, TimeZone, Utc};
 use namada::types::token::{self};
 use namada::types::transaction::{
     hash_tx, process_tx, verify_decrypted_correctly, AffineCurve, DecryptedTx,
-    EllipticCurve, PairingEngine, TxType, MIN_FEE,
+    EllipticCurve, PairingEngine, TxType, WrapperTx, MIN_FEE,
 };
+use namada::types::{address, sponsor};
 use namada::vm::wasm::{TxCache, VpCache};
 use namada::vm::WasmCacheRwAccess;
 use num_derive::{FromPrimitive, ToPrimitive};
@@ -65,8 +66,9 @@ use crate::facade::tower_abci::{request, response};
 use crate::node::ledger::shims::abcipp_shim_types::shim;
 use crate::node::ledger::shims::abcipp_shim_types::shim::response::TxResult;
 use crate::node::ledger::{storage, tendermint_node};
-#[allow(unused_imports)]
-use crate::wallet::ValidatorData;
+#[cfg(feature = "abcipp")]
+use crate::remote_signer::protocol::vote_extension_sign_bytes;
+use crate::remote_signer::protocol::{ProtocolKey, RemoteSigner};
 use crate::{config, wallet};
 
 fn key_to_tendermint(
@@ -102,6 +104,8 @@ pub enum Error {
     Broadcaster(tokio::sync::mpsc::error::TryRecvError),
     #[error("Error executing proposal {0}: {1}")]
     BadProposal(u64, String),
+    #[error("Error tallying offline proposal {0}: {1}")]
+    BadOfflineProposal(Hash, String),
     #[error("Error reading wasm: {0}")]
     ReadingWasm(#[from] eyre::Error),
     #[error("Error reading from or writing to storage: {0}")]
@@ -158,11 +162,43 @@ pub fn reset(config: config::Ledger) -> Result<()> {
     Ok(())
 }
 
+/// Get the address paying the fee of a wrapper tx. If the public key
+/// corresponds to the MASP sentinel transaction key, then the fee payer is
+/// effectively the MASP, otherwise the payer is the third-party fee payer, if
+/// any, or else derived from the public key.
+fn wrapper_fee_payer(wrapper: &WrapperTx) -> Address {
+    if wrapper.fee_payer.is_some() || wrapper.pk != masp_tx_key().ref_to() {
+        wrapper.fee_payer()
+    } else {
+        masp()
+    }
+}
+
+/// ABCI++ doesn't give the round of a vote to the app, so the vote extensions
+/// are signed once per height
+#[cfg(feature = "abcipp")]
+const VOTE_EXTENSION_ROUND: i64 = 0;
+
+/// A vote extension signed with the protocol key of the validator
+#[cfg(feature = "abcipp")]
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+struct SignedVoteExtension {
+    /// The data of the extension
+    extension: Vec<u8>,
+    /// The signature of the extension for the chain and the height of the
+    /// vote (see [`vote_extension_sign_bytes`])
+    signature: common::Signature,
+}
+
 #[derive(Debug)]
 #[allow(dead_code, clippy::large_enum_variant)]
 pub(super) enum ShellMode {
     Validator {
-        data: ValidatorData,
+        /// The validator's address
+        address: Address,
+        /// The signer of the protocol txs and vote extensions, which holds
+        /// the protocol key locally or on a remote signer
+        protocol_key: ProtocolKey,
         broadcast_sender: UnboundedSender<Vec<u8>>,
     },
     Full,
@@ -174,7 +210,7 @@ impl ShellMode {
     /// Get the validator address if ledger is in validator mode
     pub fn get_validator_address(&self) -> Option<&address::Address> {
         match &self {
-            ShellMode::Validator { data, .. } => Some(&data.address),
+            ShellMode::Validator { address, .. } => Some(address),
             _ => None,
         }
     }
@@ -275,45 +311,62 @@ where
         // load in keys and address from wallet if mode is set to `Validator`
         let mode = match mode {
             TendermintMode::Validator => {
-                #[cfg(not(feature = "dev"))]
-                {
-                    let wallet_path = &base_dir.join(chain_id.as_str());
-                    let genesis_path =
-                        &base_dir.join(format!("{}.toml", chain_id.as_str()));
-                    tracing::debug!(
-                        "{}",
-                        wallet_path.as_path().to_str().unwrap()
-                    );
-                    let wallet = wallet::Wallet::load_or_new_from_genesis(
-                        wallet_path,
-                        genesis::genesis_config::open_genesis_config(
-                            genesis_path,
-                        )
-                        .unwrap(),
-                    );
-                    wallet
-                        .take_validator_data()
-                        .map(|data| ShellMode::Validator {
-                            data,
-                            broadcast_sender,
-                        })
-                        .expect(
-                            "Validator data should have been stored in the \
-                             wallet",
-                        )
-                }
-                #[cfg(feature = "dev")]
-                {
-                    let validator_keys = wallet::defaults::validator_keys();
+                if let Some(signer) = config.tendermint.remote_signer.as_ref() {
+                    // The protocol key is held by the remote signer, so the
+                    // validator's keys aren't needed in the wallet
+                    let remote_signer = RemoteSigner::connect(
+                        signer.protocol_addr.clone(),
+                        chain_id.as_str().to_owned(),
+                    )
+                    .expect("Failed to connect to the remote signer");
                     ShellMode::Validator {
-                        data: wallet::ValidatorData {
+                        address: signer.validator_address.clone(),
+                        protocol_key: ProtocolKey::Remote(remote_signer),
+                        broadcast_sender,
+                    }
+                } else {
+                    #[cfg(not(feature = "dev"))]
+                    {
+                        let wallet_path = &base_dir.join(chain_id.as_str());
+                        let genesis_path = &base_dir
+                            .join(format!("{}.toml", chain_id.as_str()));
+                        tracing::debug!(
+                            "{}",
+                            wallet_path.as_path().to_str().unwrap()
+                        );
+                        let wallet = wallet::Wallet::load_or_new_from_genesis(
+                            wallet_path,
+                            genesis::genesis_config::open_genesis_config(
+                                genesis_path,
+                            )
+                            .unwrap(),
+                        );
+                        wallet
+                            .take_validator_data()
+                            .map(|data| ShellMode::Validator {
+                                address: data.address,
+                                protocol_key: ProtocolKey::Local {
+                                    chain_id: chain_id.as_str().to_owned(),
+                                    key: data.keys.protocol_keypair,
+                                },
+                                broadcast_sender,
+                            })
+                            .expect(
+                                "Validator data should have been stored in \
+                                 the wallet",
+                            )
+                    }
+                    #[cfg(feature = "dev")]
+                    {
+                        let validator_keys = wallet::defaults::validator_keys();
+                        ShellMode::Validator {
                             address: wallet::defaults::validator_address(),
-                            keys: wallet::ValidatorKeys {
-                                protocol_keypair: validator_keys.0,
-                                dkg_keypair: Some(validator_keys.1),
+                            protocol_key: ProtocolKey::Local {
+                                chain_id: chain_id.as_str().to_owned(),
+                                key: validator_keys.0,
                             },
-                        },
-                        broadcast_sender,
+                            broadcast_sender,
+                        }
                     }
                 }
             }
@@ -542,22 +595,101 @@ where
     #[cfg(feature = "abcipp")]
     pub fn extend_vote(
         &self,
-        _req: request::ExtendVote,
+        req: request::ExtendVote,
     ) -> response::ExtendVote {
-        Default::default()
+        let protocol_key = match &self.mode {
+            ShellMode::Validator { protocol_key, .. } => protocol_key,
+            _ => return Default::default(),
+        };
+        // The extension doesn't carry any data yet
+        let extension = vec![];
+        match protocol_key.sign_vote_extension(
+            req.height,
+            VOTE_EXTENSION_ROUND,
+            extension.clone(),
+        ) {
+            Ok(signature) => response::ExtendVote {
+                vote_extension: SignedVoteExtension {
+                    extension,
+                    signature,
+                }
+                .try_to_vec()
+                .expect("Encoding a vote extension shouldn't fail"),
+            },
+            Err(err) => {
+                tracing::error!("Failed to sign the vote extension: {}", err);
+                Default::default()
+            }
+        }
     }
 
+    /// Accept a vote extension signed with the protocol key of the validator
+    /// that cast the vote.
     /// INVARIANT: This method must be stateless.
     #[cfg(feature = "abcipp")]
     pub fn verify_vote_extension(
         &self,
-        _req: request::VerifyVoteExtension,
+        req: request::VerifyVoteExtension,
     ) -> response::VerifyVoteExtension {
+        let status = if self.is_valid_vote_extension(req) {
+            VerifyStatus::Accept
+        } else {
+            VerifyStatus::Reject
+        };
         response::VerifyVoteExtension {
-            status: VerifyStatus::Accept as i32,
+            status: status as i32,
         }
     }
 
+    /// Check the signature of a vote extension against the protocol key of
+    /// the validator that cast the vote
+    #[cfg(feature = "abcipp")]
+    fn is_valid_vote_extension(
+        &self,
+        req: request::VerifyVoteExtension,
+    ) -> bool {
+        let SignedVoteExtension {
+            extension,
+            signature,
+        } = match BorshDeserialize::try_from_slice(&req.vote_extension) {
+            Ok(vote_extension) => vote_extension,
+            Err(err) => {
+                tracing::debug!("Invalid vote extension encoding: {}", err);
+                return false;
+            }
+        };
+        let protocol_pk: Option<common::PublicKey> =
+            proof_of_stake::find_validator_by_raw_hash(
+                &self.wl_storage,
+                tm_raw_hash_to_string(req.validator_address),
+            )
+            .expect("Must be able to read storage")
+            .and_then(|validator| {
+                self.wl_storage
+                    .read(&protocol_pk_key(&validator))
+                    .expect("Must be able to read storage")
+            });
+        let protocol_pk = match protocol_pk {
+            Some(pk) => pk,
+            None => {
+                tracing::debug!("Vote extension of an unknown validator");
+                return false;
+            }
+        };
+        let sign_bytes = vote_extension_sign_bytes(
+            self.chain_id.as_str(),
+            req.height,
+            VOTE_EXTENSION_ROUND,
+            extension,
+        );
+        common::SigScheme::verify_signature_raw(
+            &protocol_pk,
+            &sign_bytes,
+            &signature,
+        )
+        .is_ok()
+    }
+
     /// Commit a block. Persist the application state and return the Merkle root
     /// hash.
     pub fn commit(&mut self) -> response::Commit {
@@ -591,17 +723,8 @@ where
         let mut response = response::CheckTx::default();
         match Tx::try_from(tx_bytes).map_err(Error::TxDecoding) {
             Ok(tx) => {
-                // Check balance for fee
+                // Check the fee
                 if let Ok(TxType::Wrapper(wrapper)) = process_tx(tx) {
-                    let fee_payer = if wrapper.pk != masp_tx_key().ref_to() {
-                        wrapper.fee_payer()
-                    } else {
-                        masp()
-                    };
-                    // check that the fee payer has sufficient balance
-                    let balance =
-                        self.get_balance(&wrapper.fee.token, &fee_payer);
-
                     // In testnets with a faucet, tx is allowed to skip fees if
                     // it includes a valid PoW
                     #[cfg(not(feature = "mainnet"))]
@@ -609,12 +732,17 @@ where
                     #[cfg(feature = "mainnet")]
                     let has_valid_pow = false;
 
-                    if !has_valid_pow && self.get_wrapper_tx_fees() > balance {
+                    let fee_check =
+                        self.validate_fee_payer(&wrapper).and_then(|()| {
+                            if has_valid_pow {
+                                Ok(())
+                            } else {
+                                self.validate_wrapper_fee(&wrapper)
+                            }
+                        });
+                    if let Err(msg) = fee_check {
                         response.code = 1;
-                        response.log = String::from(
-                            "The address given does not have sufficient \
-                             balance to pay fee",
-                        );
+                        response.log = msg;
                         return response;
                     }
                 }
@@ -633,7 +761,7 @@ where
     /// Simulate validation and application of a transaction.
     fn dry_run_tx(&self, tx_bytes: &[u8]) -> response::Query {
         let mut response = response::Query::default();
-        let mut gas_meter = BlockGasMeter::default();
+        let mut gas_meter = BlockGasMeter::new(self.get_gas_schedule());
         let mut write_log = WriteLog::default();
         let mut vp_wasm_cache = self.vp_wasm_cache.read_only();
         let mut tx_wasm_cache = self.tx_wasm_cache.read_only();
@@ -650,6 +778,7 @@ where
                     tx,
                     tx_bytes.len(),
                     TxIndex::default(),
+                    None,
                     &mut gas_meter,
                     &mut write_log,
                     &self.wl_storage.storage,
@@ -729,14 +858,112 @@ where
         false
     }
 
-    #[cfg(not(feature = "mainnet"))]
-    /// Get fixed amount of fees for wrapper tx
-    fn get_wrapper_tx_fees(&self) -> token::Amount {
-        let fees = namada::ledger::parameters::read_wrapper_tx_fees_parameter(
+    /// Get the gas schedule from the protocol parameters
+    fn get_gas_schedule(&self) -> GasSchedule {
+        namada::ledger::parameters::read_gas_schedule_parameter(
             &self.wl_storage,
         )
-        .expect("Must be able to read wrapper tx fees parameter");
-        fees.unwrap_or(token::Amount::whole(MIN_FEE))
+        .expect("Must be able to read gas schedule parameter")
+        .unwrap_or_default()
+    }
+
+    /// Get the allowed fee tokens and their minimum gas prices from the
+    /// protocol parameters
+    fn get_gas_prices(&self) -> BTreeMap<Address, token::Amount> {
+        let gas_prices = namada::ledger::parameters::read_gas_prices_parameter(
+            &self.wl_storage,
+        )
+        .expect("Must be able to read gas prices parameter");
+        gas_prices.unwrap_or_else(|| {
+            BTreeMap::from([(
+                self.wl_storage.storage.native_token.clone(),
+                token::Amount::from(MIN_FEE),
+            )])
+        })
+    }
+
+    /// Check that the signature of a wrapper tx's third-party fee payer, if
+    /// any, is valid, that it's made with a key of the fee payer's account and
+    /// that the fee payer sponsors the wrapper's signer. The sponsored tx
+    /// codes can only be checked by the fee payer's VP once the inner tx is
+    /// decrypted.
+    fn validate_fee_payer(
+        &self,
+        wrapper: &WrapperTx,
+    ) -> std::result::Result<(), String> {
+        let fee_payer = match &wrapper.fee_payer {
+            Some(fee_payer) => fee_payer,
+            None => return Ok(()),
+        };
+        wrapper
+            .validate_fee_payer_sig()
+            .map_err(|err| err.to_string())?;
+        let is_payer_key = match &fee_payer.address {
+            Address::Implicit(_) => {
+                Address::from(&fee_payer.pk) == fee_payer.address
+            }
+            address => {
+                let pk: Option<common::PublicKey> = self
+                    .wl_storage
+                    .read(&pk_key(address))
+                    .expect("Must be able to read the fee payer's public key");
+                pk.as_ref() == Some(&fee_payer.pk)
+            }
+        };
+        if !is_payer_key {
+            return Err(format!(
+                "The fee payer's public key doesn't belong to the account {}",
+                fee_payer.address
+            ));
+        }
+        let users: Option<BTreeSet<common::PublicKey>> = self
+            .wl_storage
+            .read(&sponsor::sponsored_users_key(&fee_payer.address))
+            .expect("Must be able to read the fee payer's sponsored users");
+        match users {
+            Some(users) if !users.contains(&wrapper.pk) => Err(format!(
+                "The fee payer {} doesn't sponsor the transactions of {}",
+                fee_payer.address, wrapper.pk
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that the fee of a wrapper tx is paid in an allowed token, that it
+    /// covers the minimum gas price for the tx's gas limit and that the fee
+    /// payer has sufficient balance to pay it.
+    fn validate_wrapper_fee(
+        &self,
+        wrapper: &WrapperTx,
+    ) -> std::result::Result<(), String> {
+        let gas_prices = self.get_gas_prices();
+        let min_gas_price =
+            gas_prices.get(&wrapper.fee.token).ok_or_else(|| {
+                format!(
+                    "The token {} is not allowed for the payment of fees",
+                    wrapper.fee.token
+                )
+            })?;
+        match wrapper.gas_limit.fee(*min_gas_price) {
+            Some(min_fee) if wrapper.fee.amount >= min_fee => {}
+            _ => {
+                return Err(format!(
+                    "The fee {} is lower than the minimum fee for the gas \
+                     limit {} at the minimum gas price {}",
+                    wrapper.fee.amount,
+                    u64::from(&wrapper.gas_limit),
+                    min_gas_price
+                ));
+            }
+        }
+        let balance =
+            self.get_balance(&wrapper.fee.token, &wrapper_fee_payer(wrapper));
+        if wrapper.fee.amount > balance {
+            return Err(String::from(
+                "The address given does not have sufficient balance to pay fee",
+            ));
+        }
+        Ok(())
     }
 
     #[cfg(not(feature = "mainnet"))]
@@ -926,9 +1153,17 @@ mod test_utils {
         }
 
         /// Add a wrapper tx to the queue of txs to be decrypted
-        /// in the current block proposal
+        /// in the current block proposal. Its fee is credited to the fee pool
+        /// as if it's been charged when the wrapper was accepted.
         #[cfg(test)]
         pub fn enqueue_tx(&mut self, wrapper: WrapperTx) {
+            storage_api::token::credit_tokens(
+                &mut self.shell.wl_storage,
+                &wrapper.fee.token,
+                &address::FEE_POOL,
+                wrapper.fee.amount,
+            )
+            .expect("Test failed");
             self.shell
                 .wl_storage
                 .storage
@@ -968,6 +1203,7 @@ mod test_utils {
                     time: DateTimeUtc::now(),
                     next_validators_hash: Hash([0; 32]),
                 },
+                proposer_address: vec![],
                 byzantine_validators: vec![],
                 txs: vec![],
             }
@@ -155,19 +155,6 @@ where
                             ),
                         }
                     } else {
-                        // If the public key corresponds to the MASP sentinel
-                        // transaction key, then the fee payer is effectively
-                        // the MASP, otherwise derive
-                        // they payer from public key.
-                        let fee_payer = if tx.pk != masp_tx_key().ref_to() {
-                            tx.fee_payer()
-                        } else {
-                            masp()
-                        };
-                        // check that the fee payer has sufficient balance
-                        let balance =
-                            self.get_balance(&tx.fee.token, &fee_payer);
-
                         // In testnets, tx is allowed to skip fees if it
                         // includes a valid PoW
                         #[cfg(not(feature = "mainnet"))]
@@ -175,22 +162,29 @@ where
                         #[cfg(feature = "mainnet")]
                         let has_valid_pow = false;
 
-                        if has_valid_pow
-                            || self.get_wrapper_tx_fees() <= balance
-                        {
-                            TxResult {
+                        // check the third-party fee payer's signature, if
+                        // any, and that the fee is paid in an allowed token,
+                        // that it covers the minimum gas price and that the
+                        // fee payer has sufficient balance
+                        let fee_check =
+                            self.validate_fee_payer(&tx).and_then(|()| {
+                                if has_valid_pow {
+                                    Ok(())
+                                } else {
+                                    self.validate_wrapper_fee(&tx)
+                                }
+                            });
+                        match fee_check {
+                            Ok(()) => TxResult {
                                 code: ErrorCodes::Ok.into(),
                                 info: "Process proposal accepted this \
                                        transaction"
                                     .into(),
-                            }
-                        } else {
-                            TxResult {
+                            },
+                            Err(info) => TxResult {
                                 code: ErrorCodes::InvalidTx.into(),
-                                info: "The address given does not have \
-                                       sufficient balance to pay fee"
-                                    .into(),
-                            }
+                                info,
+                            },
                         }
                     }
                 }
@@ -211,13 +205,15 @@ where
 #[cfg(test)]
 mod test_process_proposal {
     use borsh::BorshDeserialize;
-    use namada::proto::SignedTxData;
+    use namada::proto::{SignedTxData, TxCode};
     use namada::types::hash::Hash;
     use namada::types::key::*;
     use namada::types::storage::Epoch;
     use namada::types::token::Amount;
     use namada::types::transaction::encrypted::EncryptedTx;
-    use namada::types::transaction::{EncryptionKey, Fee, WrapperTx};
+    use namada::types::transaction::{
+        EncryptionKey, Fee, WrapperTx, GAS_LIMIT_RESOLUTION, MIN_FEE,
+    };
 
     use super::*;
     use crate::facade::tendermint_proto::abci::RequestInitChain;
@@ -323,7 +319,7 @@ mod test_process_proposal {
                 .try_to_vec()
                 .expect("Test failed");
             Tx {
-                code: vec![],
+                code: TxCode::Wasm(vec![]),
                 data: Some(
                     SignedTxData {
                         sig,
@@ -404,6 +400,64 @@ mod test_process_proposal {
         );
     }
 
+    /// Test that if the fee is paid in a token that's not allowed or if it
+    /// doesn't cover the minimum gas price for the gas limit,
+    /// [`process_proposal`] rejects that tx
+    #[test]
+    fn test_wrapper_fee_below_minimum() {
+        let (mut shell, _) = TestShell::new();
+        let keypair = crate::wallet::defaults::daewon_keypair();
+        let native_token = shell.wl_storage.storage.native_token.clone();
+        for (token, amount, expected_error) in [
+            (
+                address::btc(),
+                Amount::whole(1),
+                "is not allowed for the payment of fees",
+            ),
+            (
+                native_token,
+                Amount::from(MIN_FEE - 1),
+                "is lower than the minimum fee",
+            ),
+        ] {
+            let tx = Tx::new(
+                "wasm_code".as_bytes().to_owned(),
+                Some("transaction data".as_bytes().to_owned()),
+            );
+            let wrapper = WrapperTx::new(
+                Fee { amount, token },
+                &keypair,
+                Epoch(0),
+                GAS_LIMIT_RESOLUTION.into(),
+                tx,
+                Default::default(),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            )
+            .sign(&keypair)
+            .expect("Test failed");
+            let request = ProcessProposal {
+                txs: vec![wrapper.to_bytes()],
+            };
+            let response = if let [resp] = shell
+                .process_proposal(request)
+                .expect("Test failed")
+                .as_slice()
+            {
+                resp.clone()
+            } else {
+                panic!("Test failed")
+            };
+            assert_eq!(response.result.code, u32::from(ErrorCodes::InvalidTx));
+            assert!(
+                response.result.info.contains(expected_error),
+                "Result info {} doesn't contain the expected error {}",
+                response.result.info,
+                expected_error
+            );
+        }
+    }
+
     /// Test that if the account submitting the tx does
     /// not have sufficient balance to pay the fee,
     /// [`process_proposal`] rejects that tx
@@ -668,6 +722,7 @@ mod test_process_proposal {
                 token: shell.wl_storage.storage.native_token.clone(),
             },
             pk: keypair.ref_to(),
+            fee_payer: None,
             epoch: Epoch(0),
             gas_limit: 0.into(),
             inner_tx,
@@ -219,6 +219,7 @@ pub mod shim {
         pub struct FinalizeBlock {
             pub hash: BlockHash,
             pub header: Header,
+            pub proposer_address: Vec<u8>,
             pub byzantine_validators: Vec<Evidence>,
             pub txs: Vec<ProcessedTx>,
         }
@@ -236,6 +237,7 @@ pub mod shim {
                         )
                         .unwrap(),
                     },
+                    proposer_address: req.proposer_address,
                     byzantine_validators: req.byzantine_validators,
                     txs: vec![],
                 }
@@ -258,6 +260,7 @@ pub mod shim {
                         )
                         .unwrap(),
                     },
+                    proposer_address: header.proposer_address,
                     byzantine_validators: req.byzantine_validators,
                     txs: vec![],
                 }
@@ -102,8 +102,9 @@ pub async fn run(
     #[cfg(feature = "dev")]
     {
         let consensus_key = crate::wallet::defaults::validator_keypair();
-        // write the validator key file if it didn't already exist
-        if !has_validator_key {
+        // write the validator key file if it didn't already exist and the
+        // key isn't held by a remote signer
+        if !has_validator_key && config.remote_signer.is_none() {
             write_validator_key_async(&home_dir, &consensus_key).await;
         }
     }
@@ -345,6 +346,12 @@ async fn update_tendermint_config(
     config.instrumentation.namespace =
         tendermint_config.instrumentation_namespace;
 
+    // The consensus messages are signed by the remote signer connecting to
+    // this socket instead of the `priv_validator_key.json` file
+    config.priv_validator_laddr = tendermint_config
+        .remote_signer
+        .map(|remote_signer| remote_signer.privval_addr);
+
     #[cfg(feature = "abciplus")]
     {
         config.consensus.timeout_commit =
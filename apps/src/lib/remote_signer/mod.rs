@@ -0,0 +1,199 @@
+//! A remote signer holding a validator's consensus and protocol keys, so that
+//! they can be kept on a hardened host separate from the exposed node. The
+//! signer connects to the socket on which Tendermint listens to sign
+//! consensus messages with Tendermint's privval protocol (see [`privval`])
+//! and it serves the signing of protocol txs and vote extensions to the node
+//! on another socket (see [`protocol`]).
+//!
+//! The sockets are either Unix sockets, which can be forwarded between hosts,
+//! e.g. with SSH, or TCP sockets, whose connections are encrypted with
+//! Tendermint's secret connection protocol (see [`secret_connection`]). Like
+//! Tendermint's own privval connections, the peers of a TCP connection use a
+//! new identity key each time, so the sockets must only be reachable from the
+//! node's and the signer's hosts. The node still verifies that the
+//! signatures returned by the signer are valid for its protocol key.
+//!
+//! Before a signature is returned, the height, round and step of the signed
+//! message is persisted, which protects the keys from double-signing (see
+//! [`state`]).
+
+pub mod privval;
+pub mod protocol;
+pub mod secret_connection;
+pub mod state;
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use namada::types::key::*;
+use rand::rngs::OsRng;
+use thiserror::Error;
+
+use self::privval::PrivvalSigner;
+use self::protocol::ProtocolSigner;
+use self::secret_connection::SecretConnection;
+use self::state::PersistedSignState;
+use crate::config;
+use crate::facade::tendermint_config::net::Address as TendermintAddress;
+
+/// The file of the sign state of the consensus key. Nested in chain dirs.
+const CONSENSUS_SIGN_STATE_FILE: &str = "consensus_sign_state.json";
+/// The file of the sign state of the vote extensions signed with the protocol
+/// key. Nested in chain dirs.
+const VOTE_EXTENSION_SIGN_STATE_FILE: &str = "vote_extension_sign_state.json";
+/// The delay before reconnecting to Tendermint
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Double-sign protection: {0}")]
+    DoubleSign(String),
+    #[error("Remote signer I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to decode a remote signer message: {0}")]
+    Decode(String),
+    #[error("Unexpected chain ID {0}, expected {1}")]
+    ChainId(String, String),
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+    #[error("Failed to load the sign state: {0}")]
+    SignState(String),
+    #[error("The remote signer returned an error: {0}")]
+    Remote(String),
+    #[error("Unexpected response from the remote signer")]
+    UnexpectedResponse,
+    #[error("Secret connection handshake failed: {0}")]
+    Handshake(String),
+    #[error("Invalid signature from the remote signer: {0}")]
+    InvalidSignature(String),
+}
+
+/// Result of the remote signer functions
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The keys held by the remote signer
+pub struct SignerKeys {
+    /// The consensus key signing Tendermint's votes and proposals
+    pub consensus_key: common::SecretKey,
+    /// The protocol key signing protocol txs and vote extensions
+    pub protocol_key: common::SecretKey,
+}
+
+/// Run the remote signer of the given chain with the keys. Only fails if the
+/// sign states cannot be loaded or the protocol socket cannot be bound. The
+/// connection to Tendermint is re-established whenever it drops.
+pub fn run(
+    ledger_config: &config::Ledger,
+    remote_signer: config::RemoteSigner,
+    keys: SignerKeys,
+) -> Result<()> {
+    let chain_dir = ledger_config.chain_dir();
+    let chain_id = ledger_config.chain_id.as_str().to_owned();
+    let consensus_state = PersistedSignState::load_or_new(
+        chain_dir.join(CONSENSUS_SIGN_STATE_FILE),
+    )?;
+    let vote_extension_state = PersistedSignState::load_or_new(
+        chain_dir.join(VOTE_EXTENSION_SIGN_STATE_FILE),
+    )?;
+
+    let mut privval_signer = PrivvalSigner::new(
+        chain_id.clone(),
+        keys.consensus_key,
+        consensus_state,
+    );
+    let privval_addr = remote_signer.privval_addr;
+    thread::spawn(move || loop {
+        connect_privval(&mut privval_signer, &privval_addr);
+        thread::sleep(RECONNECT_DELAY);
+    });
+
+    let protocol_signer =
+        ProtocolSigner::new(chain_id, keys.protocol_key, vote_extension_state);
+    protocol::serve(protocol_signer, &remote_signer.protocol_addr)
+}
+
+/// Connect to Tendermint and sign its requests until the connection drops
+fn connect_privval(signer: &mut PrivvalSigner, addr: &TendermintAddress) {
+    match connect(addr) {
+        Ok(stream) => {
+            tracing::info!("Connected to Tendermint at {}", addr);
+            if let Err(err) = signer.serve(stream) {
+                tracing::error!("The connection to Tendermint failed: {}", err);
+            }
+        }
+        Err(err) => {
+            tracing::debug!(
+                "Failed to connect to Tendermint at {}: {}",
+                addr,
+                err
+            );
+        }
+    }
+}
+
+/// A connection of the remote signer
+pub trait Connection: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Connection for T {}
+
+/// Connect to a Unix or a TCP socket. A TCP connection is encrypted with a
+/// secret connection.
+pub fn connect(addr: &TendermintAddress) -> Result<Box<dyn Connection>> {
+    match addr {
+        TendermintAddress::Unix { path } => {
+            Ok(Box::new(UnixStream::connect(path)?))
+        }
+        TendermintAddress::Tcp { host, port, .. } => {
+            let stream = TcpStream::connect((host.as_str(), *port))?;
+            encrypt_connection(stream)
+        }
+    }
+}
+
+/// A listener on a Unix or a TCP socket
+pub enum Listener {
+    /// A Unix socket listener
+    Unix(UnixListener),
+    /// A TCP socket listener, whose connections are encrypted with a secret
+    /// connection
+    Tcp(TcpListener),
+}
+
+impl Listener {
+    /// Listen on the given socket. A Unix socket left over by a previous run
+    /// is replaced.
+    pub fn bind(addr: &TendermintAddress) -> Result<Self> {
+        match addr {
+            TendermintAddress::Unix { path } => {
+                let path = Path::new(path);
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+                Ok(Self::Unix(UnixListener::bind(path)?))
+            }
+            TendermintAddress::Tcp { host, port, .. } => {
+                Ok(Self::Tcp(TcpListener::bind((host.as_str(), *port))?))
+            }
+        }
+    }
+
+    /// Accept the next connection
+    pub fn accept(&self) -> Result<Box<dyn Connection>> {
+        match self {
+            Self::Unix(listener) => Ok(Box::new(listener.accept()?.0)),
+            Self::Tcp(listener) => encrypt_connection(listener.accept()?.0),
+        }
+    }
+}
+
+/// Perform the secret connection handshake under a new identity key
+fn encrypt_connection(stream: TcpStream) -> Result<Box<dyn Connection>> {
+    let identity = ed25519::SigScheme::generate(&mut OsRng);
+    let (conn, _remote_pubkey) = SecretConnection::new(stream, &identity)?;
+    Ok(Box::new(conn))
+}
@@ -0,0 +1,518 @@
+//! The remote signer's side of Tendermint's privval protocol. Tendermint
+//! listens on its `priv_validator_laddr` socket and the signer connects to it
+//! to receive the requests for the consensus public key and for signing votes
+//! and proposals. The messages are length-delimited protobuf messages. On a
+//! TCP socket, the connection is encrypted with Tendermint's secret connection
+//! protocol (see [`super::secret_connection`]).
+//!
+//! A vote or a proposal is signed over its canonical encoding for the chain,
+//! whose height, round and step are checked against the last signed message
+//! to prevent double-signing.
+
+use std::io::{Read, Write};
+
+use borsh::BorshSerialize;
+use namada::types::key::*;
+use prost::Message as _;
+
+use super::state::{
+    PersistedSignState, SignState, STEP_PRECOMMIT, STEP_PREVOTE, STEP_PROPOSAL,
+};
+use super::{Error, Result};
+use crate::facade::tendermint_proto::crypto::{public_key, PublicKey};
+use crate::facade::tendermint_proto::google::protobuf::Timestamp;
+use crate::facade::tendermint_proto::privval::{
+    message, Message, PingResponse, PubKeyResponse, RemoteSignerError,
+    SignedProposalResponse, SignedVoteResponse,
+};
+use crate::facade::tendermint_proto::types::{
+    BlockId, CanonicalBlockId, CanonicalPartSetHeader, CanonicalProposal,
+    CanonicalVote, Proposal, SignedMsgType, Vote,
+};
+
+/// The maximum size of a message
+const MAX_MESSAGE_SIZE: usize = 1 << 20;
+
+/// Signs Tendermint's consensus messages with the consensus key
+pub struct PrivvalSigner {
+    chain_id: String,
+    key: common::SecretKey,
+    state: PersistedSignState,
+}
+
+impl PrivvalSigner {
+    /// Sign the consensus messages of the given chain with the key, starting
+    /// from the given sign state
+    pub fn new(
+        chain_id: String,
+        key: common::SecretKey,
+        state: PersistedSignState,
+    ) -> Self {
+        Self {
+            chain_id,
+            key,
+            state,
+        }
+    }
+
+    /// Sign the requests received on the stream until the connection fails
+    pub fn serve(&mut self, mut stream: impl Read + Write) -> Result<()> {
+        loop {
+            let request = read_message(&mut stream)?;
+            let response = self.handle(request)?;
+            stream.write_all(&response.encode_length_delimited_to_vec())?;
+        }
+    }
+
+    /// Handle a request of Tendermint. The errors of valid requests are
+    /// returned to Tendermint in their response.
+    pub fn handle(&mut self, request: Message) -> Result<Message> {
+        use message::Sum;
+        let sum = match request.sum {
+            Some(Sum::PubKeyRequest(request)) => Sum::PubKeyResponse(
+                match self.check_chain_id(&request.chain_id) {
+                    Ok(()) => PubKeyResponse {
+                        pub_key: Some(tm_public_key(&self.key.ref_to())),
+                        error: None,
+                    },
+                    Err(err) => PubKeyResponse {
+                        pub_key: None,
+                        error: Some(remote_signer_error(err)),
+                    },
+                },
+            ),
+            Some(Sum::SignVoteRequest(request)) => Sum::SignedVoteResponse(
+                match self.sign_vote(request.vote, &request.chain_id) {
+                    Ok(vote) => SignedVoteResponse {
+                        vote: Some(vote),
+                        error: None,
+                    },
+                    Err(err) => {
+                        tracing::error!("Refused to sign a vote: {}", err);
+                        SignedVoteResponse {
+                            vote: None,
+                            error: Some(remote_signer_error(err)),
+                        }
+                    }
+                },
+            ),
+            Some(Sum::SignProposalRequest(request)) => {
+                Sum::SignedProposalResponse(
+                    match self
+                        .sign_proposal(request.proposal, &request.chain_id)
+                    {
+                        Ok(proposal) => SignedProposalResponse {
+                            proposal: Some(proposal),
+                            error: None,
+                        },
+                        Err(err) => {
+                            tracing::error!(
+                                "Refused to sign a proposal: {}",
+                                err
+                            );
+                            SignedProposalResponse {
+                                proposal: None,
+                                error: Some(remote_signer_error(err)),
+                            }
+                        }
+                    },
+                )
+            }
+            Some(Sum::PingRequest(_)) => Sum::PingResponse(PingResponse {}),
+            _ => {
+                return Err(Error::InvalidRequest(
+                    "Unexpected privval message".to_owned(),
+                ));
+            }
+        };
+        Ok(Message { sum: Some(sum) })
+    }
+
+    /// Sign a prevote or a precommit
+    fn sign_vote(
+        &mut self,
+        vote: Option<Vote>,
+        chain_id: &str,
+    ) -> Result<Vote> {
+        self.check_chain_id(chain_id)?;
+        let mut vote = vote
+            .ok_or_else(|| Error::InvalidRequest("Missing vote".to_owned()))?;
+        let step = match SignedMsgType::from_i32(vote.r#type) {
+            Some(SignedMsgType::Prevote) => STEP_PREVOTE,
+            Some(SignedMsgType::Precommit) => STEP_PRECOMMIT,
+            _ => {
+                return Err(Error::InvalidRequest(format!(
+                    "Invalid vote type {}",
+                    vote.r#type
+                )));
+            }
+        };
+        let (signature, timestamp) = self.sign_hrs(
+            vote.height,
+            vote.round.into(),
+            step,
+            vote.timestamp.clone(),
+            |timestamp| {
+                canonical_vote(&vote, chain_id, timestamp)
+                    .encode_length_delimited_to_vec()
+            },
+            |sign_bytes| {
+                CanonicalVote::decode_length_delimited(sign_bytes)
+                    .ok()
+                    .and_then(|vote| vote.timestamp)
+            },
+        )?;
+        #[cfg(feature = "abcipp")]
+        if step == STEP_PRECOMMIT
+            && canonical_block_id(&vote.block_id).is_some()
+        {
+            // The vote extension is signed with the precommit of a block
+            use crate::facade::tendermint_proto::types::CanonicalVoteExtension;
+            let sign_bytes = CanonicalVoteExtension {
+                extension: vote.extension.clone(),
+                height: vote.height,
+                round: vote.round.into(),
+                chain_id: chain_id.to_owned(),
+            }
+            .encode_length_delimited_to_vec();
+            vote.extension_signature = self.sign_raw(&sign_bytes);
+        }
+        vote.timestamp = timestamp;
+        vote.signature = signature;
+        Ok(vote)
+    }
+
+    /// Sign a proposal
+    fn sign_proposal(
+        &mut self,
+        proposal: Option<Proposal>,
+        chain_id: &str,
+    ) -> Result<Proposal> {
+        self.check_chain_id(chain_id)?;
+        let mut proposal = proposal.ok_or_else(|| {
+            Error::InvalidRequest("Missing proposal".to_owned())
+        })?;
+        let (signature, timestamp) = self.sign_hrs(
+            proposal.height,
+            proposal.round.into(),
+            STEP_PROPOSAL,
+            proposal.timestamp.clone(),
+            |timestamp| {
+                canonical_proposal(&proposal, chain_id, timestamp)
+                    .encode_length_delimited_to_vec()
+            },
+            |sign_bytes| {
+                CanonicalProposal::decode_length_delimited(sign_bytes)
+                    .ok()
+                    .and_then(|proposal| proposal.timestamp)
+            },
+        )?;
+        proposal.timestamp = timestamp;
+        proposal.signature = signature;
+        Ok(proposal)
+    }
+
+    /// Sign a message at the given height, round and step, whose sign bytes
+    /// with a given timestamp are encoded with `sign_bytes`, unless it would
+    /// double-sign. Returns the signature and the timestamp of the signed
+    /// message.
+    ///
+    /// Like Tendermint's file private validator, if the same message is
+    /// requested again with only a different timestamp, the signature and
+    /// timestamp of the already signed message are returned, which can be
+    /// decoded from its sign bytes with `timestamp_of`.
+    fn sign_hrs(
+        &mut self,
+        height: i64,
+        round: i64,
+        step: u8,
+        timestamp: Option<Timestamp>,
+        sign_bytes: impl Fn(Option<Timestamp>) -> Vec<u8>,
+        timestamp_of: impl Fn(&[u8]) -> Option<Timestamp>,
+    ) -> Result<(Vec<u8>, Option<Timestamp>)> {
+        let last = self.state.get();
+        if last.check_hrs(height, round, step)? {
+            let last_timestamp = timestamp_of(&last.sign_bytes);
+            if sign_bytes(last_timestamp.clone()) == last.sign_bytes {
+                return Ok((last.signature.clone(), last_timestamp));
+            }
+            return Err(Error::DoubleSign(format!(
+                "A conflicting message was already signed at height {}, round \
+                 {}, step {}",
+                height, round, step
+            )));
+        }
+        let sign_bytes = sign_bytes(timestamp.clone());
+        let signature = self.sign_raw(&sign_bytes);
+        // The state is persisted before the signature is released
+        self.state.update(SignState {
+            height,
+            round,
+            step,
+            sign_bytes,
+            signature: signature.clone(),
+        })?;
+        Ok((signature, timestamp))
+    }
+
+    /// Sign the bytes with the consensus key, encoding the signature as
+    /// Tendermint does
+    fn sign_raw(&self, bytes: &[u8]) -> Vec<u8> {
+        match common::SigScheme::sign(&self.key, bytes) {
+            common::Signature::Ed25519(sig) => sig.try_to_vec().unwrap(),
+            // Without the recovery ID
+            common::Signature::Secp256k1(sig) => sig.0.serialize().to_vec(),
+        }
+    }
+
+    fn check_chain_id(&self, chain_id: &str) -> Result<()> {
+        if chain_id == self.chain_id {
+            Ok(())
+        } else {
+            Err(Error::ChainId(chain_id.to_owned(), self.chain_id.clone()))
+        }
+    }
+}
+
+/// Read a length-delimited privval message
+pub fn read_message(stream: &mut impl Read) -> Result<Message> {
+    read_delimited(stream)
+}
+
+/// Read a length-delimited protobuf message
+pub(super) fn read_delimited<M: prost::Message + Default>(
+    stream: &mut impl Read,
+) -> Result<M> {
+    let mut len = 0_usize;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0];
+        stream.read_exact(&mut byte)?;
+        len |= usize::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+        if shift > 28 {
+            return Err(Error::Decode("Invalid message length".to_owned()));
+        }
+    }
+    if len > MAX_MESSAGE_SIZE {
+        return Err(Error::Decode(format!("Message of {} bytes too big", len)));
+    }
+    let mut bytes = vec![0; len];
+    stream.read_exact(&mut bytes)?;
+    M::decode(&bytes[..]).map_err(|err| Error::Decode(err.to_string()))
+}
+
+/// The canonical encoding of a vote, whose sign bytes are signed
+fn canonical_vote(
+    vote: &Vote,
+    chain_id: &str,
+    timestamp: Option<Timestamp>,
+) -> CanonicalVote {
+    CanonicalVote {
+        r#type: vote.r#type,
+        height: vote.height,
+        round: vote.round.into(),
+        block_id: canonical_block_id(&vote.block_id),
+        timestamp,
+        chain_id: chain_id.to_owned(),
+    }
+}
+
+/// The canonical encoding of a proposal, whose sign bytes are signed
+fn canonical_proposal(
+    proposal: &Proposal,
+    chain_id: &str,
+    timestamp: Option<Timestamp>,
+) -> CanonicalProposal {
+    CanonicalProposal {
+        r#type: proposal.r#type,
+        height: proposal.height,
+        round: proposal.round.into(),
+        pol_round: proposal.pol_round.into(),
+        block_id: canonical_block_id(&proposal.block_id),
+        timestamp,
+        chain_id: chain_id.to_owned(),
+    }
+}
+
+/// The canonical encoding of a block ID, which is omitted for a nil block
+fn canonical_block_id(block_id: &Option<BlockId>) -> Option<CanonicalBlockId> {
+    let block_id = block_id.as_ref()?;
+    let part_set_header = block_id.part_set_header.clone().unwrap_or_default();
+    if block_id.hash.is_empty()
+        && part_set_header.total == 0
+        && part_set_header.hash.is_empty()
+    {
+        return None;
+    }
+    Some(CanonicalBlockId {
+        hash: block_id.hash.clone(),
+        part_set_header: Some(CanonicalPartSetHeader {
+            total: part_set_header.total,
+            hash: part_set_header.hash,
+        }),
+    })
+}
+
+/// Convert a public key to its Tendermint encoding
+fn tm_public_key(pk: &common::PublicKey) -> PublicKey {
+    let sum = match pk {
+        common::PublicKey::Ed25519(pk) => {
+            public_key::Sum::Ed25519(pk.try_to_vec().unwrap())
+        }
+        common::PublicKey::Secp256k1(pk) => {
+            public_key::Sum::Secp256k1(pk.try_to_vec().unwrap())
+        }
+    };
+    PublicKey { sum: Some(sum) }
+}
+
+fn remote_signer_error(err: Error) -> RemoteSignerError {
+    RemoteSignerError {
+        code: 0,
+        description: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshDeserialize;
+
+    use super::*;
+    use crate::facade::tendermint_proto::privval::{
+        PubKeyRequest, SignVoteRequest,
+    };
+    use crate::facade::tendermint_proto::types::PartSetHeader;
+
+    const CHAIN_ID: &str = "test-chain";
+
+    fn signer(dir: &tempfile::TempDir) -> PrivvalSigner {
+        let state =
+            PersistedSignState::load_or_new(dir.path().join("state.json"))
+                .unwrap();
+        PrivvalSigner::new(
+            CHAIN_ID.to_owned(),
+            namada::types::key::testing::keypair_1(),
+            state,
+        )
+    }
+
+    fn prevote(height: i64, block_hash: u8, seconds: i64) -> Vote {
+        Vote {
+            r#type: SignedMsgType::Prevote as i32,
+            height,
+            round: 0,
+            block_id: Some(BlockId {
+                hash: vec![block_hash; 32],
+                part_set_header: Some(PartSetHeader {
+                    total: 1,
+                    hash: vec![block_hash; 32],
+                }),
+            }),
+            timestamp: Some(Timestamp { seconds, nanos: 0 }),
+            ..Default::default()
+        }
+    }
+
+    fn sign_vote(signer: &mut PrivvalSigner, vote: Vote) -> SignedVoteResponse {
+        let request = Message {
+            sum: Some(message::Sum::SignVoteRequest(SignVoteRequest {
+                vote: Some(vote),
+                chain_id: CHAIN_ID.to_owned(),
+            })),
+        };
+        match signer.handle(request).unwrap().sum {
+            Some(message::Sum::SignedVoteResponse(response)) => response,
+            _ => panic!("Expected a signed vote response"),
+        }
+    }
+
+    #[test]
+    fn test_pub_key_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut signer = signer(&dir);
+        let request = |chain_id: &str| Message {
+            sum: Some(message::Sum::PubKeyRequest(PubKeyRequest {
+                chain_id: chain_id.to_owned(),
+            })),
+        };
+        let pk = namada::types::key::testing::keypair_1().ref_to();
+        match signer.handle(request(CHAIN_ID)).unwrap().sum {
+            Some(message::Sum::PubKeyResponse(response)) => {
+                assert_eq!(response.pub_key, Some(tm_public_key(&pk)));
+            }
+            _ => panic!("Expected a public key response"),
+        }
+        match signer.handle(request("other-chain")).unwrap().sum {
+            Some(message::Sum::PubKeyResponse(response)) => {
+                assert!(response.pub_key.is_none());
+                assert!(response.error.is_some());
+            }
+            _ => panic!("Expected a public key response"),
+        }
+    }
+
+    #[test]
+    fn test_sign_vote() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut signer = signer(&dir);
+        let vote = prevote(10, 1, 100);
+        let signed = sign_vote(&mut signer, vote.clone()).vote.unwrap();
+
+        let sign_bytes =
+            canonical_vote(&vote, CHAIN_ID, vote.timestamp.clone())
+                .encode_length_delimited_to_vec();
+        let sig = common::Signature::Ed25519(
+            ed25519::Signature::try_from_slice(&signed.signature).unwrap(),
+        );
+        let pk = namada::types::key::testing::keypair_1().ref_to();
+        common::SigScheme::verify_signature_raw(&pk, &sign_bytes, &sig)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_double_sign_protection() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut signer = signer(&dir);
+        let signed = sign_vote(&mut signer, prevote(10, 1, 100)).vote.unwrap();
+
+        // The same vote with another timestamp gets the same signature
+        let resigned =
+            sign_vote(&mut signer, prevote(10, 1, 200)).vote.unwrap();
+        assert_eq!(resigned.signature, signed.signature);
+        assert_eq!(resigned.timestamp, signed.timestamp);
+
+        // A vote for another block at the same height, round and step or a
+        // vote at a lower height are refused, even after a restart
+        let mut signer = self::signer(&dir);
+        let response = sign_vote(&mut signer, prevote(10, 2, 100));
+        assert!(response.vote.is_none() && response.error.is_some());
+        let response = sign_vote(&mut signer, prevote(9, 1, 100));
+        assert!(response.vote.is_none() && response.error.is_some());
+
+        // The next height can be signed
+        let response = sign_vote(&mut signer, prevote(11, 2, 100));
+        assert!(response.vote.is_some());
+    }
+
+    #[test]
+    fn test_read_message() {
+        let message = Message {
+            sum: Some(message::Sum::PingResponse(PingResponse {})),
+        };
+        let mut bytes = message.encode_length_delimited_to_vec();
+        // A message of more than 127 bytes has a multi-byte length
+        let request = Message {
+            sum: Some(message::Sum::PubKeyRequest(PubKeyRequest {
+                chain_id: "c".repeat(200),
+            })),
+        };
+        bytes.extend(request.encode_length_delimited_to_vec());
+        let mut reader = &bytes[..];
+        assert_eq!(read_message(&mut reader).unwrap(), message);
+        assert_eq!(read_message(&mut reader).unwrap(), request);
+    }
+}
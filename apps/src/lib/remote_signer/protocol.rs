@@ -0,0 +1,495 @@
+//! The protocol of the node with the remote signer, which signs the node's
+//! protocol txs and vote extensions with the protocol key. The signer listens
+//! on a Unix or a TCP socket and the node connects to it for each request.
+//! Requests and responses are Borsh encoded, prefixed with their length as a
+//! big-endian `u32`. The node verifies the returned signatures against the
+//! protocol public key.
+
+use std::io::{Read, Write};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use namada::proto::{Tx, TxCode};
+use namada::types::key::*;
+use namada::types::transaction::{hash_tx, TxType};
+
+use super::state::{PersistedSignState, SignState};
+use super::{Error, Listener, Result};
+use crate::client::signer::{self, TxSigner};
+use crate::facade::tendermint_config::net::Address as TendermintAddress;
+
+/// The maximum size of a message
+const MAX_MESSAGE_SIZE: usize = 1 << 20;
+/// Vote extensions are signed once per round
+const STEP_VOTE_EXTENSION: u8 = 1;
+
+/// A request of the node to the remote signer
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum Request {
+    /// Get the protocol public key
+    PublicKey,
+    /// Sign a protocol tx of the protocol key from its signing bytes (see
+    /// [`Tx::signing_bytes`])
+    SignProtocolTx(Vec<u8>),
+    /// Sign the vote extension of the node at the given height and round
+    SignVoteExtension {
+        /// Block height
+        height: i64,
+        /// Consensus round
+        round: i64,
+        /// The vote extension
+        extension: Vec<u8>,
+    },
+}
+
+/// A response of the remote signer to the node
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub enum Response {
+    /// The protocol public key
+    PublicKey(common::PublicKey),
+    /// A signature with the protocol key
+    Signature(common::Signature),
+    /// The request was refused
+    Error(String),
+}
+
+/// Signs protocol txs and vote extensions with the protocol key
+pub struct ProtocolSigner {
+    chain_id: String,
+    key: common::SecretKey,
+    state: PersistedSignState,
+}
+
+impl ProtocolSigner {
+    /// Sign the protocol txs and vote extensions of the given chain with the
+    /// key, starting from the given sign state of the vote extensions
+    pub fn new(
+        chain_id: String,
+        key: common::SecretKey,
+        state: PersistedSignState,
+    ) -> Self {
+        Self {
+            chain_id,
+            key,
+            state,
+        }
+    }
+
+    /// Handle a request of the node
+    pub fn handle(&mut self, request: Request) -> Response {
+        let result = match request {
+            Request::PublicKey => Ok(Response::PublicKey(self.key.ref_to())),
+            Request::SignProtocolTx(signing_bytes) => self
+                .sign_protocol_tx(&signing_bytes)
+                .map(Response::Signature),
+            Request::SignVoteExtension {
+                height,
+                round,
+                extension,
+            } => self
+                .sign_vote_extension(height, round, extension)
+                .map(Response::Signature),
+        };
+        result.unwrap_or_else(|err| {
+            tracing::error!("Refused to sign: {}", err);
+            Response::Error(err.to_string())
+        })
+    }
+
+    /// Sign the hash of a tx, which must be a protocol tx of the protocol key
+    fn sign_protocol_tx(
+        &self,
+        signing_bytes: &[u8],
+    ) -> Result<common::Signature> {
+        let tx = Tx::try_from(signing_bytes)
+            .map_err(|err| Error::Decode(err.to_string()))?;
+        // The stable encoding has the code hash in place of the code
+        let is_protocol_tx = tx.code == TxCode::Wasm(hash_tx(&[]).0.to_vec())
+            && matches!(
+                tx.data.as_deref().map(TxType::try_from_slice),
+                Some(Ok(TxType::Protocol(protocol_tx)))
+                    if protocol_tx.pk == self.key.ref_to()
+            );
+        if !is_protocol_tx {
+            return Err(Error::InvalidRequest(
+                "Only the protocol txs of the protocol key can be signed"
+                    .to_owned(),
+            ));
+        }
+        Ok(common::SigScheme::sign(&self.key, hash_tx(signing_bytes).0))
+    }
+
+    /// Sign a vote extension, unless another one was already signed at this
+    /// height and round or later
+    fn sign_vote_extension(
+        &mut self,
+        height: i64,
+        round: i64,
+        extension: Vec<u8>,
+    ) -> Result<common::Signature> {
+        let sign_bytes =
+            vote_extension_sign_bytes(&self.chain_id, height, round, extension);
+        let last = self.state.get();
+        if last.check_hrs(height, round, STEP_VOTE_EXTENSION)? {
+            if last.sign_bytes == sign_bytes {
+                return common::Signature::try_from_slice(&last.signature)
+                    .map_err(|err| Error::SignState(err.to_string()));
+            }
+            return Err(Error::DoubleSign(format!(
+                "A conflicting vote extension was already signed at height \
+                 {}, round {}",
+                height, round
+            )));
+        }
+        let signature = common::SigScheme::sign(&self.key, &sign_bytes);
+        // The state is persisted before the signature is released
+        self.state.update(SignState {
+            height,
+            round,
+            step: STEP_VOTE_EXTENSION,
+            sign_bytes,
+            signature: signature.try_to_vec().unwrap(),
+        })?;
+        Ok(signature)
+    }
+}
+
+/// The bytes of a vote extension of the given chain, height and round that
+/// are signed with the protocol key
+pub fn vote_extension_sign_bytes(
+    chain_id: &str,
+    height: i64,
+    round: i64,
+    extension: Vec<u8>,
+) -> Vec<u8> {
+    (chain_id.to_owned(), height, round, extension)
+        .try_to_vec()
+        .unwrap()
+}
+
+/// Serve the requests of the node on the given socket. Only fails if the
+/// socket cannot be bound.
+pub fn serve(
+    mut signer: ProtocolSigner,
+    addr: &TendermintAddress,
+) -> Result<()> {
+    let listener = Listener::bind(addr)?;
+    tracing::info!("Serving the protocol key signer at {}", addr);
+    loop {
+        let result = listener.accept().and_then(|mut stream| {
+            let request = read_message(&mut stream)?;
+            let response = signer.handle(request);
+            write_message(&mut stream, &response)
+        });
+        if let Err(err) = result {
+            tracing::error!("Failed to serve a remote signer request: {}", err);
+        }
+    }
+}
+
+/// The node's client of the remote signer
+#[derive(Debug)]
+pub struct RemoteSigner {
+    addr: TendermintAddress,
+    chain_id: String,
+    public_key: common::PublicKey,
+}
+
+impl RemoteSigner {
+    /// Connect to the remote signer of the given chain on the given socket to
+    /// get its protocol public key
+    pub fn connect(addr: TendermintAddress, chain_id: String) -> Result<Self> {
+        match request(&addr, &Request::PublicKey)? {
+            Response::PublicKey(public_key) => Ok(Self {
+                addr,
+                chain_id,
+                public_key,
+            }),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Sign a vote extension with the protocol key
+    pub fn sign_vote_extension(
+        &self,
+        height: i64,
+        round: i64,
+        extension: Vec<u8>,
+    ) -> Result<common::Signature> {
+        let sign_bytes = vote_extension_sign_bytes(
+            &self.chain_id,
+            height,
+            round,
+            extension.clone(),
+        );
+        let sig = self.request_signature(&Request::SignVoteExtension {
+            height,
+            round,
+            extension,
+        })?;
+        self.verify(&sign_bytes, sig)
+    }
+
+    fn request_signature(&self, req: &Request) -> Result<common::Signature> {
+        match request(&self.addr, req)? {
+            Response::Signature(sig) => Ok(sig),
+            Response::Error(err) => Err(Error::Remote(err)),
+            Response::PublicKey(_) => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Check that the signature of the data is valid for the protocol key
+    fn verify(
+        &self,
+        data: &[u8],
+        sig: common::Signature,
+    ) -> Result<common::Signature> {
+        common::SigScheme::verify_signature_raw(&self.public_key, data, &sig)
+            .map_err(|err| Error::InvalidSignature(err.to_string()))?;
+        Ok(sig)
+    }
+}
+
+/// The remote signer signs the node's protocol txs
+impl TxSigner for RemoteSigner {
+    fn public_key(&self) -> common::PublicKey {
+        self.public_key.clone()
+    }
+
+    fn sign_tx_bytes(
+        &self,
+        signing_bytes: &[u8],
+    ) -> signer::Result<common::Signature> {
+        let sig = self.request_signature(&Request::SignProtocolTx(
+            signing_bytes.to_vec(),
+        ))?;
+        Ok(self.verify(&hash_tx(signing_bytes).0, sig)?)
+    }
+}
+
+/// The signer of the node's protocol txs and vote extensions, which holds the
+/// protocol key either in the node or on a remote signer
+#[derive(Debug)]
+pub enum ProtocolKey {
+    /// The protocol key loaded from the node's wallet
+    Local {
+        /// The chain of the signed vote extensions
+        chain_id: String,
+        /// The protocol key
+        key: common::SecretKey,
+    },
+    /// The protocol key held by a remote signer
+    Remote(RemoteSigner),
+}
+
+impl ProtocolKey {
+    /// Sign a vote extension with the protocol key
+    pub fn sign_vote_extension(
+        &self,
+        height: i64,
+        round: i64,
+        extension: Vec<u8>,
+    ) -> Result<common::Signature> {
+        match self {
+            Self::Local { chain_id, key } => Ok(common::SigScheme::sign(
+                key,
+                vote_extension_sign_bytes(chain_id, height, round, extension),
+            )),
+            Self::Remote(signer) => {
+                signer.sign_vote_extension(height, round, extension)
+            }
+        }
+    }
+}
+
+/// The protocol txs are signed with the protocol key
+impl TxSigner for ProtocolKey {
+    fn public_key(&self) -> common::PublicKey {
+        match self {
+            Self::Local { key, .. } => key.ref_to(),
+            Self::Remote(signer) => signer.public_key(),
+        }
+    }
+
+    fn sign_tx_bytes(
+        &self,
+        signing_bytes: &[u8],
+    ) -> signer::Result<common::Signature> {
+        match self {
+            Self::Local { key, .. } => key.sign_tx_bytes(signing_bytes),
+            Self::Remote(signer) => signer.sign_tx_bytes(signing_bytes),
+        }
+    }
+}
+
+/// Send a request to the remote signer and wait for its response
+fn request(addr: &TendermintAddress, request: &Request) -> Result<Response> {
+    let mut stream = super::connect(addr)?;
+    write_message(&mut stream, request)?;
+    read_message(&mut stream)
+}
+
+fn write_message(
+    stream: &mut impl Write,
+    message: &impl BorshSerialize,
+) -> Result<()> {
+    let bytes = message.try_to_vec()?;
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| Error::InvalidRequest("Message too big".to_owned()))?;
+    stream.write_all(&len.to_be_bytes())?;
+    stream.write_all(&bytes)?;
+    Ok(())
+}
+
+fn read_message<T: BorshDeserialize>(stream: &mut impl Read) -> Result<T> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        return Err(Error::Decode(format!("Message of {} bytes too big", len)));
+    }
+    let mut bytes = vec![0; len];
+    stream.read_exact(&mut bytes)?;
+    T::try_from_slice(&bytes).map_err(|err| Error::Decode(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::types::transaction::process_tx;
+    use namada::types::transaction::protocol::ProtocolTxType;
+
+    use super::*;
+
+    fn signer(dir: &tempfile::TempDir) -> ProtocolSigner {
+        let state =
+            PersistedSignState::load_or_new(dir.path().join("state.json"))
+                .unwrap();
+        ProtocolSigner::new(
+            "test-chain".to_owned(),
+            namada::types::key::testing::keypair_1(),
+            state,
+        )
+    }
+
+    /// Serve the signer on the given socket and connect the node's client
+    fn serve_and_connect(
+        dir: &tempfile::TempDir,
+        addr: TendermintAddress,
+    ) -> RemoteSigner {
+        let signer = signer(dir);
+        let server_addr = addr.clone();
+        std::thread::spawn(move || serve(signer, &server_addr));
+        loop {
+            match RemoteSigner::connect(addr.clone(), "test-chain".to_owned()) {
+                Ok(remote) => break remote,
+                Err(_) => {
+                    std::thread::sleep(std::time::Duration::from_millis(10))
+                }
+            }
+        }
+    }
+
+    /// Test that the remote signer signs protocol txs of its key over the
+    /// socket, which are accepted as signed by the protocol key
+    #[test]
+    fn test_sign_protocol_tx() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("protocol.sock");
+        let remote = serve_and_connect(
+            &dir,
+            TendermintAddress::Unix {
+                path: path.to_string_lossy().into_owned(),
+            },
+        );
+        let keypair = namada::types::key::testing::keypair_1();
+        assert_eq!(remote.public_key(), keypair.ref_to());
+
+        let protocol_tx =
+            || ProtocolTxType::NewDkgKeypair(Tx::new(vec![], None));
+        let tx = protocol_tx().unsigned_tx(&keypair.ref_to());
+        let signed = remote.sign_tx(tx.clone()).unwrap();
+        assert!(matches!(process_tx(signed), Ok(TxType::Protocol(_))));
+
+        // A protocol tx of another key or a non-protocol tx is refused
+        let other_key = namada::types::key::testing::keypair_2().ref_to();
+        let other_tx = protocol_tx().unsigned_tx(&other_key);
+        assert!(remote.sign_tx(other_tx).is_err());
+        let raw_tx = Tx::new(b"wasm code".to_vec(), Some(b"data".to_vec()));
+        assert!(remote.sign_tx(raw_tx).is_err());
+    }
+
+    #[test]
+    fn test_sign_vote_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut signer = signer(&dir);
+        let sign = |signer: &mut ProtocolSigner, height, extension: &[u8]| {
+            signer.handle(Request::SignVoteExtension {
+                height,
+                round: 0,
+                extension: extension.to_vec(),
+            })
+        };
+        let sig = match sign(&mut signer, 5, b"extension") {
+            Response::Signature(sig) => sig,
+            response => panic!("Unexpected response {:?}", response),
+        };
+        // Signing the same extension again gives the same signature
+        assert!(matches!(
+            sign(&mut signer, 5, b"extension"),
+            Response::Signature(resigned) if resigned == sig
+        ));
+        // Another extension at the same height or an earlier height is refused
+        assert!(matches!(sign(&mut signer, 5, b"other"), Response::Error(_)));
+        assert!(matches!(
+            sign(&mut signer, 4, b"extension"),
+            Response::Error(_)
+        ));
+        assert!(matches!(
+            sign(&mut signer, 6, b"other"),
+            Response::Signature(_)
+        ));
+    }
+
+    /// Test that the node signs vote extensions over an encrypted TCP
+    /// connection to the remote signer, and that the signatures are valid for
+    /// the protocol key
+    #[test]
+    fn test_sign_vote_extension_over_tcp() {
+        let dir = tempfile::tempdir().unwrap();
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let remote = serve_and_connect(
+            &dir,
+            TendermintAddress::Tcp {
+                peer_id: None,
+                host: "127.0.0.1".to_owned(),
+                port,
+            },
+        );
+        let protocol_key = ProtocolKey::Remote(remote);
+        let keypair = namada::types::key::testing::keypair_1();
+        assert_eq!(protocol_key.public_key(), keypair.ref_to());
+
+        let sig = protocol_key
+            .sign_vote_extension(5, 0, b"extension".to_vec())
+            .unwrap();
+        let local = ProtocolKey::Local {
+            chain_id: "test-chain".to_owned(),
+            key: keypair,
+        };
+        assert_eq!(
+            local
+                .sign_vote_extension(5, 0, b"extension".to_vec())
+                .unwrap(),
+            sig
+        );
+        // A conflicting extension is refused by the remote signer
+        assert!(
+            protocol_key
+                .sign_vote_extension(5, 0, b"other".to_vec())
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,343 @@
+//! Tendermint's secret connection protocol, which encrypts and authenticates
+//! the remote signer's TCP connections. The peers exchange ephemeral X25519
+//! keys, derive the ChaCha20-Poly1305 keys of both directions and a challenge
+//! from their shared secret and sign the challenge with their Ed25519 identity
+//! keys. The data is then sent in sealed frames of up to 1024 bytes.
+
+use std::cmp;
+use std::io::{self, Read, Write};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use merlin::Transcript;
+use namada::types::key::*;
+use prost::Message as _;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+use x25519_dalek::{x25519, X25519_BASEPOINT_BYTES};
+
+use super::privval::read_delimited;
+use super::{Error, Result};
+use crate::facade::tendermint_proto::crypto::{public_key, PublicKey};
+use crate::facade::tendermint_proto::p2p::AuthSigMessage;
+
+/// The size of the length prefix of the data in a frame
+const DATA_LEN_SIZE: usize = 4;
+/// The maximum size of the data in a frame
+const DATA_MAX_SIZE: usize = 1024;
+/// The size of a frame, whose unused data bytes are zeroed
+const TOTAL_FRAME_SIZE: usize = DATA_LEN_SIZE + DATA_MAX_SIZE;
+/// The size of the authentication tag of a sealed frame
+const TAG_SIZE: usize = 16;
+/// The size of a sealed frame
+const SEALED_FRAME_SIZE: usize = TOTAL_FRAME_SIZE + TAG_SIZE;
+/// The HKDF info of the derivation of the keys and the challenge
+const KEY_AND_CHALLENGE_GEN: &[u8] =
+    b"TENDERMINT_SECRET_CONNECTION_KEY_AND_CHALLENGE_GEN";
+
+/// A connection encrypted with Tendermint's secret connection protocol
+pub struct SecretConnection<IO> {
+    io: IO,
+    send_cipher: ChaCha20Poly1305,
+    send_nonce: u64,
+    recv_cipher: ChaCha20Poly1305,
+    recv_nonce: u64,
+    /// The data of the last received frame that wasn't read yet
+    recv_buffer: Vec<u8>,
+}
+
+impl<IO: Read + Write> SecretConnection<IO> {
+    /// Perform the handshake with the peer on the given connection under the
+    /// identity key. Returns the encrypted connection and the identity key of
+    /// the peer, which has proven that it holds its secret key.
+    pub fn new(
+        io: IO,
+        identity: &ed25519::SecretKey,
+    ) -> Result<(Self, ed25519::PublicKey)> {
+        let mut eph_secret = [0_u8; 32];
+        OsRng.fill_bytes(&mut eph_secret);
+        Self::handshake(io, identity, eph_secret)
+    }
+
+    /// Perform the handshake with the given ephemeral X25519 secret key
+    fn handshake(
+        mut io: IO,
+        identity: &ed25519::SecretKey,
+        eph_secret: [u8; 32],
+    ) -> Result<(Self, ed25519::PublicKey)> {
+        let local_eph_pub = x25519(eph_secret, X25519_BASEPOINT_BYTES);
+
+        // Exchange the ephemeral keys as protobuf `BytesValue`s
+        io.write_all(&local_eph_pub.to_vec().encode_length_delimited_to_vec())?;
+        io.flush()?;
+        let remote_eph_pub: [u8; 32] = read_delimited::<Vec<u8>>(&mut io)?
+            .try_into()
+            .map_err(|_| handshake_error("Invalid ephemeral public key"))?;
+
+        let local_is_lower = local_eph_pub < remote_eph_pub;
+        let (lower, upper) = if local_is_lower {
+            (&local_eph_pub, &remote_eph_pub)
+        } else {
+            (&remote_eph_pub, &local_eph_pub)
+        };
+        let dh_secret = x25519(eph_secret, remote_eph_pub);
+        // A remote key of low order would give a known secret
+        if dh_secret == [0; 32] {
+            return Err(handshake_error("Low order ephemeral public key"));
+        }
+
+        let mut transcript =
+            Transcript::new(b"TENDERMINT_SECRET_CONNECTION_TRANSCRIPT_HASH");
+        transcript.append_message(b"EPHEMERAL_LOWER_PUBLIC_KEY", lower);
+        transcript.append_message(b"EPHEMERAL_UPPER_PUBLIC_KEY", upper);
+        transcript.append_message(b"DH_SECRET", &dh_secret);
+        let mut challenge = [0_u8; 32];
+        transcript.challenge_bytes(b"SECRET_CONNECTION_MAC", &mut challenge);
+
+        // The last 32 bytes were the challenge of a former version of the
+        // protocol and are unused
+        let mut keys = [0_u8; 96];
+        Hkdf::<Sha256>::new(None, &dh_secret)
+            .expand(KEY_AND_CHALLENGE_GEN, &mut keys)
+            .expect("96 bytes is a valid length for HKDF-SHA256");
+        let (recv_key, send_key) = if local_is_lower {
+            (&keys[..32], &keys[32..64])
+        } else {
+            (&keys[32..64], &keys[..32])
+        };
+        let mut conn = Self {
+            io,
+            send_cipher: ChaCha20Poly1305::new(Key::from_slice(send_key)),
+            send_nonce: 0,
+            recv_cipher: ChaCha20Poly1305::new(Key::from_slice(recv_key)),
+            recv_nonce: 0,
+            recv_buffer: vec![],
+        };
+
+        // Authenticate the peers by their signatures of the challenge
+        let signature = ed25519::SigScheme::sign(identity, challenge);
+        let auth = AuthSigMessage {
+            pub_key: Some(PublicKey {
+                sum: Some(public_key::Sum::Ed25519(
+                    identity.ref_to().try_to_vec().unwrap(),
+                )),
+            }),
+            sig: signature.try_to_vec().unwrap(),
+        };
+        conn.write_all(&auth.encode_length_delimited_to_vec())?;
+        conn.flush()?;
+        let remote_auth: AuthSigMessage = read_delimited(&mut conn)?;
+        let remote_pubkey = match remote_auth.pub_key.and_then(|pk| pk.sum) {
+            Some(public_key::Sum::Ed25519(bytes)) => {
+                ed25519::PublicKey::try_from_slice(&bytes).map_err(|err| {
+                    handshake_error(format!("Invalid identity key: {}", err))
+                })?
+            }
+            _ => {
+                return Err(handshake_error(
+                    "The identity key must be an Ed25519 key",
+                ));
+            }
+        };
+        let remote_signature =
+            ed25519::Signature::try_from_slice(&remote_auth.sig).map_err(
+                |err| handshake_error(format!("Invalid signature: {}", err)),
+            )?;
+        ed25519::SigScheme::verify_signature_raw(
+            &remote_pubkey,
+            &challenge,
+            &remote_signature,
+        )
+        .map_err(|err| handshake_error(err.to_string()))?;
+        Ok((conn, remote_pubkey))
+    }
+}
+
+impl<IO: Write> Write for SecretConnection<IO> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        let chunk = &data[..cmp::min(data.len(), DATA_MAX_SIZE)];
+        let mut frame = [0_u8; TOTAL_FRAME_SIZE];
+        frame[..DATA_LEN_SIZE]
+            .copy_from_slice(&(chunk.len() as u32).to_le_bytes());
+        frame[DATA_LEN_SIZE..DATA_LEN_SIZE + chunk.len()]
+            .copy_from_slice(chunk);
+        let sealed = self
+            .send_cipher
+            .encrypt(&nonce(self.send_nonce), &frame[..])
+            .map_err(|_| invalid_data("Failed to seal a frame"))?;
+        self.send_nonce = self
+            .send_nonce
+            .checked_add(1)
+            .ok_or_else(|| invalid_data("Nonce overflow"))?;
+        self.io.write_all(&sealed)?;
+        Ok(chunk.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+impl<IO: Read> Read for SecretConnection<IO> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.recv_buffer.is_empty() {
+            let mut sealed = [0_u8; SEALED_FRAME_SIZE];
+            self.io.read_exact(&mut sealed)?;
+            let frame = self
+                .recv_cipher
+                .decrypt(&nonce(self.recv_nonce), &sealed[..])
+                .map_err(|_| invalid_data("Failed to open a frame"))?;
+            self.recv_nonce = self
+                .recv_nonce
+                .checked_add(1)
+                .ok_or_else(|| invalid_data("Nonce overflow"))?;
+            let mut len = [0; DATA_LEN_SIZE];
+            len.copy_from_slice(&frame[..DATA_LEN_SIZE]);
+            let len = u32::from_le_bytes(len) as usize;
+            if len > DATA_MAX_SIZE {
+                return Err(invalid_data("Invalid frame length"));
+            }
+            self.recv_buffer =
+                frame[DATA_LEN_SIZE..DATA_LEN_SIZE + len].to_vec();
+        }
+        let len = cmp::min(buf.len(), self.recv_buffer.len());
+        buf[..len].copy_from_slice(&self.recv_buffer[..len]);
+        self.recv_buffer.drain(..len);
+        Ok(len)
+    }
+}
+
+/// The nonce of a frame from the frame counter
+fn nonce(counter: u64) -> Nonce {
+    let mut nonce = [0_u8; 12];
+    nonce[4..].copy_from_slice(&counter.to_le_bytes());
+    *Nonce::from_slice(&nonce)
+}
+
+fn handshake_error(msg: impl ToString) -> Error {
+    Error::Handshake(msg.to_string())
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::net::UnixStream;
+
+    use data_encoding::HEXLOWER;
+
+    use super::*;
+
+    fn hex(s: &str) -> [u8; 32] {
+        HEXLOWER.decode(s.as_bytes()).unwrap().try_into().unwrap()
+    }
+
+    /// A connection that replays the recorded messages of a peer and records
+    /// the messages sent to it
+    struct ReplayedPeer {
+        received: io::Cursor<Vec<u8>>,
+        sent: Vec<u8>,
+    }
+
+    impl Read for ReplayedPeer {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.received.read(buf)
+        }
+    }
+
+    impl Write for ReplayedPeer {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.sent.write(data)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn decode_transcript(transcript: &str) -> Vec<u8> {
+        let transcript: String = transcript.split_whitespace().collect();
+        HEXLOWER.decode(transcript.as_bytes()).unwrap()
+    }
+
+    /// Test the handshake and the exchange of a frame against the transcript
+    /// of a peer recorded from an independent implementation of the
+    /// protocol. The peer sends its ephemeral key, its authentication and a
+    /// "ping" frame and expects our ephemeral key, our authentication and a
+    /// "pong" frame, all of which are deterministic for fixed ephemeral keys.
+    #[test]
+    fn test_secret_connection_transcript() {
+        let local_identity = ed25519::SecretKey::try_from_slice(&hex(
+            "3967644578fa9365f9203fcae8fc4d65e5419669643a3b3fcb3fc15eeeb525c1",
+        ))
+        .unwrap();
+        let remote_identity = ed25519::SecretKey::try_from_slice(&hex(
+            "2727b37e3d90ad76f215e14c0ca05a4e391b745ad12b743405e12e16876db327",
+        ))
+        .unwrap();
+        let local_eph_secret = hex(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        );
+        let peer = ReplayedPeer {
+            received: io::Cursor::new(decode_transcript(include_str!(
+                "testdata/secret_connection_peer.hex"
+            ))),
+            sent: vec![],
+        };
+
+        let (mut conn, peer_pubkey) = SecretConnection::handshake(
+            peer,
+            &local_identity,
+            local_eph_secret,
+        )
+        .unwrap();
+        assert_eq!(peer_pubkey, remote_identity.ref_to());
+        let mut data = [0; 4];
+        conn.read_exact(&mut data).unwrap();
+        assert_eq!(&data, b"ping");
+        conn.write_all(b"pong").unwrap();
+        assert_eq!(
+            conn.io.sent,
+            decode_transcript(include_str!(
+                "testdata/secret_connection_local.hex"
+            ))
+        );
+    }
+
+    /// Test that the peers authenticate each other and exchange data larger
+    /// than a frame
+    #[test]
+    fn test_secret_connection() {
+        let (local, remote) = UnixStream::pair().unwrap();
+        let local_identity = ed25519::SigScheme::generate(&mut OsRng);
+        let remote_identity = ed25519::SigScheme::generate(&mut OsRng);
+        let local_pubkey = local_identity.ref_to();
+        let remote_pubkey = remote_identity.ref_to();
+        let handle = std::thread::spawn(move || {
+            let (mut conn, peer_pubkey) =
+                SecretConnection::new(remote, &remote_identity).unwrap();
+            assert_eq!(peer_pubkey, local_pubkey);
+            let mut data = vec![0; 3000];
+            conn.read_exact(&mut data).unwrap();
+            conn.write_all(&data).unwrap();
+        });
+
+        let (mut conn, peer_pubkey) =
+            SecretConnection::new(local, &local_identity).unwrap();
+        assert_eq!(peer_pubkey, remote_pubkey);
+        let data: Vec<u8> = (0..3000).map(|i| i as u8).collect();
+        conn.write_all(&data).unwrap();
+        let mut echoed = vec![0; 3000];
+        conn.read_exact(&mut echoed).unwrap();
+        assert_eq!(echoed, data);
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+//! Double-sign protection of the remote signer. Like Tendermint's file
+//! private validator, the signer persists the height, round and step of the
+//! last message that it signed with a key, together with its sign bytes and
+//! signature, before it returns the signature. A message at an earlier height,
+//! round or step is refused and so is a different message at the same one.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Error, Result};
+
+/// The step of a proposal
+pub const STEP_PROPOSAL: u8 = 1;
+/// The step of a prevote
+pub const STEP_PREVOTE: u8 = 2;
+/// The step of a precommit
+pub const STEP_PRECOMMIT: u8 = 3;
+
+/// The height, round and step of the last signed message of a key
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignState {
+    /// Block height
+    pub height: i64,
+    /// Consensus round
+    pub round: i64,
+    /// Consensus step
+    pub step: u8,
+    /// The sign bytes of the last signed message
+    #[serde(with = "hex_bytes")]
+    pub sign_bytes: Vec<u8>,
+    /// The signature of the last signed message
+    #[serde(with = "hex_bytes")]
+    pub signature: Vec<u8>,
+}
+
+impl SignState {
+    /// Check that a message at the given height, round and step can be
+    /// signed. Returns `true` if a message was already signed at this height,
+    /// round and step, in which case the caller must check that it's the same
+    /// message.
+    pub fn check_hrs(&self, height: i64, round: i64, step: u8) -> Result<bool> {
+        let regression = |what: &str| {
+            Err(Error::DoubleSign(format!(
+                "{} regression at height {}, round {}, step {}, the last \
+                 signed message is at height {}, round {}, step {}",
+                what, height, round, step, self.height, self.round, self.step
+            )))
+        };
+        if self.height > height {
+            return regression("Height");
+        }
+        if self.height == height {
+            if self.round > round {
+                return regression("Round");
+            }
+            if self.round == round {
+                if self.step > step {
+                    return regression("Step");
+                }
+                if self.step == step {
+                    return Ok(!self.sign_bytes.is_empty());
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// A sign state persisted in a JSON file
+#[derive(Debug)]
+pub struct PersistedSignState {
+    path: PathBuf,
+    state: SignState,
+}
+
+impl PersistedSignState {
+    /// Load the sign state from the given file or start with an empty state
+    /// if the file doesn't exist yet
+    pub fn load_or_new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let state = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|err| {
+                Error::SignState(format!(
+                    "Invalid sign state file {}: {}",
+                    path.to_string_lossy(),
+                    err
+                ))
+            })?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                SignState::default()
+            }
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self { path, state })
+    }
+
+    /// Get the last sign state
+    pub fn get(&self) -> &SignState {
+        &self.state
+    }
+
+    /// Persist a new sign state. The file is replaced atomically, so that a
+    /// crash can't leave it half written.
+    pub fn update(&mut self, state: SignState) -> Result<()> {
+        let tmp_path = self.path.with_extension("json.tmp");
+        let json = serde_json::to_vec_pretty(&state)
+            .expect("Encoding the sign state shouldn't fail");
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(&json)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)?;
+        self.state = state;
+        Ok(())
+    }
+}
+
+/// Encode bytes as a hex string in JSON
+mod hex_bytes {
+    use data_encoding::HEXUPPER;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&HEXUPPER.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let hex = String::deserialize(deserializer)?;
+        HEXUPPER.decode(hex.as_bytes()).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_hrs() {
+        let state = SignState {
+            height: 10,
+            round: 1,
+            step: STEP_PREVOTE,
+            sign_bytes: vec![1],
+            signature: vec![2],
+        };
+        assert!(!state.check_hrs(11, 0, STEP_PROPOSAL).unwrap());
+        assert!(!state.check_hrs(10, 2, STEP_PROPOSAL).unwrap());
+        assert!(!state.check_hrs(10, 1, STEP_PRECOMMIT).unwrap());
+        assert!(state.check_hrs(10, 1, STEP_PREVOTE).unwrap());
+        for (height, round, step) in
+            [(9, 5, STEP_PRECOMMIT), (10, 0, STEP_PRECOMMIT), (10, 1, 1)]
+        {
+            assert!(matches!(
+                state.check_hrs(height, round, step),
+                Err(Error::DoubleSign(_))
+            ));
+        }
+        // Nothing has been signed yet
+        assert!(!SignState::default().check_hrs(0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_persisted_sign_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sign_state.json");
+        let mut persisted = PersistedSignState::load_or_new(&path).unwrap();
+        assert_eq!(persisted.get(), &SignState::default());
+
+        let state = SignState {
+            height: 3,
+            round: 0,
+            step: STEP_PRECOMMIT,
+            sign_bytes: vec![1, 2, 3],
+            signature: vec![4, 5, 6],
+        };
+        persisted.update(state.clone()).unwrap();
+        let reloaded = PersistedSignState::load_or_new(&path).unwrap();
+        assert_eq!(reloaded.get(), &state);
+    }
+}
@@ -0,0 +1,311 @@
+//! The address book of the wallet keeps contact information about the aliases
+//! of transparent addresses and payment addresses: a label, free-form notes
+//! and tags. The address book can be exported to and imported from CSV or
+//! JSON files.
+
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use namada::types::address::Address;
+use namada::types::masp::PaymentAddress;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+/// The separator of the tags of an entry in a CSV file
+const CSV_TAGS_SEPARATOR: char = ';';
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum AddressBookError {
+    #[error("Failed to read or write the address book: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid address book CSV: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Invalid address book JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("The label \"{0}\" is already used by the alias \"{1}\"")]
+    DuplicateLabel(String, String),
+    #[error("The address with alias \"{0}\" was not added")]
+    NotAdded(String),
+}
+
+/// An address of the address book, either transparent or shielded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BookAddress {
+    /// A transparent address
+    Transparent(Address),
+    /// A shielded payment address
+    Shielded(PaymentAddress),
+}
+
+impl Display for BookAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transparent(address) => write!(f, "{}", address.encode()),
+            Self::Shielded(payment_addr) => write!(f, "{}", payment_addr),
+        }
+    }
+}
+
+impl FromStr for BookAddress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Address::decode(s)
+            .map(Self::Transparent)
+            .or_else(|_| PaymentAddress::from_str(s).map(Self::Shielded))
+            .map_err(|_| format!("Invalid address {}", s))
+    }
+}
+
+impl Serialize for BookAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for BookAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let address = String::deserialize(deserializer)?;
+        Self::from_str(&address).map_err(D::Error::custom)
+    }
+}
+
+/// Contact information about an alias of the wallet
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactInfo {
+    /// A human readable name, unique in the wallet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Free-form notes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Tags, e.g. "exchange", "validator" or "team"
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+}
+
+impl ContactInfo {
+    /// Check if the contact has the given tag, ignoring the case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Check if there's no contact information
+    pub fn is_empty(&self) -> bool {
+        self.label.is_none() && self.notes.is_none() && self.tags.is_empty()
+    }
+}
+
+/// An entry of the address book
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressBookEntry {
+    /// The alias of the address in the wallet
+    pub alias: String,
+    /// The address
+    pub address: BookAddress,
+    /// The contact information about the alias
+    #[serde(flatten)]
+    pub info: ContactInfo,
+}
+
+/// A format of address book files
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressBookFormat {
+    /// A CSV file with a header, which has a column for each field of the
+    /// entries and the tags separated by a semicolon
+    Csv,
+    /// A JSON array of the entries
+    Json,
+}
+
+impl AddressBookFormat {
+    /// Get the format of a file from its extension, if known
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        path.as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.parse().ok())
+    }
+}
+
+impl FromStr for AddressBookFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "Unknown address book format {}, expected csv or json",
+                s
+            )),
+        }
+    }
+}
+
+/// An entry of the address book in a CSV file
+#[derive(Serialize, Deserialize)]
+struct CsvRecord {
+    alias: String,
+    address: BookAddress,
+    label: Option<String>,
+    notes: Option<String>,
+    tags: Option<String>,
+}
+
+impl From<&AddressBookEntry> for CsvRecord {
+    fn from(entry: &AddressBookEntry) -> Self {
+        let tags =
+            itertools::join(&entry.info.tags, &CSV_TAGS_SEPARATOR.to_string());
+        Self {
+            alias: entry.alias.clone(),
+            address: entry.address.clone(),
+            label: entry.info.label.clone(),
+            notes: entry.info.notes.clone(),
+            tags: Some(tags).filter(|tags| !tags.is_empty()),
+        }
+    }
+}
+
+impl From<CsvRecord> for AddressBookEntry {
+    fn from(record: CsvRecord) -> Self {
+        let tags = record
+            .tags
+            .iter()
+            .flat_map(|tags| tags.split(CSV_TAGS_SEPARATOR))
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(ToOwned::to_owned)
+            .collect();
+        Self {
+            alias: record.alias,
+            address: record.address,
+            info: ContactInfo {
+                label: record.label,
+                notes: record.notes,
+                tags,
+            },
+        }
+    }
+}
+
+/// Write the entries of an address book in the given format
+pub fn write_entries(
+    entries: &[AddressBookEntry],
+    format: AddressBookFormat,
+    mut writer: impl Write,
+) -> Result<(), AddressBookError> {
+    match format {
+        AddressBookFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(writer);
+            for entry in entries {
+                writer.serialize(CsvRecord::from(entry))?;
+            }
+            writer.flush()?;
+        }
+        AddressBookFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, entries)?;
+            writeln!(writer)?;
+        }
+    }
+    Ok(())
+}
+
+/// Read the entries of an address book in the given format
+pub fn read_entries(
+    format: AddressBookFormat,
+    reader: impl Read,
+) -> Result<Vec<AddressBookEntry>, AddressBookError> {
+    match format {
+        AddressBookFormat::Csv => csv::Reader::from_reader(reader)
+            .deserialize()
+            .map(|record: Result<CsvRecord, _>| Ok(record?.into()))
+            .collect(),
+        AddressBookFormat::Json => Ok(serde_json::from_reader(reader)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+
+    use super::*;
+
+    fn entries() -> Vec<AddressBookEntry> {
+        vec![
+            AddressBookEntry {
+                alias: "exchange".to_owned(),
+                address: BookAddress::Transparent(established_address_1()),
+                info: ContactInfo {
+                    label: Some("Exchange, hot wallet".to_owned()),
+                    notes: Some("Deposits only\nNo withdrawals".to_owned()),
+                    tags: ["exchange".to_owned(), "team".to_owned()].into(),
+                },
+            },
+            AddressBookEntry {
+                alias: "validator".to_owned(),
+                address: BookAddress::Transparent(established_address_2()),
+                info: ContactInfo::default(),
+            },
+        ]
+    }
+
+    /// Test that the entries of an address book are the same after an
+    /// export and an import in both formats
+    #[test]
+    fn test_address_book_roundtrip() {
+        for format in [AddressBookFormat::Csv, AddressBookFormat::Json] {
+            let mut bytes = vec![];
+            write_entries(&entries(), format, &mut bytes).unwrap();
+            let read = read_entries(format, bytes.as_slice()).unwrap();
+            assert_eq!(read, entries(), "Format {:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_read_csv() {
+        let csv = format!(
+            "alias,address,label,notes,tags\nexchange,{},Exchange,,exchange; \
+             team ;\n",
+            established_address_1()
+        );
+        let read =
+            read_entries(AddressBookFormat::Csv, csv.as_bytes()).unwrap();
+        assert_eq!(read.len(), 1);
+        assert_eq!(read[0].info.label.as_deref(), Some("Exchange"));
+        assert_eq!(read[0].info.notes, None);
+        assert!(read[0].info.has_tag("Team"));
+        assert_eq!(read[0].info.tags.len(), 2);
+
+        let invalid = "alias,address,label,notes,tags\nexchange,nope,,,\n";
+        assert!(
+            read_entries(AddressBookFormat::Csv, invalid.as_bytes()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            AddressBookFormat::from_path("book.CSV"),
+            Some(AddressBookFormat::Csv)
+        );
+        assert_eq!(
+            AddressBookFormat::from_path("book.json"),
+            Some(AddressBookFormat::Json)
+        );
+        assert_eq!(AddressBookFormat::from_path("book.txt"), None);
+    }
+}
@@ -0,0 +1,341 @@
+//! Hierarchical deterministic (HD) derivation of keys from the seed of a BIP39
+//! mnemonic code. Transparent keys are derived with SLIP-10 for ed25519 and
+//! with BIP32 for secp256k1 at BIP44 paths and shielded spending keys are
+//! derived with ZIP-32.
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use hmac::{Hmac, Mac, NewMac};
+use masp_primitives::zip32::ChildIndex;
+use namada::types::key::*;
+use namada::types::masp::ExtendedSpendingKey;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::Sha512;
+use thiserror::Error;
+
+/// The SLIP-44 coin type of Namada
+const NAMADA_COIN_TYPE: u32 = 877;
+/// The purpose of BIP44 derivation paths
+const BIP44_PURPOSE: u32 = 44;
+/// The purpose of ZIP-32 derivation paths
+const ZIP32_PURPOSE: u32 = 32;
+/// The first index of hardened children
+const HARDENED_OFFSET: u32 = 1 << 31;
+/// The HMAC key of the SLIP-10 master ed25519 key
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+/// The HMAC key of the BIP32 master secp256k1 key
+const SECP256K1_SEED_KEY: &[u8] = b"Bitcoin seed";
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum DerivationPathError {
+    #[error("A derivation path must start with \"m\", got \"{0}\"")]
+    InvalidRoot(String),
+    #[error("Invalid derivation path index \"{0}\"")]
+    InvalidIndex(String),
+    #[error(
+        "The derivation path {0} contains a non-hardened index, which is not \
+         supported by ed25519 keys"
+    )]
+    NonHardenedEd25519(DerivationPath),
+    #[error(
+        "The derived key at the derivation path {0} is invalid, please use \
+         another index"
+    )]
+    InvalidDerivedKey(DerivationPath),
+}
+
+/// A BIP32 derivation path, e.g. `m/44'/877'/0'/0'/0'`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// The default BIP44 path of a transparent key of the given scheme. All
+    /// the indices are hardened for ed25519, whose SLIP-10 derivation doesn't
+    /// support non-hardened children.
+    pub fn default_for_scheme(scheme: SchemeType) -> Self {
+        let account = [BIP44_PURPOSE, NAMADA_COIN_TYPE, 0]
+            .into_iter()
+            .map(hardened);
+        match scheme {
+            SchemeType::Secp256k1 => Self(account.chain([0, 0]).collect()),
+            SchemeType::Ed25519 | SchemeType::Common => {
+                Self(account.chain([hardened(0), hardened(0)]).collect())
+            }
+        }
+    }
+
+    /// The default ZIP-32 path of a shielded spending key
+    pub fn default_for_shielded() -> Self {
+        Self(
+            [ZIP32_PURPOSE, NAMADA_COIN_TYPE, 0]
+                .into_iter()
+                .map(hardened)
+                .collect(),
+        )
+    }
+
+    /// The child indices of the path, where hardened indices are offset by
+    /// 2^31 as in their BIP32 serialization
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+
+    /// Derive the secret key of the given scheme at this path from the seed.
+    /// The `Common` scheme derives an ed25519 key.
+    pub fn derive_sk(
+        &self,
+        scheme: SchemeType,
+        seed: &[u8],
+    ) -> Result<common::SecretKey, DerivationPathError> {
+        match scheme {
+            SchemeType::Secp256k1 => self
+                .derive_secp256k1(seed)
+                .map(common::SecretKey::Secp256k1),
+            SchemeType::Ed25519 | SchemeType::Common => {
+                self.derive_ed25519(seed).map(common::SecretKey::Ed25519)
+            }
+        }
+    }
+
+    /// Derive the shielded spending key at this path from the seed
+    pub fn derive_spending_key(&self, seed: &[u8]) -> ExtendedSpendingKey {
+        use masp_primitives::zip32::ExtendedSpendingKey as MaspSpendingKey;
+        let master = MaspSpendingKey::master(seed);
+        let path: Vec<ChildIndex> = self
+            .0
+            .iter()
+            .map(|&index| {
+                if is_hardened(index) {
+                    ChildIndex::Hardened(index - HARDENED_OFFSET)
+                } else {
+                    ChildIndex::NonHardened(index)
+                }
+            })
+            .collect();
+        MaspSpendingKey::from_path(&master, &path).into()
+    }
+
+    /// SLIP-10 derivation of an ed25519 key
+    fn derive_ed25519(
+        &self,
+        seed: &[u8],
+    ) -> Result<ed25519::SecretKey, DerivationPathError> {
+        if !self.0.iter().copied().all(is_hardened) {
+            return Err(DerivationPathError::NonHardenedEd25519(self.clone()));
+        }
+        let (mut key, mut chain_code) = hmac_sha512(ED25519_SEED_KEY, seed);
+        for index in &self.0 {
+            let mut data = vec![0];
+            data.extend_from_slice(&key);
+            data.extend_from_slice(&index.to_be_bytes());
+            (key, chain_code) = hmac_sha512(&chain_code, &data);
+        }
+        Ok(ed25519::SecretKey::try_from_slice(&key)
+            .expect("Any 32 bytes are a valid ed25519 secret key"))
+    }
+
+    /// BIP32 derivation of a secp256k1 key
+    fn derive_secp256k1(
+        &self,
+        seed: &[u8],
+    ) -> Result<secp256k1::SecretKey, DerivationPathError> {
+        let invalid_key =
+            || DerivationPathError::InvalidDerivedKey(self.clone());
+        let (key, mut chain_code) = hmac_sha512(SECP256K1_SEED_KEY, seed);
+        let mut sk = secp256k1::SecretKey::try_from_slice(&key)
+            .map_err(|_| invalid_key())?;
+        for index in &self.0 {
+            let mut data = if is_hardened(*index) {
+                let mut data = vec![0];
+                data.extend(sk.try_to_vec().unwrap());
+                data
+            } else {
+                // The compressed public key of the parent
+                sk.ref_to().try_to_vec().unwrap()
+            };
+            data.extend_from_slice(&index.to_be_bytes());
+            let (tweak, child_chain_code) = hmac_sha512(&chain_code, &data);
+            // The child key is the sum of the tweak and the parent key
+            let mut child = secp256k1::SecretKey::try_from_slice(&tweak)
+                .map_err(|_| invalid_key())?;
+            child.0.tweak_add_assign(&sk.0).map_err(|_| invalid_key())?;
+            sk = child;
+            chain_code = child_chain_code;
+        }
+        Ok(sk)
+    }
+}
+
+impl Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            if is_hardened(*index) {
+                write!(f, "/{}'", index - HARDENED_OFFSET)?;
+            } else {
+                write!(f, "/{}", index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<u32>> for DerivationPath {
+    fn from(indices: Vec<u32>) -> Self {
+        Self(indices)
+    }
+}
+
+impl Serialize for DerivationPath {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for DerivationPath {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let path = String::deserialize(deserializer)?;
+        Self::from_str(&path).map_err(D::Error::custom)
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = DerivationPathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = s.trim().split('/');
+        match segments.next() {
+            Some("m") => {}
+            _ => return Err(DerivationPathError::InvalidRoot(s.to_owned())),
+        }
+        segments
+            .map(|segment| {
+                let invalid =
+                    || DerivationPathError::InvalidIndex(segment.to_owned());
+                let (index, is_hardened_index) = match segment
+                    .strip_suffix('\'')
+                    .or_else(|| segment.strip_suffix('h'))
+                {
+                    Some(index) => (index, true),
+                    None => (segment, false),
+                };
+                let index: u32 = index.parse().map_err(|_| invalid())?;
+                if index >= HARDENED_OFFSET {
+                    Err(invalid())
+                } else if is_hardened_index {
+                    Ok(hardened(index))
+                } else {
+                    Ok(index)
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// The hardened child index of the given index
+fn hardened(index: u32) -> u32 {
+    index + HARDENED_OFFSET
+}
+
+/// Check if the child index is hardened
+fn is_hardened(index: u32) -> bool {
+    index >= HARDENED_OFFSET
+}
+
+/// Split the HMAC-SHA512 of the data into its left and right halves
+fn hmac_sha512(key: &[u8], data: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key)
+        .expect("HMAC can take a key of any size");
+    mac.update(data);
+    let bytes = mac.finalize().into_bytes();
+    let mut left = [0; 32];
+    let mut right = [0; 32];
+    left.copy_from_slice(&bytes[..32]);
+    right.copy_from_slice(&bytes[32..]);
+    (left, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use data_encoding::HEXLOWER;
+
+    use super::*;
+
+    /// The seed of the test vectors 1 of BIP32 and SLIP-10
+    const TEST_SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+    fn derive_hex(path: &str, scheme: SchemeType) -> String {
+        let seed = HEXLOWER.decode(TEST_SEED.as_bytes()).unwrap();
+        let sk = DerivationPath::from_str(path)
+            .unwrap()
+            .derive_sk(scheme, &seed)
+            .unwrap();
+        match sk {
+            common::SecretKey::Ed25519(sk) => sk.to_string(),
+            common::SecretKey::Secp256k1(sk) => sk.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_slip10_ed25519_vector() {
+        assert_eq!(
+            derive_hex("m", SchemeType::Ed25519),
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+        );
+        assert_eq!(
+            derive_hex("m/0'/1'/2'/2'/1000000000'", SchemeType::Ed25519),
+            "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793"
+        );
+    }
+
+    #[test]
+    fn test_bip32_secp256k1_vector() {
+        assert_eq!(
+            derive_hex("m", SchemeType::Secp256k1),
+            "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"
+        );
+        assert_eq!(
+            derive_hex("m/0'/1/2'/2/1000000000", SchemeType::Secp256k1),
+            "471b76e389e528d6de6d816857e012c5455051cad6660850e58372a6c3e6e7c8"
+        );
+    }
+
+    #[test]
+    fn test_derivation_path_parsing() {
+        for scheme in [SchemeType::Ed25519, SchemeType::Secp256k1] {
+            let path = DerivationPath::default_for_scheme(scheme);
+            assert_eq!(path, path.to_string().parse().unwrap());
+        }
+        assert_eq!(
+            DerivationPath::default_for_scheme(SchemeType::Ed25519).to_string(),
+            "m/44'/877'/0'/0'/0'"
+        );
+        assert_eq!(
+            DerivationPath::default_for_shielded().to_string(),
+            "m/32'/877'/0'"
+        );
+        assert!(DerivationPath::from_str("44'/877'").is_err());
+        assert!(DerivationPath::from_str("m/2147483648").is_err());
+        assert!(DerivationPath::from_str("m/a'").is_err());
+
+        // Ed25519 only supports hardened indices
+        let seed = [0; 64];
+        let path = DerivationPath::from_str("m/44'/877'/0'/0/0").unwrap();
+        assert!(matches!(
+            path.derive_sk(SchemeType::Ed25519, &seed),
+            Err(DerivationPathError::NonHardenedEd25519(_))
+        ));
+        assert!(path.derive_sk(SchemeType::Secp256k1, &seed).is_ok());
+    }
+}
@@ -1,5 +1,6 @@
 //! Cryptographic keys for digital signatures support for the wallet.
 
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::marker::PhantomData;
 use std::str::FromStr;
@@ -12,7 +13,7 @@ use thiserror::Error;
 
 use super::read_password;
 
-const ENCRYPTED_KEY_PREFIX: &str = "encrypted:";
+pub(super) const ENCRYPTED_KEY_PREFIX: &str = "encrypted:";
 const UNENCRYPTED_KEY_PREFIX: &str = "unencrypted:";
 
 /// A keypair stored in a wallet
@@ -110,24 +111,169 @@ pub enum DeserializeStoredKeypairError {
     MissingPrefix,
 }
 
-/// An encrypted keypair stored in a wallet
+/// An encrypted keypair stored in a wallet, together with the key derivation
+/// function deriving its encryption key from a password
 #[derive(Debug)]
-pub struct EncryptedKeypair<T: BorshSerialize + BorshDeserialize>(
-    Vec<u8>,
-    PhantomData<T>,
-);
+pub struct EncryptedKeypair<T: BorshSerialize + BorshDeserialize> {
+    kdf: Kdf,
+    data: Vec<u8>,
+    phantom: PhantomData<T>,
+}
 
 impl<T: BorshSerialize + BorshDeserialize> Display for EncryptedKeypair<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", HEXLOWER.encode(self.0.as_ref()))
+        write!(f, "{}${}", self.kdf, HEXLOWER.encode(self.data.as_ref()))
     }
 }
 
 impl<T: BorshSerialize + BorshDeserialize> FromStr for EncryptedKeypair<T> {
-    type Err = data_encoding::DecodeError;
+    type Err = DecodeEncryptedKeypairError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        HEXLOWER.decode(s.as_ref()).map(|x| Self(x, PhantomData))
+        // The keys encrypted before the KDF was recorded with them use the
+        // legacy KDF
+        let (kdf, data) = match s.rsplit_once('$') {
+            Some((kdf, data)) => (Kdf::from_str(kdf)?, data),
+            None => (Kdf::LEGACY, s),
+        };
+        let data = HEXLOWER
+            .decode(data.as_ref())
+            .map_err(DecodeEncryptedKeypairError::Hex)?;
+        Ok(Self {
+            kdf,
+            data,
+            phantom: PhantomData,
+        })
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum DecodeEncryptedKeypairError {
+    #[error("Invalid key derivation function: {0}")]
+    Kdf(String),
+    #[error("Invalid encrypted keypair: {0}")]
+    Hex(data_encoding::DecodeError),
+}
+
+/// A key derivation function with its parameters, which derives the
+/// encryption key of a keypair from a password
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    /// Argon2i, as implemented by `orion`, with the number of iterations and
+    /// the memory in KiB
+    Argon2i { iterations: u32, memory: u32 },
+    /// Argon2id with the memory cost in KiB, the number of iterations and the
+    /// degree of parallelism
+    Argon2id {
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+    },
+}
+
+impl Kdf {
+    /// The KDF of the keys encrypted before the KDF was recorded with them
+    pub const LEGACY: Self = Self::Argon2i {
+        iterations: 3,
+        memory: 1 << 17,
+    };
+
+    /// Derive an encryption key from a password
+    fn derive_key(
+        &self,
+        salt: &kdf::Salt,
+        password: String,
+    ) -> Result<aead::SecretKey, DecryptionError> {
+        match *self {
+            Kdf::Argon2i { iterations, memory } => {
+                kdf::Password::from_slice(password.as_bytes())
+                    .and_then(|password| {
+                        kdf::derive_key(&password, salt, iterations, memory, 32)
+                    })
+                    .map_err(|_| DecryptionError::BadKdf)
+            }
+            Kdf::Argon2id {
+                m_cost,
+                t_cost,
+                p_cost,
+            } => {
+                let params =
+                    argon2::Params::new(m_cost, t_cost, p_cost, Some(32))
+                        .map_err(|_| DecryptionError::BadKdf)?;
+                let mut key = [0; 32];
+                argon2::Argon2::new(
+                    argon2::Algorithm::Argon2id,
+                    argon2::Version::V0x13,
+                    params,
+                )
+                .hash_password_into(
+                    password.as_bytes(),
+                    salt.as_ref(),
+                    &mut key,
+                )
+                .map_err(|_| DecryptionError::BadKdf)?;
+                aead::SecretKey::from_slice(&key)
+                    .map_err(|_| DecryptionError::BadKdf)
+            }
+        }
+    }
+}
+
+/// The KDF of newly encrypted keys
+impl Default for Kdf {
+    fn default() -> Self {
+        Self::Argon2id {
+            m_cost: 1 << 17,
+            t_cost: 4,
+            p_cost: 1,
+        }
+    }
+}
+
+/// Encoded like the PHC string format, e.g. `argon2id$m=131072,t=4,p=1`
+impl Display for Kdf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Kdf::Argon2i { iterations, memory } => {
+                write!(f, "argon2i$t={},m={}", iterations, memory)
+            }
+            Kdf::Argon2id {
+                m_cost,
+                t_cost,
+                p_cost,
+            } => write!(f, "argon2id$m={},t={},p={}", m_cost, t_cost, p_cost),
+        }
+    }
+}
+
+impl FromStr for Kdf {
+    type Err = DecodeEncryptedKeypairError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DecodeEncryptedKeypairError::Kdf(s.to_owned());
+        let (algorithm, params) = s.split_once('$').ok_or_else(invalid)?;
+        let params: HashMap<&str, u32> = params
+            .split(',')
+            .map(|param| {
+                let (name, value) = param.split_once('=')?;
+                Some((name, value.parse().ok()?))
+            })
+            .collect::<Option<_>>()
+            .ok_or_else(invalid)?;
+        let param = |name| params.get(name).copied().ok_or_else(invalid);
+        match algorithm {
+            "argon2i" if params.len() == 2 => Ok(Kdf::Argon2i {
+                iterations: param("t")?,
+                memory: param("m")?,
+            }),
+            "argon2id" if params.len() == 3 => Ok(Kdf::Argon2id {
+                m_cost: param("m")?,
+                t_cost: param("t")?,
+                p_cost: param("p")?,
+            }),
+            _ => Err(invalid()),
+        }
     }
 }
 
@@ -136,6 +282,8 @@ impl<T: BorshSerialize + BorshDeserialize> FromStr for EncryptedKeypair<T> {
 pub enum DecryptionError {
     #[error("Unexpected encryption salt")]
     BadSalt,
+    #[error("Invalid key derivation parameters")]
+    BadKdf,
     #[error("Unable to decrypt the keypair. Is the password correct?")]
     DecryptionError,
     #[error("Unable to deserialize the keypair")]
@@ -192,13 +340,39 @@ where
             StoredKeypair::Raw(_) => false,
         }
     }
+
+    /// Re-encrypt an encrypted keypair with a new password and the default
+    /// KDF. A raw keypair is kept raw.
+    pub fn change_password(
+        &self,
+        password: String,
+        new_password: String,
+    ) -> Result<Self, DecryptionError> {
+        match self {
+            StoredKeypair::Encrypted(encrypted_keypair) => {
+                let keypair = encrypted_keypair.decrypt(password)?;
+                Ok(Self::Encrypted(EncryptedKeypair::new(
+                    &keypair,
+                    new_password,
+                )))
+            }
+            StoredKeypair::Raw(keypair) => Ok(Self::Raw(keypair.clone())),
+        }
+    }
 }
 
 impl<T: BorshSerialize + BorshDeserialize> EncryptedKeypair<T> {
-    /// Encrypt a keypair and store it with its salt.
+    /// Encrypt a keypair with the default KDF and store it with its salt.
     pub fn new(keypair: &T, password: String) -> Self {
+        Self::new_with_kdf(keypair, password, Kdf::default())
+    }
+
+    /// Encrypt a keypair with the given KDF and store it with its salt.
+    pub fn new_with_kdf(keypair: &T, password: String, kdf: Kdf) -> Self {
         let salt = encryption_salt();
-        let encryption_key = encryption_key(&salt, password);
+        let encryption_key = kdf
+            .derive_key(&salt, password)
+            .expect("Generation of encryption secret key shouldn't fail");
 
         let data = keypair
             .try_to_vec()
@@ -209,18 +383,22 @@ impl<T: BorshSerialize + BorshDeserialize> EncryptedKeypair<T> {
 
         let encrypted_data = [salt.as_ref(), &encrypted_keypair].concat();
 
-        Self(encrypted_data, PhantomData)
+        Self {
+            kdf,
+            data: encrypted_data,
+            phantom: PhantomData,
+        }
     }
 
     /// Decrypt an encrypted keypair
     pub fn decrypt(&self, password: String) -> Result<T, DecryptionError> {
         let salt_len = encryption_salt().len();
-        let (raw_salt, cipher) = self.0.split_at(salt_len);
+        let (raw_salt, cipher) = self.data.split_at(salt_len);
 
         let salt = kdf::Salt::from_slice(raw_salt)
             .map_err(|_| DecryptionError::BadSalt)?;
 
-        let encryption_key = encryption_key(&salt, password);
+        let encryption_key = self.kdf.derive_key(&salt, password)?;
 
         let decrypted_data = aead::open(&encryption_key, cipher)
             .map_err(|_| DecryptionError::DecryptionError)?;
@@ -228,6 +406,11 @@ impl<T: BorshSerialize + BorshDeserialize> EncryptedKeypair<T> {
         T::try_from_slice(&decrypted_data)
             .map_err(|_| DecryptionError::DeserializingError)
     }
+
+    /// The KDF deriving the encryption key of the keypair
+    pub fn kdf(&self) -> Kdf {
+        self.kdf
+    }
 }
 
 /// Keypair encryption salt
@@ -235,9 +418,72 @@ fn encryption_salt() -> kdf::Salt {
     kdf::Salt::default()
 }
 
-/// Make encryption secret key from a password.
-fn encryption_key(salt: &kdf::Salt, password: String) -> kdf::SecretKey {
-    kdf::Password::from_slice(password.as_bytes())
-        .and_then(|password| kdf::derive_key(&password, salt, 3, 1 << 17, 32))
-        .expect("Generation of encryption secret key shouldn't fail")
+#[cfg(test)]
+mod tests {
+    use namada::types::key::{common, RefTo};
+
+    use super::*;
+
+    /// Cheap KDFs for the tests
+    const TEST_KDFS: [Kdf; 2] = [
+        Kdf::Argon2i {
+            iterations: 1,
+            memory: 8,
+        },
+        Kdf::Argon2id {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+        },
+    ];
+
+    #[test]
+    fn test_kdf_encoding() {
+        for kdf in TEST_KDFS.into_iter().chain([Kdf::LEGACY, Kdf::default()]) {
+            assert_eq!(Kdf::from_str(&kdf.to_string()).unwrap(), kdf);
+        }
+        for invalid in [
+            "argon2i",
+            "argon2i$t=3",
+            "argon2i$t=3,m=x",
+            "argon2id$m=1,t=1,p=1,x=1",
+            "scrypt$n=1",
+        ] {
+            assert!(Kdf::from_str(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn test_encrypted_keypair_kdf() {
+        let keypair = namada::types::key::testing::keypair_1();
+        for kdf in TEST_KDFS {
+            let encrypted = EncryptedKeypair::<common::SecretKey>::new_with_kdf(
+                &keypair,
+                "password".to_owned(),
+                kdf,
+            );
+            let decoded = EncryptedKeypair::<common::SecretKey>::from_str(
+                &encrypted.to_string(),
+            )
+            .unwrap();
+            assert_eq!(decoded.kdf(), kdf);
+            assert_eq!(
+                decoded.decrypt("password".to_owned()).unwrap().ref_to(),
+                keypair.ref_to()
+            );
+            assert!(matches!(
+                decoded.decrypt("wrong".to_owned()),
+                Err(DecryptionError::DecryptionError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_legacy_encrypted_keypair() {
+        let encrypted = EncryptedKeypair::<common::SecretKey>::from_str(
+            "00112233445566778899aabbccddeeff",
+        )
+        .unwrap();
+        assert_eq!(encrypted.kdf(), Kdf::LEGACY);
+    }
 }
@@ -0,0 +1,161 @@
+//! Versioning of the wallet file format. A wallet file records the version of
+//! its format and the wallets of an older version are migrated to the current
+//! one when they're loaded, by transforming their TOML value. The wallets
+//! without a version are of version 0.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::keys::{Kdf, ENCRYPTED_KEY_PREFIX};
+
+/// The current version of the wallet file format
+pub const CURRENT_VERSION: u32 = 2;
+
+/// The field of the wallet file recording its version
+const VERSION_FIELD: &str = "version";
+
+/// The version of a wallet file format, which is the current one for a new
+/// wallet
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WalletVersion(pub u32);
+
+impl Default for WalletVersion {
+    fn default() -> Self {
+        Self(CURRENT_VERSION)
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error(
+        "The wallet version {0} is newer than the supported version {}, \
+         please upgrade Namada",
+        CURRENT_VERSION
+    )]
+    UnsupportedVersion(u32),
+    #[error("Invalid wallet: {0}")]
+    InvalidWallet(String),
+}
+
+/// Migrate a wallet to the current version. Returns the version of the
+/// wallet before the migration, if it was migrated.
+pub fn migrate(
+    wallet: &mut toml::Value,
+) -> Result<Option<u32>, MigrationError> {
+    let wallet = wallet.as_table_mut().ok_or_else(|| {
+        MigrationError::InvalidWallet("Expected a table".to_owned())
+    })?;
+    let version = match wallet.get(VERSION_FIELD) {
+        None => 0,
+        Some(version) => version
+            .as_integer()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| {
+                MigrationError::InvalidWallet(format!(
+                    "Invalid version {}",
+                    version
+                ))
+            })?,
+    };
+    if version > CURRENT_VERSION {
+        return Err(MigrationError::UnsupportedVersion(version));
+    }
+    if version == CURRENT_VERSION {
+        return Ok(None);
+    }
+    // The migrations to the next version, from each version
+    let migrations: [fn(&mut toml::value::Table); CURRENT_VERSION as usize] =
+        [migrate_v0_to_v1, migrate_v1_to_v2];
+    for migration in &migrations[version as usize..] {
+        migration(wallet);
+    }
+    wallet.insert(
+        VERSION_FIELD.to_owned(),
+        toml::Value::Integer(CURRENT_VERSION.into()),
+    );
+    Ok(Some(version))
+}
+
+/// Record the legacy KDF with the encrypted keys, which had a fixed KDF in
+/// version 0
+fn migrate_v0_to_v1(wallet: &mut toml::value::Table) {
+    for keys in ["keys", "spend_keys"] {
+        let keys =
+            match wallet.get_mut(keys).and_then(|keys| keys.as_table_mut()) {
+                Some(keys) => keys,
+                None => continue,
+            };
+        for key in keys.values_mut() {
+            let encrypted = match key
+                .as_str()
+                .and_then(|key| key.strip_prefix(ENCRYPTED_KEY_PREFIX))
+            {
+                Some(encrypted) if !encrypted.contains('$') => encrypted,
+                _ => continue,
+            };
+            *key = toml::Value::String(format!(
+                "{}{}${}",
+                ENCRYPTED_KEY_PREFIX,
+                Kdf::LEGACY,
+                encrypted
+            ));
+        }
+    }
+}
+
+/// Nothing to migrate, the address book is empty in version 1. The version
+/// prevents older versions of Namada from dropping the address book when they
+/// save the wallet.
+fn migrate_v1_to_v2(_wallet: &mut toml::value::Table) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_v0_to_v1() {
+        let mut wallet: toml::Value = toml::from_str(
+            r#"
+[keys]
+raw = "unencrypted:00aabb"
+encrypted = "encrypted:00ccdd"
+
+[spend_keys]
+encrypted = "encrypted:00eeff"
+"#,
+        )
+        .unwrap();
+        assert_eq!(migrate(&mut wallet).unwrap(), Some(0));
+        assert_eq!(
+            wallet[VERSION_FIELD].as_integer(),
+            Some(CURRENT_VERSION.into())
+        );
+        assert_eq!(wallet["keys"]["raw"].as_str(), Some("unencrypted:00aabb"));
+        assert_eq!(
+            wallet["keys"]["encrypted"].as_str(),
+            Some("encrypted:argon2i$t=3,m=131072$00ccdd")
+        );
+        assert_eq!(
+            wallet["spend_keys"]["encrypted"].as_str(),
+            Some("encrypted:argon2i$t=3,m=131072$00eeff")
+        );
+
+        // A wallet of the current version is not migrated again
+        let migrated = wallet.clone();
+        assert_eq!(migrate(&mut wallet).unwrap(), None);
+        assert_eq!(wallet, migrated);
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let mut wallet: toml::Value =
+            toml::from_str(&format!("version = {}", CURRENT_VERSION + 1))
+                .unwrap();
+        assert!(matches!(
+            migrate(&mut wallet),
+            Err(MigrationError::UnsupportedVersion(_))
+        ));
+    }
+}
@@ -1,6 +1,9 @@
+pub mod address_book;
 mod alias;
 pub mod defaults;
+mod derivation_path;
 mod keys;
+mod migration;
 pub mod pre_genesis;
 mod store;
 
@@ -10,6 +13,7 @@ use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::{env, fs};
 
+use bip39::{Language, Mnemonic, MnemonicType, Seed};
 use borsh::{BorshDeserialize, BorshSerialize};
 use masp_primitives::zip32::ExtendedFullViewingKey;
 use namada::types::address::Address;
@@ -20,10 +24,12 @@ use namada::types::masp::{
 pub use store::wallet_file;
 use thiserror::Error;
 
+use self::address_book::{AddressBookEntry, AddressBookError, BookAddress};
 use self::alias::Alias;
+pub use self::derivation_path::{DerivationPath, DerivationPathError};
 pub use self::keys::{DecryptionError, StoredKeypair};
 use self::store::Store;
-pub use self::store::{ValidatorData, ValidatorKeys};
+pub use self::store::{HardwareKey, ValidatorData, ValidatorKeys};
 use crate::cli;
 use crate::config::genesis::genesis_config::GenesisConfig;
 
@@ -102,6 +108,20 @@ impl Wallet {
         self.store.save(&self.store_dir)
     }
 
+    /// Re-encrypt all the encrypted keys and spending keys of the wallet with
+    /// a new password and the default KDF. If any of them cannot be decrypted
+    /// with the current password, nothing is changed and its alias is
+    /// returned with the error.
+    pub fn change_password(
+        &mut self,
+        password: &str,
+        new_password: &str,
+    ) -> Result<(), (String, DecryptionError)> {
+        self.store
+            .change_password(password, new_password)
+            .map_err(|(alias, err)| (alias.into(), err))
+    }
+
     /// Prompt for pssword and confirm it if parameter is false
     fn new_password_prompt(unsafe_dont_encrypt: bool) -> Option<String> {
         let password = if unsafe_dont_encrypt {
@@ -158,6 +178,72 @@ impl Wallet {
         (alias.into(), key)
     }
 
+    /// Generate a new BIP39 mnemonic code of 24 English words
+    pub fn gen_mnemonic_code() -> Mnemonic {
+        Mnemonic::new(MnemonicType::Words24, Language::English)
+    }
+
+    /// Derive a keypair at the given HD path from the seed of the mnemonic
+    /// code and the BIP39 passphrase and insert it into the store like
+    /// [`Wallet::gen_key`]. If no path is provided, the default BIP44 path of
+    /// the scheme is used.
+    pub fn derive_key_from_mnemonic_code(
+        &mut self,
+        scheme: SchemeType,
+        alias: Option<String>,
+        path: Option<DerivationPath>,
+        mnemonic: &Mnemonic,
+        bip39_passphrase: &str,
+        unsafe_dont_encrypt: bool,
+    ) -> (String, common::SecretKey) {
+        let path =
+            path.unwrap_or_else(|| DerivationPath::default_for_scheme(scheme));
+        let seed = Seed::new(mnemonic, bip39_passphrase);
+        let password = read_and_confirm_pwd(unsafe_dont_encrypt);
+        let (alias, key) = self
+            .store
+            .derive_key_from_seed(
+                scheme,
+                &path,
+                seed.as_bytes(),
+                alias,
+                password,
+            )
+            .unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                cli::safe_exit(1)
+            });
+        // Cache the newly added key
+        self.decrypted_key_cache.insert(alias.clone(), key.clone());
+        (alias.into(), key)
+    }
+
+    /// Derive a spending key at the given ZIP-32 path from the seed of the
+    /// mnemonic code and the BIP39 passphrase and insert it into the store
+    /// like [`Wallet::gen_spending_key`]. If no path is provided, the default
+    /// ZIP-32 path is used.
+    pub fn derive_spending_key_from_mnemonic_code(
+        &mut self,
+        alias: String,
+        path: Option<DerivationPath>,
+        mnemonic: &Mnemonic,
+        bip39_passphrase: &str,
+        unsafe_dont_encrypt: bool,
+    ) -> (String, ExtendedSpendingKey) {
+        let path = path.unwrap_or_else(DerivationPath::default_for_shielded);
+        let seed = Seed::new(mnemonic, bip39_passphrase);
+        let password = Self::new_password_prompt(unsafe_dont_encrypt);
+        let (alias, key) = self.store.derive_spending_key_from_seed(
+            &path,
+            seed.as_bytes(),
+            alias,
+            password,
+        );
+        // Cache the newly added key
+        self.decrypted_spendkey_cache.insert(alias.clone(), key);
+        (alias.into(), key)
+    }
+
     /// Generate keypair
     /// for signing protocol txs and for the DKG (which will also be stored)
     /// A protocol keypair may be optionally provided, indicating that
@@ -361,6 +447,53 @@ impl Wallet {
         }
     }
 
+    /// Find a hardware key by its alias
+    pub fn find_hardware_key(
+        &self,
+        alias: impl AsRef<str>,
+    ) -> Option<&HardwareKey> {
+        self.store.find_hardware_key(alias.as_ref().to_lowercase())
+    }
+
+    /// Find a hardware key by a public key hash
+    pub fn find_hardware_key_by_pkh(
+        &self,
+        pkh: &PublicKeyHash,
+    ) -> Option<&HardwareKey> {
+        self.store.find_hardware_key_by_pkh(pkh)
+    }
+
+    /// Find a hardware key by a public key
+    pub fn find_hardware_key_by_pk(
+        &self,
+        pk: &common::PublicKey,
+    ) -> Option<&HardwareKey> {
+        self.store
+            .find_hardware_key_by_pkh(&PublicKeyHash::from(pk))
+    }
+
+    /// Get all known hardware keys by their alias
+    pub fn get_hardware_keys(&self) -> HashMap<String, &HardwareKey> {
+        self.store
+            .get_hardware_keys()
+            .iter()
+            .map(|(alias, key)| (alias.clone().into(), key))
+            .collect()
+    }
+
+    /// Add a new hardware key and its implicit address with the given alias
+    /// to the wallet. Only the public key and the HD path of the key are
+    /// stored.
+    pub fn insert_hardware_key(
+        &mut self,
+        alias: String,
+        key: HardwareKey,
+    ) -> Option<String> {
+        self.store
+            .insert_hardware_key(alias.to_lowercase().into(), key)
+            .map(Into::into)
+    }
+
     /// Get all known keys by their alias, paired with PKH, if known.
     pub fn get_keys(
         &self,
@@ -385,6 +518,33 @@ impl Wallet {
         self.store.find_alias(address)
     }
 
+    /// Find the address or payment address with the given label in the
+    /// address book.
+    pub fn find_book_address_by_label(
+        &self,
+        label: impl AsRef<str>,
+    ) -> Option<BookAddress> {
+        self.store
+            .find_alias_by_label(label.as_ref())
+            .and_then(|alias| self.store.find_book_address(alias))
+    }
+
+    /// Get the entries of the address book, sorted by alias.
+    pub fn get_address_book(&self) -> Vec<AddressBookEntry> {
+        self.store.get_address_book()
+    }
+
+    /// Add an entry to the address book. If the alias is already used for
+    /// another address, will ask whether the existing alias should be
+    /// replaced, a different alias is desired, or the entry should be skipped.
+    /// Return the chosen alias.
+    pub fn add_address_book_entry(
+        &mut self,
+        entry: AddressBookEntry,
+    ) -> Result<String, AddressBookError> {
+        self.store.insert_contact(entry).map(Into::into)
+    }
+
     /// Get all known addresses by their alias, paired with PKH, if known.
     pub fn get_addresses(&self) -> HashMap<String, Address> {
         self.store
@@ -536,6 +696,61 @@ pub fn read_and_confirm_pwd(unsafe_dont_encrypt: bool) -> Option<String> {
     password
 }
 
+/// Read a new password for encryption from stdin with confirmation. Unlike
+/// [`read_password`], it's never read from the environment.
+pub fn read_new_password() -> String {
+    let password =
+        rpassword::read_password_from_tty(Some("Enter the new password: "))
+            .unwrap_or_default();
+    if password.is_empty() {
+        eprintln!("Password cannot be empty");
+        cli::safe_exit(1)
+    }
+    let to_confirm = rpassword::read_password_from_tty(Some(
+        "To confirm, please enter the same new password once more: ",
+    ))
+    .unwrap_or_default();
+    if to_confirm != password {
+        eprintln!("Your two inputs do not match!");
+        cli::safe_exit(1)
+    }
+    password
+}
+
+/// Read a BIP39 mnemonic code from stdin. Exits if it's invalid.
+pub fn read_mnemonic_code() -> Mnemonic {
+    let phrase =
+        rpassword::read_password_from_tty(Some("Input your mnemonic code: "))
+            .unwrap_or_default();
+    Mnemonic::from_phrase(phrase.trim(), Language::English).unwrap_or_else(
+        |err| {
+            eprintln!("Invalid mnemonic code: {}", err);
+            cli::safe_exit(1)
+        },
+    )
+}
+
+/// Read the optional BIP39 passphrase that extends a mnemonic code from stdin,
+/// if requested, with confirmation. Returns an empty passphrase otherwise.
+pub fn read_bip39_passphrase(prompt_passphrase: bool) -> String {
+    if !prompt_passphrase {
+        return String::new();
+    }
+    let passphrase = rpassword::read_password_from_tty(Some(
+        "Enter the BIP39 passphrase of the mnemonic code: ",
+    ))
+    .unwrap_or_default();
+    let to_confirm = rpassword::read_password_from_tty(Some(
+        "To confirm, please enter the same BIP39 passphrase once more: ",
+    ))
+    .unwrap_or_default();
+    if to_confirm != passphrase {
+        eprintln!("Your two inputs do not match!");
+        cli::safe_exit(1)
+    }
+    passphrase
+}
+
 /// Read the password for encryption/decryption from the file/env/stdin. Panics
 /// if all options are empty/invalid.
 pub fn read_password(prompt_msg: &str) -> String {
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::fs;
 use std::io::prelude::*;
 use std::io::{self, Write};
@@ -8,6 +9,7 @@ use std::str::FromStr;
 use ark_std::rand::prelude::*;
 use ark_std::rand::SeedableRng;
 use bimap::BiHashMap;
+use borsh::{BorshDeserialize, BorshSerialize};
 use file_lock::{FileLock, FileOptions};
 use masp_primitives::zip32::ExtendedFullViewingKey;
 use namada::types::address::{Address, ImplicitAddress};
@@ -17,11 +19,16 @@ use namada::types::masp::{
     ExtendedSpendingKey, ExtendedViewingKey, PaymentAddress,
 };
 use namada::types::transaction::EllipticCurve;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
+use super::address_book::{
+    AddressBookEntry, AddressBookError, BookAddress, ContactInfo,
+};
 use super::alias::{self, Alias};
-use super::keys::StoredKeypair;
+use super::derivation_path::{DerivationPath, DerivationPathError};
+use super::keys::{DecryptionError, StoredKeypair};
+use super::migration::{self, MigrationError, WalletVersion, CURRENT_VERSION};
 use super::pre_genesis;
 use crate::cli;
 use crate::config::genesis::genesis_config::GenesisConfig;
@@ -52,8 +59,45 @@ pub struct ValidatorData {
     pub keys: ValidatorKeys,
 }
 
+/// A key kept on a hardware wallet. Only its public key and its HD path on the
+/// device are stored in the wallet.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HardwareKey {
+    /// The public key
+    #[serde(
+        serialize_with = "serialize_pk",
+        deserialize_with = "deserialize_pk"
+    )]
+    pub public_key: common::PublicKey,
+    /// The HD path of the key on the device
+    pub derivation_path: DerivationPath,
+}
+
+fn serialize_pk<S>(
+    pk: &common::PublicKey,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&pk.to_string())
+}
+
+fn deserialize_pk<'de, D>(
+    deserializer: D,
+) -> Result<common::PublicKey, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+    let pk = String::deserialize(deserializer)?;
+    common::PublicKey::from_str(&pk).map_err(D::Error::custom)
+}
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Store {
+    /// The version of the wallet file format
+    version: WalletVersion,
     /// Known viewing keys
     view_keys: HashMap<Alias, ExtendedViewingKey>,
     /// Known spending keys
@@ -62,8 +106,15 @@ pub struct Store {
     payment_addrs: HashMap<Alias, PaymentAddress>,
     /// Cryptographic keypairs
     keys: HashMap<Alias, StoredKeypair<common::SecretKey>>,
+    /// Keys kept on hardware wallets
+    #[serde(default)]
+    hardware_keys: HashMap<Alias, HardwareKey>,
     /// Namada address book
     addresses: BiHashMap<Alias, Address>,
+    /// Contact information about the aliases of the `addresses` and
+    /// `payment_addrs` fields
+    #[serde(default)]
+    address_book: HashMap<Alias, ContactInfo>,
     /// Known mappings of public key hashes to their aliases in the `keys`
     /// field. Used for look-up by a public key.
     pkhs: HashMap<PublicKeyHash, Alias>,
@@ -79,6 +130,8 @@ pub enum LoadStoreError {
     ReadWallet(String, String),
     #[error("Failed to write the wallet store: {0}")]
     StoreNewWallet(String),
+    #[error("Failed to migrate the wallet store: {0}")]
+    Migrate(MigrationError),
 }
 
 impl Store {
@@ -132,7 +185,11 @@ impl Store {
 
     /// Load the store file or create a new one without any keys or addresses.
     pub fn load_or_new(store_dir: &Path) -> Result<Self, LoadStoreError> {
-        Self::load(store_dir).or_else(|_| {
+        Self::load(store_dir).or_else(|err| {
+            // A wallet that cannot be migrated mustn't be overwritten
+            if let LoadStoreError::Migrate(_) = err {
+                return Err(err);
+            }
             let store = Self::default();
             store.save(store_dir).map_err(|err| {
                 LoadStoreError::StoreNewWallet(err.to_string())
@@ -147,7 +204,11 @@ impl Store {
         store_dir: &Path,
         genesis_cfg: GenesisConfig,
     ) -> Result<Self, LoadStoreError> {
-        Self::load(store_dir).or_else(|_| {
+        Self::load(store_dir).or_else(|err| {
+            // A wallet that cannot be migrated mustn't be overwritten
+            if let LoadStoreError::Migrate(_) = err {
+                return Err(err);
+            }
             #[cfg(not(feature = "dev"))]
             let store = Self::new(genesis_cfg);
             #[cfg(feature = "dev")]
@@ -172,14 +233,33 @@ impl Store {
             FileOptions::new().read(true).write(false),
         ) {
             Ok(mut filelock) => {
-                let mut store = Vec::<u8>::new();
-                filelock.file.read_to_end(&mut store).map_err(|err| {
+                let mut data = Vec::<u8>::new();
+                filelock.file.read_to_end(&mut data).map_err(|err| {
                     LoadStoreError::ReadWallet(
                         store_dir.to_str().unwrap().into(),
                         err.to_string(),
                     )
                 })?;
-                Store::decode(store).map_err(LoadStoreError::Decode)
+                drop(filelock);
+                let (store, migrated_from) = Store::decode(&data)?;
+                if let Some(version) = migrated_from {
+                    // Keep a backup of the wallet before the migration
+                    let backup_file = wallet_file
+                        .with_extension(format!("toml.v{}.bak", version));
+                    fs::write(&backup_file, &data)
+                        .and_then(|()| store.save(store_dir))
+                        .map_err(|err| {
+                            LoadStoreError::StoreNewWallet(err.to_string())
+                        })?;
+                    println!(
+                        "Migrated the wallet from version {} to {}. The \
+                         previous wallet was backed up to {}.",
+                        version,
+                        CURRENT_VERSION,
+                        backup_file.to_string_lossy()
+                    );
+                }
+                Ok(store)
             }
             Err(err) => Err(LoadStoreError::ReadWallet(
                 wallet_file.to_string_lossy().into_owned(),
@@ -248,6 +328,29 @@ impl Store {
         self.keys.get(alias)
     }
 
+    /// Find the hardware key by an alias.
+    pub fn find_hardware_key(
+        &self,
+        alias: impl AsRef<str>,
+    ) -> Option<&HardwareKey> {
+        self.hardware_keys.get(&alias.into())
+    }
+
+    /// Find the hardware key by a public key hash.
+    pub fn find_hardware_key_by_pkh(
+        &self,
+        pkh: &PublicKeyHash,
+    ) -> Option<&HardwareKey> {
+        self.hardware_keys
+            .values()
+            .find(|key| PublicKeyHash::from(&key.public_key) == *pkh)
+    }
+
+    /// Get all known hardware keys by their alias.
+    pub fn get_hardware_keys(&self) -> &HashMap<Alias, HardwareKey> {
+        &self.hardware_keys
+    }
+
     /// Find the stored alias for a public key hash.
     pub fn find_alias_by_pkh(&self, pkh: &PublicKeyHash) -> Option<Alias> {
         self.pkhs.get(pkh).cloned()
@@ -289,6 +392,54 @@ impl Store {
         keys
     }
 
+    /// Find the address or payment address of an alias.
+    pub fn find_book_address(&self, alias: &Alias) -> Option<BookAddress> {
+        self.addresses
+            .get_by_left(alias)
+            .cloned()
+            .map(BookAddress::Transparent)
+            .or_else(|| {
+                self.payment_addrs
+                    .get(alias)
+                    .cloned()
+                    .map(BookAddress::Shielded)
+            })
+    }
+
+    /// Find the alias with the given label in the address book. Labels are
+    /// case-insensitive.
+    pub fn find_alias_by_label(&self, label: &str) -> Option<&Alias> {
+        let label = label.to_lowercase();
+        self.address_book.iter().find_map(|(alias, info)| {
+            info.label
+                .as_ref()
+                .filter(|l| l.to_lowercase() == label)
+                .map(|_| alias)
+        })
+    }
+
+    /// Get the entries of the address book, which has all the known addresses
+    /// and payment addresses with their contact information, sorted by alias.
+    pub fn get_address_book(&self) -> Vec<AddressBookEntry> {
+        let mut entries: Vec<AddressBookEntry> = self
+            .addresses
+            .iter()
+            .map(|(alias, address)| {
+                (alias, BookAddress::Transparent(address.clone()))
+            })
+            .chain(self.payment_addrs.iter().map(|(alias, payment_addr)| {
+                (alias, BookAddress::Shielded(*payment_addr))
+            }))
+            .map(|(alias, address)| AddressBookEntry {
+                alias: alias.normalize(),
+                address,
+                info: self.address_book.get(alias).cloned().unwrap_or_default(),
+            })
+            .collect();
+        entries.sort_by(|a, b| a.alias.cmp(&b.alias));
+        entries
+    }
+
     /// Get all known addresses by their alias, paired with PKH, if known.
     pub fn get_addresses(&self) -> &BiHashMap<Alias, Address> {
         &self.addresses
@@ -330,7 +481,32 @@ impl Store {
         alias: Option<String>,
         password: Option<String>,
     ) -> (Alias, common::SecretKey) {
-        let sk = gen_sk(scheme);
+        self.insert_new_keypair(gen_sk(scheme), alias, password)
+    }
+
+    /// Derive a keypair of the given scheme at the given HD path from the seed
+    /// of a mnemonic code and insert it into the store like
+    /// [`Store::gen_key`].
+    pub fn derive_key_from_seed(
+        &mut self,
+        scheme: SchemeType,
+        path: &DerivationPath,
+        seed: &[u8],
+        alias: Option<String>,
+        password: Option<String>,
+    ) -> Result<(Alias, common::SecretKey), DerivationPathError> {
+        let sk = path.derive_sk(scheme, seed)?;
+        Ok(self.insert_new_keypair(sk, alias, password))
+    }
+
+    /// Insert a new keypair and its implicit address with the provided alias.
+    /// If none provided, the alias will be the public key hash.
+    fn insert_new_keypair(
+        &mut self,
+        sk: common::SecretKey,
+        alias: Option<String>,
+        password: Option<String>,
+    ) -> (Alias, common::SecretKey) {
         let pkh: PublicKeyHash = PublicKeyHash::from(&sk.ref_to());
         let (keypair_to_store, raw_keypair) = StoredKeypair::new(sk, password);
         let address = Address::Implicit(ImplicitAddress(pkh.clone()));
@@ -355,7 +531,34 @@ impl Store {
         alias: String,
         password: Option<String>,
     ) -> (Alias, ExtendedSpendingKey) {
-        let spendkey = Self::generate_spending_key();
+        self.insert_new_spending_key(
+            Self::generate_spending_key(),
+            alias,
+            password,
+        )
+    }
+
+    /// Derive a spending key at the given ZIP-32 path from the seed of a
+    /// mnemonic code and insert it into the store like
+    /// [`Store::gen_spending_key`].
+    pub fn derive_spending_key_from_seed(
+        &mut self,
+        path: &DerivationPath,
+        seed: &[u8],
+        alias: String,
+        password: Option<String>,
+    ) -> (Alias, ExtendedSpendingKey) {
+        let spendkey = path.derive_spending_key(seed);
+        self.insert_new_spending_key(spendkey, alias, password)
+    }
+
+    /// Insert a new spending key and its viewing key with the provided alias
+    fn insert_new_spending_key(
+        &mut self,
+        spendkey: ExtendedSpendingKey,
+        alias: String,
+        password: Option<String>,
+    ) -> (Alias, ExtendedSpendingKey) {
         let viewkey = ExtendedFullViewingKey::from(&spendkey.into()).into();
         let (spendkey_to_store, _raw_spendkey) =
             StoredKeypair::new(spendkey, password);
@@ -370,6 +573,23 @@ impl Store {
         (alias, spendkey)
     }
 
+    /// Re-encrypt all the encrypted keys and spending keys with a new password
+    /// and the default KDF. If any of them cannot be decrypted with the
+    /// current password, nothing is changed and its alias is returned with
+    /// the error.
+    pub fn change_password(
+        &mut self,
+        password: &str,
+        new_password: &str,
+    ) -> Result<(), (Alias, DecryptionError)> {
+        let keys = change_keys_password(&self.keys, password, new_password)?;
+        let spend_keys =
+            change_keys_password(&self.spend_keys, password, new_password)?;
+        self.keys = keys;
+        self.spend_keys = spend_keys;
+        Ok(())
+    }
+
     /// Generate keypair for signing protocol txs and for the DKG
     /// A protocol keypair may be optionally provided
     ///
@@ -456,6 +676,35 @@ impl Store {
         Some(alias)
     }
 
+    /// Insert a hardware key and its implicit address with the given alias. If
+    /// the alias is already used, will prompt for overwrite/reselection
+    /// confirmation. If declined, then the key is not inserted and nothing is
+    /// returned, otherwise selected alias is returned.
+    pub fn insert_hardware_key(
+        &mut self,
+        alias: Alias,
+        key: HardwareKey,
+    ) -> Option<Alias> {
+        if alias.is_empty() {
+            eprintln!("Empty alias given.");
+            return None;
+        }
+        if self.contains_alias(&alias) {
+            match show_overwrite_confirmation(&alias, "a key") {
+                ConfirmationResponse::Replace => {}
+                ConfirmationResponse::Reselect(new_alias) => {
+                    return self.insert_hardware_key(new_alias, key);
+                }
+                ConfirmationResponse::Skip => return None,
+            }
+        }
+        self.remove_alias(&alias);
+        let address = Address::from(&key.public_key);
+        self.hardware_keys.insert(alias.clone(), key);
+        self.addresses.insert(alias.clone(), address);
+        Some(alias)
+    }
+
     /// Insert spending keys similarly to how it's done for keypairs
     pub fn insert_spending_key(
         &mut self,
@@ -514,6 +763,7 @@ impl Store {
             || self.view_keys.contains_key(alias)
             || self.spend_keys.contains_key(alias)
             || self.keys.contains_key(alias)
+            || self.hardware_keys.contains_key(alias)
             || self.addresses.contains_left(alias)
     }
 
@@ -523,10 +773,77 @@ impl Store {
         self.view_keys.remove(alias);
         self.spend_keys.remove(alias);
         self.keys.remove(alias);
+        self.hardware_keys.remove(alias);
         self.addresses.remove_by_left(alias);
+        self.address_book.remove(alias);
         self.pkhs.retain(|_key, val| val != alias);
     }
 
+    /// Insert an entry into the address book. If the alias is already used
+    /// for another address, will prompt for overwrite/reselection
+    /// confirmation like [`Store::insert_address`]. Otherwise only the contact
+    /// information of the alias is replaced. Return the selected alias.
+    pub fn insert_contact(
+        &mut self,
+        entry: AddressBookEntry,
+    ) -> Result<Alias, AddressBookError> {
+        let AddressBookEntry {
+            alias,
+            address,
+            info,
+        } = entry;
+        let alias: Alias = alias.into();
+        self.check_label(&info, &alias)?;
+        let alias = if self.find_book_address(&alias).as_ref() == Some(&address)
+        {
+            alias
+        } else {
+            let inserted = match address {
+                BookAddress::Transparent(address) => {
+                    self.insert_address(alias.clone(), address)
+                }
+                BookAddress::Shielded(payment_addr) => {
+                    self.insert_payment_addr(alias.clone(), payment_addr)
+                }
+            };
+            let inserted = inserted
+                .ok_or_else(|| AddressBookError::NotAdded(alias.normalize()))?;
+            // A reselected alias must be checked again
+            if inserted != alias {
+                self.check_label(&info, &inserted)?;
+            }
+            inserted
+        };
+        if info.is_empty() {
+            self.address_book.remove(&alias);
+        } else {
+            self.address_book.insert(alias.clone(), info);
+        }
+        Ok(alias)
+    }
+
+    /// Check that the label of the contact information isn't used by another
+    /// alias than the given one
+    fn check_label(
+        &self,
+        info: &ContactInfo,
+        alias: &Alias,
+    ) -> Result<(), AddressBookError> {
+        let label = match info.label.as_ref() {
+            Some(label) => label,
+            None => return Ok(()),
+        };
+        match self.find_alias_by_label(label) {
+            Some(other) if other != alias => {
+                Err(AddressBookError::DuplicateLabel(
+                    label.clone(),
+                    other.normalize(),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Insert payment addresses similarly to how it's done for keypairs
     pub fn insert_payment_addr(
         &mut self,
@@ -669,8 +986,16 @@ impl Store {
         });
     }
 
-    fn decode(data: Vec<u8>) -> Result<Self, toml::de::Error> {
-        toml::from_slice(&data)
+    /// Decode a wallet store, migrating it to the current version if needed.
+    /// Returns the version of the wallet before the migration, if it was
+    /// migrated.
+    fn decode(data: &[u8]) -> Result<(Self, Option<u32>), LoadStoreError> {
+        let mut value: toml::Value =
+            toml::from_slice(data).map_err(LoadStoreError::Decode)?;
+        let migrated_from =
+            migration::migrate(&mut value).map_err(LoadStoreError::Migrate)?;
+        let store = value.try_into().map_err(LoadStoreError::Decode)?;
+        Ok((store, migrated_from))
     }
 
     fn encode(&self) -> Vec<u8> {
@@ -678,6 +1003,25 @@ impl Store {
     }
 }
 
+/// Re-encrypt the given keys with a new password
+fn change_keys_password<T>(
+    keys: &HashMap<Alias, StoredKeypair<T>>,
+    password: &str,
+    new_password: &str,
+) -> Result<HashMap<Alias, StoredKeypair<T>>, (Alias, DecryptionError)>
+where
+    T: BorshSerialize + BorshDeserialize + Display + FromStr + Clone,
+    <T as FromStr>::Err: Display,
+{
+    keys.iter()
+        .map(|(alias, key)| {
+            key.change_password(password.to_owned(), new_password.to_owned())
+                .map(|key| (alias.clone(), key))
+                .map_err(|err| (alias.clone(), err))
+        })
+        .collect()
+}
+
 enum ConfirmationResponse {
     Replace,
     Reselect(Alias),
@@ -770,7 +1114,7 @@ mod test_wallet {
             validator_keys
         );
         let data = store.encode();
-        let _ = Store::decode(data).expect("Test failed");
+        let _ = Store::decode(&data).expect("Test failed");
     }
 
     #[test]
@@ -783,6 +1127,56 @@ mod test_wallet {
             validator_keys
         );
         let data = store.encode();
-        let _ = Store::decode(data).expect("Test failed");
+        let _ = Store::decode(&data).expect("Test failed");
+    }
+
+    #[test]
+    fn test_address_book() {
+        use namada::types::address::testing::{
+            established_address_1, established_address_2,
+        };
+
+        let mut store = Store::new();
+        let entry =
+            |alias: &str, address: Address, label: &str| AddressBookEntry {
+                alias: alias.to_owned(),
+                address: BookAddress::Transparent(address),
+                info: ContactInfo {
+                    label: Some(label.to_owned()),
+                    notes: None,
+                    tags: ["exchange".to_owned()].into(),
+                },
+            };
+        let alias = store
+            .insert_contact(entry("exchange", established_address_1(), "Ex"))
+            .unwrap();
+        assert_eq!(store.find_address(&alias), Some(&established_address_1()));
+        assert_eq!(store.find_alias_by_label("ex"), Some(&alias));
+
+        // The contact information of the same address can be edited
+        store
+            .insert_contact(entry("exchange", established_address_1(), "Ex 2"))
+            .unwrap();
+        assert_eq!(store.find_alias_by_label("Ex"), None);
+        assert_eq!(store.find_alias_by_label("Ex 2"), Some(&alias));
+
+        // A label can't be used by another alias
+        assert!(matches!(
+            store.insert_contact(entry(
+                "other",
+                established_address_2(),
+                "Ex 2"
+            )),
+            Err(AddressBookError::DuplicateLabel(_, _))
+        ));
+        assert_eq!(store.find_address("other"), None);
+
+        // The entries survive a roundtrip and their alias can be removed
+        let (mut store, _) = Store::decode(&store.encode()).unwrap();
+        let book = store.get_address_book();
+        let entry = book.iter().find(|entry| entry.alias == "exchange");
+        assert!(entry.unwrap().info.has_tag("exchange"));
+        store.remove_alias(&alias);
+        assert_eq!(store.find_alias_by_label("Ex 2"), None);
     }
 }
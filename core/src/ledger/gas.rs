@@ -4,6 +4,7 @@
 use std::convert::TryFrom;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[allow(missing_docs)]
@@ -17,8 +18,6 @@ pub enum Error {
     GasOverflow,
 }
 
-const COMPILE_GAS_PER_BYTE: u64 = 1;
-const BASE_TRANSACTION_FEE: u64 = 2;
 const PARALLEL_GAS_DIVIDER: u64 = 10;
 
 /// The maximum value should be less or equal to i64::MAX
@@ -32,12 +31,167 @@ pub const MIN_STORAGE_GAS: u64 = 1;
 /// Gas module result for functions that may fail
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The gas costs of the operations of transactions and validity predicates,
+/// set in the protocol parameters. The costs are relative to the cost of
+/// reading a byte from storage and they can be derived from the benchmarks in
+/// `tests/src/gas_schedule.rs`.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+#[serde(default)]
+pub struct GasSchedule {
+    /// The fixed cost of applying a transaction
+    pub tx_base: u64,
+    /// The cost per byte of an applied transaction
+    pub tx_byte: u64,
+    /// The cost per byte of compiled wasm code
+    pub compile_byte: u64,
+    /// The cost per byte of validated wasm code
+    pub wasm_validation_byte: u64,
+    /// The cost of a unit of fuel consumed by executed wasm instructions
+    pub wasm_fuel: u64,
+    /// The fixed cost of calling a host function
+    pub host_fn: u64,
+    /// The cost per byte copied between the wasm memory and the host
+    pub memory_byte: u64,
+    /// The fixed cost of a storage access
+    pub storage_access: u64,
+    /// The cost per byte of a key or a value read from storage
+    pub storage_read_byte: u64,
+    /// The cost per byte of a key or a value written to or deleted from
+    /// storage
+    pub storage_write_byte: u64,
+    /// The cost per byte by which a write increases the size of the storage
+    pub storage_size_byte: u64,
+    /// The fixed cost of creating a storage prefix iterator
+    pub iter_prefix: u64,
+    /// The fixed cost of advancing a storage prefix iterator, including the
+    /// entries that are skipped because they are deleted in the write log
+    pub iter_next: u64,
+    /// The fixed cost of hashing data
+    pub hash: u64,
+    /// The cost per byte of hashed data
+    pub hash_byte: u64,
+    /// The fixed cost of verifying a signature, on top of the cost of hashing
+    /// the signed data
+    pub verify_signature: u64,
+}
+
+impl Default for GasSchedule {
+    fn default() -> Self {
+        Self {
+            tx_base: 2,
+            tx_byte: 1,
+            compile_byte: 1,
+            wasm_validation_byte: 1,
+            wasm_fuel: 1,
+            host_fn: 1,
+            memory_byte: 1,
+            storage_access: MIN_STORAGE_GAS,
+            storage_read_byte: 1,
+            storage_write_byte: 1,
+            storage_size_byte: 1,
+            iter_prefix: 100,
+            iter_next: 10,
+            hash: 100,
+            hash_byte: 1,
+            verify_signature: 1000,
+        }
+    }
+}
+
+impl GasSchedule {
+    /// The gas cost of validating wasm code of the given length
+    pub fn wasm_validation_gas(&self, len: usize) -> u64 {
+        (len as u64).saturating_mul(self.wasm_validation_byte)
+    }
+
+    /// The gas cost of the given units of fuel consumed by wasm execution
+    pub fn wasm_fuel_gas(&self, fuel: u64) -> u64 {
+        fuel.saturating_mul(self.wasm_fuel)
+    }
+
+    /// The gas cost of copying the given number of bytes between the wasm
+    /// memory and the host
+    pub fn memory_gas(&self, bytes: u64) -> u64 {
+        self.host_fn
+            .saturating_add(bytes.saturating_mul(self.memory_byte))
+    }
+
+    /// The gas cost of a change of the storage size by the given number of
+    /// bytes. Only writes that grow the storage are charged.
+    pub fn storage_size_diff_gas(&self, size_diff: i64) -> u64 {
+        u64::try_from(size_diff)
+            .unwrap_or_default()
+            .saturating_mul(self.storage_size_byte)
+    }
+
+    /// The gas cost of reading the given number of bytes from storage
+    pub fn storage_read_gas(&self, bytes: u64) -> u64 {
+        self.storage_access
+            .saturating_add(bytes.saturating_mul(self.storage_read_byte))
+    }
+
+    /// The gas cost of writing or deleting the given number of bytes in
+    /// storage
+    pub fn storage_write_gas(&self, bytes: u64) -> u64 {
+        self.storage_access
+            .saturating_add(bytes.saturating_mul(self.storage_write_byte))
+    }
+
+    /// The gas cost of creating a prefix iterator with a prefix of the given
+    /// number of bytes
+    pub fn iter_prefix_gas(&self, bytes: u64) -> u64 {
+        self.iter_prefix
+            .saturating_add(bytes.saturating_mul(self.storage_read_byte))
+    }
+
+    /// The gas cost of advancing a prefix iterator to an entry of the given
+    /// number of bytes
+    pub fn iter_next_gas(&self, bytes: u64) -> u64 {
+        self.iter_next
+            .saturating_add(bytes.saturating_mul(self.storage_read_byte))
+    }
+
+    /// The gas cost of hashing data of the given length
+    pub fn hash_gas(&self, len: usize) -> u64 {
+        self.hash
+            .saturating_add((len as u64).saturating_mul(self.hash_byte))
+    }
+
+    /// The gas cost of verifying a signature over data of the given length
+    pub fn verify_signature_gas(&self, len: usize) -> u64 {
+        self.verify_signature.saturating_add(self.hash_gas(len))
+    }
+}
+
 /// Gas metering in a block. Tracks the gas in a current block and a current
 /// transaction.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct BlockGasMeter {
     block_gas: u64,
     transaction_gas: u64,
+    /// The gas limit of the current transaction, which is at most the
+    /// protocol's transaction gas limit
+    transaction_gas_limit: u64,
+    schedule: GasSchedule,
+}
+
+impl Default for BlockGasMeter {
+    fn default() -> Self {
+        Self::new(GasSchedule::default())
+    }
 }
 
 /// Gas metering in a validity predicate
@@ -47,6 +201,8 @@ pub struct VpGasMeter {
     initial_gas: u64,
     /// The current gas usage in the VP
     pub current_gas: u64,
+    /// The gas costs of the VP's operations
+    schedule: GasSchedule,
 }
 
 /// Gas meter for VPs parallel runs
@@ -59,6 +215,34 @@ pub struct VpsGas {
 }
 
 impl BlockGasMeter {
+    /// Initialize a new block gas meter with the given gas schedule.
+    pub fn new(schedule: GasSchedule) -> Self {
+        Self {
+            block_gas: 0,
+            transaction_gas: 0,
+            transaction_gas_limit: TRANSACTION_GAS_LIMIT,
+            schedule,
+        }
+    }
+
+    /// Get the gas schedule of the meter.
+    pub fn schedule(&self) -> &GasSchedule {
+        &self.schedule
+    }
+
+    /// Set the gas schedule of the meter, e.g. when it's been updated in the
+    /// protocol parameters.
+    pub fn set_schedule(&mut self, schedule: GasSchedule) {
+        self.schedule = schedule;
+    }
+
+    /// Set the gas limit of the current transaction, e.g. the gas limit
+    /// declared in its wrapper. The limit is capped at the protocol's
+    /// transaction gas limit and it's reset when the transaction is finalized.
+    pub fn set_transaction_gas_limit(&mut self, limit: u64) {
+        self.transaction_gas_limit = limit.min(TRANSACTION_GAS_LIMIT);
+    }
+
     /// Add gas cost for the current transaction. It will return error when the
     /// consumed gas exceeds the transaction gas limit, but the state will still
     /// be updated.
@@ -68,7 +252,7 @@ impl BlockGasMeter {
             .checked_add(gas)
             .ok_or(Error::GasOverflow)?;
 
-        if self.transaction_gas > TRANSACTION_GAS_LIMIT {
+        if self.transaction_gas > self.transaction_gas_limit {
             return Err(Error::TransactionGasExceededError);
         }
         Ok(())
@@ -78,26 +262,35 @@ impl BlockGasMeter {
     /// charged the moment we try to apply the transaction.
     pub fn add_base_transaction_fee(&mut self, bytes_len: usize) -> Result<()> {
         tracing::trace!("add_base_transaction_fee {}", bytes_len);
-        self.add(BASE_TRANSACTION_FEE)
+        let gas = (bytes_len as u64)
+            .checked_mul(self.schedule.tx_byte)
+            .and_then(|gas| gas.checked_add(self.schedule.tx_base))
+            .ok_or(Error::GasOverflow)?;
+        self.add(gas)
     }
 
     /// Add the compiling cost proportionate to the code length
     pub fn add_compiling_fee(&mut self, bytes_len: usize) -> Result<()> {
-        self.add(bytes_len as u64 * COMPILE_GAS_PER_BYTE)
+        let gas = (bytes_len as u64)
+            .checked_mul(self.schedule.compile_byte)
+            .ok_or(Error::GasOverflow)?;
+        self.add(gas)
     }
 
     /// Add the transaction gas to the block's total gas. Returns the
-    /// transaction's gas cost and resets the transaction meter. It will return
-    /// error when the consumed gas exceeds the block gas limit, but the state
-    /// will still be updated.
+    /// transaction's gas cost and resets the transaction meter and its gas
+    /// limit. It will return error when the consumed gas exceeds the block gas
+    /// limit, but the state will still be updated.
     pub fn finalize_transaction(&mut self) -> Result<u64> {
+        let transaction_gas = self.transaction_gas;
+        self.transaction_gas = 0;
+        self.transaction_gas_limit = TRANSACTION_GAS_LIMIT;
+
         self.block_gas = self
             .block_gas
-            .checked_add(self.transaction_gas)
+            .checked_add(transaction_gas)
             .ok_or(Error::GasOverflow)?;
 
-        let transaction_gas = self.transaction_gas;
-        self.transaction_gas = 0;
         if self.block_gas > BLOCK_GAS_LIMIT {
             return Err(Error::BlockGasExceeded);
         }
@@ -107,6 +300,7 @@ impl BlockGasMeter {
     /// Reset the gas meter.
     pub fn reset(&mut self) {
         self.transaction_gas = 0;
+        self.transaction_gas_limit = TRANSACTION_GAS_LIMIT;
         self.block_gas = 0;
     }
 
@@ -125,12 +319,24 @@ impl VpGasMeter {
     /// Initialize a new VP gas meter, starting with the gas consumed in the
     /// transaction so far.
     pub fn new(initial_gas: u64) -> Self {
+        Self::with_schedule(initial_gas, GasSchedule::default())
+    }
+
+    /// Initialize a new VP gas meter with the given gas schedule, starting
+    /// with the gas consumed in the transaction so far.
+    pub fn with_schedule(initial_gas: u64, schedule: GasSchedule) -> Self {
         Self {
             initial_gas,
             current_gas: 0,
+            schedule,
         }
     }
 
+    /// Get the gas schedule of the meter.
+    pub fn schedule(&self) -> &GasSchedule {
+        &self.schedule
+    }
+
     /// Consume gas in a validity predicate. It will return error when the
     /// consumed gas exceeds the transaction gas limit, but the state will still
     /// be updated.
@@ -155,7 +361,10 @@ impl VpGasMeter {
 
     /// Add the compiling cost proportionate to the code length
     pub fn add_compiling_fee(&mut self, bytes_len: usize) -> Result<()> {
-        self.add(bytes_len as u64 * COMPILE_GAS_PER_BYTE)
+        let gas = (bytes_len as u64)
+            .checked_mul(self.schedule.compile_byte)
+            .ok_or(Error::GasOverflow)?;
+        self.add(gas)
     }
 }
 
@@ -283,6 +492,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tx_gas_limit_set() {
+        let mut meter = BlockGasMeter::default();
+        meter.set_transaction_gas_limit(100);
+        meter.add(100).expect("cannot add the gas");
+        assert_matches!(
+            meter.add(1).expect_err("unexpectedly succeeded"),
+            Error::TransactionGasExceededError
+        );
+        // The limit is reset with the transaction
+        meter
+            .finalize_transaction()
+            .expect("over the block gas limit");
+        meter.add(101).expect("cannot add the gas");
+    }
+
     #[test]
     fn test_block_gas_limit() {
         let mut meter = BlockGasMeter::default();
@@ -309,6 +534,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_gas_schedule() {
+        let schedule = GasSchedule {
+            storage_access: 10,
+            storage_read_byte: 2,
+            storage_write_byte: 3,
+            iter_next: 5,
+            storage_size_byte: 4,
+            wasm_fuel: 2,
+            ..GasSchedule::default()
+        };
+        assert_eq!(schedule.storage_read_gas(100), 210);
+        assert_eq!(schedule.storage_write_gas(100), 310);
+        assert_eq!(schedule.iter_next_gas(100), 205);
+        assert_eq!(schedule.storage_read_gas(u64::MAX), u64::MAX);
+        assert_eq!(schedule.storage_size_diff_gas(100), 400);
+        // Writes that shrink the storage are not charged
+        assert_eq!(schedule.storage_size_diff_gas(-100), 0);
+        assert_eq!(schedule.wasm_fuel_gas(100), 200);
+
+        let mut meter = BlockGasMeter::new(schedule);
+        meter
+            .add_base_transaction_fee(10)
+            .expect("cannot add the gas");
+        meter.add_compiling_fee(10).expect("cannot add the gas");
+        let defaults = GasSchedule::default();
+        assert_eq!(
+            meter.get_current_transaction_gas(),
+            defaults.tx_base
+                + 10 * defaults.tx_byte
+                + 10 * defaults.compile_byte
+        );
+    }
+
     /// Test that the function [`as_i64`] cannot fail for transaction and block
     /// gas limit + some "tolerance" for gas exhaustion.
     #[test]
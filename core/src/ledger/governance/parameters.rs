@@ -1,6 +1,8 @@
 use std::fmt::Display;
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 
 use super::storage as gov_storage;
 use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
@@ -31,6 +33,22 @@ pub struct GovParams {
     pub max_proposal_content_size: u64,
     /// Minimum epochs between end and grace epochs
     pub min_proposal_grace_epochs: u64,
+    /// Minimum expedited proposal voting period in epochs
+    pub min_expedited_proposal_period: u64,
+    /// Minimum epochs between end and grace epochs of an expedited proposal
+    pub min_expedited_proposal_grace_epochs: u64,
+    /// Fraction of the total voting power that must vote yay for an
+    /// expedited proposal to pass
+    pub expedited_proposal_threshold: Decimal,
+    /// Fraction of the total voting power that must vote for the deposits of
+    /// a proposal to be refunded
+    pub min_proposal_quorum: Decimal,
+    /// Fraction of the cast voting power that must veto a proposal to reject
+    /// it and burn its deposits
+    pub proposal_veto_threshold: Decimal,
+    /// Fraction of the deposits burnt when a proposal doesn't reach the
+    /// quorum
+    pub no_quorum_burn_rate: Decimal,
 }
 
 impl Display for GovParams {
@@ -39,13 +57,22 @@ impl Display for GovParams {
             f,
             "Min. proposal fund: {}\nMax. proposal code size: {}\nMin. \
              proposal period: {}\nMax. proposal period: {}\nMax. proposal \
-             content size: {}\nMin. proposal grace epochs: {}",
+             content size: {}\nMin. proposal grace epochs: {}\nMin. expedited \
+             proposal period: {}\nMin. expedited proposal grace epochs: \
+             {}\nExpedited proposal threshold: {}\nMin. proposal quorum: \
+             {}\nProposal veto threshold: {}\nNo quorum burn rate: {}",
             self.min_proposal_fund,
             self.max_proposal_code_size,
             self.min_proposal_period,
             self.max_proposal_period,
             self.max_proposal_content_size,
-            self.min_proposal_grace_epochs
+            self.min_proposal_grace_epochs,
+            self.min_expedited_proposal_period,
+            self.min_expedited_proposal_grace_epochs,
+            self.expedited_proposal_threshold,
+            self.min_proposal_quorum,
+            self.proposal_veto_threshold,
+            self.no_quorum_burn_rate
         )
     }
 }
@@ -59,6 +86,12 @@ impl Default for GovParams {
             max_proposal_period: 27,
             max_proposal_content_size: 10_000,
             min_proposal_grace_epochs: 6,
+            min_expedited_proposal_period: 1,
+            min_expedited_proposal_grace_epochs: 1,
+            expedited_proposal_threshold: dec!(0.75),
+            min_proposal_quorum: dec!(0.33),
+            proposal_veto_threshold: dec!(0.33),
+            no_quorum_burn_rate: dec!(0.5),
         }
     }
 }
@@ -76,6 +109,12 @@ impl GovParams {
             max_proposal_period,
             max_proposal_content_size,
             min_proposal_grace_epochs,
+            min_expedited_proposal_period,
+            min_expedited_proposal_grace_epochs,
+            expedited_proposal_threshold,
+            min_proposal_quorum,
+            proposal_veto_threshold,
+            no_quorum_burn_rate,
         } = self;
 
         let min_proposal_fund_key = gov_storage::get_min_proposal_fund_key();
@@ -104,6 +143,39 @@ impl GovParams {
         storage
             .write(&min_proposal_grace_epoch_key, min_proposal_grace_epochs)?;
 
+        let min_expedited_proposal_period_key =
+            gov_storage::get_min_expedited_proposal_period_key();
+        storage.write(
+            &min_expedited_proposal_period_key,
+            min_expedited_proposal_period,
+        )?;
+
+        let min_expedited_proposal_grace_epoch_key =
+            gov_storage::get_min_expedited_proposal_grace_epoch_key();
+        storage.write(
+            &min_expedited_proposal_grace_epoch_key,
+            min_expedited_proposal_grace_epochs,
+        )?;
+
+        let expedited_proposal_threshold_key =
+            gov_storage::get_expedited_proposal_threshold_key();
+        storage.write(
+            &expedited_proposal_threshold_key,
+            expedited_proposal_threshold,
+        )?;
+
+        let min_proposal_quorum_key =
+            gov_storage::get_min_proposal_quorum_key();
+        storage.write(&min_proposal_quorum_key, min_proposal_quorum)?;
+
+        let proposal_veto_threshold_key =
+            gov_storage::get_proposal_veto_threshold_key();
+        storage.write(&proposal_veto_threshold_key, proposal_veto_threshold)?;
+
+        let no_quorum_burn_rate_key =
+            gov_storage::get_no_quorum_burn_rate_key();
+        storage.write(&no_quorum_burn_rate_key, no_quorum_burn_rate)?;
+
         let counter_key = gov_storage::get_counter_key();
         storage.write(&counter_key, u64::MIN)
     }
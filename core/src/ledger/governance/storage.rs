@@ -1,5 +1,6 @@
 use crate::ledger::governance::ADDRESS;
 use crate::types::address::Address;
+use crate::types::hash::Hash;
 use crate::types::storage::{DbKeySeg, Key, KeySeg};
 
 const PROPOSAL_PREFIX: &str = "proposal";
@@ -12,6 +13,10 @@ const PROPOSAL_GRACE_EPOCH: &str = "grace_epoch";
 const PROPOSAL_FUNDS: &str = "funds";
 const PROPOSAL_CODE: &str = "proposal_code";
 const PROPOSAL_COMMITTING_EPOCH: &str = "epoch";
+const PROPOSAL_STATUS: &str = "status";
+const PROPOSAL_EXPEDITED: &str = "expedited";
+const PROPOSAL_DEPOSIT: &str = "deposit";
+const PROPOSAL_DISBURSEMENTS: &str = "slash_fund_disbursements";
 
 const MIN_PROPOSAL_FUND_KEY: &str = "min_fund";
 const MAX_PROPOSAL_CODE_SIZE_KEY: &str = "max_code_size";
@@ -19,8 +24,19 @@ const MIN_PROPOSAL_PERIOD_KEY: &str = "min_period";
 const MAX_PROPOSAL_PERIOD_KEY: &str = "max_period";
 const MAX_PROPOSAL_CONTENT_SIZE_KEY: &str = "max_content";
 const MIN_GRACE_EPOCH_KEY: &str = "min_grace_epoch";
+const MIN_EXPEDITED_PROPOSAL_PERIOD_KEY: &str = "min_expedited_period";
+const MIN_EXPEDITED_GRACE_EPOCH_KEY: &str = "min_expedited_grace_epoch";
+const EXPEDITED_PROPOSAL_THRESHOLD_KEY: &str = "expedited_threshold";
+const MIN_PROPOSAL_QUORUM_KEY: &str = "min_quorum";
+const PROPOSAL_VETO_THRESHOLD_KEY: &str = "veto_threshold";
+const NO_QUORUM_BURN_RATE_KEY: &str = "no_quorum_burn_rate";
 const COUNTER_KEY: &str = "counter";
 const PENDING_PROPOSAL: &str = "pending";
+const OFFLINE_PROPOSAL_PREFIX: &str = "offline";
+const OFFLINE_PROPOSAL: &str = "proposal";
+const OFFLINE_PROPOSAL_VOTE: &str = "vote";
+const OFFLINE_PROPOSAL_RESULT: &str = "result";
+const PENDING_OFFLINE_TALLY: &str = "pending_offline";
 
 /// Check if key is inside governance address space
 pub fn is_governance_key(key: &Key) -> bool {
@@ -101,6 +117,61 @@ pub fn is_grace_epoch_key(key: &Key) -> bool {
     }
 }
 
+/// Check if key is the key of the expedited flag of a proposal
+pub fn is_expedited_key(key: &Key) -> bool {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(id),
+            DbKeySeg::StringSeg(expedited),
+        ] if addr == &ADDRESS
+            && prefix == PROPOSAL_PREFIX
+            && expedited == PROPOSAL_EXPEDITED =>
+        {
+            id.parse::<u64>().is_ok()
+        }
+        _ => false,
+    }
+}
+
+/// Check if key is the key of the slash fund disbursements of a proposal
+pub fn is_disbursements_key(key: &Key) -> bool {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(id),
+            DbKeySeg::StringSeg(disbursements),
+        ] if addr == &ADDRESS
+            && prefix == PROPOSAL_PREFIX
+            && disbursements == PROPOSAL_DISBURSEMENTS =>
+        {
+            id.parse::<u64>().is_ok()
+        }
+        _ => false,
+    }
+}
+
+/// Check if key is the key of a deposit topping up a proposal
+pub fn is_deposit_key(key: &Key) -> bool {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(id),
+            DbKeySeg::StringSeg(deposit),
+            DbKeySeg::AddressSeg(_depositor),
+        ] if addr == &ADDRESS
+            && prefix == PROPOSAL_PREFIX
+            && deposit == PROPOSAL_DEPOSIT =>
+        {
+            id.parse::<u64>().is_ok()
+        }
+        _ => false,
+    }
+}
+
 /// Check if key is content key
 pub fn is_content_key(key: &Key) -> bool {
     match &key.segments[..] {
@@ -245,6 +316,60 @@ pub fn is_min_grace_epoch_key(key: &Key) -> bool {
                     && min_grace_epoch_param == MIN_GRACE_EPOCH_KEY)
 }
 
+/// Check if key is a min expedited proposal period param key
+pub fn is_min_expedited_proposal_period_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+             DbKeySeg::AddressSeg(addr),
+             DbKeySeg::StringSeg(min_expedited_period_param),
+         ] if addr == &ADDRESS
+             && min_expedited_period_param
+                 == MIN_EXPEDITED_PROPOSAL_PERIOD_KEY)
+}
+
+/// Check if key is a min expedited grace epoch param key
+pub fn is_min_expedited_grace_epoch_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+             DbKeySeg::AddressSeg(addr),
+             DbKeySeg::StringSeg(min_expedited_grace_epoch_param),
+         ] if addr == &ADDRESS
+             && min_expedited_grace_epoch_param
+                 == MIN_EXPEDITED_GRACE_EPOCH_KEY)
+}
+
+/// Check if key is an expedited proposal threshold param key
+pub fn is_expedited_proposal_threshold_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+             DbKeySeg::AddressSeg(addr),
+             DbKeySeg::StringSeg(expedited_threshold_param),
+         ] if addr == &ADDRESS
+             && expedited_threshold_param == EXPEDITED_PROPOSAL_THRESHOLD_KEY)
+}
+
+/// Check if key is a min proposal quorum param key
+pub fn is_min_proposal_quorum_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+             DbKeySeg::AddressSeg(addr),
+             DbKeySeg::StringSeg(min_quorum_param),
+         ] if addr == &ADDRESS && min_quorum_param == MIN_PROPOSAL_QUORUM_KEY)
+}
+
+/// Check if key is a proposal veto threshold param key
+pub fn is_proposal_veto_threshold_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+             DbKeySeg::AddressSeg(addr),
+             DbKeySeg::StringSeg(veto_threshold_param),
+         ] if addr == &ADDRESS
+             && veto_threshold_param == PROPOSAL_VETO_THRESHOLD_KEY)
+}
+
+/// Check if key is a no quorum deposit burn rate param key
+pub fn is_no_quorum_burn_rate_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+             DbKeySeg::AddressSeg(addr),
+             DbKeySeg::StringSeg(burn_rate_param),
+         ] if addr == &ADDRESS && burn_rate_param == NO_QUORUM_BURN_RATE_KEY)
+}
+
 /// Check if key is parameter key
 pub fn is_parameter_key(key: &Key) -> bool {
     is_min_proposal_fund_key(key)
@@ -253,6 +378,55 @@ pub fn is_parameter_key(key: &Key) -> bool {
         || is_min_proposal_period_key(key)
         || is_max_proposal_period_key(key)
         || is_min_grace_epoch_key(key)
+        || is_min_expedited_proposal_period_key(key)
+        || is_min_expedited_grace_epoch_key(key)
+        || is_expedited_proposal_threshold_key(key)
+        || is_min_proposal_quorum_key(key)
+        || is_proposal_veto_threshold_key(key)
+        || is_no_quorum_burn_rate_key(key)
+}
+
+/// Check if a key is the key of an offline proposal submitted for tallying
+pub fn is_offline_proposal_key(key: &Key) -> bool {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(_hash),
+            DbKeySeg::StringSeg(proposal),
+        ] if addr == &ADDRESS
+            && prefix == OFFLINE_PROPOSAL_PREFIX
+            && proposal == OFFLINE_PROPOSAL => true,
+        _ => false,
+    }
+}
+
+/// Check if a key is the key of a vote of an offline proposal
+pub fn is_offline_vote_key(key: &Key) -> bool {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(_hash),
+            DbKeySeg::StringSeg(vote),
+            DbKeySeg::AddressSeg(_voter),
+        ] if addr == &ADDRESS
+            && prefix == OFFLINE_PROPOSAL_PREFIX
+            && vote == OFFLINE_PROPOSAL_VOTE => true,
+        _ => false,
+    }
+}
+
+/// Check if a key is the key of an offline proposal waiting to be tallied
+pub fn is_pending_offline_tally_key(key: &Key) -> bool {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(_hash),
+        ] if addr == &ADDRESS && prefix == PENDING_OFFLINE_TALLY => true,
+        _ => false,
+    }
 }
 
 /// Check if key is start epoch or end epoch key
@@ -309,6 +483,49 @@ pub fn get_min_proposal_grace_epoch_key() -> Key {
         .expect("Cannot obtain a storage key")
 }
 
+/// Get minimum expedited proposal period key
+pub fn get_min_expedited_proposal_period_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&MIN_EXPEDITED_PROPOSAL_PERIOD_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get min grace epoch expedited proposal key
+pub fn get_min_expedited_proposal_grace_epoch_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&MIN_EXPEDITED_GRACE_EPOCH_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get expedited proposal threshold key
+pub fn get_expedited_proposal_threshold_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&EXPEDITED_PROPOSAL_THRESHOLD_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get minimum proposal quorum key
+pub fn get_min_proposal_quorum_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&MIN_PROPOSAL_QUORUM_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get proposal veto threshold key
+pub fn get_proposal_veto_threshold_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&PROPOSAL_VETO_THRESHOLD_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the fraction of the deposits burnt when a proposal doesn't
+/// reach the quorum
+pub fn get_no_quorum_burn_rate_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&NO_QUORUM_BURN_RATE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
 /// Get key of proposal ids counter
 pub fn get_counter_key() -> Key {
     Key::from(ADDRESS.to_db_key())
@@ -379,6 +596,51 @@ pub fn get_proposal_code_key(id: u64) -> Key {
         .expect("Cannot obtain a storage key")
 }
 
+/// Get the key of the status of a proposal, which is only written once the
+/// proposal is finalized at its grace epoch
+pub fn get_proposal_status_key(id: u64) -> Key {
+    proposal_prefix()
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+        .push(&PROPOSAL_STATUS.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the flag of an expedited proposal, which is removed if the
+/// proposal is downgraded to a normal one
+pub fn get_proposal_expedited_key(id: u64) -> Key {
+    proposal_prefix()
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+        .push(&PROPOSAL_EXPEDITED.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the slash fund disbursements of a proposal
+pub fn get_proposal_disbursements_key(id: u64) -> Key {
+    proposal_prefix()
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+        .push(&PROPOSAL_DISBURSEMENTS.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the prefix of the deposits topping up a proposal
+pub fn get_proposal_deposit_prefix(id: u64) -> Key {
+    proposal_prefix()
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+        .push(&PROPOSAL_DEPOSIT.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the deposit of an address topping up a proposal
+pub fn get_proposal_deposit_key(id: u64, depositor: &Address) -> Key {
+    get_proposal_deposit_prefix(id)
+        .push(depositor)
+        .expect("Cannot obtain a storage key")
+}
+
 /// Get the proposal committing key prefix
 pub fn get_commiting_proposals_prefix(epoch: u64) -> Key {
     proposal_prefix()
@@ -417,6 +679,58 @@ pub fn get_vote_proposal_key(
         .expect("Cannot obtain a storage key")
 }
 
+/// Get the prefix of the keys of an offline proposal
+pub fn get_offline_proposal_prefix(proposal_hash: &Hash) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&OFFLINE_PROPOSAL_PREFIX.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&proposal_hash.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of an offline proposal submitted for tallying
+pub fn get_offline_proposal_key(proposal_hash: &Hash) -> Key {
+    get_offline_proposal_prefix(proposal_hash)
+        .push(&OFFLINE_PROPOSAL.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the prefix of the votes of an offline proposal
+pub fn get_offline_vote_prefix_key(proposal_hash: &Hash) -> Key {
+    get_offline_proposal_prefix(proposal_hash)
+        .push(&OFFLINE_PROPOSAL_VOTE.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the vote of an offline proposal
+pub fn get_offline_vote_key(proposal_hash: &Hash, voter: &Address) -> Key {
+    get_offline_vote_prefix_key(proposal_hash)
+        .push(voter)
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the tally result of an offline proposal
+pub fn get_offline_proposal_result_key(proposal_hash: &Hash) -> Key {
+    get_offline_proposal_prefix(proposal_hash)
+        .push(&OFFLINE_PROPOSAL_RESULT.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the prefix of the offline proposals waiting to be tallied
+pub fn get_pending_offline_tally_prefix() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&PENDING_OFFLINE_TALLY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of an offline proposal waiting to be tallied, holding the epoch
+/// at which its votes submission window closes
+pub fn get_pending_offline_tally_key(proposal_hash: &Hash) -> Key {
+    get_pending_offline_tally_prefix()
+        .push(&proposal_hash.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
 /// Get the proposal execution key
 pub fn get_proposal_execution_key(id: u64) -> Key {
     Key::from(ADDRESS.to_db_key())
@@ -479,3 +793,28 @@ pub fn get_voter_address(key: &Key) -> Option<&Address> {
         None => None,
     }
 }
+
+/// Get the depositor address from a proposal deposit key
+pub fn get_depositor_address(key: &Key) -> Option<&Address> {
+    match key.get_at(4) {
+        Some(DbKeySeg::AddressSeg(res)) => Some(res),
+        _ => None,
+    }
+}
+
+/// Get the hash of an offline proposal from one of its keys or from a pending
+/// tally key
+pub fn get_offline_proposal_hash(key: &Key) -> Option<Hash> {
+    match key.get_at(2) {
+        Some(DbKeySeg::StringSeg(hash)) => hash.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Get the voter address from an offline vote key
+pub fn get_offline_voter_address(key: &Key) -> Option<&Address> {
+    match key.get_at(4) {
+        Some(DbKeySeg::AddressSeg(res)) => Some(res),
+        _ => None,
+    }
+}
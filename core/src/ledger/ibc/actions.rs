@@ -2,6 +2,7 @@
 
 use std::str::FromStr;
 
+use borsh::{BorshDeserialize, BorshSerialize};
 use sha2::Digest;
 use thiserror::Error;
 
@@ -69,21 +70,29 @@ use crate::ibc::events::IbcEvent;
 use crate::ibc::mock::client_state::{MockClientState, MockConsensusState};
 use crate::ibc::timestamp::Timestamp;
 use crate::ledger::ibc::data::{
-    Error as IbcDataError, FungibleTokenPacketData, IbcMessage, PacketAck,
-    PacketReceipt,
+    Error as IbcDataError, FungibleTokenPacketData, IbcMessage,
+    InterchainAccountMsg, InterchainAccountPacketData,
+    InterchainAccountPacketType, MsgSendIcaTx, PacketAck, PacketReceipt,
 };
 use crate::ledger::ibc::storage;
 use crate::ledger::storage_api;
 use crate::tendermint::Time;
 use crate::tendermint_proto::{Error as ProtoError, Protobuf};
 use crate::types::address::{Address, InternalAddress};
-use crate::types::ibc::IbcEvent as NamadaIbcEvent;
-use crate::types::storage::{BlockHeight, Key};
+use crate::types::ibc::{IbcEvent as NamadaIbcEvent, IbcFlow, IbcFlowLimit};
+use crate::types::storage::{BlockHeight, Epoch, Key};
 use crate::types::time::Rfc3339String;
 use crate::types::token::{self, Amount};
 
 const COMMITMENT_PREFIX: &[u8] = b"ibc";
 
+/// The port ID of the interchain account host
+pub const ICA_HOST_PORT_ID: &str = "icahost";
+/// The prefix of the port IDs of interchain account controllers
+pub const ICA_CONTROLLER_PORT_PREFIX: &str = "icacontroller-";
+/// The channel version for interchain accounts
+pub const ICA_VERSION: &str = "ics27-1";
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum Error {
@@ -117,6 +126,10 @@ pub enum Error {
     ReceivingToken(String),
     #[error("IBC storage error: {0}")]
     IbcStorage(storage::Error),
+    #[error("Flow limit error: {0}")]
+    FlowLimit(String),
+    #[error("Interchain account error: {0}")]
+    InterchainAccount(String),
 }
 
 // This is needed to use `ibc::Handler::Error` with `IbcActions` in
@@ -176,11 +189,34 @@ pub trait IbcActions {
         &self,
     ) -> std::result::Result<Rfc3339String, Self::Error>;
 
+    /// Get the current epoch of this chain
+    fn get_epoch(&self) -> std::result::Result<Epoch, Self::Error>;
+
+    /// Check that the messages can be executed in order on the interchain
+    /// account hosted by this chain, without writing anything
+    fn check_ica_msgs(
+        &self,
+        account: &Address,
+        msgs: &[InterchainAccountMsg],
+    ) -> std::result::Result<bool, Self::Error>;
+
+    /// Execute a message on the interchain account hosted by this chain
+    fn execute_ica_msg(
+        &mut self,
+        account: &Address,
+        msg: &InterchainAccountMsg,
+    ) -> std::result::Result<(), Self::Error>;
+
     /// dispatch according to ICS26 routing
     fn dispatch_ibc_action(
         &mut self,
         tx_data: &[u8],
     ) -> std::result::Result<(), Self::Error> {
+        if let Some(msg) =
+            MsgSendIcaTx::try_from_tx_data(tx_data).map_err(Error::IbcData)?
+        {
+            return self.send_ica_tx(&msg);
+        }
         let ibc_msg = IbcMessage::decode(tx_data).map_err(Error::IbcData)?;
         match &ibc_msg.0 {
             Ics26Envelope::Ics2Msg(ics02_msg) => match ics02_msg {
@@ -461,6 +497,13 @@ pub trait IbcActions {
         let counter_key = storage::channel_counter_key();
         let counter = self.get_and_inc_counter(&counter_key)?;
         let channel_id = channel_id(counter);
+        if ica_owner(&msg.port_id).is_some() {
+            // the controller of an interchain account
+            validate_ica_channel(&msg.channel)?;
+            let conn_id = get_connection_id_from_channel(&msg.channel)?;
+            let ica_key = storage::ica_channel_key(conn_id, &msg.port_id);
+            self.write_ibc_data(&ica_key, channel_id.to_string())?;
+        }
         let port_channel_id = port_channel_id(msg.port_id.clone(), channel_id);
         let channel_key = storage::channel_key(&port_channel_id);
         self.write_ibc_data(
@@ -485,6 +528,22 @@ pub trait IbcActions {
         let counter_key = storage::channel_counter_key();
         let counter = self.get_and_inc_counter(&counter_key)?;
         let channel_id = channel_id(counter);
+        if msg.port_id.as_str() == ICA_HOST_PORT_ID {
+            // the host of an interchain account
+            validate_ica_channel(&msg.channel)?;
+            let controller_port_id = msg.channel.counterparty().port_id();
+            if !is_ica_controller_port(controller_port_id) {
+                return Err(Error::InterchainAccount(format!(
+                    "The counterparty port isn't an interchain account \
+                     controller port: Port {}",
+                    controller_port_id
+                ))
+                .into());
+            }
+            let conn_id = get_connection_id_from_channel(&msg.channel)?;
+            let ica_key = storage::ica_channel_key(conn_id, controller_port_id);
+            self.write_ibc_data(&ica_key, channel_id.to_string())?;
+        }
         let port_channel_id = port_channel_id(msg.port_id.clone(), channel_id);
         let channel_key = storage::channel_key(&port_channel_id);
         self.write_ibc_data(
@@ -677,7 +736,9 @@ pub trait IbcActions {
     ) -> std::result::Result<(), Self::Error> {
         // check the packet data
         let packet_ack =
-            if let Ok(data) = serde_json::from_slice(&msg.packet.data) {
+            if msg.packet.destination_port.as_str() == ICA_HOST_PORT_ID {
+                self.receive_ica_packet(&msg.packet)?
+            } else if let Ok(data) = serde_json::from_slice(&msg.packet.data) {
                 match self.receive_token(&msg.packet, &data) {
                     Ok(_) => PacketAck::result_success(),
                     Err(_) => PacketAck::result_error(
@@ -1015,6 +1076,13 @@ pub trait IbcActions {
             );
             (src, escrow)
         };
+        self.record_flow(
+            &msg.source_port,
+            &msg.source_channel,
+            &data.denom,
+            amount,
+            FlowDirection::Out,
+        )?;
         self.transfer_token(&source, &target, amount)?;
 
         // send a packet
@@ -1057,7 +1125,9 @@ pub trait IbcActions {
             packet.source_port.clone(),
             packet.source_channel.clone()
         );
-        let (source, target) = match data.denom.strip_prefix(&prefix) {
+        // the token is minted when the sender chain is the source
+        let is_minted = !data.denom.starts_with(&prefix);
+        let (source, target, denom) = match data.denom.strip_prefix(&prefix) {
             Some(denom) => {
                 // unescrow the token because this chain was the source
                 let escrow_prefix = storage::ibc_account_prefix(
@@ -1076,7 +1146,7 @@ pub trait IbcActions {
                         .map_err(Error::IbcStorage)?;
                     token::multitoken_balance_key(&key_prefix, &dest_addr)
                 };
-                (escrow, dest)
+                (escrow, dest, denom.to_string())
             }
             None => {
                 // mint the token because the sender chain is the source
@@ -1102,14 +1172,28 @@ pub trait IbcActions {
                 let dest =
                     token::multitoken_balance_key(&key_prefix, &dest_addr);
 
-                // store the prefixed denom
-                let token_hash = storage::calc_hash(&denom);
-                let denom_key = storage::ibc_denom_key(token_hash);
-                self.write_ibc_data(&denom_key, denom.as_bytes())?;
-
-                (mint, dest)
+                (mint, dest, denom)
             }
         };
+        // check the flow limit before any write so that a packet over the
+        // limit is only acknowledged with an error
+        let (flow_key, flow) = self.next_flow(
+            &packet.destination_port,
+            &packet.destination_channel,
+            &denom,
+            amount,
+            FlowDirection::In,
+        )?;
+        if is_minted {
+            // store the prefixed denom
+            let token_hash = storage::calc_hash(&denom);
+            let denom_key = storage::ibc_denom_key(token_hash);
+            self.write_ibc_data(&denom_key, denom.as_bytes())?;
+        }
+        self.write_ibc_data(
+            &flow_key,
+            flow.try_to_vec().expect("encoding shouldn't fail"),
+        )?;
         self.transfer_token(&source, &target, amount)?;
 
         Ok(())
@@ -1177,10 +1261,232 @@ pub trait IbcActions {
             );
             (escrow, dest)
         };
+        self.refund_flow(
+            &packet.source_port,
+            &packet.source_channel,
+            &data.denom,
+            amount,
+        )?;
         self.transfer_token(&source, &target, amount)?;
 
         Ok(())
     }
+
+    /// Send a transaction to the interchain account of the owner over the
+    /// active channel of the controller port
+    fn send_ica_tx(
+        &mut self,
+        msg: &MsgSendIcaTx,
+    ) -> std::result::Result<(), Self::Error> {
+        let port_id = ica_controller_port_id(&msg.owner)?;
+        let conn_id = ConnectionId::from_str(&msg.connection_id)
+            .map_err(|e| Error::InterchainAccount(e.to_string()))?;
+        let ica_key = storage::ica_channel_key(&conn_id, &port_id);
+        let channel_id = self.read_ibc_data(&ica_key)?.ok_or_else(|| {
+            Error::InterchainAccount(format!(
+                "No active channel for the interchain account: Port {}, \
+                 Connection {}",
+                port_id, conn_id
+            ))
+        })?;
+        let channel_id = std::str::from_utf8(&channel_id)
+            .ok()
+            .and_then(|id| ChannelId::from_str(id).ok())
+            .ok_or_else(|| {
+                Error::InterchainAccount(format!(
+                    "Invalid active channel: Key {}",
+                    ica_key
+                ))
+            })?;
+
+        let timeout_height = Height::new(
+            msg.timeout_revision_number,
+            msg.timeout_revision_height,
+        );
+        let timeout_timestamp =
+            Timestamp::from_nanoseconds(msg.timeout_timestamp)
+                .map_err(|e| Error::InterchainAccount(e.to_string()))?;
+        let packet_data = serde_json::to_vec(&msg.packet_data())
+            .expect("encoding the packet data shouldn't fail");
+        self.send_packet(
+            port_channel_id(port_id, channel_id),
+            packet_data,
+            timeout_height,
+            timeout_timestamp,
+        )
+    }
+
+    /// Receive a packet for an interchain account hosted by this chain and
+    /// execute the messages in it. The messages are checked before executing
+    /// any of them, so that the account is left untouched and an error
+    /// acknowledgement is written when any message would fail.
+    fn receive_ica_packet(
+        &mut self,
+        packet: &Packet,
+    ) -> std::result::Result<PacketAck, Self::Error> {
+        let data: InterchainAccountPacketData =
+            match serde_json::from_slice(&packet.data) {
+                Ok(data) => data,
+                Err(_) => {
+                    return Ok(PacketAck::result_error(
+                        "unknown packet data".to_string(),
+                    ));
+                }
+            };
+        if data.packet_type != InterchainAccountPacketType::ExecuteTx {
+            return Ok(PacketAck::result_error(
+                "unsupported packet type".to_string(),
+            ));
+        }
+        let msgs = match data.decode_msgs() {
+            Ok(msgs) => msgs,
+            Err(_) => {
+                return Ok(PacketAck::result_error(
+                    "invalid transaction data".to_string(),
+                ));
+            }
+        };
+
+        let port_channel_id = port_channel_id(
+            packet.destination_port.clone(),
+            packet.destination_channel,
+        );
+        let channel_key = storage::channel_key(&port_channel_id);
+        let value = self.read_ibc_data(&channel_key)?.ok_or_else(|| {
+            Error::Channel(format!(
+                "The channel doesn't exist: Port/Channel {}",
+                port_channel_id
+            ))
+        })?;
+        let channel =
+            ChannelEnd::decode_vec(&value).map_err(Error::Decoding)?;
+        let conn_id = get_connection_id_from_channel(&channel)?;
+        let account = interchain_account_address(conn_id, &packet.source_port);
+        if !self.check_ica_msgs(&account, &msgs)? {
+            return Ok(PacketAck::result_error(
+                "executing the messages failed".to_string(),
+            ));
+        }
+        for msg in &msgs {
+            self.execute_ica_msg(&account, msg)?;
+        }
+
+        Ok(PacketAck::result_success())
+    }
+
+    /// Accumulate the token flow of the denom on the channel in the current
+    /// epoch. Returns an error when the flow would exceed the limit.
+    fn record_flow(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        denom: &str,
+        amount: Amount,
+        direction: FlowDirection,
+    ) -> std::result::Result<(), Self::Error> {
+        let (flow_key, flow) =
+            self.next_flow(port_id, channel_id, denom, amount, direction)?;
+        self.write_ibc_data(
+            &flow_key,
+            flow.try_to_vec().expect("encoding shouldn't fail"),
+        )?;
+        Ok(())
+    }
+
+    /// Subtract the refunded amount of a sent packet from the outflow of the
+    /// denom on the channel in the current epoch
+    fn refund_flow(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        denom: &str,
+        amount: Amount,
+    ) -> std::result::Result<(), Self::Error> {
+        let token_hash = storage::calc_hash(denom);
+        let flow_key = storage::flow_key(port_id, channel_id, &token_hash);
+        let flow = self
+            .read_flow(&flow_key)?
+            .at_epoch(self.get_epoch()?)
+            .sub_outflow(amount);
+        self.write_ibc_data(
+            &flow_key,
+            flow.try_to_vec().expect("encoding shouldn't fail"),
+        )?;
+        Ok(())
+    }
+
+    /// Read the token flow at the given key
+    fn read_flow(
+        &self,
+        flow_key: &Key,
+    ) -> std::result::Result<IbcFlow, Self::Error> {
+        match self.read_ibc_data(flow_key)? {
+            Some(v) => IbcFlow::try_from_slice(&v).map_err(|e| {
+                Error::FlowLimit(format!(
+                    "Decoding the flow failed: Key {}, error {}",
+                    flow_key, e
+                ))
+                .into()
+            }),
+            None => Ok(IbcFlow::default()),
+        }
+    }
+
+    /// Get the key and the token flow of the denom on the channel in the
+    /// current epoch after adding the amount, without writing it. Returns an
+    /// error when the flow would exceed the limit.
+    fn next_flow(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        denom: &str,
+        amount: Amount,
+        direction: FlowDirection,
+    ) -> std::result::Result<(Key, IbcFlow), Self::Error> {
+        let token_hash = storage::calc_hash(denom);
+        let limit_key =
+            storage::flow_limit_key(port_id, channel_id, &token_hash);
+        let limit = match self.read_ibc_data(&limit_key)? {
+            Some(v) => IbcFlowLimit::try_from_slice(&v).map_err(|e| {
+                Error::FlowLimit(format!(
+                    "Decoding the flow limit failed: Key {}, error {}",
+                    limit_key, e
+                ))
+            })?,
+            None => IbcFlowLimit::default(),
+        };
+        let flow_key = storage::flow_key(port_id, channel_id, &token_hash);
+        let flow = self.read_flow(&flow_key)?.at_epoch(self.get_epoch()?);
+        let flow = match direction {
+            FlowDirection::In => flow.checked_add_inflow(amount, &limit),
+            FlowDirection::Out => flow.checked_add_outflow(amount, &limit),
+        }
+        .ok_or_else(|| {
+            Error::FlowLimit(format!(
+                "The {} of {} on {}/{} would exceed the limit: amount {}",
+                direction, denom, port_id, channel_id, amount
+            ))
+        })?;
+        Ok((flow_key, flow))
+    }
+}
+
+/// The direction of a token flow over a channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowDirection {
+    /// Tokens received from the counterparty chain
+    In,
+    /// Tokens sent to the counterparty chain
+    Out,
+}
+
+impl std::fmt::Display for FlowDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::In => write!(f, "inflow"),
+            Self::Out => write!(f, "outflow"),
+        }
+    }
 }
 
 /// Update a client with the given state and headers
@@ -1265,6 +1571,53 @@ pub fn close_channel(channel: &mut ChannelEnd) {
     channel.set_state(ChanState::Closed);
 }
 
+/// Returns the controller port ID of the interchain account of the owner
+pub fn ica_controller_port_id(owner: &Address) -> Result<PortId> {
+    port_id(&format!("{}{}", ICA_CONTROLLER_PORT_PREFIX, owner.encode()))
+}
+
+/// Check if the given port is an interchain account controller port
+pub fn is_ica_controller_port(port_id: &PortId) -> bool {
+    port_id.as_str().starts_with(ICA_CONTROLLER_PORT_PREFIX)
+}
+
+/// Returns the owner of the interchain account if the given port is an
+/// interchain account controller port of this chain
+pub fn ica_owner(port_id: &PortId) -> Option<Address> {
+    port_id
+        .as_str()
+        .strip_prefix(ICA_CONTROLLER_PORT_PREFIX)
+        .and_then(|owner| Address::decode(owner).ok())
+}
+
+/// Returns the address of the interchain account hosted by this chain for
+/// the controller port over the connection
+pub fn interchain_account_address(
+    connection_id: &ConnectionId,
+    controller_port_id: &PortId,
+) -> Address {
+    Address::Internal(InternalAddress::interchain_account_address(
+        connection_id.to_string(),
+        controller_port_id.to_string(),
+    ))
+}
+
+/// Check the ordering and the version of a channel for interchain accounts
+pub fn validate_ica_channel(channel: &ChannelEnd) -> Result<()> {
+    if !channel.order_matches(&Order::Ordered) {
+        return Err(Error::InterchainAccount(
+            "The channel for interchain accounts should be ordered".to_owned(),
+        ));
+    }
+    if channel.version().to_string() != ICA_VERSION {
+        return Err(Error::InterchainAccount(format!(
+            "Invalid channel version for interchain accounts: version {}",
+            channel.version()
+        )));
+    }
+    Ok(())
+}
+
 /// Returns a port ID
 pub fn port_id(id: &str) -> Result<PortId> {
     PortId::from_str(id).map_err(Error::PortId)
@@ -1307,6 +1660,33 @@ pub fn packet_from_message(
     }
 }
 
+/// Make a packet from MsgSendIcaTx
+pub fn packet_from_ica_message(
+    msg: &MsgSendIcaTx,
+    port_channel_id: &PortChannelId,
+    sequence: Sequence,
+    counterparty: &ChanCounterparty,
+) -> Result<Packet> {
+    let timeout_timestamp = Timestamp::from_nanoseconds(msg.timeout_timestamp)
+        .map_err(|e| Error::InterchainAccount(e.to_string()))?;
+    Ok(Packet {
+        sequence,
+        source_port: port_channel_id.port_id.clone(),
+        source_channel: port_channel_id.channel_id,
+        destination_port: counterparty.port_id.clone(),
+        destination_channel: *counterparty
+            .channel_id()
+            .expect("the counterparty channel should exist"),
+        data: serde_json::to_vec(&msg.packet_data())
+            .expect("encoding the packet data shouldn't fail"),
+        timeout_height: Height::new(
+            msg.timeout_revision_number,
+            msg.timeout_revision_height,
+        ),
+        timeout_timestamp,
+    })
+}
+
 /// Returns a commitment from the given packet
 pub fn commitment(packet: &Packet) -> PacketCommitment {
     let timeout = packet.timeout_timestamp.nanoseconds().to_be_bytes();
@@ -1560,7 +1940,8 @@ pub fn make_close_confirm_channel_event(
     Ok(attributes.into())
 }
 
-fn get_connection_id_from_channel(
+/// Returns the connection ID of the channel
+pub fn get_connection_id_from_channel(
     channel: &ChannelEnd,
 ) -> Result<&ConnectionId> {
     channel.connection_hops().get(0).ok_or_else(|| {
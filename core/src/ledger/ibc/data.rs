@@ -2,6 +2,7 @@
 use std::convert::TryFrom;
 use std::fmt::{self, Display, Formatter};
 
+use borsh::{BorshDeserialize, BorshSerialize};
 use prost::Message;
 use thiserror::Error;
 
@@ -34,6 +35,9 @@ use crate::ibc::core::ics26_routing::error::Error as Ics26Error;
 use crate::ibc::core::ics26_routing::msgs::Ics26Envelope;
 use crate::ibc::downcast;
 use crate::ibc_proto::google::protobuf::Any;
+use crate::types::address::Address;
+use crate::types::storage::Key;
+use crate::types::token::Amount;
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -46,6 +50,10 @@ pub enum Error {
     DecodingMessage(Ics26Error),
     #[error("Downcast error: {0}")]
     Downcast(String),
+    #[error("Decoding Borsh data error: {0}")]
+    DecodingBorshData(std::io::Error),
+    #[error("Decoding Base64 data error: {0}")]
+    DecodingBase64Data(data_encoding::DecodeError),
 }
 
 /// Decode result for IBC data
@@ -418,3 +426,136 @@ impl Display for FungibleTokenPacketData {
         write!(f, "{}", serde_json::to_string(self).unwrap())
     }
 }
+
+/// Type URL of [`MsgSendIcaTx`]
+pub const MSG_SEND_ICA_TX_TYPE_URL: &str = "/namada.ibc.MsgSendIcaTx";
+
+/// A message to send a transaction to the interchain account of the owner on
+/// the host chain
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct MsgSendIcaTx {
+    /// The owner controlling the interchain account
+    pub owner: Address,
+    /// The connection ID on this chain to the host chain
+    pub connection_id: String,
+    /// The transaction data to be executed on the host chain. It is opaque
+    /// to this chain, e.g. an encoded `CosmosTx` for a Cosmos SDK chain or
+    /// encoded [`InterchainAccountMsg`]s for a Namada chain.
+    pub data: Vec<u8>,
+    /// The memo of the packet
+    pub memo: String,
+    /// The revision number of the timeout height
+    pub timeout_revision_number: u64,
+    /// The revision height of the timeout height (0 for no timeout height)
+    pub timeout_revision_height: u64,
+    /// The timeout timestamp in nanoseconds (0 for no timeout timestamp)
+    pub timeout_timestamp: u64,
+}
+
+impl MsgSendIcaTx {
+    /// Encode the message as an `Any` to be used as transaction data
+    pub fn to_any(&self) -> Any {
+        Any {
+            type_url: MSG_SEND_ICA_TX_TYPE_URL.to_string(),
+            value: self.try_to_vec().expect("encoding shouldn't fail"),
+        }
+    }
+
+    /// Decode the message from transaction data. Returns `None` when the data
+    /// is another message.
+    pub fn try_from_tx_data(tx_data: &[u8]) -> Result<Option<Self>> {
+        let any = Any::decode(tx_data).map_err(Error::DecodingData)?;
+        if any.type_url != MSG_SEND_ICA_TX_TYPE_URL {
+            return Ok(None);
+        }
+        Self::try_from_slice(&any.value)
+            .map(Some)
+            .map_err(Error::DecodingBorshData)
+    }
+
+    /// Returns the packet data for the interchain account on the host chain
+    pub fn packet_data(&self) -> InterchainAccountPacketData {
+        InterchainAccountPacketData {
+            packet_type: InterchainAccountPacketType::ExecuteTx,
+            data: data_encoding::BASE64.encode(&self.data),
+            memo: self.memo.clone(),
+        }
+    }
+}
+
+/// Type of the interchain account packet data
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum InterchainAccountPacketType {
+    /// Unspecified type
+    #[serde(rename = "TYPE_UNSPECIFIED")]
+    Unspecified,
+    /// Execute a transaction on the interchain account
+    #[serde(rename = "TYPE_EXECUTE_TX")]
+    ExecuteTx,
+}
+
+/// Data to execute a transaction on an interchain account. This is the JSON
+/// encoding of ICS-27 `InterchainAccountPacketData`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct InterchainAccountPacketData {
+    /// the type of the packet data
+    #[serde(rename = "type")]
+    pub packet_type: InterchainAccountPacketType,
+    /// the base64 encoded transaction data
+    pub data: String,
+    /// the memo
+    #[serde(default)]
+    pub memo: String,
+}
+
+impl InterchainAccountPacketData {
+    /// Decode the messages to be executed on this chain
+    pub fn decode_msgs(&self) -> Result<Vec<InterchainAccountMsg>> {
+        let bytes = data_encoding::BASE64
+            .decode(self.data.as_bytes())
+            .map_err(Error::DecodingBase64Data)?;
+        Vec::<InterchainAccountMsg>::try_from_slice(&bytes)
+            .map_err(Error::DecodingBorshData)
+    }
+}
+
+impl Display for InterchainAccountPacketData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", serde_json::to_string(self).unwrap())
+    }
+}
+
+/// A message to be executed on an interchain account hosted by this chain
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum InterchainAccountMsg {
+    /// Transfer a token from the interchain account
+    Transfer {
+        /// The token address
+        token: Address,
+        /// The sub prefix of the token for a multitoken
+        sub_prefix: Option<Key>,
+        /// The target address
+        target: Address,
+        /// The amount to be transferred
+        amount: Amount,
+    },
+    /// Self-bond the native token of the interchain account to a validator
+    Bond {
+        /// The validator address
+        validator: Address,
+        /// The amount to be bonded
+        amount: Amount,
+    },
+    /// Unbond the native token of the interchain account from a validator
+    Unbond {
+        /// The validator address
+        validator: Address,
+        /// The amount to be unbonded
+        amount: Amount,
+    },
+    /// Withdraw the unbonded tokens of the interchain account
+    Withdraw {
+        /// The validator address
+        validator: Address,
+    },
+}
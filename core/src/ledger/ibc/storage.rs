@@ -26,6 +26,9 @@ const CHANNELS_COUNTER: &str = "channelEnds/counter";
 const CAPABILITIES_INDEX: &str = "capabilities/index";
 const CAPABILITIES: &str = "capabilities";
 const DENOM: &str = "denom";
+const FLOW_LIMIT: &str = "flowLimit";
+const FLOW: &str = "flow";
+const ICA: &str = "ica";
 /// Key segment for a multitoken related to IBC
 pub const MULTITOKEN_STORAGE_KEY: &str = "ibc";
 
@@ -61,6 +64,9 @@ pub enum IbcPrefix {
     Ack,
     Event,
     Denom,
+    FlowLimit,
+    Flow,
+    InterchainAccount,
     Unknown,
 }
 
@@ -84,6 +90,9 @@ pub fn ibc_prefix(key: &Key) -> Option<IbcPrefix> {
                 "acks" => IbcPrefix::Ack,
                 "event" => IbcPrefix::Event,
                 "denom" => IbcPrefix::Denom,
+                "flowLimit" => IbcPrefix::FlowLimit,
+                "flow" => IbcPrefix::Flow,
+                "ica" => IbcPrefix::InterchainAccount,
                 _ => IbcPrefix::Unknown,
             })
         }
@@ -290,6 +299,26 @@ pub fn ack_key(
         .expect("Creating a key for the ack shouldn't fail")
 }
 
+/// Returns a key prefix for the commitments of the channel
+pub fn packet_commitment_prefix(
+    port_id: &PortId,
+    channel_id: &ChannelId,
+) -> Key {
+    let path = format!(
+        "commitments/ports/{}/channels/{}/sequences",
+        port_id, channel_id
+    );
+    ibc_key(path)
+        .expect("Creating a key prefix of the commitment shouldn't fail")
+}
+
+/// Returns a key prefix for the acks of the channel
+pub fn packet_ack_prefix(port_id: &PortId, channel_id: &ChannelId) -> Key {
+    let path =
+        format!("acks/ports/{}/channels/{}/sequences", port_id, channel_id);
+    ibc_key(path).expect("Creating a key prefix of the ack shouldn't fail")
+}
+
 /// Returns a key for the timestamp for the client update
 pub fn client_update_timestamp_key(client_id: &ClientId) -> Key {
     let path = format!("clients/{}/update_timestamp", client_id);
@@ -497,6 +526,84 @@ pub fn ibc_denom_key(token_hash: impl AsRef<str>) -> Key {
     ibc_key(path).expect("Creating a key for the denom key shouldn't fail")
 }
 
+/// Returns a key for the flow limit of the hashed denom on the channel
+pub fn flow_limit_key(
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    token_hash: impl AsRef<str>,
+) -> Key {
+    let path = format!(
+        "{}/{}/{}/{}",
+        FLOW_LIMIT,
+        port_id,
+        channel_id,
+        token_hash.as_ref()
+    );
+    ibc_key(path).expect("Creating a key for the flow limit shouldn't fail")
+}
+
+/// Returns a key for the flows of the hashed denom on the channel
+pub fn flow_key(
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    token_hash: impl AsRef<str>,
+) -> Key {
+    let path = format!(
+        "{}/{}/{}/{}",
+        FLOW,
+        port_id,
+        channel_id,
+        token_hash.as_ref()
+    );
+    ibc_key(path).expect("Creating a key for the flow shouldn't fail")
+}
+
+/// Returns a key for the active channel of the interchain account
+/// controlled with the controller port over the connection
+pub fn ica_channel_key(
+    connection_id: &ConnectionId,
+    controller_port_id: &PortId,
+) -> Key {
+    let path = format!("{}/{}/{}", ICA, connection_id, controller_port_id);
+    ibc_key(path)
+        .expect("Creating a key for the interchain account shouldn't fail")
+}
+
+/// Returns a pair of the connection ID and the controller port ID from the
+/// given key of the active channel of an interchain account
+pub fn ica_connection_port_id(key: &Key) -> Result<(ConnectionId, PortId)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(conn_id),
+            DbKeySeg::StringSeg(port_id),
+        ] if addr == &Address::Internal(InternalAddress::Ibc)
+            && prefix == ICA =>
+        {
+            let conn_id = ConnectionId::from_str(&conn_id.raw())
+                .map_err(|e| Error::InvalidKey(e.to_string()))?;
+            let port_id = PortId::from_str(&port_id.raw())
+                .map_err(|e| Error::InvalidKey(e.to_string()))?;
+            Ok((conn_id, port_id))
+        }
+        _ => Err(Error::InvalidKey(format!(
+            "The key doesn't have an interchain account channel: Key {}",
+            key
+        ))),
+    }
+}
+
+/// Check if the given key is a key of a flow limit
+pub fn is_flow_limit_key(key: &Key) -> bool {
+    matches!(ibc_prefix(key), Some(IbcPrefix::FlowLimit))
+}
+
+/// The storage key prefix of all the denom names
+pub fn ibc_denom_prefix() -> Key {
+    ibc_key(DENOM).expect("Creating a key prefix of the denom shouldn't fail")
+}
+
 /// Key's prefix for the escrow, burn, or mint account
 pub fn ibc_account_prefix(
     port_id: &PortId,
@@ -1,10 +1,13 @@
 //! Protocol parameters
 pub mod storage;
 
+use std::collections::BTreeMap;
+
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use rust_decimal::Decimal;
 use thiserror::Error;
 
+use super::gas::GasSchedule;
 use super::storage::types;
 use super::storage_api::{self, ResultExt, StorageRead, StorageWrite};
 use crate::ledger::storage::{self as ledger_storage};
@@ -51,12 +54,18 @@ pub struct Parameters {
     pub staked_ratio: Decimal,
     /// PoS inflation amount from the last epoch (read + write for every epoch)
     pub pos_inflation_amount: u64,
+    /// The gas costs of transactions' and VPs' operations
+    pub gas_schedule: GasSchedule,
+    /// The tokens allowed for the payment of wrapper tx fees with their
+    /// minimum gas price, which is the amount of the token per
+    /// `GAS_LIMIT_RESOLUTION` units of gas
+    pub gas_prices: BTreeMap<Address, token::Amount>,
+    /// How many block heights in the past can the block headers be read by
+    /// transactions and validity predicates
+    pub storage_read_past_height_limit: u64,
     #[cfg(not(feature = "mainnet"))]
     /// Faucet account for free token withdrawal
     pub faucet_account: Option<Address>,
-    #[cfg(not(feature = "mainnet"))]
-    /// Fixed fees for a wrapper tx to be accepted
-    pub wrapper_tx_fees: Option<token::Amount>,
 }
 
 /// Epoch duration. A new epoch begins as soon as both the `min_num_of_blocks`
@@ -118,10 +127,11 @@ impl Parameters {
             pos_gain_d,
             staked_ratio,
             pos_inflation_amount,
+            gas_schedule,
+            gas_prices,
+            storage_read_past_height_limit,
             #[cfg(not(feature = "mainnet"))]
             faucet_account,
-            #[cfg(not(feature = "mainnet"))]
-            wrapper_tx_fees,
         } = self;
 
         // write max proposal bytes parameter
@@ -177,19 +187,22 @@ impl Parameters {
         let pos_inflation_key = storage::get_pos_inflation_amount_key();
         storage.write(&pos_inflation_key, pos_inflation_amount)?;
 
+        let gas_schedule_key = storage::get_gas_schedule_key();
+        storage.write(&gas_schedule_key, gas_schedule)?;
+
+        let gas_prices_key = storage::get_gas_prices_key();
+        storage.write(&gas_prices_key, gas_prices)?;
+
+        let past_height_limit_key =
+            storage::get_storage_read_past_height_limit_key();
+        storage
+            .write(&past_height_limit_key, storage_read_past_height_limit)?;
+
         #[cfg(not(feature = "mainnet"))]
         if let Some(faucet_account) = faucet_account {
             let faucet_account_key = storage::get_faucet_account_key();
             storage.write(&faucet_account_key, faucet_account)?;
         }
-
-        #[cfg(not(feature = "mainnet"))]
-        {
-            let wrapper_tx_fees_key = storage::get_wrapper_tx_fees_key();
-            let wrapper_tx_fees =
-                wrapper_tx_fees.unwrap_or(token::Amount::whole(100));
-            storage.write(&wrapper_tx_fees_key, wrapper_tx_fees)?;
-        }
         Ok(())
     }
 }
@@ -337,6 +350,30 @@ where
     storage.write_bytes(&key, implicit_vp)
 }
 
+/// Update the gas schedule parameter in storage.
+pub fn update_gas_schedule_parameter<S>(
+    storage: &mut S,
+    value: &GasSchedule,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_gas_schedule_key();
+    storage.write(&key, value)
+}
+
+/// Update the allowed fee tokens and their minimum gas prices in storage.
+pub fn update_gas_prices_parameter<S>(
+    storage: &mut S,
+    value: &BTreeMap<Address, token::Amount>,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_gas_prices_key();
+    storage.write(&key, value)
+}
+
 /// Read the the epoch duration parameter from store
 pub fn read_epoch_duration_parameter<S>(
     storage: &S,
@@ -352,6 +389,19 @@ where
         .into_storage_result()
 }
 
+/// Read the gas schedule parameter, if any. The schedule may be missing in
+/// chains initialized before it's been added, in which case the default
+/// schedule applies.
+pub fn read_gas_schedule_parameter<S>(
+    storage: &S,
+) -> storage_api::Result<Option<GasSchedule>>
+where
+    S: StorageRead,
+{
+    let gas_schedule_key = storage::get_gas_schedule_key();
+    storage.read(&gas_schedule_key)
+}
+
 #[cfg(not(feature = "mainnet"))]
 /// Read the faucet account's address, if any
 pub fn read_faucet_account_parameter<S>(
@@ -364,16 +414,30 @@ where
     storage.read(&faucet_account_key)
 }
 
-#[cfg(not(feature = "mainnet"))]
-/// Read the wrapper tx fees amount, if any
-pub fn read_wrapper_tx_fees_parameter<S>(
+/// Read the allowed fee tokens and their minimum gas prices, if any
+pub fn read_gas_prices_parameter<S>(
+    storage: &S,
+) -> storage_api::Result<Option<BTreeMap<Address, token::Amount>>>
+where
+    S: StorageRead,
+{
+    let gas_prices_key = storage::get_gas_prices_key();
+    storage.read(&gas_prices_key)
+}
+
+/// Read the limit of how many block heights in the past can the block headers
+/// be read by transactions and validity predicates
+pub fn read_storage_read_past_height_limit_parameter<S>(
     storage: &S,
-) -> storage_api::Result<Option<token::Amount>>
+) -> storage_api::Result<u64>
 where
     S: StorageRead,
 {
-    let wrapper_tx_fees_key = storage::get_wrapper_tx_fees_key();
-    storage.read(&wrapper_tx_fees_key)
+    let key = storage::get_storage_read_past_height_limit_key();
+    storage
+        .read(&key)?
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()
 }
 
 // Read the all the parameters from storage. Returns the parameters and gas
@@ -456,13 +520,22 @@ where
         .ok_or(ReadError::ParametersMissing)
         .into_storage_result()?;
 
-    // read faucet account
-    #[cfg(not(feature = "mainnet"))]
-    let faucet_account = read_faucet_account_parameter(storage)?;
+    // read gas schedule
+    let gas_schedule =
+        read_gas_schedule_parameter(storage)?.unwrap_or_default();
+
+    // read gas prices
+    let gas_prices = read_gas_prices_parameter(storage)?
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()?;
+
+    // read the past height limit of block headers
+    let storage_read_past_height_limit =
+        read_storage_read_past_height_limit_parameter(storage)?;
 
     // read faucet account
     #[cfg(not(feature = "mainnet"))]
-    let wrapper_tx_fees = read_wrapper_tx_fees_parameter(storage)?;
+    let faucet_account = read_faucet_account_parameter(storage)?;
 
     Ok(Parameters {
         epoch_duration,
@@ -476,9 +549,10 @@ where
         pos_gain_d,
         staked_ratio,
         pos_inflation_amount,
+        gas_schedule,
+        gas_prices,
+        storage_read_past_height_limit,
         #[cfg(not(feature = "mainnet"))]
         faucet_account,
-        #[cfg(not(feature = "mainnet"))]
-        wrapper_tx_fees,
     })
 }
@@ -3,6 +3,7 @@
 use namada_macros::StorageKeys;
 
 use super::ADDRESS;
+use crate::types::hash::Hash;
 use crate::types::storage::{DbKeySeg, Key};
 
 /// Storage keys for ledger parameters.
@@ -10,17 +11,20 @@ use crate::types::storage::{DbKeySeg, Key};
 struct Keys {
     epoch_duration: &'static str,
     epochs_per_year: &'static str,
+    gas_prices: &'static str,
+    gas_schedule: &'static str,
     implicit_vp: &'static str,
     max_expected_time_per_block: &'static str,
     pos_gain_d: &'static str,
     pos_gain_p: &'static str,
     pos_inflation_amount: &'static str,
     staked_ratio: &'static str,
+    storage_read_past_height_limit: &'static str,
     tx_whitelist: &'static str,
     vp_whitelist: &'static str,
     max_proposal_bytes: &'static str,
     faucet_account: &'static str,
-    wrapper_tx_fees: &'static str,
+    wasm_code: &'static str,
 }
 
 /// Returns if the key is a parameter key.
@@ -241,6 +245,16 @@ pub fn get_max_proposal_bytes_key() -> Key {
     }
 }
 
+/// Storage key used for the gas schedule parameter.
+pub fn get_gas_schedule_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.gas_schedule.to_string()),
+        ],
+    }
+}
+
 /// Storage key used for faucet account.
 pub fn get_faucet_account_key() -> Key {
     Key {
@@ -251,12 +265,51 @@ pub fn get_faucet_account_key() -> Key {
     }
 }
 
-/// Storage key used for staked ratio parameter.
-pub fn get_wrapper_tx_fees_key() -> Key {
+/// Storage key used for the allowed fee tokens and their minimum gas prices.
+pub fn get_gas_prices_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.gas_prices.to_string()),
+        ],
+    }
+}
+
+/// Storage key used for the limit of how many block heights in the past can
+/// the block headers be read by transactions and validity predicates.
+pub fn get_storage_read_past_height_limit_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(
+                Keys::VALUES.storage_read_past_height_limit.to_string(),
+            ),
+        ],
+    }
+}
+
+/// Storage key of a wasm code stored on-chain under the SHA-256 hash of the
+/// code. Transactions can refer to the code by this hash.
+pub fn get_wasm_code_key(code_hash: &Hash) -> Key {
     Key {
         segments: vec![
             DbKeySeg::AddressSeg(ADDRESS),
-            DbKeySeg::StringSeg(Keys::VALUES.wrapper_tx_fees.to_string()),
+            DbKeySeg::StringSeg(Keys::VALUES.wasm_code.to_string()),
+            DbKeySeg::StringSeg(code_hash.to_string()),
         ],
     }
 }
+
+/// Returns the hash of the wasm code if the key is a wasm code key.
+pub fn get_wasm_code_hash(key: &Key) -> Option<Hash> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(wasm_code),
+            DbKeySeg::StringSeg(code_hash),
+        ] if addr == &ADDRESS && wasm_code == Keys::VALUES.wasm_code => {
+            Hash::try_from(code_hash.as_str()).ok()
+        }
+        _ => None,
+    }
+}
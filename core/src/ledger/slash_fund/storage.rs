@@ -1,8 +1,85 @@
 //! Slash fund storage
 
-use crate::types::storage::{DbKeySeg, Key};
+use crate::types::storage::{DbKeySeg, Key, KeySeg};
+
+const PENDING_DISBURSEMENT: &str = "pending";
+const EXECUTED_DISBURSEMENT: &str = "executed";
 
 /// Check if a key is a slash fund key
 pub fn is_slash_fund_key(key: &Key) -> bool {
     matches!(&key.segments[0], DbKeySeg::AddressSeg(addr) if addr == &super::ADDRESS)
 }
+
+/// Check if a key is the key of a pending disbursement
+pub fn is_pending_disbursement_key(key: &Key) -> bool {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), id, index]
+            if addr == &super::ADDRESS && prefix == PENDING_DISBURSEMENT =>
+        {
+            [id, index].into_iter().all(|seg| {
+                matches!(seg, DbKeySeg::StringSeg(seg) if seg.parse::<u64>().is_ok())
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Get the prefix of the pending disbursements
+pub fn get_pending_disbursements_prefix() -> Key {
+    Key::from(super::ADDRESS.to_db_key())
+        .push(&PENDING_DISBURSEMENT.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the prefix of the pending disbursements of a proposal
+pub fn get_proposal_pending_disbursements_prefix(proposal_id: u64) -> Key {
+    get_pending_disbursements_prefix()
+        .push(&proposal_id.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of a pending disbursement of a proposal
+pub fn get_pending_disbursement_key(proposal_id: u64, index: u64) -> Key {
+    get_proposal_pending_disbursements_prefix(proposal_id)
+        .push(&index.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the prefix of the payments of the executed disbursements
+pub fn get_executed_disbursements_prefix() -> Key {
+    Key::from(super::ADDRESS.to_db_key())
+        .push(&EXECUTED_DISBURSEMENT.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the prefix of the payments of the disbursements of a proposal
+pub fn get_proposal_executed_disbursements_prefix(proposal_id: u64) -> Key {
+    get_executed_disbursements_prefix()
+        .push(&proposal_id.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of a payment of a disbursement made in the given epoch
+pub fn get_executed_disbursement_key(
+    proposal_id: u64,
+    index: u64,
+    epoch: u64,
+) -> Key {
+    get_proposal_executed_disbursements_prefix(proposal_id)
+        .push(&index.to_string())
+        .expect("Cannot obtain a storage key")
+        .push(&epoch.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the proposal id and the index of the disbursement from a pending
+/// disbursement key
+pub fn get_pending_disbursement_ids(key: &Key) -> Option<(u64, u64)> {
+    match (key.get_at(2), key.get_at(3)) {
+        (
+            Some(DbKeySeg::StringSeg(proposal_id)),
+            Some(DbKeySeg::StringSeg(index)),
+        ) => Some((proposal_id.parse().ok()?, index.parse().ok()?)),
+        _ => None,
+    }
+}
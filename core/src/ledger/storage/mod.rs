@@ -71,6 +71,10 @@ where
     /// going to be committed. After a block is committed, this is reset to
     /// `None` until the next `FinalizeBlock` phase is reached.
     pub header: Option<Header>,
+    /// During `FinalizeBlock`, this is the address of the validator that
+    /// proposed the block that is going to be committed, if it can be found.
+    /// After a block is committed, this is reset to `None`.
+    pub block_proposer: Option<Address>,
     /// The height of the most recently committed block, or `BlockHeight(0)` if
     /// no block has been committed for this chain yet.
     pub last_height: BlockHeight,
@@ -358,6 +362,7 @@ where
             chain_id,
             block,
             header: None,
+            block_proposer: None,
             last_height: BlockHeight(0),
             last_epoch: Epoch::default(),
             next_epoch_min_start_height: BlockHeight::default(),
@@ -465,6 +470,7 @@ where
         self.last_height = self.block.height;
         self.last_epoch = self.block.epoch;
         self.header = None;
+        self.block_proposer = None;
         if is_full_commit {
             // prune old merkle tree stores
             self.prune_merkle_tree_stores()?;
@@ -796,6 +802,36 @@ where
         }
     }
 
+    /// Get the header of the block at the given height. Only the headers of
+    /// the current block and of the past blocks within the
+    /// `storage_read_past_height_limit` protocol parameter can be read.
+    pub fn get_past_block_header(
+        &self,
+        height: BlockHeight,
+    ) -> Result<(Option<Header>, u64)> {
+        let current_height = self.get_block_height().0;
+        // This must not depend on the node's configuration, because the result
+        // may be used by transactions and VPs
+        let limit_key =
+            parameters::storage::get_storage_read_past_height_limit_key();
+        let (limit, limit_gas) = self.read(&limit_key)?;
+        let limit: u64 = match limit {
+            Some(limit) => types::decode(limit).map_err(Error::CodingError)?,
+            None => 0,
+        };
+        let is_out_of_limit = height.0.saturating_add(limit) < current_height.0;
+        if height > current_height || is_out_of_limit {
+            return Ok((None, limit_gas + MIN_STORAGE_GAS));
+        }
+        let (header, gas) = self.get_block_header(Some(height))?;
+        Ok((header, limit_gas + gas))
+    }
+
+    /// Get the address of the proposer of the current block, if it's known
+    pub fn get_block_proposer(&self) -> (Option<Address>, u64) {
+        (self.block_proposer.clone(), MIN_STORAGE_GAS)
+    }
+
     /// Get the current conversions
     pub fn get_conversion_state(&self) -> &ConversionState {
         &self.conversion_state
@@ -938,6 +974,7 @@ pub mod testing {
                 chain_id,
                 block,
                 header: None,
+                block_proposer: None,
                 last_height: BlockHeight(0),
                 last_epoch: Epoch::default(),
                 next_epoch_min_start_height: BlockHeight::default(),
@@ -1058,10 +1095,11 @@ mod tests {
                 pos_gain_d: dec!(0.1),
                 staked_ratio: dec!(0.1),
                 pos_inflation_amount: 0,
+                gas_schedule: Default::default(),
+                gas_prices: Default::default(),
+                storage_read_past_height_limit: 1000,
                 #[cfg(not(feature = "mainnet"))]
                 faucet_account: None,
-                #[cfg(not(feature = "mainnet"))]
-                wrapper_tx_fees: None,
             };
             parameters.init_storage(&mut wl_storage).unwrap();
 
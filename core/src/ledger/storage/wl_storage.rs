@@ -319,6 +319,16 @@ where
         Ok(self.storage.block.epoch)
     }
 
+    fn get_block_header(
+        &self,
+        height: BlockHeight,
+    ) -> std::result::Result<Option<storage::Header>, storage_api::Error> {
+        self.storage
+            .get_past_block_header(height)
+            .map(|(header, _gas)| header)
+            .into_storage_result()
+    }
+
     fn get_tx_index(
         &self,
     ) -> std::result::Result<storage::TxIndex, storage_api::Error> {
@@ -9,6 +9,7 @@ use thiserror::Error;
 use crate::ledger;
 use crate::ledger::storage::{Storage, StorageHasher};
 use crate::types::address::{Address, EstablishedAddressGen};
+use crate::types::event::TxEvent;
 use crate::types::ibc::IbcEvent;
 use crate::types::storage;
 
@@ -70,6 +71,12 @@ pub struct WriteLog {
     tx_write_log: HashMap<storage::Key, StorageModification>,
     /// The IBC event for the current transaction
     ibc_event: Option<IbcEvent>,
+    /// The custom events emitted by the current transaction
+    events: Vec<TxEvent>,
+    /// When applying a batch transaction, the block write log modifications
+    /// prior to the batch of the keys committed by its calls, used to drop
+    /// the whole batch
+    batch_undo_log: Option<HashMap<storage::Key, Option<StorageModification>>>,
 }
 
 /// Write log prefix iterator
@@ -95,6 +102,8 @@ impl Default for WriteLog {
             block_write_log: HashMap::with_capacity(100_000),
             tx_write_log: HashMap::with_capacity(100),
             ibc_event: None,
+            events: Vec::new(),
+            batch_undo_log: None,
         }
     }
 }
@@ -338,6 +347,14 @@ impl WriteLog {
         len as _
     }
 
+    /// Append a custom event of the current transaction and return the gas
+    /// cost.
+    pub fn emit_event(&mut self, event: TxEvent) -> u64 {
+        let gas = event.gas_cost();
+        self.events.push(event);
+        gas
+    }
+
     /// Get the storage keys changed and accounts keys initialized in the
     /// current transaction. The account keys point to the validity predicates
     /// of the newly created accounts.
@@ -390,6 +407,17 @@ impl WriteLog {
         self.ibc_event.as_ref()
     }
 
+    /// Take the custom events of the current transaction in the order of
+    /// their emission
+    pub fn take_events(&mut self) -> Vec<TxEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Get the custom events of the current transaction
+    pub fn get_events(&self) -> &[TxEvent] {
+        &self.events
+    }
+
     /// Commit the current transaction's write log to the block when it's
     /// accepted by all the triggered validity predicates. Starts a new
     /// transaction write log.
@@ -401,14 +429,56 @@ impl WriteLog {
             &mut self.tx_write_log,
             HashMap::with_capacity(100),
         );
+        if let Some(batch_undo_log) = self.batch_undo_log.as_mut() {
+            for key in tx_write_log.keys() {
+                batch_undo_log
+                    .entry(key.clone())
+                    .or_insert_with(|| self.block_write_log.get(key).cloned());
+            }
+        }
         self.block_write_log.extend(tx_write_log);
         self.take_ibc_event();
+        self.events.clear();
     }
 
     /// Drop the current transaction's write log when it's declined by any of
     /// the triggered validity predicates. Starts a new transaction write log.
     pub fn drop_tx(&mut self) {
         self.tx_write_log.clear();
+        self.events.clear();
+    }
+
+    /// Start applying a batch transaction. The write log of each of its calls
+    /// is committed to the block with [`WriteLog::commit_tx`] when it's
+    /// accepted, so that the next calls can read it, but the calls can still
+    /// be dropped all together with [`WriteLog::drop_batch`] until the batch
+    /// is committed with [`WriteLog::commit_batch`].
+    pub fn begin_batch(&mut self) {
+        self.batch_undo_log = Some(HashMap::new());
+    }
+
+    /// Commit the calls of the current batch transaction when all of them
+    /// have been accepted.
+    pub fn commit_batch(&mut self) {
+        self.batch_undo_log = None;
+    }
+
+    /// Drop the calls of the current batch transaction, including those that
+    /// have already been committed, when any of them is declined.
+    pub fn drop_batch(&mut self) {
+        self.drop_tx();
+        if let Some(batch_undo_log) = self.batch_undo_log.take() {
+            for (key, prev) in batch_undo_log {
+                match prev {
+                    Some(prev) => {
+                        self.block_write_log.insert(key, prev);
+                    }
+                    None => {
+                        self.block_write_log.remove(&key);
+                    }
+                }
+            }
+        }
     }
 
     /// Commit the current block's write log to the storage. Starts a new block
@@ -723,6 +793,83 @@ mod tests {
         assert_eq!(value, None);
     }
 
+    #[test]
+    fn test_batch() {
+        let mut write_log = WriteLog::default();
+
+        let key1 =
+            storage::Key::parse("key1").expect("cannot parse the key string");
+        let key2 =
+            storage::Key::parse("key2").expect("cannot parse the key string");
+        let val1 = "val1".as_bytes().to_vec();
+        let val2 = "val2".as_bytes().to_vec();
+        write_log.write(&key1, val1.clone()).unwrap();
+        write_log.commit_tx();
+
+        // the calls of a dropped batch are dropped even if committed
+        write_log.begin_batch();
+        write_log.write(&key1, val2.clone()).unwrap();
+        write_log.commit_tx();
+        write_log.write(&key2, val2.clone()).unwrap();
+        write_log.commit_tx();
+        // the committed calls are visible to the next calls
+        let (value, _) = write_log.read_pre(&key1);
+        assert_matches!(
+            value,
+            Some(StorageModification::Write { value }) if value == &val2
+        );
+        write_log.write(&key2, val1.clone()).unwrap();
+        write_log.drop_batch();
+
+        let (value, _) = write_log.read(&key1);
+        assert_matches!(
+            value,
+            Some(StorageModification::Write { value }) if value == &val1
+        );
+        let (value, _) = write_log.read(&key2);
+        assert!(value.is_none());
+
+        // the calls of a committed batch are kept
+        write_log.begin_batch();
+        write_log.write(&key1, val2.clone()).unwrap();
+        write_log.commit_tx();
+        write_log.write(&key2, val2.clone()).unwrap();
+        write_log.commit_tx();
+        write_log.commit_batch();
+
+        let (value, _) = write_log.read(&key1);
+        assert_matches!(
+            value,
+            Some(StorageModification::Write { value }) if value == &val2
+        );
+        let (value, _) = write_log.read(&key2);
+        assert_matches!(
+            value,
+            Some(StorageModification::Write { value }) if value == &val2
+        );
+    }
+
+    #[test]
+    fn test_events() {
+        let mut write_log = WriteLog::default();
+
+        let event1 = TxEvent::new("event1").with_attribute("key", "value");
+        let gas = write_log.emit_event(event1.clone());
+        assert_eq!(gas, ("event1".len() + "key".len() + "value".len()) as u64);
+        let event2 = TxEvent::new("event2");
+        write_log.emit_event(event2.clone());
+        assert_eq!(write_log.get_events(), &[event1.clone(), event2.clone()]);
+
+        // the events are dropped with the tx
+        write_log.drop_tx();
+        assert!(write_log.get_events().is_empty());
+
+        write_log.emit_event(event1.clone());
+        write_log.emit_event(event2.clone());
+        assert_eq!(write_log.take_events(), vec![event1, event2]);
+        assert!(write_log.get_events().is_empty());
+    }
+
     prop_compose! {
         fn arb_verifiers_changed_key_tx_all_key()
             (verifiers_from_tx in testing::arb_verifiers_from_tx())
@@ -4,7 +4,8 @@ use super::token;
 use crate::ledger::governance::{storage, ADDRESS as governance_address};
 use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
 use crate::types::transaction::governance::{
-    InitProposalData, VoteProposalData,
+    InitProposalData, TallyOfflineProposalData, TopUpProposalDepositData,
+    VoteProposalData,
 };
 
 /// A proposal creation transaction.
@@ -43,6 +44,17 @@ where
         storage.write_bytes(&proposal_code_key, proposal_code)?;
     }
 
+    if data.expedited {
+        let expedited_key = storage::get_proposal_expedited_key(proposal_id);
+        storage.write(&expedited_key, true)?;
+    }
+
+    if !data.slash_fund_disbursements.is_empty() {
+        let disbursements_key =
+            storage::get_proposal_disbursements_key(proposal_id);
+        storage.write(&disbursements_key, data.slash_fund_disbursements)?;
+    }
+
     storage.write(&counter_key, proposal_id + 1)?;
 
     let min_proposal_funds_key = storage::get_min_proposal_fund_key();
@@ -66,6 +78,29 @@ where
     )
 }
 
+/// A transaction to top up the deposit of a proposal.
+pub fn top_up_proposal_deposit<S>(
+    storage: &mut S,
+    data: TopUpProposalDepositData,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let deposit_key =
+        storage::get_proposal_deposit_key(data.id, &data.depositor);
+    let deposit: token::Amount =
+        storage.read(&deposit_key)?.unwrap_or_default();
+    storage.write(&deposit_key, deposit + data.amount)?;
+
+    token::transfer(
+        storage,
+        &storage.get_native_token()?,
+        &data.depositor,
+        &governance_address,
+        data.amount,
+    )
+}
+
 /// A proposal vote transaction.
 pub fn vote_proposal<S>(
     storage: &mut S,
@@ -84,3 +119,39 @@ where
     }
     Ok(())
 }
+
+/// An offline proposal tally transaction. The first submission of a proposal
+/// opens a window of `min_proposal_period` epochs during which anyone can
+/// submit it again with more votes. The governance VP verifies the signatures
+/// and the protocol computes the tally once the window closes. The votes that
+/// have already been submitted are skipped.
+pub fn tally_offline_proposal<S>(
+    storage: &mut S,
+    data: TallyOfflineProposalData,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let proposal_hash = data.proposal.compute_hash();
+    let proposal_key = storage::get_offline_proposal_key(&proposal_hash);
+    if !storage.has_key(&proposal_key)? {
+        storage.write(&proposal_key, data.proposal)?;
+
+        let min_period: u64 = storage
+            .read(&storage::get_min_proposal_period_key())?
+            .expect("Missing min_proposal_period parameter");
+        let close_epoch = storage.get_block_epoch()? + min_period;
+        let pending_tally_key =
+            storage::get_pending_offline_tally_key(&proposal_hash);
+        storage.write(&pending_tally_key, close_epoch)?;
+    }
+
+    for vote in data.votes {
+        let vote_key =
+            storage::get_offline_vote_key(&proposal_hash, &vote.address);
+        if !storage.has_key(&vote_key)? {
+            storage.write(&vote_key, vote)?;
+        }
+    }
+    Ok(())
+}
@@ -12,7 +12,9 @@ use borsh::{BorshDeserialize, BorshSerialize};
 pub use error::{CustomError, Error, OptionExt, Result, ResultExt};
 
 use crate::types::address::Address;
-use crate::types::storage::{self, BlockHash, BlockHeight, Epoch, TxIndex};
+use crate::types::storage::{
+    self, BlockHash, BlockHeight, Epoch, Header, TxIndex,
+};
 
 /// Common storage read interface
 ///
@@ -87,6 +89,11 @@ pub trait StorageRead {
     /// current transaction is being applied.
     fn get_block_epoch(&self) -> Result<Epoch>;
 
+    /// Getting the block header at the given height. Only the headers of the
+    /// current block and of the past blocks within the
+    /// `storage_read_past_height_limit` protocol parameter are available.
+    fn get_block_header(&self, height: BlockHeight) -> Result<Option<Header>>;
+
     /// Get the transaction index.
     fn get_tx_index(&self) -> Result<TxIndex>;
 
@@ -70,3 +70,23 @@ where
     let new_balance = read_balance(storage, token, dest)? + amount;
     storage.write(&key, new_balance)
 }
+
+/// Burn tokens from an account, to be used only by protocol. Returns an `Err`
+/// if the account has insufficient balance.
+pub fn burn_tokens<S>(
+    storage: &mut S,
+    token: &Address,
+    src: &Address,
+    amount: token::Amount,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = token::balance_key(token, src);
+    match read_balance(storage, token, src)?.checked_sub(amount) {
+        Some(new_balance) => storage.write(&key, new_balance),
+        None => {
+            Err(storage_api::Error::new_const("Insufficient source balance"))
+        }
+    }
+}
@@ -5,6 +5,7 @@ use borsh::BorshSerialize;
 
 use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
 use crate::types::address::Address;
+use crate::types::event::TxEvent;
 use crate::types::ibc::IbcEvent;
 use crate::types::storage;
 use crate::types::time::Rfc3339String;
@@ -58,6 +59,11 @@ pub trait TxEnv: StorageRead + StorageWrite {
         event: &IbcEvent,
     ) -> Result<(), storage_api::Error>;
 
+    /// Emit a custom event. Unlike IBC events, all the events emitted by a
+    /// transaction are kept in the order of their emission.
+    fn emit_event(&mut self, event: &TxEvent)
+        -> Result<(), storage_api::Error>;
+
     /// Get time of the current block header as rfc 3339 string
     fn get_block_time(&self) -> Result<Rfc3339String, storage_api::Error>;
 }
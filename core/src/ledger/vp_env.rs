@@ -1,13 +1,16 @@
 //! Validity predicate environment contains functions that can be called from
 //! inside validity predicates.
 
-use borsh::BorshDeserialize;
+use borsh::{BorshDeserialize, BorshSerialize};
 
-use super::storage_api::{self, StorageRead};
+use super::storage_api::{self, ResultExt, StorageRead};
 use crate::types::address::Address;
 use crate::types::hash::Hash;
 use crate::types::key::common;
-use crate::types::storage::{BlockHash, BlockHeight, Epoch, Key, TxIndex};
+use crate::types::storage::{
+    BlockHash, BlockHeight, Epoch, Header, Key, TxIndex,
+};
+use crate::types::time::DateTimeUtc;
 
 /// Validity predicate's environment is available for native VPs and WASM VPs
 pub trait VpEnv<'view>
@@ -61,6 +64,24 @@ where
     /// current transaction is being applied.
     fn get_block_epoch(&self) -> Result<Epoch, storage_api::Error>;
 
+    /// Getting the block header at the given height. Only the headers of the
+    /// current block and of the past blocks within the
+    /// `storage_read_past_height_limit` protocol parameter are available.
+    fn get_block_header(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<Header>, storage_api::Error>;
+
+    /// Getting the block time. The time is that of the block header to which
+    /// the current transaction is being applied. This fails when the header
+    /// is not available, e.g. when a transaction is being dry-ran.
+    fn get_block_time(&self) -> Result<DateTimeUtc, storage_api::Error>;
+
+    /// Getting the address of the proposer of the block to which the current
+    /// transaction is being applied, if it's known.
+    fn get_block_proposer(&self)
+        -> Result<Option<Address>, storage_api::Error>;
+
     /// Get the shielded transaction index.
     fn get_tx_index(&self) -> Result<TxIndex, storage_api::Error>;
 
@@ -86,6 +107,19 @@ where
         input_data: Vec<u8>,
     ) -> Result<bool, storage_api::Error>;
 
+    /// Query the validity predicate of the account with the given address
+    /// via its read-only query entrypoint with the given input data. The
+    /// queried VP is the one from the prior state and it cannot modify the
+    /// storage.
+    ///
+    /// If the query fails for whatever reason, this will return `None`.
+    /// Otherwise returns the Borsh-encoded result of the query.
+    fn query_vp(
+        &self,
+        address: &Address,
+        input_data: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, storage_api::Error>;
+
     /// Verify a transaction signature. The signature is expected to have been
     /// produced on the encoded transaction [`crate::proto::Tx`]
     /// using [`crate::proto::Tx::sign`].
@@ -141,6 +175,22 @@ where
         self.post().read_bytes(key)
     }
 
+    /// Query the validity predicate of the account with the given address
+    /// with the Borsh encoded input and decode the result of the query.
+    fn query_vp_with<I: BorshSerialize, T: BorshDeserialize>(
+        &self,
+        address: &Address,
+        input: &I,
+    ) -> Result<Option<T>, storage_api::Error> {
+        let input_data = input.try_to_vec().into_storage_result()?;
+        match self.query_vp(address, input_data)? {
+            Some(result) => T::try_from_slice(&result[..])
+                .into_storage_result()
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
     /// Storage `has_key` in prior state (before tx execution). It will try to
     /// read from the storage.
     fn has_key_pre(&'view self, key: &Key) -> Result<bool, storage_api::Error> {
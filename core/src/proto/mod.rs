@@ -3,7 +3,7 @@
 pub mod generated;
 mod types;
 
-pub use types::{Dkg, Error, Signed, SignedTxData, Tx};
+pub use types::{Dkg, Error, Signed, SignedTxData, Tx, TxCode};
 
 #[cfg(test)]
 mod tests {
@@ -19,6 +19,7 @@ mod tests {
             code: "wasm code".as_bytes().to_owned(),
             data: Some("arbitrary data".as_bytes().to_owned()),
             timestamp: Some(std::time::SystemTime::now().into()),
+            code_hash: vec![],
         };
         let mut tx_bytes = vec![];
         tx.encode(&mut tx_bytes).unwrap();
@@ -9,6 +9,7 @@ use thiserror::Error;
 use super::generated::types;
 #[cfg(any(feature = "tendermint", feature = "tendermint-abcipp"))]
 use crate::tendermint_proto::abci::ResponseDeliverTx;
+use crate::types::hash::{self, HASH_LENGTH};
 use crate::types::key::*;
 use crate::types::time::DateTimeUtc;
 #[cfg(feature = "ferveo-tpke")]
@@ -33,6 +34,12 @@ pub enum Error {
     NoTimestampError,
     #[error("Timestamp is invalid: {0}")]
     InvalidTimestamp(prost_types::TimestampOutOfSystemRangeError),
+    #[error("Both the code and the code hash are set")]
+    CodeAndCodeHashError,
+    #[error("A batch transaction can't have a code or a code hash")]
+    BatchWithCodeError,
+    #[error("The code hash is invalid: {0}")]
+    InvalidCodeHash(hash::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -139,23 +146,37 @@ pub struct SigningTx {
 }
 
 impl SigningTx {
-    pub fn hash(&self) -> [u8; 32] {
+    /// Get the stable encoding of the transaction whose hash is signed. A
+    /// hardware wallet displays the transaction from this encoding before
+    /// signing its hash.
+    pub fn signing_bytes(&self) -> Vec<u8> {
         let timestamp = Some(self.timestamp.into());
         let mut bytes = vec![];
         types::Tx {
             code: self.code_hash.to_vec(),
             data: self.data.clone(),
             timestamp,
+            code_hash: vec![],
+            batch: None,
         }
         .encode(&mut bytes)
         .expect("encoding a transaction failed");
-        hash_tx(&bytes).0
+        bytes
+    }
+
+    pub fn hash(&self) -> [u8; 32] {
+        hash_tx(&self.signing_bytes()).0
     }
 
     /// Sign a transaction using [`SignedTxData`].
     pub fn sign(self, keypair: &common::SecretKey) -> Self {
-        let to_sign = self.hash();
-        let sig = common::SigScheme::sign(keypair, to_sign);
+        let sig = common::SigScheme::sign(keypair, self.hash());
+        self.with_signature(sig)
+    }
+
+    /// Attach a signature of the hash of the transaction, which may have been
+    /// made outside of this process, using [`SignedTxData`].
+    pub fn with_signature(self, sig: common::Signature) -> Self {
         let signed = SignedTxData {
             data: self.data,
             sig,
@@ -191,9 +212,10 @@ impl SigningTx {
     }
 
     /// Expand this reduced Tx using the supplied code only if the the code
-    /// hashes to the stored code hash
-    pub fn expand(self, code: Vec<u8>) -> Option<Tx> {
-        if hash_tx(&code).0 == self.code_hash {
+    /// hashes to the stored code hash or if it's a reference to the code by
+    /// the stored code hash
+    pub fn expand(self, code: TxCode) -> Option<Tx> {
+        if code.hash() == self.code_hash {
             Some(Tx {
                 code,
                 data: self.data,
@@ -208,21 +230,94 @@ impl SigningTx {
 impl From<Tx> for SigningTx {
     fn from(tx: Tx) -> SigningTx {
         SigningTx {
-            code_hash: hash_tx(&tx.code).0,
+            code_hash: tx.code.hash(),
             data: tx.data,
             timestamp: tx.timestamp,
         }
     }
 }
 
-/// A SigningTx but with the full code embedded. This structure will almost
-/// certainly be bigger than SigningTxs and contains enough information to
-/// execute the transaction.
+/// The code of a transaction
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, Hash)]
+pub enum TxCode {
+    /// The full wasm code, which is empty for a wrapper
+    Wasm(Vec<u8>),
+    /// The SHA-256 hash of a wasm code stored on-chain
+    Hash(hash::Hash),
+    /// The calls of a batch transaction, which are applied in order and
+    /// committed atomically (see [`Tx::new_batch`])
+    Batch(Vec<Tx>),
+}
+
+impl TxCode {
+    /// Get the hash of the code, which is the hash itself when the code is
+    /// referred to by its hash. The hash of a batch is the hash of its Borsh
+    /// encoded calls.
+    pub fn hash(&self) -> [u8; 32] {
+        match self {
+            TxCode::Wasm(code) => hash_tx(code).0,
+            TxCode::Hash(code_hash) => code_hash.0,
+            TxCode::Batch(calls) => {
+                hash_tx(
+                    &calls.try_to_vec().expect("Encoding calls shouldn't fail"),
+                )
+                .0
+            }
+        }
+    }
+
+    /// Get the size of the code in the transaction
+    pub fn size(&self) -> usize {
+        match self {
+            TxCode::Wasm(code) => code.len(),
+            TxCode::Hash(_) => HASH_LENGTH,
+            TxCode::Batch(calls) => calls
+                .try_to_vec()
+                .expect("Encoding calls shouldn't fail")
+                .len(),
+        }
+    }
+}
+
+/// The schema is implemented by hand, because the calls of a batch are
+/// transactions, whose code is a [`TxCode`] again. The definition is added
+/// before the definitions of the variants, so that adding it terminates.
+impl BorshSchema for TxCode {
+    fn add_definitions_recursively(
+        definitions: &mut std::collections::HashMap<
+            borsh::schema::Declaration,
+            borsh::schema::Definition,
+        >,
+    ) {
+        if definitions.contains_key(&Self::declaration()) {
+            return;
+        }
+        let variants = vec![
+            ("Wasm".into(), Vec::<u8>::declaration()),
+            ("Hash".into(), hash::Hash::declaration()),
+            ("Batch".into(), Vec::<Tx>::declaration()),
+        ];
+        let definition = borsh::schema::Definition::Enum { variants };
+        definitions.insert(Self::declaration(), definition);
+        Vec::<u8>::add_definitions_recursively(definitions);
+        hash::Hash::add_definitions_recursively(definitions);
+        Vec::<Tx>::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> borsh::schema::Declaration {
+        "TxCode".into()
+    }
+}
+
+/// A SigningTx but with the code embedded. This structure contains enough
+/// information to execute the transaction.
 #[derive(
     Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema, Hash,
 )]
 pub struct Tx {
-    pub code: Vec<u8>,
+    /// Either the full wasm code or the hash of a wasm code stored on-chain
+    /// (see [`Tx::new_with_code_hash`])
+    pub code: TxCode,
     pub data: Option<Vec<u8>>,
     pub timestamp: DateTimeUtc,
 }
@@ -232,12 +327,39 @@ impl TryFrom<&[u8]> for Tx {
 
     fn try_from(tx_bytes: &[u8]) -> Result<Self> {
         let tx = types::Tx::decode(tx_bytes).map_err(Error::TxDecodingError)?;
+        tx.try_into()
+    }
+}
+
+impl TryFrom<types::Tx> for Tx {
+    type Error = Error;
+
+    fn try_from(tx: types::Tx) -> Result<Self> {
         let timestamp = match tx.timestamp {
             Some(t) => t.try_into().map_err(Error::InvalidTimestamp)?,
             None => return Err(Error::NoTimestampError),
         };
+        let code = if let Some(batch) = tx.batch {
+            if !tx.code.is_empty() || !tx.code_hash.is_empty() {
+                return Err(Error::BatchWithCodeError);
+            }
+            let calls = batch
+                .calls
+                .into_iter()
+                .map(Tx::try_from)
+                .collect::<Result<_>>()?;
+            TxCode::Batch(calls)
+        } else if tx.code_hash.is_empty() {
+            TxCode::Wasm(tx.code)
+        } else if tx.code.is_empty() {
+            let code_hash = hash::Hash::try_from(&tx.code_hash[..])
+                .map_err(Error::InvalidCodeHash)?;
+            TxCode::Hash(code_hash)
+        } else {
+            return Err(Error::CodeAndCodeHashError);
+        };
         Ok(Tx {
-            code: tx.code,
+            code,
             data: tx.data,
             timestamp,
         })
@@ -247,10 +369,20 @@ impl TryFrom<&[u8]> for Tx {
 impl From<Tx> for types::Tx {
     fn from(tx: Tx) -> Self {
         let timestamp = Some(tx.timestamp.into());
+        let (code, code_hash, batch) = match tx.code {
+            TxCode::Wasm(code) => (code, vec![], None),
+            TxCode::Hash(code_hash) => (vec![], code_hash.0.to_vec(), None),
+            TxCode::Batch(calls) => {
+                let calls = calls.into_iter().map(types::Tx::from).collect();
+                (vec![], vec![], Some(types::Batch { calls }))
+            }
+        };
         types::Tx {
-            code: tx.code,
+            code,
             data: tx.data,
             timestamp,
+            code_hash,
+            batch,
         }
     }
 }
@@ -344,12 +476,64 @@ impl From<Tx> for ResponseDeliverTx {
 impl Tx {
     pub fn new(code: Vec<u8>, data: Option<Vec<u8>>) -> Self {
         Tx {
-            code,
+            code: TxCode::Wasm(code),
             data,
             timestamp: DateTimeUtc::now(),
         }
     }
 
+    /// Create a transaction that refers to a wasm code stored on-chain by its
+    /// hash instead of embedding the code
+    pub fn new_with_code_hash(
+        code_hash: hash::Hash,
+        data: Option<Vec<u8>>,
+    ) -> Self {
+        Tx {
+            code: TxCode::Hash(code_hash),
+            data,
+            timestamp: DateTimeUtc::now(),
+        }
+    }
+
+    /// Get the hash of the wasm code stored on-chain if this transaction
+    /// refers to the code by its hash
+    pub fn code_hash_ref(&self) -> Option<hash::Hash> {
+        match &self.code {
+            TxCode::Hash(code_hash) => Some(code_hash.clone()),
+            TxCode::Wasm(_) | TxCode::Batch(_) => None,
+        }
+    }
+
+    /// Create a batch transaction, whose calls are applied in order and
+    /// committed atomically. A batch has no data and its calls are signed
+    /// individually.
+    pub fn new_batch(calls: Vec<Tx>) -> Self {
+        Tx {
+            code: TxCode::Batch(calls),
+            data: None,
+            timestamp: DateTimeUtc::now(),
+        }
+    }
+
+    /// Get the calls of this transaction if it's a batch transaction (see
+    /// [`Tx::new_batch`])
+    pub fn batch_calls(&self) -> Option<&[Tx]> {
+        match &self.code {
+            TxCode::Batch(calls) => Some(calls),
+            TxCode::Wasm(_) | TxCode::Hash(_) => None,
+        }
+    }
+
+    /// Replace the embedded wasm code with a reference to the code by its
+    /// hash. The hash and the signature of the transaction don't change.
+    pub fn with_code_hash_ref(self) -> Self {
+        let code_hash = self.code_hash();
+        Self {
+            code: TxCode::Hash(hash::Hash(code_hash)),
+            ..self
+        }
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = vec![];
         let tx: types::Tx = self.clone().into();
@@ -366,11 +550,25 @@ impl Tx {
         SigningTx::from(self.clone()).code_hash
     }
 
+    /// Get the stable encoding of the transaction whose hash is signed (see
+    /// [`SigningTx::signing_bytes`])
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        SigningTx::from(self.clone()).signing_bytes()
+    }
+
     /// Sign a transaction using [`SignedTxData`].
     pub fn sign(self, keypair: &common::SecretKey) -> Self {
+        let sig = common::SigScheme::sign(keypair, self.hash());
+        self.with_signature(sig)
+    }
+
+    /// Attach a signature of the hash of the transaction, which may have been
+    /// made outside of this process, e.g. by a hardware wallet, using
+    /// [`SignedTxData`].
+    pub fn with_signature(self, sig: common::Signature) -> Self {
         let code = self.code.clone();
         SigningTx::from(self)
-            .sign(keypair)
+            .with_signature(sig)
             .expand(code)
             .expect("code hashes to unexpected value")
     }
@@ -479,6 +677,8 @@ mod tests {
             code,
             data: Some(data),
             timestamp: None,
+            code_hash: vec![],
+            batch: None,
         };
         let mut bytes = vec![];
         types_tx.encode(&mut bytes).expect("encoding failed");
@@ -488,6 +688,89 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_tx_code_hash_ref() {
+        let code = "wasm code".as_bytes().to_owned();
+        let data = "arbitrary data".as_bytes().to_owned();
+        let tx = Tx::new(code, Some(data));
+        assert!(tx.code_hash_ref().is_none());
+
+        let keypair = crate::types::key::testing::keypair_1();
+        let signed_tx = tx.sign(&keypair);
+        let code_hash = signed_tx.code_hash();
+        let tx_ref = signed_tx.clone().with_code_hash_ref();
+        assert_eq!(tx_ref.code_hash_ref(), Some(hash::Hash(code_hash)));
+        assert_eq!(tx_ref.code_hash(), code_hash);
+        assert_eq!(tx_ref.hash(), signed_tx.hash());
+        let bytes = tx_ref.to_bytes();
+        let tx_from_bytes =
+            Tx::try_from(bytes.as_ref()).expect("decoding failed");
+        assert_eq!(tx_from_bytes, tx_ref);
+
+        // The signature must be valid for the reference too
+        let signed = SignedTxData::try_from_slice(
+            tx_ref.data.as_ref().expect("missing data"),
+        )
+        .expect("decoding failed");
+        tx_ref
+            .verify_sig(&keypair.ref_to(), &signed.sig)
+            .expect("invalid signature");
+    }
+
+    #[test]
+    fn test_tx_with_signature() {
+        let code = "wasm code".as_bytes().to_owned();
+        let data = "arbitrary data".as_bytes().to_owned();
+        let tx = Tx::new(code, Some(data));
+        assert_eq!(tx.hash(), hash_tx(&tx.signing_bytes()).0);
+
+        // A signature of the hash made elsewhere gives the same signed tx
+        let keypair = crate::types::key::testing::keypair_1();
+        let sig = common::SigScheme::sign(&keypair, tx.hash());
+        let signed_tx = tx.clone().with_signature(sig.clone());
+        assert_eq!(signed_tx, tx.sign(&keypair));
+        signed_tx
+            .verify_sig(&keypair.ref_to(), &sig)
+            .expect("invalid signature");
+    }
+
+    #[test]
+    fn test_tx_batch() {
+        let code = [WASM_MAGIC, "wasm code".as_bytes()].concat();
+        let data = "arbitrary data".as_bytes().to_owned();
+        let tx = Tx::new(code, Some(data));
+        assert!(tx.batch_calls().is_none());
+
+        let keypair = crate::types::key::testing::keypair_1();
+        let calls = vec![tx.clone().sign(&keypair), tx];
+        let batch = Tx::new_batch(calls.clone());
+        assert!(batch.code_hash_ref().is_none());
+
+        let bytes = batch.to_bytes();
+        let batch_from_bytes =
+            Tx::try_from(bytes.as_ref()).expect("decoding failed");
+        assert_eq!(batch_from_bytes.batch_calls(), Some(&calls[..]));
+        assert_eq!(batch_from_bytes.hash(), batch.hash());
+
+        // A tx without code whose data encode some calls is not a batch
+        let data = calls.try_to_vec().expect("encoding failed");
+        let tx = Tx::new(vec![], Some(data));
+        assert!(tx.batch_calls().is_none());
+        let tx_from_bytes =
+            Tx::try_from(tx.to_bytes().as_ref()).expect("decoding failed");
+        assert!(tx_from_bytes.batch_calls().is_none());
+
+        // A batch can't have a code
+        let mut tx: types::Tx = batch.into();
+        tx.code = "wasm code".as_bytes().to_owned();
+        let mut bytes = vec![];
+        tx.encode(&mut bytes).expect("encoding failed");
+        assert!(matches!(
+            Tx::try_from(bytes.as_ref()),
+            Err(Error::BatchWithCodeError)
+        ));
+    }
+
     #[test]
     fn test_dkg_gossip_message() {
         let data = "arbitrary string".to_owned();
@@ -44,6 +44,8 @@ pub const POS: Address = Address::Internal(InternalAddress::PoS);
 /// Internal PoS slash pool address
 pub const POS_SLASH_POOL: Address =
     Address::Internal(InternalAddress::PosSlashPool);
+/// Internal fee pool address
+pub const FEE_POOL: Address = Address::Internal(InternalAddress::FeePool);
 
 /// Raw strings used to produce internal addresses. All the strings must begin
 /// with `PREFIX_INTERNAL` and be `FIXED_LEN_STRING_BYTES` characters long.
@@ -69,6 +71,8 @@ mod internal {
         "ibc::IBC Mint Address                        ";
     pub const ETH_BRIDGE: &str =
         "ano::ETH Bridge Address                      ";
+    pub const FEE_POOL: &str =
+        "ano::Fee Pool                                ";
 }
 
 /// Fixed-length address strings prefix for established addresses.
@@ -79,6 +83,8 @@ const PREFIX_IMPLICIT: &str = "imp";
 const PREFIX_INTERNAL: &str = "ano";
 /// Fixed-length address strings prefix for IBC addresses.
 const PREFIX_IBC: &str = "ibc";
+/// Fixed-length address strings prefix for IBC interchain accounts.
+const PREFIX_ICA: &str = "ica";
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -190,6 +196,9 @@ impl Address {
                     InternalAddress::IbcToken(hash) => {
                         format!("{}::{}", PREFIX_IBC, hash)
                     }
+                    InternalAddress::InterchainAccount(hash) => {
+                        format!("{}::{}", PREFIX_ICA, hash)
+                    }
                     InternalAddress::IbcEscrow => {
                         internal::IBC_ESCROW.to_string()
                     }
@@ -198,6 +207,7 @@ impl Address {
                     InternalAddress::EthBridge => {
                         internal::ETH_BRIDGE.to_string()
                     }
+                    InternalAddress::FeePool => internal::FEE_POOL.to_string(),
                 };
                 debug_assert_eq!(string.len(), FIXED_LEN_STRING_BYTES);
                 string
@@ -251,6 +261,9 @@ impl Address {
                 internal::ETH_BRIDGE => {
                     Ok(Address::Internal(InternalAddress::EthBridge))
                 }
+                internal::FEE_POOL => {
+                    Ok(Address::Internal(InternalAddress::FeePool))
+                }
                 _ => Err(Error::new(
                     ErrorKind::InvalidData,
                     "Invalid internal address",
@@ -275,6 +288,18 @@ impl Address {
                     "Invalid IBC internal address",
                 )),
             },
+            Some((PREFIX_ICA, hash)) => {
+                if hash.len() == HASH_LEN {
+                    Ok(Address::Internal(InternalAddress::InterchainAccount(
+                        hash.to_string(),
+                    )))
+                } else {
+                    Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "Interchain account hash must be 40 characters long",
+                    ))
+                }
+            }
             _ => Err(Error::new(
                 ErrorKind::InvalidData,
                 "Invalid address prefix",
@@ -460,12 +485,17 @@ pub enum InternalAddress {
     IbcBurn,
     /// Mint tokens from this address with IBC token transfer
     IbcMint,
+    /// IBC interchain account controlled by an account on another chain
+    InterchainAccount(String),
     /// Governance address
     Governance,
     /// SlashFund address for governance
     SlashFund,
     /// Bridge to Ethereum
     EthBridge,
+    /// Pool of the transaction fees that are held until the gas used by
+    /// their transactions is known
+    FeePool,
 }
 
 impl InternalAddress {
@@ -481,6 +511,19 @@ impl InternalAddress {
         let hash = format!("{:.width$x}", hasher.finalize(), width = HASH_LEN);
         InternalAddress::IbcToken(hash)
     }
+
+    /// Get an interchain account address from the connection ID on this chain
+    /// and the port ID of the controller on the counterparty chain
+    pub fn interchain_account_address(
+        connection_id: String,
+        controller_port_id: String,
+    ) -> Self {
+        let mut hasher = Sha256::new();
+        let s = format!("{}/{}", connection_id, controller_port_id);
+        hasher.update(&s);
+        let hash = format!("{:.width$x}", hasher.finalize(), width = HASH_LEN);
+        InternalAddress::InterchainAccount(hash)
+    }
 }
 
 impl Display for InternalAddress {
@@ -499,7 +542,11 @@ impl Display for InternalAddress {
                 Self::IbcEscrow => "IbcEscrow".to_string(),
                 Self::IbcBurn => "IbcBurn".to_string(),
                 Self::IbcMint => "IbcMint".to_string(),
+                Self::InterchainAccount(hash) => {
+                    format!("InterchainAccount: {}", hash)
+                }
                 Self::EthBridge => "EthBridge".to_string(),
+                Self::FeePool => "FeePool".to_string(),
             }
         )
     }
@@ -783,8 +830,10 @@ pub mod testing {
             InternalAddress::IbcEscrow => {}
             InternalAddress::IbcBurn => {}
             InternalAddress::IbcMint => {}
-            InternalAddress::EthBridge => {} /* Add new addresses in the
-                                              * `prop_oneof` below. */
+            InternalAddress::InterchainAccount(_) => {}
+            InternalAddress::EthBridge => {}
+            InternalAddress::FeePool => {} /* Add new addresses in the
+                                            * `prop_oneof` below. */
         };
         prop_oneof![
             Just(InternalAddress::PoS),
@@ -796,9 +845,11 @@ pub mod testing {
             Just(InternalAddress::IbcEscrow),
             Just(InternalAddress::IbcBurn),
             Just(InternalAddress::IbcMint),
+            arb_interchain_account(),
             Just(InternalAddress::Governance),
             Just(InternalAddress::SlashFund),
             Just(InternalAddress::EthBridge),
+            Just(InternalAddress::FeePool),
         ]
     }
 
@@ -818,4 +869,13 @@ pub mod testing {
             InternalAddress::IbcToken(hash)
         })
     }
+
+    fn arb_interchain_account() -> impl Strategy<Value = InternalAddress> {
+        ("[a-zA-Z0-9_]{2,128}", any::<u64>()).prop_map(|(id, counter)| {
+            InternalAddress::interchain_account_address(
+                format!("connection-{}", counter),
+                format!("icacontroller-{}", id),
+            )
+        })
+    }
 }
@@ -0,0 +1,138 @@
+//! Custom events emitted by transactions
+
+use std::collections::HashMap;
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use thiserror::Error;
+
+/// The prefix of the type of every event emitted by a transaction, which
+/// separates them from the events emitted by the ledger
+pub const TX_EVENT_TYPE_PREFIX: &str = "tx/";
+
+/// The attribute of the hash of the transaction which emitted the event. It is
+/// set by the ledger and can't be set by a transaction.
+pub const TX_HASH_ATTRIBUTE: &str = "hash";
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("The event type is empty")]
+    EmptyEventType,
+    #[error("The event type {0} contains an invalid character")]
+    InvalidEventType(String),
+    #[error("The event attribute {0} is reserved")]
+    ReservedAttribute(String),
+}
+
+/// Custom event emitted by a transaction to announce its domain-specific
+/// state changes. There can be many events per transaction.
+#[derive(
+    Debug, Clone, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq,
+)]
+pub struct TxEvent {
+    /// The event type
+    pub event_type: String,
+    /// The attributes of the event
+    pub attributes: HashMap<String, String>,
+}
+
+impl TxEvent {
+    /// Make a new event of the given type without any attributes
+    pub fn new(event_type: impl Into<String>) -> Self {
+        Self {
+            event_type: event_type.into(),
+            attributes: HashMap::new(),
+        }
+    }
+
+    /// Add an attribute to the event
+    pub fn with_attribute(
+        mut self,
+        key: impl Into<String>,
+        value: impl ToString,
+    ) -> Self {
+        self.attributes.insert(key.into(), value.to_string());
+        self
+    }
+
+    /// Check that the event type isn't empty and can be used in a Tendermint
+    /// event query, and that no attribute is reserved by the ledger
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.event_type.is_empty() {
+            return Err(Error::EmptyEventType);
+        }
+        if self.event_type.chars().any(|c| {
+            c.is_whitespace()
+                || matches!(
+                    c,
+                    '.' | '\\' | '(' | ')' | '"' | '\'' | '=' | '<' | '>'
+                )
+        }) {
+            return Err(Error::InvalidEventType(self.event_type.clone()));
+        }
+        if self.attributes.contains_key(TX_HASH_ATTRIBUTE) {
+            return Err(Error::ReservedAttribute(TX_HASH_ATTRIBUTE.to_owned()));
+        }
+        Ok(())
+    }
+
+    /// The event type in the namespace of the events emitted by transactions,
+    /// e.g. `tx/transfer`
+    pub fn namespaced_type(&self) -> String {
+        format!("{}{}", TX_EVENT_TYPE_PREFIX, self.event_type)
+    }
+
+    /// The gas cost to emit the event
+    pub fn gas_cost(&self) -> u64 {
+        self.attributes
+            .iter()
+            .fold(self.event_type.len(), |acc, (k, v)| acc + k.len() + v.len())
+            as _
+    }
+}
+
+impl std::fmt::Display for TxEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let attributes = self
+            .attributes
+            .iter()
+            .map(|(k, v)| format!("{}: {};", k, v))
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(
+            f,
+            "Event type: {}, Attributes: {}",
+            self.event_type, attributes
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_event_type() {
+        let event = TxEvent::new("transfer").with_attribute("amount", 10);
+        assert!(event.validate().is_ok());
+        assert_eq!(event.attributes["amount"], "10");
+
+        assert!(TxEvent::new("").validate().is_err());
+        assert!(
+            TxEvent::new("transfer")
+                .with_attribute("hash", "")
+                .validate()
+                .is_err()
+        );
+        assert!(TxEvent::new("my transfer").validate().is_err());
+        assert!(TxEvent::new("transfer.amount").validate().is_err());
+    }
+
+    #[test]
+    fn test_namespaced_type() {
+        // The ledger event types can't be emitted by a transaction
+        let event = TxEvent::new("applied");
+        assert!(event.validate().is_ok());
+        assert_eq!(event.namespaced_type(), "tx/applied");
+    }
+}
@@ -14,7 +14,7 @@ use crate::types::hash::Hash;
 use crate::types::key::common::{self, Signature};
 use crate::types::key::SigScheme;
 use crate::types::storage::Epoch;
-use crate::types::token::SCALE;
+use crate::types::token::{self, SCALE};
 
 /// Type alias for vote power
 pub type VotePower = u128;
@@ -35,6 +35,9 @@ pub enum ProposalVote {
     Yay,
     /// No
     Nay,
+    /// No with veto, which burns the proposal deposits if enough voting
+    /// power vetoes
+    Veto,
 }
 
 impl ProposalVote {
@@ -42,9 +45,14 @@ impl ProposalVote {
     pub fn is_yay(&self) -> bool {
         match self {
             ProposalVote::Yay => true,
-            ProposalVote::Nay => false,
+            ProposalVote::Nay | ProposalVote::Veto => false,
         }
     }
+
+    /// Check if a vote is a veto
+    pub fn is_veto(&self) -> bool {
+        matches!(self, ProposalVote::Veto)
+    }
 }
 
 impl Display for ProposalVote {
@@ -52,6 +60,7 @@ impl Display for ProposalVote {
         match self {
             ProposalVote::Yay => write!(f, "yay"),
             ProposalVote::Nay => write!(f, "nay"),
+            ProposalVote::Veto => write!(f, "veto"),
         }
     }
 }
@@ -59,7 +68,7 @@ impl Display for ProposalVote {
 #[allow(missing_docs)]
 #[derive(Debug, Error)]
 pub enum ProposalVoteParseError {
-    #[error("Invalid vote. Vote shall be yay or nay.")]
+    #[error("Invalid vote. Vote shall be yay, nay or veto.")]
     InvalidVote,
 }
 
@@ -71,6 +80,8 @@ impl FromStr for ProposalVote {
             Ok(ProposalVote::Yay)
         } else if s.eq("nay") {
             Ok(ProposalVote::Nay)
+        } else if s.eq("veto") {
+            Ok(ProposalVote::Veto)
         } else {
             Err(ProposalVoteParseError::InvalidVote)
         }
@@ -78,6 +89,17 @@ impl FromStr for ProposalVote {
 }
 
 /// The result of a proposal
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
 pub enum TallyResult {
     /// Proposal was accepted
     Passed,
@@ -88,6 +110,16 @@ pub enum TallyResult {
 }
 
 /// The result with votes of a proposal
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
 pub struct ProposalResult {
     /// The result of a proposal
     pub result: TallyResult,
@@ -95,8 +127,10 @@ pub struct ProposalResult {
     pub total_voting_power: VotePower,
     /// The total voting power from yay votes
     pub total_yay_power: VotePower,
-    /// The total voting power from nay votes (unused at the moment)
+    /// The total voting power from nay votes, excluding vetoes
     pub total_nay_power: VotePower,
+    /// The total voting power from veto votes
+    pub total_veto_power: VotePower,
 }
 
 impl Display for ProposalResult {
@@ -128,6 +162,263 @@ impl Display for TallyResult {
     }
 }
 
+/// The status of a proposal
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub enum ProposalStatus {
+    /// The voting period hasn't started yet
+    Pending,
+    /// The voting period is on-going
+    Voting,
+    /// The proposal was accepted. Its code, if any, is executed at the grace
+    /// epoch.
+    Passed,
+    /// The proposal was rejected or its code failed
+    Rejected,
+    /// The proposal was accepted and its code was executed
+    Executed,
+}
+
+impl Display for ProposalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProposalStatus::Pending => write!(f, "pending"),
+            ProposalStatus::Voting => write!(f, "voting"),
+            ProposalStatus::Passed => write!(f, "passed"),
+            ProposalStatus::Rejected => write!(f, "rejected"),
+            ProposalStatus::Executed => write!(f, "executed"),
+        }
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum ProposalStatusParseError {
+    #[error(
+        "Invalid proposal status. It shall be pending, voting, passed, \
+         rejected or executed."
+    )]
+    InvalidStatus,
+}
+
+impl FromStr for ProposalStatus {
+    type Err = ProposalStatusParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(ProposalStatus::Pending),
+            "voting" => Ok(ProposalStatus::Voting),
+            "passed" => Ok(ProposalStatus::Passed),
+            "rejected" => Ok(ProposalStatus::Rejected),
+            "executed" => Ok(ProposalStatus::Executed),
+            _ => Err(ProposalStatusParseError::InvalidStatus),
+        }
+    }
+}
+
+/// A summary of an on-chain proposal
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct ProposalSummary {
+    /// The proposal id
+    pub id: u64,
+    /// The proposal author address
+    pub author: Address,
+    /// The epoch from which voting is allowed
+    pub voting_start_epoch: Epoch,
+    /// The epoch from which voting is stopped
+    pub voting_end_epoch: Epoch,
+    /// The epoch from which this changes are executed
+    pub grace_epoch: Epoch,
+    /// The status of the proposal
+    pub status: ProposalStatus,
+}
+
+/// An on-chain proposal with its content
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct ProposalDetails {
+    /// The summary of the proposal
+    pub summary: ProposalSummary,
+    /// The proposal content
+    pub content: BTreeMap<String, String>,
+    /// The hash of the proposal code, if any
+    pub code_hash: Option<Hash>,
+}
+
+/// A page of proposals
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct ProposalsPage {
+    /// The proposals of the page, by increasing id
+    pub proposals: Vec<ProposalSummary>,
+    /// The id from which to query the next page, if there may be more
+    /// proposals
+    pub next_id: Option<u64>,
+}
+
+/// The schedule of a slash fund disbursement
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub enum DisbursementSchedule {
+    /// A single payment when the proposal is executed, at the start of its
+    /// grace epoch
+    OneOff,
+    /// A payment at the start of every epoch of the range, both included
+    Streamed {
+        /// The epoch of the first payment
+        start_epoch: Epoch,
+        /// The epoch of the last payment
+        end_epoch: Epoch,
+    },
+}
+
+/// A payment from the slash fund to an address, approved by a governance
+/// proposal
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct SlashFundDisbursement {
+    /// The address receiving the payments
+    pub target: Address,
+    /// The amount of native tokens of each payment
+    pub amount: token::Amount,
+    /// When the payments are made
+    pub schedule: DisbursementSchedule,
+}
+
+impl SlashFundDisbursement {
+    /// Check if a disbursement is valid for a proposal with the given grace
+    /// epoch. Streamed payments can't start before the proposal is executed.
+    pub fn is_valid(&self, grace_epoch: Epoch) -> bool {
+        let is_valid_schedule = match self.schedule {
+            DisbursementSchedule::OneOff => true,
+            DisbursementSchedule::Streamed {
+                start_epoch,
+                end_epoch,
+            } => grace_epoch <= start_epoch && start_epoch <= end_epoch,
+        };
+        self.amount != token::Amount::default() && is_valid_schedule
+    }
+}
+
+/// A slash fund disbursement of a passed proposal that isn't fully paid yet
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct PendingDisbursement {
+    /// The id of the proposal that approved the disbursement
+    pub proposal_id: u64,
+    /// The index of the disbursement in the proposal
+    pub index: u64,
+    /// The disbursement
+    pub disbursement: SlashFundDisbursement,
+}
+
+/// A payment made from the slash fund
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct DisbursementPayment {
+    /// The id of the proposal that approved the disbursement
+    pub proposal_id: u64,
+    /// The index of the disbursement in the proposal
+    pub index: u64,
+    /// The address that received the payment
+    pub target: Address,
+    /// The amount of native tokens paid
+    pub amount: token::Amount,
+    /// The epoch in which the payment was made
+    pub epoch: Epoch,
+}
+
+/// A vote on a proposal with its voting power
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct WeightedVote {
+    /// The voter address
+    pub voter: Address,
+    /// The validator of the voter's delegation, or the voter itself for a
+    /// validator
+    pub validator: Address,
+    /// Whether the voter is a validator
+    pub is_validator: bool,
+    /// The vote
+    pub vote: ProposalVote,
+    /// The voting power, i.e. the validator's stake or the delegator's bond
+    pub power: VotePower,
+}
+
 #[derive(
     Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
 )]
@@ -147,6 +438,13 @@ pub struct Proposal {
     pub grace_epoch: Epoch,
     /// The code containing the storage changes
     pub proposal_code_path: Option<String>,
+    /// Whether the proposal is expedited, with shorter voting and grace
+    /// periods but a higher threshold to pass
+    #[serde(default)]
+    pub expedited: bool,
+    /// The payments from the slash fund to make if the proposal passes
+    #[serde(default)]
+    pub slash_fund_disbursements: Vec<SlashFundDisbursement>,
 }
 
 impl Display for Proposal {
@@ -271,7 +569,8 @@ impl OfflineVote {
             .expect("Conversion to bytes shouldn't fail.");
         let vote_serialized =
             &[proposal_hash_data, proposal_vote_data].concat();
-        let signature = common::SigScheme::sign(signing_key, vote_serialized);
+        let vote_data_hash = Hash::sha256(vote_serialized);
+        let signature = common::SigScheme::sign(signing_key, vote_data_hash);
         Self {
             proposal_hash,
             vote,
@@ -24,6 +24,8 @@ pub enum Error {
     ConversionFailed(std::array::TryFromSliceError),
     #[error("Failed to convert string into a hash: {0}")]
     FromStringError(data_encoding::DecodeError),
+    #[error("Unknown hash algorithm: {0}")]
+    UnknownAlgorithm(u64),
 }
 
 /// Result for functions that may fail
@@ -176,3 +178,38 @@ impl From<&H256> for Hash {
         Self(hash.into())
     }
 }
+
+/// The hash functions that can be computed by the host on behalf of the wasm
+/// code. All of them produce 32 bytes digests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// SHA-256
+    Sha256,
+    /// Keccak-256 as used in Ethereum
+    Keccak256,
+    /// BLAKE2b with 32 bytes output
+    Blake2b256,
+}
+
+impl From<HashAlgorithm> for u64 {
+    fn from(algo: HashAlgorithm) -> Self {
+        match algo {
+            HashAlgorithm::Sha256 => 0,
+            HashAlgorithm::Keccak256 => 1,
+            HashAlgorithm::Blake2b256 => 2,
+        }
+    }
+}
+
+impl TryFrom<u64> for HashAlgorithm {
+    type Error = self::Error;
+
+    fn try_from(value: u64) -> HashResult<Self> {
+        match value {
+            0 => Ok(Self::Sha256),
+            1 => Ok(Self::Keccak256),
+            2 => Ok(Self::Blake2b256),
+            _ => Err(Error::UnknownAlgorithm(value)),
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Types for hash-time-locked contracts (HTLC), which release their tokens to
+//! a recipient on presentation of the preimage of a hash before a deadline and
+//! refund the sender afterwards.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use thiserror::Error;
+
+use super::address::Address;
+use super::hash::Hash;
+use super::storage::{DbKeySeg, Key, KeySeg};
+use super::time::DateTimeUtc;
+use super::token;
+
+/// The storage sub-key of an HTLC's terms
+const HTLC_STORAGE_KEY: &str = "htlc";
+/// The storage sub-key of the preimage revealed to claim an HTLC
+const PREIMAGE_STORAGE_KEY: &str = "htlc_preimage";
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("The amount of locked tokens must not be zero")]
+    ZeroAmount,
+    #[error("The deadline {0} has already passed")]
+    ExpiredDeadline(String),
+    #[error("The hash lock must be a SHA-256 hash of a non-empty preimage")]
+    InvalidHashLock,
+}
+
+/// The terms of an HTLC
+#[derive(
+    Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, BorshSchema,
+)]
+pub struct Htlc {
+    /// The account that locked the tokens and that gets them refunded after
+    /// the deadline
+    pub sender: Address,
+    /// The account that may claim the tokens before the deadline
+    pub recipient: Address,
+    /// The address of the locked token
+    pub token: Address,
+    /// The amount of locked tokens
+    pub amount: token::Amount,
+    /// The SHA-256 hash of the preimage required to claim the tokens
+    pub hash_lock: Hash,
+    /// The time from which the tokens can no longer be claimed and can be
+    /// refunded to the sender
+    pub deadline: DateTimeUtc,
+}
+
+impl Htlc {
+    /// Check if the HTLC has expired at the given time
+    pub fn is_expired(&self, time: DateTimeUtc) -> bool {
+        time >= self.deadline
+    }
+
+    /// Validate the terms of a new HTLC initialized at the given time
+    pub fn validate(&self, time: DateTimeUtc) -> Result<(), Error> {
+        if self.amount == token::Amount::default() {
+            return Err(Error::ZeroAmount);
+        }
+        if self.is_expired(time) {
+            return Err(Error::ExpiredDeadline(self.deadline.to_rfc3339()));
+        }
+        // A zero hash has no known preimage and the hash of an empty
+        // preimage can be claimed by anyone
+        if self.hash_lock == Hash::default()
+            || self.hash_lock == Hash::sha256(b"")
+        {
+            return Err(Error::InvalidHashLock);
+        }
+        Ok(())
+    }
+}
+
+/// Tx data of a transaction that creates a new HTLC account and locks the
+/// tokens in it
+#[derive(
+    Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema,
+)]
+pub struct InitHtlc {
+    /// The VP code of the HTLC account
+    pub vp_code: Vec<u8>,
+    /// The terms of the HTLC
+    pub htlc: Htlc,
+}
+
+/// Tx data of a transaction that claims the tokens of an HTLC for its
+/// recipient
+#[derive(
+    Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema,
+)]
+pub struct ClaimHtlc {
+    /// The address of the HTLC account
+    pub htlc: Address,
+    /// The preimage of the HTLC's hash lock
+    pub preimage: Vec<u8>,
+}
+
+/// Tx data of a transaction that refunds the tokens of an expired HTLC to its
+/// sender
+#[derive(
+    Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema,
+)]
+pub struct RefundHtlc {
+    /// The address of the HTLC account
+    pub htlc: Address,
+}
+
+/// Obtain a storage key for the terms of the given HTLC account.
+pub fn htlc_key(htlc: &Address) -> Key {
+    Key::from(htlc.to_db_key())
+        .push(&HTLC_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Obtain a storage key for the preimage revealed to claim the given HTLC
+/// account. The preimage is kept in storage so that the sender can use it to
+/// claim the counterpart of an atomic swap.
+pub fn preimage_key(htlc: &Address) -> Key {
+    Key::from(htlc.to_db_key())
+        .push(&PREIMAGE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is an HTLC terms key. If it is, returns the
+/// HTLC address.
+pub fn is_htlc_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(htlc), DbKeySeg::StringSeg(key)]
+            if key == HTLC_STORAGE_KEY =>
+        {
+            Some(htlc)
+        }
+        _ => None,
+    }
+}
+
+/// Check if the given storage key is an HTLC preimage key. If it is, returns
+/// the HTLC address.
+pub fn is_preimage_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(htlc), DbKeySeg::StringSeg(key)]
+            if key == PREIMAGE_STORAGE_KEY =>
+        {
+            Some(htlc)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address;
+    use crate::types::time::Duration;
+
+    #[test]
+    fn test_htlc_keys() {
+        let htlc = address::testing::established_address_1();
+        let terms = htlc_key(&htlc);
+        let preimage = preimage_key(&htlc);
+        assert_eq!(is_htlc_key(&terms), Some(&htlc));
+        assert_eq!(is_preimage_key(&terms), None);
+        assert_eq!(is_preimage_key(&preimage), Some(&htlc));
+        assert_eq!(is_htlc_key(&preimage), None);
+    }
+
+    #[test]
+    fn test_htlc_validate() {
+        let now = DateTimeUtc::now();
+        let htlc = Htlc {
+            sender: address::testing::established_address_1(),
+            recipient: address::testing::established_address_2(),
+            token: address::nam(),
+            amount: token::Amount::whole(1),
+            hash_lock: Hash::sha256(b"secret"),
+            deadline: DateTimeUtc(now.0 + Duration::days(1)),
+        };
+        assert!(htlc.validate(now).is_ok());
+
+        let zero_amount = Htlc {
+            amount: token::Amount::default(),
+            ..htlc.clone()
+        };
+        assert!(matches!(zero_amount.validate(now), Err(Error::ZeroAmount)));
+
+        assert!(matches!(
+            htlc.validate(htlc.deadline),
+            Err(Error::ExpiredDeadline(_))
+        ));
+
+        for hash_lock in [Hash::default(), Hash::sha256(b"")] {
+            let invalid_hash_lock = Htlc {
+                hash_lock,
+                ..htlc.clone()
+            };
+            assert!(matches!(
+                invalid_hash_lock.validate(now),
+                Err(Error::InvalidHashLock)
+            ));
+        }
+    }
+}
@@ -4,6 +4,9 @@ use std::collections::HashMap;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 
+use crate::types::storage::Epoch;
+use crate::types::token::Amount;
+
 /// Wrapped IbcEvent
 #[derive(
     Debug, Clone, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq,
@@ -31,6 +34,104 @@ impl std::fmt::Display for IbcEvent {
     }
 }
 
+/// The per-epoch caps of the token flows of a channel for a denomination.
+/// `None` means that the flow in that direction is not limited.
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    PartialEq,
+    Eq,
+)]
+pub struct IbcFlowLimit {
+    /// The maximum amount that can be received in an epoch
+    pub inflow: Option<Amount>,
+    /// The maximum amount that can be sent in an epoch
+    pub outflow: Option<Amount>,
+}
+
+/// The token flows of a channel for a denomination in an epoch
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    PartialEq,
+    Eq,
+)]
+pub struct IbcFlow {
+    /// The epoch in which the flows have been accumulated
+    pub epoch: Epoch,
+    /// The amount received in the epoch
+    pub inflow: Amount,
+    /// The amount sent in the epoch
+    pub outflow: Amount,
+}
+
+impl IbcFlow {
+    /// Get the flows to be accumulated in the given epoch. The flows are reset
+    /// when they have been accumulated in a different epoch.
+    pub fn at_epoch(self, epoch: Epoch) -> Self {
+        if self.epoch == epoch {
+            self
+        } else {
+            Self {
+                epoch,
+                ..Default::default()
+            }
+        }
+    }
+
+    /// Add the received amount. Returns `None` when the inflow would exceed
+    /// the given limit.
+    pub fn checked_add_inflow(
+        &self,
+        amount: Amount,
+        limit: &IbcFlowLimit,
+    ) -> Option<Self> {
+        let inflow = self.inflow.checked_add(amount)?;
+        match limit.inflow {
+            Some(max) if inflow > max => None,
+            _ => Some(Self {
+                inflow,
+                ..self.clone()
+            }),
+        }
+    }
+
+    /// Add the sent amount. Returns `None` when the outflow would exceed the
+    /// given limit.
+    pub fn checked_add_outflow(
+        &self,
+        amount: Amount,
+        limit: &IbcFlowLimit,
+    ) -> Option<Self> {
+        let outflow = self.outflow.checked_add(amount)?;
+        match limit.outflow {
+            Some(max) if outflow > max => None,
+            _ => Some(Self {
+                outflow,
+                ..self.clone()
+            }),
+        }
+    }
+
+    /// Subtract the refunded amount of a sent packet from the outflow. The
+    /// outflow doesn't go below zero when the packet was sent in an earlier
+    /// epoch.
+    pub fn sub_outflow(&self, amount: Amount) -> Self {
+        Self {
+            outflow: self.outflow.checked_sub(amount).unwrap_or_default(),
+            ..self.clone()
+        }
+    }
+}
+
 #[cfg(any(feature = "abciplus", feature = "abcipp"))]
 mod ibc_rs_conversion {
     use std::collections::HashMap;
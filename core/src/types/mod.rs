@@ -2,14 +2,18 @@
 
 pub mod address;
 pub mod chain;
+pub mod event;
 pub mod governance;
 pub mod hash;
+pub mod htlc;
 pub mod ibc;
 pub mod internal;
 pub mod key;
 pub mod masp;
+pub mod sponsor;
 pub mod storage;
 pub mod time;
 pub mod token;
 pub mod transaction;
 pub mod validity_predicate;
+pub mod vesting;
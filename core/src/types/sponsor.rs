@@ -0,0 +1,72 @@
+//! Storage keys of the policy of an account that pays the fees of other
+//! users' transactions as a third-party fee payer. An account that sets
+//! neither of the keys sponsors any transaction that it co-signs.
+
+use super::address::Address;
+use super::storage::{DbKeySeg, Key, KeySeg};
+
+/// The storage sub-key of the set of tx code hashes that an account sponsors
+const SPONSORED_TX_CODES_STORAGE_KEY: &str = "sponsored_tx_codes";
+/// The storage sub-key of the set of public keys of the users whose txs an
+/// account sponsors
+const SPONSORED_USERS_STORAGE_KEY: &str = "sponsored_users";
+
+/// Obtain a storage key for the set of tx code hashes, stored as a
+/// `BTreeSet<Hash>`, that the given account sponsors.
+pub fn sponsored_tx_codes_key(sponsor: &Address) -> Key {
+    Key::from(sponsor.to_db_key())
+        .push(&SPONSORED_TX_CODES_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Obtain a storage key for the set of public keys, stored as a
+/// `BTreeSet<common::PublicKey>`, of the users whose txs the given account
+/// sponsors.
+pub fn sponsored_users_key(sponsor: &Address) -> Key {
+    Key::from(sponsor.to_db_key())
+        .push(&SPONSORED_USERS_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a sponsored tx codes key. If it is,
+/// returns the sponsor's address.
+pub fn is_sponsored_tx_codes_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(sponsor), DbKeySeg::StringSeg(key)]
+            if key == SPONSORED_TX_CODES_STORAGE_KEY =>
+        {
+            Some(sponsor)
+        }
+        _ => None,
+    }
+}
+
+/// Check if the given storage key is a sponsored users key. If it is, returns
+/// the sponsor's address.
+pub fn is_sponsored_users_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(sponsor), DbKeySeg::StringSeg(key)]
+            if key == SPONSORED_USERS_STORAGE_KEY =>
+        {
+            Some(sponsor)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address;
+
+    #[test]
+    fn test_sponsor_keys() {
+        let sponsor = address::testing::established_address_1();
+        let codes = sponsored_tx_codes_key(&sponsor);
+        let users = sponsored_users_key(&sponsor);
+        assert_eq!(is_sponsored_tx_codes_key(&codes), Some(&sponsor));
+        assert_eq!(is_sponsored_users_key(&codes), None);
+        assert_eq!(is_sponsored_users_key(&users), Some(&sponsor));
+        assert_eq!(is_sponsored_tx_codes_key(&users), None);
+    }
+}
@@ -2,8 +2,12 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
 use crate::types::address::Address;
-use crate::types::governance::{Proposal, ProposalError, ProposalVote};
+use crate::types::governance::{
+    OfflineProposal, OfflineVote, Proposal, ProposalError, ProposalVote,
+    SlashFundDisbursement,
+};
 use crate::types::storage::Epoch;
+use crate::types::token;
 
 /// A tx data type to hold proposal data
 #[derive(
@@ -30,6 +34,10 @@ pub struct InitProposalData {
     pub grace_epoch: Epoch,
     /// The code containing the storage changes
     pub proposal_code: Option<Vec<u8>>,
+    /// Whether the proposal is expedited
+    pub expedited: bool,
+    /// The payments from the slash fund to make if the proposal passes
+    pub slash_fund_disbursements: Vec<SlashFundDisbursement>,
 }
 
 /// A tx data type to hold vote proposal data
@@ -53,6 +61,38 @@ pub struct VoteProposalData {
     pub delegations: Vec<Address>,
 }
 
+/// A tx data type to top up the deposit of a proposal before its voting period
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct TopUpProposalDepositData {
+    /// The proposal id
+    pub id: u64,
+    /// The address depositing the tokens, which are refunded to it together
+    /// with the proposal's deposit
+    pub depositor: Address,
+    /// The amount of native tokens to deposit
+    pub amount: token::Amount,
+}
+
+/// A tx data type to submit an offline proposal with its votes to be tallied
+/// on-chain
+#[derive(
+    Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub struct TallyOfflineProposalData {
+    /// The signed offline proposal
+    pub proposal: OfflineProposal,
+    /// The signed votes for the proposal
+    pub votes: Vec<OfflineVote>,
+}
+
 impl TryFrom<Proposal> for InitProposalData {
     type Error = ProposalError;
 
@@ -74,6 +114,8 @@ impl TryFrom<Proposal> for InitProposalData {
             voting_end_epoch: proposal.voting_end_epoch,
             grace_epoch: proposal.grace_epoch,
             proposal_code,
+            expedited: proposal.expedited,
+            slash_fund_disbursements: proposal.slash_fund_disbursements,
         })
     }
 }
@@ -28,10 +28,12 @@ pub use wrapper::*;
 
 use crate::ledger::gas::VpsGas;
 use crate::types::address::Address;
+use crate::types::event::TxEvent;
 use crate::types::hash::Hash;
 use crate::types::ibc::IbcEvent;
 use crate::types::key::*;
 use crate::types::storage;
+use crate::types::vesting::VestingSchedule;
 
 /// Get the hash of a transaction
 pub fn hash_tx(tx_bytes: &[u8]) -> Hash {
@@ -53,6 +55,11 @@ pub struct TxResult {
     pub initialized_accounts: Vec<Address>,
     /// Optional IBC event emitted by the transaction
     pub ibc_event: Option<IbcEvent>,
+    /// Custom events emitted by the transaction
+    pub events: Vec<TxEvent>,
+    /// The results of the calls of a batch transaction in their order. The
+    /// calls following a declined call are not applied.
+    pub batch_calls: Vec<TxResult>,
 }
 
 impl TxResult {
@@ -167,6 +174,24 @@ pub struct InitAccount {
     pub vp_code: Vec<u8>,
 }
 
+/// A tx data type to initialize a new vesting account. The vested tokens are
+/// transferred to the new account from the source.
+#[derive(
+    Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema,
+)]
+pub struct InitVestingAccount {
+    /// Public key to be written into the account's storage. This can be used
+    /// for signature verification of transactions for the newly created
+    /// account.
+    pub public_key: common::PublicKey,
+    /// The VP code
+    pub vp_code: Vec<u8>,
+    /// The source of the vested tokens
+    pub source: Address,
+    /// The schedule of the vested tokens
+    pub schedule: VestingSchedule,
+}
+
 /// A tx data type to initialize a new validator account.
 #[derive(
     Debug,
@@ -209,7 +234,7 @@ pub mod tx_types {
     use thiserror;
 
     use super::*;
-    use crate::proto::{SignedTxData, Tx};
+    use crate::proto::{SignedTxData, Tx, TxCode};
     use crate::types::transaction::protocol::ProtocolTx;
 
     /// Errors relating to decrypting a wrapper tx and its
@@ -299,7 +324,7 @@ pub mod tx_types {
             }
             .hash();
             match TxType::try_from(Tx {
-                code: vec![],
+                code: TxCode::Wasm(vec![]),
                 data: Some(data),
                 timestamp: tx.timestamp,
             })
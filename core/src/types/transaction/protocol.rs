@@ -88,6 +88,12 @@ mod protocol_txs {
             pk: &common::PublicKey,
             signing_key: &common::SecretKey,
         ) -> Tx {
+            self.unsigned_tx(pk).sign(signing_key)
+        }
+
+        /// Wrap a ProtocolTxType up in a normal Tx that is yet to be signed
+        /// by the secret key of the given protocol public key
+        pub fn unsigned_tx(self, pk: &common::PublicKey) -> Tx {
             Tx::new(
                 vec![],
                 Some(
@@ -99,7 +105,6 @@ mod protocol_txs {
                     .expect("Could not serialize ProtocolTx"),
                 ),
             )
-            .sign(signing_key)
         }
 
         /// Create a new tx requesting a new DKG session keypair
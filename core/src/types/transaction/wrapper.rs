@@ -21,10 +21,11 @@ pub mod wrapper_tx {
         hash_tx, EncryptionKey, Hash, TxError, TxType,
     };
 
-    /// Minimum fee amount in micro NAMs
+    /// Minimum fee amount in micro NAMs per [`GAS_LIMIT_RESOLUTION`] units of
+    /// gas, used as the default minimum gas price of the native token
     pub const MIN_FEE: u64 = 100;
     /// TODO: Determine a sane number for this
-    const GAS_LIMIT_RESOLUTION: u64 = 1_000_000;
+    pub const GAS_LIMIT_RESOLUTION: u64 = 1_000_000;
 
     /// Errors relating to decrypting a wrapper tx and its
     /// encrypted payload from a Tx type
@@ -44,6 +45,8 @@ pub mod wrapper_tx {
              differs from that in the WrapperTx"
         )]
         InvalidKeyPair,
+        #[error("The fee payer's signature of the WrapperTx is invalid: {0}")]
+        InvalidFeePayerSig(String),
     }
 
     /// A fee is an amount of a specified token
@@ -64,6 +67,27 @@ pub mod wrapper_tx {
         pub token: Address,
     }
 
+    /// A third-party account paying the fee of a wrapper tx on behalf of its
+    /// signer
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        BorshSerialize,
+        BorshDeserialize,
+        BorshSchema,
+        Serialize,
+        Deserialize,
+    )]
+    pub struct FeePayer {
+        /// The address of the account paying the fee
+        pub address: Address,
+        /// The public key of the fee payer's account
+        pub pk: common::PublicKey,
+        /// The fee payer's signature of the wrapper tx
+        pub sig: common::Signature,
+    }
+
     /// Gas limits must be multiples of GAS_LIMIT_RESOLUTION
     /// This is done to minimize the amount of information leak from
     /// a wrapper tx. The larger the GAS_LIMIT_RESOLUTION, the
@@ -102,6 +126,15 @@ pub mod wrapper_tx {
             }
             .into()
         }
+
+        /// The minimum fee for this gas limit at the given gas price, which
+        /// is the amount of the fee token per [`GAS_LIMIT_RESOLUTION`] units
+        /// of gas. Returns `None` on overflow.
+        pub fn fee(&self, gas_price: Amount) -> Option<Amount> {
+            u64::from(gas_price)
+                .checked_mul(self.multiplier)
+                .map(Amount::from)
+        }
     }
 
     /// Round the input number up to the next highest multiple
@@ -133,21 +166,21 @@ pub mod wrapper_tx {
     /// Get back the gas limit as a raw number
     impl From<&GasLimit> for u64 {
         fn from(limit: &GasLimit) -> u64 {
-            limit.multiplier * GAS_LIMIT_RESOLUTION
+            limit.multiplier.saturating_mul(GAS_LIMIT_RESOLUTION)
         }
     }
 
     /// Get back the gas limit as a raw number
     impl From<GasLimit> for u64 {
         fn from(limit: GasLimit) -> u64 {
-            limit.multiplier * GAS_LIMIT_RESOLUTION
+            limit.multiplier.saturating_mul(GAS_LIMIT_RESOLUTION)
         }
     }
 
     /// Get back the gas limit as a raw number, viewed as an Amount
     impl From<GasLimit> for Amount {
         fn from(limit: GasLimit) -> Amount {
-            Amount::from(limit.multiplier * GAS_LIMIT_RESOLUTION)
+            Amount::from(limit.multiplier.saturating_mul(GAS_LIMIT_RESOLUTION))
         }
     }
 
@@ -166,8 +199,11 @@ pub mod wrapper_tx {
     pub struct WrapperTx {
         /// The fee to be payed for including the tx
         pub fee: Fee,
-        /// Used to determine an implicit account of the fee payer
+        /// Used to determine an implicit account of the fee payer, unless a
+        /// third-party fee payer is given
         pub pk: common::PublicKey,
+        /// A third-party account paying the fee, if any
+        pub fee_payer: Option<FeePayer>,
         /// The epoch in which the tx is to be submitted. This determines
         /// which decryption key will be used
         pub epoch: Epoch,
@@ -198,11 +234,38 @@ pub mod wrapper_tx {
             #[cfg(not(feature = "mainnet"))] pow_solution: Option<
                 crate::ledger::testnet_pow::Solution,
             >,
+        ) -> WrapperTx {
+            Self::new_with_pk(
+                fee,
+                keypair.ref_to(),
+                epoch,
+                gas_limit,
+                tx,
+                encryption_key,
+                #[cfg(not(feature = "mainnet"))]
+                pow_solution,
+            )
+        }
+
+        /// Create a new wrapper tx like [`WrapperTx::new`] from the public
+        /// key of its signer, whose secret key may be kept outside of this
+        /// process, e.g. on a hardware wallet
+        pub fn new_with_pk(
+            fee: Fee,
+            pk: common::PublicKey,
+            epoch: Epoch,
+            gas_limit: GasLimit,
+            tx: Tx,
+            encryption_key: EncryptionKey,
+            #[cfg(not(feature = "mainnet"))] pow_solution: Option<
+                crate::ledger::testnet_pow::Solution,
+            >,
         ) -> WrapperTx {
             let inner_tx = EncryptedTx::encrypt(&tx.to_bytes(), encryption_key);
             Self {
                 fee,
-                pk: keypair.ref_to(),
+                pk,
+                fee_payer: None,
                 epoch,
                 gas_limit,
                 inner_tx,
@@ -212,10 +275,76 @@ pub mod wrapper_tx {
             }
         }
 
-        /// Get the address of the implicit account associated
-        /// with the public key
+        /// Get the address of the third-party fee payer, if any, or else of
+        /// the implicit account associated with the public key
         pub fn fee_payer(&self) -> Address {
-            Address::from(&self.pk)
+            match &self.fee_payer {
+                Some(fee_payer) => fee_payer.address.clone(),
+                None => Address::from(&self.pk),
+            }
+        }
+
+        /// Get the data signed by a third-party fee payer, which is the
+        /// wrapper tx without the fee payer's signature
+        fn fee_payer_signed_data(&self) -> Vec<u8> {
+            let wrapper = Self {
+                fee_payer: None,
+                ..self.clone()
+            };
+            wrapper.try_to_vec().expect("Could not serialize WrapperTx")
+        }
+
+        /// Co-sign the wrapper tx with a key of a third-party account that
+        /// pays the fee. This must be done before the wrapper tx is signed.
+        pub fn sign_fee_payer(
+            mut self,
+            address: Address,
+            keypair: &common::SecretKey,
+        ) -> Self {
+            let sig =
+                common::SigScheme::sign(keypair, self.fee_payer_signed_data());
+            self.fee_payer = Some(FeePayer {
+                address,
+                pk: keypair.ref_to(),
+                sig,
+            });
+            self
+        }
+
+        /// Validate the signature of a third-party fee payer, if any. This
+        /// doesn't check that the public key belongs to the fee payer's
+        /// account, which depends on the storage.
+        pub fn validate_fee_payer_sig(&self) -> Result<(), WrapperTxErr> {
+            match &self.fee_payer {
+                Some(FeePayer { pk, sig, .. }) => {
+                    common::SigScheme::verify_signature_raw(
+                        pk,
+                        &self.fee_payer_signed_data(),
+                        sig,
+                    )
+                    .map_err(|err| {
+                        WrapperTxErr::InvalidFeePayerSig(err.to_string())
+                    })
+                }
+                None => Ok(()),
+            }
+        }
+
+        /// Get the part of the fee to be refunded to the fee payer after
+        /// the inner tx used the given amount of gas. The fee is paid for the
+        /// whole gas limit, so the refund is proportional to the unused gas
+        /// that's refunded by [`GasLimit::refund_amount`].
+        pub fn fee_refund(&self, used_gas: u64) -> Amount {
+            let gas_limit = u64::from(&self.gas_limit);
+            if gas_limit == 0 {
+                return Amount::default();
+            }
+            let refund_gas = u64::from(self.gas_limit.refund_amount(used_gas));
+            let refund = u128::from(self.fee.amount) * u128::from(refund_gas)
+                / u128::from(gas_limit);
+            // the refund gas cannot exceed the gas limit, so the refund cannot
+            // exceed the fee
+            Amount::from(refund as u64)
         }
 
         /// A validity check on the ciphertext.
@@ -254,7 +383,13 @@ pub mod wrapper_tx {
             if self.pk != keypair.ref_to() {
                 return Err(WrapperTxErr::InvalidKeyPair);
             }
-            Ok(Tx::new(
+            Ok(self.unsigned_tx().sign(keypair))
+        }
+
+        /// Convert to a normal Tx type that is yet to be signed by the key of
+        /// the wrapper's public key
+        pub fn unsigned_tx(&self) -> Tx {
+            Tx::new(
                 vec![],
                 Some(
                     TxType::Wrapper(self.clone())
@@ -262,7 +397,6 @@ pub mod wrapper_tx {
                         .expect("Could not serialize WrapperTx"),
                 ),
             )
-            .sign(keypair))
         }
 
         /// Validate the signature of a wrapper tx
@@ -341,6 +475,15 @@ pub mod wrapper_tx {
             let refund = limit.refund_amount(GAS_LIMIT_RESOLUTION + 1);
             assert_eq!(refund, Amount::from(0u64));
         }
+
+        /// Test that the minimum fee is the gas price per gas limit
+        /// resolution
+        #[test]
+        fn test_gas_limit_fee() {
+            let limit = GasLimit { multiplier: 3 };
+            assert_eq!(limit.fee(Amount::from(10)), Some(Amount::from(30)));
+            assert_eq!(limit.fee(Amount::max()), None);
+        }
     }
 
     #[cfg(test)]
@@ -495,6 +638,46 @@ pub mod wrapper_tx {
                 .expect_err("Test failed");
             assert_matches!(err, TxError::SigError(_));
         }
+
+        /// We test that a third-party fee payer's signature is validated and
+        /// that the fee payer's address is used to pay the fee.
+        #[test]
+        fn test_fee_payer_sig() {
+            let keypair = gen_keypair();
+            let fee_payer_keypair = gen_keypair();
+            let fee_payer = Address::from(&fee_payer_keypair.ref_to());
+            let tx = Tx::new(
+                "wasm code".as_bytes().to_owned(),
+                Some("transaction data".as_bytes().to_owned()),
+            );
+
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 10.into(),
+                    token: nam(),
+                },
+                &keypair,
+                Epoch(0),
+                0.into(),
+                tx,
+                Default::default(),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            assert_eq!(wrapper.fee_payer(), Address::from(&keypair.ref_to()));
+            wrapper.validate_fee_payer_sig().expect("Test failed");
+
+            let mut wrapper =
+                wrapper.sign_fee_payer(fee_payer.clone(), &fee_payer_keypair);
+            assert_eq!(wrapper.fee_payer(), fee_payer);
+            wrapper.validate_fee_payer_sig().expect("Test failed");
+
+            // the fee payer's signature must cover the fee
+            wrapper.fee.amount = 20.into();
+            let err =
+                wrapper.validate_fee_payer_sig().expect_err("Test failed");
+            assert_matches!(err, WrapperTxErr::InvalidFeePayerSig(_));
+        }
     }
 }
 
@@ -0,0 +1,138 @@
+//! Types for vesting accounts, whose tokens are unlocked over time.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+use super::address::Address;
+use super::storage::{DbKeySeg, Key, KeySeg};
+use super::time::DateTimeUtc;
+use super::token;
+
+/// The storage sub-key of an account's vesting schedule
+const VESTING_SCHEDULE_STORAGE_KEY: &str = "vesting_schedule";
+
+/// The schedule of the tokens locked in a vesting account. Nothing is unlocked
+/// before the `cliff`. The `amount` is unlocked linearly from the `start` to
+/// the `end` of the schedule, so at the `cliff`, all the tokens vested since
+/// the `start` are unlocked at once.
+#[derive(
+    Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, BorshSchema,
+)]
+pub struct VestingSchedule {
+    /// The address of the vested token
+    pub token: Address,
+    /// The total amount of vested tokens
+    pub amount: token::Amount,
+    /// The time from which the tokens start to vest
+    pub start: DateTimeUtc,
+    /// The time before which no tokens are unlocked
+    pub cliff: DateTimeUtc,
+    /// The time at which all the tokens are unlocked
+    pub end: DateTimeUtc,
+}
+
+impl VestingSchedule {
+    /// Check that the `cliff` is between the `start` and the `end` of the
+    /// schedule.
+    pub fn is_valid(&self) -> bool {
+        self.start <= self.cliff && self.cliff <= self.end
+    }
+
+    /// Get the amount of tokens that are still locked at the given time.
+    pub fn locked_amount(&self, time: DateTimeUtc) -> token::Amount {
+        if time < self.cliff || time < self.start {
+            return self.amount;
+        }
+        if time >= self.end {
+            return token::Amount::default();
+        }
+        let elapsed = (time.0 - self.start.0).num_seconds() as u128;
+        let duration = (self.end.0 - self.start.0).num_seconds() as u128;
+        let unlocked = u128::from(self.amount) * elapsed / duration;
+        // The unlocked amount is never greater than the total amount, so the
+        // conversion can't fail
+        self.amount - token::Amount::from(unlocked as u64)
+    }
+}
+
+/// Obtain a storage key for the vesting schedule of the given account.
+pub fn vesting_schedule_key(owner: &Address) -> Key {
+    Key::from(owner.to_db_key())
+        .push(&VESTING_SCHEDULE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a vesting schedule key. If it is, returns
+/// the owner.
+pub fn is_vesting_schedule_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(owner), DbKeySeg::StringSeg(key)]
+            if key == VESTING_SCHEDULE_STORAGE_KEY =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::types::address;
+
+    fn schedule(cliff_days: i64) -> VestingSchedule {
+        let start = DateTimeUtc::now();
+        VestingSchedule {
+            token: address::nam(),
+            amount: token::Amount::whole(1_000),
+            start,
+            cliff: (start.0 + Duration::days(cliff_days)).into(),
+            end: (start.0 + Duration::days(100)).into(),
+        }
+    }
+
+    #[test]
+    fn test_locked_amount() {
+        let schedule = schedule(0);
+        assert!(schedule.is_valid());
+        let at = |days| DateTimeUtc(schedule.start.0 + Duration::days(days));
+
+        assert_eq!(schedule.locked_amount(at(-1)), schedule.amount);
+        assert_eq!(schedule.locked_amount(at(0)), schedule.amount);
+        assert_eq!(schedule.locked_amount(at(25)), token::Amount::whole(750));
+        assert_eq!(schedule.locked_amount(at(50)), token::Amount::whole(500));
+        assert_eq!(schedule.locked_amount(at(100)), token::Amount::default());
+        assert_eq!(schedule.locked_amount(at(200)), token::Amount::default());
+    }
+
+    #[test]
+    fn test_locked_amount_with_cliff() {
+        let schedule = schedule(50);
+        assert!(schedule.is_valid());
+        let at = |days| DateTimeUtc(schedule.start.0 + Duration::days(days));
+
+        assert_eq!(schedule.locked_amount(at(25)), schedule.amount);
+        assert_eq!(schedule.locked_amount(at(49)), schedule.amount);
+        assert_eq!(schedule.locked_amount(at(50)), token::Amount::whole(500));
+        assert_eq!(schedule.locked_amount(at(75)), token::Amount::whole(250));
+        assert_eq!(schedule.locked_amount(at(100)), token::Amount::default());
+    }
+
+    #[test]
+    fn test_invalid_schedule() {
+        assert!(!schedule(-1).is_valid());
+        assert!(!schedule(101).is_valid());
+    }
+
+    #[test]
+    fn test_vesting_schedule_key() {
+        let owner = address::testing::established_address_1();
+        let key = vesting_schedule_key(&owner);
+        assert_eq!(is_vesting_schedule_key(&key), Some(&owner));
+        assert_eq!(
+            is_vesting_schedule_key(&Key::from(owner.to_db_key())),
+            None
+        );
+    }
+}
@@ -152,6 +152,57 @@ pub fn validity_predicate(
     TokenStream::from(gen)
 }
 
+/// Generate WASM binding for a validity predicate read-only query entrypoint
+/// function, which can be called by other validity predicates.
+///
+/// This macro expects a function with signature:
+///
+/// ```compiler_fail
+/// fn query(
+///     ctx: &Ctx,
+///     input_data: Vec<u8>
+/// ) -> EnvResult<Vec<u8>>
+/// ```
+///
+/// The returned bytes are the Borsh-encoded result of the query.
+#[proc_macro_attribute]
+pub fn vp_query(_attr: TokenStream, input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as ItemFn);
+    let ident = &ast.sig.ident;
+    let gen = quote! {
+        #ast
+
+        // The query entrypoint callable by wasm runtime
+        #[no_mangle]
+        extern "C" fn _query(input_data_ptr: u64, input_data_len: u64) -> u64 {
+            let slice = unsafe {
+                core::slice::from_raw_parts(
+                    input_data_ptr as *const u8,
+                    input_data_len as _,
+                )
+            };
+            let input_data = slice.to_vec();
+
+            // The context on WASM side is only provided by the VM once its
+            // being executed (in here it's implicit), same as in the
+            // `#[validity_predicate]` entrypoint.
+            let ctx = unsafe { namada_vp_prelude::Ctx::new() };
+
+            match #ident(&ctx, input_data) {
+                Ok(result) => {
+                    namada_vp_prelude::set_query_result(&result);
+                    1
+                }
+                Err(err) => {
+                    namada_vp_prelude::debug_log!("VP query error: {}", err);
+                    0
+                },
+            }
+        }
+    };
+    TokenStream::from(gen)
+}
+
 #[proc_macro_derive(StorageKeys)]
 pub fn derive_storage_keys(struct_def: TokenStream) -> TokenStream {
     derive_storage_keys_inner(struct_def.into()).into()
@@ -366,6 +366,11 @@ where
         }
     }
 
+    /// Return the number of past epochs to keep data for
+    pub fn get_num_past_epochs() -> u64 {
+        NUM_PAST_EPOCHS
+    }
+
     /// init at genesis
     pub fn init_at_genesis<S>(
         &self,
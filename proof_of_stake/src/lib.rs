@@ -676,7 +676,7 @@ pub fn is_validator<S>(
     epoch: namada_core::types::storage::Epoch,
 ) -> storage_api::Result<bool>
 where
-    S: StorageRead + StorageWrite,
+    S: StorageRead,
 {
     let state = validator_state_handle(address).get(storage, epoch, params)?;
     Ok(state.is_some())
@@ -714,21 +714,18 @@ where
     }
 }
 
-/// Self-bond tokens to a validator when `source` is `None` or equal to
-/// the `validator` address, or delegate tokens from the `source` to the
-/// `validator`.
-pub fn bond_tokens<S>(
-    storage: &mut S,
+/// Check that tokens can be bonded from the `source` to the `validator` in the
+/// current epoch, without writing anything. Returns the same error as
+/// [`bond_tokens`] would.
+pub fn check_bond<S>(
+    storage: &S,
     source: Option<&Address>,
     validator: &Address,
-    amount: token::Amount,
     current_epoch: Epoch,
 ) -> storage_api::Result<()>
 where
-    S: StorageRead + StorageWrite,
+    S: StorageRead,
 {
-    let amount = amount.change();
-    tracing::debug!("Bonding token amount {amount} at epoch {current_epoch}");
     let params = read_pos_params(storage)?;
     let pipeline_epoch = current_epoch + params.pipeline_len;
     if let Some(source) = source {
@@ -740,19 +737,12 @@ where
             );
         }
     }
-    let state = validator_state_handle(validator).get(
-        storage,
-        pipeline_epoch,
-        &params,
-    )?;
+    let validator_state_handle = validator_state_handle(validator);
+    let state = validator_state_handle.get(storage, pipeline_epoch, &params)?;
     if state.is_none() {
         return Err(BondError::NotAValidator(validator.clone()).into());
     }
 
-    let validator_state_handle = validator_state_handle(validator);
-    let source = source.unwrap_or(validator);
-    let bond_handle = bond_handle(source, validator);
-
     // Check that validator is not inactive at anywhere between the current
     // epoch and pipeline offset
     for epoch in current_epoch.iter_range(params.pipeline_len) {
@@ -762,6 +752,29 @@ where
             return Err(BondError::InactiveValidator(validator.clone()).into());
         }
     }
+    Ok(())
+}
+
+/// Self-bond tokens to a validator when `source` is `None` or equal to
+/// the `validator` address, or delegate tokens from the `source` to the
+/// `validator`.
+pub fn bond_tokens<S>(
+    storage: &mut S,
+    source: Option<&Address>,
+    validator: &Address,
+    amount: token::Amount,
+    current_epoch: Epoch,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let amount = amount.change();
+    tracing::debug!("Bonding token amount {amount} at epoch {current_epoch}");
+    let params = read_pos_params(storage)?;
+    check_bond(storage, source, validator, current_epoch)?;
+
+    let source = source.unwrap_or(validator);
+    let bond_handle = bond_handle(source, validator);
 
     // Initialize or update the bond at the pipeline offset
     let offset = params.pipeline_len;
@@ -1316,27 +1329,21 @@ where
     Ok(())
 }
 
-/// Unbond.
-pub fn unbond_tokens<S>(
-    storage: &mut S,
+/// Check that the amount can be unbonded from the bond of the `source` to the
+/// `validator` in the current epoch, without writing anything. Returns the
+/// same error as [`unbond_tokens`] would.
+pub fn check_unbond<S>(
+    storage: &S,
     source: Option<&Address>,
     validator: &Address,
     amount: token::Amount,
     current_epoch: Epoch,
 ) -> storage_api::Result<()>
 where
-    S: StorageRead + StorageWrite,
+    S: StorageRead,
 {
-    let amount = amount.change();
-    tracing::debug!("Unbonding token amount {amount} at epoch {current_epoch}");
     let params = read_pos_params(storage)?;
     let pipeline_epoch = current_epoch + params.pipeline_len;
-    tracing::debug!(
-        "Current validator stake at pipeline: {}",
-        read_validator_stake(storage, &params, validator, pipeline_epoch)?
-            .unwrap_or_default()
-    );
-
     if let Some(source) = source {
         if source != validator
             && is_validator(storage, source, &params, pipeline_epoch)?
@@ -1361,22 +1368,53 @@ where
     // Err(BondError::InactiveValidator(validator.clone()).into());     }
     // }
 
-    let source = source.unwrap_or(validator);
-    let _bond_amount_handle = bond_handle(source, validator);
-    let bond_remain_handle = bond_handle(source, validator);
-
     // Make sure there are enough tokens left in the bond at the pipeline offset
-    let pipeline_epoch = current_epoch + params.pipeline_len;
-    let remaining_at_pipeline = bond_remain_handle
+    let source = source.unwrap_or(validator);
+    let remaining_at_pipeline = bond_handle(source, validator)
         .get_sum(storage, pipeline_epoch, &params)?
         .unwrap_or_default();
-    if amount > remaining_at_pipeline {
+    if amount.change() > remaining_at_pipeline {
         return Err(UnbondError::UnbondAmountGreaterThanBond(
-            token::Amount::from_change(amount),
+            amount,
             token::Amount::from_change(remaining_at_pipeline),
         )
         .into());
     }
+    Ok(())
+}
+
+/// Unbond.
+pub fn unbond_tokens<S>(
+    storage: &mut S,
+    source: Option<&Address>,
+    validator: &Address,
+    amount: token::Amount,
+    current_epoch: Epoch,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let amount = amount.change();
+    tracing::debug!("Unbonding token amount {amount} at epoch {current_epoch}");
+    let params = read_pos_params(storage)?;
+    let pipeline_epoch = current_epoch + params.pipeline_len;
+    tracing::debug!(
+        "Current validator stake at pipeline: {}",
+        read_validator_stake(storage, &params, validator, pipeline_epoch)?
+            .unwrap_or_default()
+    );
+
+    check_unbond(
+        storage,
+        source,
+        validator,
+        token::Amount::from_change(amount),
+        current_epoch,
+    )?;
+
+    let source = source.unwrap_or(validator);
+    let _bond_amount_handle = bond_handle(source, validator);
+    let bond_remain_handle = bond_handle(source, validator);
 
     // Iterate thru this, find non-zero delta entries starting from most recent,
     // then just start decrementing those values For every delta val that
@@ -1568,6 +1606,47 @@ where
     S: StorageRead + StorageWrite,
 {
     tracing::debug!("Withdrawing tokens in epoch {current_epoch}");
+    let (withdrawable_amount, unbonds_to_remove) =
+        withdrawable_unbonds(storage, source, validator, current_epoch)?;
+    tracing::debug!("Withdrawing total {withdrawable_amount}");
+
+    // Remove the unbond data from storage
+    let source = source.unwrap_or(validator);
+    let unbond_handle = unbond_handle(source, validator);
+    for (withdraw_epoch, start_epoch, _) in unbonds_to_remove {
+        tracing::debug!("Remove ({start_epoch}..{withdraw_epoch}) from unbond");
+        unbond_handle
+            .at(&withdraw_epoch)
+            .remove(storage, &start_epoch)?;
+        // TODO: check if the `end_epoch` layer is now empty and remove it if
+        // so, may need to implement remove/delete for nested map
+    }
+
+    // Transfer the tokens from the PoS address back to the source
+    transfer_tokens(
+        storage,
+        &staking_token_address(),
+        withdrawable_amount,
+        &ADDRESS,
+        source,
+    )?;
+
+    Ok(withdrawable_amount)
+}
+
+/// Find the unbonds of the `source` to the `validator` that are withdrawable
+/// in the current epoch, without writing anything. Returns the amount that
+/// would be withdrawn after slashing and the withdraw epoch, the start epoch
+/// and the amount of each unbond.
+pub fn withdrawable_unbonds<S>(
+    storage: &S,
+    source: Option<&Address>,
+    validator: &Address,
+    current_epoch: Epoch,
+) -> storage_api::Result<(token::Amount, Vec<(Epoch, Epoch, token::Amount)>)>
+where
+    S: StorageRead,
+{
     let params = read_pos_params(storage)?;
     let source = source.unwrap_or(validator);
 
@@ -1576,7 +1655,7 @@ where
 
     let mut slashed = token::Amount::default();
     let mut withdrawable_amount = token::Amount::default();
-    let mut unbonds_to_remove: Vec<(Epoch, Epoch)> = Vec::new();
+    let mut unbonds = Vec::new();
     // TODO: use `find_unbonds`
     let unbond_iter = unbond_handle.iter(storage)?;
     for unbond in unbond_iter {
@@ -1621,31 +1700,11 @@ where
             }
         }
         withdrawable_amount += amount;
-        unbonds_to_remove.push((withdraw_epoch, start_epoch));
+        unbonds.push((withdraw_epoch, start_epoch, amount));
     }
     withdrawable_amount -= slashed;
-    tracing::debug!("Withdrawing total {withdrawable_amount}");
 
-    // Remove the unbond data from storage
-    for (withdraw_epoch, start_epoch) in unbonds_to_remove {
-        tracing::debug!("Remove ({start_epoch}..{withdraw_epoch}) from unbond");
-        unbond_handle
-            .at(&withdraw_epoch)
-            .remove(storage, &start_epoch)?;
-        // TODO: check if the `end_epoch` layer is now empty and remove it if
-        // so, may need to implement remove/delete for nested map
-    }
-
-    // Transfer the tokens from the PoS address back to the source
-    transfer_tokens(
-        storage,
-        &staking_token_address(),
-        withdrawable_amount,
-        &ADDRESS,
-        source,
-    )?;
-
-    Ok(withdrawable_amount)
+    Ok((withdrawable_amount, unbonds))
 }
 
 /// Change the commission rate of a validator
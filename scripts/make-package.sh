@@ -8,7 +8,7 @@ set -e
 VERSION="$(git describe --dirty --broken)"
 PLATFORM="$(uname -s)-$(uname -m)"
 PACKAGE_NAME="namada-${VERSION}-${PLATFORM}"
-BIN="namada namadac namadan namadaw"
+BIN="namada namadac namadan namadaw namada-signer"
 
 mkdir -p ${PACKAGE_NAME} && \
 cd target/release && \
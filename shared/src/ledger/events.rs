@@ -11,6 +11,7 @@ use thiserror::Error;
 
 use crate::ledger::native_vp::governance::utils::ProposalEvent;
 use crate::tendermint_proto::abci::EventAttribute;
+use crate::types::event::TxEvent;
 use crate::types::ibc::IbcEvent;
 #[cfg(feature = "ferveo-tpke")]
 use crate::types::transaction::{hash_tx, TxType};
@@ -49,6 +50,11 @@ pub enum EventType {
     Ibc(String),
     /// The proposal that has been executed
     Proposal,
+    /// The custom event emitted by a transaction, of which type is in the
+    /// `tx/` namespace
+    Custom(String),
+    /// The result of a call of a batch transaction
+    BatchCall,
 }
 
 impl Display for EventType {
@@ -58,6 +64,8 @@ impl Display for EventType {
             EventType::Applied => write!(f, "applied"),
             EventType::Ibc(t) => write!(f, "{}", t),
             EventType::Proposal => write!(f, "proposal"),
+            EventType::Custom(t) => write!(f, "{}", t),
+            EventType::BatchCall => write!(f, "batch_call"),
         }?;
         Ok(())
     }
@@ -112,6 +120,23 @@ impl Event {
         event
     }
 
+    /// Creates a new event for the call at the given index of the batch
+    /// transaction of the given event, with its hash and height filled in
+    pub fn new_batch_call_event(tx_event: &Event, index: usize) -> Self {
+        let mut event = Event {
+            event_type: EventType::BatchCall,
+            level: EventLevel::Tx,
+            attributes: HashMap::new(),
+        };
+        for key in ["hash", "height"] {
+            if let Some(value) = tx_event.get(key) {
+                event[key] = value.clone();
+            }
+        }
+        event["index"] = index.to_string();
+        event
+    }
+
     /// Check if the events keys contains a given string
     pub fn contains_key(&self, key: &str) -> bool {
         self.attributes.contains_key(key)
@@ -151,6 +176,16 @@ impl From<IbcEvent> for Event {
     }
 }
 
+impl From<TxEvent> for Event {
+    fn from(tx_event: TxEvent) -> Self {
+        Self {
+            event_type: EventType::Custom(tx_event.namespaced_type()),
+            level: EventLevel::Tx,
+            attributes: tx_event.attributes,
+        }
+    }
+}
+
 impl From<ProposalEvent> for Event {
     fn from(proposal_event: ProposalEvent) -> Self {
         Self {
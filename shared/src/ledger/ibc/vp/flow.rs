@@ -0,0 +1,338 @@
+//! IBC validity predicate for token flows and their limits
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use borsh::BorshDeserialize;
+use namada_core::ledger::ibc::actions::FlowDirection;
+use namada_core::ledger::ibc::storage::{
+    calc_hash, flow_key, flow_limit_key, ibc_denom_key, ibc_prefix,
+    token_hash_from_denom, IbcPrefix,
+};
+use thiserror::Error;
+
+use super::Ibc;
+use crate::ibc::applications::ics20_fungible_token_transfer::msgs::transfer::MsgTransfer;
+use crate::ibc::core::ics04_channel::msgs::PacketMsg;
+use crate::ibc::core::ics04_channel::packet::Packet;
+use crate::ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::ibc::core::ics26_routing::msgs::Ics26Envelope;
+use crate::ledger::native_vp::governance::utils::is_proposal_accepted;
+use crate::ledger::native_vp::{self, Ctx, VpEnv};
+use crate::ledger::storage::{self as ledger_storage, StorageHasher};
+use crate::types::ibc::data::{
+    Error as IbcDataError, FungibleTokenPacketData, IbcMessage, PacketAck,
+};
+use crate::types::ibc::{IbcFlow, IbcFlowLimit};
+use crate::types::storage::Key;
+use crate::types::token::Amount;
+use crate::vm::WasmCacheAccess;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Native VP error: {0}")]
+    NativeVpError(native_vp::Error),
+    #[error("IBC data error: {0}")]
+    InvalidIbcData(IbcDataError),
+    #[error("Invalid packet data: {0}")]
+    PacketData(String),
+    #[error("Denom error: {0}")]
+    Denom(String),
+    #[error("Decoding error: {0}")]
+    Decoding(std::io::Error),
+    #[error("Flow limit error: {0}")]
+    FlowLimit(String),
+    #[error("Flow error: {0}")]
+    Flow(String),
+}
+
+/// IBC flow functions result
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl<'a, DB, H, CA> Ibc<'a, DB, H, CA>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    /// Flow limits can be updated only by an accepted governance proposal
+    /// which doesn't touch any other IBC state
+    pub(super) fn validate_flow_limits(
+        &self,
+        tx_data: &[u8],
+        keys_changed: &BTreeSet<Key>,
+    ) -> Result<()> {
+        for key in keys_changed {
+            match ibc_prefix(key) {
+                Some(IbcPrefix::FlowLimit) => {
+                    if let Some(bytes) = self.ctx.read_bytes_post(key)? {
+                        IbcFlowLimit::try_from_slice(&bytes)
+                            .map_err(Error::Decoding)?;
+                    }
+                }
+                Some(_) => {
+                    return Err(Error::FlowLimit(format!(
+                        "The flow limit can't be updated with other IBC \
+                         state: Key {}",
+                        key
+                    )));
+                }
+                None => {}
+            }
+        }
+        if is_proposal_accepted(&self.ctx.pre(), tx_data)? {
+            Ok(())
+        } else {
+            Err(Error::FlowLimit(
+                "The flow limit can be updated only by governance".to_owned(),
+            ))
+        }
+    }
+
+    pub(super) fn validate_flow(
+        &self,
+        key: &Key,
+        tx_data: &[u8],
+        keys_changed: &BTreeSet<Key>,
+    ) -> Result<()> {
+        let ibc_msg = IbcMessage::decode(tx_data)?;
+        let flow = token_flow(&self.ctx, &ibc_msg)?.ok_or_else(|| {
+            Error::Flow(format!(
+                "The flow was updated without a token transfer: Key {}",
+                key
+            ))
+        })?;
+        if *key != flow.key() {
+            return Err(Error::Flow(format!(
+                "The flow key is mismatched: Key {}",
+                key
+            )));
+        }
+        validate_flow_update(&self.ctx, &flow, keys_changed)
+    }
+}
+
+/// A token flow over a channel made by an IBC message
+pub(super) struct TokenFlow {
+    port_id: PortId,
+    channel_id: ChannelId,
+    denom: String,
+    amount: Amount,
+    direction: FlowDirection,
+    /// Whether the amount of a sent packet is refunded, which decreases the
+    /// outflow
+    is_refund: bool,
+}
+
+impl TokenFlow {
+    fn key(&self) -> Key {
+        flow_key(&self.port_id, &self.channel_id, calc_hash(&self.denom))
+    }
+}
+
+/// Get the token flow of the IBC message, if it moves tokens over a channel
+pub(super) fn token_flow<DB, H, CA>(
+    ctx: &Ctx<'_, DB, H, CA>,
+    ibc_msg: &IbcMessage,
+) -> Result<Option<TokenFlow>>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    match &ibc_msg.0 {
+        Ics26Envelope::Ics20Msg(msg) => outflow(ctx, msg).map(Some),
+        Ics26Envelope::Ics4PacketMsg(PacketMsg::RecvPacket(msg)) => {
+            inflow(&msg.packet).map(Some)
+        }
+        Ics26Envelope::Ics4PacketMsg(PacketMsg::AckPacket(msg)) => {
+            let ack = PacketAck::try_from(msg.acknowledgement.clone())?;
+            if ack.is_success() {
+                Ok(None)
+            } else {
+                refund(&msg.packet)
+            }
+        }
+        Ics26Envelope::Ics4PacketMsg(PacketMsg::ToPacket(msg)) => {
+            refund(&msg.packet)
+        }
+        Ics26Envelope::Ics4PacketMsg(PacketMsg::ToClosePacket(msg)) => {
+            refund(&msg.packet)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Check that the flow of the denom on the channel is updated with the amount
+/// within the limit. The update can be omitted only when the flow in that
+/// direction isn't limited.
+pub(super) fn validate_flow_update<DB, H, CA>(
+    ctx: &Ctx<'_, DB, H, CA>,
+    flow: &TokenFlow,
+    keys_changed: &BTreeSet<Key>,
+) -> Result<()>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    let TokenFlow {
+        port_id,
+        channel_id,
+        denom,
+        amount,
+        direction,
+        is_refund,
+    } = flow;
+    let key = flow.key();
+    let limit_key = flow_limit_key(port_id, channel_id, calc_hash(denom));
+    let limit: IbcFlowLimit = ctx.read_pre(&limit_key)?.unwrap_or_default();
+    if !keys_changed.contains(&key) {
+        let is_limited = match direction {
+            FlowDirection::In => limit.inflow.is_some(),
+            FlowDirection::Out => limit.outflow.is_some(),
+        };
+        return if is_limited {
+            Err(Error::Flow(format!(
+                "The {} of {} on {}/{} is limited but it isn't recorded: \
+                 amount {}",
+                direction, denom, port_id, channel_id, amount
+            )))
+        } else {
+            Ok(())
+        };
+    }
+
+    let epoch = ctx.get_block_epoch()?;
+    let pre: IbcFlow = ctx.read_pre(&key)?.unwrap_or_default();
+    let pre = pre.at_epoch(epoch);
+    let expected = match direction {
+        FlowDirection::In => pre.checked_add_inflow(*amount, &limit),
+        FlowDirection::Out if *is_refund => Some(pre.sub_outflow(*amount)),
+        FlowDirection::Out => pre.checked_add_outflow(*amount, &limit),
+    }
+    .ok_or_else(|| {
+        Error::FlowLimit(format!(
+            "The {} of {} on {}/{} exceeds the limit: amount {}",
+            direction, denom, port_id, channel_id, amount
+        ))
+    })?;
+    let post: Option<IbcFlow> = ctx.read_post(&key)?;
+    if post == Some(expected) {
+        Ok(())
+    } else {
+        Err(Error::Flow(format!(
+            "The flow isn't updated correctly: Key {}",
+            key
+        )))
+    }
+}
+
+fn outflow<DB, H, CA>(
+    ctx: &Ctx<'_, DB, H, CA>,
+    msg: &MsgTransfer,
+) -> Result<TokenFlow>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    let mut data = FungibleTokenPacketData::from(msg.clone());
+    if let Some(token_hash) = token_hash_from_denom(&data.denom)
+        .map_err(|e| Error::Denom(e.to_string()))?
+    {
+        let denom_key = ibc_denom_key(token_hash);
+        let denom_bytes = ctx.read_bytes_pre(&denom_key)?.ok_or_else(|| {
+            Error::Denom(format!("No original denom: denom_key {}", denom_key))
+        })?;
+        data.denom = std::str::from_utf8(&denom_bytes)
+            .map_err(|e| {
+                Error::Denom(format!(
+                    "Decoding the denom failed: denom_key {}, error {}",
+                    denom_key, e
+                ))
+            })?
+            .to_string();
+    }
+    let amount = Amount::from_str(&data.amount).map_err(|e| {
+        Error::PacketData(format!(
+            "Invalid amount: amount {}, error {}",
+            data.amount, e
+        ))
+    })?;
+    Ok(TokenFlow {
+        port_id: msg.source_port.clone(),
+        channel_id: msg.source_channel,
+        denom: data.denom,
+        amount,
+        direction: FlowDirection::Out,
+        is_refund: false,
+    })
+}
+
+fn inflow(packet: &Packet) -> Result<TokenFlow> {
+    let data: FungibleTokenPacketData = serde_json::from_slice(&packet.data)
+        .map_err(|e| {
+            Error::PacketData(format!("unknown packet data: error {}", e))
+        })?;
+    let amount = Amount::from_str(&data.amount).map_err(|e| {
+        Error::PacketData(format!(
+            "Invalid amount: amount {}, error {}",
+            data.amount, e
+        ))
+    })?;
+    // the denom on this chain
+    let prefix = format!("{}/{}/", packet.source_port, packet.source_channel);
+    let denom = match data.denom.strip_prefix(&prefix) {
+        Some(denom) => denom.to_string(),
+        None => format!(
+            "{}/{}/{}",
+            packet.destination_port, packet.destination_channel, data.denom
+        ),
+    };
+    Ok(TokenFlow {
+        port_id: packet.destination_port.clone(),
+        channel_id: packet.destination_channel,
+        denom,
+        amount,
+        direction: FlowDirection::In,
+        is_refund: false,
+    })
+}
+
+/// Get the refunded outflow of a sent packet, if it transferred a token
+fn refund(packet: &Packet) -> Result<Option<TokenFlow>> {
+    // Only the packets of a token transfer are refunded
+    let data: FungibleTokenPacketData =
+        match serde_json::from_slice(&packet.data) {
+            Ok(data) => data,
+            Err(_) => return Ok(None),
+        };
+    let amount = Amount::from_str(&data.amount).map_err(|e| {
+        Error::PacketData(format!(
+            "Invalid amount: amount {}, error {}",
+            data.amount, e
+        ))
+    })?;
+    Ok(Some(TokenFlow {
+        port_id: packet.source_port.clone(),
+        channel_id: packet.source_channel,
+        denom: data.denom,
+        amount,
+        direction: FlowDirection::Out,
+        is_refund: true,
+    }))
+}
+
+impl From<native_vp::Error> for Error {
+    fn from(err: native_vp::Error) -> Self {
+        Self::NativeVpError(err)
+    }
+}
+
+impl From<IbcDataError> for Error {
+    fn from(err: IbcDataError) -> Self {
+        Self::InvalidIbcData(err)
+    }
+}
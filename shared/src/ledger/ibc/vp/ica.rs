@@ -0,0 +1,563 @@
+//! IBC interchain account validation as a native validity predicate
+
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+
+use borsh::BorshDeserialize;
+use namada_core::ledger::ibc::actions::{
+    get_connection_id_from_channel, ica_controller_port_id, ica_owner,
+    interchain_account_address, is_ica_controller_port,
+    packet_from_ica_message, Error as IbcError, ICA_HOST_PORT_ID,
+};
+use namada_core::ledger::ibc::storage::{
+    channel_key, ica_channel_key, ica_connection_port_id, receipt_key,
+    Error as IbcStorageError,
+};
+use thiserror::Error;
+
+use super::Ibc;
+use crate::ibc::core::ics04_channel::channel::{ChannelEnd, Counterparty};
+use crate::ibc::core::ics04_channel::msgs::ChannelMsg;
+use crate::ibc::core::ics04_channel::packet::{Packet, Sequence};
+use crate::ibc::core::ics24_host::identifier::{
+    ChannelId, ConnectionId, PortChannelId, PortId,
+};
+use crate::ibc::core::ics26_routing::msgs::Ics26Envelope;
+use crate::ledger::native_vp::{self, Ctx, NativeVp, VpEnv};
+use crate::ledger::pos::namada_proof_of_stake::withdrawable_unbonds;
+use crate::ledger::pos::{self, is_bond_key, is_unbond_key};
+use crate::ledger::storage::{self as ledger_storage, StorageHasher};
+use crate::proto::SignedTxData;
+use crate::tendermint_proto::Protobuf;
+use crate::types::address::{Address, InternalAddress};
+use crate::types::ibc::data::{
+    Error as IbcDataError, IbcMessage, InterchainAccountMsg,
+    InterchainAccountPacketData, MsgSendIcaTx,
+};
+use crate::types::key::{common, pk_key};
+use crate::types::storage::Key;
+use crate::types::token::{self, Amount, Change};
+use crate::vm::WasmCacheAccess;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Native VP error: {0}")]
+    NativeVpError(native_vp::Error),
+    #[error("IBC data error: {0}")]
+    InvalidIbcData(IbcDataError),
+    #[error("IBC error: {0}")]
+    Ibc(IbcError),
+    #[error("IBC storage error: {0}")]
+    IbcStorage(IbcStorageError),
+    #[error("Decoding error: {0}")]
+    Decoding(std::io::Error),
+    #[error("Decoding PacketData error: {0}")]
+    DecodingPacketData(serde_json::Error),
+    #[error("IBC message is required as transaction data")]
+    NoTxData,
+    #[error("Channel error: {0}")]
+    InvalidChannel(String),
+    #[error("Packet error: {0}")]
+    InvalidPacket(String),
+    #[error("Owner error: {0}")]
+    InvalidOwner(String),
+    #[error("Interchain account error: {0}")]
+    InvalidAccount(String),
+}
+
+/// Result for IBC interchain account functions
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Interchain account native VP. The account can be debited and its bonds can
+/// be updated only by the messages in a packet from the controller chain, and
+/// only exactly as the messages.
+pub struct InterchainAccount<'a, DB, H, CA>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    /// Context to interact with the host structures.
+    pub ctx: Ctx<'a, DB, H, CA>,
+}
+
+impl<'a, DB, H, CA> NativeVp for InterchainAccount<'a, DB, H, CA>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    type Error = Error;
+
+    const ADDR: InternalAddress =
+        InternalAddress::InterchainAccount(String::new());
+
+    fn validate_tx(
+        &self,
+        tx_data: &[u8],
+        keys_changed: &BTreeSet<Key>,
+        _verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        let account = self.ctx.address;
+        let mut actual = Changes::default();
+        let mut is_debited = false;
+        for key in keys_changed {
+            let owner = token::is_any_token_balance_key(key).or_else(|| {
+                token::is_any_multitoken_balance_key(key).map(|(_, o)| o)
+            });
+            if let Some(owner) = owner {
+                if owner == account {
+                    let change = self.balance_change(key)?;
+                    is_debited |= change < 0;
+                    actual.balances.insert(key.clone(), change);
+                }
+                continue;
+            }
+            if let Some((bond_id, _)) = is_bond_key(key) {
+                if &bond_id.source == account {
+                    let pre: Change =
+                        self.ctx.read_pre(key)?.unwrap_or_default();
+                    let post: Change =
+                        self.ctx.read_post(key)?.unwrap_or_default();
+                    *actual.bonds.entry(bond_id.validator).or_default() +=
+                        post - pre;
+                }
+                continue;
+            }
+            if let Some((bond_id, _, _)) = is_unbond_key(key) {
+                if &bond_id.source == account {
+                    let pre: Amount =
+                        self.ctx.read_pre(key)?.unwrap_or_default();
+                    let post: Amount =
+                        self.ctx.read_post(key)?.unwrap_or_default();
+                    *actual.unbonds.entry(bond_id.validator).or_default() +=
+                        post.change() - pre.change();
+                }
+                continue;
+            }
+            if key.segments.first() == Some(&account.to_db_key()) {
+                return Err(Error::InvalidAccount(format!(
+                    "The key of the interchain account can't be updated: Key \
+                     {}",
+                    key
+                )));
+            }
+        }
+        if !is_debited && actual.bonds.is_empty() && actual.unbonds.is_empty() {
+            // only credits to the account
+            return Ok(true);
+        }
+
+        let signed =
+            SignedTxData::try_from_slice(tx_data).map_err(Error::Decoding)?;
+        let tx_data = &signed.data.ok_or(Error::NoTxData)?;
+        let msgs = self.received_msgs(tx_data)?;
+        let expected = self.expected_changes(&msgs)?;
+
+        // the balances of the account and the balances updated by the
+        // messages should be changed exactly as the messages
+        let balance_keys: BTreeSet<&Key> = actual
+            .balances
+            .keys()
+            .chain(expected.balances.keys())
+            .collect();
+        for key in balance_keys {
+            let change = match actual.balances.get(key) {
+                Some(change) => *change,
+                None => self.balance_change(key)?,
+            };
+            let expected_change =
+                expected.balances.get(key).cloned().unwrap_or_default();
+            if change != expected_change {
+                return Err(Error::InvalidAccount(format!(
+                    "The balance change doesn't match the messages: Key {}, \
+                     change {}, expected {}",
+                    key, change, expected_change
+                )));
+            }
+        }
+        // the bonds and unbonds of the account should be changed exactly as
+        // the messages
+        for (changes, expected_changes) in [
+            (&actual.bonds, &expected.bonds),
+            (&actual.unbonds, &expected.unbonds),
+        ] {
+            let validators: BTreeSet<&Address> =
+                changes.keys().chain(expected_changes.keys()).collect();
+            for validator in validators {
+                let change =
+                    changes.get(validator).cloned().unwrap_or_default();
+                let expected_change = expected_changes
+                    .get(validator)
+                    .cloned()
+                    .unwrap_or_default();
+                if change != expected_change {
+                    return Err(Error::InvalidAccount(format!(
+                        "The staking change doesn't match the messages: \
+                         Validator {}, change {}, expected {}",
+                        validator, change, expected_change
+                    )));
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// The changes of the balances, and the bonds and unbonds to each validator
+#[derive(Default)]
+struct Changes {
+    balances: HashMap<Key, Change>,
+    bonds: HashMap<Address, Change>,
+    unbonds: HashMap<Address, Change>,
+}
+
+impl<'a, DB, H, CA> InterchainAccount<'a, DB, H, CA>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    /// Returns the change of the balance
+    fn balance_change(&self, key: &Key) -> Result<Change> {
+        let pre: Amount = self.ctx.read_pre(key)?.unwrap_or_default();
+        let post: Amount = self.ctx.read_post(key)?.unwrap_or_default();
+        Ok(post.change() - pre.change())
+    }
+
+    /// Returns the changes which the messages should make
+    fn expected_changes(
+        &self,
+        msgs: &[InterchainAccountMsg],
+    ) -> Result<Changes> {
+        let account = self.ctx.address;
+        let native_token = self.ctx.get_native_token()?;
+        let current_epoch = self.ctx.get_block_epoch()?;
+        let account_native_key = token::balance_key(&native_token, account);
+        let pos_native_key = token::balance_key(&native_token, &pos::ADDRESS);
+        let mut changes = Changes::default();
+        let mut withdrawn = BTreeSet::new();
+        for msg in msgs {
+            match msg {
+                InterchainAccountMsg::Transfer {
+                    token,
+                    sub_prefix,
+                    target,
+                    amount,
+                } => {
+                    let (src_key, dest_key) = match sub_prefix {
+                        Some(sub_prefix) => {
+                            let prefix = token::multitoken_balance_prefix(
+                                token, sub_prefix,
+                            );
+                            (
+                                token::multitoken_balance_key(&prefix, account),
+                                token::multitoken_balance_key(&prefix, target),
+                            )
+                        }
+                        None => (
+                            token::balance_key(token, account),
+                            token::balance_key(token, target),
+                        ),
+                    };
+                    *changes.balances.entry(src_key).or_default() -=
+                        amount.change();
+                    *changes.balances.entry(dest_key).or_default() +=
+                        amount.change();
+                }
+                InterchainAccountMsg::Bond { validator, amount } => {
+                    *changes
+                        .balances
+                        .entry(account_native_key.clone())
+                        .or_default() -= amount.change();
+                    *changes
+                        .balances
+                        .entry(pos_native_key.clone())
+                        .or_default() += amount.change();
+                    *changes.bonds.entry(validator.clone()).or_default() +=
+                        amount.change();
+                }
+                InterchainAccountMsg::Unbond { validator, amount } => {
+                    *changes.bonds.entry(validator.clone()).or_default() -=
+                        amount.change();
+                    *changes.unbonds.entry(validator.clone()).or_default() +=
+                        amount.change();
+                }
+                InterchainAccountMsg::Withdraw { validator } => {
+                    // the following withdrawals withdraw nothing
+                    if !withdrawn.insert(validator) {
+                        continue;
+                    }
+                    let (amount, unbonds) = withdrawable_unbonds(
+                        &self.ctx.pre(),
+                        Some(account),
+                        validator,
+                        current_epoch,
+                    )?;
+                    *changes
+                        .balances
+                        .entry(account_native_key.clone())
+                        .or_default() += amount.change();
+                    *changes
+                        .balances
+                        .entry(pos_native_key.clone())
+                        .or_default() -= amount.change();
+                    let unbonded: Change = unbonds
+                        .iter()
+                        .map(|(_, _, amount)| amount.change())
+                        .sum();
+                    *changes.unbonds.entry(validator.clone()).or_default() -=
+                        unbonded;
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Returns the messages for this account in the packet received by this
+    /// transaction
+    fn received_msgs(
+        &self,
+        tx_data: &[u8],
+    ) -> Result<Vec<InterchainAccountMsg>> {
+        let msg = IbcMessage::decode(tx_data)?.msg_recv_packet()?;
+        let packet = &msg.packet;
+        if packet.destination_port.as_str() != ICA_HOST_PORT_ID {
+            return Err(Error::InvalidPacket(format!(
+                "The packet isn't for the interchain account host: Port {}",
+                packet.destination_port
+            )));
+        }
+        // the packet should be received just now, the IBC VP checks its proof
+        let receipt_key = receipt_key(
+            &packet.destination_port,
+            &packet.destination_channel,
+            packet.sequence,
+        );
+        if self.ctx.has_key_pre(&receipt_key)?
+            || !self.ctx.has_key_post(&receipt_key)?
+        {
+            return Err(Error::InvalidPacket(format!(
+                "The packet isn't received by this transaction: Key {}",
+                receipt_key
+            )));
+        }
+
+        let port_channel_id = PortChannelId {
+            port_id: packet.destination_port.clone(),
+            channel_id: packet.destination_channel,
+        };
+        let channel_key = channel_key(&port_channel_id);
+        let channel = match self.ctx.read_bytes_pre(&channel_key)? {
+            Some(value) => ChannelEnd::decode_vec(&value).map_err(|e| {
+                Error::InvalidChannel(format!(
+                    "Decoding the channel failed: Key {}, error {}",
+                    channel_key, e
+                ))
+            })?,
+            None => {
+                return Err(Error::InvalidChannel(format!(
+                    "The channel doesn't exist: Port/Channel {}",
+                    port_channel_id
+                )));
+            }
+        };
+        let conn_id = get_connection_id_from_channel(&channel)?;
+        let account = interchain_account_address(conn_id, &packet.source_port);
+        if &account != self.ctx.address {
+            return Err(Error::InvalidAccount(format!(
+                "The packet isn't for this interchain account: Port {}, \
+                 Connection {}",
+                packet.source_port, conn_id
+            )));
+        }
+
+        let data: InterchainAccountPacketData =
+            serde_json::from_slice(&packet.data)
+                .map_err(Error::DecodingPacketData)?;
+        Ok(data.decode_msgs()?)
+    }
+}
+
+impl<'a, DB, H, CA> Ibc<'a, DB, H, CA>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    /// Only the owner can register the interchain account, close the channel
+    /// and send a transaction to it
+    pub(super) fn validate_ica_owner(
+        &self,
+        signed: &SignedTxData,
+        tx_data: &[u8],
+    ) -> Result<()> {
+        let owner = match MsgSendIcaTx::try_from_tx_data(tx_data)? {
+            Some(msg) => Some(msg.owner),
+            None => match IbcMessage::decode(tx_data).map(|msg| msg.0) {
+                Ok(Ics26Envelope::Ics4ChannelMsg(
+                    ChannelMsg::ChannelOpenInit(msg),
+                )) => ica_owner(&msg.port_id),
+                Ok(Ics26Envelope::Ics4ChannelMsg(
+                    ChannelMsg::ChannelCloseInit(msg),
+                )) => ica_owner(&msg.port_id),
+                _ => None,
+            },
+        };
+        let owner = match owner {
+            Some(owner) => owner,
+            None => return Ok(()),
+        };
+        let pk: Option<common::PublicKey> =
+            self.ctx.read_pre(&pk_key(&owner))?;
+        let is_signed = match pk {
+            Some(pk) => self.ctx.verify_tx_signature(&pk, &signed.sig)?,
+            None => false,
+        };
+        if is_signed {
+            Ok(())
+        } else {
+            Err(Error::InvalidOwner(format!(
+                "The transaction isn't signed by the owner: Owner {}",
+                owner
+            )))
+        }
+    }
+
+    /// The active channel of an interchain account should be the channel
+    /// opened by this transaction
+    pub(super) fn validate_ica_channel(&self, key: &Key) -> Result<()> {
+        let (conn_id, controller_port_id) = ica_connection_port_id(key)?;
+        let channel_id = match self.ctx.read_bytes_post(key)? {
+            Some(value) => std::str::from_utf8(&value)
+                .ok()
+                .and_then(|id| ChannelId::from_str(id).ok())
+                .ok_or_else(|| {
+                    Error::InvalidChannel(format!(
+                        "Invalid active channel: Key {}",
+                        key
+                    ))
+                })?,
+            None => {
+                return Err(Error::InvalidChannel(format!(
+                    "The active channel can't be deleted: Key {}",
+                    key
+                )));
+            }
+        };
+        if !is_ica_controller_port(&controller_port_id) {
+            return Err(Error::InvalidChannel(format!(
+                "Invalid controller port: Key {}",
+                key
+            )));
+        }
+        // the port on this chain is the controller port when this chain is
+        // the controller, otherwise the host port
+        let port_id = if ica_owner(&controller_port_id).is_some() {
+            controller_port_id.clone()
+        } else {
+            PortId::from_str(ICA_HOST_PORT_ID).expect("valid port ID")
+        };
+        let port_channel_id = PortChannelId {
+            port_id: port_id.clone(),
+            channel_id,
+        };
+        let channel_key = channel_key(&port_channel_id);
+        if self.ctx.has_key_pre(&channel_key)? {
+            return Err(Error::InvalidChannel(format!(
+                "The active channel should be opened by this transaction: \
+                 Port/Channel {}",
+                port_channel_id
+            )));
+        }
+        let channel = match self.ctx.read_bytes_post(&channel_key)? {
+            Some(value) => ChannelEnd::decode_vec(&value).map_err(|e| {
+                Error::InvalidChannel(format!(
+                    "Decoding the channel failed: Key {}, error {}",
+                    channel_key, e
+                ))
+            })?,
+            None => {
+                return Err(Error::InvalidChannel(format!(
+                    "The active channel doesn't exist: Port/Channel {}",
+                    port_channel_id
+                )));
+            }
+        };
+        let counterparty_port_id = channel.counterparty().port_id();
+        let is_valid_port = if port_id == controller_port_id {
+            counterparty_port_id.as_str() == ICA_HOST_PORT_ID
+        } else {
+            *counterparty_port_id == controller_port_id
+        };
+        if !is_valid_port
+            || get_connection_id_from_channel(&channel)? != &conn_id
+        {
+            return Err(Error::InvalidChannel(format!(
+                "The active channel is mismatched: Key {}",
+                key
+            )));
+        }
+        Ok(())
+    }
+
+    /// Make the packet sent by the owner of the interchain account
+    pub(super) fn ica_packet(
+        &self,
+        msg: &MsgSendIcaTx,
+        port_channel_id: &PortChannelId,
+        sequence: Sequence,
+        counterparty: &Counterparty,
+    ) -> Result<Packet> {
+        let port_id = ica_controller_port_id(&msg.owner)?;
+        if port_id != port_channel_id.port_id {
+            return Err(Error::InvalidOwner(format!(
+                "The port isn't owned by the owner: Port {}, Owner {}",
+                port_channel_id.port_id, msg.owner
+            )));
+        }
+        let conn_id = ConnectionId::from_str(&msg.connection_id)
+            .map_err(|e| Error::InvalidChannel(e.to_string()))?;
+        let ica_key = ica_channel_key(&conn_id, &port_id);
+        let active = self.ctx.read_bytes_pre(&ica_key)?;
+        if active.as_deref()
+            != Some(port_channel_id.channel_id.to_string().as_bytes())
+        {
+            return Err(Error::InvalidChannel(format!(
+                "The channel isn't active for the interchain account: Key {}",
+                ica_key
+            )));
+        }
+        Ok(packet_from_ica_message(
+            msg,
+            port_channel_id,
+            sequence,
+            counterparty,
+        )?)
+    }
+}
+
+impl From<native_vp::Error> for Error {
+    fn from(err: native_vp::Error) -> Self {
+        Self::NativeVpError(err)
+    }
+}
+
+impl From<IbcDataError> for Error {
+    fn from(err: IbcDataError) -> Self {
+        Self::InvalidIbcData(err)
+    }
+}
+
+impl From<IbcError> for Error {
+    fn from(err: IbcError) -> Self {
+        Self::Ibc(err)
+    }
+}
+
+impl From<IbcStorageError> for Error {
+    fn from(err: IbcStorageError) -> Self {
+        Self::IbcStorage(err)
+    }
+}
@@ -4,6 +4,8 @@ mod channel;
 mod client;
 mod connection;
 mod denom;
+mod flow;
+mod ica;
 mod packet;
 mod port;
 mod sequence;
@@ -12,8 +14,9 @@ mod token;
 use std::collections::{BTreeSet, HashSet};
 
 use borsh::BorshDeserialize;
+pub use ica::{Error as InterchainAccountError, InterchainAccount};
 use namada_core::ledger::ibc::storage::{
-    client_id, ibc_prefix, is_client_counter_key, IbcPrefix,
+    client_id, ibc_prefix, is_client_counter_key, is_flow_limit_key, IbcPrefix,
 };
 use namada_core::ledger::storage::{self as ledger_storage, StorageHasher};
 use namada_core::proto::SignedTxData;
@@ -51,6 +54,10 @@ pub enum Error {
     SequenceError(sequence::Error),
     #[error("Denom validation error: {0}")]
     DenomError(denom::Error),
+    #[error("Flow validation error: {0}")]
+    FlowError(flow::Error),
+    #[error("Interchain account validation error: {0}")]
+    InterchainAccountError(ica::Error),
     #[error("IBC event error: {0}")]
     IbcEvent(String),
     #[error("Decoding transaction data error: {0}")]
@@ -89,9 +96,16 @@ where
         keys_changed: &BTreeSet<Key>,
         _verifiers: &BTreeSet<Address>,
     ) -> Result<bool> {
+        if keys_changed.iter().any(is_flow_limit_key) {
+            // the tx data of a governance proposal isn't an IBC message
+            self.validate_flow_limits(tx_data, keys_changed)?;
+            return Ok(true);
+        }
+
         let signed = SignedTxData::try_from_slice(tx_data)
             .map_err(Error::TxDataDecoding)?;
-        let tx_data = &signed.data.ok_or(Error::NoTxData)?;
+        let tx_data = signed.data.as_deref().ok_or(Error::NoTxData)?;
+        self.validate_ica_owner(&signed, tx_data)?;
         let mut clients = HashSet::new();
 
         for key in keys_changed {
@@ -147,6 +161,15 @@ where
                     IbcPrefix::Ack => self.validate_ack(key)?,
                     IbcPrefix::Event => {}
                     IbcPrefix::Denom => self.validate_denom(tx_data)?,
+                    IbcPrefix::Flow => {
+                        self.validate_flow(key, tx_data, keys_changed)?
+                    }
+                    IbcPrefix::InterchainAccount => {
+                        self.validate_ica_channel(key)?
+                    }
+                    IbcPrefix::FlowLimit => {
+                        unreachable!("flow limits are validated above")
+                    }
                     IbcPrefix::Unknown => {
                         return Err(Error::KeyError(format!(
                             "Invalid IBC-related key: {}",
@@ -300,6 +323,18 @@ impl From<denom::Error> for Error {
     }
 }
 
+impl From<flow::Error> for Error {
+    fn from(err: flow::Error) -> Self {
+        Self::FlowError(err)
+    }
+}
+
+impl From<ica::Error> for Error {
+    fn from(err: ica::Error) -> Self {
+        Self::InterchainAccountError(err)
+    }
+}
+
 /// A dummy header used for testing
 #[cfg(any(feature = "test", feature = "testing"))]
 pub fn get_dummy_header() -> crate::types::storage::Header {
@@ -4,7 +4,7 @@ use namada_core::ledger::ibc::actions::{
     self, make_send_packet_event, make_timeout_event, packet_from_message,
 };
 use namada_core::ledger::ibc::data::{
-    Error as IbcDataError, FungibleTokenPacketData, IbcMessage,
+    Error as IbcDataError, FungibleTokenPacketData, IbcMessage, MsgSendIcaTx,
 };
 use namada_core::ledger::ibc::storage::{
     ibc_denom_key, port_channel_sequence_id, token_hash_from_denom,
@@ -99,18 +99,36 @@ where
         {
             StateChange::Created => {
                 // sending a packet
-                let ibc_msg = IbcMessage::decode(tx_data)?;
-                let msg = ibc_msg.msg_transfer()?;
-                // make a packet
                 let channel = self
                     .channel_end(&(commitment_key.0.clone(), commitment_key.1))
                     .map_err(|e| Error::InvalidChannel(e.to_string()))?;
-                let mut packet = packet_from_message(
-                    &msg,
-                    commitment_key.2,
-                    channel.counterparty(),
-                );
-                self.update_denom(&mut packet)?;
+                // make a packet
+                let packet = match MsgSendIcaTx::try_from_tx_data(tx_data)? {
+                    Some(msg) => {
+                        let port_channel_id = PortChannelId {
+                            port_id: commitment_key.0.clone(),
+                            channel_id: commitment_key.1,
+                        };
+                        self.ica_packet(
+                            &msg,
+                            &port_channel_id,
+                            commitment_key.2,
+                            channel.counterparty(),
+                        )
+                        .map_err(|e| Error::InvalidPacket(e.to_string()))?
+                    }
+                    None => {
+                        let ibc_msg = IbcMessage::decode(tx_data)?;
+                        let msg = ibc_msg.msg_transfer()?;
+                        let mut packet = packet_from_message(
+                            &msg,
+                            commitment_key.2,
+                            channel.counterparty(),
+                        );
+                        self.update_denom(&mut packet)?;
+                        packet
+                    }
+                };
                 let commitment = self
                     .get_packet_commitment(&commitment_key)
                     .map_err(|_| {
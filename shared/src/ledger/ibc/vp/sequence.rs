@@ -9,7 +9,9 @@ use crate::ibc::core::ics04_channel::channel::Order;
 use crate::ibc::core::ics04_channel::context::ChannelReader;
 use crate::ibc::core::ics24_host::identifier::PortChannelId;
 use crate::ledger::storage::{self as ledger_storage, StorageHasher};
-use crate::types::ibc::data::{Error as IbcDataError, IbcMessage};
+use crate::types::ibc::data::{
+    Error as IbcDataError, IbcMessage, MsgSendIcaTx,
+};
 use crate::types::storage::Key;
 use crate::vm::WasmCacheAccess;
 
@@ -47,8 +49,6 @@ where
         tx_data: &[u8],
     ) -> Result<()> {
         let port_channel_id = port_channel_id(key)?;
-        let ibc_msg = IbcMessage::decode(tx_data)?;
-        let msg = ibc_msg.msg_transfer()?;
         // make a packet
         let channel = self
             .channel_end(&(
@@ -59,8 +59,21 @@ where
         let next_seq_pre = self
             .get_next_sequence_send_pre(&port_channel_id)
             .map_err(|e| Error::InvalidSequence(e.to_string()))?;
-        let packet =
-            packet_from_message(&msg, next_seq_pre, channel.counterparty());
+        let packet = match MsgSendIcaTx::try_from_tx_data(tx_data)? {
+            Some(msg) => self
+                .ica_packet(
+                    &msg,
+                    &port_channel_id,
+                    next_seq_pre,
+                    channel.counterparty(),
+                )
+                .map_err(|e| Error::InvalidPacket(e.to_string()))?,
+            None => {
+                let ibc_msg = IbcMessage::decode(tx_data)?;
+                let msg = ibc_msg.msg_transfer()?;
+                packet_from_message(&msg, next_seq_pre, channel.counterparty())
+            }
+        };
         let next_seq = self
             .get_next_sequence_send(&(
                 port_channel_id.port_id.clone(),
@@ -6,6 +6,7 @@ use std::str::FromStr;
 use borsh::BorshDeserialize;
 use thiserror::Error;
 
+use super::flow;
 use crate::ibc::applications::ics20_fungible_token_transfer::msgs::transfer::MsgTransfer;
 use crate::ibc::core::ics04_channel::msgs::PacketMsg;
 use crate::ibc::core::ics04_channel::packet::Packet;
@@ -49,6 +50,8 @@ pub enum Error {
     NoTxData,
     #[error("Invalid denom error: {0}")]
     Denom(String),
+    #[error("Token flow error: {0}")]
+    Flow(flow::Error),
 }
 
 /// Result for IBC token VP
@@ -140,6 +143,14 @@ where
 
         // Check the message
         let ibc_msg = IbcMessage::decode(tx_data).map_err(Error::IbcMessage)?;
+        // An escrow, a burn or a mint of a denom with a flow limit has to be
+        // recorded in its flow even if the tx doesn't use `IbcActions`
+        if let Some(flow) =
+            flow::token_flow(&self.ctx, &ibc_msg).map_err(Error::Flow)?
+        {
+            flow::validate_flow_update(&self.ctx, &flow, keys_changed)
+                .map_err(Error::Flow)?;
+        }
         match &ibc_msg.0 {
             Ics26Envelope::Ics20Msg(msg) => self.validate_sending_token(msg),
             Ics26Envelope::Ics4PacketMsg(PacketMsg::RecvPacket(msg)) => {
@@ -7,13 +7,18 @@ use std::collections::BTreeSet;
 use namada_core::ledger::governance::storage as gov_storage;
 use namada_core::ledger::storage;
 use namada_core::ledger::vp_env::VpEnv;
+use namada_proof_of_stake::types::ValidatorDeltas;
 use thiserror::Error;
 use utils::is_valid_validator_voting_period;
 
 use crate::ledger::native_vp::{Ctx, NativeVp};
-use crate::ledger::storage_api::StorageRead;
+use crate::ledger::storage_api::{self, StorageRead};
 use crate::ledger::{native_vp, pos};
 use crate::types::address::{Address, InternalAddress};
+use crate::types::governance::{
+    OfflineProposal, OfflineVote, SlashFundDisbursement,
+};
+use crate::types::hash::Hash;
 use crate::types::storage::{Epoch, Key};
 use crate::types::token;
 use crate::vm::WasmCacheAccess;
@@ -85,6 +90,18 @@ where
                 (KeyType::END_EPOCH, Some(proposal_id)) => {
                     self.is_valid_end_epoch(proposal_id)
                 }
+                (KeyType::EXPEDITED, Some(proposal_id)) => {
+                    self.is_valid_expedited(proposal_id)
+                }
+                (KeyType::DISBURSEMENTS, Some(proposal_id)) => {
+                    self.is_valid_disbursements(proposal_id)
+                }
+                (KeyType::DEPOSIT, Some(proposal_id)) => self.is_valid_deposit(
+                    proposal_id,
+                    key,
+                    &native_token,
+                    verifiers,
+                ),
                 (KeyType::FUNDS, Some(proposal_id)) => {
                     self.is_valid_funds(proposal_id, &native_token)
                 }
@@ -96,7 +113,18 @@ where
                     self.is_valid_proposal_commit()
                 }
                 (KeyType::PARAMETER, _) => self.is_valid_parameter(tx_data),
-                (KeyType::BALANCE, _) => self.is_valid_balance(&native_token),
+                (KeyType::BALANCE, _) => self.is_valid_balance(
+                    &native_token,
+                    keys_changed,
+                    set_count,
+                ),
+                (KeyType::OFFLINE_PROPOSAL, _) => {
+                    self.is_valid_offline_proposal(key)
+                }
+                (KeyType::OFFLINE_VOTE, _) => self.is_valid_offline_vote(key),
+                (KeyType::PENDING_OFFLINE_TALLY, _) => {
+                    self.is_valid_pending_offline_tally(key)
+                }
                 (KeyType::UNKNOWN_GOVERNANCE, _) => Ok(false),
                 (KeyType::UNKNOWN, _) => Ok(true),
                 _ => Ok(false),
@@ -275,8 +303,11 @@ where
     pub fn is_valid_grace_epoch(&self, proposal_id: u64) -> Result<bool> {
         let end_epoch_key = gov_storage::get_voting_end_epoch_key(proposal_id);
         let grace_epoch_key = gov_storage::get_grace_epoch_key(proposal_id);
-        let min_grace_epoch_key =
-            gov_storage::get_min_proposal_grace_epoch_key();
+        let min_grace_epoch_key = if self.is_expedited(proposal_id)? {
+            gov_storage::get_min_expedited_proposal_grace_epoch_key()
+        } else {
+            gov_storage::get_min_proposal_grace_epoch_key()
+        };
 
         let has_pre_grace_epoch = self.ctx.has_key_pre(&grace_epoch_key)?;
         if has_pre_grace_epoch {
@@ -311,8 +342,7 @@ where
         let start_epoch_key =
             gov_storage::get_voting_start_epoch_key(proposal_id);
         let end_epoch_key = gov_storage::get_voting_end_epoch_key(proposal_id);
-        let min_period_parameter_key =
-            gov_storage::get_min_proposal_period_key();
+        let min_period_parameter_key = self.min_period_key(proposal_id)?;
 
         let current_epoch = self.ctx.get_block_epoch().ok();
 
@@ -351,8 +381,7 @@ where
         let start_epoch_key =
             gov_storage::get_voting_start_epoch_key(proposal_id);
         let end_epoch_key = gov_storage::get_voting_end_epoch_key(proposal_id);
-        let min_period_parameter_key =
-            gov_storage::get_min_proposal_period_key();
+        let min_period_parameter_key = self.min_period_key(proposal_id)?;
         let max_period_parameter_key =
             gov_storage::get_max_proposal_period_key();
 
@@ -397,6 +426,72 @@ where
         }
     }
 
+    /// Validate the expedited flag of a proposal, which can only be set when
+    /// the proposal is created
+    pub fn is_valid_expedited(&self, proposal_id: u64) -> Result<bool> {
+        let expedited_key =
+            gov_storage::get_proposal_expedited_key(proposal_id);
+        let start_epoch_key =
+            gov_storage::get_voting_start_epoch_key(proposal_id);
+
+        let has_pre_expedited = self.ctx.has_key_pre(&expedited_key)?;
+        let has_pre_start_epoch = self.ctx.has_key_pre(&start_epoch_key)?;
+        if has_pre_expedited || has_pre_start_epoch {
+            return Ok(false);
+        }
+
+        let expedited: Option<bool> = self.ctx.post().read(&expedited_key)?;
+        let has_post_start_epoch = self.ctx.has_key_post(&start_epoch_key)?;
+        Ok(expedited == Some(true) && has_post_start_epoch)
+    }
+
+    /// Validate the slash fund disbursements of a proposal, which can only be
+    /// set when the proposal is created
+    pub fn is_valid_disbursements(&self, proposal_id: u64) -> Result<bool> {
+        let disbursements_key =
+            gov_storage::get_proposal_disbursements_key(proposal_id);
+        let start_epoch_key =
+            gov_storage::get_voting_start_epoch_key(proposal_id);
+        let grace_epoch_key = gov_storage::get_grace_epoch_key(proposal_id);
+
+        let has_pre_disbursements = self.ctx.has_key_pre(&disbursements_key)?;
+        let has_pre_start_epoch = self.ctx.has_key_pre(&start_epoch_key)?;
+        if has_pre_disbursements || has_pre_start_epoch {
+            return Ok(false);
+        }
+
+        let disbursements: Option<Vec<SlashFundDisbursement>> =
+            self.ctx.post().read(&disbursements_key)?;
+        let grace_epoch: Option<Epoch> =
+            self.ctx.post().read(&grace_epoch_key)?;
+        match (disbursements, grace_epoch) {
+            (Some(disbursements), Some(grace_epoch)) => Ok(!disbursements
+                .is_empty()
+                && disbursements
+                    .iter()
+                    .all(|disbursement| disbursement.is_valid(grace_epoch))),
+            _ => Ok(false),
+        }
+    }
+
+    /// Check if a proposal is submitted as expedited
+    fn is_expedited(&self, proposal_id: u64) -> Result<bool> {
+        let expedited_key =
+            gov_storage::get_proposal_expedited_key(proposal_id);
+        let expedited: Option<bool> = self.ctx.post().read(&expedited_key)?;
+        Ok(expedited.unwrap_or_default())
+    }
+
+    /// Get the key of the minimum voting period parameter that applies to a
+    /// proposal
+    fn min_period_key(&self, proposal_id: u64) -> Result<Key> {
+        if self.is_expedited(proposal_id)? {
+            Ok(gov_storage::get_min_expedited_proposal_period_key())
+        } else {
+            Ok(gov_storage::get_min_proposal_period_key())
+        }
+    }
+
     /// Validate a funds key
     pub fn is_valid_funds(
         &self,
@@ -439,30 +534,107 @@ where
     }
 
     /// Validate a balance key
-    fn is_valid_balance(&self, native_token_address: &Address) -> Result<bool> {
+    /// The balance must increase by at least the funds of the new proposals
+    /// and the deposits topping up proposals
+    fn is_valid_balance(
+        &self,
+        native_token_address: &Address,
+        keys_changed: &BTreeSet<Key>,
+        set_count: u64,
+    ) -> Result<bool> {
         let balance_key =
             token::balance_key(native_token_address, self.ctx.address);
         let min_funds_parameter_key = gov_storage::get_min_proposal_fund_key();
 
         let min_funds_parameter: Option<token::Amount> =
             self.ctx.pre().read(&min_funds_parameter_key)?;
-        let pre_balance: Option<token::Amount> =
-            self.ctx.pre().read(&balance_key)?;
+        let pre_balance: token::Amount =
+            self.ctx.pre().read(&balance_key)?.unwrap_or_default();
         let post_balance: Option<token::Amount> =
             self.ctx.post().read(&balance_key)?;
 
-        match (min_funds_parameter, pre_balance, post_balance) {
-            (
-                Some(min_funds_parameter),
-                Some(pre_balance),
-                Some(post_balance),
-            ) => Ok(post_balance > pre_balance
-                && post_balance - pre_balance >= min_funds_parameter),
-            (Some(min_funds_parameter), None, Some(post_balance)) => {
-                Ok(post_balance >= min_funds_parameter)
+        let (min_funds_parameter, post_balance) =
+            match (min_funds_parameter, post_balance) {
+                (Some(min_funds_parameter), Some(post_balance)) => {
+                    (min_funds_parameter, post_balance)
+                }
+                _ => return Ok(false),
+            };
+        let mut expected_increase = min_funds_parameter * set_count;
+        for key in keys_changed {
+            if gov_storage::is_deposit_key(key) {
+                let pre_deposit: token::Amount =
+                    self.ctx.pre().read(key)?.unwrap_or_default();
+                let post_deposit: token::Amount =
+                    self.ctx.post().read(key)?.unwrap_or_default();
+                match post_deposit.checked_sub(pre_deposit) {
+                    Some(deposit) => expected_increase += deposit,
+                    None => return Ok(false),
+                }
             }
-            _ => Ok(false),
         }
+        Ok(expected_increase != token::Amount::default()
+            && post_balance > pre_balance
+            && post_balance - pre_balance >= expected_increase)
+    }
+
+    /// Validate the deposit of an address topping up a proposal. It can only
+    /// increase before the voting period starts and must be paid by the
+    /// depositor, whose signature is checked by its VP.
+    pub fn is_valid_deposit(
+        &self,
+        proposal_id: u64,
+        key: &Key,
+        native_token_address: &Address,
+        verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        let depositor = match gov_storage::get_depositor_address(key) {
+            Some(depositor) => depositor,
+            None => return Ok(false),
+        };
+        let start_epoch_key =
+            gov_storage::get_voting_start_epoch_key(proposal_id);
+        let balance_key =
+            token::balance_key(native_token_address, self.ctx.address);
+
+        let start_epoch: Option<Epoch> =
+            self.ctx.pre().read(&start_epoch_key)?;
+        let pre_deposit: token::Amount =
+            self.ctx.pre().read(key)?.unwrap_or_default();
+        let post_deposit: Option<token::Amount> = self.ctx.post().read(key)?;
+        let (start_epoch, post_deposit) = match (start_epoch, post_deposit) {
+            (Some(start_epoch), Some(post_deposit)) => {
+                (start_epoch, post_deposit)
+            }
+            _ => return Ok(false),
+        };
+
+        let current_epoch = self.ctx.get_block_epoch()?;
+        let pre_balance: token::Amount =
+            self.ctx.pre().read(&balance_key)?.unwrap_or_default();
+        let post_balance: token::Amount =
+            self.ctx.post().read(&balance_key)?.unwrap_or_default();
+        let depositor_balance_key =
+            token::balance_key(native_token_address, depositor);
+        let pre_depositor_balance: token::Amount = self
+            .ctx
+            .pre()
+            .read(&depositor_balance_key)?
+            .unwrap_or_default();
+        let post_depositor_balance: token::Amount = self
+            .ctx
+            .post()
+            .read(&depositor_balance_key)?
+            .unwrap_or_default();
+
+        Ok(current_epoch < start_epoch
+            && post_deposit > pre_deposit
+            && post_balance > pre_balance
+            && post_balance - pre_balance >= post_deposit - pre_deposit
+            && pre_depositor_balance > post_depositor_balance
+            && pre_depositor_balance - post_depositor_balance
+                >= post_deposit - pre_deposit
+            && verifiers.contains(depositor))
     }
 
     /// Validate a author key
@@ -534,6 +706,122 @@ where
             .map_err(Error::NativeVpError)
     }
 
+    /// Validate an offline proposal submitted for tallying. It must be new and
+    /// signed. Its tally epoch must not be in the future, nor older than the
+    /// PoS data that will still be kept when its votes submission window
+    /// closes.
+    pub fn is_valid_offline_proposal(&self, key: &Key) -> Result<bool> {
+        let proposal_hash = match gov_storage::get_offline_proposal_hash(key) {
+            Some(proposal_hash) => proposal_hash,
+            None => return Ok(false),
+        };
+        if !self.is_new_offline_proposal(&proposal_hash)? {
+            return Ok(false);
+        }
+        let result_key =
+            gov_storage::get_offline_proposal_result_key(&proposal_hash);
+        if self.ctx.has_key_pre(&result_key)? {
+            return Ok(false);
+        }
+
+        let pending_tally_key =
+            gov_storage::get_pending_offline_tally_key(&proposal_hash);
+        let close_epoch: Option<Epoch> =
+            self.ctx.post().read(&pending_tally_key)?;
+        let min_period: Option<u64> = self
+            .ctx
+            .pre()
+            .read(&gov_storage::get_min_proposal_period_key())?;
+        let proposal: Option<OfflineProposal> = self.ctx.post().read(key)?;
+        let current_epoch = self.ctx.get_block_epoch()?;
+        match (proposal, close_epoch, min_period) {
+            (Some(proposal), Some(close_epoch), Some(min_period)) => {
+                // The stake at the tally epoch must still be available when
+                // the proposal gets tallied
+                let num_past_epochs = ValidatorDeltas::get_num_past_epochs();
+                let oldest_tally_epoch = close_epoch
+                    .checked_sub(Epoch(num_past_epochs))
+                    .unwrap_or_default();
+                let public_key =
+                    storage_api::key::get(&self.ctx.pre(), &proposal.address)?;
+                Ok(proposal.compute_hash() == proposal_hash
+                    && close_epoch == current_epoch + min_period
+                    && proposal.tally_epoch <= current_epoch
+                    && proposal.tally_epoch >= oldest_tally_epoch
+                    && public_key.map_or(false, |public_key| {
+                        proposal.check_signature(&public_key)
+                    }))
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Validate a vote of an offline proposal. Anyone can submit the signed
+    /// votes of a proposal until its votes submission window closes. Only the
+    /// validators and the delegators at the tally epoch of the proposal can
+    /// vote, and a vote cannot be overwritten.
+    pub fn is_valid_offline_vote(&self, key: &Key) -> Result<bool> {
+        let proposal_hash = gov_storage::get_offline_proposal_hash(key);
+        let voter = gov_storage::get_offline_voter_address(key);
+        let (proposal_hash, voter) = match (proposal_hash, voter) {
+            (Some(proposal_hash), Some(voter)) => (proposal_hash, voter),
+            _ => return Ok(false),
+        };
+        if self.ctx.has_key_pre(key)? {
+            return Ok(false);
+        }
+
+        let pending_tally_key =
+            gov_storage::get_pending_offline_tally_key(&proposal_hash);
+        let close_epoch: Option<Epoch> =
+            self.ctx.post().read(&pending_tally_key)?;
+        let current_epoch = self.ctx.get_block_epoch()?;
+        if !close_epoch.map_or(false, |close_epoch| current_epoch < close_epoch)
+        {
+            return Ok(false);
+        }
+
+        let proposal: Option<OfflineProposal> = self
+            .ctx
+            .post()
+            .read(&gov_storage::get_offline_proposal_key(&proposal_hash))?;
+        let vote: Option<OfflineVote> = self.ctx.post().read(key)?;
+        match (proposal, vote) {
+            (Some(proposal), Some(vote)) => {
+                let public_key =
+                    storage_api::key::get(&self.ctx.pre(), &vote.address)?;
+                Ok(vote.proposal_hash == proposal_hash
+                    && &vote.address == voter
+                    && utils::is_offline_voter(
+                        &self.ctx.pre(),
+                        proposal.tally_epoch,
+                        voter,
+                    )?
+                    && public_key.map_or(false, |public_key| {
+                        vote.check_signature(&public_key)
+                    }))
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Validate the pending tally key of an offline proposal, which can only
+    /// be written together with the proposal
+    pub fn is_valid_pending_offline_tally(&self, key: &Key) -> Result<bool> {
+        match gov_storage::get_offline_proposal_hash(key) {
+            Some(proposal_hash) => Ok(self.ctx.has_key_post(key)?
+                && self.is_new_offline_proposal(&proposal_hash)?),
+            None => Ok(false),
+        }
+    }
+
+    /// Check if an offline proposal is submitted by the current tx
+    fn is_new_offline_proposal(&self, proposal_hash: &Hash) -> Result<bool> {
+        let proposal_key = gov_storage::get_offline_proposal_key(proposal_hash);
+        Ok(!self.ctx.has_key_pre(&proposal_key)?
+            && self.ctx.has_key_post(&proposal_key)?)
+    }
+
     /// Check if a vote is from a validator
     pub fn is_validator(
         &self,
@@ -616,6 +904,12 @@ enum KeyType {
     #[allow(non_camel_case_types)]
     END_EPOCH,
     #[allow(non_camel_case_types)]
+    EXPEDITED,
+    #[allow(non_camel_case_types)]
+    DISBURSEMENTS,
+    #[allow(non_camel_case_types)]
+    DEPOSIT,
+    #[allow(non_camel_case_types)]
     FUNDS,
     #[allow(non_camel_case_types)]
     BALANCE,
@@ -624,6 +918,12 @@ enum KeyType {
     #[allow(non_camel_case_types)]
     PARAMETER,
     #[allow(non_camel_case_types)]
+    OFFLINE_PROPOSAL,
+    #[allow(non_camel_case_types)]
+    OFFLINE_VOTE,
+    #[allow(non_camel_case_types)]
+    PENDING_OFFLINE_TALLY,
+    #[allow(non_camel_case_types)]
     UNKNOWN_GOVERNANCE,
     #[allow(non_camel_case_types)]
     UNKNOWN,
@@ -645,6 +945,12 @@ impl KeyType {
             KeyType::PROPOSAL_COMMIT
         } else if gov_storage::is_end_epoch_key(key) {
             KeyType::END_EPOCH
+        } else if gov_storage::is_expedited_key(key) {
+            KeyType::EXPEDITED
+        } else if gov_storage::is_disbursements_key(key) {
+            KeyType::DISBURSEMENTS
+        } else if gov_storage::is_deposit_key(key) {
+            KeyType::DEPOSIT
         } else if gov_storage::is_balance_key(key) {
             KeyType::FUNDS
         } else if gov_storage::is_author_key(key) {
@@ -653,6 +959,12 @@ impl KeyType {
             KeyType::COUNTER
         } else if gov_storage::is_parameter_key(key) {
             KeyType::PARAMETER
+        } else if gov_storage::is_offline_proposal_key(key) {
+            KeyType::OFFLINE_PROPOSAL
+        } else if gov_storage::is_offline_vote_key(key) {
+            KeyType::OFFLINE_VOTE
+        } else if gov_storage::is_pending_offline_tally_key(key) {
+            KeyType::PENDING_OFFLINE_TALLY
         } else if token::is_balance_key(native_token, key).is_some() {
             KeyType::BALANCE
         } else if gov_storage::is_governance_key(key) {
@@ -4,28 +4,75 @@ use std::collections::HashMap;
 
 use borsh::BorshDeserialize;
 use namada_proof_of_stake::{
-    bond_amount, read_all_validator_addresses, read_pos_params,
-    read_total_stake, read_validator_stake,
+    bond_amount, find_delegation_validators, read_all_validator_addresses,
+    read_pos_params, read_total_stake, read_validator_stake,
 };
+use rust_decimal::Decimal;
 use thiserror::Error;
 
 use crate::ledger::governance::storage as gov_storage;
 use crate::ledger::pos::BondId;
 use crate::ledger::storage_api;
 use crate::types::address::Address;
-use crate::types::governance::{ProposalVote, TallyResult, VotePower};
+use crate::types::governance::{
+    OfflineVote, ProposalResult, ProposalStatus, ProposalVote, TallyResult,
+    VotePower, WeightedVote,
+};
+use crate::types::hash::Hash;
 use crate::types::storage::Epoch;
-use crate::types::token;
 
 /// Proposal structure holding votes information necessary to compute the
 /// outcome
+#[derive(Default)]
 pub struct Votes {
     /// Map from validators who votes yay to their total stake amount
     pub yay_validators: HashMap<Address, VotePower>,
+    /// Map from validators who votes nay to their total stake amount
+    pub nay_validators: HashMap<Address, VotePower>,
+    /// Map from validators who votes veto to their total stake amount
+    pub veto_validators: HashMap<Address, VotePower>,
     /// Map from delegation who votes yay to their bond amount
     pub yay_delegators: HashMap<Address, HashMap<Address, VotePower>>,
     /// Map from delegation who votes nay to their bond amount
     pub nay_delegators: HashMap<Address, HashMap<Address, VotePower>>,
+    /// Map from delegation who votes veto to their bond amount
+    pub veto_delegators: HashMap<Address, HashMap<Address, VotePower>>,
+}
+
+impl Votes {
+    /// Add the vote of a validator with its stake
+    pub fn add_validator_vote(
+        &mut self,
+        validator: Address,
+        vote: &ProposalVote,
+        power: VotePower,
+    ) {
+        let validators = match vote {
+            ProposalVote::Yay => &mut self.yay_validators,
+            ProposalVote::Nay => &mut self.nay_validators,
+            ProposalVote::Veto => &mut self.veto_validators,
+        };
+        validators.insert(validator, power);
+    }
+
+    /// Add the vote of a delegator with its bond to the given validator
+    pub fn add_delegator_vote(
+        &mut self,
+        delegator: Address,
+        validator: Address,
+        vote: &ProposalVote,
+        power: VotePower,
+    ) {
+        let delegators = match vote {
+            ProposalVote::Yay => &mut self.yay_delegators,
+            ProposalVote::Nay => &mut self.nay_delegators,
+            ProposalVote::Veto => &mut self.veto_delegators,
+        };
+        delegators
+            .entry(delegator)
+            .or_default()
+            .insert(validator, power);
+    }
 }
 
 /// Proposal errors
@@ -73,14 +120,37 @@ impl ProposalEvent {
             attributes,
         }
     }
+
+    /// Create an event for the tally of an offline proposal
+    pub fn new_offline(
+        event_type: String,
+        tally: TallyResult,
+        proposal_hash: &Hash,
+        tally_epoch: Epoch,
+    ) -> Self {
+        let attributes = HashMap::from([
+            ("tally_result".to_string(), tally.to_string()),
+            (
+                "offline_proposal_hash".to_string(),
+                proposal_hash.to_string(),
+            ),
+            ("tally_epoch".to_string(), tally_epoch.to_string()),
+        ]);
+        Self {
+            event_type,
+            attributes,
+        }
+    }
 }
 
-/// Return a proposal result - accepted only when the result is `Ok(true)`.
+/// Compute the result of a proposal from its votes. The vote of a delegator
+/// overrides the vote of its validator for the delegated stake. A proposal
+/// is rejected if it's vetoed, even if it has enough yay votes.
 pub fn compute_tally<S>(
     storage: &S,
     epoch: Epoch,
     votes: Votes,
-) -> storage_api::Result<bool>
+) -> storage_api::Result<ProposalResult>
 where
     S: storage_api::StorageRead,
 {
@@ -90,34 +160,126 @@ where
 
     let Votes {
         yay_validators,
+        nay_validators,
+        veto_validators,
         yay_delegators,
         nay_delegators,
+        veto_delegators,
     } = votes;
 
-    let mut total_yay_staked_tokens = VotePower::from(0_u64);
-    for (_, amount) in yay_validators.clone().into_iter() {
-        total_yay_staked_tokens += amount;
-    }
+    let mut total_yay_staked_tokens: VotePower = yay_validators.values().sum();
+    let mut total_nay_staked_tokens: VotePower = nay_validators.values().sum();
+    let mut total_veto_staked_tokens: VotePower =
+        veto_validators.values().sum();
 
-    // YAY: Add delegator amount whose validator didn't vote / voted nay
-    for (_, vote_map) in yay_delegators.iter() {
-        for (validator_address, vote_power) in vote_map.iter() {
-            if !yay_validators.contains_key(validator_address) {
-                total_yay_staked_tokens += vote_power;
+    let delegators = [
+        (ProposalVote::Yay, &yay_delegators),
+        (ProposalVote::Nay, &nay_delegators),
+        (ProposalVote::Veto, &veto_delegators),
+    ];
+    for (vote, delegators) in delegators {
+        for (_, vote_map) in delegators.iter() {
+            for (validator_address, vote_power) in vote_map.iter() {
+                // Remove the delegator amount from its validator's vote
+                if yay_validators.contains_key(validator_address) {
+                    total_yay_staked_tokens -= vote_power;
+                } else if nay_validators.contains_key(validator_address) {
+                    total_nay_staked_tokens -= vote_power;
+                } else if veto_validators.contains_key(validator_address) {
+                    total_veto_staked_tokens -= vote_power;
+                }
+                match vote {
+                    ProposalVote::Yay => total_yay_staked_tokens += vote_power,
+                    ProposalVote::Nay => total_nay_staked_tokens += vote_power,
+                    ProposalVote::Veto => {
+                        total_veto_staked_tokens += vote_power
+                    }
+                }
             }
         }
     }
 
-    // NAY: Remove delegator amount whose validator validator vote yay
-    for (_, vote_map) in nay_delegators.iter() {
-        for (validator_address, vote_power) in vote_map.iter() {
-            if yay_validators.contains_key(validator_address) {
-                total_yay_staked_tokens -= vote_power;
-            }
-        }
+    let result = ProposalResult {
+        result: TallyResult::Rejected,
+        total_voting_power: total_stake,
+        total_yay_power: total_yay_staked_tokens,
+        total_nay_power: total_nay_staked_tokens,
+        total_veto_power: total_veto_staked_tokens,
+    };
+    let is_vetoed = is_vetoed(storage, &result)?;
+    let result = if !is_vetoed && 3 * total_yay_staked_tokens >= 2 * total_stake
+    {
+        TallyResult::Passed
+    } else {
+        TallyResult::Rejected
+    };
+    Ok(ProposalResult {
+        result,
+        total_voting_power: total_stake,
+        total_yay_power: total_yay_staked_tokens,
+        total_nay_power: total_nay_staked_tokens,
+        total_veto_power: total_veto_staked_tokens,
+    })
+}
+
+/// Check if the share of the cast voting power that vetoed a proposal reaches
+/// the veto threshold
+pub fn is_vetoed<S>(
+    storage: &S,
+    tally: &ProposalResult,
+) -> storage_api::Result<bool>
+where
+    S: storage_api::StorageRead,
+{
+    if tally.total_veto_power == 0 {
+        return Ok(false);
+    }
+    let threshold: Decimal = storage
+        .read(&gov_storage::get_proposal_veto_threshold_key())?
+        .ok_or_else(|| {
+            storage_api::Error::new_const(
+                "Missing the proposal veto threshold parameter",
+            )
+        })?;
+    let total_veto_power = Decimal::from(tally.total_veto_power);
+    Ok(total_veto_power >= threshold * Decimal::from(total_cast_power(tally)))
+}
+
+/// Get the fraction of the deposits of a tallied proposal that must be burnt.
+/// The deposits are burnt if the proposal is vetoed, partially burnt if the
+/// quorum isn't reached and otherwise refunded, whatever the result.
+pub fn get_deposit_burn_rate<S>(
+    storage: &S,
+    tally: &ProposalResult,
+) -> storage_api::Result<Decimal>
+where
+    S: storage_api::StorageRead,
+{
+    if is_vetoed(storage, tally)? {
+        return Ok(Decimal::ONE);
+    }
+    let quorum: Decimal = storage
+        .read(&gov_storage::get_min_proposal_quorum_key())?
+        .ok_or_else(|| {
+            storage_api::Error::new_const(
+                "Missing the minimum proposal quorum parameter",
+            )
+        })?;
+    let total_cast_power = Decimal::from(total_cast_power(tally));
+    if total_cast_power >= quorum * Decimal::from(tally.total_voting_power) {
+        return Ok(Decimal::ZERO);
     }
+    storage
+        .read(&gov_storage::get_no_quorum_burn_rate_key())?
+        .ok_or_else(|| {
+            storage_api::Error::new_const(
+                "Missing the no quorum deposit burn rate parameter",
+            )
+        })
+}
 
-    Ok(3 * total_yay_staked_tokens >= 2 * total_stake)
+fn total_cast_power(tally: &ProposalResult) -> VotePower {
+    tally.total_yay_power + tally.total_nay_power + tally.total_veto_power
 }
 
 /// Prepare Votes structure to compute proposal tally
@@ -126,6 +288,32 @@ pub fn get_proposal_votes<S>(
     epoch: Epoch,
     proposal_id: u64,
 ) -> storage_api::Result<Votes>
+where
+    S: storage_api::StorageRead,
+{
+    let mut votes = Votes::default();
+    for vote in get_proposal_weighted_votes(storage, epoch, proposal_id)? {
+        if vote.is_validator {
+            votes.add_validator_vote(vote.voter, &vote.vote, vote.power);
+        } else if vote.power != 0 {
+            votes.add_delegator_vote(
+                vote.voter,
+                vote.validator,
+                &vote.vote,
+                vote.power,
+            );
+        }
+    }
+    Ok(votes)
+}
+
+/// Get all the votes of a proposal with their voting power at the given
+/// epoch: the stake of a validator or the bond of a delegator
+pub fn get_proposal_weighted_votes<S>(
+    storage: &S,
+    epoch: Epoch,
+    proposal_id: u64,
+) -> storage_api::Result<Vec<WeightedVote>>
 where
     S: storage_api::StorageRead,
 {
@@ -137,76 +325,259 @@ where
     let vote_iter =
         storage_api::iter_prefix::<ProposalVote>(storage, &vote_prefix_key)?;
 
-    let mut yay_validators = HashMap::new();
-    let mut yay_delegators: HashMap<Address, HashMap<Address, VotePower>> =
-        HashMap::new();
-    let mut nay_delegators: HashMap<Address, HashMap<Address, VotePower>> =
-        HashMap::new();
-
+    let mut votes = vec![];
     for next_vote in vote_iter {
         let (vote_key, vote) = next_vote?;
-        let voter_address = gov_storage::get_voter_address(&vote_key);
-        match voter_address {
-            Some(voter_address) => {
-                if vote.is_yay() && validators.contains(voter_address) {
-                    let amount: VotePower = read_validator_stake(
-                        storage,
-                        &params,
-                        voter_address,
-                        epoch,
-                    )?
+        let voter = match gov_storage::get_voter_address(&vote_key) {
+            Some(voter) => voter,
+            None => continue,
+        };
+        let weighted_vote = if validators.contains(voter) {
+            let power: VotePower =
+                read_validator_stake(storage, &params, voter, epoch)?
                     .unwrap_or_default()
                     .into();
+            WeightedVote {
+                voter: voter.clone(),
+                validator: voter.clone(),
+                is_validator: true,
+                vote,
+                power,
+            }
+        } else {
+            let validator =
+                match gov_storage::get_vote_delegation_address(&vote_key) {
+                    Some(validator) => validator,
+                    None => continue,
+                };
+            let bond_id = BondId {
+                source: voter.clone(),
+                validator: validator.clone(),
+            };
+            let amount = bond_amount(storage, &params, &bond_id, epoch)?.1;
+            WeightedVote {
+                voter: voter.clone(),
+                validator: validator.clone(),
+                is_validator: false,
+                vote,
+                power: VotePower::from(amount),
+            }
+        };
+        votes.push(weighted_vote);
+    }
+    Ok(votes)
+}
 
-                    yay_validators.insert(voter_address.clone(), amount);
-                } else if !validators.contains(voter_address) {
-                    let validator_address =
-                        gov_storage::get_vote_delegation_address(&vote_key);
-                    match validator_address {
-                        Some(validator) => {
-                            let bond_id = BondId {
-                                source: voter_address.clone(),
-                                validator: validator.clone(),
-                            };
-                            let amount =
-                                bond_amount(storage, &params, &bond_id, epoch)?
-                                    .1;
-
-                            if amount != token::Amount::default() {
-                                if vote.is_yay() {
-                                    let entry = yay_delegators
-                                        .entry(voter_address.to_owned())
-                                        .or_default();
-                                    entry.insert(
-                                        validator.to_owned(),
-                                        VotePower::from(amount),
-                                    );
-                                } else {
-                                    let entry = nay_delegators
-                                        .entry(voter_address.to_owned())
-                                        .or_default();
-                                    entry.insert(
-                                        validator.to_owned(),
-                                        VotePower::from(amount),
-                                    );
-                                }
-                            }
-                        }
-                        None => continue,
-                    }
+/// Get the status of a proposal at the current epoch, if the proposal exists.
+/// The status of a finalized proposal is read from storage, otherwise it
+/// depends on the voting period and, once it's over, on the tally.
+pub fn get_proposal_status<S>(
+    storage: &S,
+    current_epoch: Epoch,
+    proposal_id: u64,
+) -> storage_api::Result<Option<ProposalStatus>>
+where
+    S: storage_api::StorageRead,
+{
+    let status_key = gov_storage::get_proposal_status_key(proposal_id);
+    if let Some(status) = storage.read(&status_key)? {
+        return Ok(Some(status));
+    }
+    let start_epoch: Option<Epoch> =
+        storage.read(&gov_storage::get_voting_start_epoch_key(proposal_id))?;
+    let end_epoch: Option<Epoch> =
+        storage.read(&gov_storage::get_voting_end_epoch_key(proposal_id))?;
+    let (start_epoch, end_epoch) = match (start_epoch, end_epoch) {
+        (Some(start_epoch), Some(end_epoch)) => (start_epoch, end_epoch),
+        _ => return Ok(None),
+    };
+    let status = if current_epoch < start_epoch {
+        ProposalStatus::Pending
+    } else if current_epoch <= end_epoch {
+        ProposalStatus::Voting
+    } else {
+        let votes = get_proposal_votes(storage, end_epoch, proposal_id)?;
+        let tally = compute_tally(storage, end_epoch, votes)?;
+        if is_expedited(storage, proposal_id)? {
+            // An expedited proposal that doesn't reach its threshold is
+            // downgraded to a normal one at its grace epoch and keeps on
+            // voting, unless its voting period cannot be extended
+            if is_expedited_threshold_met(storage, &tally)? {
+                ProposalStatus::Passed
+            } else {
+                let grace_epoch: Epoch = storage
+                    .read(&gov_storage::get_grace_epoch_key(proposal_id))?
+                    .ok_or_else(|| {
+                        storage_api::Error::new_const(
+                            "Missing the proposal grace epoch",
+                        )
+                    })?;
+                let downgraded_period = get_downgraded_voting_period(
+                    storage,
+                    start_epoch,
+                    grace_epoch,
+                )?;
+                if downgraded_period.is_some() {
+                    ProposalStatus::Voting
+                } else {
+                    ProposalStatus::Rejected
+                }
+            }
+        } else {
+            match tally.result {
+                TallyResult::Passed => ProposalStatus::Passed,
+                TallyResult::Rejected | TallyResult::Failed => {
+                    ProposalStatus::Rejected
                 }
             }
-            None => continue,
         }
-    }
+    };
+    Ok(Some(status))
+}
 
-    Ok(Votes {
-        yay_validators,
-        yay_delegators,
-        nay_delegators,
+/// Check if a proposal is expedited. The flag is removed when an expedited
+/// proposal is downgraded to a normal one.
+pub fn is_expedited<S>(
+    storage: &S,
+    proposal_id: u64,
+) -> storage_api::Result<bool>
+where
+    S: storage_api::StorageRead,
+{
+    let expedited_key = gov_storage::get_proposal_expedited_key(proposal_id);
+    Ok(storage.read(&expedited_key)?.unwrap_or_default())
+}
+
+/// Get the voting period of an expedited proposal downgraded to a normal one
+/// in the given epoch. It's the smallest multiple of the minimum proposal
+/// period that keeps the proposal open for voting in that epoch, or `None` if
+/// it exceeds the maximum proposal period.
+pub fn get_downgraded_voting_period<S>(
+    storage: &S,
+    start_epoch: Epoch,
+    epoch: Epoch,
+) -> storage_api::Result<Option<u64>>
+where
+    S: storage_api::StorageRead,
+{
+    let min_period: u64 = storage
+        .read(&gov_storage::get_min_proposal_period_key())?
+        .unwrap_or_default()
+        .max(1);
+    let max_period: u64 = storage
+        .read(&gov_storage::get_max_proposal_period_key())?
+        .unwrap_or_default();
+
+    let elapsed = epoch.0.saturating_sub(start_epoch.0);
+    let period = (elapsed / min_period + 1) * min_period;
+    Ok(if period > max_period {
+        None
+    } else {
+        Some(period)
     })
 }
 
+/// Check if the tally of an expedited proposal passes both the threshold of
+/// normal proposals and the expedited proposal threshold
+pub fn is_expedited_threshold_met<S>(
+    storage: &S,
+    tally: &ProposalResult,
+) -> storage_api::Result<bool>
+where
+    S: storage_api::StorageRead,
+{
+    let threshold: Decimal = storage
+        .read(&gov_storage::get_expedited_proposal_threshold_key())?
+        .ok_or_else(|| {
+            storage_api::Error::new_const(
+                "Missing the expedited proposal threshold parameter",
+            )
+        })?;
+    let total_yay_power = Decimal::from(tally.total_yay_power);
+    let total_voting_power = Decimal::from(tally.total_voting_power);
+    Ok(tally.result == TallyResult::Passed
+        && total_yay_power >= threshold * total_voting_power)
+}
+
+/// Prepare the Votes structure to compute the tally of an offline proposal
+/// from its submitted votes. The vote of a delegator applies to all its
+/// delegations.
+pub fn get_offline_proposal_votes<S>(
+    storage: &S,
+    epoch: Epoch,
+    proposal_hash: &Hash,
+) -> storage_api::Result<Votes>
+where
+    S: storage_api::StorageRead,
+{
+    let params = read_pos_params(storage)?;
+    let validators = read_all_validator_addresses(storage, epoch)?;
+
+    let vote_prefix_key =
+        gov_storage::get_offline_vote_prefix_key(proposal_hash);
+    let vote_iter =
+        storage_api::iter_prefix::<OfflineVote>(storage, &vote_prefix_key)?;
+
+    let mut votes = Votes::default();
+    for next_vote in vote_iter {
+        let (_vote_key, vote) = next_vote?;
+        if validators.contains(&vote.address) {
+            let power: VotePower =
+                read_validator_stake(storage, &params, &vote.address, epoch)?
+                    .unwrap_or_default()
+                    .into();
+            votes.add_validator_vote(vote.address, &vote.vote, power);
+            continue;
+        }
+        for validator in find_delegation_validators(storage, &vote.address)? {
+            let bond_id = BondId {
+                source: vote.address.clone(),
+                validator,
+            };
+            let power = VotePower::from(
+                bond_amount(storage, &params, &bond_id, epoch)?.1,
+            );
+            if power != 0 {
+                votes.add_delegator_vote(
+                    vote.address.clone(),
+                    bond_id.validator,
+                    &vote.vote,
+                    power,
+                );
+            }
+        }
+    }
+    Ok(votes)
+}
+
+/// Check if an address can vote on an offline proposal tallied at the given
+/// epoch, i.e. if it's a validator or it has some bonded stake at that epoch
+pub fn is_offline_voter<S>(
+    storage: &S,
+    epoch: Epoch,
+    address: &Address,
+) -> storage_api::Result<bool>
+where
+    S: storage_api::StorageRead,
+{
+    if read_all_validator_addresses(storage, epoch)?.contains(address) {
+        return Ok(true);
+    }
+    let params = read_pos_params(storage)?;
+    for validator in find_delegation_validators(storage, address)? {
+        let bond_id = BondId {
+            source: address.clone(),
+            validator,
+        };
+        let power =
+            VotePower::from(bond_amount(storage, &params, &bond_id, epoch)?.1);
+        if power != 0 {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
 /// Calculate the valid voting window for validator given a proposal epoch
 /// details
 pub fn is_valid_validator_voting_period(
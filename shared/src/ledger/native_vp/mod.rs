@@ -10,7 +10,7 @@ use std::collections::BTreeSet;
 
 pub use namada_core::ledger::vp_env::VpEnv;
 
-use super::storage_api::{self, ResultExt, StorageRead};
+use super::storage_api::{self, OptionExt, ResultExt, StorageRead};
 use super::vp_host_fns;
 use crate::ledger::gas::VpGasMeter;
 use crate::ledger::storage;
@@ -19,7 +19,10 @@ use crate::ledger::storage::{Storage, StorageHasher};
 use crate::proto::Tx;
 use crate::types::address::{Address, InternalAddress};
 use crate::types::hash::Hash;
-use crate::types::storage::{BlockHash, BlockHeight, Epoch, Key, TxIndex};
+use crate::types::storage::{
+    BlockHash, BlockHeight, Epoch, Header, Key, TxIndex,
+};
+use crate::types::time::DateTimeUtc;
 use crate::vm::prefix_iter::PrefixIterators;
 use crate::vm::WasmCacheAccess;
 
@@ -244,6 +247,13 @@ where
         self.ctx.get_block_epoch()
     }
 
+    fn get_block_header(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<Header>, storage_api::Error> {
+        self.ctx.get_block_header(height)
+    }
+
     fn get_tx_index(&self) -> Result<TxIndex, storage_api::Error> {
         self.ctx.get_tx_index().into_storage_result()
     }
@@ -328,6 +338,13 @@ where
         self.ctx.get_block_epoch()
     }
 
+    fn get_block_header(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<Header>, storage_api::Error> {
+        self.ctx.get_block_header(height)
+    }
+
     fn get_tx_index(&self) -> Result<TxIndex, storage_api::Error> {
         self.ctx.get_tx_index().into_storage_result()
     }
@@ -412,6 +429,36 @@ where
         .into_storage_result()
     }
 
+    fn get_block_header(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<Header>, storage_api::Error> {
+        vp_host_fns::get_block_header(
+            &mut self.gas_meter.borrow_mut(),
+            self.storage,
+            height,
+        )
+        .into_storage_result()
+    }
+
+    fn get_block_time(&self) -> Result<DateTimeUtc, storage_api::Error> {
+        let height = self.get_block_height()?;
+        let header = self
+            .get_block_header(height)?
+            .ok_or_err_msg("The block header is not available")?;
+        Ok(header.time)
+    }
+
+    fn get_block_proposer(
+        &self,
+    ) -> Result<Option<Address>, storage_api::Error> {
+        vp_host_fns::get_block_proposer(
+            &mut self.gas_meter.borrow_mut(),
+            self.storage,
+        )
+        .into_storage_result()
+    }
+
     fn get_tx_index(&self) -> Result<TxIndex, storage_api::Error> {
         vp_host_fns::get_tx_index(
             &mut self.gas_meter.borrow_mut(),
@@ -502,6 +549,74 @@ where
         }
     }
 
+    fn query_vp(
+        &self,
+        address: &Address,
+        input_data: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, storage_api::Error> {
+        #[cfg(feature = "wasm-runtime")]
+        {
+            use std::marker::PhantomData;
+
+            use crate::vm::host_env::VpCtx;
+            use crate::vm::wasm::run::VpEvalWasm;
+
+            let vp_code =
+                match self.read_bytes_pre(&Key::validity_predicate(address))? {
+                    Some(vp_code) => vp_code,
+                    None => return Ok(None),
+                };
+            let eval_runner = VpEvalWasm {
+                db: PhantomData,
+                hasher: PhantomData,
+                cache_access: PhantomData,
+            };
+            let mut iterators: PrefixIterators<'_, DB> =
+                PrefixIterators::default();
+            let mut result_buffer: Option<Vec<u8>> = None;
+            let mut vp_wasm_cache = self.vp_wasm_cache.clone();
+
+            let ctx = VpCtx::new(
+                self.address,
+                self.storage,
+                self.write_log,
+                &mut self.gas_meter.borrow_mut(),
+                self.tx,
+                self.tx_index,
+                &mut iterators,
+                self.verifiers,
+                &mut result_buffer,
+                self.keys_changed,
+                &eval_runner,
+                &mut vp_wasm_cache,
+                #[cfg(not(feature = "mainnet"))]
+                false,
+            );
+            match eval_runner
+                .query_native_result(ctx, address, vp_code, input_data)
+            {
+                Ok(result) => Ok(result),
+                Err(err) => {
+                    tracing::warn!(
+                        "VP query from a native VP failed with: {}",
+                        err
+                    );
+                    Ok(None)
+                }
+            }
+        }
+
+        #[cfg(not(feature = "wasm-runtime"))]
+        {
+            // This line is here to prevent unused var clippy warning
+            let _ = (address, input_data);
+            unimplemented!(
+                "The \"wasm-runtime\" feature must be enabled to use the \
+                 `query_vp` function."
+            )
+        }
+    }
+
     fn verify_tx_signature(
         &self,
         pk: &crate::types::key::common::PublicKey,
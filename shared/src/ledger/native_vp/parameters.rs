@@ -4,11 +4,12 @@ use std::collections::BTreeSet;
 
 use namada_core::ledger::storage;
 use namada_core::types::address::{Address, InternalAddress};
+use namada_core::types::hash::Hash;
 use namada_core::types::storage::Key;
 use thiserror::Error;
 
 use super::governance;
-use crate::ledger::native_vp::{self, Ctx, NativeVp};
+use crate::ledger::native_vp::{self, Ctx, NativeVp, VpEnv};
 use crate::vm::WasmCacheAccess;
 
 #[allow(missing_docs)]
@@ -56,6 +57,14 @@ where
                     tx_data,
                 )
                 .unwrap_or(false),
+                KeyType::WASM_CODE(code_hash) => {
+                    governance::utils::is_proposal_accepted(
+                        &self.ctx.pre(),
+                        tx_data,
+                    )
+                    .unwrap_or(false)
+                        && self.is_valid_wasm_code(key, &code_hash)
+                }
                 KeyType::UNKNOWN_PARAMETER => false,
                 KeyType::UNKNOWN => true,
             }
@@ -64,6 +73,23 @@ where
     }
 }
 
+impl<'a, DB, H, CA> ParametersVp<'a, DB, H, CA>
+where
+    DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: 'static + storage::StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    /// A wasm code can only be stored under its own hash
+    fn is_valid_wasm_code(&self, key: &Key, code_hash: &Hash) -> bool {
+        match self.ctx.read_bytes_post(key) {
+            Ok(Some(code)) => Hash::sha256(code) == *code_hash,
+            // The code can be deleted
+            Ok(None) => true,
+            Err(_) => false,
+        }
+    }
+}
+
 impl From<native_vp::Error> for Error {
     fn from(err: native_vp::Error) -> Self {
         Self::NativeVpError(err)
@@ -76,6 +102,9 @@ enum KeyType {
     PARAMETER,
     #[allow(clippy::upper_case_acronyms)]
     #[allow(non_camel_case_types)]
+    WASM_CODE(Hash),
+    #[allow(clippy::upper_case_acronyms)]
+    #[allow(non_camel_case_types)]
     UNKNOWN_PARAMETER,
     #[allow(clippy::upper_case_acronyms)]
     UNKNOWN,
@@ -83,7 +112,11 @@ enum KeyType {
 
 impl From<&Key> for KeyType {
     fn from(value: &Key) -> Self {
-        if namada_core::ledger::parameters::storage::is_protocol_parameter_key(
+        if let Some(code_hash) =
+            namada_core::ledger::parameters::storage::get_wasm_code_hash(value)
+        {
+            KeyType::WASM_CODE(code_hash)
+        } else if namada_core::ledger::parameters::storage::is_protocol_parameter_key(
             value,
         ) {
             KeyType::PARAMETER
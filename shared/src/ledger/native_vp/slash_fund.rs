@@ -7,7 +7,7 @@ use namada_core::ledger::slash_fund;
 pub use namada_core::ledger::slash_fund::storage;
 use thiserror::Error;
 
-use crate::ledger::native_vp::{self, governance, Ctx, NativeVp};
+use crate::ledger::native_vp::{self, Ctx, NativeVp};
 use crate::ledger::storage::{self as ledger_storage, StorageHasher};
 use crate::ledger::storage_api::StorageRead;
 use crate::types::address::{Address, InternalAddress};
@@ -48,23 +48,18 @@ where
 
     fn validate_tx(
         &self,
-        tx_data: &[u8],
+        _tx_data: &[u8],
         keys_changed: &BTreeSet<Key>,
         _verifiers: &BTreeSet<Address>,
     ) -> Result<bool> {
-        let native_token = self.ctx.pre().get_native_token()?;
         let result = keys_changed.iter().all(|key| {
-            let key_type: KeyType = get_key_type(key, &native_token);
+            let key_type: KeyType = get_key_type(key);
             match key_type {
                 KeyType::BALANCE(addr) => {
                     if addr.ne(&slash_fund::ADDRESS) {
                         return true;
                     }
-                    governance::utils::is_proposal_accepted(
-                        &self.ctx.pre(),
-                        tx_data,
-                    )
-                    .unwrap_or(false)
+                    self.is_valid_balance(key).unwrap_or(false)
                 }
                 KeyType::UNKNOWN_SLASH_FUND => false,
                 KeyType::UNKNOWN => true,
@@ -74,6 +69,25 @@ where
     }
 }
 
+impl<'a, DB, H, CA> SlashFundVp<'a, DB, H, CA>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    /// Validate a change of a balance of the slash fund. The fund can be
+    /// credited by anyone, but no transaction can debit it. It's only paid
+    /// out by the protocol, in the disbursements registered by accepted
+    /// governance proposals.
+    fn is_valid_balance(&self, key: &Key) -> Result<bool> {
+        let pre_balance: token::Amount =
+            self.ctx.pre().read(key)?.unwrap_or_default();
+        let post_balance: token::Amount =
+            self.ctx.post().read(key)?.unwrap_or_default();
+        Ok(post_balance >= pre_balance)
+    }
+}
+
 #[allow(clippy::upper_case_acronyms)]
 enum KeyType {
     #[allow(clippy::upper_case_acronyms)]
@@ -85,14 +99,11 @@ enum KeyType {
     UNKNOWN,
 }
 
-fn get_key_type(value: &Key, native_token: &Address) -> KeyType {
+fn get_key_type(value: &Key) -> KeyType {
     if storage::is_slash_fund_key(value) {
         KeyType::UNKNOWN_SLASH_FUND
-    } else if token::is_any_token_balance_key(value).is_some() {
-        match token::is_balance_key(native_token, value) {
-            Some(addr) => KeyType::BALANCE(addr.clone()),
-            None => KeyType::UNKNOWN,
-        }
+    } else if let Some(addr) = token::is_any_token_balance_key(value) {
+        KeyType::BALANCE(addr.clone())
     } else {
         KeyType::UNKNOWN
     }
@@ -6,17 +6,18 @@ use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use thiserror::Error;
 
 use crate::ledger::eth_bridge::vp::EthBridge;
-use crate::ledger::gas::{self, BlockGasMeter, VpGasMeter};
-use crate::ledger::ibc::vp::{Ibc, IbcToken};
+use crate::ledger::gas::{self, BlockGasMeter, GasSchedule, VpGasMeter};
+use crate::ledger::ibc::vp::{Ibc, IbcToken, InterchainAccount};
 use crate::ledger::native_vp::governance::GovernanceVp;
 use crate::ledger::native_vp::parameters::{self, ParametersVp};
 use crate::ledger::native_vp::slash_fund::SlashFundVp;
 use crate::ledger::native_vp::{self, NativeVp};
 use crate::ledger::pos::{self, PosVP};
-use crate::ledger::storage::write_log::WriteLog;
+use crate::ledger::storage::write_log::{StorageModification, WriteLog};
 use crate::ledger::storage::{DBIter, Storage, StorageHasher, DB};
-use crate::proto::{self, Tx};
+use crate::proto::{self, Tx, TxCode};
 use crate::types::address::{Address, InternalAddress};
+use crate::types::hash::Hash;
 use crate::types::storage;
 use crate::types::storage::TxIndex;
 use crate::types::transaction::{DecryptedTx, TxResult, TxType, VpsResult};
@@ -50,6 +51,10 @@ pub enum Error {
     ParametersNativeVpError(parameters::Error),
     #[error("IBC Token native VP: {0}")]
     IbcTokenNativeVpError(crate::ledger::ibc::vp::IbcTokenError),
+    #[error("IBC interchain account native VP: {0}")]
+    InterchainAccountNativeVpError(
+        crate::ledger::ibc::vp::InterchainAccountError,
+    ),
     #[error("Governance native VP error: {0}")]
     GovernanceNativeVpError(crate::ledger::native_vp::governance::Error),
     #[error("SlashFund native VP error: {0}")]
@@ -58,6 +63,10 @@ pub enum Error {
     EthBridgeNativeVpError(crate::ledger::eth_bridge::vp::Error),
     #[error("Access to an internal address {0} is forbidden")]
     AccessForbidden(InternalAddress),
+    #[error("The wasm code with hash {0} doesn't exist")]
+    MissingWasmCode(Hash),
+    #[error("A call of a batch transaction can't be another batch")]
+    NestedBatchTx,
 }
 
 /// Result of applying a transaction
@@ -70,11 +79,16 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// If the given tx is a successfully decrypted payload apply the necessary
 /// vps. Otherwise, we include the tx on chain with the gas charge added
 /// but no further validations.
+///
+/// A third-party account that paid the fee of the tx's wrapper, if any, is
+/// added to the verifiers of a decrypted tx so that its VP can decide whether
+/// to sponsor it.
 #[allow(clippy::too_many_arguments)]
 pub fn apply_tx<D, H, CA>(
     tx: TxType,
     tx_length: usize,
     tx_index: TxIndex,
+    fee_payer: Option<&Address>,
     block_gas_meter: &mut BlockGasMeter,
     write_log: &mut WriteLog,
     storage: &Storage<D, H>,
@@ -96,44 +110,38 @@ where
             tx,
             #[cfg(not(feature = "mainnet"))]
             has_valid_pow,
-        }) => {
-            let verifiers = execute_tx(
-                &tx,
+        }) => match tx.batch_calls() {
+            Some(calls) => apply_batch_tx(
+                calls,
                 &tx_index,
-                storage,
+                fee_payer,
                 block_gas_meter,
                 write_log,
-                vp_wasm_cache,
-                tx_wasm_cache,
-            )?;
-
-            let vps_result = check_vps(
-                &tx,
-                &tx_index,
                 storage,
-                block_gas_meter,
-                write_log,
-                &verifiers,
                 vp_wasm_cache,
+                tx_wasm_cache,
                 #[cfg(not(feature = "mainnet"))]
                 has_valid_pow,
-            )?;
-
-            let gas_used = block_gas_meter
-                .finalize_transaction()
-                .map_err(Error::GasError)?;
-            let initialized_accounts = write_log.get_initialized_accounts();
-            let changed_keys = write_log.get_keys();
-            let ibc_event = write_log.take_ibc_event();
-
-            Ok(TxResult {
-                gas_used,
-                changed_keys,
-                vps_result,
-                initialized_accounts,
-                ibc_event,
-            })
-        }
+            ),
+            None => {
+                let mut result = apply_call(
+                    &tx,
+                    &tx_index,
+                    fee_payer,
+                    block_gas_meter,
+                    write_log,
+                    storage,
+                    vp_wasm_cache,
+                    tx_wasm_cache,
+                    #[cfg(not(feature = "mainnet"))]
+                    has_valid_pow,
+                )?;
+                result.gas_used = block_gas_meter
+                    .finalize_transaction()
+                    .map_err(Error::GasError)?;
+                Ok(result)
+            }
+        },
         _ => {
             let gas_used = block_gas_meter
                 .finalize_transaction()
@@ -146,6 +154,156 @@ where
     }
 }
 
+/// Execute a single wasm transaction, either on its own or as a call of a
+/// batch transaction, and check it with the triggered VPs. The returned gas
+/// is the gas used by this call, without finalizing the transaction's gas.
+#[allow(clippy::too_many_arguments)]
+fn apply_call<D, H, CA>(
+    tx: &Tx,
+    tx_index: &TxIndex,
+    fee_payer: Option<&Address>,
+    block_gas_meter: &mut BlockGasMeter,
+    write_log: &mut WriteLog,
+    storage: &Storage<D, H>,
+    vp_wasm_cache: &mut VpCache<CA>,
+    tx_wasm_cache: &mut TxCache<CA>,
+    #[cfg(not(feature = "mainnet"))] has_valid_pow: bool,
+) -> Result<TxResult>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+    CA: 'static + WasmCacheAccess + Sync,
+{
+    let initial_gas = block_gas_meter.get_current_transaction_gas();
+    let mut verifiers = execute_tx(
+        tx,
+        tx_index,
+        storage,
+        block_gas_meter,
+        write_log,
+        vp_wasm_cache,
+        tx_wasm_cache,
+    )?;
+    if let Some(fee_payer) = fee_payer {
+        verifiers.insert(fee_payer.clone());
+    }
+
+    let vps_result = check_vps(
+        tx,
+        tx_index,
+        storage,
+        block_gas_meter,
+        write_log,
+        &verifiers,
+        vp_wasm_cache,
+        #[cfg(not(feature = "mainnet"))]
+        has_valid_pow,
+    )?;
+
+    let gas_used = block_gas_meter.get_current_transaction_gas() - initial_gas;
+    let initialized_accounts = write_log.get_initialized_accounts();
+    let changed_keys = write_log.get_keys();
+    let ibc_event = write_log.take_ibc_event();
+    let events = write_log.take_events();
+
+    Ok(TxResult {
+        gas_used,
+        changed_keys,
+        vps_result,
+        initialized_accounts,
+        ibc_event,
+        events,
+        batch_calls: vec![],
+    })
+}
+
+/// Apply the calls of a batch transaction in order. Each call is executed
+/// against the changes of the previous calls and it's checked by the VPs
+/// triggered by its own changes. The batch is committed only if all the calls
+/// are accepted, otherwise all of them are dropped and the following calls
+/// are not applied.
+#[allow(clippy::too_many_arguments)]
+fn apply_batch_tx<D, H, CA>(
+    calls: &[Tx],
+    tx_index: &TxIndex,
+    fee_payer: Option<&Address>,
+    block_gas_meter: &mut BlockGasMeter,
+    write_log: &mut WriteLog,
+    storage: &Storage<D, H>,
+    vp_wasm_cache: &mut VpCache<CA>,
+    tx_wasm_cache: &mut TxCache<CA>,
+    #[cfg(not(feature = "mainnet"))] has_valid_pow: bool,
+) -> Result<TxResult>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+    CA: 'static + WasmCacheAccess + Sync,
+{
+    write_log.begin_batch();
+    let mut result = TxResult::default();
+    for call in calls {
+        let call_result = if call.batch_calls().is_some() {
+            Err(Error::NestedBatchTx)
+        } else {
+            apply_call(
+                call,
+                tx_index,
+                fee_payer,
+                block_gas_meter,
+                write_log,
+                storage,
+                vp_wasm_cache,
+                tx_wasm_cache,
+                #[cfg(not(feature = "mainnet"))]
+                has_valid_pow,
+            )
+        };
+        let call_result = match call_result {
+            Ok(call_result) => call_result,
+            Err(err) => {
+                write_log.drop_batch();
+                return Err(err);
+            }
+        };
+        let is_accepted = call_result.is_accepted();
+        if is_accepted {
+            write_log.commit_tx();
+        }
+        result
+            .changed_keys
+            .extend(call_result.changed_keys.iter().cloned());
+        result
+            .vps_result
+            .accepted_vps
+            .extend(call_result.vps_result.accepted_vps.iter().cloned());
+        result
+            .vps_result
+            .rejected_vps
+            .extend(call_result.vps_result.rejected_vps.iter().cloned());
+        result
+            .vps_result
+            .errors
+            .extend(call_result.vps_result.errors.iter().cloned());
+        result
+            .initialized_accounts
+            .extend(call_result.initialized_accounts.iter().cloned());
+        result.events.extend(call_result.events.iter().cloned());
+        result.batch_calls.push(call_result);
+        if !is_accepted {
+            break;
+        }
+    }
+    if result.is_accepted() {
+        write_log.commit_batch();
+    } else {
+        write_log.drop_batch();
+    }
+    result.gas_used = block_gas_meter
+        .finalize_transaction()
+        .map_err(Error::GasError)?;
+    Ok(result)
+}
+
 /// Execute a transaction code. Returns verifiers requested by the transaction.
 fn execute_tx<D, H, CA>(
     tx: &Tx,
@@ -161,8 +319,19 @@ where
     H: 'static + StorageHasher + Sync,
     CA: 'static + WasmCacheAccess + Sync,
 {
+    let stored_code;
+    let tx_code = match &tx.code {
+        TxCode::Wasm(code) => code,
+        TxCode::Hash(code_hash) => {
+            stored_code =
+                read_wasm_code(code_hash, storage, gas_meter, write_log)?;
+            &stored_code
+        }
+        // The calls of a batch are applied by `apply_batch_tx`
+        TxCode::Batch(_) => return Err(Error::NestedBatchTx),
+    };
     gas_meter
-        .add_compiling_fee(tx.code.len())
+        .add_compiling_fee(tx_code.len())
         .map_err(Error::GasError)?;
     let empty = vec![];
     let tx_data = tx.data.as_ref().unwrap_or(&empty);
@@ -171,7 +340,7 @@ where
         write_log,
         gas_meter,
         tx_index,
-        &tx.code,
+        tx_code,
         tx_data,
         vp_wasm_cache,
         tx_wasm_cache,
@@ -179,6 +348,37 @@ where
     .map_err(Error::TxRunnerError)
 }
 
+/// Read the wasm code stored on-chain under the given hash, to which a
+/// transaction refers
+fn read_wasm_code<D, H>(
+    code_hash: &Hash,
+    storage: &Storage<D, H>,
+    gas_meter: &mut BlockGasMeter,
+    write_log: &WriteLog,
+) -> Result<Vec<u8>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let key = crate::ledger::parameters::storage::get_wasm_code_key(code_hash);
+    let (modification, gas) = write_log.read(&key);
+    let gas = gas_meter.schedule().storage_read_gas(gas);
+    gas_meter.add(gas).map_err(Error::GasError)?;
+    let code = match modification {
+        Some(StorageModification::Write { value }) => Some(value.clone()),
+        // The code has been deleted or it's not a permanent write
+        Some(_) => None,
+        None => {
+            let (code, gas) =
+                storage.read(&key).map_err(Error::StorageError)?;
+            let gas = gas_meter.schedule().storage_read_gas(gas);
+            gas_meter.add(gas).map_err(Error::GasError)?;
+            code
+        }
+    };
+    code.ok_or_else(|| Error::MissingWasmCode(code_hash.clone()))
+}
+
 /// Check the acceptance of a transaction by validity predicates
 #[allow(clippy::too_many_arguments)]
 fn check_vps<D, H, CA>(
@@ -212,6 +412,7 @@ where
         storage,
         write_log,
         initial_gas,
+        gas_meter.schedule(),
         vp_wasm_cache,
         #[cfg(not(feature = "mainnet"))]
         has_valid_pow,
@@ -235,6 +436,7 @@ fn execute_vps<D, H, CA>(
     storage: &Storage<D, H>,
     write_log: &WriteLog,
     initial_gas: u64,
+    gas_schedule: &GasSchedule,
     vp_wasm_cache: &mut VpCache<CA>,
     #[cfg(not(feature = "mainnet"))]
     // This is true when the wrapper of this tx contained a valid
@@ -249,12 +451,14 @@ where
     verifiers
         .par_iter()
         .try_fold(VpsResult::default, |mut result, addr| {
-            let mut gas_meter = VpGasMeter::new(initial_gas);
+            let mut gas_meter =
+                VpGasMeter::with_schedule(initial_gas, gas_schedule.clone());
             let accept = match &addr {
                 Address::Implicit(_) | Address::Established(_) => {
                     let (vp, gas) = storage
                         .validity_predicate(addr)
                         .map_err(Error::StorageError)?;
+                    let gas = gas_schedule.storage_read_gas(gas);
                     gas_meter.add(gas).map_err(Error::GasError)?;
                     let vp =
                         vp.ok_or_else(|| Error::MissingAddress(addr.clone()))?;
@@ -346,7 +550,8 @@ where
                             gas_meter = parameters.ctx.gas_meter.into_inner();
                             result
                         }
-                        InternalAddress::PosSlashPool => {
+                        InternalAddress::PosSlashPool
+                        | InternalAddress::FeePool => {
                             // Take the gas meter back out of the context
                             gas_meter = ctx.gas_meter.into_inner();
                             Err(Error::AccessForbidden(
@@ -381,6 +586,14 @@ where
                             gas_meter = ibc_token.ctx.gas_meter.into_inner();
                             result
                         }
+                        InternalAddress::InterchainAccount(_) => {
+                            let ica = InterchainAccount { ctx };
+                            let result = ica
+                                .validate_tx(tx_data, &keys_changed, &verifiers)
+                                .map_err(Error::InterchainAccountNativeVpError);
+                            gas_meter = ica.ctx.gas_meter.into_inner();
+                            result
+                        }
                         InternalAddress::EthBridge => {
                             let bridge = EthBridge { ctx };
                             let result = bridge
@@ -0,0 +1,524 @@
+//! Governance queries
+//!
+//! The status of a proposal and its tally are computed with the same functions
+//! as the ledger uses to finalize proposals.
+
+use std::collections::BTreeMap;
+
+use namada_core::ledger::governance::storage as gov_storage;
+use namada_core::ledger::slash_fund::storage as slash_fund_storage;
+
+use crate::ledger::native_vp::governance::utils::{
+    compute_tally, get_proposal_status, get_proposal_votes,
+    get_proposal_weighted_votes, is_expedited,
+};
+use crate::ledger::queries::types::RequestCtx;
+use crate::ledger::storage::{DBIter, StorageHasher, DB};
+use crate::ledger::storage_api::{self, StorageRead};
+use crate::types::address::Address;
+use crate::types::governance::{
+    DisbursementPayment, PendingDisbursement, ProposalDetails, ProposalResult,
+    ProposalStatus, ProposalSummary, ProposalsPage, SlashFundDisbursement,
+    WeightedVote,
+};
+use crate::types::hash::Hash;
+use crate::types::storage::Epoch;
+
+/// The maximum number of proposals in a page
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+// Governance queries
+router! {GOV,
+    // Proposals from the given id, optionally only those with the given
+    // status. A page covers the given number of proposal ids, capped to
+    // `MAX_PAGE_SIZE`, so it may have fewer proposals with a status filter
+    // while there are more pages.
+    ( "proposals" / [status: opt ProposalStatus] / [from_id: u64]
+      / [limit: u64] )
+        -> ProposalsPage = proposals,
+
+    ( "proposal" / [id: u64] ) -> Option<ProposalDetails> = proposal,
+
+    // The votes with their voting power at the end of the voting period
+    ( "votes" / [id: u64] ) -> Vec<WeightedVote> = proposal_votes,
+
+    // The tally at the end of the voting period, which is live until then
+    ( "tally" / [id: u64] ) -> Option<ProposalResult> = proposal_tally,
+
+    // The result of an offline proposal tallied on-chain
+    ( "offline" / [proposal_hash: Hash] ) -> Option<ProposalResult>
+        = offline_proposal_tally,
+
+    // The slash fund disbursements of passed proposals, optionally only those
+    // of the given proposal
+    ( "slash_fund" ) = {
+        ( "pending" / [proposal_id: opt u64] )
+            -> Vec<PendingDisbursement> = pending_disbursements,
+
+        ( "executed" / [proposal_id: opt u64] )
+            -> Vec<DisbursementPayment> = executed_disbursements,
+    },
+}
+
+/// Get a page of proposals
+fn proposals<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    status: Option<ProposalStatus>,
+    from_id: u64,
+    limit: u64,
+) -> storage_api::Result<ProposalsPage>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let limit = limit.clamp(1, MAX_PAGE_SIZE);
+    let counter: u64 = ctx
+        .wl_storage
+        .read(&gov_storage::get_counter_key())?
+        .unwrap_or_default();
+    let to_id = from_id.saturating_add(limit).min(counter);
+    let current_epoch = ctx.wl_storage.storage.last_epoch;
+    let mut proposals = vec![];
+    for id in from_id..to_id {
+        if let Some(status) = status {
+            if !may_have_status(&ctx, current_epoch, id, status)? {
+                continue;
+            }
+        }
+        match read_proposal_summary(&ctx, id)? {
+            Some(summary)
+                if status.map_or(true, |status| status == summary.status) =>
+            {
+                proposals.push(summary)
+            }
+            _ => {}
+        }
+    }
+    let next_id = if to_id < counter { Some(to_id) } else { None };
+    Ok(ProposalsPage { proposals, next_id })
+}
+
+/// Check if a proposal may have the given status from its stored status or
+/// its voting period, so that only the proposals whose voting period is over
+/// but that aren't finalized yet have to be tallied to filter them
+fn may_have_status<D, H>(
+    ctx: &RequestCtx<'_, D, H>,
+    current_epoch: Epoch,
+    id: u64,
+    status: ProposalStatus,
+) -> storage_api::Result<bool>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let stored_status: Option<ProposalStatus> = ctx
+        .wl_storage
+        .read(&gov_storage::get_proposal_status_key(id))?;
+    if let Some(stored_status) = stored_status {
+        return Ok(stored_status == status);
+    }
+    let end_epoch = match read_end_epoch(ctx, id)? {
+        Some(end_epoch) => end_epoch,
+        None => return Ok(false),
+    };
+    if current_epoch <= end_epoch {
+        return Ok(matches!(
+            status,
+            ProposalStatus::Pending | ProposalStatus::Voting
+        ));
+    }
+    Ok(match status {
+        // Only a finalized proposal can be executed
+        ProposalStatus::Pending | ProposalStatus::Executed => false,
+        // Only a downgraded expedited proposal keeps on voting
+        ProposalStatus::Voting => is_expedited(ctx.wl_storage, id)?,
+        ProposalStatus::Passed | ProposalStatus::Rejected => true,
+    })
+}
+
+/// Get a proposal with its content and the hash of its code
+fn proposal<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    id: u64,
+) -> storage_api::Result<Option<ProposalDetails>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let summary = match read_proposal_summary(&ctx, id)? {
+        Some(summary) => summary,
+        None => return Ok(None),
+    };
+    let content: BTreeMap<String, String> = ctx
+        .wl_storage
+        .read(&gov_storage::get_content_key(id))?
+        .unwrap_or_default();
+    let code_hash = ctx
+        .wl_storage
+        .read_bytes(&gov_storage::get_proposal_code_key(id))?
+        .map(|code| Hash::sha256(&code));
+    Ok(Some(ProposalDetails {
+        summary,
+        content,
+        code_hash,
+    }))
+}
+
+/// Get the votes of a proposal with their voting power
+fn proposal_votes<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    id: u64,
+) -> storage_api::Result<Vec<WeightedVote>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    match read_end_epoch(&ctx, id)? {
+        Some(end_epoch) => {
+            get_proposal_weighted_votes(ctx.wl_storage, end_epoch, id)
+        }
+        None => Ok(vec![]),
+    }
+}
+
+/// Get the tally of a proposal
+fn proposal_tally<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    id: u64,
+) -> storage_api::Result<Option<ProposalResult>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let end_epoch = match read_end_epoch(&ctx, id)? {
+        Some(end_epoch) => end_epoch,
+        None => return Ok(None),
+    };
+    let votes = get_proposal_votes(ctx.wl_storage, end_epoch, id)?;
+    compute_tally(ctx.wl_storage, end_epoch, votes).map(Some)
+}
+
+/// Get the result of an offline proposal tallied on-chain
+fn offline_proposal_tally<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    proposal_hash: Hash,
+) -> storage_api::Result<Option<ProposalResult>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    ctx.wl_storage
+        .read(&gov_storage::get_offline_proposal_result_key(
+            &proposal_hash,
+        ))
+}
+
+/// Get the slash fund disbursements that aren't fully paid yet
+fn pending_disbursements<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    proposal_id: Option<u64>,
+) -> storage_api::Result<Vec<PendingDisbursement>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let prefix = match proposal_id {
+        Some(proposal_id) => {
+            slash_fund_storage::get_proposal_pending_disbursements_prefix(
+                proposal_id,
+            )
+        }
+        None => slash_fund_storage::get_pending_disbursements_prefix(),
+    };
+    let mut pending = vec![];
+    for next in storage_api::iter_prefix::<SlashFundDisbursement>(
+        ctx.wl_storage,
+        &prefix,
+    )? {
+        let (key, disbursement) = next?;
+        let (key_proposal_id, index) =
+            match slash_fund_storage::get_pending_disbursement_ids(&key) {
+                Some(ids) => ids,
+                None => continue,
+            };
+        // The prefix of a proposal id also matches longer ids
+        if proposal_id.map_or(true, |id| id == key_proposal_id) {
+            pending.push(PendingDisbursement {
+                proposal_id: key_proposal_id,
+                index,
+                disbursement,
+            });
+        }
+    }
+    Ok(pending)
+}
+
+/// Get the payments made from the slash fund
+fn executed_disbursements<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    proposal_id: Option<u64>,
+) -> storage_api::Result<Vec<DisbursementPayment>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let prefix = match proposal_id {
+        Some(proposal_id) => {
+            slash_fund_storage::get_proposal_executed_disbursements_prefix(
+                proposal_id,
+            )
+        }
+        None => slash_fund_storage::get_executed_disbursements_prefix(),
+    };
+    let mut payments = vec![];
+    for next in storage_api::iter_prefix::<DisbursementPayment>(
+        ctx.wl_storage,
+        &prefix,
+    )? {
+        let (_key, payment) = next?;
+        // The prefix of a proposal id also matches longer ids
+        if proposal_id.map_or(true, |id| id == payment.proposal_id) {
+            payments.push(payment);
+        }
+    }
+    Ok(payments)
+}
+
+fn read_end_epoch<D, H>(
+    ctx: &RequestCtx<'_, D, H>,
+    id: u64,
+) -> storage_api::Result<Option<Epoch>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    ctx.wl_storage
+        .read(&gov_storage::get_voting_end_epoch_key(id))
+}
+
+fn read_proposal_summary<D, H>(
+    ctx: &RequestCtx<'_, D, H>,
+    id: u64,
+) -> storage_api::Result<Option<ProposalSummary>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let current_epoch = ctx.wl_storage.storage.last_epoch;
+    let status = get_proposal_status(ctx.wl_storage, current_epoch, id)?;
+    let author: Option<Address> =
+        ctx.wl_storage.read(&gov_storage::get_author_key(id))?;
+    let voting_start_epoch: Option<Epoch> = ctx
+        .wl_storage
+        .read(&gov_storage::get_voting_start_epoch_key(id))?;
+    let voting_end_epoch = read_end_epoch(ctx, id)?;
+    let grace_epoch: Option<Epoch> =
+        ctx.wl_storage.read(&gov_storage::get_grace_epoch_key(id))?;
+    match (
+        status,
+        author,
+        voting_start_epoch,
+        voting_end_epoch,
+        grace_epoch,
+    ) {
+        (
+            Some(status),
+            Some(author),
+            Some(voting_start_epoch),
+            Some(voting_end_epoch),
+            Some(grace_epoch),
+        ) => Ok(Some(ProposalSummary {
+            id,
+            author,
+            voting_start_epoch,
+            voting_end_epoch,
+            grace_epoch,
+            status,
+        })),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use namada_core::ledger::governance::storage as gov_storage;
+    use namada_core::ledger::slash_fund::storage as slash_fund_storage;
+
+    use crate::ledger::queries::testing::TestClient;
+    use crate::ledger::queries::RPC;
+    use crate::ledger::storage_api::{self, StorageWrite};
+    use crate::types::address::testing::established_address_1;
+    use crate::types::governance::{
+        DisbursementPayment, DisbursementSchedule, PendingDisbursement,
+        ProposalStatus, SlashFundDisbursement,
+    };
+    use crate::types::hash::Hash;
+    use crate::types::token;
+
+    #[tokio::test]
+    async fn test_gov_proposal_queries() -> storage_api::Result<()> {
+        // Initialize the `TestClient`
+        let mut client = TestClient::new(RPC);
+        let current_epoch = client.wl_storage.storage.last_epoch;
+
+        // Write a pending proposal, a proposal in its voting period and an
+        // executed proposal with code
+        let epochs = [
+            (current_epoch.next(), current_epoch.next().next()),
+            (current_epoch, current_epoch.next()),
+            (current_epoch, current_epoch),
+        ];
+        for (id, (start_epoch, end_epoch)) in epochs.into_iter().enumerate() {
+            let id = id as u64;
+            let storage = &mut client.wl_storage;
+            storage.write(
+                &gov_storage::get_author_key(id),
+                established_address_1(),
+            )?;
+            storage.write(
+                &gov_storage::get_voting_start_epoch_key(id),
+                start_epoch,
+            )?;
+            storage
+                .write(&gov_storage::get_voting_end_epoch_key(id), end_epoch)?;
+            storage.write(
+                &gov_storage::get_grace_epoch_key(id),
+                end_epoch.next(),
+            )?;
+            storage.write(
+                &gov_storage::get_content_key(id),
+                std::collections::BTreeMap::from([(
+                    "title".to_owned(),
+                    format!("Proposal {}", id),
+                )]),
+            )?;
+        }
+        client
+            .wl_storage
+            .write_bytes(&gov_storage::get_proposal_code_key(2), b"code")?;
+        client.wl_storage.write(
+            &gov_storage::get_proposal_status_key(2),
+            ProposalStatus::Executed,
+        )?;
+        client
+            .wl_storage
+            .write(&gov_storage::get_counter_key(), epochs.len() as u64)?;
+        // It has to be committed to be visible in a query
+        client.wl_storage.commit_tx();
+        client.wl_storage.commit_block().unwrap();
+
+        let page = RPC.gov().proposals(&client, &None, &0, &2).await.unwrap();
+        let statuses: Vec<_> = page
+            .proposals
+            .iter()
+            .map(|proposal| proposal.status)
+            .collect();
+        assert_eq!(statuses, [ProposalStatus::Pending, ProposalStatus::Voting]);
+        assert_eq!(page.next_id, Some(2));
+
+        let page = RPC
+            .gov()
+            .proposals(&client, &Some(ProposalStatus::Executed), &0, &10)
+            .await
+            .unwrap();
+        assert_eq!(page.proposals.len(), 1);
+        assert_eq!(page.proposals[0].id, 2);
+        assert_eq!(page.next_id, None);
+
+        // A page covers the limit of proposal ids, even if they don't match
+        // the status
+        let page = RPC
+            .gov()
+            .proposals(&client, &Some(ProposalStatus::Executed), &0, &2)
+            .await
+            .unwrap();
+        assert!(page.proposals.is_empty());
+        assert_eq!(page.next_id, Some(2));
+
+        let proposal = RPC.gov().proposal(&client, &2).await.unwrap().unwrap();
+        assert_eq!(proposal.content["title"], "Proposal 2");
+        assert_eq!(proposal.code_hash, Some(Hash::sha256(b"code")));
+        assert_eq!(proposal.summary.grace_epoch, current_epoch.next());
+        assert!(RPC.gov().proposal(&client, &3).await.unwrap().is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_slash_fund_queries() -> storage_api::Result<()> {
+        // Initialize the `TestClient`
+        let mut client = TestClient::new(RPC);
+        let current_epoch = client.wl_storage.storage.last_epoch;
+
+        // Write pending disbursements of proposals 1 and 10, whose keys share
+        // a prefix, and a payment of proposal 1
+        let disbursement = SlashFundDisbursement {
+            target: established_address_1(),
+            amount: token::Amount::whole(10),
+            schedule: DisbursementSchedule::OneOff,
+        };
+        for proposal_id in [1, 10] {
+            client.wl_storage.write(
+                &slash_fund_storage::get_pending_disbursement_key(
+                    proposal_id,
+                    0,
+                ),
+                disbursement.clone(),
+            )?;
+        }
+        let payment = DisbursementPayment {
+            proposal_id: 1,
+            index: 1,
+            target: established_address_1(),
+            amount: token::Amount::whole(5),
+            epoch: current_epoch,
+        };
+        client.wl_storage.write(
+            &slash_fund_storage::get_executed_disbursement_key(
+                1,
+                1,
+                current_epoch.0,
+            ),
+            payment.clone(),
+        )?;
+        // It has to be committed to be visible in a query
+        client.wl_storage.commit_tx();
+        client.wl_storage.commit_block().unwrap();
+
+        let pending = RPC
+            .gov()
+            .pending_disbursements(&client, &None)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 2);
+
+        let pending = RPC
+            .gov()
+            .pending_disbursements(&client, &Some(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            pending,
+            vec![PendingDisbursement {
+                proposal_id: 1,
+                index: 0,
+                disbursement,
+            }]
+        );
+
+        let executed = RPC
+            .gov()
+            .executed_disbursements(&client, &Some(1))
+            .await
+            .unwrap();
+        assert_eq!(executed, vec![payment]);
+        let executed = RPC
+            .gov()
+            .executed_disbursements(&client, &Some(10))
+            .await
+            .unwrap();
+        assert!(executed.is_empty());
+
+        Ok(())
+    }
+}
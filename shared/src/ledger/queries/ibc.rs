@@ -0,0 +1,466 @@
+//! IBC queries
+//!
+//! The IBC state is stored encoded with protobuf, so the handlers that can
+//! return a proof respond with the borsh-encoded raw value, if any. The value
+//! can be decoded with the client-only methods.
+
+use borsh::BorshSerialize;
+use namada_core::ledger::ibc::storage::{
+    calc_hash, channel_key, client_state_key, connection_key,
+    consensus_state_key, flow_key, flow_limit_key, ibc_denom_key,
+    ibc_denom_prefix, packet_ack_prefix, packet_commitment_prefix,
+};
+
+use crate::ibc::core::ics02_client::height::Height;
+use crate::ibc::core::ics24_host::identifier::{
+    ChannelId, ClientId, ConnectionId, PortChannelId, PortId,
+};
+use crate::ledger::queries::shell::{storage_prefix, storage_value};
+use crate::ledger::queries::types::{RequestCtx, RequestQuery};
+use crate::ledger::queries::EncodedResponseQuery;
+use crate::ledger::storage::{DBIter, StorageHasher, DB};
+use crate::ledger::storage_api::{self, ResultExt, StorageRead};
+use crate::types::ibc::{IbcFlow, IbcFlowLimit};
+use crate::types::storage::{self, PrefixValue};
+
+type FlowUsage = (IbcFlowLimit, IbcFlow);
+
+// IBC queries
+router! {IBC,
+    ( "client_state" / [client_id: ClientId] )
+        -> Option<Vec<u8>> = (with_options client_state),
+
+    ( "consensus_state" / [client_id: ClientId] / [revision_number: u64]
+      / [revision_height: u64] )
+        -> Option<Vec<u8>> = (with_options consensus_state),
+
+    ( "connection" / [conn_id: ConnectionId] )
+        -> Option<Vec<u8>> = (with_options connection),
+
+    ( "channel" / [port_id: PortId] / [channel_id: ChannelId] )
+        -> Option<Vec<u8>> = (with_options channel),
+
+    // Commitments of the packets which haven't been acknowledged yet
+    ( "commitments" / [port_id: PortId] / [channel_id: ChannelId] )
+        -> Vec<PrefixValue> = (with_options packet_commitments),
+
+    ( "acks" / [port_id: PortId] / [channel_id: ChannelId] )
+        -> Vec<PrefixValue> = (with_options packet_acks),
+
+    ( "denom" / [token_hash: String] )
+        -> Option<Vec<u8>> = (with_options denom),
+
+    ( "denoms" ) -> Vec<PrefixValue> = (with_options denoms),
+
+    // The denom is the last argument because it may contain slashes
+    ( "flow" / [port_id: PortId] / [channel_id: ChannelId] / [denom: String] )
+        -> FlowUsage = flow,
+}
+
+/// Read the raw value of the given key with an optional proof. The value is
+/// borsh-encoded as `Option<Vec<u8>>`.
+fn read_raw_value<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    key: storage::Key,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let response = storage_value(ctx, request, key)?;
+    let value = if response.data.is_empty() {
+        None
+    } else {
+        Some(response.data)
+    };
+    Ok(EncodedResponseQuery {
+        data: value.try_to_vec().into_storage_result()?,
+        info: response.info,
+        proof: response.proof,
+    })
+}
+
+/// Get the protobuf-encoded client state
+fn client_state<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    client_id: ClientId,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    read_raw_value(ctx, request, client_state_key(&client_id))
+}
+
+/// Get the protobuf-encoded consensus state of the client at the given height
+fn consensus_state<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    client_id: ClientId,
+    revision_number: u64,
+    revision_height: u64,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let height = Height::new(revision_number, revision_height);
+    read_raw_value(ctx, request, consensus_state_key(&client_id, height))
+}
+
+/// Get the protobuf-encoded connection end
+fn connection<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    conn_id: ConnectionId,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    read_raw_value(ctx, request, connection_key(&conn_id))
+}
+
+/// Get the protobuf-encoded channel end
+fn channel<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    port_id: PortId,
+    channel_id: ChannelId,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let port_channel_id = PortChannelId {
+        port_id,
+        channel_id,
+    };
+    read_raw_value(ctx, request, channel_key(&port_channel_id))
+}
+
+/// Get the pending packet commitments of the channel
+fn packet_commitments<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    port_id: PortId,
+    channel_id: ChannelId,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    storage_prefix(
+        ctx,
+        request,
+        packet_commitment_prefix(&port_id, &channel_id),
+    )
+}
+
+/// Get the acknowledgement commitments of the channel
+fn packet_acks<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    port_id: PortId,
+    channel_id: ChannelId,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    storage_prefix(ctx, request, packet_ack_prefix(&port_id, &channel_id))
+}
+
+/// Get the denom trace of the hashed IBC token
+fn denom<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    token_hash: String,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    read_raw_value(ctx, request, ibc_denom_key(token_hash))
+}
+
+/// Get all the denom traces of the IBC tokens received by this chain
+fn denoms<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    storage_prefix(ctx, request, ibc_denom_prefix())
+}
+
+/// Get the flow limit and the flows in the current epoch of the given denom on
+/// the channel. The denom is the one on this chain, i.e. prefixed with this
+/// chain's port and channel for a token received from the counterparty.
+fn flow<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    port_id: PortId,
+    channel_id: ChannelId,
+    denom: String,
+) -> storage_api::Result<FlowUsage>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let token_hash = calc_hash(&denom);
+    let limit = ctx
+        .wl_storage
+        .read(&flow_limit_key(&port_id, &channel_id, &token_hash))?
+        .unwrap_or_default();
+    let flow: IbcFlow = ctx
+        .wl_storage
+        .read(&flow_key(&port_id, &channel_id, &token_hash))?
+        .unwrap_or_default();
+    let flow = flow.at_epoch(ctx.wl_storage.storage.last_epoch);
+    Ok((limit, flow))
+}
+
+#[cfg(any(test, feature = "async-client"))]
+pub mod client_only_methods {
+    use namada_core::ledger::ibc::storage::port_channel_sequence_id;
+
+    use super::Ibc;
+    use crate::ibc::core::ics02_client::client_consensus::AnyConsensusState;
+    use crate::ibc::core::ics02_client::client_state::AnyClientState;
+    use crate::ibc::core::ics02_client::height::Height;
+    use crate::ibc::core::ics03_connection::connection::ConnectionEnd;
+    use crate::ibc::core::ics04_channel::channel::ChannelEnd;
+    use crate::ibc::core::ics04_channel::packet::Sequence;
+    use crate::ibc::core::ics24_host::identifier::{
+        ChannelId, ClientId, ConnectionId, PortId,
+    };
+    use crate::ledger::queries::{Client, ResponseQuery};
+    use crate::tendermint_proto::Protobuf;
+    use crate::types::storage::{BlockHeight, PrefixValue};
+
+    fn invalid_data(err: impl std::fmt::Display) -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string())
+    }
+
+    fn decode_response<T, E>(
+        response: ResponseQuery<Option<Vec<u8>>>,
+        decode: impl FnOnce(&[u8]) -> Result<T, E>,
+    ) -> Result<ResponseQuery<Option<T>>, std::io::Error>
+    where
+        E: std::fmt::Display,
+    {
+        let ResponseQuery { data, info, proof } = response;
+        let data = data
+            .map(|bytes| decode(&bytes))
+            .transpose()
+            .map_err(invalid_data)?;
+        Ok(ResponseQuery { data, info, proof })
+    }
+
+    fn sequences(
+        response: ResponseQuery<Vec<PrefixValue>>,
+    ) -> Result<ResponseQuery<Vec<(Sequence, Vec<u8>)>>, std::io::Error> {
+        let ResponseQuery { data, info, proof } = response;
+        let data = data
+            .into_iter()
+            .map(|PrefixValue { key, value }| {
+                let (_, _, sequence) =
+                    port_channel_sequence_id(&key).map_err(invalid_data)?;
+                Ok((sequence, value))
+            })
+            .collect::<Result<_, std::io::Error>>()?;
+        Ok(ResponseQuery { data, info, proof })
+    }
+
+    impl Ibc {
+        /// Get the decoded client state, optionally at the given height and
+        /// with a proof.
+        pub async fn decoded_client_state<CLIENT>(
+            &self,
+            client: &CLIENT,
+            height: Option<BlockHeight>,
+            prove: bool,
+            client_id: &ClientId,
+        ) -> Result<ResponseQuery<Option<AnyClientState>>, CLIENT::Error>
+        where
+            CLIENT: Client + Sync,
+        {
+            let response = self
+                .client_state(client, None, height, prove, client_id)
+                .await?;
+            Ok(decode_response(response, AnyClientState::decode_vec)?)
+        }
+
+        /// Get the decoded consensus state of the client at the given client
+        /// height, optionally at the given block height and with a proof.
+        pub async fn decoded_consensus_state<CLIENT>(
+            &self,
+            client: &CLIENT,
+            height: Option<BlockHeight>,
+            prove: bool,
+            client_id: &ClientId,
+            client_height: &Height,
+        ) -> Result<ResponseQuery<Option<AnyConsensusState>>, CLIENT::Error>
+        where
+            CLIENT: Client + Sync,
+        {
+            let response = self
+                .consensus_state(
+                    client,
+                    None,
+                    height,
+                    prove,
+                    client_id,
+                    &client_height.revision_number,
+                    &client_height.revision_height,
+                )
+                .await?;
+            Ok(decode_response(response, AnyConsensusState::decode_vec)?)
+        }
+
+        /// Get the decoded connection end, optionally at the given height and
+        /// with a proof.
+        pub async fn decoded_connection<CLIENT>(
+            &self,
+            client: &CLIENT,
+            height: Option<BlockHeight>,
+            prove: bool,
+            conn_id: &ConnectionId,
+        ) -> Result<ResponseQuery<Option<ConnectionEnd>>, CLIENT::Error>
+        where
+            CLIENT: Client + Sync,
+        {
+            let response = self
+                .connection(client, None, height, prove, conn_id)
+                .await?;
+            Ok(decode_response(response, ConnectionEnd::decode_vec)?)
+        }
+
+        /// Get the decoded channel end, optionally at the given height and
+        /// with a proof.
+        pub async fn decoded_channel<CLIENT>(
+            &self,
+            client: &CLIENT,
+            height: Option<BlockHeight>,
+            prove: bool,
+            port_id: &PortId,
+            channel_id: &ChannelId,
+        ) -> Result<ResponseQuery<Option<ChannelEnd>>, CLIENT::Error>
+        where
+            CLIENT: Client + Sync,
+        {
+            let response = self
+                .channel(client, None, height, prove, port_id, channel_id)
+                .await?;
+            Ok(decode_response(response, ChannelEnd::decode_vec)?)
+        }
+
+        /// Get the sequences and the commitments of the pending packets of
+        /// the channel, optionally with a proof.
+        pub async fn decoded_packet_commitments<CLIENT>(
+            &self,
+            client: &CLIENT,
+            prove: bool,
+            port_id: &PortId,
+            channel_id: &ChannelId,
+        ) -> Result<ResponseQuery<Vec<(Sequence, Vec<u8>)>>, CLIENT::Error>
+        where
+            CLIENT: Client + Sync,
+        {
+            let response = self
+                .packet_commitments(
+                    client, None, None, prove, port_id, channel_id,
+                )
+                .await?;
+            Ok(sequences(response)?)
+        }
+
+        /// Get the sequences and the acknowledgement commitments of the
+        /// received packets of the channel, optionally with a proof.
+        pub async fn decoded_packet_acks<CLIENT>(
+            &self,
+            client: &CLIENT,
+            prove: bool,
+            port_id: &PortId,
+            channel_id: &ChannelId,
+        ) -> Result<ResponseQuery<Vec<(Sequence, Vec<u8>)>>, CLIENT::Error>
+        where
+            CLIENT: Client + Sync,
+        {
+            let response = self
+                .packet_acks(client, None, None, prove, port_id, channel_id)
+                .await?;
+            Ok(sequences(response)?)
+        }
+
+        /// Get the denom trace of the hashed IBC token, optionally at the
+        /// given height and with a proof.
+        pub async fn decoded_denom<CLIENT>(
+            &self,
+            client: &CLIENT,
+            height: Option<BlockHeight>,
+            prove: bool,
+            token_hash: &String,
+        ) -> Result<ResponseQuery<Option<String>>, CLIENT::Error>
+        where
+            CLIENT: Client + Sync,
+        {
+            let response =
+                self.denom(client, None, height, prove, token_hash).await?;
+            Ok(decode_response(response, |bytes| {
+                String::from_utf8(bytes.to_vec())
+            })?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use namada_core::ledger::ibc::storage::{calc_hash, ibc_denom_key};
+
+    use crate::ledger::queries::testing::TestClient;
+    use crate::ledger::queries::RPC;
+    use crate::ledger::storage_api::{self, StorageWrite};
+
+    #[tokio::test]
+    async fn test_ibc_denom_queries() -> storage_api::Result<()> {
+        // Initialize the `TestClient`
+        let mut client = TestClient::new(RPC);
+
+        let denom = "transfer/channel-0/nam".to_string();
+        let token_hash = calc_hash(&denom);
+        // There is no denom trace yet
+        let read_denom = RPC
+            .ibc()
+            .decoded_denom(&client, None, false, &token_hash)
+            .await
+            .unwrap();
+        assert!(read_denom.data.is_none());
+
+        // Write the denom trace
+        client
+            .wl_storage
+            .write_bytes(&ibc_denom_key(&token_hash), denom.as_bytes())?;
+        // It has to be committed to be visible in a query
+        client.wl_storage.commit_tx();
+        client.wl_storage.commit_block().unwrap();
+
+        let read_denom = RPC
+            .ibc()
+            .decoded_denom(&client, None, true, &token_hash)
+            .await
+            .unwrap();
+        assert_eq!(read_denom.data, Some(denom));
+        assert!(read_denom.proof.is_some());
+
+        let read_denoms =
+            RPC.ibc().denoms(&client, None, None, false).await.unwrap();
+        assert_eq!(read_denoms.data.len(), 1);
+
+        Ok(())
+    }
+}
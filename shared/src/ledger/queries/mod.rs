@@ -13,12 +13,19 @@ use vp::VP;
 // Re-export to show in rustdoc!
 pub use vp::{Pos, Vp};
 
+// Re-export to show in rustdoc!
+pub use self::gov::Gov;
+use self::gov::GOV;
+pub use self::ibc::Ibc;
+use self::ibc::IBC;
 use super::storage::{DBIter, StorageHasher, DB};
 use super::storage_api;
 use crate::types::storage::BlockHeight;
 
 #[macro_use]
 mod router;
+mod gov;
+mod ibc;
 mod shell;
 mod types;
 mod vp;
@@ -30,6 +37,12 @@ router! {RPC,
 
     // Validity-predicate's specific storage queries
     ( "vp" ) = (sub VP),
+
+    // IBC specific queries
+    ( "ibc" ) = (sub IBC),
+
+    // Governance specific queries
+    ( "gov" ) = (sub GOV),
 }
 
 /// Handle RPC query request in the ledger. On success, returns response with
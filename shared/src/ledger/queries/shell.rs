@@ -70,13 +70,15 @@ where
     H: 'static + StorageHasher + Sync,
 {
     use crate::ledger::gas::BlockGasMeter;
-    use crate::ledger::protocol;
     use crate::ledger::storage::write_log::WriteLog;
+    use crate::ledger::{parameters, protocol};
     use crate::proto::Tx;
     use crate::types::storage::TxIndex;
     use crate::types::transaction::{DecryptedTx, TxType};
 
-    let mut gas_meter = BlockGasMeter::default();
+    let gas_schedule = parameters::read_gas_schedule_parameter(ctx.wl_storage)?
+        .unwrap_or_default();
+    let mut gas_meter = BlockGasMeter::new(gas_schedule);
     let mut write_log = WriteLog::default();
     let tx = Tx::try_from(&request.data[..]).into_storage_result()?;
     let tx = TxType::Decrypted(DecryptedTx::Decrypted {
@@ -88,6 +90,7 @@ where
         tx,
         request.data.len(),
         TxIndex(0),
+        None,
         &mut gas_meter,
         &mut write_log,
         &ctx.wl_storage.storage,
@@ -185,7 +188,7 @@ where
 /// borsh-encoded types, it is safe to check `data.is_empty()` to see if the
 /// value was found, except for unit - see `fn query_storage_value` in
 /// `apps/src/lib/client/rpc.rs` for unit type handling via `storage_has_key`.
-fn storage_value<D, H>(
+pub(super) fn storage_value<D, H>(
     ctx: RequestCtx<'_, D, H>,
     request: &RequestQuery,
     storage_key: storage::Key,
@@ -252,7 +255,7 @@ where
     }
 }
 
-fn storage_prefix<D, H>(
+pub(super) fn storage_prefix<D, H>(
     ctx: RequestCtx<'_, D, H>,
     request: &RequestQuery,
     storage_key: storage::Key,
@@ -5,11 +5,10 @@ use std::num::TryFromIntError;
 use namada_core::types::address::Address;
 use namada_core::types::hash::Hash;
 use namada_core::types::storage::{
-    BlockHash, BlockHeight, Epoch, Key, TxIndex,
+    BlockHash, BlockHeight, Epoch, Header, Key, TxIndex,
 };
 use thiserror::Error;
 
-use super::gas::MIN_STORAGE_GAS;
 use crate::ledger::gas;
 use crate::ledger::gas::VpGasMeter;
 use crate::ledger::storage::write_log::WriteLog;
@@ -36,6 +35,10 @@ pub enum RuntimeError {
     ReadTemporaryValueError,
     #[error("Trying to read a permament value with read_temp")]
     ReadPermanentValueError,
+    #[error("Hash error: {0}")]
+    HashError(crate::types::hash::Error),
+    #[error("Address error: {0}")]
+    AddressError(namada_core::types::address::DecodeError),
 }
 
 /// VP environment function result
@@ -50,6 +53,27 @@ pub fn add_gas(gas_meter: &mut VpGasMeter, used_gas: u64) -> EnvResult<()> {
     result
 }
 
+/// Add a gas cost of reading the given number of bytes from storage in a
+/// validity predicate, as set in the gas schedule
+pub fn add_read_gas(gas_meter: &mut VpGasMeter, bytes: u64) -> EnvResult<()> {
+    let gas = gas_meter.schedule().storage_read_gas(bytes);
+    add_gas(gas_meter, gas)
+}
+
+/// Add a gas cost of copying the given number of bytes between the wasm
+/// memory and the host in a validity predicate, as set in the gas schedule
+pub fn add_memory_gas(gas_meter: &mut VpGasMeter, bytes: u64) -> EnvResult<()> {
+    let gas = gas_meter.schedule().memory_gas(bytes);
+    add_gas(gas_meter, gas)
+}
+
+/// Add the fixed gas cost of a host function call in a validity predicate, as
+/// set in the gas schedule
+pub fn add_host_fn_gas(gas_meter: &mut VpGasMeter) -> EnvResult<()> {
+    let gas = gas_meter.schedule().host_fn;
+    add_gas(gas_meter, gas)
+}
+
 /// Storage read prior state (before tx execution). It will try to read from the
 /// storage.
 pub fn read_pre<DB, H>(
@@ -63,7 +87,7 @@ where
     H: StorageHasher,
 {
     let (log_val, gas) = write_log.read_pre(key);
-    add_gas(gas_meter, gas)?;
+    add_read_gas(gas_meter, gas)?;
     match log_val {
         Some(&write_log::StorageModification::Write { ref value }) => {
             Ok(Some(value.clone()))
@@ -85,7 +109,7 @@ where
             // When not found in write log, try to read from the storage
             let (value, gas) =
                 storage.read(key).map_err(RuntimeError::StorageError)?;
-            add_gas(gas_meter, gas)?;
+            add_read_gas(gas_meter, gas)?;
             Ok(value)
         }
     }
@@ -105,7 +129,7 @@ where
 {
     // Try to read from the write log first
     let (log_val, gas) = write_log.read(key);
-    add_gas(gas_meter, gas)?;
+    add_read_gas(gas_meter, gas)?;
     match log_val {
         Some(&write_log::StorageModification::Write { ref value }) => {
             Ok(Some(value.clone()))
@@ -127,7 +151,7 @@ where
             // When not found in write log, try to read from the storage
             let (value, gas) =
                 storage.read(key).map_err(RuntimeError::StorageError)?;
-            add_gas(gas_meter, gas)?;
+            add_read_gas(gas_meter, gas)?;
             Ok(value)
         }
     }
@@ -142,7 +166,7 @@ pub fn read_temp(
 ) -> EnvResult<Option<Vec<u8>>> {
     // Try to read from the write log first
     let (log_val, gas) = write_log.read(key);
-    add_gas(gas_meter, gas)?;
+    add_read_gas(gas_meter, gas)?;
     match log_val {
         Some(&write_log::StorageModification::Temp { ref value }) => {
             Ok(Some(value.clone()))
@@ -166,7 +190,7 @@ where
 {
     // Try to read from the write log first
     let (log_val, gas) = write_log.read_pre(key);
-    add_gas(gas_meter, gas)?;
+    add_read_gas(gas_meter, gas)?;
     match log_val {
         Some(&write_log::StorageModification::Write { .. }) => Ok(true),
         Some(&write_log::StorageModification::Delete) => {
@@ -179,7 +203,7 @@ where
             // When not found in write log, try to check the storage
             let (present, gas) =
                 storage.has_key(key).map_err(RuntimeError::StorageError)?;
-            add_gas(gas_meter, gas)?;
+            add_read_gas(gas_meter, gas)?;
             Ok(present)
         }
     }
@@ -199,7 +223,7 @@ where
 {
     // Try to read from the write log first
     let (log_val, gas) = write_log.read(key);
-    add_gas(gas_meter, gas)?;
+    add_read_gas(gas_meter, gas)?;
     match log_val {
         Some(&write_log::StorageModification::Write { .. }) => Ok(true),
         Some(&write_log::StorageModification::Delete) => {
@@ -212,7 +236,7 @@ where
             // When not found in write log, try to check the storage
             let (present, gas) =
                 storage.has_key(key).map_err(RuntimeError::StorageError)?;
-            add_gas(gas_meter, gas)?;
+            add_read_gas(gas_meter, gas)?;
             Ok(present)
         }
     }
@@ -227,8 +251,8 @@ where
     DB: storage::DB + for<'iter> storage::DBIter<'iter>,
     H: StorageHasher,
 {
-    let (chain_id, gas) = storage.get_chain_id();
-    add_gas(gas_meter, gas)?;
+    let (chain_id, _gas) = storage.get_chain_id();
+    add_host_fn_gas(gas_meter)?;
     Ok(chain_id)
 }
 
@@ -242,8 +266,8 @@ where
     DB: storage::DB + for<'iter> storage::DBIter<'iter>,
     H: StorageHasher,
 {
-    let (height, gas) = storage.get_block_height();
-    add_gas(gas_meter, gas)?;
+    let (height, _gas) = storage.get_block_height();
+    add_host_fn_gas(gas_meter)?;
     Ok(height)
 }
 
@@ -257,11 +281,45 @@ where
     DB: storage::DB + for<'iter> storage::DBIter<'iter>,
     H: StorageHasher,
 {
-    let (hash, gas) = storage.get_block_hash();
-    add_gas(gas_meter, gas)?;
+    let (hash, _gas) = storage.get_block_hash();
+    add_host_fn_gas(gas_meter)?;
     Ok(hash)
 }
 
+/// Getting the block header at the given height. Only the headers of the
+/// current block and of the past blocks within the
+/// `storage_read_past_height_limit` protocol parameter are available.
+pub fn get_block_header<DB, H>(
+    gas_meter: &mut VpGasMeter,
+    storage: &Storage<DB, H>,
+    height: BlockHeight,
+) -> EnvResult<Option<Header>>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let (header, gas) = storage
+        .get_past_block_header(height)
+        .map_err(RuntimeError::StorageError)?;
+    add_gas(gas_meter, gas)?;
+    Ok(header)
+}
+
+/// Getting the address of the proposer of the block to which the current
+/// transaction is being applied, if it's known.
+pub fn get_block_proposer<DB, H>(
+    gas_meter: &mut VpGasMeter,
+    storage: &Storage<DB, H>,
+) -> EnvResult<Option<Address>>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let (proposer, _gas) = storage.get_block_proposer();
+    add_host_fn_gas(gas_meter)?;
+    Ok(proposer)
+}
+
 /// Getting the block hash. The height is that of the block to which the
 /// current transaction is being applied.
 pub fn get_tx_code_hash(
@@ -269,7 +327,7 @@ pub fn get_tx_code_hash(
     tx: &Tx,
 ) -> EnvResult<Hash> {
     let hash = Hash(tx.code_hash());
-    add_gas(gas_meter, MIN_STORAGE_GAS)?;
+    add_host_fn_gas(gas_meter)?;
     Ok(hash)
 }
 
@@ -283,8 +341,8 @@ where
     DB: storage::DB + for<'iter> storage::DBIter<'iter>,
     H: StorageHasher,
 {
-    let (epoch, gas) = storage.get_current_epoch();
-    add_gas(gas_meter, gas)?;
+    let (epoch, _gas) = storage.get_current_epoch();
+    add_host_fn_gas(gas_meter)?;
     Ok(epoch)
 }
 
@@ -294,7 +352,7 @@ pub fn get_tx_index(
     gas_meter: &mut VpGasMeter,
     tx_index: &TxIndex,
 ) -> EnvResult<TxIndex> {
-    add_gas(gas_meter, MIN_STORAGE_GAS)?;
+    add_host_fn_gas(gas_meter)?;
     Ok(*tx_index)
 }
 
@@ -307,7 +365,7 @@ where
     DB: storage::DB + for<'iter> storage::DBIter<'iter>,
     H: StorageHasher,
 {
-    add_gas(gas_meter, MIN_STORAGE_GAS)?;
+    add_host_fn_gas(gas_meter)?;
     Ok(storage.native_token.clone())
 }
 
@@ -324,6 +382,7 @@ where
     H: StorageHasher,
 {
     let (iter, gas) = storage::iter_prefix_pre(write_log, storage, prefix);
+    let gas = gas_meter.schedule().iter_prefix_gas(gas);
     add_gas(gas_meter, gas)?;
     Ok(iter)
 }
@@ -341,6 +400,7 @@ where
     H: StorageHasher,
 {
     let (iter, gas) = storage::iter_prefix_post(write_log, storage, prefix);
+    let gas = gas_meter.schedule().iter_prefix_gas(gas);
     add_gas(gas_meter, gas)?;
     Ok(iter)
 }
@@ -354,6 +414,7 @@ where
     DB: storage::DB + for<'iter> storage::DBIter<'iter>,
 {
     if let Some((key, val, gas)) = iter.next() {
+        let gas = gas_meter.schedule().iter_next_gas(gas);
         add_gas(gas_meter, gas)?;
         return Ok(Some((key, val)));
     }
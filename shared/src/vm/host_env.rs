@@ -13,25 +13,24 @@ use super::wasm::TxCache;
 #[cfg(feature = "wasm-runtime")]
 use super::wasm::VpCache;
 use super::WasmCacheAccess;
-use crate::ledger::gas::{self, BlockGasMeter, VpGasMeter, MIN_STORAGE_GAS};
+use crate::ledger::gas::{self, BlockGasMeter, VpGasMeter};
 use crate::ledger::storage::write_log::{self, WriteLog};
 use crate::ledger::storage::{self, Storage, StorageHasher};
 use crate::ledger::vp_host_fns;
 use crate::proto::Tx;
 use crate::types::address::{self, Address};
+use crate::types::event::TxEvent;
+use crate::types::hash::{HashAlgorithm, HASH_LENGTH};
 use crate::types::ibc::IbcEvent;
 use crate::types::internal::HostEnvResult;
 use crate::types::key::*;
-use crate::types::storage::{Key, TxIndex};
+use crate::types::storage::{BlockHeight, Key, TxIndex};
 use crate::vm::memory::VmMemory;
 use crate::vm::prefix_iter::{PrefixIteratorId, PrefixIterators};
 use crate::vm::{
     validate_untrusted_wasm, HostRef, MutHostRef, WasmValidationError,
 };
 
-const VERIFY_TX_SIG_GAS_COST: u64 = 1000;
-const WASM_VALIDATION_GAS_PER_BYTE: u64 = 1;
-
 /// These runtime errors will abort tx WASM execution immediately
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -58,6 +57,10 @@ pub enum TxRuntimeError {
     NumConversionError(TryFromIntError),
     #[error("Memory error: {0}")]
     MemoryError(Box<dyn std::error::Error + Sync + Send + 'static>),
+    #[error("Invalid event: {0}")]
+    InvalidEvent(crate::types::event::Error),
+    #[error("Hash error: {0}")]
+    HashError(crate::types::hash::Error),
 }
 
 type TxResult<T> = std::result::Result<T, TxRuntimeError>;
@@ -289,6 +292,20 @@ pub trait VpEvaluator {
         vp_code: Vec<u8>,
         input_data: Vec<u8>,
     ) -> HostEnvResult;
+
+    /// Run the read-only query entrypoint of the given VP code of the account
+    /// with the given address with the given input data. Returns the
+    /// Borsh-encoded result of the query, if it succeeded.
+    ///
+    /// Invariant: Calling `VpEvalRunner::query` from the VP is synchronous as
+    /// it shares mutable access to the host context with the VP.
+    fn query(
+        &self,
+        ctx: VpCtx<'static, Self::Db, Self::H, Self::Eval, Self::CA>,
+        address: &Address,
+        vp_code: Vec<u8>,
+        input_data: Vec<u8>,
+    ) -> Option<Vec<u8>>;
 }
 
 impl<'a, MEM, DB, H, EVAL, CA> VpVmEnv<'a, MEM, DB, H, EVAL, CA>
@@ -456,10 +473,11 @@ where
     }
 }
 
-/// Called from tx wasm to request to use the given gas amount
+/// Called from tx wasm to request to use the gas of the given amount of
+/// consumed fuel
 pub fn tx_charge_gas<MEM, DB, H, CA>(
     env: &TxVmEnv<MEM, DB, H, CA>,
-    used_gas: i32,
+    used_fuel: i32,
 ) -> TxResult<()>
 where
     MEM: VmMemory,
@@ -467,12 +485,11 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
-    tx_add_gas(
-        env,
-        used_gas
-            .try_into()
-            .map_err(TxRuntimeError::NumConversionError)?,
-    )
+    let used_fuel: u64 = used_fuel
+        .try_into()
+        .map_err(TxRuntimeError::NumConversionError)?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    tx_add_gas(env, gas_meter.schedule().wasm_fuel_gas(used_fuel))
 }
 
 /// Add a gas cost incured in a transaction
@@ -498,10 +515,90 @@ where
     result
 }
 
-/// Called from VP wasm to request to use the given gas amount
+/// Add a gas cost of reading the given number of bytes from storage in a
+/// transaction, as set in the gas schedule
+fn tx_add_read_gas<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    bytes: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    tx_add_gas(env, gas_meter.schedule().storage_read_gas(bytes))
+}
+
+/// Add a gas cost of writing or deleting the given number of bytes in storage
+/// in a transaction, as set in the gas schedule
+fn tx_add_write_gas<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    bytes: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    tx_add_gas(env, gas_meter.schedule().storage_write_gas(bytes))
+}
+
+/// Add a gas cost of a write that changed the size of the storage by the
+/// given number of bytes in a transaction, as set in the gas schedule
+fn tx_add_size_diff_gas<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    size_diff: i64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    tx_add_gas(env, gas_meter.schedule().storage_size_diff_gas(size_diff))
+}
+
+/// Add a gas cost of copying the given number of bytes between the wasm
+/// memory and the host in a transaction, as set in the gas schedule
+fn tx_add_memory_gas<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    bytes: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    tx_add_gas(env, gas_meter.schedule().memory_gas(bytes))
+}
+
+/// Add the fixed gas cost of a host function call in a transaction, as set in
+/// the gas schedule
+fn tx_add_host_fn_gas<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    tx_add_gas(env, gas_meter.schedule().host_fn)
+}
+
+/// Called from VP wasm to request to use the gas of the given amount of
+/// consumed fuel
 pub fn vp_charge_gas<MEM, DB, H, EVAL, CA>(
     env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
-    used_gas: i32,
+    used_fuel: i32,
 ) -> vp_host_fns::EnvResult<()>
 where
     MEM: VmMemory,
@@ -510,13 +607,12 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    let used_fuel: u64 = used_fuel
+        .try_into()
+        .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    vp_host_fns::add_gas(
-        gas_meter,
-        used_gas
-            .try_into()
-            .map_err(vp_host_fns::RuntimeError::NumConversionError)?,
-    )
+    let gas = gas_meter.schedule().wasm_fuel_gas(used_fuel);
+    vp_host_fns::add_gas(gas_meter, gas)
 }
 
 /// Storage `has_key` function exposed to the wasm VM Tx environment. It will
@@ -536,7 +632,7 @@ where
         .memory
         .read_string(key_ptr, key_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
 
     tracing::debug!("tx_has_key {}, key {}", key, key_ptr,);
 
@@ -545,7 +641,7 @@ where
     // try to read from the write log first
     let write_log = unsafe { env.ctx.write_log.get() };
     let (log_val, gas) = write_log.read(&key);
-    tx_add_gas(env, gas)?;
+    tx_add_read_gas(env, gas)?;
     Ok(match log_val {
         Some(&write_log::StorageModification::Write { .. }) => {
             HostEnvResult::Success.to_i64()
@@ -566,7 +662,7 @@ where
             let (present, gas) = storage
                 .has_key(&key)
                 .map_err(TxRuntimeError::StorageError)?;
-            tx_add_gas(env, gas)?;
+            tx_add_read_gas(env, gas)?;
             HostEnvResult::from(present).to_i64()
         }
     })
@@ -592,7 +688,7 @@ where
         .memory
         .read_string(key_ptr, key_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
 
     tracing::debug!("tx_read {}, key {}", key, key_ptr,);
 
@@ -601,7 +697,7 @@ where
     // try to read from the write log first
     let write_log = unsafe { env.ctx.write_log.get() };
     let (log_val, gas) = write_log.read(&key);
-    tx_add_gas(env, gas)?;
+    tx_add_read_gas(env, gas)?;
     Ok(match log_val {
         Some(&write_log::StorageModification::Write { ref value }) => {
             let len: i64 = value
@@ -642,7 +738,7 @@ where
             let storage = unsafe { env.ctx.storage.get() };
             let (value, gas) =
                 storage.read(&key).map_err(TxRuntimeError::StorageError)?;
-            tx_add_gas(env, gas)?;
+            tx_add_read_gas(env, gas)?;
             match value {
                 Some(value) => {
                     let len: i64 = value
@@ -683,7 +779,7 @@ where
         .memory
         .write_bytes(result_ptr, value)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)
+    tx_add_memory_gas(env, gas)
 }
 
 /// Storage prefix iterator function exposed to the wasm VM Tx environment.
@@ -704,7 +800,7 @@ where
         .memory
         .read_string(prefix_ptr, prefix_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
 
     tracing::debug!("tx_iter_prefix {}", prefix);
 
@@ -714,7 +810,8 @@ where
     let write_log = unsafe { env.ctx.write_log.get() };
     let storage = unsafe { env.ctx.storage.get() };
     let (iter, gas) = storage::iter_prefix_post(write_log, storage, &prefix);
-    tx_add_gas(env, gas)?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    tx_add_gas(env, gas_meter.schedule().iter_prefix_gas(gas))?;
 
     let iterators = unsafe { env.ctx.iterators.get() };
     Ok(iterators.insert(iter).id())
@@ -746,7 +843,10 @@ where
             &Key::parse(key.clone())
                 .map_err(TxRuntimeError::StorageDataError)?,
         );
-        tx_add_gas(env, iter_gas + log_gas)?;
+        // Every step is charged, including the skipped deleted entries
+        let gas_meter = unsafe { env.ctx.gas_meter.get() };
+        let gas = gas_meter.schedule().iter_next_gas(iter_gas + log_gas);
+        tx_add_gas(env, gas)?;
         match log_val {
             Some(&write_log::StorageModification::Write { ref value }) => {
                 let key_val = KeyVal {
@@ -822,12 +922,12 @@ where
         .memory
         .read_string(key_ptr, key_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
     let (value, gas) = env
         .memory
         .read_bytes(val_ptr, val_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
 
     tracing::debug!("tx_update {}, {:?}", key, value);
 
@@ -839,11 +939,11 @@ where
     check_address_existence(env, &key)?;
 
     let write_log = unsafe { env.ctx.write_log.get() };
-    let (gas, _size_diff) = write_log
+    let (gas, size_diff) = write_log
         .write(&key, value)
         .map_err(TxRuntimeError::StorageModificationError)?;
-    tx_add_gas(env, gas)
-    // TODO: charge the size diff
+    tx_add_write_gas(env, gas)?;
+    tx_add_size_diff_gas(env, size_diff)
 }
 
 /// Temporary storage write function exposed to the wasm VM Tx environment. The
@@ -866,12 +966,12 @@ where
         .memory
         .read_string(key_ptr, key_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
     let (value, gas) = env
         .memory
         .read_bytes(val_ptr, val_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
 
     tracing::debug!("tx_write_temp {}, {:?}", key, value);
 
@@ -880,11 +980,13 @@ where
     check_address_existence(env, &key)?;
 
     let write_log = unsafe { env.ctx.write_log.get() };
-    let (gas, _size_diff) = write_log
+    // The temporary writes are charged for their size too, because they grow
+    // the write log for the rest of the transaction
+    let (gas, size_diff) = write_log
         .write_temp(&key, value)
         .map_err(TxRuntimeError::StorageModificationError)?;
-    tx_add_gas(env, gas)
-    // TODO: charge the size diff
+    tx_add_write_gas(env, gas)?;
+    tx_add_size_diff_gas(env, size_diff)
 }
 
 fn check_address_existence<MEM, DB, H, CA>(
@@ -906,14 +1008,14 @@ where
         }
         let vp_key = Key::validity_predicate(&addr);
         let (vp, gas) = write_log.read(&vp_key);
-        tx_add_gas(env, gas)?;
+        tx_add_read_gas(env, gas)?;
         // just check the existence because the write log should not have the
         // delete log of the VP
         if vp.is_none() {
             let (is_present, gas) = storage
                 .has_key(&vp_key)
                 .map_err(TxRuntimeError::StorageError)?;
-            tx_add_gas(env, gas)?;
+            tx_add_read_gas(env, gas)?;
             if !is_present {
                 tracing::info!(
                     "Trying to write into storage with a key containing an \
@@ -946,7 +1048,7 @@ where
         .memory
         .read_string(key_ptr, key_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
 
     tracing::debug!("tx_delete {}", key);
 
@@ -956,11 +1058,12 @@ where
     }
 
     let write_log = unsafe { env.ctx.write_log.get() };
-    let (gas, _size_diff) = write_log
+    // A deletion only shrinks the storage, so the size diff is free
+    let (gas, size_diff) = write_log
         .delete(&key)
         .map_err(TxRuntimeError::StorageModificationError)?;
-    tx_add_gas(env, gas)
-    // TODO: charge the size diff
+    tx_add_write_gas(env, gas)?;
+    tx_add_size_diff_gas(env, size_diff)
 }
 
 /// Emitting an IBC event function exposed to the wasm VM Tx environment.
@@ -980,7 +1083,7 @@ where
         .memory
         .read_bytes(event_ptr, event_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
     let event: IbcEvent = BorshDeserialize::try_from_slice(&event)
         .map_err(TxRuntimeError::EncodingError)?;
     let write_log = unsafe { env.ctx.write_log.get() };
@@ -988,6 +1091,111 @@ where
     tx_add_gas(env, gas)
 }
 
+/// Emitting a custom event function exposed to the wasm VM Tx environment.
+/// The given event will be appended to the events of the tx in the write log.
+pub fn tx_emit_event<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    event_ptr: u64,
+    event_len: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (event, gas) = env
+        .memory
+        .read_bytes(event_ptr, event_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_add_memory_gas(env, gas)?;
+    let event: TxEvent = BorshDeserialize::try_from_slice(&event)
+        .map_err(TxRuntimeError::EncodingError)?;
+    event.validate().map_err(TxRuntimeError::InvalidEvent)?;
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let gas = write_log.emit_event(event);
+    tx_add_gas(env, gas)
+}
+
+/// Hashing function exposed to the wasm VM Tx environment. The digest of the
+/// given data is written into the memory at the given result pointer.
+pub fn tx_hash<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    algo: u64,
+    data_ptr: u64,
+    data_len: u64,
+    result_ptr: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let algo =
+        HashAlgorithm::try_from(algo).map_err(TxRuntimeError::HashError)?;
+    let (data, gas) = env
+        .memory
+        .read_bytes(data_ptr, data_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_add_memory_gas(env, gas)?;
+
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    tx_add_gas(env, gas_meter.schedule().hash_gas(data.len()))?;
+    let hash = hash_data(algo, &data);
+    let gas = env
+        .memory
+        .write_bytes(result_ptr, hash)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_add_memory_gas(env, gas)
+}
+
+/// Verifying a signature over arbitrary data function exposed to the wasm VM
+/// Tx environment.
+pub fn tx_verify_signature<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    pk_ptr: u64,
+    pk_len: u64,
+    data_ptr: u64,
+    data_len: u64,
+    sig_ptr: u64,
+    sig_len: u64,
+) -> TxResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (pk, gas) = env
+        .memory
+        .read_bytes(pk_ptr, pk_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_add_memory_gas(env, gas)?;
+    let pk: common::PublicKey = BorshDeserialize::try_from_slice(&pk)
+        .map_err(TxRuntimeError::EncodingError)?;
+
+    let (data, gas) = env
+        .memory
+        .read_bytes(data_ptr, data_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_add_memory_gas(env, gas)?;
+
+    let (sig, gas) = env
+        .memory
+        .read_bytes(sig_ptr, sig_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_add_memory_gas(env, gas)?;
+    let sig: common::Signature = BorshDeserialize::try_from_slice(&sig)
+        .map_err(TxRuntimeError::EncodingError)?;
+
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    tx_add_gas(env, gas_meter.schedule().verify_signature_gas(data.len()))?;
+    let is_valid =
+        common::SigScheme::verify_signature_raw(&pk, &data, &sig).is_ok();
+    Ok(HostEnvResult::from(is_valid).to_i64())
+}
+
 /// Storage read prior state (before tx execution) function exposed to the wasm
 /// VM VP environment. It will try to read from the storage.
 ///
@@ -1010,7 +1218,7 @@ where
         .read_string(key_ptr, key_len as _)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    vp_host_fns::add_gas(gas_meter, gas)?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
 
     // try to read from the storage
     let key =
@@ -1061,7 +1269,7 @@ where
         .read_string(key_ptr, key_len as _)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    vp_host_fns::add_gas(gas_meter, gas)?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
 
     tracing::debug!("vp_read_post {}, key {}", key, key_ptr,);
 
@@ -1107,7 +1315,7 @@ where
         .read_string(key_ptr, key_len as _)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    vp_host_fns::add_gas(gas_meter, gas)?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
 
     tracing::debug!("vp_read_temp {}, key {}", key, key_ptr);
 
@@ -1156,7 +1364,7 @@ where
         .write_bytes(result_ptr, value)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    vp_host_fns::add_gas(gas_meter, gas)
+    vp_host_fns::add_memory_gas(gas_meter, gas)
 }
 
 /// Storage `has_key` in prior state (before tx execution) function exposed to
@@ -1178,7 +1386,7 @@ where
         .read_string(key_ptr, key_len as _)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    vp_host_fns::add_gas(gas_meter, gas)?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
 
     tracing::debug!("vp_has_key_pre {}, key {}", key, key_ptr,);
 
@@ -1211,7 +1419,7 @@ where
         .read_string(key_ptr, key_len as _)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    vp_host_fns::add_gas(gas_meter, gas)?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
 
     tracing::debug!("vp_has_key_post {}, key {}", key, key_ptr,);
 
@@ -1245,7 +1453,7 @@ where
         .read_string(prefix_ptr, prefix_len as _)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    vp_host_fns::add_gas(gas_meter, gas)?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
 
     tracing::debug!("vp_iter_prefix_pre {}", prefix);
 
@@ -1282,7 +1490,7 @@ where
         .read_string(prefix_ptr, prefix_len as _)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    vp_host_fns::add_gas(gas_meter, gas)?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
 
     tracing::debug!("vp_iter_prefix_post {}", prefix);
 
@@ -1352,7 +1560,7 @@ where
         .memory
         .read_string(addr_ptr, addr_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
 
     tracing::debug!("tx_insert_verifier {}, addr_ptr {}", addr, addr_ptr,);
 
@@ -1360,7 +1568,7 @@ where
 
     let verifiers = unsafe { env.ctx.verifiers.get() };
     verifiers.insert(addr);
-    tx_add_gas(env, addr_len)
+    tx_add_host_fn_gas(env)
 }
 
 /// Update a validity predicate function exposed to the wasm VM Tx environment
@@ -1381,7 +1589,7 @@ where
         .memory
         .read_string(addr_ptr, addr_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
 
     let addr = Address::decode(addr).map_err(TxRuntimeError::AddressError)?;
     tracing::debug!("tx_update_validity_predicate for addr {}", addr);
@@ -1391,16 +1599,16 @@ where
         .memory
         .read_bytes(code_ptr, code_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
 
     tx_validate_vp_code(env, &code)?;
 
     let write_log = unsafe { env.ctx.write_log.get() };
-    let (gas, _size_diff) = write_log
+    let (gas, size_diff) = write_log
         .write(&key, code)
         .map_err(TxRuntimeError::StorageModificationError)?;
-    tx_add_gas(env, gas)
-    // TODO: charge the size diff
+    tx_add_write_gas(env, gas)?;
+    tx_add_size_diff_gas(env, size_diff)
 }
 
 /// Initialize a new account established address.
@@ -1420,7 +1628,7 @@ where
         .memory
         .read_bytes(code_ptr, code_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)?;
+    tx_add_memory_gas(env, gas)?;
 
     tx_validate_vp_code(env, &code)?;
     #[cfg(feature = "wasm-runtime")]
@@ -1441,7 +1649,7 @@ where
         .memory
         .write_bytes(result_ptr, addr_bytes)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)
+    tx_add_memory_gas(env, gas)
 }
 
 /// Getting the chain ID function exposed to the wasm VM Tx environment.
@@ -1456,13 +1664,13 @@ where
     CA: WasmCacheAccess,
 {
     let storage = unsafe { env.ctx.storage.get() };
-    let (chain_id, gas) = storage.get_chain_id();
-    tx_add_gas(env, gas)?;
+    let (chain_id, _gas) = storage.get_chain_id();
+    tx_add_host_fn_gas(env)?;
     let gas = env
         .memory
         .write_string(result_ptr, chain_id)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)
+    tx_add_memory_gas(env, gas)
 }
 
 /// Getting the block height function exposed to the wasm VM Tx
@@ -1478,8 +1686,8 @@ where
     CA: WasmCacheAccess,
 {
     let storage = unsafe { env.ctx.storage.get() };
-    let (height, gas) = storage.get_block_height();
-    tx_add_gas(env, gas)?;
+    let (height, _gas) = storage.get_block_height();
+    tx_add_host_fn_gas(env)?;
     Ok(height.0)
 }
 
@@ -1496,7 +1704,7 @@ where
     CA: WasmCacheAccess,
 {
     let tx_index = unsafe { env.ctx.tx_index.get() };
-    tx_add_gas(env, crate::vm::host_env::gas::MIN_STORAGE_GAS)?;
+    tx_add_host_fn_gas(env)?;
     Ok(tx_index.0)
 }
 
@@ -1532,13 +1740,13 @@ where
     CA: WasmCacheAccess,
 {
     let storage = unsafe { env.ctx.storage.get() };
-    let (hash, gas) = storage.get_block_hash();
-    tx_add_gas(env, gas)?;
+    let (hash, _gas) = storage.get_block_hash();
+    tx_add_host_fn_gas(env)?;
     let gas = env
         .memory
         .write_bytes(result_ptr, hash.0)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)
+    tx_add_memory_gas(env, gas)
 }
 
 /// Getting the block epoch function exposed to the wasm VM Tx
@@ -1554,8 +1762,8 @@ where
     CA: WasmCacheAccess,
 {
     let storage = unsafe { env.ctx.storage.get() };
-    let (epoch, gas) = storage.get_current_epoch();
-    tx_add_gas(env, gas)?;
+    let (epoch, _gas) = storage.get_current_epoch();
+    tx_add_host_fn_gas(env)?;
     Ok(epoch.0)
 }
 
@@ -1571,14 +1779,14 @@ where
     CA: WasmCacheAccess,
 {
     let storage = unsafe { env.ctx.storage.get() };
-    tx_add_gas(env, MIN_STORAGE_GAS)?;
+    tx_add_host_fn_gas(env)?;
     let native_token = storage.native_token.clone();
     let native_token_string = native_token.encode();
     let gas = env
         .memory
         .write_string(result_ptr, native_token_string)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
-    tx_add_gas(env, gas)
+    tx_add_memory_gas(env, gas)
 }
 
 /// Getting the chain ID function exposed to the wasm VM VP environment.
@@ -1600,7 +1808,7 @@ where
         .memory
         .write_string(result_ptr, chain_id)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
-    vp_host_fns::add_gas(gas_meter, gas)
+    vp_host_fns::add_memory_gas(gas_meter, gas)
 }
 
 /// Getting the block height function exposed to the wasm VM VP
@@ -1658,6 +1866,147 @@ where
     })
 }
 
+/// Getting the block header function exposed to the wasm VM Tx environment.
+/// Only the headers of the current block and of the past blocks within the
+/// `storage_read_past_height_limit` protocol parameter are available. The
+/// Borsh encoded header is stored in the result buffer and its length is
+/// returned, or `-1` if the header is not available.
+pub fn tx_get_block_header<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    height: u64,
+) -> TxResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let storage = unsafe { env.ctx.storage.get() };
+    let (header, gas) = storage
+        .get_past_block_header(BlockHeight(height))
+        .map_err(TxRuntimeError::StorageError)?;
+    tx_add_gas(env, gas)?;
+    Ok(match header {
+        Some(h) => {
+            let value =
+                h.try_to_vec().map_err(TxRuntimeError::EncodingError)?;
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(TxRuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
+/// Getting the block header function exposed to the wasm VM VP environment.
+/// Only the headers of the current block and of the past blocks within the
+/// `storage_read_past_height_limit` protocol parameter are available. The
+/// Borsh encoded header is stored in the result buffer and its length is
+/// returned, or `-1` if the header is not available.
+pub fn vp_get_block_header<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    height: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let storage = unsafe { env.ctx.storage.get() };
+    let header =
+        vp_host_fns::get_block_header(gas_meter, storage, BlockHeight(height))?;
+    Ok(match header {
+        Some(header) => {
+            let value = header
+                .try_to_vec()
+                .map_err(vp_host_fns::RuntimeError::EncodingError)?;
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
+/// Getting the block time function exposed to the wasm VM VP environment. The
+/// time is that of the block header to which the current transaction is being
+/// applied. The Borsh encoded time is stored in the result buffer and its
+/// length is returned, or `-1` if the header is not available.
+pub fn vp_get_block_time<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let storage = unsafe { env.ctx.storage.get() };
+    let height = vp_host_fns::get_block_height(gas_meter, storage)?;
+    let header = vp_host_fns::get_block_header(gas_meter, storage, height)?;
+    Ok(match header {
+        Some(header) => {
+            let value = header
+                .time
+                .try_to_vec()
+                .map_err(vp_host_fns::RuntimeError::EncodingError)?;
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
+/// Getting the block proposer function exposed to the wasm VM VP environment.
+/// The encoded address of the proposer of the block to which the current
+/// transaction is being applied is stored in the result buffer and its length
+/// is returned, or `-1` if the proposer is not known.
+pub fn vp_get_block_proposer<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let storage = unsafe { env.ctx.storage.get() };
+    let proposer = vp_host_fns::get_block_proposer(gas_meter, storage)?;
+    Ok(match proposer {
+        Some(proposer) => {
+            let value = proposer.encode().into_bytes();
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
 /// Getting the block hash function exposed to the wasm VM VP environment. The
 /// hash is that of the block to which the current transaction is being applied.
 pub fn vp_get_block_hash<MEM, DB, H, EVAL, CA>(
@@ -1678,7 +2027,7 @@ where
         .memory
         .write_bytes(result_ptr, hash.0)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
-    vp_host_fns::add_gas(gas_meter, gas)
+    vp_host_fns::add_memory_gas(gas_meter, gas)
 }
 
 /// Getting the transaction hash function exposed to the wasm VM VP environment.
@@ -1700,7 +2049,7 @@ where
         .memory
         .write_bytes(result_ptr, hash.0)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
-    vp_host_fns::add_gas(gas_meter, gas)
+    vp_host_fns::add_memory_gas(gas_meter, gas)
 }
 
 /// Getting the block epoch function exposed to the wasm VM VP
@@ -1742,7 +2091,7 @@ where
         .read_bytes(pk_ptr, pk_len as _)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    vp_host_fns::add_gas(gas_meter, gas)?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
     let pk: common::PublicKey = BorshDeserialize::try_from_slice(&pk)
         .map_err(vp_host_fns::RuntimeError::EncodingError)?;
 
@@ -1750,15 +2099,100 @@ where
         .memory
         .read_bytes(sig_ptr, sig_len as _)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
-    vp_host_fns::add_gas(gas_meter, gas)?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
     let sig: common::Signature = BorshDeserialize::try_from_slice(&sig)
         .map_err(vp_host_fns::RuntimeError::EncodingError)?;
 
-    vp_host_fns::add_gas(gas_meter, VERIFY_TX_SIG_GAS_COST)?;
     let tx = unsafe { env.ctx.tx.get() };
+    let signed_len =
+        tx.code.size() + tx.data.as_ref().map(Vec::len).unwrap_or_default();
+    let gas = gas_meter.schedule().verify_signature_gas(signed_len);
+    vp_host_fns::add_gas(gas_meter, gas)?;
     Ok(HostEnvResult::from(tx.verify_sig(&pk, &sig).is_ok()).to_i64())
 }
 
+/// Hashing function exposed to the wasm VM VP environment. The digest of the
+/// given data is written into the memory at the given result pointer.
+pub fn vp_hash<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    algo: u64,
+    data_ptr: u64,
+    data_len: u64,
+    result_ptr: u64,
+) -> vp_host_fns::EnvResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let algo = HashAlgorithm::try_from(algo)
+        .map_err(vp_host_fns::RuntimeError::HashError)?;
+    let (data, gas) = env
+        .memory
+        .read_bytes(data_ptr, data_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
+
+    let gas = gas_meter.schedule().hash_gas(data.len());
+    vp_host_fns::add_gas(gas_meter, gas)?;
+    let hash = hash_data(algo, &data);
+    let gas = env
+        .memory
+        .write_bytes(result_ptr, hash)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)
+}
+
+/// Verify a signature over arbitrary data.
+pub fn vp_verify_signature<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    pk_ptr: u64,
+    pk_len: u64,
+    data_ptr: u64,
+    data_len: u64,
+    sig_ptr: u64,
+    sig_len: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let (pk, gas) = env
+        .memory
+        .read_bytes(pk_ptr, pk_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
+    let pk: common::PublicKey = BorshDeserialize::try_from_slice(&pk)
+        .map_err(vp_host_fns::RuntimeError::EncodingError)?;
+
+    let (data, gas) = env
+        .memory
+        .read_bytes(data_ptr, data_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
+
+    let (sig, gas) = env
+        .memory
+        .read_bytes(sig_ptr, sig_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
+    let sig: common::Signature = BorshDeserialize::try_from_slice(&sig)
+        .map_err(vp_host_fns::RuntimeError::EncodingError)?;
+
+    let gas = gas_meter.schedule().verify_signature_gas(data.len());
+    vp_host_fns::add_gas(gas_meter, gas)?;
+    let is_valid =
+        common::SigScheme::verify_signature_raw(&pk, &data, &sig).is_ok();
+    Ok(HostEnvResult::from(is_valid).to_i64())
+}
+
 /// Verify a ShieldedTransaction.
 pub fn vp_verify_masp<MEM, DB, H, EVAL, CA>(
     env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
@@ -1779,7 +2213,7 @@ where
         .memory
         .read_bytes(tx_ptr, tx_len as _)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
-    vp_host_fns::add_gas(gas_meter, gas)?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
 
     let full_tx: Transfer =
         BorshDeserialize::try_from_slice(tx_bytes.as_slice())
@@ -1827,7 +2261,8 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
-    tx_add_gas(env, code.len() as u64 * WASM_VALIDATION_GAS_PER_BYTE)?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    tx_add_gas(env, gas_meter.schedule().wasm_validation_gas(code.len()))?;
     validate_untrusted_wasm(code).map_err(TxRuntimeError::InvalidVpCode)
 }
 
@@ -1851,13 +2286,13 @@ where
             .read_bytes(vp_code_ptr, vp_code_len as _)
             .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    vp_host_fns::add_gas(gas_meter, gas)?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
 
     let (input_data, gas) = env
         .memory
         .read_bytes(input_data_ptr, input_data_len as _)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
-    vp_host_fns::add_gas(gas_meter, gas)?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
 
     let eval_runner = unsafe { env.ctx.eval_runner.get() };
     Ok(eval_runner
@@ -1865,6 +2300,93 @@ where
         .to_i64())
 }
 
+/// Query the validity predicate of the account with the given address with
+/// the given input data. The result of the query is stored in the result
+/// buffer and its length is returned, or `-1` if the query failed.
+pub fn vp_query<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<'static, MEM, DB, H, EVAL, CA>,
+    addr_ptr: u64,
+    addr_len: u64,
+    input_data_ptr: u64,
+    input_data_len: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator<Db = DB, H = H, Eval = EVAL, CA = CA>,
+    CA: WasmCacheAccess,
+{
+    let (addr, gas) = env
+        .memory
+        .read_string(addr_ptr, addr_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
+    let addr = Address::decode(addr)
+        .map_err(vp_host_fns::RuntimeError::AddressError)?;
+
+    let (input_data, gas) = env
+        .memory
+        .read_bytes(input_data_ptr, input_data_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
+
+    // The queried VP is the one from the prior state
+    let storage = unsafe { env.ctx.storage.get() };
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let vp_key = Key::validity_predicate(&addr);
+    let vp_code =
+        match vp_host_fns::read_pre(gas_meter, storage, write_log, &vp_key)? {
+            Some(vp_code) => vp_code,
+            None => return Ok(HostEnvResult::Fail.to_i64()),
+        };
+    // The queried VP has to be compiled, unless it's cached
+    gas_meter.add_compiling_fee(vp_code.len()).map_err(|err| {
+        tracing::info!("Stopping VP execution because of gas error: {}", err);
+        vp_host_fns::RuntimeError::OutOfGas(err)
+    })?;
+
+    let eval_runner = unsafe { env.ctx.eval_runner.get() };
+    match eval_runner.query(env.ctx.clone(), &addr, vp_code, input_data) {
+        Some(result) => {
+            let len: i64 = result
+                .len()
+                .try_into()
+                .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(result);
+            Ok(len)
+        }
+        None => Ok(HostEnvResult::Fail.to_i64()),
+    }
+}
+
+/// Set the result of a VP query. This is called from the query entrypoint of
+/// a VP right before it returns.
+pub fn vp_set_query_result<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    result_ptr: u64,
+    result_len: u64,
+) -> vp_host_fns::EnvResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let (result, gas) = env
+        .memory
+        .read_bytes(result_ptr, result_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    vp_host_fns::add_memory_gas(gas_meter, gas)?;
+    let result_buffer = unsafe { env.ctx.result_buffer.get() };
+    result_buffer.replace(result);
+    Ok(())
+}
+
 /// Get the native token's address
 pub fn vp_get_native_token<MEM, DB, H, EVAL, CA>(
     env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
@@ -1885,7 +2407,7 @@ where
         .memory
         .write_string(result_ptr, native_token_string)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
-    vp_host_fns::add_gas(gas_meter, gas)
+    vp_host_fns::add_memory_gas(gas_meter, gas)
 }
 
 /// Find if the wrapper tx had a valid `testnet_pow::Solution`
@@ -1938,6 +2460,28 @@ where
     Ok(())
 }
 
+/// Compute the digest of the given data with the given hash function
+fn hash_data(algo: HashAlgorithm, data: &[u8]) -> [u8; HASH_LENGTH] {
+    use sha2::Digest;
+
+    let mut hash = [0u8; HASH_LENGTH];
+    match algo {
+        HashAlgorithm::Sha256 => {
+            hash.copy_from_slice(&sha2::Sha256::digest(data));
+        }
+        HashAlgorithm::Keccak256 => {
+            hash.copy_from_slice(&sha3::Keccak256::digest(data));
+        }
+        HashAlgorithm::Blake2b256 => {
+            let digest = blake2b_simd::Params::new()
+                .hash_length(HASH_LENGTH)
+                .hash(data);
+            hash.copy_from_slice(digest.as_bytes());
+        }
+    }
+    hash
+}
+
 /// A helper module for testing
 #[cfg(feature = "testing")]
 pub mod testing {
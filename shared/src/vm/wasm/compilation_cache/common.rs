@@ -417,8 +417,10 @@ fn exponential_backoff(iteration: u64) {
     sleep(Duration::from_millis(u64::pow(2, iteration as u32) * 10))
 }
 
+/// The modules are keyed by the SHA-256 hash of their code, which is the same
+/// hash that transactions use to refer to the wasm code stored on-chain
 fn hash_of_code(code: impl AsRef<[u8]>) -> Hash {
-    Hash::generate(code.as_ref())
+    Hash::new(crate::types::hash::Hash::sha256(code).0)
 }
 
 fn hash_to_store_dir(hash: &Hash) -> PathBuf {
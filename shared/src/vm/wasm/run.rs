@@ -23,13 +23,28 @@ use crate::vm::types::VpInput;
 use crate::vm::wasm::host_env::{tx_imports, vp_imports};
 use crate::vm::wasm::{memory, VpCache};
 use crate::vm::{
-    validate_untrusted_wasm, WasmCacheAccess, WasmValidationError,
+    validate_untrusted_wasm, HostRef, MutHostRef, WasmCacheAccess,
+    WasmValidationError,
 };
 
 const TX_ENTRYPOINT: &str = "_apply_tx";
 const VP_ENTRYPOINT: &str = "_validate_tx";
+const VP_QUERY_ENTRYPOINT: &str = "_query";
 const WASM_STACK_LIMIT: u32 = u16::MAX as u32;
 
+/// The fuel of a regular wasm instruction
+const WASM_FUEL_REGULAR: u32 = 1;
+/// The fuel of a wasm multiplication
+const WASM_FUEL_MUL: u32 = 3;
+/// The fuel of a wasm division or remainder
+const WASM_FUEL_DIV: u32 = 8;
+/// The fuel of a wasm memory load or store
+const WASM_FUEL_MEMORY_ACCESS: u32 = 2;
+/// The fuel of a wasm branch, call or return
+const WASM_FUEL_CONTROL_FLOW: u32 = 2;
+/// The fuel of a single page (64KiB) of grown wasm memory
+const WASM_FUEL_MEMORY_PAGE: u32 = 1024;
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum Error {
@@ -330,6 +345,22 @@ where
             }
         }
     }
+
+    fn query(
+        &self,
+        ctx: VpCtx<'static, DB, H, Self, CA>,
+        address: &Address,
+        vp_code: Vec<u8>,
+        input_data: Vec<u8>,
+    ) -> Option<Vec<u8>> {
+        match self.query_native_result(ctx, address, vp_code, input_data) {
+            Ok(result) => result,
+            Err(err) => {
+                tracing::warn!("VP query error {}", err);
+                None
+            }
+        }
+    }
 }
 
 impl<DB, H, CA> VpEvalWasm<DB, H, CA>
@@ -375,6 +406,79 @@ where
             verifiers,
         )
     }
+
+    /// Run the read-only query entrypoint of the given VP code of the account
+    /// with the given address. The VP host functions don't allow any storage
+    /// modifications, so the query has no side effects. The queried VP doesn't
+    /// see the keys changed and the verifiers of the tx and it has its own
+    /// result buffer, so it can't alter what the caller reads from its buffer.
+    pub fn query_native_result(
+        &self,
+        mut ctx: VpCtx<'static, DB, H, Self, CA>,
+        address: &Address,
+        vp_code: Vec<u8>,
+        input_data: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>> {
+        validate_untrusted_wasm(&vp_code).map_err(Error::ValidationError)?;
+
+        // The queried VP runs in the context of its own account
+        let keys_changed = BTreeSet::new();
+        let verifiers = BTreeSet::new();
+        let mut result_buffer: Option<Vec<u8>> = None;
+        ctx.address = unsafe { HostRef::new(address) };
+        ctx.keys_changed = unsafe { HostRef::new(&keys_changed) };
+        ctx.verifiers = unsafe { HostRef::new(&verifiers) };
+        ctx.result_buffer = unsafe { MutHostRef::new(&mut result_buffer) };
+        let vp_wasm_cache = unsafe { ctx.vp_wasm_cache.get() };
+        let env = VpVmEnv {
+            memory: WasmMemory::default(),
+            ctx,
+        };
+
+        // Compile the wasm module
+        let (module, store) = vp_wasm_cache.fetch_or_compile(&vp_code)?;
+
+        let initial_memory =
+            memory::prepare_vp_memory(&store).map_err(Error::MemoryError)?;
+
+        let imports = vp_imports(&store, initial_memory, env);
+
+        // Instantiate the wasm module
+        let instance = wasmer::Instance::new(&module, &imports)
+            .map_err(|e| Error::InstantiationError(Box::new(e)))?;
+
+        // We need to write the inputs in the memory exported from the wasm
+        // module
+        let memory = instance
+            .exports
+            .get_memory("memory")
+            .map_err(Error::MissingModuleMemory)?;
+        let memory::TxCallInput {
+            tx_data_ptr: input_data_ptr,
+            tx_data_len: input_data_len,
+        } = memory::write_tx_inputs(memory, input_data)
+            .map_err(Error::MemoryError)?;
+
+        // Get the module's query entrypoint to be called
+        let query = instance
+            .exports
+            .get_function(VP_QUERY_ENTRYPOINT)
+            .map_err(Error::MissingModuleEntrypoint)?
+            .native::<(u64, u64), u64>()
+            .map_err(|error| Error::UnexpectedModuleEntrypointInterface {
+                entrypoint: VP_QUERY_ENTRYPOINT,
+                error,
+            })?;
+        let is_success = query
+            .call(input_data_ptr, input_data_len)
+            .map_err(Error::RuntimeError)?;
+        tracing::debug!("VP query is_success {}", is_success);
+        if is_success == 1 {
+            Ok(result_buffer.take())
+        } else {
+            Ok(None)
+        }
+    }
 }
 
 /// Prepare a wasm store for untrusted code.
@@ -400,9 +504,33 @@ pub fn prepare_wasm_code<T: AsRef<[u8]>>(code: T) -> Result<Vec<u8>> {
     elements::serialize(module).map_err(Error::SerializationError)
 }
 
-/// Get the gas rules used to meter wasm operations
+/// Get the gas rules used to meter wasm operations. The injected counter
+/// measures the fuel consumed by the executed instructions, which is converted
+/// to gas with the `wasm_fuel` cost of the gas schedule when it's charged.
 fn get_gas_rules() -> rules::Set {
-    rules::Set::default().with_grow_cost(1)
+    use rules::{InstructionType, Metering};
+    rules::Set::new(
+        WASM_FUEL_REGULAR,
+        [
+            (InstructionType::Mul, Metering::Fixed(WASM_FUEL_MUL)),
+            (InstructionType::Div, Metering::Fixed(WASM_FUEL_DIV)),
+            (
+                InstructionType::Load,
+                Metering::Fixed(WASM_FUEL_MEMORY_ACCESS),
+            ),
+            (
+                InstructionType::Store,
+                Metering::Fixed(WASM_FUEL_MEMORY_ACCESS),
+            ),
+            (
+                InstructionType::ControlFlow,
+                Metering::Fixed(WASM_FUEL_CONTROL_FLOW),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+    )
+    .with_grow_cost(WASM_FUEL_MEMORY_PAGE)
 }
 
 #[cfg(test)]
@@ -151,6 +151,74 @@ fn wallet_unencrypted_key_cmds() -> Result<()> {
     Ok(())
 }
 
+/// Test wallet key commands with a mnemonic code:
+/// 1. key gen with a mnemonic code
+/// 2. key restore from the mnemonic code
+/// 3. key find both keys to check that they are the same
+#[test]
+fn wallet_mnemonic_key_cmds() -> Result<()> {
+    let test = setup::single_node_net()?;
+    let key_alias = "test_key_1";
+    let restored_alias = "test_key_2";
+
+    // 1. key gen with a mnemonic code
+    let mut cmd = run!(
+        test,
+        Bin::Wallet,
+        &[
+            "key",
+            "gen",
+            "--alias",
+            key_alias,
+            "--mnemonic",
+            "--unsafe-dont-encrypt",
+        ],
+        Some(20),
+    )?;
+    cmd.exp_string("if the wallet is lost:")?;
+    let (_unread, mnemonic) = cmd.exp_regex(r"[a-z]+( [a-z]+){23}")?;
+    cmd.exp_string(&format!(
+        "Successfully added a key and an address with alias: \"{}\"",
+        key_alias
+    ))?;
+
+    // 2. key restore from the mnemonic code
+    let mut cmd = run!(
+        test,
+        Bin::Wallet,
+        &[
+            "key",
+            "restore",
+            "--alias",
+            restored_alias,
+            "--unsafe-dont-encrypt",
+        ],
+        Some(20),
+    )?;
+    cmd.exp_string("Input your mnemonic code:")?;
+    cmd.send_line(&mnemonic)?;
+    cmd.exp_string(&format!(
+        "Successfully added a key and an address with alias: \"{}\"",
+        restored_alias
+    ))?;
+
+    // 3. key find both keys to check that they are the same
+    let mut public_keys = vec![];
+    for alias in [key_alias, restored_alias] {
+        let mut cmd = run!(
+            test,
+            Bin::Wallet,
+            &["key", "find", "--alias", alias],
+            Some(20),
+        )?;
+        let (_unread, public_key) = cmd.exp_regex("Public key: .*")?;
+        public_keys.push(public_key);
+    }
+    assert_eq!(public_keys[0], public_keys[1]);
+
+    Ok(())
+}
+
 /// Test wallet address commands:
 /// 1. address gen
 /// 2. address add
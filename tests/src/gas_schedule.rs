@@ -0,0 +1,170 @@
+//! A benchmarking harness that derives the costs of the [`GasSchedule`] from
+//! the time it takes to execute the operations of transactions and validity
+//! predicates on the persistent storage.
+//!
+//! Every cost is fitted with a linear model `cost = fixed + per_byte * bytes`
+//! and normalized to the time it takes to read a single byte from storage,
+//! which is the unit of gas. The benchmarks are ignored by default, run them
+//! with `make bench-gas` on a machine representative of a validator node and
+//! update the gas schedule in the genesis parameters (or with a governance
+//! proposal) with the printed schedule.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use namada::ledger::gas::GasSchedule;
+use namada::types::address;
+use namada::types::chain::ChainId;
+use namada::types::hash::Hash;
+use namada::types::key::{self, common, RefTo, SigScheme};
+use namada::types::storage::Key;
+use namada_apps::node::ledger::storage::PersistentStorage;
+use tempfile::TempDir;
+
+/// The sizes of the benchmarked values in bytes
+const SIZES: [usize; 5] = [32, 256, 2048, 16384, 131072];
+/// The number of repetitions of every benchmarked operation per value size
+const REPETITIONS: usize = 200;
+/// The number of entries under a benchmarked iterator prefix
+const ITER_ENTRIES: usize = 1000;
+
+/// A linear fit of the time of an operation in nanoseconds
+#[derive(Debug, Clone, Copy)]
+struct Fit {
+    fixed: f64,
+    per_byte: f64,
+}
+
+impl Fit {
+    /// A least squares linear fit of the given `(bytes, nanoseconds)` samples
+    fn new(samples: &[(f64, f64)]) -> Self {
+        let n = samples.len() as f64;
+        let mean_x = samples.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = samples.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let cov: f64 = samples
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum();
+        let var: f64 = samples.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+        let per_byte = if var == 0.0 { 0.0 } else { cov / var };
+        Self {
+            fixed: (mean_y - per_byte * mean_x).max(0.0),
+            per_byte: per_byte.max(0.0),
+        }
+    }
+}
+
+/// Convert a time in nanoseconds to gas with the given gas unit, rounding up
+/// to at least 1
+fn to_gas(ns: f64, unit: f64) -> u64 {
+    (ns / unit).ceil().max(1.0) as u64
+}
+
+/// Time the given operation on every value size and fit the samples
+fn bench(mut op: impl FnMut(usize, usize)) -> Fit {
+    let samples: Vec<(f64, f64)> = SIZES
+        .iter()
+        .map(|&size| {
+            let start = Instant::now();
+            for i in 0..REPETITIONS {
+                op(size, i);
+            }
+            let ns = start.elapsed().as_nanos() as f64 / REPETITIONS as f64;
+            (size as f64, ns)
+        })
+        .collect();
+    Fit::new(&samples)
+}
+
+fn key(size: usize, i: usize) -> Key {
+    Key::parse(format!("bench/{}/{}", size, i)).unwrap()
+}
+
+/// Derive and print the gas schedule from the benchmarks.
+#[test]
+#[ignore]
+fn derive_gas_schedule() {
+    let db_path =
+        TempDir::new().expect("Unable to create a temporary DB directory");
+    let mut storage = PersistentStorage::open(
+        db_path.path(),
+        ChainId::default(),
+        address::nam(),
+        None,
+        None,
+    );
+
+    let write = bench(|size, i| {
+        storage.write(&key(size, i), vec![1_u8; size]).unwrap();
+    });
+    let read = bench(|size, i| {
+        let (value, _gas) = storage.read(&key(size, i)).unwrap();
+        assert_eq!(value.map(|value| value.len()), Some(size));
+    });
+
+    for i in 0..ITER_ENTRIES {
+        let key = Key::parse(format!("iter/{}", i)).unwrap();
+        storage.write(&key, [1_u8; 32]).unwrap();
+    }
+    let prefix = Key::parse("iter").unwrap();
+    let start = Instant::now();
+    for _ in 0..REPETITIONS {
+        let (_iter, _gas) = storage.iter_prefix(&prefix);
+    }
+    let iter_prefix_ns = start.elapsed().as_nanos() as f64 / REPETITIONS as f64;
+    let start = Instant::now();
+    let (iter, _gas) = storage.iter_prefix(&prefix);
+    assert_eq!(iter.count(), ITER_ENTRIES);
+    let iter_next_ns = start.elapsed().as_nanos() as f64 / ITER_ENTRIES as f64;
+
+    let hash = bench(|size, _i| {
+        let hash = Hash::sha256(vec![1_u8; size]);
+        assert_ne!(hash, Hash::default());
+    });
+
+    // Copying the data between the wasm memory and the host
+    let memory = bench(|size, _i| {
+        let data = vec![1_u8; size];
+        let copy = data.clone();
+        assert_eq!(copy.len(), size);
+    });
+
+    let keypair = key::testing::keypair_1();
+    let pk = keypair.ref_to();
+    let signed: HashMap<usize, (Vec<u8>, common::Signature)> = SIZES
+        .iter()
+        .map(|&size| {
+            let data = vec![1_u8; size];
+            let sig = common::SigScheme::sign(&keypair, &data);
+            (size, (data, sig))
+        })
+        .collect();
+    let verify_signature = bench(|size, _i| {
+        let (data, sig) = &signed[&size];
+        common::SigScheme::verify_signature_raw(&pk, data, sig).unwrap();
+    });
+
+    // The time of reading a single byte from storage is the unit of gas
+    let unit = read.per_byte.max(f64::MIN_POSITIVE);
+    // The costs of the wasm fuel and of the growth of the storage size are not
+    // derived from the execution time, so they are kept at their defaults
+    let schedule = GasSchedule {
+        host_fn: to_gas(memory.fixed, unit),
+        memory_byte: to_gas(memory.per_byte, unit),
+        storage_access: to_gas(read.fixed.min(write.fixed), unit),
+        storage_read_byte: 1,
+        storage_write_byte: to_gas(write.per_byte, unit),
+        iter_prefix: to_gas(iter_prefix_ns, unit),
+        iter_next: to_gas(iter_next_ns, unit),
+        hash: to_gas(hash.fixed, unit),
+        hash_byte: to_gas(hash.per_byte, unit),
+        verify_signature: to_gas(
+            (verify_signature.fixed - hash.fixed).max(0.0),
+            unit,
+        ),
+        ..GasSchedule::default()
+    };
+    println!("Gas unit: {:.3}ns", unit);
+    println!("{:#?}", schedule);
+    println!("{}", toml::to_string(&schedule).unwrap());
+}
@@ -0,0 +1,363 @@
+//! # Governance validity predicate tests
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use namada::ledger::governance::parameters::GovParams;
+    use namada::ledger::governance::{
+        storage as gov_storage, ADDRESS as gov_address,
+    };
+    use namada::ledger::native_vp::governance::GovernanceVp;
+    use namada::ledger::pos::types::{GenesisValidator, ValidatorDeltas};
+    use namada::ledger::pos::PosParams;
+    use namada::ledger::storage_api::{self, StorageRead, StorageWrite};
+    use namada::proof_of_stake::bond_tokens;
+    use namada::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+    };
+    use namada::types::address::Address;
+    use namada::types::governance::{
+        OfflineProposal, OfflineVote, ProposalVote,
+    };
+    use namada::types::key::{self, common, RefTo, SigScheme};
+    use namada::types::storage::Epoch;
+    use namada::types::token;
+    use namada::types::transaction::governance::{
+        TallyOfflineProposalData, TopUpProposalDepositData,
+    };
+    use namada_tx_prelude::BorshSerialize;
+    use rust_decimal::Decimal;
+
+    use crate::native_vp::pos::init_pos;
+    use crate::native_vp::TestNativeVpEnv;
+    use crate::tx::tx_host_env;
+
+    const PROPOSAL_ID: u64 = 0;
+    const START_EPOCH: Epoch = Epoch(2);
+
+    /// Initialize the governance parameters and a proposal whose voting
+    /// period starts at [`START_EPOCH`], and credit the given accounts
+    fn init_proposal(accounts: &[&Address]) {
+        tx_host_env::init();
+
+        tx_host_env::with(|tx_env| {
+            let native_token = tx_env.wl_storage.storage.native_token.clone();
+            tx_env.spawn_accounts([&native_token]);
+            for account in accounts {
+                tx_env.spawn_accounts([*account]);
+                tx_env.credit_tokens(
+                    account,
+                    &native_token,
+                    None,
+                    token::Amount::whole(1000),
+                );
+            }
+            GovParams::default()
+                .init_storage(&mut tx_env.wl_storage)
+                .unwrap();
+            tx_env
+                .wl_storage
+                .storage
+                .write(
+                    &gov_storage::get_voting_start_epoch_key(PROPOSAL_ID),
+                    START_EPOCH.try_to_vec().unwrap(),
+                )
+                .unwrap();
+            tx_env.commit_genesis();
+        });
+    }
+
+    /// Validate the changes of the tx in the given epoch with the governance
+    /// VP
+    fn validate_in_epoch(epoch: Epoch) -> bool {
+        let mut tx_env = tx_host_env::take();
+        tx_env.wl_storage.storage.block.epoch = epoch;
+
+        let vp_env = TestNativeVpEnv::from_tx_env(tx_env, gov_address);
+        let result = vp_env
+            .validate_tx(|ctx| GovernanceVp { ctx })
+            .expect("Validation must not fail");
+
+        // Put the tx_env back to validate it again
+        tx_host_env::set(vp_env.tx_env);
+        result
+    }
+
+    /// Top up the proposal deposit of the given depositor
+    fn top_up_deposit(depositor: &Address) {
+        let data = TopUpProposalDepositData {
+            id: PROPOSAL_ID,
+            depositor: depositor.clone(),
+            amount: token::Amount::whole(10),
+        };
+        storage_api::governance::top_up_proposal_deposit(
+            tx_host_env::ctx(),
+            data,
+        )
+        .unwrap();
+    }
+
+    /// Test that a deposit can be topped up before the voting period starts
+    #[test]
+    fn test_top_up_deposit_before_start_epoch() {
+        let depositor = established_address_1();
+        init_proposal(&[&depositor]);
+
+        top_up_deposit(&depositor);
+
+        assert!(validate_in_epoch(START_EPOCH.prev()));
+    }
+
+    /// Test that a deposit cannot be topped up once the voting period started
+    #[test]
+    fn test_top_up_deposit_after_start_epoch() {
+        let depositor = established_address_1();
+        init_proposal(&[&depositor]);
+
+        top_up_deposit(&depositor);
+
+        assert!(!validate_in_epoch(START_EPOCH));
+        assert!(!validate_in_epoch(START_EPOCH.next()));
+    }
+
+    /// Test that a deposit cannot be credited to an address that didn't pay
+    /// for it, as it hasn't signed the tx
+    #[test]
+    fn test_top_up_deposit_of_non_signing_depositor() {
+        let depositor = established_address_1();
+        let payer = established_address_2();
+        init_proposal(&[&depositor, &payer]);
+
+        let amount = token::Amount::whole(10);
+        let deposit_key =
+            gov_storage::get_proposal_deposit_key(PROPOSAL_ID, &depositor);
+        tx_host_env::ctx().write(&deposit_key, amount).unwrap();
+        let native_token = tx_host_env::with(|tx_env| {
+            tx_env.wl_storage.storage.native_token.clone()
+        });
+        storage_api::token::transfer(
+            tx_host_env::ctx(),
+            &native_token,
+            &payer,
+            &gov_address,
+            amount,
+        )
+        .unwrap();
+
+        assert!(!validate_in_epoch(START_EPOCH.prev()));
+    }
+
+    /// The epoch in which the offline proposals of the tests are submitted
+    const OFFLINE_EPOCH: Epoch = Epoch(3);
+    /// The tally epoch of the offline proposals of the tests, at which the
+    /// delegation of the delegator is active
+    const OFFLINE_TALLY_EPOCH: Epoch = Epoch(2);
+
+    /// An account with its signing key
+    type Signer = (Address, common::SecretKey);
+
+    /// A validator, a delegator bonded to it and an account without any
+    /// stake, with their signing keys
+    fn offline_signers() -> [Signer; 3] {
+        [
+            (established_address_1(), 1),
+            (established_address_2(), 2),
+            (established_address_3(), 3),
+        ]
+        .map(|(address, seed)| {
+            (address, key::testing::common_sk_from_simple_seed(seed))
+        })
+    }
+
+    /// Initialize PoS with the validator and the delegator of
+    /// [`offline_signers`] and the governance parameters, and set the block
+    /// epoch
+    fn init_offline_signers(epoch: Epoch) {
+        let [(validator, _), (delegator, _), (outsider, _)] = offline_signers();
+        let genesis_validator = GenesisValidator {
+            address: validator.clone(),
+            tokens: token::Amount::whole(1000),
+            consensus_key: key::testing::keypair_1().ref_to(),
+            commission_rate: Decimal::new(5, 2),
+            max_commission_rate_change: Decimal::new(1, 2),
+        };
+        init_pos(&[genesis_validator], &PosParams::default(), Epoch(0));
+
+        tx_host_env::with(|tx_env| {
+            let native_token = tx_env.wl_storage.storage.native_token.clone();
+            tx_env.spawn_accounts([&delegator, &outsider]);
+            tx_env.credit_tokens(
+                &delegator,
+                &native_token,
+                None,
+                token::Amount::whole(1000),
+            );
+            for (address, signing_key) in offline_signers() {
+                tx_env
+                    .wl_storage
+                    .write(&key::pk_key(&address), signing_key.ref_to())
+                    .unwrap();
+            }
+            bond_tokens(
+                &mut tx_env.wl_storage,
+                Some(&delegator),
+                &validator,
+                token::Amount::whole(100),
+                Epoch(0),
+            )
+            .unwrap();
+            GovParams::default()
+                .init_storage(&mut tx_env.wl_storage)
+                .unwrap();
+            tx_env.wl_storage.storage.block.epoch = epoch;
+            tx_env.commit_genesis();
+        });
+    }
+
+    /// Sign an offline proposal of the validator tallied at the given epoch
+    fn sign_offline_proposal(tally_epoch: Epoch) -> OfflineProposal {
+        let [(validator, signing_key), _, _] = offline_signers();
+        let mut proposal = OfflineProposal {
+            content: BTreeMap::new(),
+            author: validator.clone(),
+            tally_epoch,
+            signature: common::SigScheme::sign(&signing_key, []),
+            address: validator,
+        };
+        proposal.signature =
+            common::SigScheme::sign(&signing_key, proposal.compute_hash());
+        proposal
+    }
+
+    /// Sign a vote of the given signer on an offline proposal
+    fn sign_offline_vote(
+        proposal: &OfflineProposal,
+        vote: ProposalVote,
+        (address, signing_key): &Signer,
+    ) -> OfflineVote {
+        OfflineVote::new(proposal, vote, address.clone(), signing_key)
+    }
+
+    /// Initialize the offline signers and submit an offline proposal tallied
+    /// at [`OFFLINE_TALLY_EPOCH`] in [`OFFLINE_EPOCH`] with the votes of the
+    /// given signers
+    fn init_offline_proposal(voters: &[&Signer]) -> OfflineProposal {
+        init_offline_signers(OFFLINE_EPOCH);
+        let proposal = sign_offline_proposal(OFFLINE_TALLY_EPOCH);
+        let votes = voters
+            .iter()
+            .map(|voter| sign_offline_vote(&proposal, ProposalVote::Yay, voter))
+            .collect();
+
+        tx_host_env::with(|tx_env| {
+            storage_api::governance::tally_offline_proposal(
+                &mut tx_env.wl_storage,
+                TallyOfflineProposalData {
+                    proposal: proposal.clone(),
+                    votes,
+                },
+            )
+            .unwrap();
+            tx_env.commit_genesis();
+        });
+        proposal
+    }
+
+    /// Submit an offline proposal with the given votes in the tx
+    fn submit_offline_proposal(
+        proposal: OfflineProposal,
+        votes: Vec<OfflineVote>,
+    ) {
+        storage_api::governance::tally_offline_proposal(
+            tx_host_env::ctx(),
+            TallyOfflineProposalData { proposal, votes },
+        )
+        .unwrap();
+    }
+
+    /// Test that the validator and the delegator can vote on an offline
+    /// proposal while its votes submission window is open, and not after
+    #[test]
+    fn test_offline_votes_submission_window() {
+        let [validator, delegator, _] = offline_signers();
+        let proposal = init_offline_proposal(&[]);
+        let close_epoch: Epoch = tx_host_env::with(|tx_env| {
+            tx_env
+                .wl_storage
+                .read(&gov_storage::get_pending_offline_tally_key(
+                    &proposal.compute_hash(),
+                ))
+                .unwrap()
+                .unwrap()
+        });
+
+        let votes = [&validator, &delegator]
+            .map(|voter| sign_offline_vote(&proposal, ProposalVote::Yay, voter))
+            .to_vec();
+        submit_offline_proposal(proposal, votes);
+
+        assert!(validate_in_epoch(OFFLINE_EPOCH));
+        assert!(validate_in_epoch(close_epoch.prev()));
+        assert!(!validate_in_epoch(close_epoch));
+        assert!(!validate_in_epoch(close_epoch.next()));
+    }
+
+    /// Test that an account that has no stake at the tally epoch of an
+    /// offline proposal cannot vote on it
+    #[test]
+    fn test_offline_vote_of_non_delegator() {
+        let [_, _, outsider] = offline_signers();
+        let proposal = init_offline_proposal(&[]);
+
+        let vote = sign_offline_vote(&proposal, ProposalVote::Yay, &outsider);
+        submit_offline_proposal(proposal, vec![vote]);
+
+        assert!(!validate_in_epoch(OFFLINE_EPOCH));
+    }
+
+    /// Test that a submitted vote on an offline proposal cannot be
+    /// overwritten
+    #[test]
+    fn test_duplicate_offline_vote() {
+        let [validator, _, _] = offline_signers();
+        let proposal = init_offline_proposal(&[&validator]);
+
+        // A resubmitted vote is skipped by the tx
+        let vote = sign_offline_vote(&proposal, ProposalVote::Nay, &validator);
+        submit_offline_proposal(proposal.clone(), vec![vote.clone()]);
+        assert!(validate_in_epoch(OFFLINE_EPOCH));
+
+        // and it's rejected if written anyway
+        let vote_key = gov_storage::get_offline_vote_key(
+            &proposal.compute_hash(),
+            &validator.0,
+        );
+        tx_host_env::ctx().write(&vote_key, vote).unwrap();
+        assert!(!validate_in_epoch(OFFLINE_EPOCH));
+    }
+
+    /// Test that the tally epoch of an offline proposal can neither be in the
+    /// future, nor older than the PoS data that will be kept when its votes
+    /// submission window closes
+    #[test]
+    fn test_offline_proposal_tally_epoch() {
+        let current_epoch = Epoch(30);
+        let min_period = GovParams::default().min_proposal_period;
+        let num_past_epochs = ValidatorDeltas::get_num_past_epochs();
+        let oldest_tally_epoch = current_epoch + min_period - num_past_epochs;
+
+        for (tally_epoch, is_valid) in [
+            (oldest_tally_epoch.prev(), false),
+            (oldest_tally_epoch, true),
+            (current_epoch, true),
+            (current_epoch.next(), false),
+        ] {
+            init_offline_signers(current_epoch);
+            let proposal = sign_offline_proposal(tally_epoch);
+            submit_offline_proposal(proposal, vec![]);
+
+            assert_eq!(validate_in_epoch(current_epoch), is_valid);
+        }
+    }
+}
@@ -1,4 +1,6 @@
+pub mod governance;
 pub mod pos;
+pub mod slash_fund;
 
 use std::collections::BTreeSet;
 
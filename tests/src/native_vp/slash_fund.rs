@@ -0,0 +1,94 @@
+//! # Slash fund validity predicate tests
+
+#[cfg(test)]
+mod tests {
+    use namada::core::ledger::slash_fund::ADDRESS as slash_fund_address;
+    use namada::ledger::governance::storage as gov_storage;
+    use namada::ledger::native_vp::slash_fund::SlashFundVp;
+    use namada::ledger::storage_api;
+    use namada::types::address::testing::established_address_1;
+    use namada::types::address::Address;
+    use namada::types::token;
+    use namada_tx_prelude::BorshSerialize;
+
+    use crate::native_vp::TestNativeVpEnv;
+    use crate::tx::tx_host_env;
+
+    const PROPOSAL_ID: u64 = 0;
+
+    /// Initialize the slash fund and an account with some native tokens, and
+    /// mark the proposal [`PROPOSAL_ID`] as being executed
+    fn init_slash_fund() {
+        tx_host_env::init();
+
+        tx_host_env::with(|tx_env| {
+            let native_token = tx_env.wl_storage.storage.native_token.clone();
+            let account = established_address_1();
+            tx_env.spawn_accounts([&native_token, &account]);
+            for owner in [&account, &slash_fund_address] {
+                tx_env.credit_tokens(
+                    owner,
+                    &native_token,
+                    None,
+                    token::Amount::whole(1000),
+                );
+            }
+            tx_env
+                .wl_storage
+                .storage
+                .write(
+                    &gov_storage::get_proposal_execution_key(PROPOSAL_ID),
+                    ().try_to_vec().unwrap(),
+                )
+                .unwrap();
+            tx_env.tx.data = Some(PROPOSAL_ID.try_to_vec().unwrap());
+            tx_env.commit_genesis();
+        });
+    }
+
+    /// Transfer some native tokens with the tx
+    fn transfer(source: &Address, target: &Address) {
+        let native_token = tx_host_env::with(|tx_env| {
+            tx_env.wl_storage.storage.native_token.clone()
+        });
+        storage_api::token::transfer(
+            tx_host_env::ctx(),
+            &native_token,
+            source,
+            target,
+            token::Amount::whole(10),
+        )
+        .unwrap();
+    }
+
+    /// Validate the changes of the tx with the slash fund VP
+    fn validate() -> bool {
+        let tx_env = tx_host_env::take();
+        let vp_env = TestNativeVpEnv::from_tx_env(tx_env, slash_fund_address);
+        vp_env
+            .validate_tx(|ctx| SlashFundVp { ctx })
+            .expect("Validation must not fail")
+    }
+
+    /// Test that anyone can credit the slash fund
+    #[test]
+    fn test_credit_slash_fund() {
+        init_slash_fund();
+
+        transfer(&established_address_1(), &slash_fund_address);
+
+        assert!(validate());
+    }
+
+    /// Test that a tx cannot debit the slash fund, even when its data is the
+    /// id of an accepted proposal being executed, as the fund is only paid
+    /// out by the protocol
+    #[test]
+    fn test_debit_slash_fund() {
+        init_slash_fund();
+
+        transfer(&slash_fund_address, &established_address_1());
+
+        assert!(!validate());
+    }
+}
@@ -2,6 +2,7 @@ use core::time::Duration;
 use std::collections::HashMap;
 use std::str::FromStr;
 
+use borsh::BorshSerialize;
 pub use namada::core::ledger::ibc::actions::*;
 use namada::ibc::applications::ics20_fungible_token_transfer::msgs::transfer::MsgTransfer;
 use namada::ibc::core::ics02_client::client_consensus::ConsensusState;
@@ -51,11 +52,11 @@ pub use namada::ledger::ibc::storage::{
     ack_key, capability_index_key, capability_key, channel_counter_key,
     channel_key, client_counter_key, client_state_key, client_type_key,
     commitment_key, connection_counter_key, connection_key,
-    consensus_state_key, next_sequence_ack_key, next_sequence_recv_key,
-    next_sequence_send_key, port_key, receipt_key,
+    consensus_state_key, ica_channel_key, next_sequence_ack_key,
+    next_sequence_recv_key, next_sequence_send_key, port_key, receipt_key,
 };
 use namada::ledger::ibc::vp::{
-    get_dummy_header as tm_dummy_header, Ibc, IbcToken,
+    get_dummy_header as tm_dummy_header, Ibc, IbcToken, InterchainAccount,
 };
 use namada::ledger::native_vp::{Ctx, NativeVp};
 use namada::ledger::storage::mockdb::MockDB;
@@ -64,7 +65,10 @@ use namada::ledger::tx_env::TxEnv;
 use namada::proto::Tx;
 use namada::tendermint_proto::Protobuf;
 use namada::types::address::{self, Address, InternalAddress};
-use namada::types::ibc::data::{FungibleTokenPacketData, PacketAck};
+use namada::types::ibc::data::{
+    FungibleTokenPacketData, InterchainAccountMsg, InterchainAccountPacketData,
+    InterchainAccountPacketType, PacketAck,
+};
 use namada::types::storage::{self, BlockHash, BlockHeight, Key, TxIndex};
 use namada::types::token::{self, Amount};
 use namada::vm::{wasm, WasmCacheRwAccess};
@@ -109,6 +113,26 @@ impl<'a> TestIbcTokenVp<'a> {
     }
 }
 
+pub struct TestInterchainAccountVp<'a> {
+    pub ica: InterchainAccount<'a, MockDB, Sha256Hasher, WasmCacheRwAccess>,
+}
+
+impl<'a> TestInterchainAccountVp<'a> {
+    pub fn validate(
+        &self,
+        tx_data: &[u8],
+    ) -> std::result::Result<
+        bool,
+        namada::ledger::ibc::vp::InterchainAccountError,
+    > {
+        self.ica.validate_tx(
+            tx_data,
+            self.ica.ctx.keys_changed,
+            self.ica.ctx.verifiers,
+        )
+    }
+}
+
 /// Validate an IBC transaction with IBC VP.
 pub fn validate_ibc_vp_from_tx<'a>(
     tx_env: &'a TestTxEnv,
@@ -180,6 +204,43 @@ pub fn validate_token_vp_from_tx<'a>(
     TestIbcTokenVp { token }.validate(tx.data.as_ref().unwrap())
 }
 
+/// Validate the interchain account VP for the given account
+pub fn validate_ica_vp_from_tx<'a>(
+    tx_env: &'a TestTxEnv,
+    tx: &'a Tx,
+    account: &'a Address,
+) -> std::result::Result<bool, namada::ledger::ibc::vp::InterchainAccountError>
+{
+    let (verifiers, keys_changed) = tx_env
+        .wl_storage
+        .write_log
+        .verifiers_and_changed_keys(&tx_env.verifiers);
+    if !verifiers.contains(account) {
+        panic!(
+            "The interchain account {} isn't part of the tx verifiers set: \
+             {:#?}",
+            account, verifiers
+        );
+    }
+    let (vp_wasm_cache, _vp_cache_dir) =
+        wasm::compilation_cache::common::testing::cache();
+
+    let ctx = Ctx::new(
+        account,
+        &tx_env.wl_storage.storage,
+        &tx_env.wl_storage.write_log,
+        tx,
+        &TxIndex(0),
+        VpGasMeter::new(0),
+        &keys_changed,
+        &verifiers,
+        vp_wasm_cache,
+    );
+    let ica = InterchainAccount { ctx };
+
+    TestInterchainAccountVp { ica }.validate(tx.data.as_ref().unwrap())
+}
+
 /// Initialize the test storage. Requires initialized [`tx_host_env::ENV`].
 pub fn init_storage() -> (Address, Address) {
     tx_host_env::with(|env| {
@@ -287,6 +348,64 @@ pub fn prepare_opened_channel(
     (port_id, channel_id, writes)
 }
 
+/// Prepare an opened channel of the interchain account host port or the
+/// controller port of the owner on this chain, and its active channel
+pub fn prepare_opened_ica_channel(
+    conn_id: &ConnectionId,
+    owner: Option<&Address>,
+) -> (PortId, ChannelId, PortId, HashMap<storage::Key, Vec<u8>>) {
+    let mut writes = HashMap::new();
+
+    let (port_id, controller_port_id, counterparty_port_id) = match owner {
+        Some(owner) => {
+            let controller_port_id = ica_controller_port_id(owner).unwrap();
+            (
+                controller_port_id.clone(),
+                controller_port_id,
+                port_id(ICA_HOST_PORT_ID).unwrap(),
+            )
+        }
+        None => {
+            let controller_port_id =
+                port_id("icacontroller-cosmos1controller").unwrap();
+            (
+                port_id(ICA_HOST_PORT_ID).unwrap(),
+                controller_port_id.clone(),
+                controller_port_id,
+            )
+        }
+    };
+    // port
+    let key = port_key(&port_id);
+    writes.insert(key, 0_u64.to_be_bytes().to_vec());
+    // capability
+    let key = capability_key(0);
+    let bytes = port_id.as_bytes().to_vec();
+    writes.insert(key, bytes);
+    // channel
+    let channel_id = channel_id(0);
+    let port_channel_id = port_channel_id(port_id.clone(), channel_id);
+    let key = channel_key(&port_channel_id);
+    let counterparty = channel_counterparty(
+        counterparty_port_id,
+        *dummy_channel_counterparty().channel_id().unwrap(),
+    );
+    let channel = ChannelEnd::new(
+        ChanState::Open,
+        Order::Ordered,
+        counterparty,
+        vec![conn_id.clone()],
+        ChanVersion::new(ICA_VERSION.to_string()),
+    );
+    let bytes = channel.encode_vec().expect("encoding failed");
+    writes.insert(key, bytes);
+    // active channel
+    let key = ica_channel_key(conn_id, &controller_port_id);
+    writes.insert(key, channel_id.to_string().into_bytes());
+
+    (port_id, channel_id, controller_port_id, writes)
+}
+
 pub fn msg_create_client() -> MsgCreateAnyClient {
     let height = Height::new(0, 1);
     let header = MockHeader {
@@ -564,6 +683,17 @@ pub fn msg_packet_ack(packet: Packet) -> MsgAcknowledgement {
     }
 }
 
+pub fn msg_packet_ack_error(packet: Packet) -> MsgAcknowledgement {
+    MsgAcknowledgement {
+        packet,
+        acknowledgement: PacketAck::result_error("test".to_owned())
+            .encode_to_vec()
+            .into(),
+        proofs: dummy_proofs(),
+        signer: Signer::new("test"),
+    }
+}
+
 pub fn received_packet(
     port_id: PortId,
     channel_id: ChannelId,
@@ -592,6 +722,33 @@ pub fn received_packet(
     }
 }
 
+pub fn ica_received_packet(
+    port_id: PortId,
+    channel_id: ChannelId,
+    sequence: Sequence,
+    controller_port_id: PortId,
+    msgs: &[InterchainAccountMsg],
+) -> Packet {
+    let timeout_timestamp =
+        (Timestamp::now() + Duration::from_secs(100)).unwrap();
+    let data = InterchainAccountPacketData {
+        packet_type: InterchainAccountPacketType::ExecuteTx,
+        data: data_encoding::BASE64
+            .encode(&msgs.to_vec().try_to_vec().unwrap()),
+        memo: String::new(),
+    };
+    Packet {
+        sequence,
+        source_port: controller_port_id,
+        source_channel: *dummy_channel_counterparty().channel_id().unwrap(),
+        destination_port: port_id,
+        destination_channel: channel_id,
+        data: serde_json::to_vec(&data).unwrap(),
+        timeout_height: Height::new(1, 10),
+        timeout_timestamp,
+    }
+}
+
 pub fn msg_timeout(packet: Packet, next_sequence_recv: Sequence) -> MsgTimeout {
     MsgTimeout {
         packet,
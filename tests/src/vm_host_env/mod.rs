@@ -22,16 +22,31 @@ mod tests {
 
     use itertools::Itertools;
     use namada::core::ledger::ibc::actions::IbcActions;
+    use namada::ibc::applications::ics20_fungible_token_transfer::msgs::transfer::MsgTransfer;
+    use namada::ibc::core::ics24_host::identifier::{ChannelId, PortId};
     use namada::ibc::tx_msg::Msg;
+    use namada::ledger::gas::GasSchedule;
     use namada::ledger::ibc::storage as ibc_storage;
     use namada::ledger::ibc::vp::{
-        get_dummy_header as tm_dummy_header, Error as IbcError,
+        get_dummy_header as tm_dummy_header, Error as IbcError, IbcTokenError,
+        InterchainAccountError,
     };
+    use namada::ledger::pos::types::GenesisValidator;
+    use namada::ledger::pos::PosParams;
     use namada::ledger::tx_env::TxEnv;
-    use namada::proto::{SignedTxData, Tx};
+    use namada::proto::{SignedTxData, Tx, TxCode};
     use namada::tendermint_proto::Protobuf;
+    use namada::types::address::Address;
+    use namada::types::event::TxEvent;
+    use namada::types::hash::{Hash, HashAlgorithm};
+    use namada::types::ibc::data::{
+        InterchainAccountMsg, MsgSendIcaTx, PacketAck,
+    };
+    use namada::types::ibc::{IbcFlow, IbcFlowLimit};
     use namada::types::key::*;
-    use namada::types::storage::{self, BlockHash, BlockHeight, Key, KeySeg};
+    use namada::types::storage::{
+        self, BlockHash, BlockHeight, Epoch, Key, KeySeg,
+    };
     use namada::types::time::DateTimeUtc;
     use namada::types::token::{self, Amount};
     use namada::types::{address, key};
@@ -43,6 +58,7 @@ mod tests {
     use test_log::test;
 
     use super::{ibc, tx, vp};
+    use crate::native_vp::pos::init_pos;
     use crate::tx::{tx_host_env, TestTxEnv};
     use crate::vp::{vp_host_env, TestVpEnv};
 
@@ -84,6 +100,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tx_write_charges_size_diff() {
+        // The environment must be initialized first
+        tx_host_env::init();
+        tx_host_env::with(|env| {
+            env.gas_meter.set_schedule(GasSchedule {
+                storage_size_byte: 100,
+                ..GasSchedule::default()
+            })
+        });
+        let current_gas = || {
+            tx_host_env::with(|env| env.gas_meter.get_current_transaction_gas())
+        };
+
+        let key = storage::Key::parse("key").unwrap();
+        tx::ctx().write(&key, vec![1_u8; 10]).unwrap();
+
+        // A write that grows the value is charged for the added bytes
+        let gas_before = current_gas();
+        tx::ctx().write(&key, vec![1_u8; 1000]).unwrap();
+        let grow_gas = current_gas() - gas_before;
+        assert!(grow_gas >= 990 * 100);
+
+        // A write that shrinks the value is not charged for the size diff
+        let gas_before = current_gas();
+        tx::ctx().write(&key, vec![1_u8; 10]).unwrap();
+        let shrink_gas = current_gas() - gas_before;
+        assert!(shrink_gas < 100 * 100);
+    }
+
     #[test]
     fn test_tx_has_key() {
         // The environment must be initialized first
@@ -225,6 +271,60 @@ mod tests {
         tx::ctx().init_account(code).unwrap();
     }
 
+    #[test]
+    fn test_tx_emit_event() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        let event1 = TxEvent::new("transfer")
+            .with_attribute("source", "alice")
+            .with_attribute("amount", 10);
+        let event2 = TxEvent::new("transfer").with_attribute("amount", 20);
+        tx::ctx().emit_event(&event1).unwrap();
+        tx::ctx().emit_event(&event2).unwrap();
+
+        // All the events should be kept in the order of their emission
+        tx_host_env::with(|env| {
+            assert_eq!(
+                env.wl_storage.write_log.get_events(),
+                &[event1.clone(), event2.clone()]
+            );
+        });
+
+        // An event with a reserved attribute cannot be emitted
+        let reserved = TxEvent::new("transfer").with_attribute("hash", "");
+        assert!(
+            panic::catch_unwind(|| tx::ctx().emit_event(&reserved).unwrap())
+                .err()
+                .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+                .unwrap()
+                .contains("InvalidEvent")
+        );
+    }
+
+    #[test]
+    fn test_tx_hash_and_verify_signature() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        let data = b"abc";
+        let hash = namada_tx_prelude::hash_data(HashAlgorithm::Sha256, data);
+        assert_eq!(hash, Hash::sha256(data));
+
+        let keypair = key::testing::keypair_1();
+        let sig = common::SigScheme::sign(&keypair, data);
+        assert!(namada_tx_prelude::verify_signature(
+            &keypair.ref_to(),
+            data,
+            &sig
+        ));
+        assert!(!namada_tx_prelude::verify_signature(
+            &key::testing::keypair_2().ref_to(),
+            data,
+            &sig
+        ));
+    }
+
     #[test]
     fn test_tx_get_metadata() {
         // The environment must be initialized first
@@ -477,6 +577,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_vp_hash_and_verify_signature() {
+        // The environment must be initialized first
+        vp_host_env::init();
+
+        let data = b"abc";
+        let hashes = [
+            (
+                HashAlgorithm::Sha256,
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                HashAlgorithm::Keccak256,
+                "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+            ),
+            (
+                HashAlgorithm::Blake2b256,
+                "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319",
+            ),
+        ];
+        for (algo, expected) in hashes {
+            let hash = namada_vp_prelude::hash_data(algo, data);
+            assert_eq!(hash.to_string().to_lowercase(), expected);
+        }
+
+        for keypair in [
+            key::testing::keypair_1(),
+            key::testing::gen_keypair::<secp256k1::SigScheme>()
+                .try_to_sk()
+                .unwrap(),
+        ] {
+            let sig = common::SigScheme::sign(&keypair, data);
+            assert!(namada_vp_prelude::verify_signature(
+                &keypair.ref_to(),
+                data,
+                &sig
+            ));
+            // a signature over other data is invalid
+            assert!(!namada_vp_prelude::verify_signature(
+                &keypair.ref_to(),
+                b"abd",
+                &sig
+            ));
+            // a signature by another key is invalid
+            assert!(!namada_vp_prelude::verify_signature(
+                &key::testing::keypair_2().ref_to(),
+                data,
+                &sig
+            ));
+        }
+    }
+
     #[test]
     fn test_vp_get_metadata() {
         // The environment must be initialized first
@@ -516,6 +668,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_vp_get_block_header() {
+        // The environment must be initialized first
+        vp_host_env::init();
+
+        // without a header, the block time is not available
+        assert!(vp::CTX.get_block_time().is_err());
+        assert_eq!(vp::CTX.get_block_proposer().unwrap(), None);
+
+        let header = storage::Header {
+            hash: Hash([1; 32]),
+            time: DateTimeUtc::now(),
+            next_validators_hash: Hash([2; 32]),
+        };
+        let proposer = address::testing::established_address_1();
+        vp_host_env::with(|env| {
+            env.wl_storage.storage.set_header(header.clone()).unwrap();
+            env.wl_storage.storage.block_proposer = Some(proposer.clone());
+        });
+        let height = vp::CTX.get_block_height().unwrap();
+
+        assert_eq!(vp::CTX.get_block_time().unwrap(), header.time);
+        assert_eq!(vp::CTX.get_block_proposer().unwrap(), Some(proposer));
+        assert_eq!(vp::CTX.get_block_header(height).unwrap(), Some(header));
+        // the header of a future block is not available
+        assert_eq!(
+            vp::CTX.get_block_header(height.next_height()).unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn test_vp_eval() {
         // The environment must be initialized first
@@ -543,6 +726,106 @@ mod tests {
         assert!(!result);
     }
 
+    #[test]
+    fn test_vp_query() {
+        // The environment must be initialized first
+        vp_host_env::init();
+
+        // A VP whose query entrypoint echoes back the input data and fails
+        // on an empty input
+        let vp_code = wat::parse_str(
+            r#"
+            (module
+                (import "env" "memory" (memory 1))
+                (import "env" "namada_vp_set_query_result"
+                    (func $set_query_result (param i64 i64)))
+
+                (func $_query (param i64 i64) (result i64)
+                (if
+                (result i64)
+                (i64.eqz (local.get 1))
+                (then (i64.const 0))
+                (else
+                    (call $set_query_result (local.get 0) (local.get 1))
+                    (i64.const 1))))
+
+                (export "memory" (memory 0))
+                (export "_query" (func $_query)))
+            "#,
+        )
+        .expect("unexpected error converting wat2wasm");
+
+        // Querying an account without a VP should fail
+        let queried = address::testing::established_address_2();
+        let result = vp::CTX.query_vp(&queried, vec![1, 2, 3]).unwrap();
+        assert_eq!(result, None);
+
+        // Set up the VP of the queried account in the prior state
+        let vp_key = Key::validity_predicate(&queried);
+        vp_host_env::with(|env| {
+            env.wl_storage.write(&vp_key, vp_code.clone()).unwrap();
+            env.wl_storage.commit_tx();
+        });
+
+        // A successful query returns the result set by the queried VP
+        let gas_before = vp_host_env::with(|env| env.gas_meter.current_gas);
+        let input: u64 = 42;
+        let result: Option<u64> =
+            vp::CTX.query_vp_with(&queried, &input).unwrap();
+        assert_eq!(result, Some(input));
+
+        // The compilation of the queried VP must be charged
+        let gas_after = vp_host_env::with(|env| env.gas_meter.current_gas);
+        assert!(gas_after - gas_before >= vp_code.len() as u64);
+
+        // A failing query returns nothing
+        let result = vp::CTX.query_vp(&queried, vec![]).unwrap();
+        assert_eq!(result, None);
+    }
+
+    /// Test that a query can't change what the caller reads from its result
+    /// buffer, even when the queried VP sets a result before failing
+    #[test]
+    fn test_vp_query_result_buffer() {
+        // The environment must be initialized first
+        vp_host_env::init();
+
+        // A VP whose query entrypoint sets the input data as its result, but
+        // fails
+        let vp_code = wat::parse_str(
+            r#"
+            (module
+                (import "env" "memory" (memory 1))
+                (import "env" "namada_vp_set_query_result"
+                    (func $set_query_result (param i64 i64)))
+
+                (func $_query (param i64 i64) (result i64)
+                (call $set_query_result (local.get 0) (local.get 1))
+                (i64.const 0))
+
+                (export "memory" (memory 0))
+                (export "_query" (func $_query)))
+            "#,
+        )
+        .expect("unexpected error converting wat2wasm");
+
+        let queried = address::testing::established_address_2();
+        let vp_key = Key::validity_predicate(&queried);
+        vp_host_env::with(|env| {
+            env.wl_storage.write(&vp_key, vp_code).unwrap();
+            env.wl_storage.commit_tx();
+            // A value that the caller has yet to read from its buffer
+            env.result_buffer = Some(vec![7]);
+        });
+
+        let result = vp::CTX.query_vp(&queried, vec![1, 2, 3]).unwrap();
+        assert_eq!(result, None);
+
+        // The caller's result buffer is untouched
+        let result_buffer = vp_host_env::with(|env| env.result_buffer.clone());
+        assert_eq!(result_buffer, Some(vec![7]));
+    }
+
     #[test]
     fn test_ibc_client() {
         // The environment must be initialized first
@@ -558,7 +841,7 @@ mod tests {
             .encode(&mut tx_data)
             .expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -595,7 +878,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -632,7 +915,7 @@ mod tests {
             .encode(&mut tx_data)
             .expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -677,7 +960,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -710,7 +993,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -751,7 +1034,7 @@ mod tests {
             .encode(&mut tx_data)
             .expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -788,7 +1071,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -817,7 +1100,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -856,7 +1139,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -886,7 +1169,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -930,7 +1213,7 @@ mod tests {
             .encode(&mut tx_data)
             .expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -971,7 +1254,7 @@ mod tests {
             .encode(&mut tx_data)
             .expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1015,7 +1298,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1039,7 +1322,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1080,7 +1363,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1105,7 +1388,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1148,7 +1431,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1191,7 +1474,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1239,7 +1522,7 @@ mod tests {
             .encode(&mut tx_data)
             .expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1279,7 +1562,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1295,6 +1578,117 @@ mod tests {
         assert!(result.expect("validation failed unexpectedly"));
     }
 
+    #[test]
+    fn test_ibc_send_token_over_flow_limit() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Set the initial state before starting transactions
+        let (token, sender) = ibc::init_storage();
+        let (client_id, _client_state, mut writes) = ibc::prepare_client();
+        let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
+        writes.extend(conn_writes);
+        let (port_id, channel_id, channel_writes) =
+            ibc::prepare_opened_channel(&conn_id, false);
+        writes.extend(channel_writes);
+        // limit the outflow below the transferred amount
+        let token_hash = ibc_storage::calc_hash(token.to_string());
+        let limit_key =
+            ibc_storage::flow_limit_key(&port_id, &channel_id, token_hash);
+        let limit = IbcFlowLimit {
+            inflow: None,
+            outflow: Some(Amount::from(99u64)),
+        };
+        writes.insert(limit_key, limit.try_to_vec().unwrap());
+        writes.into_iter().for_each(|(key, val)| {
+            tx_host_env::with(|env| {
+                env.wl_storage
+                    .storage
+                    .write(&key, &val)
+                    .expect("write error");
+            });
+        });
+
+        // Start a transaction to send a packet
+        let msg =
+            ibc::msg_transfer(port_id, channel_id, token.to_string(), &sender);
+        let mut tx_data = vec![];
+        msg.to_any().encode(&mut tx_data).expect("encoding failed");
+        // sending the token should fail
+        tx::ctx()
+            .dispatch_ibc_action(&tx_data)
+            .expect_err("sending over the flow limit should fail");
+    }
+
+    #[test]
+    fn test_ibc_escrow_token_without_flow() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Set the initial state before starting transactions
+        let (token, sender) = ibc::init_storage();
+        let (client_id, _client_state, mut writes) = ibc::prepare_client();
+        let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
+        writes.extend(conn_writes);
+        let (port_id, channel_id, channel_writes) =
+            ibc::prepare_opened_channel(&conn_id, false);
+        writes.extend(channel_writes);
+        // limit the outflow above the transferred amount
+        let token_hash = ibc_storage::calc_hash(token.to_string());
+        let limit_key =
+            ibc_storage::flow_limit_key(&port_id, &channel_id, token_hash);
+        let limit = IbcFlowLimit {
+            inflow: None,
+            outflow: Some(Amount::whole(1_000)),
+        };
+        writes.insert(limit_key, limit.try_to_vec().unwrap());
+        writes.into_iter().for_each(|(key, val)| {
+            tx_host_env::with(|env| {
+                env.wl_storage
+                    .storage
+                    .write(&key, &val)
+                    .expect("write error");
+            });
+        });
+
+        // Start a custom transaction which escrows the token for a transfer
+        // without recording its flow
+        let msg = ibc::msg_transfer(
+            port_id.clone(),
+            channel_id,
+            token.to_string(),
+            &sender,
+        );
+        let mut tx_data = vec![];
+        msg.clone()
+            .to_any()
+            .encode(&mut tx_data)
+            .expect("encoding failed");
+        let tx = Tx {
+            code: TxCode::Wasm(vec![]),
+            data: Some(tx_data),
+            timestamp: DateTimeUtc::now(),
+        }
+        .sign(&key::testing::keypair_1());
+        let amount: Amount = msg.token.unwrap().amount.parse().unwrap();
+        let key_prefix =
+            ibc_storage::ibc_account_prefix(&port_id, &channel_id, &token);
+        let escrow = token::multitoken_balance_key(
+            &key_prefix,
+            &address::Address::Internal(address::InternalAddress::IbcEscrow),
+        );
+        tx::ctx().write(&escrow, amount).unwrap();
+        let balance_key = token::balance_key(&token, &sender);
+        let balance: Amount = tx::ctx().read(&balance_key).unwrap().unwrap();
+        tx::ctx().write(&balance_key, balance - amount).unwrap();
+
+        // Check that the token VP requires the flow to be recorded
+        let env = tx_host_env::take();
+        let token_vp_result =
+            ibc::validate_token_vp_from_tx(&env, &tx, &escrow);
+        assert!(matches!(token_vp_result, Err(IbcTokenError::Flow(_))));
+    }
+
     #[test]
     fn test_ibc_burn_token() {
         // The environment must be initialized first
@@ -1331,7 +1725,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1399,7 +1793,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1425,7 +1819,7 @@ mod tests {
     }
 
     #[test]
-    fn test_ibc_unescrow_token() {
+    fn test_ibc_receive_token_over_flow_limit() {
         // The environment must be initialized first
         tx_host_env::init();
 
@@ -1437,6 +1831,17 @@ mod tests {
         let (port_id, channel_id, channel_writes) =
             ibc::prepare_opened_channel(&conn_id, false);
         writes.extend(channel_writes);
+        // limit the inflow of the origin-specific token below the received
+        // amount
+        let denom = format!("{}/{}/{}", port_id, channel_id, token);
+        let token_hash = ibc_storage::calc_hash(&denom);
+        let limit_key =
+            ibc_storage::flow_limit_key(&port_id, &channel_id, &token_hash);
+        let limit = IbcFlowLimit {
+            inflow: Some(Amount::from(99u64)),
+            outflow: None,
+        };
+        writes.insert(limit_key, limit.try_to_vec().unwrap());
         writes.into_iter().for_each(|(key, val)| {
             tx_host_env::with(|env| {
                 env.wl_storage
@@ -1445,75 +1850,73 @@ mod tests {
                     .expect("write error");
             });
         });
-        // escrow in advance
-        let key_prefix =
-            ibc_storage::ibc_account_prefix(&port_id, &channel_id, &token);
-        let escrow = token::multitoken_balance_key(
-            &key_prefix,
-            &address::Address::Internal(address::InternalAddress::IbcEscrow),
-        );
-        let val = Amount::from(1_000_000_000u64).try_to_vec().unwrap();
-        tx_host_env::with(|env| {
-            env.wl_storage
-                .storage
-                .write(&escrow, &val)
-                .expect("write error");
-        });
 
-        // Set this chain as the source zone
-        let counterparty = ibc::dummy_channel_counterparty();
-        let token = format!(
-            "{}/{}/{}",
-            counterparty.port_id().clone(),
-            counterparty.channel_id().unwrap().clone(),
-            token
-        );
         // packet
         let packet = ibc::received_packet(
-            port_id,
+            port_id.clone(),
             channel_id,
             ibc::sequence(1),
-            token,
+            token.to_string(),
             &receiver,
         );
 
         // Start a transaction to receive a packet
-        let msg = ibc::msg_packet_recv(packet);
+        let msg = ibc::msg_packet_recv(packet.clone());
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
         .sign(&key::testing::keypair_1());
-        // receive a packet with the message
+        // receiving the packet succeeds with an error acknowledgement
         tx::ctx()
             .dispatch_ibc_action(&tx_data)
             .expect("receiving a packet failed");
 
         // Check
+        let ack_key = ibc_storage::ack_key(
+            &packet.destination_port,
+            &packet.destination_channel,
+            packet.sequence,
+        );
+        let ack =
+            PacketAck::result_error("receiving a token failed".to_string())
+                .encode_to_vec();
+        let ack_commitment = tx::ctx().read_bytes(&ack_key).unwrap().unwrap();
+        assert_eq!(ack_commitment, Hash::sha256(&ack).0.to_vec());
+        // Check that neither the flow nor the denom were written
+        let flow_key =
+            ibc_storage::flow_key(&port_id, &channel_id, &token_hash);
+        assert!(!tx::ctx().has_key(&flow_key).unwrap());
+        let denom_key = ibc_storage::ibc_denom_key(&token_hash);
+        assert!(!tx::ctx().has_key(&denom_key).unwrap());
         let env = tx_host_env::take();
         let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
         assert!(result.expect("validation failed unexpectedly"));
-        // Check if the token was unescrowed
-        let result = ibc::validate_token_vp_from_tx(&env, &tx, &escrow);
-        assert!(result.expect("token validation failed unexpectedly"));
     }
 
     #[test]
-    fn test_ibc_send_packet_unordered() {
+    fn test_ica_host_execute_tx() {
         // The environment must be initialized first
         tx_host_env::init();
 
         // Set the initial state before starting transactions
-        let (token, sender) = ibc::init_storage();
+        let (token, receiver) = ibc::init_storage();
         let (client_id, _client_state, mut writes) = ibc::prepare_client();
         let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
         writes.extend(conn_writes);
-        let (port_id, channel_id, channel_writes) =
-            ibc::prepare_opened_channel(&conn_id, false);
+        let (port_id, channel_id, controller_port_id, channel_writes) =
+            ibc::prepare_opened_ica_channel(&conn_id, None);
         writes.extend(channel_writes);
+        // the interchain account has some tokens
+        let account =
+            ibc::interchain_account_address(&conn_id, &controller_port_id);
+        let key = token::balance_key(&token, &account);
+        let init_bal = Amount::from(1_000_000_000u64);
+        writes.insert(key, init_bal.try_to_vec().unwrap());
+
         writes.into_iter().for_each(|(key, val)| {
             tx_host_env::with(|env| {
                 env.wl_storage
@@ -1523,64 +1926,51 @@ mod tests {
             });
         });
 
-        // Start a transaction to send a packet
-        let msg =
-            ibc::msg_transfer(port_id, channel_id, token.to_string(), &sender);
-        let mut tx_data = vec![];
-        msg.clone()
-            .to_any()
-            .encode(&mut tx_data)
-            .expect("encoding failed");
-        let tx = Tx {
-            code: vec![],
-            data: Some(tx_data.clone()),
-            timestamp: DateTimeUtc::now(),
-        }
-        .sign(&key::testing::keypair_1());
-        // send a packet with the message
-        tx::ctx()
-            .dispatch_ibc_action(&tx_data)
-            .expect("sending a packet failed");
-
-        // the transaction does something before senging a packet
-
-        // Check
-        let mut env = tx_host_env::take();
-        let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
-        assert!(result.expect("validation failed unexpectedly"));
-
-        // Commit
-        env.commit_tx_and_block();
+        // packet to transfer the token from the interchain account
+        let amount = Amount::from(100u64);
+        let msgs = vec![InterchainAccountMsg::Transfer {
+            token: token.clone(),
+            sub_prefix: None,
+            target: receiver.clone(),
+            amount,
+        }];
+        let packet = ibc::ica_received_packet(
+            port_id,
+            channel_id,
+            ibc::sequence(1),
+            controller_port_id,
+            &msgs,
+        );
 
-        // Start the next transaction for receiving an ack
-        tx_host_env::set(env);
-        let counterparty = ibc::dummy_channel_counterparty();
-        let packet =
-            ibc::packet_from_message(&msg, ibc::sequence(1), &counterparty);
-        let msg = ibc::msg_packet_ack(packet);
+        // Start a transaction to receive a packet
+        let msg = ibc::msg_packet_recv(packet);
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
         .sign(&key::testing::keypair_1());
-        // ack the packet with the message
+        // receive a packet and execute the messages
         tx::ctx()
             .dispatch_ibc_action(&tx_data)
-            .expect("the packet ack failed");
-
-        // the transaction does something after the ack
+            .expect("receiving a packet failed");
 
         // Check
         let env = tx_host_env::take();
         let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
         assert!(result.expect("validation failed unexpectedly"));
+        let result = ibc::validate_ica_vp_from_tx(&env, &tx, &account);
+        assert!(result.expect("ICA validation failed unexpectedly"));
+        // Check if the token was transferred
+        let key = token::balance_key(&token, &account);
+        let balance: Option<Amount> = env.wl_storage.read(&key).unwrap();
+        assert_eq!(balance, Some(init_bal - amount));
     }
 
     #[test]
-    fn test_ibc_receive_packet_unordered() {
+    fn test_ica_host_redirect_transfer() {
         // The environment must be initialized first
         tx_host_env::init();
 
@@ -1589,9 +1979,16 @@ mod tests {
         let (client_id, _client_state, mut writes) = ibc::prepare_client();
         let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
         writes.extend(conn_writes);
-        let (port_id, channel_id, channel_writes) =
-            ibc::prepare_opened_channel(&conn_id, false);
+        let (port_id, channel_id, controller_port_id, channel_writes) =
+            ibc::prepare_opened_ica_channel(&conn_id, None);
         writes.extend(channel_writes);
+        // the interchain account has some tokens
+        let account =
+            ibc::interchain_account_address(&conn_id, &controller_port_id);
+        let key = token::balance_key(&token, &account);
+        let init_bal = Amount::from(1_000_000_000u64);
+        writes.insert(key, init_bal.try_to_vec().unwrap());
+
         writes.into_iter().for_each(|(key, val)| {
             tx_host_env::with(|env| {
                 env.wl_storage
@@ -1601,22 +1998,547 @@ mod tests {
             });
         });
 
-        // packet (sequence number isn't checked for the unordered channel)
-        let packet = ibc::received_packet(
-            port_id,
+        // packet to transfer the token from the interchain account
+        let amount = Amount::from(100u64);
+        let msgs = vec![InterchainAccountMsg::Transfer {
+            token: token.clone(),
+            sub_prefix: None,
+            target: receiver,
+            amount,
+        }];
+        let packet = ibc::ica_received_packet(
+            port_id.clone(),
             channel_id,
-            ibc::sequence(100),
-            token.to_string(),
-            &receiver,
+            ibc::sequence(1),
+            controller_port_id.clone(),
+            &msgs,
         );
-
-        // Start a transaction to receive a packet
         let msg = ibc::msg_packet_recv(packet);
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
-            data: Some(tx_data.clone()),
+            code: TxCode::Wasm(vec![]),
+            data: Some(tx_data),
+            timestamp: DateTimeUtc::now(),
+        }
+        .sign(&key::testing::keypair_1());
+
+        // Start a transaction executing the same packet, but transferring the
+        // token to another target
+        let msgs = vec![InterchainAccountMsg::Transfer {
+            token,
+            sub_prefix: None,
+            target: address::testing::established_address_2(),
+            amount,
+        }];
+        let packet = ibc::ica_received_packet(
+            port_id,
+            channel_id,
+            ibc::sequence(1),
+            controller_port_id,
+            &msgs,
+        );
+        let msg = ibc::msg_packet_recv(packet);
+        let mut redirect_tx_data = vec![];
+        msg.to_any()
+            .encode(&mut redirect_tx_data)
+            .expect("encoding failed");
+        tx::ctx()
+            .dispatch_ibc_action(&redirect_tx_data)
+            .expect("receiving a packet failed");
+
+        // Check
+        let env = tx_host_env::take();
+        let result = ibc::validate_ica_vp_from_tx(&env, &tx, &account);
+        assert!(matches!(
+            result.expect_err("validation succeeded unexpectedly"),
+            InterchainAccountError::InvalidAccount(_)
+        ));
+    }
+
+    /// Initialize PoS with two validators and an opened channel for an
+    /// interchain account which has some native tokens. Returns the account,
+    /// the validators, the port ID, the channel ID and the controller port ID.
+    fn prepare_ica_host_with_pos(
+    ) -> (Address, [Address; 2], PortId, ChannelId, PortId) {
+        let validators = [
+            address::testing::established_address_1(),
+            address::testing::established_address_2(),
+        ];
+        let genesis_validators =
+            validators.clone().map(|address| GenesisValidator {
+                address,
+                tokens: Amount::from(1_000_000_000u64),
+                consensus_key: key::testing::keypair_1().ref_to(),
+                commission_rate: rust_decimal::Decimal::new(5, 2),
+                max_commission_rate_change: rust_decimal::Decimal::new(1, 2),
+            });
+        init_pos(&genesis_validators, &PosParams::default(), Epoch(0));
+
+        // Set the initial state before starting transactions
+        ibc::init_storage();
+        let (client_id, _client_state, mut writes) = ibc::prepare_client();
+        let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
+        writes.extend(conn_writes);
+        let (port_id, channel_id, controller_port_id, channel_writes) =
+            ibc::prepare_opened_ica_channel(&conn_id, None);
+        writes.extend(channel_writes);
+        // the interchain account has some native tokens
+        let account =
+            ibc::interchain_account_address(&conn_id, &controller_port_id);
+        let native_token = tx_host_env::with(|env| {
+            env.wl_storage.storage.native_token.clone()
+        });
+        let key = token::balance_key(&native_token, &account);
+        let init_bal = Amount::from(1_000_000_000u64);
+        writes.insert(key, init_bal.try_to_vec().unwrap());
+
+        writes.into_iter().for_each(|(key, val)| {
+            tx_host_env::with(|env| {
+                env.wl_storage
+                    .storage
+                    .write(&key, &val)
+                    .expect("write error");
+            });
+        });
+        (account, validators, port_id, channel_id, controller_port_id)
+    }
+
+    #[test]
+    fn test_ica_host_bond() {
+        let (account, validators, port_id, channel_id, controller_port_id) =
+            prepare_ica_host_with_pos();
+
+        // packet to bond the token from the interchain account
+        let msgs = vec![InterchainAccountMsg::Bond {
+            validator: validators[0].clone(),
+            amount: Amount::from(100u64),
+        }];
+        let packet = ibc::ica_received_packet(
+            port_id,
+            channel_id,
+            ibc::sequence(1),
+            controller_port_id,
+            &msgs,
+        );
+
+        // Start a transaction to receive a packet
+        let msg = ibc::msg_packet_recv(packet);
+        let mut tx_data = vec![];
+        msg.to_any().encode(&mut tx_data).expect("encoding failed");
+        let tx = Tx {
+            code: TxCode::Wasm(vec![]),
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        }
+        .sign(&key::testing::keypair_1());
+        // receive a packet and execute the messages
+        tx::ctx()
+            .dispatch_ibc_action(&tx_data)
+            .expect("receiving a packet failed");
+
+        // Check
+        let env = tx_host_env::take();
+        let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
+        assert!(result.expect("validation failed unexpectedly"));
+        let result = ibc::validate_ica_vp_from_tx(&env, &tx, &account);
+        assert!(result.expect("ICA validation failed unexpectedly"));
+    }
+
+    #[test]
+    fn test_ica_host_redirect_bond() {
+        let (account, validators, port_id, channel_id, controller_port_id) =
+            prepare_ica_host_with_pos();
+
+        // packet to bond the token from the interchain account
+        let amount = Amount::from(100u64);
+        let msgs = vec![InterchainAccountMsg::Bond {
+            validator: validators[0].clone(),
+            amount,
+        }];
+        let packet = ibc::ica_received_packet(
+            port_id.clone(),
+            channel_id,
+            ibc::sequence(1),
+            controller_port_id.clone(),
+            &msgs,
+        );
+        let msg = ibc::msg_packet_recv(packet);
+        let mut tx_data = vec![];
+        msg.to_any().encode(&mut tx_data).expect("encoding failed");
+        let tx = Tx {
+            code: TxCode::Wasm(vec![]),
+            data: Some(tx_data),
+            timestamp: DateTimeUtc::now(),
+        }
+        .sign(&key::testing::keypair_1());
+
+        // Start a transaction executing the same packet, but bonding the token
+        // to another validator
+        let msgs = vec![InterchainAccountMsg::Bond {
+            validator: validators[1].clone(),
+            amount,
+        }];
+        let packet = ibc::ica_received_packet(
+            port_id,
+            channel_id,
+            ibc::sequence(1),
+            controller_port_id,
+            &msgs,
+        );
+        let msg = ibc::msg_packet_recv(packet);
+        let mut redirect_tx_data = vec![];
+        msg.to_any()
+            .encode(&mut redirect_tx_data)
+            .expect("encoding failed");
+        tx::ctx()
+            .dispatch_ibc_action(&redirect_tx_data)
+            .expect("receiving a packet failed");
+
+        // Check
+        let env = tx_host_env::take();
+        let result = ibc::validate_ica_vp_from_tx(&env, &tx, &account);
+        assert!(matches!(
+            result.expect_err("validation succeeded unexpectedly"),
+            InterchainAccountError::InvalidAccount(_)
+        ));
+    }
+
+    #[test]
+    fn test_ica_host_execute_tx_failure() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Set the initial state before starting transactions
+        let (token, receiver) = ibc::init_storage();
+        let (client_id, _client_state, mut writes) = ibc::prepare_client();
+        let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
+        writes.extend(conn_writes);
+        let (port_id, channel_id, controller_port_id, channel_writes) =
+            ibc::prepare_opened_ica_channel(&conn_id, None);
+        writes.extend(channel_writes);
+        // the interchain account has some tokens
+        let account =
+            ibc::interchain_account_address(&conn_id, &controller_port_id);
+        let key = token::balance_key(&token, &account);
+        let init_bal = Amount::from(150u64);
+        writes.insert(key, init_bal.try_to_vec().unwrap());
+
+        writes.into_iter().for_each(|(key, val)| {
+            tx_host_env::with(|env| {
+                env.wl_storage
+                    .storage
+                    .write(&key, &val)
+                    .expect("write error");
+            });
+        });
+
+        // packet to transfer more than the balance in total
+        let amount = Amount::from(100u64);
+        let msg = InterchainAccountMsg::Transfer {
+            token: token.clone(),
+            sub_prefix: None,
+            target: receiver,
+            amount,
+        };
+        let msgs = vec![msg.clone(), msg];
+        let packet = ibc::ica_received_packet(
+            port_id,
+            channel_id,
+            ibc::sequence(1),
+            controller_port_id,
+            &msgs,
+        );
+
+        // Start a transaction to receive a packet
+        let msg = ibc::msg_packet_recv(packet.clone());
+        let mut tx_data = vec![];
+        msg.to_any().encode(&mut tx_data).expect("encoding failed");
+        let tx = Tx {
+            code: TxCode::Wasm(vec![]),
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        }
+        .sign(&key::testing::keypair_1());
+        // receiving the packet succeeds with an error acknowledgement
+        tx::ctx()
+            .dispatch_ibc_action(&tx_data)
+            .expect("receiving a packet failed");
+
+        // Check
+        let ack_key = ibc_storage::ack_key(
+            &packet.destination_port,
+            &packet.destination_channel,
+            packet.sequence,
+        );
+        let ack = PacketAck::result_error(
+            "executing the messages failed".to_string(),
+        )
+        .encode_to_vec();
+        let ack_commitment = tx::ctx().read_bytes(&ack_key).unwrap().unwrap();
+        assert_eq!(ack_commitment, Hash::sha256(&ack).0.to_vec());
+        let env = tx_host_env::take();
+        let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
+        assert!(result.expect("validation failed unexpectedly"));
+        // Check that no message was executed
+        let key = token::balance_key(&token, &account);
+        let balance: Option<Amount> = env.wl_storage.read(&key).unwrap();
+        assert_eq!(balance, Some(init_bal));
+    }
+
+    #[test]
+    fn test_ica_controller_send_tx() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Set the initial state before starting transactions
+        let (_token, owner) = ibc::init_storage();
+        let (client_id, _client_state, mut writes) = ibc::prepare_client();
+        let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
+        writes.extend(conn_writes);
+        let (_port_id, _channel_id, _controller_port_id, channel_writes) =
+            ibc::prepare_opened_ica_channel(&conn_id, Some(&owner));
+        writes.extend(channel_writes);
+        // the owner's public key to verify the signature
+        let owner_keypair = key::testing::keypair_1();
+        let key = key::pk_key(&owner);
+        writes.insert(key, owner_keypair.ref_to().try_to_vec().unwrap());
+
+        writes.into_iter().for_each(|(key, val)| {
+            tx_host_env::with(|env| {
+                env.wl_storage
+                    .storage
+                    .write(&key, &val)
+                    .expect("write error");
+            });
+        });
+
+        // Start a transaction to send a packet to the interchain account
+        let msg = MsgSendIcaTx {
+            owner: owner.clone(),
+            connection_id: conn_id.to_string(),
+            data: b"cosmos tx".to_vec(),
+            memo: String::new(),
+            timeout_revision_number: 1,
+            timeout_revision_height: 100,
+            timeout_timestamp: 0,
+        };
+        let mut tx_data = vec![];
+        msg.to_any().encode(&mut tx_data).expect("encoding failed");
+        let tx = Tx {
+            code: TxCode::Wasm(vec![]),
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        };
+        // send a packet with the message
+        tx::ctx()
+            .dispatch_ibc_action(&tx_data)
+            .expect("sending a packet failed");
+
+        // Check
+        let env = tx_host_env::take();
+        let signed_tx = tx.clone().sign(&owner_keypair);
+        let result = ibc::validate_ibc_vp_from_tx(&env, &signed_tx);
+        assert!(result.expect("validation failed unexpectedly"));
+        // the transaction should be signed by the owner
+        let signed_tx = tx.sign(&key::testing::keypair_2());
+        let result = ibc::validate_ibc_vp_from_tx(&env, &signed_tx);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_ibc_unescrow_token() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Set the initial state before starting transactions
+        let (token, receiver) = ibc::init_storage();
+        let (client_id, _client_state, mut writes) = ibc::prepare_client();
+        let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
+        writes.extend(conn_writes);
+        let (port_id, channel_id, channel_writes) =
+            ibc::prepare_opened_channel(&conn_id, false);
+        writes.extend(channel_writes);
+        writes.into_iter().for_each(|(key, val)| {
+            tx_host_env::with(|env| {
+                env.wl_storage
+                    .storage
+                    .write(&key, &val)
+                    .expect("write error");
+            });
+        });
+        // escrow in advance
+        let key_prefix =
+            ibc_storage::ibc_account_prefix(&port_id, &channel_id, &token);
+        let escrow = token::multitoken_balance_key(
+            &key_prefix,
+            &address::Address::Internal(address::InternalAddress::IbcEscrow),
+        );
+        let val = Amount::from(1_000_000_000u64).try_to_vec().unwrap();
+        tx_host_env::with(|env| {
+            env.wl_storage
+                .storage
+                .write(&escrow, &val)
+                .expect("write error");
+        });
+
+        // Set this chain as the source zone
+        let counterparty = ibc::dummy_channel_counterparty();
+        let token = format!(
+            "{}/{}/{}",
+            counterparty.port_id().clone(),
+            counterparty.channel_id().unwrap().clone(),
+            token
+        );
+        // packet
+        let packet = ibc::received_packet(
+            port_id,
+            channel_id,
+            ibc::sequence(1),
+            token,
+            &receiver,
+        );
+
+        // Start a transaction to receive a packet
+        let msg = ibc::msg_packet_recv(packet);
+        let mut tx_data = vec![];
+        msg.to_any().encode(&mut tx_data).expect("encoding failed");
+        let tx = Tx {
+            code: TxCode::Wasm(vec![]),
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        }
+        .sign(&key::testing::keypair_1());
+        // receive a packet with the message
+        tx::ctx()
+            .dispatch_ibc_action(&tx_data)
+            .expect("receiving a packet failed");
+
+        // Check
+        let env = tx_host_env::take();
+        let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
+        assert!(result.expect("validation failed unexpectedly"));
+        // Check if the token was unescrowed
+        let result = ibc::validate_token_vp_from_tx(&env, &tx, &escrow);
+        assert!(result.expect("token validation failed unexpectedly"));
+    }
+
+    #[test]
+    fn test_ibc_send_packet_unordered() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Set the initial state before starting transactions
+        let (token, sender) = ibc::init_storage();
+        let (client_id, _client_state, mut writes) = ibc::prepare_client();
+        let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
+        writes.extend(conn_writes);
+        let (port_id, channel_id, channel_writes) =
+            ibc::prepare_opened_channel(&conn_id, false);
+        writes.extend(channel_writes);
+        writes.into_iter().for_each(|(key, val)| {
+            tx_host_env::with(|env| {
+                env.wl_storage
+                    .storage
+                    .write(&key, &val)
+                    .expect("write error");
+            });
+        });
+
+        // Start a transaction to send a packet
+        let msg =
+            ibc::msg_transfer(port_id, channel_id, token.to_string(), &sender);
+        let mut tx_data = vec![];
+        msg.clone()
+            .to_any()
+            .encode(&mut tx_data)
+            .expect("encoding failed");
+        let tx = Tx {
+            code: TxCode::Wasm(vec![]),
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        }
+        .sign(&key::testing::keypair_1());
+        // send a packet with the message
+        tx::ctx()
+            .dispatch_ibc_action(&tx_data)
+            .expect("sending a packet failed");
+
+        // the transaction does something before senging a packet
+
+        // Check
+        let mut env = tx_host_env::take();
+        let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
+        assert!(result.expect("validation failed unexpectedly"));
+
+        // Commit
+        env.commit_tx_and_block();
+
+        // Start the next transaction for receiving an ack
+        tx_host_env::set(env);
+        let counterparty = ibc::dummy_channel_counterparty();
+        let packet =
+            ibc::packet_from_message(&msg, ibc::sequence(1), &counterparty);
+        let msg = ibc::msg_packet_ack(packet);
+        let mut tx_data = vec![];
+        msg.to_any().encode(&mut tx_data).expect("encoding failed");
+        let tx = Tx {
+            code: TxCode::Wasm(vec![]),
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        }
+        .sign(&key::testing::keypair_1());
+        // ack the packet with the message
+        tx::ctx()
+            .dispatch_ibc_action(&tx_data)
+            .expect("the packet ack failed");
+
+        // the transaction does something after the ack
+
+        // Check
+        let env = tx_host_env::take();
+        let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
+        assert!(result.expect("validation failed unexpectedly"));
+    }
+
+    #[test]
+    fn test_ibc_receive_packet_unordered() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Set the initial state before starting transactions
+        let (token, receiver) = ibc::init_storage();
+        let (client_id, _client_state, mut writes) = ibc::prepare_client();
+        let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
+        writes.extend(conn_writes);
+        let (port_id, channel_id, channel_writes) =
+            ibc::prepare_opened_channel(&conn_id, false);
+        writes.extend(channel_writes);
+        writes.into_iter().for_each(|(key, val)| {
+            tx_host_env::with(|env| {
+                env.wl_storage
+                    .storage
+                    .write(&key, &val)
+                    .expect("write error");
+            });
+        });
+
+        // packet (sequence number isn't checked for the unordered channel)
+        let packet = ibc::received_packet(
+            port_id,
+            channel_id,
+            ibc::sequence(100),
+            token.to_string(),
+            &receiver,
+        );
+
+        // Start a transaction to receive a packet
+        let msg = ibc::msg_packet_recv(packet);
+        let mut tx_data = vec![];
+        msg.to_any().encode(&mut tx_data).expect("encoding failed");
+        let tx = Tx {
+            code: TxCode::Wasm(vec![]),
+            data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
         .sign(&key::testing::keypair_1());
@@ -1680,7 +2602,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1755,7 +2677,7 @@ mod tests {
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
         let tx = Tx {
-            code: vec![],
+            code: TxCode::Wasm(vec![]),
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
         }
@@ -1783,4 +2705,147 @@ mod tests {
         let result = ibc::validate_token_vp_from_tx(&env, &tx, &escrow);
         assert!(result.expect("token validation failed unexpectedly"));
     }
+
+    /// Open a channel whose outflow of the token is limited, send the token
+    /// over it and check that the outflow is recorded. Returns the transfer
+    /// message, the token and the key of the flow.
+    fn send_token_with_outflow_limit(
+        ordered: bool,
+    ) -> (MsgTransfer, Address, Key) {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Set the initial state before starting transactions
+        let (token, sender) = ibc::init_storage();
+        let (client_id, _client_state, mut writes) = ibc::prepare_client();
+        let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
+        writes.extend(conn_writes);
+        let (port_id, channel_id, channel_writes) =
+            ibc::prepare_opened_channel(&conn_id, ordered);
+        writes.extend(channel_writes);
+        let token_hash = ibc_storage::calc_hash(token.to_string());
+        let flow_key =
+            ibc_storage::flow_key(&port_id, &channel_id, &token_hash);
+        let limit_key =
+            ibc_storage::flow_limit_key(&port_id, &channel_id, &token_hash);
+        let limit = IbcFlowLimit {
+            inflow: None,
+            outflow: Some(Amount::whole(1_000)),
+        };
+        writes.insert(limit_key, limit.try_to_vec().unwrap());
+        writes.into_iter().for_each(|(key, val)| {
+            tx_host_env::with(|env| {
+                env.wl_storage
+                    .storage
+                    .write(&key, &val)
+                    .expect("write error");
+            })
+        });
+
+        // Start a transaction to send a packet
+        let mut msg =
+            ibc::msg_transfer(port_id, channel_id, token.to_string(), &sender);
+        ibc::set_timeout_timestamp(&mut msg);
+        let mut tx_data = vec![];
+        msg.clone()
+            .to_any()
+            .encode(&mut tx_data)
+            .expect("encoding failed");
+        // send a packet with the message
+        tx::ctx()
+            .dispatch_ibc_action(&tx_data)
+            .expect("sending a packet failed");
+        let amount: Amount = msg.token.clone().unwrap().amount.parse().unwrap();
+        let flow: IbcFlow = tx::ctx().read(&flow_key).unwrap().unwrap();
+        assert_eq!(flow.outflow, amount);
+
+        // Commit
+        tx_host_env::commit_tx_and_block();
+
+        (msg, token, flow_key)
+    }
+
+    #[test]
+    fn test_ibc_packet_timeout_refunds_outflow() {
+        let (msg, token, flow_key) = send_token_with_outflow_limit(true);
+
+        // Start a transaction to notify the timeout
+        let counterparty = ibc::dummy_channel_counterparty();
+        let packet =
+            ibc::packet_from_message(&msg, ibc::sequence(1), &counterparty);
+        let msg = ibc::msg_timeout(packet.clone(), ibc::sequence(1));
+        let mut tx_data = vec![];
+        msg.to_any().encode(&mut tx_data).expect("encoding failed");
+        let tx = Tx {
+            code: TxCode::Wasm(vec![]),
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        }
+        .sign(&key::testing::keypair_1());
+        // refund the token with the message
+        tx::ctx()
+            .dispatch_ibc_action(&tx_data)
+            .expect("the timeout failed");
+        // the refunded amount isn't counted in the outflow anymore
+        let flow: IbcFlow = tx::ctx().read(&flow_key).unwrap().unwrap();
+        assert_eq!(flow.outflow, Amount::default());
+
+        // Check
+        let env = tx_host_env::take();
+        let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
+        assert!(result.expect("validation failed unexpectedly"));
+        let key_prefix = ibc_storage::ibc_account_prefix(
+            &packet.source_port,
+            &packet.source_channel,
+            &token,
+        );
+        let escrow = token::multitoken_balance_key(
+            &key_prefix,
+            &address::Address::Internal(address::InternalAddress::IbcEscrow),
+        );
+        let result = ibc::validate_token_vp_from_tx(&env, &tx, &escrow);
+        assert!(result.expect("token validation failed unexpectedly"));
+    }
+
+    #[test]
+    fn test_ibc_packet_error_ack_refunds_outflow() {
+        let (msg, token, flow_key) = send_token_with_outflow_limit(false);
+
+        // Start a transaction to receive an error ack
+        let counterparty = ibc::dummy_channel_counterparty();
+        let packet =
+            ibc::packet_from_message(&msg, ibc::sequence(1), &counterparty);
+        let msg = ibc::msg_packet_ack_error(packet.clone());
+        let mut tx_data = vec![];
+        msg.to_any().encode(&mut tx_data).expect("encoding failed");
+        let tx = Tx {
+            code: TxCode::Wasm(vec![]),
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        }
+        .sign(&key::testing::keypair_1());
+        // refund the token with the message
+        tx::ctx()
+            .dispatch_ibc_action(&tx_data)
+            .expect("the packet ack failed");
+        // the refunded amount isn't counted in the outflow anymore
+        let flow: IbcFlow = tx::ctx().read(&flow_key).unwrap().unwrap();
+        assert_eq!(flow.outflow, Amount::default());
+
+        // Check
+        let env = tx_host_env::take();
+        let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
+        assert!(result.expect("validation failed unexpectedly"));
+        let key_prefix = ibc_storage::ibc_account_prefix(
+            &packet.source_port,
+            &packet.source_channel,
+            &token,
+        );
+        let escrow = token::multitoken_balance_key(
+            &key_prefix,
+            &address::Address::Internal(address::InternalAddress::IbcEscrow),
+        );
+        let result = ibc::validate_token_vp_from_tx(&env, &tx, &escrow);
+        assert!(result.expect("token validation failed unexpectedly"));
+    }
 }
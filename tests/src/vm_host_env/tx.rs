@@ -7,7 +7,7 @@ use namada::ledger::storage::mockdb::MockDB;
 use namada::ledger::storage::testing::TestStorage;
 use namada::ledger::storage::write_log::WriteLog;
 use namada::ledger::storage::{Sha256Hasher, WlStorage};
-use namada::proto::Tx;
+use namada::proto::{Tx, TxCode};
 use namada::types::address::Address;
 use namada::types::storage::{Key, TxIndex};
 use namada::types::time::DurationSecs;
@@ -200,12 +200,22 @@ impl TestTxEnv {
     /// Apply the tx changes to the write log.
     pub fn execute_tx(&mut self) -> Result<(), Error> {
         let empty_data = vec![];
+        let code = match &self.tx.code {
+            TxCode::Wasm(code) => code,
+            TxCode::Hash(_) => unimplemented!(
+                "Executing a tx code referred to by its hash isn't supported \
+                 in the test environment"
+            ),
+            TxCode::Batch(_) => unimplemented!(
+                "Executing a batch tx isn't supported in the test environment"
+            ),
+        };
         wasm::run::tx(
             &self.wl_storage.storage,
             &mut self.wl_storage.write_log,
             &mut self.gas_meter,
             &self.tx_index,
-            &self.tx.code,
+            code,
             self.tx.data.as_ref().unwrap_or(&empty_data),
             &mut self.vp_wasm_cache,
             &mut self.tx_wasm_cache,
@@ -424,12 +434,28 @@ mod native_tx_host_env {
         result_ptr: u64
     ));
     native_host_fn!(tx_emit_ibc_event(event_ptr: u64, event_len: u64));
+    native_host_fn!(tx_emit_event(event_ptr: u64, event_len: u64));
     native_host_fn!(tx_get_chain_id(result_ptr: u64));
     native_host_fn!(tx_get_block_height() -> u64);
     native_host_fn!(tx_get_tx_index() -> u32);
     native_host_fn!(tx_get_block_time() -> i64);
     native_host_fn!(tx_get_block_hash(result_ptr: u64));
+    native_host_fn!(tx_get_block_header(height: u64) -> i64);
     native_host_fn!(tx_get_block_epoch() -> u64);
     native_host_fn!(tx_get_native_token(result_ptr: u64));
+    native_host_fn!(tx_hash(
+        algo: u64,
+        data_ptr: u64,
+        data_len: u64,
+        result_ptr: u64
+    ));
+    native_host_fn!(tx_verify_signature(
+        pk_ptr: u64,
+        pk_len: u64,
+        data_ptr: u64,
+        data_len: u64,
+        sig_ptr: u64,
+        sig_len: u64,
+    ) -> i64);
     native_host_fn!(tx_log_string(str_ptr: u64, str_len: u64));
 }
@@ -239,6 +239,19 @@ mod native_vp_host_env {
                  the `eval` function."
             )
         }
+
+        fn query(
+            &self,
+            _ctx: VpCtx<'static, Self::Db, Self::H, Self::Eval, Self::CA>,
+            _address: &Address,
+            _vp_code: Vec<u8>,
+            _input_data: Vec<u8>,
+        ) -> Option<Vec<u8>> {
+            unimplemented!(
+                "The \"wasm-runtime\" feature must be enabled to test with \
+                 the `query` function."
+            )
+        }
     }
 
     /// A helper macro to create implementations of the host environment
@@ -354,6 +367,9 @@ mod native_vp_host_env {
     native_host_fn!(vp_get_block_height() -> u64);
     native_host_fn!(vp_get_tx_index() -> u32);
     native_host_fn!(vp_get_block_hash(result_ptr: u64));
+    native_host_fn!(vp_get_block_header(height: u64) -> i64);
+    native_host_fn!(vp_get_block_time() -> i64);
+    native_host_fn!(vp_get_block_proposer() -> i64);
     native_host_fn!(vp_get_tx_code_hash(result_ptr: u64));
     native_host_fn!(vp_get_block_epoch() -> u64);
     native_host_fn!(vp_get_native_token(result_ptr: u64));
@@ -363,12 +379,33 @@ mod native_vp_host_env {
             sig_ptr: u64,
             sig_len: u64,
         ) -> i64);
+    native_host_fn!(vp_verify_signature(
+            pk_ptr: u64,
+            pk_len: u64,
+            data_ptr: u64,
+            data_len: u64,
+            sig_ptr: u64,
+            sig_len: u64,
+        ) -> i64);
+    native_host_fn!(vp_hash(
+        algo: u64,
+        data_ptr: u64,
+        data_len: u64,
+        result_ptr: u64
+    ));
     native_host_fn!(vp_eval(
             vp_code_ptr: u64,
             vp_code_len: u64,
             input_data_ptr: u64,
             input_data_len: u64,
         ) -> i64);
+    native_host_fn!(vp_query(
+            addr_ptr: u64,
+            addr_len: u64,
+            input_data_ptr: u64,
+            input_data_len: u64,
+        ) -> i64);
+    native_host_fn!(vp_set_query_result(result_ptr: u64, result_len: u64));
     native_host_fn!(vp_has_valid_pow() -> i64);
     native_host_fn!(vp_log_string(str_ptr: u64, str_len: u64));
 }
@@ -1,14 +1,20 @@
 //! IBC lower-level functions for transactions.
 
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
 pub use namada_core::ledger::ibc::actions::{Error, IbcActions, Result};
+use namada_core::ledger::ibc::data::InterchainAccountMsg;
 use namada_core::ledger::storage_api::{StorageRead, StorageWrite};
 use namada_core::ledger::tx_env::TxEnv;
+use namada_core::types::address::Address;
 pub use namada_core::types::ibc::IbcEvent;
-use namada_core::types::storage::{BlockHeight, Key};
+use namada_core::types::storage::{BlockHeight, Epoch, Key};
 use namada_core::types::time::Rfc3339String;
-use namada_core::types::token::Amount;
+use namada_core::types::token::{self, Amount, Change};
+use namada_proof_of_stake::{check_bond, check_unbond};
 
-use crate::token::transfer_with_keys;
+use crate::token::{transfer, transfer_with_keys};
 use crate::Ctx;
 
 impl IbcActions for Ctx {
@@ -68,4 +74,120 @@ impl IbcActions for Ctx {
         let val = self.get_block_time()?;
         Ok(val)
     }
+
+    fn get_epoch(&self) -> std::result::Result<Epoch, Self::Error> {
+        let val = self.get_block_epoch()?;
+        Ok(val)
+    }
+
+    fn check_ica_msgs(
+        &self,
+        account: &Address,
+        msgs: &[InterchainAccountMsg],
+    ) -> std::result::Result<bool, Self::Error> {
+        let current_epoch = self.get_block_epoch()?;
+        let native_token = self.get_native_token()?;
+        // the balances of the account and the bonds to each validator updated
+        // by the previous messages
+        let mut balances: HashMap<Key, Amount> = HashMap::new();
+        let mut bond_changes: HashMap<Address, Change> = HashMap::new();
+        for msg in msgs {
+            let (key, amount) = match msg {
+                InterchainAccountMsg::Transfer {
+                    token,
+                    sub_prefix,
+                    amount,
+                    ..
+                } => {
+                    let key = match sub_prefix {
+                        Some(sub_prefix) => {
+                            let prefix = token::multitoken_balance_prefix(
+                                token, sub_prefix,
+                            );
+                            token::multitoken_balance_key(&prefix, account)
+                        }
+                        None => token::balance_key(token, account),
+                    };
+                    (key, *amount)
+                }
+                InterchainAccountMsg::Bond { validator, amount } => {
+                    if check_bond(self, Some(account), validator, current_epoch)
+                        .is_err()
+                    {
+                        return Ok(false);
+                    }
+                    *bond_changes.entry(validator.clone()).or_default() +=
+                        amount.change();
+                    (token::balance_key(&native_token, account), *amount)
+                }
+                InterchainAccountMsg::Unbond { validator, amount } => {
+                    let change =
+                        bond_changes.entry(validator.clone()).or_default();
+                    *change -= amount.change();
+                    // the tokens bonded by the previous messages can be
+                    // unbonded too
+                    let to_unbond = Amount::from_change((-*change).max(0));
+                    if check_unbond(
+                        self,
+                        Some(account),
+                        validator,
+                        to_unbond,
+                        current_epoch,
+                    )
+                    .is_err()
+                    {
+                        return Ok(false);
+                    }
+                    continue;
+                }
+                InterchainAccountMsg::Withdraw { .. } => continue,
+            };
+            let balance = match balances.entry(key) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let balance = self.read(entry.key())?.unwrap_or_default();
+                    entry.insert(balance)
+                }
+            };
+            match balance.checked_sub(amount) {
+                Some(remaining) => *balance = remaining,
+                None => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+
+    fn execute_ica_msg(
+        &mut self,
+        account: &Address,
+        msg: &InterchainAccountMsg,
+    ) -> std::result::Result<(), Self::Error> {
+        match msg {
+            InterchainAccountMsg::Transfer {
+                token,
+                sub_prefix,
+                target,
+                amount,
+            } => transfer(
+                self,
+                account,
+                target,
+                token,
+                sub_prefix.clone(),
+                *amount,
+                &None,
+                &None,
+            ),
+            InterchainAccountMsg::Bond { validator, amount } => {
+                self.bond_tokens(Some(account), validator, *amount)
+            }
+            InterchainAccountMsg::Unbond { validator, amount } => {
+                self.unbond_tokens(Some(account), validator, *amount)
+            }
+            InterchainAccountMsg::Withdraw { validator } => {
+                self.withdraw_tokens(Some(account), validator)?;
+                Ok(())
+            }
+        }
+    }
 }
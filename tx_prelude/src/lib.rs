@@ -27,11 +27,14 @@ pub use namada_core::ledger::tx_env::TxEnv;
 pub use namada_core::proto::{Signed, SignedTxData};
 pub use namada_core::types::address::Address;
 use namada_core::types::chain::CHAIN_ID_LENGTH;
+pub use namada_core::types::hash::HashAlgorithm;
+use namada_core::types::hash::{Hash, HASH_LENGTH};
 use namada_core::types::internal::HostEnvResult;
-use namada_core::types::storage::TxIndex;
+use namada_core::types::key::common;
 pub use namada_core::types::storage::{
     self, BlockHash, BlockHeight, Epoch, BLOCK_HASH_LENGTH,
 };
+use namada_core::types::storage::{Header, TxIndex};
 use namada_core::types::time::Rfc3339String;
 pub use namada_core::types::*;
 pub use namada_macros::transaction;
@@ -48,6 +51,46 @@ pub fn log_string<T: AsRef<str>>(msg: T) {
     }
 }
 
+/// Hash the given data with the given hash function. The hash is computed by
+/// the host, which is much cheaper than hashing inside the wasm.
+pub fn hash_data(algo: HashAlgorithm, data: impl AsRef<[u8]>) -> Hash {
+    let data = data.as_ref();
+    let result = Vec::with_capacity(HASH_LENGTH);
+    unsafe {
+        namada_tx_hash(
+            u64::from(algo),
+            data.as_ptr() as _,
+            data.len() as _,
+            result.as_ptr() as _,
+        );
+    }
+    let slice = unsafe { slice::from_raw_parts(result.as_ptr(), HASH_LENGTH) };
+    Hash::try_from(slice).expect("Cannot convert the hash")
+}
+
+/// Verify a signature over the given data. A secp256k1 signature is expected
+/// to be made over the SHA-256 digest of the data.
+pub fn verify_signature(
+    pk: &common::PublicKey,
+    data: impl AsRef<[u8]>,
+    sig: &common::Signature,
+) -> bool {
+    let pk = BorshSerialize::try_to_vec(pk).unwrap();
+    let data = data.as_ref();
+    let sig = BorshSerialize::try_to_vec(sig).unwrap();
+    let valid = unsafe {
+        namada_tx_verify_signature(
+            pk.as_ptr() as _,
+            pk.len() as _,
+            data.as_ptr() as _,
+            data.len() as _,
+            sig.as_ptr() as _,
+            sig.len() as _,
+        )
+    };
+    HostEnvResult::is_success(valid)
+}
+
 /// Format and log a string in a debug build.
 ///
 /// In WASM target debug build, the message will be printed at the
@@ -162,6 +205,19 @@ impl StorageRead for Ctx {
         Ok(Epoch(unsafe { namada_tx_get_block_epoch() }))
     }
 
+    fn get_block_header(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<Header>, Error> {
+        let read_result = unsafe { namada_tx_get_block_header(height.0) };
+        match read_from_buffer(read_result, namada_tx_result_buffer) {
+            Some(value) => Header::try_from_slice(&value[..])
+                .into_storage_result()
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
     /// Get the native token address
     fn get_native_token(&self) -> Result<Address, Error> {
         let result = Vec::with_capacity(address::ADDRESS_LEN);
@@ -322,4 +378,10 @@ impl TxEnv for Ctx {
         };
         Ok(())
     }
+
+    fn emit_event(&mut self, event: &event::TxEvent) -> Result<(), Error> {
+        let event = BorshSerialize::try_to_vec(event).unwrap();
+        unsafe { namada_tx_emit_event(event.as_ptr() as _, event.len() as _) };
+        Ok(())
+    }
 }
@@ -78,6 +78,9 @@ pub mod tx {
         // Emit an IBC event
         pub fn namada_tx_emit_ibc_event(event_ptr: u64, event_len: u64);
 
+        // Emit a custom event
+        pub fn namada_tx_emit_event(event_ptr: u64, event_len: u64);
+
         // Get the chain ID
         pub fn namada_tx_get_chain_id(result_ptr: u64);
 
@@ -90,6 +93,9 @@ pub mod tx {
         // Get the current block hash
         pub fn namada_tx_get_block_hash(result_ptr: u64);
 
+        // Get the block header at the given height
+        pub fn namada_tx_get_block_header(height: u64) -> i64;
+
         // Get the current block epoch
         pub fn namada_tx_get_block_epoch() -> u64;
 
@@ -99,6 +105,24 @@ pub mod tx {
         // Get the native token address
         pub fn namada_tx_get_native_token(result_ptr: u64);
 
+        // Hash the data with the given algorithm into the 32 bytes result
+        pub fn namada_tx_hash(
+            algo: u64,
+            data_ptr: u64,
+            data_len: u64,
+            result_ptr: u64,
+        );
+
+        // Verify a signature over arbitrary data
+        pub fn namada_tx_verify_signature(
+            pk_ptr: u64,
+            pk_len: u64,
+            data_ptr: u64,
+            data_len: u64,
+            sig_ptr: u64,
+            sig_len: u64,
+        ) -> i64;
+
         // Requires a node running with "Info" log level
         pub fn namada_tx_log_string(str_ptr: u64, str_len: u64);
     }
@@ -169,6 +193,15 @@ pub mod vp {
         // Get the current block hash
         pub fn namada_vp_get_block_hash(result_ptr: u64);
 
+        // Get the block header at the given height
+        pub fn namada_vp_get_block_header(height: u64) -> i64;
+
+        // Get the current block time
+        pub fn namada_vp_get_block_time() -> i64;
+
+        // Get the current block proposer
+        pub fn namada_vp_get_block_proposer() -> i64;
+
         // Get the current tx hash
         pub fn namada_vp_get_tx_code_hash(result_ptr: u64);
 
@@ -189,6 +222,24 @@ pub mod vp {
             sig_len: u64,
         ) -> i64;
 
+        // Verify a signature over arbitrary data
+        pub fn namada_vp_verify_signature(
+            pk_ptr: u64,
+            pk_len: u64,
+            data_ptr: u64,
+            data_len: u64,
+            sig_ptr: u64,
+            sig_len: u64,
+        ) -> i64;
+
+        // Hash the data with the given algorithm into the 32 bytes result
+        pub fn namada_vp_hash(
+            algo: u64,
+            data_ptr: u64,
+            data_len: u64,
+            result_ptr: u64,
+        );
+
         // Requires a node running with "Info" log level
         pub fn namada_vp_log_string(str_ptr: u64, str_len: u64);
 
@@ -199,6 +250,18 @@ pub mod vp {
             input_data_len: u64,
         ) -> i64;
 
+        // Query the VP of the given account. Returns the length of the result
+        // in the result buffer or `-1` if the query failed.
+        pub fn namada_vp_query(
+            addr_ptr: u64,
+            addr_len: u64,
+            input_data_ptr: u64,
+            input_data_len: u64,
+        ) -> i64;
+
+        // Set the result of the VP query entrypoint
+        pub fn namada_vp_set_query_result(result_ptr: u64, result_len: u64);
+
         pub fn namada_vp_verify_masp(tx_ptr: u64, tx_len: u64) -> i64;
 
         pub fn namada_vp_has_valid_pow() -> i64;
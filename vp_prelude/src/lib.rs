@@ -27,14 +27,16 @@ pub use namada_core::ledger::{parameters, testnet_pow};
 pub use namada_core::proto::{Signed, SignedTxData};
 pub use namada_core::types::address::Address;
 use namada_core::types::chain::CHAIN_ID_LENGTH;
+pub use namada_core::types::hash::HashAlgorithm;
 use namada_core::types::hash::{Hash, HASH_LENGTH};
 use namada_core::types::internal::HostEnvResult;
 use namada_core::types::key::*;
 use namada_core::types::storage::{
-    BlockHash, BlockHeight, Epoch, TxIndex, BLOCK_HASH_LENGTH,
+    BlockHash, BlockHeight, Epoch, Header, TxIndex, BLOCK_HASH_LENGTH,
 };
+use namada_core::types::time::DateTimeUtc;
 pub use namada_core::types::*;
-pub use namada_macros::validity_predicate;
+pub use namada_macros::{validity_predicate, vp_query};
 pub use namada_proof_of_stake::storage as proof_of_stake;
 use namada_vm_env::vp::*;
 use namada_vm_env::{read_from_buffer, read_key_val_bytes_from_buffer};
@@ -71,6 +73,54 @@ pub fn log_string<T: AsRef<str>>(msg: T) {
     }
 }
 
+/// Hash the given data with the given hash function. The hash is computed by
+/// the host, which is much cheaper than hashing inside the wasm.
+pub fn hash_data(algo: HashAlgorithm, data: impl AsRef<[u8]>) -> Hash {
+    let data = data.as_ref();
+    let result = Vec::with_capacity(HASH_LENGTH);
+    unsafe {
+        namada_vp_hash(
+            u64::from(algo),
+            data.as_ptr() as _,
+            data.len() as _,
+            result.as_ptr() as _,
+        );
+    }
+    let slice = unsafe { slice::from_raw_parts(result.as_ptr(), HASH_LENGTH) };
+    Hash::try_from(slice).expect("Cannot convert the hash")
+}
+
+/// Verify a signature over the given data. A secp256k1 signature is expected
+/// to be made over the SHA-256 digest of the data.
+pub fn verify_signature(
+    pk: &common::PublicKey,
+    data: impl AsRef<[u8]>,
+    sig: &common::Signature,
+) -> bool {
+    let pk = BorshSerialize::try_to_vec(pk).unwrap();
+    let data = data.as_ref();
+    let sig = BorshSerialize::try_to_vec(sig).unwrap();
+    let valid = unsafe {
+        namada_vp_verify_signature(
+            pk.as_ptr() as _,
+            pk.len() as _,
+            data.as_ptr() as _,
+            data.len() as _,
+            sig.as_ptr() as _,
+            sig.len() as _,
+        )
+    };
+    HostEnvResult::is_success(valid)
+}
+
+/// Set the result of a VP query. This is called by the query entrypoint
+/// generated with the `#[vp_query]` macro.
+pub fn set_query_result(result: &[u8]) {
+    unsafe {
+        namada_vp_set_query_result(result.as_ptr() as _, result.len() as _);
+    }
+}
+
 /// Checks if a proposal id is being executed
 pub fn is_proposal_accepted(ctx: &Ctx, proposal_id: u64) -> VpResult {
     let proposal_execution_key =
@@ -249,6 +299,33 @@ impl<'view> VpEnv<'view> for Ctx {
         get_block_epoch()
     }
 
+    fn get_block_header(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<Header>, Error> {
+        // Both `CtxPreStorageRead` and `CtxPostStorageRead` have the same impl
+        get_block_header(height)
+    }
+
+    fn get_block_time(&self) -> Result<DateTimeUtc, Error> {
+        let read_result = unsafe { namada_vp_get_block_time() };
+        let value = read_from_buffer(read_result, namada_vp_result_buffer)
+            .ok_or_err_msg("The block time is not available")?;
+        DateTimeUtc::try_from_slice(&value[..]).into_storage_result()
+    }
+
+    fn get_block_proposer(&self) -> Result<Option<Address>, Error> {
+        let read_result = unsafe { namada_vp_get_block_proposer() };
+        match read_from_buffer(read_result, namada_vp_result_buffer) {
+            Some(value) => {
+                let address_str =
+                    std::str::from_utf8(&value[..]).into_storage_result()?;
+                Address::decode(address_str).into_storage_result().map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
     fn get_tx_index(&self) -> Result<TxIndex, Error> {
         get_tx_index()
     }
@@ -281,6 +358,23 @@ impl<'view> VpEnv<'view> for Ctx {
         Ok(HostEnvResult::is_success(result))
     }
 
+    fn query_vp(
+        &self,
+        address: &Address,
+        input_data: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let addr = address.encode();
+        let result = unsafe {
+            namada_vp_query(
+                addr.as_ptr() as _,
+                addr.len() as _,
+                input_data.as_ptr() as _,
+                input_data.len() as _,
+            )
+        };
+        Ok(read_from_buffer(result, namada_vp_result_buffer))
+    }
+
     fn verify_tx_signature(
         &self,
         pk: &common::PublicKey,
@@ -369,6 +463,13 @@ impl StorageRead for CtxPreStorageRead<'_> {
         get_block_epoch()
     }
 
+    fn get_block_header(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<Header>, Error> {
+        get_block_header(height)
+    }
+
     fn get_tx_index(&self) -> Result<TxIndex, storage_api::Error> {
         get_tx_index()
     }
@@ -432,6 +533,13 @@ impl StorageRead for CtxPostStorageRead<'_> {
         get_block_epoch()
     }
 
+    fn get_block_header(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<Header>, Error> {
+        get_block_header(height)
+    }
+
     fn get_tx_index(&self) -> Result<TxIndex, storage_api::Error> {
         get_tx_index()
     }
@@ -492,6 +600,16 @@ fn get_block_epoch() -> Result<Epoch, Error> {
     Ok(Epoch(unsafe { namada_vp_get_block_epoch() }))
 }
 
+fn get_block_header(height: BlockHeight) -> Result<Option<Header>, Error> {
+    let read_result = unsafe { namada_vp_get_block_header(height.0) };
+    match read_from_buffer(read_result, namada_vp_result_buffer) {
+        Some(value) => Header::try_from_slice(&value[..])
+            .into_storage_result()
+            .map(Some),
+        None => Ok(None),
+    }
+}
+
 fn get_tx_index() -> Result<TxIndex, storage_api::Error> {
     Ok(TxIndex(unsafe { namada_vp_get_tx_index() }))
 }
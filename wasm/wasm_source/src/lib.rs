@@ -2,16 +2,28 @@
 pub mod tx_bond;
 #[cfg(feature = "tx_change_validator_commission")]
 pub mod tx_change_validator_commission;
+#[cfg(feature = "tx_claim_htlc")]
+pub mod tx_claim_htlc;
 #[cfg(feature = "tx_ibc")]
 pub mod tx_ibc;
 #[cfg(feature = "tx_init_account")]
 pub mod tx_init_account;
+#[cfg(feature = "tx_init_htlc")]
+pub mod tx_init_htlc;
 #[cfg(feature = "tx_init_proposal")]
 pub mod tx_init_proposal;
 #[cfg(feature = "tx_init_validator")]
 pub mod tx_init_validator;
+#[cfg(feature = "tx_init_vesting_account")]
+pub mod tx_init_vesting_account;
+#[cfg(feature = "tx_refund_htlc")]
+pub mod tx_refund_htlc;
 #[cfg(feature = "tx_reveal_pk")]
 pub mod tx_reveal_pk;
+#[cfg(feature = "tx_tally_offline_proposal")]
+pub mod tx_tally_offline_proposal;
+#[cfg(feature = "tx_top_up_proposal_deposit")]
+pub mod tx_top_up_proposal_deposit;
 #[cfg(feature = "tx_transfer")]
 pub mod tx_transfer;
 #[cfg(feature = "tx_unbond")]
@@ -23,6 +35,8 @@ pub mod tx_vote_proposal;
 #[cfg(feature = "tx_withdraw")]
 pub mod tx_withdraw;
 
+#[cfg(feature = "vp_htlc")]
+pub mod vp_htlc;
 #[cfg(feature = "vp_implicit")]
 pub mod vp_implicit;
 #[cfg(feature = "vp_masp")]
@@ -33,6 +47,8 @@ pub mod vp_testnet_faucet;
 pub mod vp_token;
 #[cfg(feature = "vp_user")]
 pub mod vp_user;
+#[cfg(feature = "vp_vesting")]
+pub mod vp_vesting;
 
 #[cfg(feature = "vp_validator")]
 pub mod vp_validator;
@@ -0,0 +1,32 @@
+//! A tx to claim the tokens locked in a hash-time-locked contract (HTLC) for
+//! its recipient by revealing the preimage of its hash lock.
+
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Vec<u8>) -> TxResult {
+    let signed = SignedTxData::try_from_slice(&tx_data[..])
+        .wrap_err("failed to decode SignedTxData")?;
+    let data = signed.data.ok_or_err_msg("Missing data")?;
+    let htlc::ClaimHtlc {
+        htlc: address,
+        preimage,
+    } = htlc::ClaimHtlc::try_from_slice(&data[..])
+        .wrap_err("failed to decode ClaimHtlc")?;
+    debug_log!("apply_tx called to claim the HTLC {}", address);
+
+    let htlc: htlc::Htlc = ctx
+        .read(&htlc::htlc_key(&address))?
+        .ok_or_err_msg("The HTLC doesn't exist")?;
+    ctx.write(&htlc::preimage_key(&address), preimage)?;
+    token::transfer(
+        ctx,
+        &address,
+        &htlc.recipient,
+        &htlc.token,
+        None,
+        htlc.amount,
+        &None,
+        &None,
+    )
+}
@@ -0,0 +1,34 @@
+//! A tx to initialize a new hash-time-locked contract (HTLC) account. The
+//! locked tokens are transferred to the new account from the HTLC's sender.
+
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Vec<u8>) -> TxResult {
+    let signed = SignedTxData::try_from_slice(&tx_data[..])
+        .wrap_err("failed to decode SignedTxData")?;
+    let data = signed.data.ok_or_err_msg("Missing data")?;
+    let htlc::InitHtlc { vp_code, htlc } =
+        htlc::InitHtlc::try_from_slice(&data[..])
+            .wrap_err("failed to decode InitHtlc")?;
+    debug_log!("apply_tx called to init a new HTLC account");
+
+    // The VP of the new account doesn't run in this tx, so the terms must be
+    // validated here
+    let now = time::DateTimeUtc::try_from(ctx.get_block_time()?)
+        .wrap_err("failed to decode the block time")?;
+    htlc.validate(now).wrap_err("invalid HTLC terms")?;
+
+    let address = ctx.init_account(&vp_code)?;
+    ctx.write(&htlc::htlc_key(&address), &htlc)?;
+    token::transfer(
+        ctx,
+        &htlc.sender,
+        &address,
+        &htlc.token,
+        None,
+        htlc.amount,
+        &None,
+        &None,
+    )
+}
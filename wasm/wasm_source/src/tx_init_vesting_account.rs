@@ -0,0 +1,44 @@
+//! A tx to initialize a new vesting account with a given public key, a
+//! validity predicate and a vesting schedule. The vested tokens are
+//! transferred to the new account from the source.
+
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Vec<u8>) -> TxResult {
+    let signed = SignedTxData::try_from_slice(&tx_data[..])
+        .wrap_err("failed to decode SignedTxData")?;
+    let data = signed.data.ok_or_err_msg("Missing data")?;
+    let tx_data = transaction::InitVestingAccount::try_from_slice(&data[..])
+        .wrap_err("failed to decode InitVestingAccount")?;
+    debug_log!("apply_tx called to init a new vesting account");
+
+    let transaction::InitVestingAccount {
+        public_key,
+        vp_code,
+        source,
+        schedule,
+    } = tx_data;
+    if !schedule.is_valid() {
+        return Err(Error::new_const(
+            "The cliff of the vesting schedule must be between its start and \
+             end",
+        ));
+    }
+
+    let address = ctx.init_account(&vp_code)?;
+    let pk_key = key::pk_key(&address);
+    ctx.write(&pk_key, &public_key)?;
+    let schedule_key = vesting::vesting_schedule_key(&address);
+    ctx.write(&schedule_key, &schedule)?;
+    token::transfer(
+        ctx,
+        &source,
+        &address,
+        &schedule.token,
+        None,
+        schedule.amount,
+        &None,
+        &None,
+    )
+}
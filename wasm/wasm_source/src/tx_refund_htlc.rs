@@ -0,0 +1,29 @@
+//! A tx to refund the tokens locked in an expired hash-time-locked contract
+//! (HTLC) to its sender.
+
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Vec<u8>) -> TxResult {
+    let signed = SignedTxData::try_from_slice(&tx_data[..])
+        .wrap_err("failed to decode SignedTxData")?;
+    let data = signed.data.ok_or_err_msg("Missing data")?;
+    let htlc::RefundHtlc { htlc: address } =
+        htlc::RefundHtlc::try_from_slice(&data[..])
+            .wrap_err("failed to decode RefundHtlc")?;
+    debug_log!("apply_tx called to refund the HTLC {}", address);
+
+    let htlc: htlc::Htlc = ctx
+        .read(&htlc::htlc_key(&address))?
+        .ok_or_err_msg("The HTLC doesn't exist")?;
+    token::transfer(
+        ctx,
+        &address,
+        &htlc.sender,
+        &htlc.token,
+        None,
+        htlc.amount,
+        &None,
+        &None,
+    )
+}
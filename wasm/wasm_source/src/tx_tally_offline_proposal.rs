@@ -0,0 +1,19 @@
+//! A tx to submit an offline proposal with its votes to be tallied on-chain
+
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Vec<u8>) -> TxResult {
+    let signed = SignedTxData::try_from_slice(&tx_data[..])
+        .wrap_err("failed to decode SignedTxData")?;
+    let data = signed.data.ok_or_err_msg("Missing data")?;
+    let tx_data =
+        transaction::governance::TallyOfflineProposalData::try_from_slice(
+            &data[..],
+        )
+        .wrap_err("failed to decode TallyOfflineProposalData")?;
+
+    debug_log!("apply_tx called to tally an offline governance proposal");
+
+    governance::tally_offline_proposal(ctx, tx_data)
+}
@@ -0,0 +1,19 @@
+//! A tx to top up the deposit of a proposal before its voting period
+
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Vec<u8>) -> TxResult {
+    let signed = SignedTxData::try_from_slice(&tx_data[..])
+        .wrap_err("failed to decode SignedTxData")?;
+    let data = signed.data.ok_or_err_msg("Missing data")?;
+    let tx_data =
+        transaction::governance::TopUpProposalDepositData::try_from_slice(
+            &data[..],
+        )
+        .wrap_err("failed to decode TopUpProposalDepositData")?;
+
+    debug_log!("apply_tx called to top up a governance proposal deposit");
+
+    governance::top_up_proposal_deposit(ctx, tx_data)
+}
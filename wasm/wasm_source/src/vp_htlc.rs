@@ -0,0 +1,441 @@
+//! A hash-time-locked contract (HTLC) VP.
+//!
+//! The tokens locked in the HTLC can be released:
+//! - to the recipient before the deadline, if the preimage of the HTLC's hash
+//!   lock is revealed in the same transaction, or
+//! - to the sender from the deadline on.
+//!
+//! The whole locked amount must be released at once and the beneficiary must
+//! be credited with at least the sum of all the HTLCs' tokens released to it in
+//! the same transaction. Anyone may credit the HTLC. The terms of the HTLC, the
+//! revealed preimage and the VP cannot be modified. No signature is required,
+//! because the tokens can only ever be released to the recipient or to the
+//! sender.
+
+use namada_vp_prelude::storage::KeySeg;
+use namada_vp_prelude::*;
+
+enum KeyType<'a> {
+    Token(&'a Address),
+    Preimage(&'a Address),
+    Unknown,
+}
+
+impl<'a> From<&'a storage::Key> for KeyType<'a> {
+    fn from(key: &'a storage::Key) -> KeyType<'a> {
+        if let Some(address) = token::is_any_token_balance_key(key) {
+            Self::Token(address)
+        } else if let Some((_, address)) =
+            token::is_any_multitoken_balance_key(key)
+        {
+            Self::Token(address)
+        } else if let Some(address) = htlc::is_preimage_key(key) {
+            Self::Preimage(address)
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+#[validity_predicate]
+fn validate_tx(
+    ctx: &Ctx,
+    _tx_data: Vec<u8>,
+    addr: Address,
+    keys_changed: BTreeSet<storage::Key>,
+    verifiers: BTreeSet<Address>,
+) -> VpResult {
+    debug_log!(
+        "vp_htlc called with addr: {}, key_changed: {:?}, verifiers: {:?}",
+        addr,
+        keys_changed,
+        verifiers
+    );
+
+    let htlc: htlc::Htlc = match ctx.read_pre(&htlc::htlc_key(&addr))? {
+        Some(htlc) => htlc,
+        None => {
+            debug_log!("The HTLC {} has no terms", addr);
+            return reject();
+        }
+    };
+    let is_expired = htlc.is_expired(ctx.get_block_time()?);
+
+    for key in keys_changed.iter() {
+        let key_type: KeyType = key.into();
+        let is_valid = match key_type {
+            KeyType::Token(owner) => {
+                if owner == &addr {
+                    let pre: token::Amount =
+                        ctx.read_pre(key)?.unwrap_or_default();
+                    let post: token::Amount =
+                        ctx.read_post(key)?.unwrap_or_default();
+                    if post >= pre {
+                        // Credit is allowed
+                        true
+                    } else if token::is_balance_key(&htlc.token, key)
+                        != Some(&addr)
+                    {
+                        // Only the locked token may be released
+                        false
+                    } else if pre - post != htlc.amount {
+                        debug_log!(
+                            "The HTLC {} must release exactly {}",
+                            addr,
+                            htlc.amount
+                        );
+                        false
+                    } else {
+                        let beneficiary = if is_expired {
+                            &htlc.sender
+                        } else if is_valid_claim(ctx, &addr, &htlc)? {
+                            &htlc.recipient
+                        } else {
+                            debug_log!(
+                                "The HTLC {} is not expired nor claimed",
+                                addr
+                            );
+                            return reject();
+                        };
+                        is_credited(
+                            ctx,
+                            &htlc.token,
+                            beneficiary,
+                            &keys_changed,
+                        )?
+                    }
+                } else {
+                    // If this is not the owner, allow any change
+                    true
+                }
+            }
+            KeyType::Preimage(owner) => {
+                owner != &addr
+                    || (!is_expired && is_valid_claim(ctx, &addr, &htlc)?)
+            }
+            KeyType::Unknown => {
+                // Any other changes to this address space, including the terms
+                // of the HTLC and its VP, are not allowed, while changes
+                // anywhere else are permitted
+                key.segments.get(0) != Some(&addr.to_db_key())
+            }
+        };
+        if !is_valid {
+            debug_log!("key {} modification failed vp", key);
+            return reject();
+        }
+    }
+
+    accept()
+}
+
+/// Check that the preimage of the HTLC's hash lock is revealed in this
+/// transaction
+fn is_valid_claim(ctx: &Ctx, addr: &Address, htlc: &htlc::Htlc) -> VpResult {
+    let preimage_key = htlc::preimage_key(addr);
+    if ctx.has_key_pre(&preimage_key)? {
+        debug_log!("The HTLC {} has already been claimed", addr);
+        return reject();
+    }
+    let preimage: Vec<u8> = match ctx.read_post(&preimage_key)? {
+        Some(preimage) if !preimage.is_empty() => preimage,
+        _ => return reject(),
+    };
+    Ok(hash_data(HashAlgorithm::Sha256, preimage) == htlc.hash_lock)
+}
+
+/// Check that the balance of the given token of the beneficiary is credited
+/// with at least the sum of the tokens released to it by all the HTLCs in this
+/// transaction, so that a single credit cannot be used to release the tokens
+/// of several HTLCs
+fn is_credited(
+    ctx: &Ctx,
+    token: &Address,
+    beneficiary: &Address,
+    keys_changed: &BTreeSet<storage::Key>,
+) -> VpResult {
+    let now = ctx.get_block_time()?;
+    let mut released = token::Amount::default();
+    for key in keys_changed {
+        let owner = match token::is_balance_key(token, key) {
+            Some(owner) => owner,
+            None => continue,
+        };
+        let htlc: htlc::Htlc = match ctx.read_pre(&htlc::htlc_key(owner))? {
+            Some(htlc) => htlc,
+            None => continue,
+        };
+        let htlc_beneficiary = if htlc.is_expired(now) {
+            &htlc.sender
+        } else {
+            &htlc.recipient
+        };
+        if htlc_beneficiary != beneficiary {
+            continue;
+        }
+        let pre: token::Amount = ctx.read_pre(key)?.unwrap_or_default();
+        let post: token::Amount = ctx.read_post(key)?.unwrap_or_default();
+        released += pre.checked_sub(post).unwrap_or_default();
+    }
+
+    let key = token::balance_key(token, beneficiary);
+    let pre: token::Amount = ctx.read_pre(&key)?.unwrap_or_default();
+    let post: token::Amount = ctx.read_post(&key)?.unwrap_or_default();
+    Ok(post.checked_sub(pre).unwrap_or_default() >= released)
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::types::hash::Hash;
+    use namada::types::storage::Header;
+    use namada::types::time::{DateTimeUtc, Duration};
+    // Use this as `#[test]` annotation to enable logging
+    use namada_tests::log::test;
+    use namada_tests::tx::{self, tx_host_env, TestTxEnv};
+    use namada_tests::vp::*;
+    use namada_tx_prelude::StorageWrite;
+
+    use super::*;
+
+    const PREIMAGE: &[u8] = b"atomic swap secret";
+
+    /// The terms of an HTLC that expires after the given number of days from
+    /// now
+    fn htlc_terms(token: &Address, expires_in_days: i64) -> htlc::Htlc {
+        htlc::Htlc {
+            sender: address::testing::established_address_2(),
+            recipient: address::testing::established_address_3(),
+            token: token.clone(),
+            amount: token::Amount::whole(1_000),
+            hash_lock: Hash::sha256(PREIMAGE),
+            deadline: DateTimeUtc(
+                DateTimeUtc::now().0 + Duration::days(expires_in_days),
+            ),
+        }
+    }
+
+    /// Initialize an HTLC account with its locked tokens and the header of
+    /// the current block with the current time
+    fn init_htlc(tx_env: &mut TestTxEnv, addr: &Address, htlc: &htlc::Htlc) {
+        tx_env.spawn_accounts([
+            addr,
+            &htlc.sender,
+            &htlc.recipient,
+            &htlc.token,
+        ]);
+        tx_env.credit_tokens(addr, &htlc.token, None, htlc.amount);
+        tx_env
+            .wl_storage
+            .storage
+            .write(&htlc::htlc_key(addr), htlc.try_to_vec().unwrap())
+            .unwrap();
+        tx_env
+            .wl_storage
+            .storage
+            .set_header(Header {
+                hash: Default::default(),
+                time: DateTimeUtc::now(),
+                next_validators_hash: Default::default(),
+            })
+            .unwrap();
+    }
+
+    /// Apply a transaction that releases the HTLC's tokens to the given
+    /// target and reveals the given preimage, if any
+    fn release(
+        addr: &Address,
+        tx_env: TestTxEnv,
+        htlc: &htlc::Htlc,
+        target: &Address,
+        preimage: Option<&[u8]>,
+    ) {
+        vp_host_env::init_from_tx(addr.clone(), tx_env, |address| {
+            if let Some(preimage) = preimage {
+                tx::ctx()
+                    .write(&htlc::preimage_key(address), preimage.to_vec())
+                    .unwrap();
+            }
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                address,
+                target,
+                &htlc.token,
+                None,
+                htlc.amount,
+                &None,
+                &None,
+            )
+            .unwrap();
+        });
+    }
+
+    fn validate(addr: Address) -> bool {
+        let vp_env = vp_host_env::take();
+        let tx_data: Vec<u8> = vec![];
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        validate_tx(&CTX, tx_data, addr, keys_changed, verifiers).unwrap()
+    }
+
+    /// Test that a claim with the preimage before the deadline is accepted.
+    #[test]
+    fn test_claim_with_preimage_accepted() {
+        let mut tx_env = TestTxEnv::default();
+        let addr = address::testing::established_address_1();
+        let htlc = htlc_terms(&address::nam(), 1);
+        init_htlc(&mut tx_env, &addr, &htlc);
+
+        release(&addr, tx_env, &htlc, &htlc.recipient, Some(PREIMAGE));
+
+        assert!(validate(addr));
+    }
+
+    /// Test that a claim with an invalid preimage is rejected.
+    #[test]
+    fn test_claim_with_invalid_preimage_rejected() {
+        let mut tx_env = TestTxEnv::default();
+        let addr = address::testing::established_address_1();
+        let htlc = htlc_terms(&address::nam(), 1);
+        init_htlc(&mut tx_env, &addr, &htlc);
+
+        release(&addr, tx_env, &htlc, &htlc.recipient, Some(b"guess"));
+
+        assert!(!validate(addr));
+    }
+
+    /// Test that a claim after the deadline is rejected.
+    #[test]
+    fn test_claim_after_deadline_rejected() {
+        let mut tx_env = TestTxEnv::default();
+        let addr = address::testing::established_address_1();
+        let htlc = htlc_terms(&address::nam(), -1);
+        init_htlc(&mut tx_env, &addr, &htlc);
+
+        release(&addr, tx_env, &htlc, &htlc.recipient, Some(PREIMAGE));
+
+        assert!(!validate(addr));
+    }
+
+    /// Test that a release to anyone but the recipient is rejected, even with
+    /// the preimage.
+    #[test]
+    fn test_claim_to_other_target_rejected() {
+        let mut tx_env = TestTxEnv::default();
+        let addr = address::testing::established_address_1();
+        let htlc = htlc_terms(&address::nam(), 1);
+        let target = address::testing::established_address_4();
+        init_htlc(&mut tx_env, &addr, &htlc);
+        tx_env.spawn_accounts([&target]);
+
+        release(&addr, tx_env, &htlc, &target, Some(PREIMAGE));
+
+        assert!(!validate(addr));
+    }
+
+    /// Test that a refund to the sender before the deadline is rejected.
+    #[test]
+    fn test_refund_before_deadline_rejected() {
+        let mut tx_env = TestTxEnv::default();
+        let addr = address::testing::established_address_1();
+        let htlc = htlc_terms(&address::nam(), 1);
+        init_htlc(&mut tx_env, &addr, &htlc);
+
+        release(&addr, tx_env, &htlc, &htlc.sender, None);
+
+        assert!(!validate(addr));
+    }
+
+    /// Test that a refund to the sender after the deadline is accepted.
+    #[test]
+    fn test_refund_after_deadline_accepted() {
+        let mut tx_env = TestTxEnv::default();
+        let addr = address::testing::established_address_1();
+        let htlc = htlc_terms(&address::nam(), -1);
+        init_htlc(&mut tx_env, &addr, &htlc);
+
+        release(&addr, tx_env, &htlc, &htlc.sender, None);
+
+        assert!(validate(addr));
+    }
+
+    /// Test that a partial release of the locked tokens is rejected.
+    #[test]
+    fn test_partial_refund_rejected() {
+        let mut tx_env = TestTxEnv::default();
+        let addr = address::testing::established_address_1();
+        let htlc = htlc_terms(&address::nam(), -1);
+        init_htlc(&mut tx_env, &addr, &htlc);
+
+        let partial = htlc::Htlc {
+            amount: token::Amount::whole(500),
+            ..htlc.clone()
+        };
+        release(&addr, tx_env, &partial, &htlc.sender, None);
+
+        assert!(!validate(addr));
+    }
+
+    /// Test that a single credit of the beneficiary cannot release the tokens
+    /// of two HTLCs.
+    #[test]
+    fn test_refund_of_two_htlcs_with_one_credit_rejected() {
+        let mut tx_env = TestTxEnv::default();
+        let addr = address::testing::established_address_1();
+        let other_addr = address::testing::established_address_4();
+        let target = address::testing::gen_established_address();
+        let htlc = htlc_terms(&address::nam(), -1);
+        init_htlc(&mut tx_env, &addr, &htlc);
+        init_htlc(&mut tx_env, &other_addr, &htlc);
+        tx_env.spawn_accounts([&target]);
+
+        vp_host_env::init_from_tx(addr.clone(), tx_env, |address| {
+            // Refund this HTLC to the sender ...
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                address,
+                &htlc.sender,
+                &htlc.token,
+                None,
+                htlc.amount,
+                &None,
+                &None,
+            )
+            .unwrap();
+            // ... and the other one elsewhere
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                &other_addr,
+                &target,
+                &htlc.token,
+                None,
+                htlc.amount,
+                &None,
+                &None,
+            )
+            .unwrap();
+        });
+
+        assert!(!validate(addr));
+    }
+
+    /// Test that the terms of the HTLC cannot be modified.
+    #[test]
+    fn test_terms_update_rejected() {
+        let mut tx_env = TestTxEnv::default();
+        let addr = address::testing::established_address_1();
+        let htlc = htlc_terms(&address::nam(), 1);
+        init_htlc(&mut tx_env, &addr, &htlc);
+
+        vp_host_env::init_from_tx(addr.clone(), tx_env, |address| {
+            let terms = htlc::Htlc {
+                recipient: address::testing::established_address_4(),
+                ..htlc.clone()
+            };
+            tx::ctx().write(&htlc::htlc_key(address), terms).unwrap();
+        });
+
+        assert!(!validate(addr));
+    }
+}
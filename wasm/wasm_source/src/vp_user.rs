@@ -7,6 +7,10 @@
 //! valid signature.
 //!
 //! Any other storage key changes are allowed only with a valid signature.
+//!
+//! When the account is only a verifier of a tx that's not signed by it, e.g.
+//! as the third-party fee payer of its wrapper, the tx must satisfy the
+//! account's sponsorship policy, if any.
 
 use namada_vp_prelude::address::masp;
 use namada_vp_prelude::storage::KeySeg;
@@ -87,6 +91,17 @@ fn validate_tx(
         return reject();
     }
 
+    let is_account_changed = keys_changed
+        .iter()
+        .any(|key| key.find_addresses().contains(&addr));
+    if !is_account_changed && !*valid_sig {
+        let signed_tx_data = signed_tx_data.as_ref().ok();
+        if !is_sponsored(ctx, &addr, signed_tx_data)? {
+            debug_log!("tx is not sponsored by {}", addr);
+            return reject();
+        }
+    }
+
     for key in keys_changed.iter() {
         let key_type: KeyType = key.into();
         let is_valid = match key_type {
@@ -188,6 +203,41 @@ fn validate_tx(
     accept()
 }
 
+/// Check that the tx's code and signer are allowed by the sponsorship policy
+/// of the account. If a set of sponsored tx codes or users isn't set, any is
+/// allowed. The ledger checks the sponsored users before charging the fee of
+/// a wrapper tx and refunds the whole fee when this VP rejects its inner tx.
+fn is_sponsored(
+    ctx: &Ctx,
+    addr: &Address,
+    signed_tx_data: Option<&SignedTxData>,
+) -> VpResult {
+    let codes_key = sponsor::sponsored_tx_codes_key(addr);
+    let codes: Option<BTreeSet<hash::Hash>> = ctx.read_pre(&codes_key)?;
+    if let Some(codes) = codes {
+        let code_hash = ctx.get_tx_code_hash()?;
+        if !codes.contains(&code_hash) {
+            return reject();
+        }
+    }
+    let users_key = sponsor::sponsored_users_key(addr);
+    let users: Option<BTreeSet<key::common::PublicKey>> =
+        ctx.read_pre(&users_key)?;
+    if let Some(users) = users {
+        let sig = match signed_tx_data {
+            Some(signed_tx_data) => &signed_tx_data.sig,
+            None => return reject(),
+        };
+        for pk in users.iter() {
+            if ctx.verify_tx_signature(pk, sig)? {
+                return accept();
+            }
+        }
+        return reject();
+    }
+    accept()
+}
+
 #[cfg(test)]
 mod tests {
     use address::testing::arb_non_internal_address;
@@ -551,6 +601,138 @@ mod tests {
         );
     }
 
+    /// Test that a tx sponsored by the VP owner as its fee payer is accepted
+    /// if it's signed by one of the sponsored users.
+    #[test]
+    fn test_sponsored_user_accepted() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let source = address::testing::established_address_2();
+        let target = address::testing::established_address_3();
+        let token = address::nam();
+        let amount = token::Amount::from(10_098_123);
+        let sponsor_keypair = key::testing::keypair_1();
+        let user_keypair = key::testing::keypair_2();
+        let sponsored_users = BTreeSet::from([user_keypair.ref_to()]);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &source, &target, &token]);
+
+        tx_env.write_public_key(&vp_owner, &sponsor_keypair.ref_to());
+        tx_env
+            .wl_storage
+            .storage
+            .write(
+                &sponsor::sponsored_users_key(&vp_owner),
+                sponsored_users.try_to_vec().unwrap(),
+            )
+            .unwrap();
+
+        // Credit the tokens to the source before running the transaction to
+        // be able to transfer from it
+        tx_env.credit_tokens(&source, &token, None, amount);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // The VP owner is a verifier as the fee payer
+            tx::ctx().insert_verifier(address).unwrap();
+            // Apply transfer in a transaction
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                &source,
+                &target,
+                &token,
+                None,
+                amount,
+                &None,
+                &None,
+            )
+            .unwrap();
+        });
+
+        let mut vp_env = vp_host_env::take();
+        let tx = vp_env.tx.clone();
+        let signed_tx = tx.sign(&user_keypair);
+        let tx_data: Vec<u8> = signed_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = signed_tx;
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        assert!(
+            validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
+    /// Test that a tx sponsored by the VP owner as its fee payer is rejected
+    /// if it's not signed by one of the sponsored users.
+    #[test]
+    fn test_sponsored_user_rejected() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let source = address::testing::established_address_2();
+        let target = address::testing::established_address_3();
+        let token = address::nam();
+        let amount = token::Amount::from(10_098_123);
+        let sponsor_keypair = key::testing::keypair_1();
+        let user_keypair = key::testing::keypair_2();
+        let sponsored_users = BTreeSet::from([sponsor_keypair.ref_to()]);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &source, &target, &token]);
+
+        tx_env.write_public_key(&vp_owner, &sponsor_keypair.ref_to());
+        tx_env
+            .wl_storage
+            .storage
+            .write(
+                &sponsor::sponsored_users_key(&vp_owner),
+                sponsored_users.try_to_vec().unwrap(),
+            )
+            .unwrap();
+
+        // Credit the tokens to the source before running the transaction to
+        // be able to transfer from it
+        tx_env.credit_tokens(&source, &token, None, amount);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // The VP owner is a verifier as the fee payer
+            tx::ctx().insert_verifier(address).unwrap();
+            // Apply transfer in a transaction
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                &source,
+                &target,
+                &token,
+                None,
+                amount,
+                &None,
+                &None,
+            )
+            .unwrap();
+        });
+
+        let mut vp_env = vp_host_env::take();
+        let tx = vp_env.tx.clone();
+        let signed_tx = tx.sign(&user_keypair);
+        let tx_data: Vec<u8> = signed_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = signed_tx;
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        assert!(
+            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
     prop_compose! {
         /// Generates an account address and a storage key inside its storage.
         fn arb_account_storage_subspace_key()
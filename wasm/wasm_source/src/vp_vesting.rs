@@ -0,0 +1,647 @@
+//! A vesting account VP.
+//!
+//! This VP behaves like the basic user VP, except that the tokens locked by
+//! the account's vesting schedule cannot be spent until they are unlocked. The
+//! locked tokens can still be bonded to and unbonded from the PoS system with
+//! a valid signature.
+//!
+//! The vesting schedule cannot be modified and the VP cannot be updated until
+//! all the vested tokens are unlocked.
+//!
+//! Any other storage key changes are allowed only with a valid signature.
+
+use namada_vp_prelude::address::masp;
+use namada_vp_prelude::storage::KeySeg;
+use namada_vp_prelude::*;
+use once_cell::unsync::Lazy;
+
+enum KeyType<'a> {
+    Token(&'a Address),
+    PoS,
+    Vp(&'a Address),
+    Masp,
+    GovernanceVote(&'a Address),
+    VestingSchedule(&'a Address),
+    Unknown,
+}
+
+impl<'a> From<&'a storage::Key> for KeyType<'a> {
+    fn from(key: &'a storage::Key) -> KeyType<'a> {
+        if let Some(address) = token::is_any_token_balance_key(key) {
+            Self::Token(address)
+        } else if let Some((_, address)) =
+            token::is_any_multitoken_balance_key(key)
+        {
+            Self::Token(address)
+        } else if proof_of_stake::is_pos_key(key) {
+            Self::PoS
+        } else if gov_storage::is_vote_key(key) {
+            let voter_address = gov_storage::get_voter_address(key);
+            if let Some(address) = voter_address {
+                Self::GovernanceVote(address)
+            } else {
+                Self::Unknown
+            }
+        } else if let Some(address) = key.is_validity_predicate() {
+            Self::Vp(address)
+        } else if token::is_masp_key(key) {
+            Self::Masp
+        } else if let Some(address) = vesting::is_vesting_schedule_key(key) {
+            Self::VestingSchedule(address)
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+#[validity_predicate]
+fn validate_tx(
+    ctx: &Ctx,
+    tx_data: Vec<u8>,
+    addr: Address,
+    keys_changed: BTreeSet<storage::Key>,
+    verifiers: BTreeSet<Address>,
+) -> VpResult {
+    debug_log!(
+        "vp_vesting called with user addr: {}, key_changed: {:?}, verifiers: \
+         {:?}",
+        addr,
+        keys_changed,
+        verifiers
+    );
+
+    let signed_tx_data =
+        Lazy::new(|| SignedTxData::try_from_slice(&tx_data[..]));
+
+    let valid_sig = Lazy::new(|| match &*signed_tx_data {
+        Ok(signed_tx_data) => {
+            let pk = key::get(ctx, &addr);
+            match pk {
+                Ok(Some(pk)) => {
+                    matches!(
+                        ctx.verify_tx_signature(&pk, &signed_tx_data.sig),
+                        Ok(true)
+                    )
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    });
+
+    if !is_valid_tx(ctx, &tx_data)? {
+        return reject();
+    }
+
+    for key in keys_changed.iter() {
+        let key_type: KeyType = key.into();
+        let is_valid = match key_type {
+            KeyType::Token(owner) => {
+                if owner == &addr {
+                    let pre: token::Amount =
+                        ctx.read_pre(key)?.unwrap_or_default();
+                    let post: token::Amount =
+                        ctx.read_post(key)?.unwrap_or_default();
+                    let change = post.change() - pre.change();
+                    // debit has to signed and may only spend the unlocked
+                    // tokens, credit doesn't
+                    let valid = change >= 0
+                        || addr == masp()
+                        || (*valid_sig
+                            && is_unlocked_debit(
+                                ctx,
+                                &addr,
+                                key,
+                                pre,
+                                post,
+                                &keys_changed,
+                            )?);
+                    debug_log!(
+                        "token key: {}, change: {}, valid_sig: {}, valid \
+                         modification: {}",
+                        key,
+                        change,
+                        *valid_sig,
+                        valid
+                    );
+                    valid
+                } else {
+                    debug_log!(
+                        "This address ({}) is not of owner ({}) of token key: \
+                         {}",
+                        addr,
+                        owner,
+                        key
+                    );
+                    // If this is not the owner, allow any change
+                    true
+                }
+            }
+            KeyType::PoS => {
+                // Allow the account to be used in PoS
+                let bond_id = proof_of_stake::is_bond_key(key)
+                    .map(|(bond_id, _)| bond_id)
+                    .or_else(|| {
+                        proof_of_stake::is_unbond_key(key)
+                            .map(|(bond_id, _, _)| bond_id)
+                    });
+                let valid = match bond_id {
+                    Some(bond_id) => {
+                        // Bonds and unbonds changes for this address
+                        // must be signed
+                        bond_id.source != addr || *valid_sig
+                    }
+                    None => {
+                        // Any other PoS changes are allowed without signature
+                        true
+                    }
+                };
+                debug_log!(
+                    "PoS key {} {}",
+                    key,
+                    if valid { "accepted" } else { "rejected" }
+                );
+                valid
+            }
+            KeyType::GovernanceVote(voter) => {
+                if voter == &addr {
+                    *valid_sig
+                } else {
+                    true
+                }
+            }
+            KeyType::Vp(owner) => {
+                let has_post: bool = ctx.has_key_post(key)?;
+                if owner == &addr {
+                    if has_post {
+                        // The VP cannot be updated to escape the vesting
+                        // schedule
+                        let vp: Vec<u8> = ctx.read_bytes_post(key)?.unwrap();
+                        *valid_sig
+                            && is_vp_whitelisted(ctx, &vp)?
+                            && is_fully_vested(ctx, &addr)?
+                    } else {
+                        false
+                    }
+                } else {
+                    let vp: Vec<u8> = ctx.read_bytes_post(key)?.unwrap();
+                    is_vp_whitelisted(ctx, &vp)?
+                }
+            }
+            KeyType::Masp => true,
+            // The vesting schedule of this account cannot be modified
+            KeyType::VestingSchedule(owner) => owner != &addr,
+            KeyType::Unknown => {
+                if key.segments.get(0) == Some(&addr.to_db_key()) {
+                    // Unknown changes to this address space require a valid
+                    // signature
+                    *valid_sig
+                } else {
+                    // Unknown changes anywhere else are permitted
+                    true
+                }
+            }
+        };
+        if !is_valid {
+            debug_log!("key {} modification failed vp", key);
+            return reject();
+        }
+    }
+
+    accept()
+}
+
+/// Check that a debit of the account's balance doesn't spend any tokens that
+/// are still locked by the vesting schedule, unless they are being bonded.
+fn is_unlocked_debit(
+    ctx: &Ctx,
+    addr: &Address,
+    key: &storage::Key,
+    pre: token::Amount,
+    post: token::Amount,
+    keys_changed: &BTreeSet<storage::Key>,
+) -> VpResult {
+    let schedule_key = vesting::vesting_schedule_key(addr);
+    let schedule: vesting::VestingSchedule =
+        match ctx.read_pre(&schedule_key)? {
+            Some(schedule) => schedule,
+            None => return accept(),
+        };
+    if token::is_balance_key(&schedule.token, key) != Some(addr) {
+        // The balances of other tokens are not locked
+        return accept();
+    }
+    let locked = schedule.locked_amount(ctx.get_block_time()?);
+    if post >= locked {
+        return accept();
+    }
+
+    // The locked tokens may only be bonded by this account, so only its own
+    // bond changes are counted
+    let mut bonded: token::Change = 0;
+    for key in keys_changed {
+        match proof_of_stake::is_bond_key(key) {
+            Some((bond_id, _)) if &bond_id.source == addr => {
+                let pre: token::Change = ctx.read_pre(key)?.unwrap_or_default();
+                let post: token::Change =
+                    ctx.read_post(key)?.unwrap_or_default();
+                bonded += post - pre;
+            }
+            _ => {}
+        }
+    }
+    if bonded <= 0 || schedule.token != ctx.get_native_token()? {
+        debug_log!(
+            "Cannot spend the locked tokens, post balance: {}, locked: {}",
+            post,
+            locked
+        );
+        return reject();
+    }
+    let bonded = std::cmp::min(token::Amount::from_change(bonded), pre - post);
+    Ok(post + bonded >= locked)
+}
+
+/// Check that all the tokens of the vesting schedule are unlocked
+fn is_fully_vested(ctx: &Ctx, addr: &Address) -> VpResult {
+    let schedule_key = vesting::vesting_schedule_key(addr);
+    let schedule: Option<vesting::VestingSchedule> =
+        ctx.read_pre(&schedule_key)?;
+    match schedule {
+        Some(schedule) => {
+            let locked = schedule.locked_amount(ctx.get_block_time()?);
+            Ok(locked == token::Amount::default())
+        }
+        None => accept(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::ledger::pos::{GenesisValidator, PosParams};
+    use namada::types::storage::{Epoch, Header};
+    use namada::types::time::{DateTimeUtc, Duration};
+    // Use this as `#[test]` annotation to enable logging
+    use namada_tests::log::test;
+    use namada_tests::native_vp::pos::init_pos;
+    use namada_tests::tx::{self, tx_host_env, TestTxEnv};
+    use namada_tests::vp::*;
+    use namada_tx_prelude::{StorageWrite, TxEnv};
+    use namada_vp_prelude::key::RefTo;
+
+    use super::*;
+
+    const VP_ALWAYS_TRUE_WASM: &str =
+        "../../wasm_for_tests/vp_always_true.wasm";
+
+    /// A vesting schedule of the given amount in which half of the tokens
+    /// are unlocked at the time of the current block
+    fn half_vested_schedule(
+        token: &Address,
+        amount: token::Amount,
+    ) -> vesting::VestingSchedule {
+        let now = DateTimeUtc::now();
+        vesting::VestingSchedule {
+            token: token.clone(),
+            amount,
+            start: DateTimeUtc(now.0 - Duration::days(50)),
+            cliff: DateTimeUtc(now.0 - Duration::days(50)),
+            end: DateTimeUtc(now.0 + Duration::days(50)),
+        }
+    }
+
+    /// Write the vesting schedule and the header of the current block with
+    /// the current time
+    fn init_vesting(
+        tx_env: &mut TestTxEnv,
+        owner: &Address,
+        schedule: &vesting::VestingSchedule,
+    ) {
+        tx_env
+            .wl_storage
+            .storage
+            .write(
+                &vesting::vesting_schedule_key(owner),
+                schedule.try_to_vec().unwrap(),
+            )
+            .unwrap();
+        tx_env
+            .wl_storage
+            .storage
+            .set_header(Header {
+                hash: Default::default(),
+                time: DateTimeUtc::now(),
+                next_validators_hash: Default::default(),
+            })
+            .unwrap();
+    }
+
+    /// Sign the tx in the VP environment and validate it
+    fn validate_signed(
+        vp_owner: Address,
+        keypair: &key::common::SecretKey,
+    ) -> bool {
+        let mut vp_env = vp_host_env::take();
+        let tx = vp_env.tx.clone();
+        let signed_tx = tx.sign(keypair);
+        let tx_data: Vec<u8> = signed_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = signed_tx;
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers).unwrap()
+    }
+
+    /// Test that no-op transaction (i.e. no storage modifications) accepted.
+    #[test]
+    fn test_no_op_transaction() {
+        let tx_data: Vec<u8> = vec![];
+        let addr: Address = address::testing::established_address_1();
+        let keys_changed: BTreeSet<storage::Key> = BTreeSet::default();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+
+        // The VP env must be initialized before calling `validate_tx`
+        vp_host_env::init();
+
+        assert!(
+            validate_tx(&CTX, tx_data, addr, keys_changed, verifiers).unwrap()
+        );
+    }
+
+    /// Test that a signed debit transfer of the unlocked tokens is accepted.
+    #[test]
+    fn test_signed_unlocked_debit_transfer_accepted() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::testing::keypair_1();
+        let public_key = keypair.ref_to();
+        let target = address::testing::established_address_2();
+        let token = address::nam();
+        let amount = token::Amount::whole(1_000);
+        // Less than the half of the amount that is unlocked
+        let transfer_amount = token::Amount::whole(400);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &target, &token]);
+        tx_env.credit_tokens(&vp_owner, &token, None, amount);
+        tx_env.write_public_key(&vp_owner, &public_key);
+        init_vesting(
+            &mut tx_env,
+            &vp_owner,
+            &half_vested_schedule(&token, amount),
+        );
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Apply transfer in a transaction
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                address,
+                &target,
+                &token,
+                None,
+                transfer_amount,
+                &None,
+                &None,
+            )
+            .unwrap();
+        });
+
+        assert!(validate_signed(vp_owner, &keypair));
+    }
+
+    /// Test that a signed debit transfer of the locked tokens is rejected.
+    #[test]
+    fn test_signed_locked_debit_transfer_rejected() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::testing::keypair_1();
+        let public_key = keypair.ref_to();
+        let target = address::testing::established_address_2();
+        let token = address::nam();
+        let amount = token::Amount::whole(1_000);
+        // More than the half of the amount that is unlocked
+        let transfer_amount = token::Amount::whole(600);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &target, &token]);
+        tx_env.credit_tokens(&vp_owner, &token, None, amount);
+        tx_env.write_public_key(&vp_owner, &public_key);
+        init_vesting(
+            &mut tx_env,
+            &vp_owner,
+            &half_vested_schedule(&token, amount),
+        );
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Apply transfer in a transaction
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                address,
+                &target,
+                &token,
+                None,
+                transfer_amount,
+                &None,
+                &None,
+            )
+            .unwrap();
+        });
+
+        assert!(!validate_signed(vp_owner, &keypair));
+    }
+
+    /// Test that the locked tokens can be bonded with a valid signature.
+    #[test]
+    fn test_signed_locked_bond_accepted() {
+        // Init PoS genesis
+        let pos_params = PosParams::default();
+        let validator = address::testing::established_address_3();
+        let initial_stake = token::Amount::from(10_098_123);
+        let consensus_key = key::testing::keypair_2().ref_to();
+        let commission_rate = rust_decimal::Decimal::new(5, 2);
+        let max_commission_rate_change = rust_decimal::Decimal::new(1, 2);
+
+        let genesis_validators = [GenesisValidator {
+            address: validator.clone(),
+            tokens: initial_stake,
+            consensus_key,
+            commission_rate,
+            max_commission_rate_change,
+        }];
+
+        init_pos(&genesis_validators[..], &pos_params, Epoch(0));
+
+        // Initialize a tx environment
+        let mut tx_env = tx_host_env::take();
+
+        let keypair = key::testing::keypair_1();
+        let public_key = keypair.ref_to();
+        let vp_owner: Address = address::testing::established_address_2();
+        let token = address::nam();
+        let amount = token::Amount::whole(1_000);
+        // More than the half of the amount that is unlocked
+        let bond_amount = token::Amount::whole(900);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &token]);
+        tx_env.credit_tokens(&vp_owner, &token, None, amount);
+        tx_env.write_public_key(&vp_owner, &public_key);
+        init_vesting(
+            &mut tx_env,
+            &vp_owner,
+            &half_vested_schedule(&token, amount),
+        );
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |_address| {
+            tx::ctx()
+                .bond_tokens(Some(&vp_owner), &validator, bond_amount)
+                .unwrap();
+        });
+
+        assert!(validate_signed(vp_owner, &keypair));
+    }
+
+    /// Test that the locked tokens cannot be transferred out while another
+    /// account is bonding in the same transaction.
+    #[test]
+    fn test_signed_locked_transfer_with_other_bond_rejected() {
+        // Init PoS genesis
+        let pos_params = PosParams::default();
+        let validator = address::testing::established_address_3();
+        let initial_stake = token::Amount::from(10_098_123);
+        let consensus_key = key::testing::keypair_2().ref_to();
+        let commission_rate = rust_decimal::Decimal::new(5, 2);
+        let max_commission_rate_change = rust_decimal::Decimal::new(1, 2);
+
+        let genesis_validators = [GenesisValidator {
+            address: validator.clone(),
+            tokens: initial_stake,
+            consensus_key,
+            commission_rate,
+            max_commission_rate_change,
+        }];
+
+        init_pos(&genesis_validators[..], &pos_params, Epoch(0));
+
+        // Initialize a tx environment
+        let mut tx_env = tx_host_env::take();
+
+        let keypair = key::testing::keypair_1();
+        let public_key = keypair.ref_to();
+        let vp_owner: Address = address::testing::established_address_2();
+        let other = address::testing::established_address_1();
+        let token = address::nam();
+        let amount = token::Amount::whole(1_000);
+        // More than the half of the amount that is unlocked
+        let transfer_amount = token::Amount::whole(900);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &other, &token]);
+        tx_env.credit_tokens(&vp_owner, &token, None, amount);
+        tx_env.write_public_key(&vp_owner, &public_key);
+        init_vesting(
+            &mut tx_env,
+            &vp_owner,
+            &half_vested_schedule(&token, amount),
+        );
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Transfer the locked tokens to the other account, which bonds
+            // them
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                address,
+                &other,
+                &token,
+                None,
+                transfer_amount,
+                &None,
+                &None,
+            )
+            .unwrap();
+            tx::ctx()
+                .bond_tokens(Some(&other), &validator, transfer_amount)
+                .unwrap();
+        });
+
+        assert!(!validate_signed(vp_owner, &keypair));
+    }
+
+    /// Test that the vesting schedule cannot be modified, even with a valid
+    /// signature.
+    #[test]
+    fn test_signed_vesting_schedule_update_rejected() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::testing::keypair_1();
+        let public_key = keypair.ref_to();
+        let token = address::nam();
+        let amount = token::Amount::whole(1_000);
+        let schedule = half_vested_schedule(&token, amount);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner]);
+        tx_env.write_public_key(&vp_owner, &public_key);
+        init_vesting(&mut tx_env, &vp_owner, &schedule);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Unlock all the tokens
+            let schedule = vesting::VestingSchedule {
+                end: schedule.start,
+                ..schedule.clone()
+            };
+            tx::ctx()
+                .write(&vesting::vesting_schedule_key(address), schedule)
+                .unwrap();
+        });
+
+        assert!(!validate_signed(vp_owner, &keypair));
+    }
+
+    /// Test that the VP cannot be updated while some tokens are locked, even
+    /// with a valid signature.
+    #[test]
+    fn test_signed_vp_update_with_locked_tokens_rejected() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::testing::keypair_1();
+        let public_key = keypair.ref_to();
+        let vp_code =
+            std::fs::read(VP_ALWAYS_TRUE_WASM).expect("cannot load wasm");
+        let token = address::nam();
+        let amount = token::Amount::whole(1_000);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner]);
+        tx_env.write_public_key(&vp_owner, &public_key);
+        init_vesting(
+            &mut tx_env,
+            &vp_owner,
+            &half_vested_schedule(&token, amount),
+        );
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Update VP in a transaction
+            tx::ctx()
+                .update_validity_predicate(address, &vp_code)
+                .unwrap();
+        });
+
+        assert!(!validate_signed(vp_owner, &keypair));
+    }
+}